@@ -0,0 +1,234 @@
+use bollard::service::{ContainerStateStatusEnum, HealthStatusEnum};
+use iced::{
+    theme::Text,
+    widget::{button, column, component, container, row, scrollable, text, Component},
+    Color, Element, Length, Renderer,
+};
+use iced_aw::{badge, BadgeStyles};
+
+use super::{
+    cleanup::format_size,
+    state_badge::{state_badge, LEGEND, UNHEALTHY_LEGEND},
+};
+use crate::{data::DatabaseConfig, docker::EngineInfo};
+
+#[derive(Clone)]
+pub enum Event {
+    CopyDiagnostics,
+}
+
+fn or_unknown(value: &Option<String>) -> String {
+    value.clone().unwrap_or_else(|| "unknown".to_string())
+}
+
+/// The engine fields shown in the about view and the diagnostics, in display order.
+fn engine_fields(engine: &EngineInfo) -> Vec<(&'static str, String)> {
+    vec![
+        ("Engine version", or_unknown(&engine.version)),
+        ("Engine API version", or_unknown(&engine.api_version)),
+        ("Client API version", engine.client_api_version.clone()),
+        ("Operating system", or_unknown(&engine.os)),
+        ("Architecture", or_unknown(&engine.arch)),
+        ("Kernel", or_unknown(&engine.kernel)),
+        ("Storage driver", or_unknown(&engine.storage_driver)),
+        (
+            "CPUs",
+            engine
+                .cpus
+                .map(|cpus| cpus.to_string())
+                .unwrap_or_else(|| "unknown".to_string()),
+        ),
+        (
+            "Memory",
+            engine
+                .memory
+                .map(format_size)
+                .unwrap_or_else(|| "unknown".to_string()),
+        ),
+    ]
+}
+
+/// A markdown summary for bug reports. Only the names and images of the config entries are
+/// included, never their variables or defaults.
+pub fn diagnostics(
+    engine: Option<&EngineInfo>,
+    databases: &[DatabaseConfig],
+    icon_errors: &[(String, String)],
+) -> String {
+    let mut out = format!(
+        "## db-mgr diagnostics\n\n- db-mgr version: {}\n",
+        env!("CARGO_PKG_VERSION")
+    );
+
+    out.push_str("\n### Docker engine\n\n");
+    match engine {
+        Some(engine) => {
+            for (name, value) in engine_fields(engine) {
+                out.push_str(&format!("- {name}: {value}\n"));
+            }
+            for error in engine.errors.iter() {
+                out.push_str(&format!("- Error: {error}\n"));
+            }
+        }
+        None => out.push_str("- Not loaded\n"),
+    }
+
+    out.push_str("\n### Config\n\n");
+    if databases.is_empty() {
+        out.push_str("- No databases configured\n");
+    }
+    for database in databases {
+        out.push_str(&format!("- {} ({})\n", database.name, database.image_ref()));
+    }
+
+    if !icon_errors.is_empty() {
+        out.push_str("\n### Icons\n\n");
+        for (image, error) in icon_errors {
+            out.push_str(&format!("- {image}: {error}\n"));
+        }
+    }
+
+    out
+}
+
+pub fn about<Message>(
+    engine: Option<EngineInfo>,
+    databases: Vec<DatabaseConfig>,
+) -> About<Message> {
+    About::new(engine, databases)
+}
+
+pub struct About<Message> {
+    engine: Option<EngineInfo>,
+    databases: Vec<DatabaseConfig>,
+    icon_errors: Vec<(String, String)>,
+    on_copy_click: Option<Box<dyn Fn(String) -> Message>>,
+}
+
+impl<Message> About<Message> {
+    pub fn new(engine: Option<EngineInfo>, databases: Vec<DatabaseConfig>) -> Self {
+        Self {
+            engine,
+            databases,
+            icon_errors: vec![],
+            on_copy_click: None,
+        }
+    }
+
+    /// The icons that could not be loaded and why, by image, drawn with the default icon instead.
+    pub fn icon_errors(self, icon_errors: Vec<(String, String)>) -> Self {
+        Self {
+            icon_errors,
+            ..self
+        }
+    }
+
+    pub fn on_copy_click<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn(String) -> Message + 'static,
+    {
+        Self {
+            on_copy_click: Some(Box::new(handler)),
+            ..self
+        }
+    }
+}
+
+impl<Message> Component<Message, Renderer> for About<Message> {
+    type State = ();
+
+    type Event = Event;
+
+    fn update(&mut self, _: &mut Self::State, event: Self::Event) -> Option<Message> {
+        match event {
+            Event::CopyDiagnostics => self.on_copy_click.as_ref().map(|fun| {
+                fun(diagnostics(
+                    self.engine.as_ref(),
+                    &self.databases,
+                    &self.icon_errors,
+                ))
+            }),
+        }
+    }
+
+    fn view(&self, _: &Self::State) -> Element<'_, Self::Event, Renderer> {
+        let mut content = column!(
+            text("About").size(22),
+            text(format!("db-mgr {}", env!("CARGO_PKG_VERSION")))
+        )
+        .align_items(iced::Alignment::Center)
+        .spacing(15)
+        .padding(15);
+
+        match self.engine.as_ref() {
+            None => content = content.push(badge("Loading engine info").style(BadgeStyles::Info)),
+            Some(engine) => {
+                for (name, value) in engine_fields(engine) {
+                    content = content.push(row!(
+                        text(name)
+                            .style(Text::Color(Color::from_rgb8(150, 150, 150)))
+                            .width(Length::FillPortion(1)),
+                        text(value).width(Length::FillPortion(1))
+                    ));
+                }
+                for error in engine.errors.iter() {
+                    content = content.push(
+                        text(error)
+                            .size(12)
+                            .style(Text::Color(Color::from_rgb8(220, 60, 60))),
+                    );
+                }
+            }
+        }
+
+        if !self.icon_errors.is_empty() {
+            content = content.push(text("Icons that could not be loaded").size(16));
+        }
+        for (image, error) in self.icon_errors.iter() {
+            content = content.push(row!(
+                text(image)
+                    .style(Text::Color(Color::from_rgb8(150, 150, 150)))
+                    .width(Length::FillPortion(1)),
+                text(error)
+                    .size(12)
+                    .style(Text::Color(Color::from_rgb8(220, 60, 60)))
+                    .width(Length::FillPortion(1))
+            ));
+        }
+
+        content = content.push(text("Container states").size(16));
+        for (state, meaning) in LEGEND.iter() {
+            content = content.push(
+                row!(
+                    container(state_badge(*state, None)).width(Length::FillPortion(1)),
+                    text(*meaning).width(Length::FillPortion(3))
+                )
+                .align_items(iced::Alignment::Center),
+            );
+        }
+        content = content.push(
+            row!(
+                container(state_badge(
+                    ContainerStateStatusEnum::RUNNING,
+                    Some(HealthStatusEnum::UNHEALTHY)
+                ))
+                .width(Length::FillPortion(1)),
+                text(UNHEALTHY_LEGEND).width(Length::FillPortion(3))
+            )
+            .align_items(iced::Alignment::Center),
+        );
+
+        content = content.push(button("Copy diagnostics").on_press(Event::CopyDiagnostics));
+
+        scrollable(content).into()
+    }
+}
+
+impl<'a, Message> From<About<Message>> for Element<'a, Message, Renderer>
+where
+    Message: 'a,
+{
+    fn from(value: About<Message>) -> Self {
+        component(value)
+    }
+}
@@ -1,45 +1,180 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, time::Instant};
 
 use iced::{
+    font,
+    theme::Container,
     theme::Text,
     widget::{
-        button, checkbox, column, component, pick_list, progress_bar, row, scrollable, text,
-        text_input, Component,
+        button, checkbox, column, component, container, horizontal_space, pick_list, progress_bar,
+        row, scrollable, text, text_input, tooltip, vertical_space, Component,
     },
-    Color, Element, Length, Renderer,
+    Color, Element, Font, Length, Renderer,
 };
 use iced_aw::{badge, BadgeStyles};
+use itertools::Itertools;
 
-use crate::{data::DatabaseConfig, docker::DbContainerConfig};
+use super::{
+    cleanup::{format_size, parse_size},
+    notes::{note_lines, NoteLine},
+    pull_progress::PullProgress,
+};
+use crate::{
+    data::{is_secret, DatabaseConfig, Draft, RememberedValues},
+    docker::{describe_gpus, DbContainerConfig, LogSettings, Ulimit, LOG_DRIVERS},
+    env_file::{format_env_file, parse_env_lines, LineError},
+    names::{default_hostname, generate_name},
+    platform::is_host_path,
+    ports::PortAssignment,
+    template::render_args,
+    validate::{
+        is_valid_cpuset, is_valid_extra_host, is_valid_hostname, is_valid_user,
+        is_valid_variable_name,
+    },
+};
+
+/// The uid and gid db-mgr runs as, so files the container writes to bind mounts stay the user's.
+#[cfg(unix)]
+fn current_user() -> Option<String> {
+    // SAFETY: getuid and getgid always succeed and touch no memory
+    let (uid, gid) = unsafe { (libc::getuid(), libc::getgid()) };
+    Some(format!("{uid}:{gid}"))
+}
+
+#[cfg(not(unix))]
+fn current_user() -> Option<String> {
+    None
+}
 
 #[derive(Clone)]
 pub enum Event {
-    SelectContainer(DatabaseConfig),
+    SelectContainer(Box<DatabaseConfig>),
     SelectedTag(String),
     NameChanged(String),
-    EnvVarChanged { key: String, value: String },
+    GenerateName,
+    ProjectChanged(String),
+    EnvVarChanged {
+        key: String,
+        value: String,
+    },
+    AddVariable,
+    NewVariableKeyChanged(String),
+    NewVariableValueChanged(String),
+    ConfirmVariable,
+    CancelVariable,
+    RemoveVariable(String),
+    ToggleEnvText,
+    /// A line of the variables edited as text changed, pasted text may hold several lines.
+    EnvLineChanged(usize, String),
+    /// Adds an empty line after this one.
+    EnvLineAdded(usize),
+    EnvLineRemoved(usize),
     Persist(bool),
+    VolumeReadOnly(String, bool),
+    VolumeOptionsChanged(String, String),
+    /// The host folder to bind instead of the volume, empty to keep the volume.
+    BindFolderChanged(String, String),
+    Tmpfs(bool),
+    TmpfsSizeChanged(String),
+    StartAfterCreate(bool),
+    DryRun(bool),
+    HostPortChanged(String),
+    ToggleAdvanced,
+    ToggleBuildOutput,
+    ToggleNotes,
+    ShmSizeChanged(String),
+    CpusetChanged(String),
+    LogDriverSelected(&'static str),
+    LogMaxSizeChanged(String),
+    LogMaxFileChanged(String),
+    HostnameChanged(String),
+    ExtraHostsChanged(String),
+    UlimitsChanged(String),
+    SysctlsChanged(String),
+    GpuToggled(bool),
+    GpuDevice(String, bool),
+    UserChanged(String),
+    UseCurrentUser,
+    DependsOn(String, bool),
+    ImportEnv,
+    Discard,
+    AddUnmatchedEnv,
+    DismissUnmatchedEnv,
     SubmitPressed,
+    AcceptDataLoss(bool),
+    ConfirmCreate,
+    BackToForm,
 }
 
 #[derive(Debug, Clone)]
 pub enum ButtonState {
     None,
     Ready,
-    Pulling(HashMap<String, f32>),
+    Pulling(PullProgress),
+    /// The lines printed by the image build so far.
+    BuildingImage(Vec<String>),
     Creating,
 }
 
 pub struct AddContainer<Message> {
     images: Vec<DatabaseConfig>,
+    remembered: HashMap<String, RememberedValues>,
+    draft: AddContainerState,
     on_add: Box<dyn Fn(DbContainerConfig) -> Message>,
+    on_change: Option<Box<dyn Fn(AddContainerState) -> Message>>,
+    on_import_env: Option<Box<dyn Fn() -> Message>>,
+    on_discard: Option<Box<dyn Fn() -> Message>>,
     button_state: ButtonState,
+    never_default_latest: bool,
+    taken_names: Vec<String>,
+    dependency_options: Vec<String>,
+    project_options: Vec<String>,
+    skip_review: bool,
+    /// Whether docker has the nvidia runtime, `None` until the engine info is loaded.
+    nvidia_runtime: Option<bool>,
+    /// The GPUs docker advertises, which can be picked from when there are several.
+    gpus: Vec<String>,
 }
 
-#[derive(Debug)]
+/// The contents of the create form, owned by the app so the form can be reopened pre-filled.
+#[derive(Debug, Clone)]
 pub struct AddContainerState {
     data: Option<(DbContainerConfig, DatabaseConfig)>,
     persist: bool,
+    recalled: Vec<String>,
+    advanced: bool,
+    /// The tmpfs size limit as typed, such as `512m`.
+    tmpfs_size: String,
+    /// The extra hosts as typed, separated by commas or spaces.
+    extra_hosts: String,
+    /// The host port as typed, empty to assign one when the container is created.
+    host_port: String,
+    /// The ulimits as typed, such as `nofile=65536 memlock=unlimited`.
+    ulimits: String,
+    /// The sysctls as typed, such as `net.core.somaxconn=1024`.
+    sysctls: String,
+    /// Why the daemon refused the sysctls when the container was last created, shown next to
+    /// them until they are changed.
+    sysctl_error: Option<String>,
+    show_build_output: bool,
+    /// Whether the notes of the image and tag are shown, their panel keeps its size either way.
+    show_notes: bool,
+    /// Shown above the form, such as what to check in an imported definition.
+    warnings: Vec<String>,
+    /// The config exactly as it will be created, shown for review before it is submitted.
+    review: Option<DbContainerConfig>,
+    /// The user acknowledged the reviewed container loses its data when removed.
+    accept_data_loss: bool,
+    /// Variables of an imported `.env` file that match none of the entry's variables.
+    unmatched_env: Vec<(String, String)>,
+    /// The name and value of the extra variable being added.
+    new_variable: Option<(String, String)>,
+    /// The driver options of each volume as typed, such as `type=tmpfs device=tmpfs`.
+    volume_options: HashMap<String, String>,
+    /// The host folder typed for each volume of the image, bound in its place when it is an
+    /// absolute path.
+    bind_folders: HashMap<String, String>,
+    /// The variables as `KEY=VALUE` lines while they are edited as text instead of fields.
+    env_text: Option<Vec<String>>,
 }
 
 impl Default for AddContainerState {
@@ -47,24 +182,423 @@ impl Default for AddContainerState {
         Self {
             data: None,
             persist: true,
+            recalled: vec![],
+            advanced: false,
+            tmpfs_size: String::new(),
+            extra_hosts: String::new(),
+            host_port: String::new(),
+            ulimits: String::new(),
+            sysctls: String::new(),
+            sysctl_error: None,
+            show_build_output: false,
+            show_notes: true,
+            warnings: vec![],
+            review: None,
+            accept_data_loss: false,
+            unmatched_env: vec![],
+            new_variable: None,
+            volume_options: HashMap::new(),
+            bind_folders: HashMap::new(),
+            env_text: None,
+        }
+    }
+}
+
+impl AddContainerState {
+    /// The form filled in with `config` for `database`, awaiting only a name.
+    pub fn imported(
+        config: DbContainerConfig,
+        database: DatabaseConfig,
+        warnings: Vec<String>,
+    ) -> Self {
+        Self {
+            persist: !config.volumes.is_empty(),
+            advanced: config.shm_size_mb.is_some()
+                || config.cpuset_cpus.is_some()
+                || config.user.is_some()
+                || !config.extra_hosts.is_empty()
+                || !config.ulimits.is_empty()
+                || !config.sysctls.is_empty()
+                || config.gpus.is_some()
+                || config.log != LogSettings::default(),
+            tmpfs_size: config
+                .tmpfs_size
+                .map(|size| size.to_string())
+                .unwrap_or_default(),
+            extra_hosts: config.extra_hosts.join(", "),
+            host_port: config
+                .host_port
+                .and_then(|assignment| assignment.host)
+                .map(|port| port.to_string())
+                .unwrap_or_default(),
+            ulimits: typed_ulimits(&config.ulimits),
+            sysctls: typed_sysctls(&config.sysctls),
+            volume_options: typed_volume_options(&config),
+            bind_folders: typed_bind_folders(&config, &database),
+            warnings,
+            data: Some((config, database)),
+            ..Self::default()
+        }
+    }
+
+    /// The form picked up again from a saved draft, matched against `databases`.
+    pub fn resumed(draft: Draft, databases: &[DatabaseConfig]) -> Self {
+        let persist = draft.persist;
+        let (config, database, warnings) = draft.restore(databases);
+        Self {
+            persist,
+            ..Self::imported(config, database, warnings)
+        }
+    }
+
+    /// Nothing was filled in, not even the image.
+    pub fn is_empty(&self) -> bool {
+        self.data.is_none()
+    }
+
+    /// The config shown for review, the one confirming it submits.
+    pub fn reviewed(&self) -> Option<&DbContainerConfig> {
+        self.review.as_ref()
+    }
+
+    /// The variables as the `KEY=VALUE` lines being edited, `None` while they are edited as
+    /// fields.
+    pub fn env_text(&self) -> Option<&[String]> {
+        self.env_text.as_deref()
+    }
+
+    /// The form to save as a draft, `None` until an image is chosen. Secret values are only kept
+    /// with `include_secrets`.
+    pub fn draft(&self, include_secrets: bool) -> Option<Draft> {
+        let (config, database) = self.data.as_ref()?;
+        Some(Draft::new(config, database, self.persist, include_secrets))
+    }
+
+    /// Shows why the daemon refused the sysctl `key` next to the sysctls, opening the advanced
+    /// options so it is seen.
+    pub fn reject_sysctl(&mut self, key: &str, message: &str) {
+        self.advanced = true;
+        self.sysctl_error = Some(format!("Docker refused {key}: {message}"));
+    }
+
+    /// Switches the variables to editing as `KEY=VALUE` lines, the declared ones first.
+    fn edit_env_as_text(&mut self) {
+        let Some((config, database)) = self.data.as_ref() else {
+            return;
+        };
+
+        let declared = database
+            .variables
+            .values()
+            .sorted()
+            .dedup()
+            .collect::<Vec<_>>();
+        let variables = declared
+            .iter()
+            .map(|key| {
+                let value = config.variables.get(*key).map(String::as_str);
+                (key.as_str(), value.unwrap_or_default())
+            })
+            .chain(
+                config
+                    .variables
+                    .iter()
+                    .filter(|(key, _)| !declared.contains(key))
+                    .sorted()
+                    .map(|(key, value)| (key.as_str(), value.as_str())),
+            );
+        self.env_text = Some(
+            format_env_file(variables)
+                .lines()
+                .map(String::from)
+                .collect(),
+        );
+    }
+
+    /// Replaces the variables with the ones typed as text and switches back to the fields,
+    /// staying on the text if one of the lines can't be used.
+    fn apply_env_text(&mut self) -> Result<(), Vec<LineError>> {
+        let (Some(lines), Some((config, database))) = (self.env_text.as_ref(), self.data.as_mut())
+        else {
+            return Ok(());
+        };
+
+        let variables = parse_env_lines(lines.iter().map(String::as_str))?;
+        let previous = std::mem::take(&mut config.variables);
+        for (key, value) in variables {
+            // Cleared declared variables are left out like in the fields, extra ones are kept
+            if !value.is_empty() || !database.declares(&key) {
+                config.variables.insert(key, value);
+            }
+        }
+        self.recalled
+            .retain(|key| config.variables.get(key) == previous.get(key));
+        self.env_text = None;
+        Ok(())
+    }
+
+    /// Fills in the variables set in a `.env` file, keeping the ones the entry doesn't know
+    /// aside so they can be added as extra variables.
+    pub fn import_env(&mut self, variables: Vec<(String, String)>) {
+        let Some((config, database)) = self.data.as_mut() else {
+            return;
+        };
+
+        self.unmatched_env.clear();
+        for (key, value) in variables {
+            let Some(variable) = database.env_variable(&key) else {
+                self.unmatched_env.push((key, value));
+                continue;
+            };
+
+            self.recalled.retain(|recalled| recalled != &variable);
+            if value.is_empty() {
+                config.variables.remove(&variable);
+            } else {
+                config.variables.insert(variable, value);
+            }
+        }
+    }
+}
+
+/// Why `key` can't be added as an extra variable to `config`, `None` if it can.
+fn new_variable_error(
+    key: &str,
+    config: &DbContainerConfig,
+    database: &DatabaseConfig,
+) -> Option<String> {
+    if !is_valid_variable_name(key) {
+        Some(
+            "Names may only contain letters, digits and underscores and can't start with a digit"
+                .to_string(),
+        )
+    } else if database.declares(key) {
+        Some(format!(
+            "{key} is already one of the variables of {}",
+            database.name
+        ))
+    } else if config.variables.contains_key(key) {
+        Some(format!("{key} is already added"))
+    } else {
+        None
+    }
+}
+
+/// Parses volume driver options typed as `key=value` pairs separated by spaces, `None` if one of
+/// them has no key.
+fn parse_volume_options(options: &str) -> Option<HashMap<String, String>> {
+    options
+        .split_whitespace()
+        .map(|option| {
+            let (key, value) = option.split_once('=')?;
+            (!key.is_empty()).then(|| (key.to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+/// Parses a limit typed as a number or `unlimited`.
+fn parse_limit(limit: &str) -> Option<i64> {
+    match limit {
+        "unlimited" => Some(-1),
+        limit => limit.parse().ok().filter(|limit| *limit >= -1),
+    }
+}
+
+/// Parses ulimits typed as `name=soft:hard` or `name=limit` separated by commas or spaces, `None`
+/// if one of them is malformed.
+fn parse_ulimits(ulimits: &str) -> Option<Vec<Ulimit>> {
+    ulimits
+        .split([',', ' '])
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let (name, limits) = entry.split_once('=')?;
+            let (soft, hard) = limits.split_once(':').unwrap_or((limits, limits));
+            (!name.is_empty()).then_some(())?;
+            Some(Ulimit {
+                name: name.to_string(),
+                soft: parse_limit(soft)?,
+                hard: parse_limit(hard)?,
+            })
+        })
+        .collect()
+}
+
+/// The ulimits as they are typed in the form.
+fn typed_ulimits(ulimits: &[Ulimit]) -> String {
+    let limit = |limit: i64| match limit {
+        -1 => "unlimited".to_string(),
+        limit => limit.to_string(),
+    };
+
+    ulimits
+        .iter()
+        .map(|ulimit| match ulimit.soft == ulimit.hard {
+            true => format!("{}={}", ulimit.name, limit(ulimit.soft)),
+            false => format!(
+                "{}={}:{}",
+                ulimit.name,
+                limit(ulimit.soft),
+                limit(ulimit.hard)
+            ),
+        })
+        .join(" ")
+}
+
+/// Parses sysctls typed as `key=value` separated by commas or spaces, `None` if one of them has
+/// no key or value.
+fn parse_sysctls(sysctls: &str) -> Option<HashMap<String, String>> {
+    sysctls
+        .split([',', ' '])
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let (key, value) = entry.split_once('=')?;
+            (!key.is_empty() && !value.is_empty()).then(|| (key.to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+/// The sysctls as they are typed in the form, sorted by key.
+fn typed_sysctls(sysctls: &HashMap<String, String>) -> String {
+    sysctls
+        .iter()
+        .sorted()
+        .map(|(key, value)| format!("{key}={value}"))
+        .join(" ")
+}
+
+/// The host folders `config` binds in place of the volumes of `database`, by volume name.
+fn typed_bind_folders(
+    config: &DbContainerConfig,
+    database: &DatabaseConfig,
+) -> HashMap<String, String> {
+    database
+        .volumes
+        .iter()
+        .filter_map(|(name, target)| {
+            config
+                .volumes
+                .iter()
+                .find(|(source, path)| *path == target && is_host_path(source))
+                .map(|(source, _)| (name.clone(), source.clone()))
+        })
+        .collect()
+}
+
+/// The source of the mount of the volume `name`, the host folder typed for it if it is one.
+fn mount_source(name: &str, typed: &str) -> String {
+    let typed = typed.trim();
+    if is_host_path(typed) {
+        typed.to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+/// The driver options of the volumes of `config` as they are typed in the form.
+fn typed_volume_options(config: &DbContainerConfig) -> HashMap<String, String> {
+    config
+        .volume_options
+        .iter()
+        .map(|(volume, options)| {
+            let typed = options
+                .iter()
+                .sorted()
+                .map(|(key, value)| format!("{key}={value}"))
+                .join(" ");
+            (volume.clone(), typed)
+        })
+        .collect()
+}
+
+/// The variables of `config` beyond the ones `database` declares, sorted by name.
+fn extra_variables(config: &DbContainerConfig, database: &DatabaseConfig) -> Vec<String> {
+    config
+        .variables
+        .keys()
+        .filter(|key| !database.declares(key))
+        .sorted()
+        .cloned()
+        .collect()
+}
+
+/// Whether `config` leaves out the volumes `database` stores its data in, for entries that warn
+/// about it.
+fn loses_data(config: &DbContainerConfig, database: &DatabaseConfig) -> bool {
+    database.data_loss_warning && !database.volumes.is_empty() && config.volumes.is_empty()
+}
+
+/// The host port typed into the form, `Some(None)` to assign one, `None` if it isn't a port.
+fn parse_host_port(typed: &str) -> Option<Option<u16>> {
+    match typed.trim() {
+        "" | "auto" => Some(None),
+        port => port.parse().ok().filter(|port| *port != 0).map(Some),
+    }
+}
+
+/// Turns the values entered for a container into the config it is created with, prefixing the
+/// container and volume names and rendering the command.
+pub fn prepare_config(config: &DbContainerConfig) -> DbContainerConfig {
+    let mut new_config = config.clone();
+    // Host folders are bound as they are, only volumes are named after the container
+    let volume_name = |name: &str| {
+        if is_host_path(name) {
+            name.to_string()
+        } else {
+            format!("db-mgr__{}__{name}", config.name)
         }
+    };
+    new_config.volumes = new_config
+        .volumes
+        .into_iter()
+        .map(|(name, value)| (volume_name(&name), value))
+        .collect();
+    new_config.read_only_volumes = new_config
+        .read_only_volumes
+        .iter()
+        .map(|name| volume_name(name))
+        .collect();
+    new_config.volume_options = new_config
+        .volume_options
+        .into_iter()
+        .map(|(name, options)| (volume_name(&name), options))
+        .collect();
+
+    new_config.variables.retain(|_, value| !value.is_empty());
+    new_config.command = render_args(&config.command, &new_config.variables);
+
+    new_config.project = config
+        .project
+        .as_deref()
+        .map(str::trim)
+        .filter(|project| !project.is_empty())
+        .map(String::from);
+
+    new_config.name = format!("db-mgr__{}", config.name);
+    if new_config.hostname.is_none() {
+        new_config.hostname = Some(default_hostname(&config.name));
     }
+
+    new_config
 }
 
 pub fn add_container<Message, Handler>(
     images: Vec<DatabaseConfig>,
+    remembered: HashMap<String, RememberedValues>,
+    draft: AddContainerState,
     button_state: ButtonState,
     on_add: Handler,
 ) -> AddContainer<Message>
 where
     Handler: Fn(DbContainerConfig) -> Message + 'static,
 {
-    AddContainer::new(images, button_state, on_add)
+    AddContainer::new(images, remembered, draft, button_state, on_add)
 }
 
 impl<Message> AddContainer<Message> {
     pub fn new<Handler>(
         images: Vec<DatabaseConfig>,
+        remembered: HashMap<String, RememberedValues>,
+        draft: AddContainerState,
         button_state: ButtonState,
         on_add: Handler,
     ) -> Self
@@ -73,207 +607,1725 @@ impl<Message> AddContainer<Message> {
     {
         Self {
             images,
+            remembered,
+            draft,
             button_state,
             on_add: Box::new(on_add),
+            on_change: None,
+            on_import_env: None,
+            on_discard: None,
+            never_default_latest: false,
+            taken_names: vec![],
+            dependency_options: vec![],
+            project_options: vec![],
+            skip_review: false,
+            nvidia_runtime: None,
+            gpus: vec![],
+        }
+    }
+
+    /// Whether docker can give containers GPUs and which ones it advertises, for entries that
+    /// are GPU capable.
+    pub fn gpu_support(self, nvidia_runtime: Option<bool>, gpus: Vec<String>) -> Self {
+        Self {
+            nvidia_runtime,
+            gpus,
+            ..self
+        }
+    }
+
+    /// Submit the form right away instead of showing the final config for review first.
+    pub fn skip_review(self, skip_review: bool) -> Self {
+        Self {
+            skip_review,
+            ..self
+        }
+    }
+
+    /// Names of the existing containers, which generated names avoid.
+    pub fn taken_names(self, taken_names: Vec<String>) -> Self {
+        Self {
+            taken_names,
+            ..self
+        }
+    }
+
+    /// Names of the existing containers the new one can depend on.
+    pub fn dependency_options(self, dependency_options: Vec<String>) -> Self {
+        Self {
+            dependency_options,
+            ..self
+        }
+    }
+
+    /// The projects of the existing containers, suggested while typing a project.
+    pub fn project_options(self, project_options: Vec<String>) -> Self {
+        Self {
+            project_options,
+            ..self
+        }
+    }
+
+    /// Start with an empty tag instead of `latest` for entries without configured tags.
+    pub fn never_default_latest(self, never_default_latest: bool) -> Self {
+        Self {
+            never_default_latest,
+            ..self
+        }
+    }
+
+    /// Called to pick a `.env` file to fill the variables in from.
+    pub fn on_import_env<Handler>(self, handler: Handler) -> Self
+    where
+        Handler: Fn() -> Message + 'static,
+    {
+        Self {
+            on_import_env: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    /// Called to throw the filled in form away, along with its saved draft.
+    pub fn on_discard<Handler>(self, handler: Handler) -> Self
+    where
+        Handler: Fn() -> Message + 'static,
+    {
+        Self {
+            on_discard: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    pub fn on_change<Handler>(self, handler: Handler) -> Self
+    where
+        Handler: Fn(AddContainerState) -> Message + 'static,
+    {
+        Self {
+            on_change: Some(Box::new(handler)),
+            ..self
         }
     }
+
+    fn changed(&self) -> Option<Message> {
+        self.on_change
+            .as_ref()
+            .map(|on_change| on_change(self.draft.clone()))
+    }
 }
 
 impl<Message> Component<Message, Renderer> for AddContainer<Message> {
-    type State = AddContainerState;
+    type State = ();
 
     type Event = Event;
 
-    fn update(&mut self, state: &mut Self::State, event: Self::Event) -> Option<Message> {
+    fn update(&mut self, _: &mut Self::State, event: Self::Event) -> Option<Message> {
+        let state = &mut self.draft;
         match event {
             Event::SelectContainer(image) => {
-                state.data = Some((
-                    DbContainerConfig {
-                        name: "".into(),
-                        variables: HashMap::with_capacity(image.variables.len()),
-                        image: image.image.clone(),
-                        voluems: image.volumes.clone(),
-                        tag: image
-                            .tags
-                            .get(0)
-                            .map(|f| f.to_owned())
-                            .unwrap_or_else(|| "latest".to_string()),
-                    },
-                    image,
-                ));
+                let image = *image;
+                let mut config = DbContainerConfig {
+                    name: "".into(),
+                    database: image.name.clone(),
+                    variables: image.defaults.clone(),
+                    image: image.image_name(),
+                    volumes: image.volumes.clone(),
+                    tag: image.default_tag(self.never_default_latest),
+                    command: image.command.clone(),
+                    reuse_volumes: false,
+                    force_shared_volume: false,
+                    start_after_create: true,
+                    dry_run: false,
+                    shm_size_mb: image.shm_size_mb,
+                    cpuset_cpus: image.cpuset_cpus.clone(),
+                    tmpfs: vec![],
+                    tmpfs_size: None,
+                    hostname: None,
+                    extra_hosts: vec![],
+                    user: None,
+                    build: image.image_build(),
+                    depends_on: vec![],
+                    extra_variables: vec![],
+                    read_only_volumes: image.read_only_volumes.clone(),
+                    volume_options: image.volume_options.clone(),
+                    project: None,
+                    ulimits: image.ulimits.clone(),
+                    sysctls: image.sysctls.clone(),
+                    companion_of: None,
+                    published_ports: vec![],
+                    host_port: image.port.map(PortAssignment::auto),
+                    gpus: None,
+                    log: LogSettings::default(),
+                };
 
-                None
+                state.recalled = self
+                    .remembered
+                    .get(&image.name)
+                    .map(|remembered| remembered.apply(&image, &mut config))
+                    .unwrap_or_default();
+                state.advanced = config.shm_size_mb.is_some()
+                    || config.cpuset_cpus.is_some()
+                    || !config.ulimits.is_empty()
+                    || !config.sysctls.is_empty();
+                state.extra_hosts = String::new();
+                state.host_port = String::new();
+                state.ulimits = typed_ulimits(&config.ulimits);
+                state.sysctls = typed_sysctls(&config.sysctls);
+                state.sysctl_error = None;
+                state.volume_options = typed_volume_options(&config);
+                state.bind_folders.clear();
+                state.env_text = None;
+                state.warnings.clear();
+                state.data = Some((config, image));
+
+                self.changed()
             }
             Event::SelectedTag(tag) => {
                 if let Some((config, _)) = state.data.as_mut() {
                     config.tag = tag;
                 }
 
-                None
+                self.changed()
             }
             Event::NameChanged(name) => {
                 if let Some((config, _)) = state.data.as_mut() {
                     config.name = name.replace(' ', "-");
                 }
 
-                None
+                self.changed()
             }
-            Event::EnvVarChanged { key, value } => {
+            Event::GenerateName => {
+                if let Some((config, _)) = state.data.as_mut() {
+                    config.name = generate_name(&mut rand::thread_rng(), &self.taken_names);
+                }
+
+                self.changed()
+            }
+            Event::ProjectChanged(project) => {
                 if let Some((config, _)) = state.data.as_mut() {
-                    if value.is_empty() {
+                    config.project = Some(project).filter(|project| !project.is_empty());
+                }
+
+                self.changed()
+            }
+            Event::EnvVarChanged { key, value } => {
+                state.recalled.retain(|recalled| recalled != &key);
+                if let Some((config, database)) = state.data.as_mut() {
+                    // Extra variables keep their row while their value is cleared
+                    if value.is_empty() && database.declares(&key) {
                         _ = config.variables.remove(&key);
                     } else {
                         *config.variables.entry(key).or_insert("".into()) = value;
                     }
                 }
 
-                None
+                self.changed()
             }
             Event::SubmitPressed => {
-                if let Some((config, _)) = state.data.as_mut() {
-                    let mut new_config = config.clone();
-                    new_config.voluems = new_config
-                        .voluems
-                        .into_iter()
-                        .map(|(name, value)| (format!("db-mgr__{}__{name}", config.name), value))
-                        .collect();
-
-                    new_config.variables.retain(|_, value| !value.is_empty());
-
-                    new_config.name = format!("db-mgr__{}", config.name);
+                if state.apply_env_text().is_err() {
+                    return self.changed();
+                }
+                if let Some((config, database)) = state.data.as_mut() {
+                    config.extra_variables = extra_variables(config, database);
+                    let prepared = prepare_config(config);
+                    // Losing data has to be acknowledged, which only the review asks for
+                    if self.skip_review && !loses_data(config, database) {
+                        let on_add = self.on_add.as_ref();
 
-                    let on_add = self.on_add.as_ref();
+                        return Some(on_add(prepared));
+                    }
 
-                    return Some(on_add(new_config));
+                    state.review = Some(prepared);
+                    state.accept_data_loss = false;
+                    return self.changed();
                 }
 
                 println!("Well this is awkward");
                 None
             }
-            Event::Persist(voluems_state) => {
-                state.persist = voluems_state;
-                if let Some((config, selected_container)) = state.data.as_mut() {
-                    if voluems_state {
-                        config.voluems = selected_container.volumes.clone();
-                    } else {
-                        config.voluems = HashMap::new();
-                    }
+            Event::AcceptDataLoss(accept) => {
+                state.accept_data_loss = accept;
+
+                self.changed()
+            }
+            Event::ConfirmCreate => {
+                let (config, database) = state.data.as_ref()?;
+                if loses_data(config, database) && !state.accept_data_loss {
+                    return None;
                 }
 
-                None
+                let on_add = self.on_add.as_ref();
+                // The reviewed config is submitted as is, so the review never differs from it
+                state.review.clone().map(on_add)
             }
-        }
-    }
+            Event::BackToForm => {
+                state.review = None;
 
-    fn view(&self, state: &Self::State) -> iced_aw::Element<'_, Self::Event, Renderer> {
-        let mut content = column!(pick_list(
-            self.images.clone(),
-            state
-                .data
-                .as_ref()
-                .map(|(_, selected_database)| selected_database.clone())
-                .clone(),
-            Event::SelectContainer,
-        )
-        .placeholder("Choose image")
-        .width(200),)
-        .align_items(iced::Alignment::Center)
-        .spacing(15)
-        .padding(15);
+                self.changed()
+            }
+            Event::StartAfterCreate(start) => {
+                if let Some((config, _)) = state.data.as_mut() {
+                    config.start_after_create = start;
+                }
 
-        if let Some((config, selecetd_image)) = state.data.as_ref() {
-            content = content.push(
-                row!(
-                    text_input("name", &config.name).on_input(Event::NameChanged),
-                    pick_list(
-                        selecetd_image.tags.clone(),
-                        Some(config.tag.clone()),
-                        Event::SelectedTag
-                    )
-                )
-                .spacing(15),
-            );
+                self.changed()
+            }
+            Event::DryRun(dry_run) => {
+                if let Some((config, _)) = state.data.as_mut() {
+                    config.dry_run = dry_run;
+                }
 
-            for (name, variable) in selecetd_image.variables.iter() {
-                let value = config
-                    .variables
-                    .get(variable)
-                    .map(|a| a.to_owned())
-                    .unwrap_or_default();
+                self.changed()
+            }
+            Event::HostPortChanged(host_port) => {
+                if let Some(assignment) = state
+                    .data
+                    .as_mut()
+                    .and_then(|(config, _)| config.host_port.as_mut())
+                {
+                    assignment.host = parse_host_port(&host_port).flatten();
+                }
+                state.host_port = host_port;
 
-                content = content.push(env_var_row(name.clone(), variable.clone(), value));
+                self.changed()
             }
+            Event::ToggleAdvanced => {
+                state.advanced = !state.advanced;
 
-            content = content.push(checkbox(
-                "Presistant container",
-                state.persist,
-                Event::Persist,
-            ));
+                self.changed()
+            }
+            Event::DependsOn(name, depends) => {
+                if let Some((config, _)) = state.data.as_mut() {
+                    config.depends_on.retain(|dependency| dependency != &name);
+                    if depends {
+                        config.depends_on.push(name);
+                    }
+                }
 
-            if state.persist {
-                content = content.push(text("The following mounts will be created").size(20));
-                for (name, path) in selecetd_image.volumes.iter() {
-                    content = content.push(
-                        row!(
-                            text(name),
-                            text(path)
-                                .size(12)
-                                .style(Text::Color(Color::from_rgb8(150, 150, 150)))
-                        )
-                        .align_items(iced::Alignment::Center)
-                        .spacing(5),
-                    );
+                self.changed()
+            }
+            Event::AddVariable => {
+                state.new_variable = Some((String::new(), String::new()));
+
+                self.changed()
+            }
+            Event::NewVariableKeyChanged(key) => {
+                if let Some((new_key, _)) = state.new_variable.as_mut() {
+                    *new_key = key.trim().to_string();
+                }
+
+                self.changed()
+            }
+            Event::NewVariableValueChanged(value) => {
+                if let Some((_, new_value)) = state.new_variable.as_mut() {
+                    *new_value = value;
                 }
+
+                self.changed()
             }
+            Event::ConfirmVariable => {
+                let (config, database) = state.data.as_mut()?;
+                let (key, value) = state.new_variable.as_ref()?;
+                if new_variable_error(key, config, database).is_some() {
+                    return None;
+                }
+                config.variables.insert(key.clone(), value.clone());
+                state.new_variable = None;
 
-            match (&self.button_state, config.name.as_str()) {
-                (ButtonState::None, _) => {}
-                (ButtonState::Ready, "") => {}
+                self.changed()
+            }
+            Event::CancelVariable => {
+                state.new_variable = None;
 
-                (ButtonState::Ready, _) => {
-                    content =
-                        content.push(button("Create Container").on_press(Event::SubmitPressed));
+                self.changed()
+            }
+            Event::RemoveVariable(key) => {
+                if let Some((config, _)) = state.data.as_mut() {
+                    config.variables.remove(&key);
                 }
-                (ButtonState::Pulling(states), _) => {
-                    content = content.push(badge("Pulling").style(BadgeStyles::Success));
-                    for (image, progress) in states {
-                        content = content.push(
-                            row!(
-                                text(image).width(Length::FillPortion(1)),
-                                progress_bar(0.0..=1.0, *progress).width(Length::FillPortion(3))
-                            )
-                            .spacing(15),
-                        );
+
+                self.changed()
+            }
+            Event::ToggleEnvText => {
+                match state.env_text {
+                    // The errors are shown next to the lines
+                    Some(_) => _ = state.apply_env_text(),
+                    None => state.edit_env_as_text(),
+                }
+
+                self.changed()
+            }
+            Event::EnvLineChanged(index, line) => {
+                if let Some(lines) = state.env_text.as_mut().filter(|lines| index < lines.len()) {
+                    let pasted = line
+                        .lines()
+                        .map(|line| line.trim_end_matches('\r').to_string())
+                        .collect::<Vec<_>>();
+                    match pasted.is_empty() {
+                        true => lines[index].clear(),
+                        false => _ = lines.splice(index..=index, pasted),
                     }
                 }
-                (ButtonState::Creating, _) => {
-                    content = content.push(badge("Creating").style(BadgeStyles::Success));
+
+                self.changed()
+            }
+            Event::EnvLineAdded(index) => {
+                if let Some(lines) = state.env_text.as_mut() {
+                    lines.insert((index + 1).min(lines.len()), String::new());
                 }
+
+                self.changed()
             }
-        }
+            Event::EnvLineRemoved(index) => {
+                if let Some(lines) = state.env_text.as_mut().filter(|lines| index < lines.len()) {
+                    lines.remove(index);
+                }
 
-        return scrollable(content).into();
-    }
-}
+                self.changed()
+            }
+            Event::ImportEnv => self.on_import_env.as_ref().map(|fun| fun()),
+            Event::Discard => self.on_discard.as_ref().map(|fun| fun()),
+            Event::AddUnmatchedEnv => {
+                if let Some((config, _)) = state.data.as_mut() {
+                    for (key, value) in state.unmatched_env.drain(..) {
+                        if !value.is_empty() {
+                            config.variables.insert(key, value);
+                        }
+                    }
+                }
 
-impl<'a, Message> From<AddContainer<Message>> for Element<'a, Message, Renderer>
-where
-    Message: 'a,
-{
-    fn from(value: AddContainer<Message>) -> Self {
-        component(value)
-    }
-}
+                self.changed()
+            }
+            Event::DismissUnmatchedEnv => {
+                state.unmatched_env.clear();
 
-fn env_var_row<'a>(name: String, key: String, value: String) -> Element<'a, Event, Renderer> {
-    row!(
-        column!(
-            text(name),
-            text(&key)
-                .size(12)
-                .style(Text::Color(Color::from_rgb8(150, 150, 150)))
-        )
-        .width(Length::FillPortion(2)),
+                self.changed()
+            }
+            Event::ToggleBuildOutput => {
+                state.show_build_output = !state.show_build_output;
+
+                self.changed()
+            }
+            Event::ToggleNotes => {
+                state.show_notes = !state.show_notes;
+
+                self.changed()
+            }
+            Event::ShmSizeChanged(size) => {
+                if let Some((config, _)) = state.data.as_mut() {
+                    if size.is_empty() {
+                        config.shm_size_mb = None;
+                    } else if let Ok(size) = size.parse() {
+                        config.shm_size_mb = Some(size);
+                    }
+                }
+
+                self.changed()
+            }
+            Event::CpusetChanged(cpuset) => {
+                if let Some((config, _)) = state.data.as_mut() {
+                    config.cpuset_cpus = Some(cpuset.replace(' ', "")).filter(|c| !c.is_empty());
+                }
+
+                self.changed()
+            }
+            Event::LogDriverSelected(driver) => {
+                if let Some((config, _)) = state.data.as_mut() {
+                    config.log = match driver {
+                        "json-file" => LogSettings::default(),
+                        driver => LogSettings {
+                            driver: driver.to_string(),
+                            options: Default::default(),
+                        },
+                    };
+                }
+
+                self.changed()
+            }
+            Event::LogMaxSizeChanged(size) => {
+                if let Some((config, _)) = state.data.as_mut() {
+                    config.log.set_option("max-size", &size);
+                }
+
+                self.changed()
+            }
+            Event::LogMaxFileChanged(files) => {
+                if let Some((config, _)) = state.data.as_mut() {
+                    if files.is_empty() || files.parse::<u32>().is_ok() {
+                        config.log.set_option("max-file", &files);
+                    }
+                }
+
+                self.changed()
+            }
+            Event::HostnameChanged(hostname) => {
+                if let Some((config, _)) = state.data.as_mut() {
+                    config.hostname = Some(hostname.trim().to_string()).filter(|h| !h.is_empty());
+                }
+
+                self.changed()
+            }
+            Event::UserChanged(user) => {
+                if let Some((config, _)) = state.data.as_mut() {
+                    config.user = Some(user.trim().to_string()).filter(|u| !u.is_empty());
+                }
+
+                self.changed()
+            }
+            Event::UseCurrentUser => {
+                if let Some((config, _)) = state.data.as_mut() {
+                    config.user = current_user();
+                }
+
+                self.changed()
+            }
+            Event::ExtraHostsChanged(extra_hosts) => {
+                if let Some((config, _)) = state.data.as_mut() {
+                    config.extra_hosts = extra_hosts
+                        .split([',', ' '])
+                        .filter(|entry| !entry.is_empty())
+                        .map(String::from)
+                        .collect();
+                }
+                state.extra_hosts = extra_hosts;
+
+                self.changed()
+            }
+            Event::UlimitsChanged(ulimits) => {
+                if let Some((config, _)) = state.data.as_mut() {
+                    if let Some(parsed) = parse_ulimits(&ulimits) {
+                        config.ulimits = parsed;
+                    }
+                }
+                state.ulimits = ulimits;
+
+                self.changed()
+            }
+            Event::SysctlsChanged(sysctls) => {
+                if let Some((config, _)) = state.data.as_mut() {
+                    if let Some(parsed) = parse_sysctls(&sysctls) {
+                        config.sysctls = parsed;
+                    }
+                }
+                state.sysctls = sysctls;
+                state.sysctl_error = None;
+
+                self.changed()
+            }
+            Event::GpuToggled(enabled) => {
+                if let Some((config, _)) = state.data.as_mut() {
+                    config.gpus = enabled.then(Vec::new);
+                }
+
+                self.changed()
+            }
+            Event::GpuDevice(device, selected) => {
+                if let Some(gpus) = state
+                    .data
+                    .as_mut()
+                    .and_then(|(config, _)| config.gpus.as_mut())
+                {
+                    gpus.retain(|gpu| gpu != &device);
+                    if selected {
+                        gpus.push(device);
+                    }
+                }
+
+                self.changed()
+            }
+            Event::Persist(persist) => {
+                state.persist = persist;
+                if let Some((config, selected_container)) = state.data.as_mut() {
+                    if persist {
+                        config.volumes = selected_container.volumes.clone();
+                        config.tmpfs.clear();
+                    } else {
+                        config.volumes = HashMap::new();
+                    }
+                }
+                state.bind_folders.clear();
+
+                self.changed()
+            }
+            Event::VolumeReadOnly(volume, read_only) => {
+                if let Some((config, _)) = state.data.as_mut() {
+                    config.read_only_volumes.retain(|name| name != &volume);
+                    if read_only {
+                        config.read_only_volumes.push(volume);
+                    }
+                }
+
+                self.changed()
+            }
+            Event::VolumeOptionsChanged(volume, typed) => {
+                if let Some((config, _)) = state.data.as_mut() {
+                    match parse_volume_options(&typed) {
+                        Some(options) if options.is_empty() => {
+                            _ = config.volume_options.remove(&volume)
+                        }
+                        Some(options) => _ = config.volume_options.insert(volume.clone(), options),
+                        // Kept as typed, the form can't be submitted until it parses
+                        None => {}
+                    }
+                }
+                state.volume_options.insert(volume, typed);
+
+                self.changed()
+            }
+            Event::BindFolderChanged(volume, typed) => {
+                let old_source = mount_source(
+                    &volume,
+                    state
+                        .bind_folders
+                        .get(&volume)
+                        .map(String::as_str)
+                        .unwrap_or_default(),
+                );
+                let new_source = mount_source(&volume, &typed);
+                if let Some((config, _)) = state.data.as_mut() {
+                    if old_source != new_source {
+                        if let Some(path) = config.volumes.remove(&old_source) {
+                            config.volumes.insert(new_source.clone(), path);
+                        }
+                        if config.read_only_volumes.contains(&old_source) {
+                            config.read_only_volumes.retain(|name| name != &old_source);
+                            config.read_only_volumes.push(new_source.clone());
+                        }
+                    }
+                    if new_source != volume {
+                        // Driver options only apply to volumes
+                        config.volume_options.remove(&volume);
+                        state.volume_options.remove(&volume);
+                    }
+                }
+                state.bind_folders.insert(volume, typed);
+
+                self.changed()
+            }
+            Event::Tmpfs(tmpfs) => {
+                if let Some((config, selected_container)) = state.data.as_mut() {
+                    config.tmpfs = if tmpfs {
+                        selected_container.volumes.values().cloned().collect()
+                    } else {
+                        vec![]
+                    };
+                }
+
+                self.changed()
+            }
+            Event::TmpfsSizeChanged(size) => {
+                if let Some((config, _)) = state.data.as_mut() {
+                    config.tmpfs_size = parse_size(&size);
+                }
+                state.tmpfs_size = size;
+
+                self.changed()
+            }
+        }
+    }
+
+    fn view(&self, _: &Self::State) -> iced_aw::Element<'_, Self::Event, Renderer> {
+        let state = &self.draft;
+        if let Some((review, (config, database))) = state.review.as_ref().zip(state.data.as_ref()) {
+            let content = column!()
+                .align_items(iced::Alignment::Center)
+                .spacing(15)
+                .padding(15);
+            return scrollable(self.review(content, review, config, database)).into();
+        }
+
+        let mut picker = row!(pick_list(
+            self.images.clone(),
+            state
+                .data
+                .as_ref()
+                .map(|(_, selected_database)| selected_database.clone())
+                .clone(),
+            |image| Event::SelectContainer(Box::new(image)),
+        )
+        .placeholder("Choose image")
+        .width(200))
+        .align_items(iced::Alignment::Center)
+        .spacing(10);
+        if state.data.is_some() && self.on_discard.is_some() {
+            picker = picker.push(
+                button("Discard")
+                    .style(iced::theme::Button::Secondary)
+                    .on_press(Event::Discard),
+            );
+        }
+        let mut content = column!(picker)
+            .align_items(iced::Alignment::Center)
+            .spacing(15)
+            .padding(15);
+
+        for warning in state.warnings.iter() {
+            content = content.push(
+                text(warning)
+                    .size(12)
+                    .style(Text::Color(Color::from_rgb8(230, 160, 40))),
+            );
+        }
+
+        if let Some((config, selecetd_image)) = state.data.as_ref() {
+            content = content.push(
+                row!(
+                    text_input("name", &config.name).on_input(Event::NameChanged),
+                    button("Generate name")
+                        .style(iced::theme::Button::Secondary)
+                        .on_press(Event::GenerateName),
+                    if selecetd_image.tags.is_empty() {
+                        Element::from(
+                            text_input("tag", &config.tag)
+                                .on_input(Event::SelectedTag)
+                                .width(150),
+                        )
+                    } else {
+                        pick_list(
+                            selecetd_image.tags.clone(),
+                            Some(config.tag.clone()),
+                            Event::SelectedTag,
+                        )
+                        .into()
+                    }
+                )
+                .spacing(15),
+            );
+            if selecetd_image.has_notes() {
+                content = content.push(notes_panel(selecetd_image, &config.tag, state.show_notes));
+            }
+
+            let project = config.project.clone().unwrap_or_default();
+            let typed = project.trim().to_lowercase();
+            let suggestions = self
+                .project_options
+                .iter()
+                .filter(|option| option.to_lowercase().starts_with(&typed) && **option != project)
+                .map(|option| {
+                    button(text(option).size(12))
+                        .style(iced::theme::Button::Secondary)
+                        .padding([2, 6])
+                        .on_press(Event::ProjectChanged(option.clone()))
+                        .into()
+                })
+                .collect::<Vec<_>>();
+            content = content.push(
+                row!(
+                    text("Project").width(Length::FillPortion(2)),
+                    text_input("optional, e.g. billing-api", &project)
+                        .on_input(Event::ProjectChanged)
+                        .width(Length::FillPortion(3))
+                )
+                .align_items(iced::Alignment::Center),
+            );
+            if !suggestions.is_empty() {
+                content = content.push(row(suggestions).spacing(5));
+            }
+
+            if config.tag == "latest" {
+                content = content.push(
+                    text("latest changes whenever the image is updated, pin a version tag in config.yaml to keep it stable")
+                        .size(12)
+                        .style(Text::Color(Color::from_rgb8(90, 150, 220))),
+                );
+            }
+
+            if !state.recalled.is_empty() {
+                content = content.push(
+                    badge("Filled in with the values you last used").style(BadgeStyles::Info),
+                );
+            }
+
+            if let Some(lines) = state.env_text.as_ref() {
+                content = content.push(env_text_editor(lines));
+            } else {
+                for (name, variable) in selecetd_image.variables.iter() {
+                    let value = config
+                        .variables
+                        .get(variable)
+                        .map(|a| a.to_owned())
+                        .unwrap_or_default();
+
+                    content = content.push(env_var_row(
+                        name.clone(),
+                        variable.clone(),
+                        value,
+                        state.recalled.contains(variable),
+                    ));
+                }
+
+                let extra = extra_variables(config, selecetd_image);
+                if !extra.is_empty() {
+                    content = content.push(text("Extra variables").size(16));
+                }
+                for key in extra {
+                    let value = config.variables.get(&key).cloned().unwrap_or_default();
+                    content = content.push(extra_var_row(key, value));
+                }
+
+                match state.new_variable.as_ref() {
+                    Some((key, value)) => {
+                        let error = (!key.is_empty())
+                            .then(|| new_variable_error(key, config, selecetd_image))
+                            .flatten();
+                        let mut add = button("Add");
+                        if !key.is_empty() && error.is_none() {
+                            add = add.on_press(Event::ConfirmVariable);
+                        }
+                        content = content.push(
+                            row!(
+                                text_input("NAME", key)
+                                    .on_input(Event::NewVariableKeyChanged)
+                                    .font(Font::MONOSPACE)
+                                    .width(Length::FillPortion(2)),
+                                text_input("value", value)
+                                    .on_input(Event::NewVariableValueChanged)
+                                    .on_submit(Event::ConfirmVariable)
+                                    .width(Length::FillPortion(3)),
+                                add,
+                                button("Cancel")
+                                    .style(iced::theme::Button::Secondary)
+                                    .on_press(Event::CancelVariable)
+                            )
+                            .align_items(iced::Alignment::Center)
+                            .spacing(5),
+                        );
+                        if let Some(error) = error {
+                            content = content.push(
+                                text(error)
+                                    .size(12)
+                                    .style(Text::Color(Color::from_rgb8(220, 60, 60))),
+                            );
+                        }
+                    }
+                    None => {
+                        let mut buttons = row!(
+                            button("Add variable")
+                                .style(iced::theme::Button::Secondary)
+                                .on_press(Event::AddVariable),
+                            button("Edit as text")
+                                .style(iced::theme::Button::Secondary)
+                                .on_press(Event::ToggleEnvText)
+                        )
+                        .spacing(15);
+                        if self.on_import_env.is_some() {
+                            buttons = buttons.push(
+                                button("Import from .env…")
+                                    .style(iced::theme::Button::Secondary)
+                                    .on_press(Event::ImportEnv),
+                            );
+                        }
+                        content = content.push(buttons);
+                    }
+                }
+            }
+            if !state.unmatched_env.is_empty() {
+                let keys = state.unmatched_env.iter().map(|(key, _)| key).join(", ");
+                content = content.push(
+                    column!(
+                        text(format!("Not used by {}: {keys}", selecetd_image.name))
+                            .size(12)
+                            .style(Text::Color(Color::from_rgb8(230, 160, 40))),
+                        row!(
+                            button("Add as extra variables").on_press(Event::AddUnmatchedEnv),
+                            button("Dismiss")
+                                .style(iced::theme::Button::Secondary)
+                                .on_press(Event::DismissUnmatchedEnv)
+                        )
+                        .spacing(15)
+                    )
+                    .spacing(5),
+                );
+            }
+
+            if !config.command.is_empty() {
+                content = content.push(text("The container will be started with").size(20));
+                for arg in render_args(&config.command, &config.variables) {
+                    content = content.push(
+                        text(arg)
+                            .size(12)
+                            .style(Text::Color(Color::from_rgb8(150, 150, 150))),
+                    );
+                }
+            }
+
+            content = content.push(checkbox(
+                "Presistant container",
+                state.persist,
+                Event::Persist,
+            ));
+            if loses_data(config, selecetd_image) {
+                content = content.push(
+                    text("Without persistence all data is lost when the container is removed, only turn it off for throwaway databases")
+                        .size(18)
+                        .style(Text::Color(Color::from_rgb8(220, 60, 60))),
+                );
+            }
+
+            let valid_tmpfs_size =
+                state.tmpfs_size.trim().is_empty() || config.tmpfs_size.is_some();
+            if !state.persist && !selecetd_image.volumes.is_empty() {
+                content = content.push(checkbox(
+                    "Use tmpfs for the data directory",
+                    !config.tmpfs.is_empty(),
+                    Event::Tmpfs,
+                ));
+
+                if !config.tmpfs.is_empty() {
+                    content = content.push(
+                        row!(
+                            text("Size limit").width(Length::FillPortion(2)),
+                            text_input("e.g. 512m or 2g", &state.tmpfs_size)
+                                .on_input(Event::TmpfsSizeChanged)
+                                .width(Length::FillPortion(3))
+                        )
+                        .align_items(iced::Alignment::Center),
+                    );
+
+                    if !valid_tmpfs_size {
+                        content = content.push(
+                            text("The size limit must be a size such as 512m or 2g")
+                                .size(12)
+                                .style(Text::Color(Color::from_rgb8(220, 60, 60))),
+                        );
+                    }
+                }
+            }
+
+            let valid_volume_options = state
+                .volume_options
+                .values()
+                .all(|typed| parse_volume_options(typed).is_some());
+            if state.persist {
+                content = content.push(text("The following mounts will be created").size(20));
+                for (name, path) in selecetd_image.volumes.iter() {
+                    let typed = state
+                        .volume_options
+                        .get(name)
+                        .map(String::as_str)
+                        .unwrap_or_default();
+                    let folder = state
+                        .bind_folders
+                        .get(name)
+                        .map(String::as_str)
+                        .unwrap_or_default();
+                    let source = mount_source(name, folder);
+                    let bound = source != *name;
+                    let folder_volume = name.clone();
+                    let options_volume = name.clone();
+                    let mut mount = row!(column!(
+                        text(name),
+                        text(path)
+                            .size(12)
+                            .style(Text::Color(Color::from_rgb8(150, 150, 150)))
+                    )
+                    .width(Length::FillPortion(2)))
+                    .align_items(iced::Alignment::Center)
+                    .spacing(5)
+                    .push(
+                        text_input("Host folder, empty for a volume", folder)
+                            .on_input(move |typed| {
+                                Event::BindFolderChanged(folder_volume.clone(), typed)
+                            })
+                            .width(Length::FillPortion(3)),
+                    );
+                    if !bound {
+                        mount = mount.push(
+                            text_input("Driver options, e.g. type=nfs o=addr=10.0.0.5", typed)
+                                .on_input(move |typed| {
+                                    Event::VolumeOptionsChanged(options_volume.clone(), typed)
+                                })
+                                .width(Length::FillPortion(3)),
+                        );
+                    }
+                    let read_only = config.read_only_volumes.contains(&source);
+                    content = content.push(mount.push(checkbox(
+                        "Read-only",
+                        read_only,
+                        move |read_only| Event::VolumeReadOnly(source.clone(), read_only),
+                    )));
+                    if !folder.trim().is_empty() && !bound {
+                        content = content.push(
+                            text("Host folders must be absolute paths, such as /srv/data or C:\\data")
+                                .size(12)
+                                .style(Text::Color(Color::from_rgb8(220, 60, 60))),
+                        );
+                    }
+                    if parse_volume_options(typed).is_none() {
+                        content = content.push(
+                            text("Driver options must be key=value pairs separated by spaces")
+                                .size(12)
+                                .style(Text::Color(Color::from_rgb8(220, 60, 60))),
+                        );
+                    }
+                }
+            }
+
+            content = content.push(checkbox(
+                "Start after creating",
+                config.start_after_create,
+                Event::StartAfterCreate,
+            ));
+
+            let valid_host_port = parse_host_port(&state.host_port).is_some();
+            if let Some(assignment) = config.host_port {
+                content = content.push(
+                    row!(
+                        text(format!("Host port for {}", assignment.container))
+                            .width(Length::FillPortion(2)),
+                        text_input("auto", &state.host_port)
+                            .on_input(Event::HostPortChanged)
+                            .width(Length::FillPortion(3))
+                    )
+                    .align_items(iced::Alignment::Center),
+                );
+                if !valid_host_port {
+                    content = content.push(
+                        text("The host port must be a number from 1 to 65535, or empty for a free one")
+                            .size(12)
+                            .style(Text::Color(Color::from_rgb8(220, 60, 60))),
+                    );
+                }
+            }
+
+            content = content.push(
+                button(if state.advanced {
+                    "Hide advanced options"
+                } else {
+                    "Advanced options"
+                })
+                .style(iced::theme::Button::Secondary)
+                .on_press(Event::ToggleAdvanced),
+            );
+
+            let valid_cpuset = config.cpuset_cpus.as_deref().is_none_or(is_valid_cpuset);
+            let valid_env_text = state
+                .env_text
+                .as_ref()
+                .is_none_or(|lines| parse_env_lines(lines.iter().map(String::as_str)).is_ok());
+            let valid_hostname = config.hostname.as_deref().is_none_or(is_valid_hostname);
+            let valid_user = config.user.as_deref().is_none_or(is_valid_user);
+            let valid_extra_hosts = config
+                .extra_hosts
+                .iter()
+                .all(|entry| is_valid_extra_host(entry));
+            let valid_ulimits = parse_ulimits(&state.ulimits).is_some();
+            let valid_sysctls = parse_sysctls(&state.sysctls).is_some();
+            if state.advanced {
+                content = content
+                    .push(
+                        row!(
+                            text("Shared memory (MB)").width(Length::FillPortion(2)),
+                            text_input(
+                                "64",
+                                &config
+                                    .shm_size_mb
+                                    .map(|size| size.to_string())
+                                    .unwrap_or_default()
+                            )
+                            .on_input(Event::ShmSizeChanged)
+                            .width(Length::FillPortion(3))
+                        )
+                        .align_items(iced::Alignment::Center),
+                    )
+                    .push(
+                        row!(
+                            text("CPUs").width(Length::FillPortion(2)),
+                            text_input(
+                                "all, or e.g. 0-3",
+                                config.cpuset_cpus.as_deref().unwrap_or_default()
+                            )
+                            .on_input(Event::CpusetChanged)
+                            .width(Length::FillPortion(3))
+                        )
+                        .align_items(iced::Alignment::Center),
+                    )
+                    .push(
+                        row!(
+                            text("Log driver").width(Length::FillPortion(2)),
+                            pick_list(
+                                &LOG_DRIVERS[..],
+                                LOG_DRIVERS
+                                    .into_iter()
+                                    .find(|driver| *driver == config.log.driver),
+                                Event::LogDriverSelected
+                            )
+                            .width(Length::FillPortion(3))
+                        )
+                        .align_items(iced::Alignment::Center),
+                    );
+                if config.log.rotates() {
+                    content = content.push(
+                        row!(
+                            text("Rotate logs at").width(Length::FillPortion(2)),
+                            text_input(
+                                "unlimited, or e.g. 10m",
+                                config.log.option("max-size").unwrap_or_default()
+                            )
+                            .on_input(Event::LogMaxSizeChanged)
+                            .width(Length::FillPortion(2)),
+                            text("keeping").width(Length::Shrink),
+                            text_input("1", config.log.option("max-file").unwrap_or_default())
+                                .on_input(Event::LogMaxFileChanged)
+                                .width(Length::FillPortion(1)),
+                            text("files").width(Length::Shrink),
+                        )
+                        .spacing(5)
+                        .align_items(iced::Alignment::Center),
+                    );
+                }
+                content = content
+                    .push(
+                        row!(
+                            text("Hostname").width(Length::FillPortion(2)),
+                            text_input(
+                                &default_hostname(&config.name),
+                                config.hostname.as_deref().unwrap_or_default()
+                            )
+                            .on_input(Event::HostnameChanged)
+                            .width(Length::FillPortion(3))
+                        )
+                        .align_items(iced::Alignment::Center),
+                    )
+                    .push(
+                        row!(
+                            text("Extra hosts").width(Length::FillPortion(2)),
+                            text_input("e.g. api:10.0.0.5, cache:10.0.0.6", &state.extra_hosts)
+                                .on_input(Event::ExtraHostsChanged)
+                                .width(Length::FillPortion(3))
+                        )
+                        .align_items(iced::Alignment::Center),
+                    )
+                    .push(
+                        row!(
+                            text("Ulimits").width(Length::FillPortion(2)),
+                            text_input("e.g. nofile=65536 memlock=unlimited", &state.ulimits)
+                                .on_input(Event::UlimitsChanged)
+                                .width(Length::FillPortion(3))
+                        )
+                        .align_items(iced::Alignment::Center),
+                    )
+                    .push(
+                        row!(
+                            text("Sysctls").width(Length::FillPortion(2)),
+                            text_input("e.g. net.core.somaxconn=1024", &state.sysctls)
+                                .on_input(Event::SysctlsChanged)
+                                .width(Length::FillPortion(3))
+                        )
+                        .align_items(iced::Alignment::Center),
+                    );
+
+                if let Some(sysctl_error) = state.sysctl_error.as_ref() {
+                    content = content.push(
+                        text(sysctl_error)
+                            .size(12)
+                            .style(Text::Color(Color::from_rgb8(220, 60, 60))),
+                    );
+                }
+
+                content = content.push({
+                    let mut user = row!(
+                        text("Run as user").width(Length::FillPortion(2)),
+                        text_input(
+                            "image default, or uid:gid",
+                            config.user.as_deref().unwrap_or_default()
+                        )
+                        .on_input(Event::UserChanged)
+                        .width(Length::FillPortion(3))
+                    )
+                    .spacing(5)
+                    .align_items(iced::Alignment::Center);
+                    if current_user().is_some() {
+                        user = user.push(
+                            button("Use mine")
+                                .style(iced::theme::Button::Secondary)
+                                .on_press(Event::UseCurrentUser),
+                        );
+                    }
+                    user
+                });
+
+                if config.user.is_some() && selecetd_image.arbitrary_user == Some(false) {
+                    content = content.push(
+                        text(format!(
+                            "{} images may fail to start as another user",
+                            selecetd_image.name
+                        ))
+                        .size(12)
+                        .style(Text::Color(Color::from_rgb8(230, 160, 40))),
+                    );
+                }
+
+                if selecetd_image.gpu_capable {
+                    content = content.push(self.gpu_toggle(config));
+                }
+
+                if !self.dependency_options.is_empty() {
+                    content = content.push(
+                        text("Depends on")
+                            .size(12)
+                            .style(Text::Color(Color::from_rgb8(150, 150, 150))),
+                    );
+                }
+                for name in self.dependency_options.iter() {
+                    let name = name.clone();
+                    content = content.push(checkbox(
+                        name.clone(),
+                        config.depends_on.contains(&name),
+                        move |depends| Event::DependsOn(name.clone(), depends),
+                    ));
+                }
+
+                content = content.push(checkbox(
+                    "Dry run, only list the docker calls creating it would make",
+                    config.dry_run,
+                    Event::DryRun,
+                ));
+
+                if !valid_cpuset {
+                    content = content.push(
+                        text("CPUs must be a list such as 0-3 or 0,2,4-5")
+                            .size(12)
+                            .style(Text::Color(Color::from_rgb8(220, 60, 60))),
+                    );
+                }
+                if !valid_hostname {
+                    content = content.push(
+                        text("The hostname may only contain letters, digits and inner dashes")
+                            .size(12)
+                            .style(Text::Color(Color::from_rgb8(220, 60, 60))),
+                    );
+                }
+                if !valid_extra_hosts {
+                    content = content.push(
+                        text("Extra hosts must be host:ip pairs such as api:10.0.0.5")
+                            .size(12)
+                            .style(Text::Color(Color::from_rgb8(220, 60, 60))),
+                    );
+                }
+                if !valid_ulimits {
+                    content = content.push(
+                        text("Ulimits must be name=soft:hard or name=limit, such as nofile=65536")
+                            .size(12)
+                            .style(Text::Color(Color::from_rgb8(220, 60, 60))),
+                    );
+                }
+                if !valid_sysctls {
+                    content = content.push(
+                        text("Sysctls must be key=value pairs such as net.core.somaxconn=1024")
+                            .size(12)
+                            .style(Text::Color(Color::from_rgb8(220, 60, 60))),
+                    );
+                }
+                if !valid_user {
+                    content = content.push(
+                        text("The user must be a name or uid, optionally followed by :group")
+                            .size(12)
+                            .style(Text::Color(Color::from_rgb8(220, 60, 60))),
+                    );
+                }
+            }
+
+            match (&self.button_state, config.name.as_str()) {
+                (ButtonState::None, _) => {}
+                (ButtonState::Ready, "") => {}
+                (ButtonState::Ready, _) if config.tag.trim().is_empty() => {}
+                (ButtonState::Ready, _) if !valid_cpuset || !valid_env_text => {}
+                (ButtonState::Ready, _) if config.host_port.is_some() && !valid_host_port => {}
+                (ButtonState::Ready, _) if !valid_hostname || !valid_extra_hosts => {}
+                (ButtonState::Ready, _) if !valid_user => {}
+                (ButtonState::Ready, _) if !valid_ulimits || !valid_sysctls => {}
+                (ButtonState::Ready, _) if !config.tmpfs.is_empty() && !valid_tmpfs_size => {}
+                (ButtonState::Ready, _) if state.persist && !valid_volume_options => {}
+
+                (ButtonState::Ready, _) => {
+                    content = content.push(
+                        button(if config.dry_run {
+                            "Plan Container"
+                        } else if config.start_after_create {
+                            "Create Container"
+                        } else {
+                            "Create without starting"
+                        })
+                        .on_press(Event::SubmitPressed),
+                    );
+                }
+                (_, _) => content = self.progress(content),
+            }
+        }
+
+        scrollable(content).into()
+    }
+}
+
+type Column<'a> = iced::widget::Column<'a, Event, Renderer>;
+
+impl<Message> AddContainer<Message> {
+    /// How far creating the container got, nothing before it is submitted.
+    fn progress<'a>(&'a self, mut content: Column<'a>) -> Column<'a> {
+        match &self.button_state {
+            ButtonState::None | ButtonState::Ready => {}
+            ButtonState::Pulling(pull) => {
+                content = content.push(badge("Pulling").style(BadgeStyles::Success));
+                for (layer, progress) in pull.layers() {
+                    content = content.push(
+                        row!(
+                            text(layer).width(Length::FillPortion(1)),
+                            progress_bar(0.0..=1.0, progress).width(Length::FillPortion(3))
+                        )
+                        .spacing(15),
+                    );
+                }
+                if let Some(summary) = pull.summary(Instant::now()) {
+                    content = content.push(
+                        text(summary)
+                            .size(12)
+                            .style(Text::Color(Color::from_rgb8(150, 150, 150))),
+                    );
+                }
+            }
+            ButtonState::BuildingImage(output) => {
+                content = content
+                    .push(badge("Building image").style(BadgeStyles::Success))
+                    .push(
+                        button(if self.draft.show_build_output {
+                            "Hide build output"
+                        } else {
+                            "Show build output"
+                        })
+                        .style(iced::theme::Button::Secondary)
+                        .on_press(Event::ToggleBuildOutput),
+                    );
+                if self.draft.show_build_output {
+                    content = content.push(column(
+                        output
+                            .iter()
+                            .map(|line| text(line).size(12).font(Font::MONOSPACE).into())
+                            .collect(),
+                    ));
+                }
+            }
+            ButtonState::Creating => {
+                content = content.push(badge("Creating").style(BadgeStyles::Success));
+            }
+        }
+
+        content
+    }
+
+    /// The config about to be created, read-only, noting the values db-mgr filled in.
+    fn review<'a>(
+        &'a self,
+        mut content: Column<'a>,
+        review: &'a DbContainerConfig,
+        typed: &'a DbContainerConfig,
+        database: &'a DatabaseConfig,
+    ) -> Column<'a> {
+        content = content.push(text("Review").size(20)).push(
+            text("This is exactly what will be sent to docker")
+                .size(12)
+                .style(Text::Color(Color::from_rgb8(150, 150, 150))),
+        );
+
+        let image = match review.build {
+            Some(_) => format!("{}:{} (built from a Dockerfile)", review.image, review.tag),
+            None => format!("{}:{}", review.image, review.tag),
+        };
+        content = content
+            .push(review_row("Container", &review.name, Some("prefixed")))
+            .push(review_row("Image", &image, None));
+        if let Some(project) = review.project.as_ref() {
+            content = content.push(review_row("Project", project, None));
+        }
+        if let Some(hostname) = review.hostname.as_ref() {
+            let note = typed.hostname.is_none().then_some("from the name");
+            content = content.push(review_row("Hostname", hostname, note));
+        }
+        if let Some(assignment) = review.host_port {
+            let published = match assignment.host {
+                Some(host) => format!("127.0.0.1:{host} → {}", assignment.container),
+                None => format!("a free port → {}", assignment.container),
+            };
+            let note = assignment.host.is_none().then_some("assigned when created");
+            content = content.push(review_row("Host port", &published, note));
+        }
+
+        if !review.variables.is_empty() {
+            content = content.push(text("Environment").size(16));
+        }
+        for (key, value) in review.variables.iter().sorted() {
+            let name = database
+                .variables
+                .iter()
+                .find(|(_, declared)| *declared == key)
+                .map_or(key.as_str(), |(name, _)| name.as_str());
+            let shown = if is_secret(name, key) {
+                "••••••"
+            } else {
+                value.as_str()
+            };
+            let note = if self.draft.recalled.contains(key) {
+                Some("recalled")
+            } else if database.defaults.get(key) == Some(value) {
+                Some("default")
+            } else if !database.declares(key) {
+                Some("extra")
+            } else {
+                None
+            };
+            content = content.push(review_row(key, shown, note));
+        }
+
+        if !review.volumes.is_empty() {
+            content = content.push(text("Volumes").size(16));
+        }
+        for (volume, path) in review.volumes.iter().sorted() {
+            let mut notes = vec![];
+            if review.read_only_volumes.contains(volume) {
+                notes.push("read-only".to_string());
+            }
+            if let Some(options) = review.volume_options.get(volume) {
+                let options = options
+                    .iter()
+                    .sorted()
+                    .map(|(key, value)| format!("{key}={value}"))
+                    .join(" ");
+                notes.push(format!("driver options {options}"));
+            }
+            let note = Some(notes.join(", ")).filter(|note| !note.is_empty());
+            content = content.push(review_row(volume, path, note.as_deref()));
+        }
+        for path in review.tmpfs.iter() {
+            let size = review
+                .tmpfs_size
+                .map(|size| format!("tmpfs of {}", format_size(size as i64)))
+                .unwrap_or_else(|| "tmpfs".to_string());
+            content = content.push(review_row(path, &size, None));
+        }
+
+        if !review.command.is_empty() {
+            let note = (review.command != typed.command).then_some("placeholders filled in");
+            content = content.push(review_row("Command", &review.command.join(" "), note));
+        }
+        if let Some(size) = review.shm_size_mb {
+            content = content.push(review_row("Shared memory", &format!("{size} MB"), None));
+        }
+        if let Some(cpuset) = review.cpuset_cpus.as_ref() {
+            content = content.push(review_row("CPUs", cpuset, None));
+        }
+        if review.log != LogSettings::default() {
+            content = content.push(review_row("Logs", &review.log.to_string(), None));
+        }
+        if let Some(user) = review.user.as_ref() {
+            content = content.push(review_row("User", user, None));
+        }
+        if !review.extra_hosts.is_empty() {
+            content = content.push(review_row(
+                "Extra hosts",
+                &review.extra_hosts.join(", "),
+                None,
+            ));
+        }
+        if !review.ulimits.is_empty() {
+            content = content.push(review_row("Ulimits", &typed_ulimits(&review.ulimits), None));
+        }
+        if !review.sysctls.is_empty() {
+            content = content.push(review_row("Sysctls", &typed_sysctls(&review.sysctls), None));
+        }
+        if let Some(gpus) = review.gpus.as_ref() {
+            content = content.push(review_row("GPUs", &describe_gpus(gpus), None));
+        }
+        if !review.depends_on.is_empty() {
+            content = content.push(review_row(
+                "Depends on",
+                &review.depends_on.join(", "),
+                None,
+            ));
+        }
+        content = content.push(review_row(
+            "Start after creating",
+            if review.start_after_create {
+                "yes"
+            } else {
+                "no"
+            },
+            None,
+        ));
+        if review.dry_run {
+            content = content.push(review_row("Dry run", "yes, nothing is created", None));
+        }
+
+        let loses_data = loses_data(typed, database);
+        if loses_data {
+            content = content.push(
+                text("The container has no volumes, its data is lost when it is removed")
+                    .size(18)
+                    .style(Text::Color(Color::from_rgb8(220, 60, 60))),
+            );
+        }
+
+        if let ButtonState::Ready = self.button_state {
+            if loses_data {
+                content = content.push(checkbox(
+                    "I understand data will be lost when the container is removed",
+                    self.draft.accept_data_loss,
+                    Event::AcceptDataLoss,
+                ));
+            }
+
+            let mut confirm = button("Confirm");
+            if !loses_data || self.draft.accept_data_loss {
+                confirm = confirm.on_press(Event::ConfirmCreate);
+            }
+            content = content.push(
+                row!(
+                    button("Back")
+                        .style(iced::theme::Button::Secondary)
+                        .on_press(Event::BackToForm),
+                    confirm
+                )
+                .spacing(15),
+            );
+        }
+
+        self.progress(content)
+    }
+}
+
+impl<Message> AddContainer<Message> {
+    /// The toggle giving the container GPUs, with the GPUs to pick from when docker advertises
+    /// several. Disabled with the reason on hover when docker has no nvidia runtime.
+    fn gpu_toggle(&self, config: &DbContainerConfig) -> Element<'_, Event, Renderer> {
+        let unavailable = match self.nvidia_runtime {
+            Some(true) => None,
+            Some(false) => Some(
+                "Docker has no nvidia runtime, install the NVIDIA Container Toolkit and restart docker to give containers GPUs",
+            ),
+            None => Some("Checking whether docker has the nvidia runtime"),
+        };
+        if let Some(reason) = unavailable {
+            return tooltip(
+                text("Enable GPU (unavailable)")
+                    .style(Text::Color(Color::from_rgb8(150, 150, 150))),
+                reason,
+                tooltip::Position::Bottom,
+            )
+            .style(iced::theme::Container::Box)
+            .into();
+        }
+
+        let mut content = column!(checkbox(
+            "Enable GPU",
+            config.gpus.is_some(),
+            Event::GpuToggled
+        ))
+        .spacing(5);
+        if let Some(selected) = config.gpus.as_ref().filter(|_| self.gpus.len() > 1) {
+            content = content.push(
+                text("GPUs, all of them when none are ticked")
+                    .size(12)
+                    .style(Text::Color(Color::from_rgb8(150, 150, 150))),
+            );
+            for gpu in self.gpus.iter() {
+                let gpu = gpu.clone();
+                content = content.push(checkbox(
+                    gpu.clone(),
+                    selected.contains(&gpu),
+                    move |ticked| Event::GpuDevice(gpu.clone(), ticked),
+                ));
+            }
+        }
+
+        content.into()
+    }
+}
+
+fn review_row<'a>(label: &str, value: &str, note: Option<&str>) -> Element<'a, Event, Renderer> {
+    let mut value = column!(text(value).size(12).font(Font::MONOSPACE));
+    if let Some(note) = note {
+        value = value.push(
+            text(note)
+                .size(12)
+                .style(Text::Color(Color::from_rgb8(90, 150, 220))),
+        );
+    }
+
+    row!(
+        text(label).width(Length::FillPortion(2)),
+        value.width(Length::FillPortion(3))
+    )
+    .align_items(iced::Alignment::Start)
+    .into()
+}
+
+impl<'a, Message> From<AddContainer<Message>> for Element<'a, Message, Renderer>
+where
+    Message: 'a,
+{
+    fn from(value: AddContainer<Message>) -> Self {
+        component(value)
+    }
+}
+
+/// How tall the notes panel is, whether they are shown or not, so toggling them doesn't move the
+/// rest of the form.
+const NOTES_HEIGHT: f32 = 160.0;
+
+/// The notes of `database` and of `tag`, scrolling within the panel when they are longer.
+fn notes_panel<'a>(
+    database: &DatabaseConfig,
+    tag: &str,
+    shown: bool,
+) -> Element<'a, Event, Renderer> {
+    let grey = Text::Color(Color::from_rgb8(150, 150, 150));
+    let header = row!(
+        text(format!("About {}:{tag}", database.image_name())).size(14),
+        horizontal_space(Length::Fill),
+        button(if shown { "Hide" } else { "Show" })
+            .style(iced::theme::Button::Secondary)
+            .padding([2, 8])
+            .on_press(Event::ToggleNotes),
+    )
+    .align_items(iced::Alignment::Center);
+
+    let notes = database.notes_for(tag);
+    let body: Element<'a, Event, Renderer> = if !shown {
+        vertical_space(Length::Fill).into()
+    } else if notes.is_empty() {
+        text("No notes for this tag").size(12).style(grey).into()
+    } else {
+        let lines = notes
+            .iter()
+            .enumerate()
+            .flat_map(|(index, notes)| {
+                (index > 0)
+                    .then_some(NoteLine::Gap)
+                    .into_iter()
+                    .chain(note_lines(notes))
+            })
+            .map(|line| match line {
+                NoteLine::Heading(heading) => text(heading)
+                    .size(14)
+                    .font(Font {
+                        weight: font::Weight::Bold,
+                        ..Font::DEFAULT
+                    })
+                    .into(),
+                NoteLine::Bullet(item) => text(format!("• {item}")).size(12).into(),
+                NoteLine::Text(line) => text(line).size(12).into(),
+                NoteLine::Gap => vertical_space(6).into(),
+            })
+            .collect();
+        scrollable(column(lines).spacing(3).padding([0, 12, 0, 0]))
+            .height(Length::Fill)
+            .into()
+    };
+
+    container(column!(header, body).spacing(5))
+        .width(Length::Fill)
+        .height(Length::Fixed(NOTES_HEIGHT))
+        .padding(10)
+        .style(Container::Box)
+        .into()
+}
+
+/// The variables as editable `KEY=VALUE` lines, each marked with why it can't be used. Pasting
+/// several lines into one splits them, since there is no multiline text input.
+fn env_text_editor<'a>(lines: &[String]) -> Element<'a, Event, Renderer> {
+    let errors = parse_env_lines(lines.iter().map(String::as_str))
+        .err()
+        .unwrap_or_default();
+
+    let mut editor = column!(text(
+        "One KEY=VALUE per line, blank lines and lines starting with # are ignored. Press enter \
+         for a new line, pasted lines are split."
+    )
+    .size(12)
+    .style(Text::Color(Color::from_rgb8(150, 150, 150))))
+    .spacing(5);
+    for (index, line) in lines.iter().enumerate() {
+        editor = editor.push(
+            row!(
+                text_input("KEY=VALUE", line)
+                    .on_input(move |line| Event::EnvLineChanged(index, line))
+                    .on_submit(Event::EnvLineAdded(index))
+                    .font(Font::MONOSPACE),
+                button("Remove")
+                    .style(iced::theme::Button::Secondary)
+                    .on_press(Event::EnvLineRemoved(index))
+            )
+            .align_items(iced::Alignment::Center)
+            .spacing(5),
+        );
+        for error in errors.iter().filter(|error| error.line == index) {
+            editor = editor.push(
+                text(&error.message)
+                    .size(12)
+                    .style(Text::Color(Color::from_rgb8(220, 60, 60))),
+            );
+        }
+    }
+
+    let mut back = button("Edit as fields").style(iced::theme::Button::Secondary);
+    if errors.is_empty() {
+        back = back.on_press(Event::ToggleEnvText);
+    }
+    editor
+        .push(
+            row!(
+                button("Add line")
+                    .style(iced::theme::Button::Secondary)
+                    .on_press(Event::EnvLineAdded(lines.len())),
+                back
+            )
+            .spacing(15),
+        )
+        .into()
+}
+
+fn extra_var_row<'a>(key: String, value: String) -> Element<'a, Event, Renderer> {
+    let remove = Event::RemoveVariable(key.clone());
+    row!(
+        text(&key)
+            .font(Font::MONOSPACE)
+            .width(Length::FillPortion(2)),
+        text_input(&key, &value)
+            .on_input(move |text| {
+                let key = key.clone();
+                Event::EnvVarChanged { key, value: text }
+            })
+            .width(Length::FillPortion(3)),
+        button("Remove")
+            .style(iced::theme::Button::Destructive)
+            .on_press(remove)
+    )
+    .align_items(iced::Alignment::Center)
+    .spacing(5)
+    .into()
+}
+
+fn env_var_row<'a>(
+    name: String,
+    key: String,
+    value: String,
+    recalled: bool,
+) -> Element<'a, Event, Renderer> {
+    let mut label = column!(
+        text(name),
+        text(&key)
+            .size(12)
+            .style(Text::Color(Color::from_rgb8(150, 150, 150)))
+    );
+
+    if recalled {
+        label = label.push(
+            text("recalled")
+                .size(12)
+                .style(Text::Color(Color::from_rgb8(90, 150, 220))),
+        );
+    }
+
+    row!(
+        label.width(Length::FillPortion(2)),
         text_input(&key, &value)
             .on_input(move |text| {
                 let key = key.clone();
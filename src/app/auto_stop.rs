@@ -0,0 +1,114 @@
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    time::{Duration, Instant},
+};
+
+/// How long before the deadline the user is asked whether to keep the container running.
+pub const WARNING: Duration = Duration::from_secs(5 * 60);
+
+/// How much longer a container keeps running when the user snoozes the warning.
+pub const SNOOZE: Duration = Duration::from_secs(30 * 60);
+
+/// A choice for how long a container may keep running.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StopAfter(pub u64);
+
+impl StopAfter {
+    pub const OPTIONS: [StopAfter; 5] = [
+        StopAfter(30),
+        StopAfter(60),
+        StopAfter(2 * 60),
+        StopAfter(4 * 60),
+        StopAfter(8 * 60),
+    ];
+
+    pub fn duration(&self) -> Duration {
+        Duration::from_secs(self.0 * 60)
+    }
+}
+
+impl Default for StopAfter {
+    fn default() -> Self {
+        StopAfter(2 * 60)
+    }
+}
+
+impl Display for StopAfter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            60 => write!(f, "1 hour"),
+            minutes if minutes % 60 == 0 => write!(f, "{} hours", minutes / 60),
+            minutes => write!(f, "{minutes} minutes"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AutoStopAction {
+    Warn,
+    Stop,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Deadline {
+    at: Instant,
+    warned: bool,
+}
+
+/// Deadlines after which containers are stopped, keyed by container id. Only kept for the
+/// session.
+#[derive(Debug, Default)]
+pub struct AutoStopTimers {
+    deadlines: HashMap<String, Deadline>,
+}
+
+impl AutoStopTimers {
+    pub fn set(&mut self, id: String, after: Duration, now: Instant) {
+        self.deadlines.insert(
+            id,
+            Deadline {
+                at: now + after,
+                warned: false,
+            },
+        );
+    }
+
+    pub fn cancel(&mut self, id: &str) {
+        self.deadlines.remove(id);
+    }
+
+    /// Pushes the deadline of `id` back to [`SNOOZE`] from now.
+    pub fn snooze(&mut self, id: &str, now: Instant) {
+        if let Some(deadline) = self.deadlines.get_mut(id) {
+            deadline.at = now + SNOOZE;
+            deadline.warned = false;
+        }
+    }
+
+    pub fn remaining(&self, id: &str, now: Instant) -> Option<Duration> {
+        Some(self.deadlines.get(id)?.at.saturating_duration_since(now))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.deadlines.is_empty()
+    }
+
+    /// What has to happen to each container at `now`. Containers that are due are forgotten and
+    /// each warning is only given once per deadline.
+    pub fn tick(&mut self, now: Instant) -> Vec<(String, AutoStopAction)> {
+        let mut actions = vec![];
+        for (id, deadline) in self.deadlines.iter_mut() {
+            if now >= deadline.at {
+                actions.push((id.clone(), AutoStopAction::Stop));
+            } else if !deadline.warned && deadline.at - now <= WARNING {
+                deadline.warned = true;
+                actions.push((id.clone(), AutoStopAction::Warn));
+            }
+        }
+
+        self.deadlines.retain(|_, deadline| now < deadline.at);
+
+        actions
+    }
+}
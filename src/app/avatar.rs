@@ -0,0 +1,121 @@
+use iced::{
+    alignment, mouse,
+    widget::{
+        canvas::{path::Builder, Canvas, Frame, Geometry, Path, Program, Text},
+        image::{self, Handle},
+    },
+    Color, Element, Point, Rectangle, Renderer, Theme,
+};
+
+use crate::image_ref::ImageRef;
+
+/// The letter the avatar of `image` shows, the first letter of its name without the registry and
+/// namespace, such as `P` for `ghcr.io/acme/postgres:16`. Names starting with digits show their
+/// first letter, or their first digit if they have none.
+pub fn avatar_letter(image: &str) -> char {
+    let repository = ImageRef::parse(image).repository;
+    let name = repository.rsplit('/').next().unwrap_or_default();
+    name.chars()
+        .find(|c| c.is_alphabetic())
+        .or_else(|| name.chars().find(|c| c.is_alphanumeric()))
+        .and_then(|c| c.to_uppercase().next())
+        .unwrap_or('?')
+}
+
+/// The background of the avatar of `image`, the same for every tag and registry of a repository.
+pub fn avatar_color(image: &str) -> Color {
+    let repository = ImageRef::parse(image).repository;
+    // FNV-1a, unlike the std hasher its result doesn't change between releases
+    let hash = repository
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        });
+    from_hsl((hash % 360) as f32, 0.55, 0.45)
+}
+
+/// The colour of `hue` in degrees, `saturation` and `lightness` between 0 and 1.
+fn from_hsl(hue: f32, saturation: f32, lightness: f32) -> Color {
+    let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+    let section = hue / 60.0;
+    let x = chroma * (1.0 - (section % 2.0 - 1.0).abs());
+    let (r, g, b) = match section as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let m = lightness - chroma / 2.0;
+    Color::from_rgb(r + m, g + m, b + m)
+}
+
+fn rounded_square(top_left: Point, size: f32, radius: f32) -> Path {
+    let corner = |x: f32, y: f32| Point::new(top_left.x + x, top_left.y + y);
+    let mut builder = Builder::new();
+    builder.move_to(corner(radius, 0.0));
+    builder.arc_to(corner(size, 0.0), corner(size, size), radius);
+    builder.arc_to(corner(size, size), corner(0.0, size), radius);
+    builder.arc_to(corner(0.0, size), corner(0.0, 0.0), radius);
+    builder.arc_to(corner(0.0, 0.0), corner(size, 0.0), radius);
+    builder.close();
+    builder.build()
+}
+
+struct Avatar {
+    letter: char,
+    color: Color,
+}
+
+impl<Message> Program<Message, Renderer> for Avatar {
+    type State = ();
+
+    fn draw(
+        &self,
+        _: &Self::State,
+        renderer: &Renderer,
+        _: &Theme,
+        bounds: Rectangle,
+        _: mouse::Cursor,
+    ) -> Vec<Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+        let size = bounds.width.min(bounds.height);
+        let top_left = Point::new((bounds.width - size) / 2.0, (bounds.height - size) / 2.0);
+        frame.fill(&rounded_square(top_left, size, size * 0.2), self.color);
+        frame.fill_text(Text {
+            content: self.letter.to_string(),
+            position: frame.center(),
+            color: Color::WHITE,
+            size: size * 0.6,
+            horizontal_alignment: alignment::Horizontal::Center,
+            vertical_alignment: alignment::Vertical::Center,
+            ..Default::default()
+        });
+        vec![frame.into_geometry()]
+    }
+}
+
+/// A square of `size` in the colour of `image` with the first letter of its name, drawn in place
+/// of its icon until one is fetched.
+pub fn avatar<'a, Message: 'a>(image: &str, size: f32) -> Element<'a, Message, Renderer> {
+    Canvas::new(Avatar {
+        letter: avatar_letter(image),
+        color: avatar_color(image),
+    })
+    .width(size)
+    .height(size)
+    .into()
+}
+
+/// The icon of `image` if one was fetched, its avatar otherwise.
+pub fn thumbnail_or_avatar<'a, Message: 'a>(
+    image: &str,
+    thumbnail: Option<&Handle>,
+    size: f32,
+) -> Element<'a, Message, Renderer> {
+    match thumbnail {
+        Some(handle) => image::Image::new(handle.clone()).height(size).into(),
+        None => avatar(image, size),
+    }
+}
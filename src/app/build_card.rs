@@ -0,0 +1,307 @@
+use std::{collections::BTreeSet, time::Instant};
+
+use iced::{
+    theme::{Button, Text},
+    widget::{button, column, component, horizontal_rule, progress_bar, row, text, Component},
+    Color, Element, Font, Length, Pixels, Renderer,
+};
+
+use super::{add_container::AddContainerState, pull_progress::PullProgress};
+use crate::docker::{DbContainerConfig, PlannedOp, StartFailure};
+
+/// How many lines of the image build output are kept.
+const BUILD_OUTPUT_LINES: usize = 500;
+
+#[derive(Clone, Debug)]
+pub enum BuildPhase {
+    Pulling(PullProgress),
+    /// Building a custom image, with the lines it printed so far.
+    BuildingImage(Vec<String>),
+    Creating,
+    Starting,
+    /// Started, waiting to see whether it stays up.
+    Checking,
+    Created,
+    Failed(String),
+    /// A stopped db-mgr container with the same name and image exists, with its id.
+    Conflict(String),
+    /// Exited with an error right after it was started.
+    Exited(StartFailure),
+    /// A dry run finished, with the docker calls creating the container would make.
+    Planned(Vec<PlannedOp>),
+}
+
+/// A container that is being created, kept on the app so its progress outlives the create view.
+#[derive(Clone, Debug)]
+pub struct BuildJob {
+    pub config: DbContainerConfig,
+    pub draft: AddContainerState,
+    pub phase: BuildPhase,
+}
+
+impl BuildJob {
+    pub fn new(config: DbContainerConfig, draft: AddContainerState) -> Self {
+        Self {
+            config,
+            draft,
+            phase: BuildPhase::Creating,
+        }
+    }
+
+    pub fn push_build_output(&mut self, line: String) {
+        if !matches!(self.phase, BuildPhase::BuildingImage(_)) {
+            self.phase = BuildPhase::BuildingImage(vec![]);
+        }
+        if let BuildPhase::BuildingImage(output) = &mut self.phase {
+            output.push(line);
+            if output.len() > BUILD_OUTPUT_LINES {
+                output.drain(..output.len() - BUILD_OUTPUT_LINES);
+            }
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        !matches!(
+            self.phase,
+            BuildPhase::Checking
+                | BuildPhase::Created
+                | BuildPhase::Failed(_)
+                | BuildPhase::Conflict(_)
+                | BuildPhase::Exited(_)
+                | BuildPhase::Planned(_)
+        )
+    }
+}
+
+#[derive(Clone)]
+pub enum Event {
+    Retry,
+    Dismiss,
+    Replace,
+    /// Shows or hides the request body of the planned call at this index.
+    ToggleOp(usize),
+}
+
+pub fn build_card<Message>(job: &BuildJob) -> BuildCard<Message> {
+    BuildCard::new(job.clone())
+}
+
+pub struct BuildCard<Message> {
+    job: BuildJob,
+    on_retry_click: Option<Box<dyn Fn() -> Message>>,
+    on_dismiss_click: Option<Box<dyn Fn() -> Message>>,
+    on_replace_click: Option<Box<dyn Fn() -> Message>>,
+}
+
+impl<Message> BuildCard<Message> {
+    pub fn new(job: BuildJob) -> Self {
+        Self {
+            job,
+            on_retry_click: None,
+            on_dismiss_click: None,
+            on_replace_click: None,
+        }
+    }
+
+    pub fn on_retry_click<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn() -> Message + 'static,
+    {
+        Self {
+            on_retry_click: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    pub fn on_dismiss_click<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn() -> Message + 'static,
+    {
+        Self {
+            on_dismiss_click: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    /// Shows the button replacing a conflicting container, the new one keeps its volumes.
+    pub fn on_replace_click<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn() -> Message + 'static,
+    {
+        Self {
+            on_replace_click: Some(Box::new(handler)),
+            ..self
+        }
+    }
+}
+
+/// The calls of a dry run, each opening to its request body, `expanded` holding the indices of
+/// the open ones.
+fn plan_list<'a>(
+    plan: &'a [PlannedOp],
+    expanded: &BTreeSet<usize>,
+) -> Element<'a, Event, Renderer> {
+    let grey = Text::Color(Color::from_rgb8(150, 150, 150));
+    let mut list = column(vec![]).spacing(5);
+    for (index, op) in plan.iter().enumerate() {
+        let open = expanded.contains(&index);
+        let body = op.body_text();
+        let mut call = button(
+            text(format!(
+                "{} {}",
+                match (&body, open) {
+                    (None, _) => " ",
+                    (Some(_), false) => "▸",
+                    (Some(_), true) => "▾",
+                },
+                op.call
+            ))
+            .size(12)
+            .font(Font::MONOSPACE),
+        )
+        .style(Button::Text)
+        .padding(0);
+        if body.is_some() {
+            call = call.on_press(Event::ToggleOp(index));
+        }
+        list = list.push(column!(call, text(&op.purpose).size(12).style(grey)).spacing(2));
+        if let Some(body) = body.filter(|_| open) {
+            list = list.push(text(body).size(12).font(Font::MONOSPACE));
+        }
+    }
+    list.into()
+}
+
+impl<Message> Component<Message, Renderer> for BuildCard<Message> {
+    type State = BTreeSet<usize>;
+
+    type Event = Event;
+
+    fn update(&mut self, expanded: &mut Self::State, event: Self::Event) -> Option<Message> {
+        match event {
+            Event::Retry => self.on_retry_click.as_ref().map(|fun| fun()),
+            Event::Dismiss => self.on_dismiss_click.as_ref().map(|fun| fun()),
+            Event::Replace => self.on_replace_click.as_ref().map(|fun| fun()),
+            Event::ToggleOp(index) => {
+                if !expanded.remove(&index) {
+                    expanded.insert(index);
+                }
+                None
+            }
+        }
+    }
+
+    fn view(&self, expanded: &Self::State) -> Element<'_, Self::Event, Renderer> {
+        let status: Element<'_, Self::Event, Renderer> = match &self.job.phase {
+            BuildPhase::Pulling(pull) => {
+                let progress = pull.fraction();
+                let mut status = format!("Pulling {:.0}%", progress * 100.0);
+                if let Some(summary) = pull.summary(Instant::now()) {
+                    status = format!("{status}, {summary}");
+                }
+
+                row!(
+                    text(status).size(12),
+                    progress_bar(0.0..=1.0, progress).height(8)
+                )
+                .align_items(iced::Alignment::Center)
+                .spacing(5)
+                .into()
+            }
+            BuildPhase::BuildingImage(output) => text(match output.last() {
+                Some(line) => format!("Building image: {line}"),
+                None => "Building image".to_string(),
+            })
+            .size(12)
+            .into(),
+            BuildPhase::Creating => text("Creating").size(12).into(),
+            BuildPhase::Starting => text("Starting").size(12).into(),
+            BuildPhase::Checking => text("Checking it started").size(12).into(),
+            BuildPhase::Created => text("Created").size(12).into(),
+            BuildPhase::Exited(failure) => text(format!("Exited with code {}", failure.exit_code))
+                .size(12)
+                .style(Text::Color(Color::from_rgb8(220, 60, 60)))
+                .into(),
+            BuildPhase::Failed(ex) => text(format!("Failed: {ex}"))
+                .size(12)
+                .style(Text::Color(Color::from_rgb8(220, 60, 60)))
+                .into(),
+            BuildPhase::Conflict(_) => {
+                text("A stopped container left over with this name is in the way")
+                    .size(12)
+                    .style(Text::Color(Color::from_rgb8(220, 160, 40)))
+                    .into()
+            }
+            BuildPhase::Planned(plan) => text(format!(
+                "Dry run, {} docker calls would be made, nothing was created",
+                plan.len()
+            ))
+            .size(12)
+            .into(),
+        };
+
+        let mut buttons = row(vec![])
+            .width(Length::FillPortion(2))
+            .height(Length::Fill)
+            .align_items(iced::Alignment::Center)
+            .spacing(5);
+
+        if let (BuildPhase::Conflict(_), Some(_)) = (&self.job.phase, &self.on_replace_click) {
+            buttons = buttons
+                .push(button("Replace existing container (volumes kept)").on_press(Event::Replace));
+        }
+        if let BuildPhase::Failed(_) | BuildPhase::Conflict(_) = self.job.phase {
+            buttons = buttons.push(button("Retry").on_press(Event::Retry)).push(
+                button("Dismiss")
+                    .style(Button::Secondary)
+                    .on_press(Event::Dismiss),
+            );
+        }
+        if let BuildPhase::Planned(_) = self.job.phase {
+            buttons = buttons
+                .push(button("Back to form").on_press(Event::Retry))
+                .push(
+                    button("Dismiss")
+                        .style(Button::Secondary)
+                        .on_press(Event::Dismiss),
+                );
+        }
+
+        let BuildPhase::Planned(plan) = &self.job.phase else {
+            return column!(
+                row!(
+                    column!(text(&self.job.config.name).size(20), status)
+                        .width(Length::FillPortion(4))
+                        .height(Length::Fill),
+                    buttons,
+                ),
+                horizontal_rule(2)
+            )
+            .width(Length::Fill)
+            .height(Pixels(50.0f32))
+            .into();
+        };
+
+        // The plan grows with the calls opened, so the card isn't held to the usual height
+        column!(
+            row!(
+                column!(text(&self.job.config.name).size(20), status).width(Length::FillPortion(4)),
+                buttons.height(Length::Shrink),
+            ),
+            plan_list(plan, expanded),
+            horizontal_rule(2)
+        )
+        .spacing(5)
+        .width(Length::Fill)
+        .into()
+    }
+}
+
+impl<'a, Message> From<BuildCard<Message>> for Element<'a, Message, Renderer>
+where
+    Message: 'a,
+{
+    fn from(value: BuildCard<Message>) -> Self {
+        component(value)
+    }
+}
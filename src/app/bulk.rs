@@ -0,0 +1,106 @@
+use std::fmt::Display;
+
+use bollard::Docker;
+use futures::future::join_all;
+
+use crate::docker::{remove_container, start_container, stop_container};
+
+/// An action run on every selected container at once.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BulkAction {
+    Start,
+    Stop,
+    Remove,
+}
+
+impl Display for BulkAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                BulkAction::Start => "start",
+                BulkAction::Stop => "stop",
+                BulkAction::Remove => "remove",
+            }
+        )
+    }
+}
+
+/// The outcome of a bulk action for one container.
+#[derive(Clone, Debug)]
+pub struct BulkResult {
+    pub id: String,
+    pub name: String,
+    pub error: Option<String>,
+}
+
+async fn run_one(
+    docker: &'static Docker,
+    action: BulkAction,
+    id: String,
+    name: String,
+) -> BulkResult {
+    let result = match action {
+        BulkAction::Start => start_container(id.clone(), docker).await,
+        BulkAction::Stop => stop_container(id.clone(), None, docker).await,
+        BulkAction::Remove => remove_container(id.clone(), docker).await,
+    };
+
+    BulkResult {
+        id,
+        name,
+        error: result.err().map(|ex| format!("{ex}")),
+    }
+}
+
+/// Runs `action` on all `containers`, given as id and name, concurrently. Containers are stopped
+/// one at a time in the given order instead, so dependents can be stopped first.
+pub async fn run_bulk(
+    docker: &'static Docker,
+    action: BulkAction,
+    containers: Vec<(String, String)>,
+) -> Vec<BulkResult> {
+    if action == BulkAction::Stop {
+        let mut results = vec![];
+        for (id, name) in containers {
+            results.push(run_one(docker, action, id, name).await);
+        }
+        return results;
+    }
+
+    join_all(
+        containers
+            .into_iter()
+            .map(|(id, name)| run_one(docker, action, id, name)),
+    )
+    .await
+}
+
+/// One message for all results, listing the containers the action failed for.
+pub fn summary(action: BulkAction, results: &[BulkResult]) -> String {
+    let failed = results
+        .iter()
+        .filter_map(|result| Some((&result.name, result.error.as_ref()?)))
+        .collect::<Vec<_>>();
+
+    let done = match action {
+        BulkAction::Start => "Started",
+        BulkAction::Stop => "Stopped",
+        BulkAction::Remove => "Removed",
+    };
+    let mut out = if failed.is_empty() {
+        format!("{done} {} containers", results.len())
+    } else {
+        format!(
+            "{done} {} of {} containers",
+            results.len() - failed.len(),
+            results.len()
+        )
+    };
+    for (name, error) in failed {
+        out.push_str(&format!("\n\nCould not {action} {name}: {error}"));
+    }
+
+    out
+}
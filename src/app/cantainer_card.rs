@@ -1,22 +1,31 @@
+use bollard::service::ContainerStateStatusEnum;
 use iced::{
-    theme::{Button, Text},
-    widget::{button, column, component, container, horizontal_rule, image, row, text, Component},
-    Color, Element, Length, Pixels, Renderer,
+    theme::{self, Button, Text},
+    widget::{
+        button, checkbox, column, component, container, horizontal_rule, image, row, text, tooltip,
+        Component, Space,
+    },
+    Color, Element, Length, Pixels, Renderer, Theme,
 };
-use iced_aw::{Icon, ICON_FONT};
+use iced_aw::{badge, BadgeStyles, Icon, ICON_FONT};
 
-use crate::docker::DbContainer;
+use super::{avatar::thumbnail_or_avatar, state_badge::state_badge_tip, stopping::StopState};
+use crate::{docker::DbContainer, exits::ExitNotes, idle::format_days, versions::EolStatus};
 
 #[derive(Clone)]
 pub enum Event {
     Start,
     Stop,
+    ForceStop,
     View,
+    Select(bool),
+    Update,
+    BackUp,
 }
 
 pub fn container_card<Message>(
     container: &DbContainer,
-    thumbnail: image::Handle,
+    thumbnail: Option<image::Handle>,
 ) -> ContainerCard<Message> {
     ContainerCard::new(container.clone(), thumbnail)
 }
@@ -25,18 +34,184 @@ pub struct ContainerCard<Message> {
     container: DbContainer,
     on_start_click: Option<Box<dyn Fn(String) -> Message>>,
     on_stop_click: Option<Box<dyn Fn(String) -> Message>>,
+    on_force_stop_click: Option<Box<dyn Fn(String) -> Message>>,
     on_view_click: Option<Box<dyn Fn(String) -> Message>>,
-    image: image::Handle,
+    on_select: Option<Box<dyn Fn(String, bool) -> Message>>,
+    on_update_click: Option<Box<dyn Fn(String) -> Message>>,
+    on_back_up_click: Option<Box<dyn Fn(String) -> Message>>,
+    /// The fetched icon of the image, an avatar is drawn without one.
+    image: Option<image::Handle>,
+    tile: bool,
+    legacy: Option<String>,
+    selectable: bool,
+    selected: bool,
+    idle_days: Option<u64>,
+    update_available: bool,
+    highlighted: bool,
+    indented: bool,
+    stop_state: StopState,
+    started: Option<String>,
+    eol: Option<EolStatus>,
+    exit: ExitNotes,
+}
+
+/// The color a card is marked with while its container just changed state.
+const HIGHLIGHT: Color = Color::from_rgb(0.35, 0.6, 0.85);
+
+/// The bar left of a highlighted card in the list.
+struct HighlightBar;
+
+impl container::StyleSheet for HighlightBar {
+    type Style = Theme;
+
+    fn appearance(&self, _: &Self::Style) -> container::Appearance {
+        container::Appearance {
+            background: Some(HIGHLIGHT.into()),
+            ..Default::default()
+        }
+    }
+}
+
+/// The box of a highlighted tile, outlined in the highlight color.
+struct HighlightedTile;
+
+impl container::StyleSheet for HighlightedTile {
+    type Style = Theme;
+
+    fn appearance(&self, style: &Self::Style) -> container::Appearance {
+        container::Appearance {
+            border_width: 2.0,
+            border_color: HIGHLIGHT,
+            ..style.appearance(&theme::Container::Box)
+        }
+    }
 }
 
 impl<Message> ContainerCard<Message> {
-    pub fn new(container: DbContainer, thumbnail: image::Handle) -> Self {
+    pub fn new(container: DbContainer, thumbnail: Option<image::Handle>) -> Self {
         Self {
             container,
             on_start_click: None,
             on_stop_click: None,
+            on_force_stop_click: None,
             on_view_click: None,
+            on_select: None,
+            on_update_click: None,
+            on_back_up_click: None,
             image: thumbnail,
+            tile: false,
+            legacy: None,
+            selectable: false,
+            selected: false,
+            idle_days: None,
+            update_available: false,
+            highlighted: false,
+            indented: false,
+            stop_state: StopState::Idle,
+            started: None,
+            eol: None,
+            exit: ExitNotes::default(),
+        }
+    }
+
+    /// Indents the card in the list, for companions shown under their parent.
+    pub fn indented(self, indented: bool) -> Self {
+        Self { indented, ..self }
+    }
+
+    /// How far stopping the container got, replaces the stop button while it is stopping.
+    pub fn stop_state(self, stop_state: StopState) -> Self {
+        Self { stop_state, ..self }
+    }
+
+    /// Who started the container and when, shown when hovering its state.
+    pub fn started(self, started: Option<String>) -> Self {
+        Self { started, ..self }
+    }
+
+    /// Marks the container as having just changed state.
+    pub fn highlighted(self, highlighted: bool) -> Self {
+        Self {
+            highlighted,
+            ..self
+        }
+    }
+
+    /// Marks the container as created by an older version, `explanation` is shown on hover.
+    pub fn legacy(self, explanation: Option<String>) -> Self {
+        Self {
+            legacy: explanation,
+            ..self
+        }
+    }
+
+    /// Marks the container as running untouched for `idle_days`.
+    pub fn idle(self, idle_days: Option<u64>) -> Self {
+        Self { idle_days, ..self }
+    }
+
+    /// Marks the image tag as moved on in the registry since the container was created.
+    pub fn update_available(self, update_available: bool) -> Self {
+        Self {
+            update_available,
+            ..self
+        }
+    }
+
+    /// Marks the major version of the image as past or near its end of life.
+    pub fn eol(self, eol: Option<EolStatus>) -> Self {
+        Self { eol, ..self }
+    }
+
+    /// Marks the container as killed for running out of memory or restarted by its policy.
+    pub fn exit(self, exit: ExitNotes) -> Self {
+        Self { exit, ..self }
+    }
+
+    /// Renders the container as a larger tile for the grid layout.
+    pub fn tile(self, tile: bool) -> Self {
+        Self { tile, ..self }
+    }
+
+    /// Shows a checkbox for selecting the container for bulk actions.
+    pub fn selectable(self, selectable: bool) -> Self {
+        Self { selectable, ..self }
+    }
+
+    pub fn selected(self, selected: bool) -> Self {
+        Self { selected, ..self }
+    }
+
+    pub fn on_select<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn(String, bool) -> Message + 'static,
+    {
+        Self {
+            on_select: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    /// Makes the update badge a button that recreates the container from the newest image.
+    pub fn on_update_click<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn(String) -> Message + 'static,
+    {
+        Self {
+            on_update_click: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    /// Makes the end of life badge a button that opens the backups of the container, the first
+    /// step of moving its data to a newer major version.
+    pub fn on_back_up_click<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn(String) -> Message + 'static,
+    {
+        Self {
+            on_back_up_click: Some(Box::new(handler)),
+            ..self
         }
     }
 
@@ -60,6 +235,17 @@ impl<Message> ContainerCard<Message> {
         }
     }
 
+    /// Called when the stop button is clicked again while the container is stopping.
+    pub fn on_force_stop_click<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn(String) -> Message + 'static,
+    {
+        Self {
+            on_force_stop_click: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
     pub fn on_view_click<Callback>(self, handler: Callback) -> Self
     where
         Callback: Fn(String) -> Message + 'static,
@@ -86,51 +272,79 @@ impl<Message> Component<Message, Renderer> for ContainerCard<Message> {
                 .on_stop_click
                 .as_ref()
                 .map(|fun| fun(self.container.id.clone())),
+            Event::ForceStop => self
+                .on_force_stop_click
+                .as_ref()
+                .map(|fun| fun(self.container.id.clone())),
+            Event::Select(selected) => self
+                .on_select
+                .as_ref()
+                .map(|fun| fun(self.container.id.clone(), selected)),
             Event::View => self
                 .on_view_click
                 .as_ref()
                 .map(|fun| fun(self.container.id.clone())),
+            Event::Update => self
+                .on_update_click
+                .as_ref()
+                .map(|fun| fun(self.container.id.clone())),
+            Event::BackUp => self
+                .on_back_up_click
+                .as_ref()
+                .map(|fun| fun(self.container.id.clone())),
         }
     }
 
     fn view(&self, _: &Self::State) -> Element<'_, Self::Event, Renderer> {
+        if self.tile {
+            return self.tile_view();
+        }
+
         let mut buttons = row(vec![])
             .width(Length::FillPortion(2))
             .height(Length::Fill)
             .align_items(iced::Alignment::Center)
             .spacing(5);
 
-        match self.container.state {
-            bollard::service::ContainerStateStatusEnum::CREATED
-            | bollard::service::ContainerStateStatusEnum::PAUSED
-            | bollard::service::ContainerStateStatusEnum::EXITED => {
-                buttons = buttons.push(
-                    button(text(Icon::PlayFill).font(ICON_FONT))
-                        .style(Button::Positive)
-                        .on_press(Event::Start),
-                )
-            }
-            bollard::service::ContainerStateStatusEnum::RUNNING => {
-                buttons = buttons.push(
-                    button(text(Icon::StopFill).font(ICON_FONT))
-                        .style(Button::Destructive)
-                        .on_press(Event::Stop),
-                );
-            }
-            _ => {}
-        };
+        if let Some(action) = self.primary_action() {
+            buttons = buttons.push(action);
+        }
 
         buttons = buttons.push(button("View").on_press(Event::View));
 
         column!(
             row!(
-                container(image::Image::new(self.image.clone()).height(30))
-                    .width(Length::FillPortion(1))
-                    .height(Length::Fill)
-                    .align_y(iced::alignment::Vertical::Center)
-                    .align_x(iced::alignment::Horizontal::Center),
+                self.highlight_bar(),
+                Space::with_width(if self.indented { 30 } else { 0 }),
+                self.select_box(),
+                container(thumbnail_or_avatar(
+                    &self.container.image,
+                    self.image.as_ref(),
+                    30.0
+                ))
+                .width(Length::FillPortion(1))
+                .height(Length::Fill)
+                .align_y(iced::alignment::Vertical::Center)
+                .align_x(iced::alignment::Horizontal::Center),
                 column!(
-                    text(&self.container.name).size(20),
+                    row!(
+                        text(&self.container.name).size(20),
+                        state_badge_tip(
+                            self.container.state,
+                            self.container.health,
+                            self.started.as_deref()
+                        ),
+                        self.stopping_badge(),
+                        self.legacy_badge(),
+                        self.partial_icon(),
+                        self.idle_badge(),
+                        self.ephemeral_badge(),
+                        self.update_badge(),
+                        self.eol_badge(),
+                        self.exit_badge()
+                    )
+                    .align_items(iced::Alignment::Center)
+                    .spacing(5),
                     text(&self.container.image).style(Text::Color(Color::from_rgb8(150, 150, 150)))
                 )
                 .width(Length::FillPortion(3))
@@ -145,6 +359,285 @@ impl<Message> Component<Message, Renderer> for ContainerCard<Message> {
     }
 }
 
+impl<Message> ContainerCard<Message> {
+    /// The start or stop button, depending on the state of the container.
+    fn primary_action(&self) -> Option<Element<'_, Event, Renderer>> {
+        match self.container.state {
+            ContainerStateStatusEnum::CREATED
+            | ContainerStateStatusEnum::PAUSED
+            | ContainerStateStatusEnum::EXITED
+                if self.on_start_click.is_some() =>
+            {
+                Some(
+                    button(text(Icon::PlayFill).font(ICON_FONT))
+                        .style(Button::Positive)
+                        .on_press(Event::Start)
+                        .into(),
+                )
+            }
+            ContainerStateStatusEnum::RUNNING if self.on_stop_click.is_some() => {
+                Some(self.stop_action())
+            }
+            _ => None,
+        }
+    }
+
+    /// The stop button, which offers to force the stop while the container is stopping.
+    fn stop_action(&self) -> Element<'_, Event, Renderer> {
+        let force = self
+            .on_force_stop_click
+            .is_some()
+            .then_some(Event::ForceStop);
+        match self.stop_state {
+            StopState::Idle => button(text(Icon::StopFill).font(ICON_FONT))
+                .style(Button::Destructive)
+                .on_press(Event::Stop)
+                .into(),
+            StopState::Stopping(_) => button(text("Stopping… (force)").size(14))
+                .style(Button::Secondary)
+                .on_press_maybe(force)
+                .into(),
+            StopState::ForceOffered(_) => button(text("Force stop").size(14))
+                .style(Button::Destructive)
+                .on_press_maybe(force)
+                .into(),
+            StopState::Killing => button(text("Killing…").size(14))
+                .style(Button::Secondary)
+                .into(),
+        }
+    }
+
+    fn stopping_badge(&self) -> Element<'_, Event, Renderer> {
+        match self.stop_state {
+            StopState::Stopping(seconds) => {
+                badge(text(format!("stopping for {seconds}s")).size(12))
+                    .style(BadgeStyles::Info)
+                    .into()
+            }
+            StopState::ForceOffered(seconds) => tooltip(
+                badge(text(format!("stopping for {seconds}s")).size(12))
+                    .style(BadgeStyles::Warning),
+                "Still running after its stop timeout, it can be force stopped",
+                tooltip::Position::Bottom,
+            )
+            .style(theme::Container::Box)
+            .into(),
+            StopState::Idle | StopState::Killing => row(vec![]).into(),
+        }
+    }
+
+    fn legacy_badge(&self) -> Element<'_, Event, Renderer> {
+        match self.legacy.as_ref() {
+            Some(explanation) => tooltip(
+                badge(text("legacy").size(12)).style(BadgeStyles::Warning),
+                explanation,
+                tooltip::Position::Bottom,
+            )
+            .style(theme::Container::Box)
+            .into(),
+            None => row(vec![]).into(),
+        }
+    }
+
+    fn highlight_bar(&self) -> Element<'_, Event, Renderer> {
+        if !self.highlighted {
+            return row(vec![]).into();
+        }
+
+        container(Space::with_width(4))
+            .height(Length::Fill)
+            .style(theme::Container::Custom(Box::new(HighlightBar)))
+            .into()
+    }
+
+    fn select_box(&self) -> Element<'_, Event, Renderer> {
+        if !self.selectable {
+            return row(vec![]).into();
+        }
+
+        container(checkbox("", self.selected, Event::Select))
+            .height(Length::Fill)
+            .center_y()
+            .into()
+    }
+
+    fn partial_icon(&self) -> Element<'_, Event, Renderer> {
+        if !self.container.partial {
+            return row(vec![]).into();
+        }
+
+        tooltip(
+            text(Icon::ExclamationTriangleFill)
+                .font(ICON_FONT)
+                .style(Text::Color(Color::from_rgb8(230, 160, 40))),
+            "Docker returned incomplete data for this container",
+            tooltip::Position::Bottom,
+        )
+        .style(theme::Container::Box)
+        .into()
+    }
+
+    fn idle_badge(&self) -> Element<'_, Event, Renderer> {
+        let Some(days) = self.idle_days else {
+            return row(vec![]).into();
+        };
+
+        tooltip(
+            badge("idle").style(BadgeStyles::Warning),
+            format!("Running untouched for {}", format_days(days)),
+            tooltip::Position::Bottom,
+        )
+        .style(theme::Container::Box)
+        .into()
+    }
+
+    fn ephemeral_badge(&self) -> Element<'_, Event, Renderer> {
+        if !self.container.ephemeral() {
+            return row(vec![]).into();
+        }
+
+        tooltip(
+            badge(text("ephemeral").size(12)).style(BadgeStyles::Danger),
+            "Created without volumes, its data is lost when it is removed",
+            tooltip::Position::Bottom,
+        )
+        .style(theme::Container::Box)
+        .into()
+    }
+
+    fn update_badge(&self) -> Element<'_, Event, Renderer> {
+        if !self.update_available {
+            return row(vec![]).into();
+        }
+
+        let hint = format!(
+            "A newer {} was pushed to the registry",
+            self.container.image
+        );
+        let badge: Element<'_, Event, Renderer> = if self.on_update_click.is_some() {
+            button(text("update available").size(12))
+                .style(Button::Positive)
+                .padding([2, 6])
+                .on_press(Event::Update)
+                .into()
+        } else {
+            badge(text("update available").size(12))
+                .style(BadgeStyles::Info)
+                .into()
+        };
+
+        tooltip(badge, hint, tooltip::Position::Bottom)
+            .style(theme::Container::Box)
+            .into()
+    }
+
+    fn eol_badge(&self) -> Element<'_, Event, Renderer> {
+        let Some(eol) = self.eol else {
+            return row(vec![]).into();
+        };
+
+        let badge = badge(text(eol.describe()).size(12)).style(if eol.is_past() {
+            BadgeStyles::Danger
+        } else {
+            BadgeStyles::Warning
+        });
+        let badge: Element<'_, Event, Renderer> = if self.on_back_up_click.is_some() {
+            button(badge)
+                .style(Button::Text)
+                .padding(0)
+                .on_press(Event::BackUp)
+                .into()
+        } else {
+            badge.into()
+        };
+
+        tooltip(
+            badge,
+            "This major version gets no more fixes, back up the data and restore it into a container of a newer one",
+            tooltip::Position::Bottom,
+        )
+        .style(theme::Container::Box)
+        .into()
+    }
+
+    fn exit_badge(&self) -> Element<'_, Event, Renderer> {
+        let (label, style, hint) = if self.exit.out_of_memory {
+            (
+                "killed: out of memory".to_string(),
+                BadgeStyles::Danger,
+                "It used more memory than its limit or the host had, see its details",
+            )
+        } else if self.exit.policy_restarts > 0 {
+            (
+                format!("restarted {}×", self.exit.policy_restarts),
+                BadgeStyles::Warning,
+                "Its restart policy brought it back after it exited, check its logs for why",
+            )
+        } else {
+            return row(vec![]).into();
+        };
+
+        tooltip(
+            badge(text(label).size(12)).style(style),
+            hint,
+            tooltip::Position::Bottom,
+        )
+        .style(theme::Container::Box)
+        .into()
+    }
+
+    fn tile_view(&self) -> Element<'_, Event, Renderer> {
+        let mut buttons = row(vec![]).align_items(iced::Alignment::Center).spacing(5);
+        if let Some(action) = self.primary_action() {
+            buttons = buttons.push(action);
+        }
+        buttons = buttons.push(button("View").on_press(Event::View));
+
+        container(
+            column!(
+                thumbnail_or_avatar(&self.container.image, self.image.as_ref(), 80.0),
+                text(
+                    self.container
+                        .name
+                        .strip_prefix('/')
+                        .unwrap_or(&self.container.name)
+                )
+                .size(20),
+                text(&self.container.image)
+                    .size(12)
+                    .style(Text::Color(Color::from_rgb8(150, 150, 150))),
+                row!(
+                    self.select_box(),
+                    state_badge_tip(
+                        self.container.state,
+                        self.container.health,
+                        self.started.as_deref()
+                    ),
+                    self.legacy_badge(),
+                    self.partial_icon(),
+                    self.idle_badge(),
+                    self.ephemeral_badge(),
+                    self.update_badge(),
+                    self.eol_badge(),
+                    self.exit_badge()
+                )
+                .spacing(5),
+                buttons
+            )
+            .align_items(iced::Alignment::Center)
+            .spacing(5),
+        )
+        .width(Pixels(200.0f32))
+        .padding(10)
+        .style(if self.highlighted {
+            theme::Container::Custom(Box::new(HighlightedTile))
+        } else {
+            theme::Container::Box
+        })
+        .into()
+    }
+}
+
 impl<'a, Message> From<ContainerCard<Message>> for Element<'a, Message, Renderer>
 where
     Message: 'a,
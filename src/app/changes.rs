@@ -0,0 +1,153 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use bollard::service::ContainerStateStatusEnum;
+use itertools::Itertools;
+
+use crate::docker::DbContainer;
+
+/// How long the card of a container whose state changed stays highlighted.
+pub const HIGHLIGHT_DURATION: Duration = Duration::from_secs(5);
+
+/// How long a toast is shown unless it is dismissed.
+pub const TOAST_DURATION: Duration = Duration::from_secs(8);
+
+/// How long after the user acted on a container its changes are not announced, long enough for
+/// the action to finish and the following refresh to see it.
+const EXPECTED_FOR: Duration = Duration::from_secs(60);
+
+/// A container that was in the last refresh and is in this one in another state.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StateChange {
+    pub id: String,
+    pub name: String,
+    pub from: ContainerStateStatusEnum,
+    pub to: ContainerStateStatusEnum,
+}
+
+/// How the container list changed between two refreshes, names are without the leading `/`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Changeset {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub state_changed: Vec<StateChange>,
+}
+
+fn display_name(container: &DbContainer) -> String {
+    container.name.trim_start_matches('/').to_string()
+}
+
+/// Compares two refreshes of the container list by id, each part of the changeset is sorted by
+/// name.
+pub fn diff_containers(old: &[DbContainer], new: &[DbContainer]) -> Changeset {
+    let old_by_id = old
+        .iter()
+        .map(|container| (container.id.as_str(), container))
+        .collect::<HashMap<_, _>>();
+    let new_by_id = new
+        .iter()
+        .map(|container| (container.id.as_str(), container))
+        .collect::<HashMap<_, _>>();
+
+    Changeset {
+        added: new
+            .iter()
+            .filter(|container| !old_by_id.contains_key(container.id.as_str()))
+            .map(display_name)
+            .sorted()
+            .collect(),
+        removed: old
+            .iter()
+            .filter(|container| !new_by_id.contains_key(container.id.as_str()))
+            .map(display_name)
+            .sorted()
+            .collect(),
+        state_changed: new
+            .iter()
+            .filter_map(|container| {
+                let previous = old_by_id.get(container.id.as_str())?;
+                (previous.state != container.state).then(|| StateChange {
+                    id: container.id.clone(),
+                    name: display_name(container),
+                    from: previous.state,
+                    to: container.state,
+                })
+            })
+            .sorted_by(|a, b| a.name.cmp(&b.name))
+            .collect(),
+    }
+}
+
+impl Changeset {
+    /// The toasts announcing containers created or removed outside the app, leaving out the ones
+    /// `expected` says the user acted on.
+    pub fn announcements(&self, expected: impl Fn(&str) -> bool) -> Vec<String> {
+        let added = self
+            .added
+            .iter()
+            .filter(|name| !expected(name))
+            .map(|name| format!("{name} was created outside the app"));
+        let removed = self
+            .removed
+            .iter()
+            .filter(|name| !expected(name))
+            .map(|name| format!("{name} was removed outside the app"));
+
+        added.chain(removed).collect()
+    }
+}
+
+/// The containers the user just started, stopped, created or removed, keyed by name without the
+/// leading `/`. Their changes are the user's own so refreshes don't announce them.
+#[derive(Clone, Debug, Default)]
+pub struct ExpectedChanges {
+    names: HashMap<String, Instant>,
+}
+
+impl ExpectedChanges {
+    pub fn expect(&mut self, name: &str, now: Instant) {
+        self.names
+            .insert(name.trim_start_matches('/').to_string(), now);
+    }
+
+    pub fn contains(&self, name: &str, now: Instant) -> bool {
+        self.names
+            .get(name)
+            .is_some_and(|since| now.duration_since(*since) < EXPECTED_FOR)
+    }
+
+    /// Forgets the actions too old to still be waited on.
+    pub fn prune(&mut self, now: Instant) {
+        self.names
+            .retain(|_, since| now.duration_since(*since) < EXPECTED_FOR);
+    }
+}
+
+/// A short notice shown above the status bar until it expires or is dismissed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Toast {
+    pub message: String,
+    pub until: Instant,
+    /// The id of the retry of the failed operation the toast is about, see `Retries`.
+    pub retry: Option<u64>,
+}
+
+impl Toast {
+    pub fn new(message: String, now: Instant) -> Self {
+        Self {
+            message,
+            until: now + TOAST_DURATION,
+            retry: None,
+        }
+    }
+
+    /// A toast about a failed operation with a "Retry" button.
+    pub fn with_retry(message: String, retry: u64, now: Instant) -> Self {
+        Self {
+            retry: Some(retry),
+            ..Self::new(message, now)
+        }
+    }
+}
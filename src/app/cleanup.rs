@@ -0,0 +1,262 @@
+use iced::{
+    theme::{Button, Text},
+    widget::{button, checkbox, column, component, row, scrollable, text, text_input, Component},
+    Color, Element, Renderer,
+};
+use iced_aw::{badge, BadgeStyles};
+
+use crate::docker::{total_size, CleanupItem, CleanupSummary};
+
+const CONFIRMATION: &str = "DELETE";
+
+#[derive(Clone)]
+pub enum Event {
+    Containers(bool),
+    Volumes(bool),
+    Images(bool),
+    ConfirmChanged(String),
+    Run,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CleanupResult {
+    Removed(String),
+    Failed(String),
+}
+
+pub fn cleanup_view<Message>(
+    summary: Option<CleanupSummary>,
+    results: Vec<CleanupResult>,
+    running: bool,
+) -> CleanupView<Message> {
+    CleanupView::new(summary, results, running)
+}
+
+pub struct CleanupView<Message> {
+    summary: Option<CleanupSummary>,
+    results: Vec<CleanupResult>,
+    running: bool,
+    on_run: Option<Box<dyn Fn(CleanupSummary) -> Message>>,
+}
+
+pub struct CleanupState {
+    containers: bool,
+    volumes: bool,
+    images: bool,
+    confirm: String,
+}
+
+impl Default for CleanupState {
+    fn default() -> Self {
+        Self {
+            containers: true,
+            volumes: true,
+            images: false,
+            confirm: String::new(),
+        }
+    }
+}
+
+impl<Message> CleanupView<Message> {
+    pub fn new(
+        summary: Option<CleanupSummary>,
+        results: Vec<CleanupResult>,
+        running: bool,
+    ) -> Self {
+        Self {
+            summary,
+            results,
+            running,
+            on_run: None,
+        }
+    }
+
+    pub fn on_run<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn(CleanupSummary) -> Message + 'static,
+    {
+        Self {
+            on_run: Some(Box::new(handler)),
+            ..self
+        }
+    }
+}
+
+/// Parses a size such as `512m`, `2g` or `1024` (bytes) into bytes, units are powers of 1024.
+pub fn parse_size(size: &str) -> Option<u64> {
+    let size = size.trim().to_lowercase();
+    let size = size.strip_suffix('b').unwrap_or(&size);
+    let (number, multiplier) = match size.char_indices().last()? {
+        (i, 'k') => (&size[..i], 1 << 10),
+        (i, 'm') => (&size[..i], 1 << 20),
+        (i, 'g') => (&size[..i], 1 << 30),
+        (i, 't') => (&size[..i], 1 << 40),
+        _ => (size, 1),
+    };
+
+    number.trim().parse::<u64>().ok()?.checked_mul(multiplier)
+}
+
+pub fn format_size(bytes: i64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{bytes} {}", UNITS[unit])
+    } else {
+        format!("{size:.1} {}", UNITS[unit])
+    }
+}
+
+fn category<'a>(
+    label: &str,
+    items: &'a [CleanupItem],
+    checked: bool,
+    on_toggle: impl Fn(bool) -> Event + 'a,
+) -> Element<'a, Event, Renderer> {
+    let mut content = column!(checkbox(
+        format!(
+            "{label} ({}, {})",
+            items.len(),
+            format_size(total_size(items))
+        ),
+        checked,
+        on_toggle,
+    ))
+    .spacing(5);
+
+    if checked {
+        for item in items {
+            content = content.push(
+                text(match item.size {
+                    Some(size) => format!("Would remove {} ({})", item.name, format_size(size)),
+                    None => format!("Would remove {}", item.name),
+                })
+                .size(12)
+                .style(Text::Color(Color::from_rgb8(150, 150, 150))),
+            );
+        }
+    }
+
+    content.into()
+}
+
+impl<Message> Component<Message, Renderer> for CleanupView<Message> {
+    type State = CleanupState;
+
+    type Event = Event;
+
+    fn update(&mut self, state: &mut Self::State, event: Self::Event) -> Option<Message> {
+        match event {
+            Event::Containers(checked) => state.containers = checked,
+            Event::Volumes(checked) => state.volumes = checked,
+            Event::Images(checked) => state.images = checked,
+            Event::ConfirmChanged(confirm) => state.confirm = confirm,
+            Event::Run => {
+                if state.confirm != CONFIRMATION {
+                    return None;
+                }
+                state.confirm.clear();
+
+                let summary = self.summary.as_ref()?;
+                let selected = CleanupSummary {
+                    containers: if state.containers {
+                        summary.containers.clone()
+                    } else {
+                        vec![]
+                    },
+                    volumes: if state.volumes {
+                        summary.volumes.clone()
+                    } else {
+                        vec![]
+                    },
+                    images: if state.images {
+                        summary.images.clone()
+                    } else {
+                        vec![]
+                    },
+                };
+
+                return self.on_run.as_ref().map(|fun| fun(selected));
+            }
+        }
+
+        None
+    }
+
+    fn view(&self, state: &Self::State) -> Element<'_, Self::Event, Renderer> {
+        let mut content = column!(text("Clean up").size(22)).spacing(15).padding(15);
+
+        match self.summary.as_ref() {
+            None => content = content.push(badge("Loading").style(BadgeStyles::Info)),
+            Some(summary) if summary.is_empty() && self.results.is_empty() => {
+                content = content.push(text("There is nothing to clean up"));
+            }
+            Some(summary) => {
+                content = content
+                    .push(category(
+                        "Containers",
+                        &summary.containers,
+                        state.containers,
+                        Event::Containers,
+                    ))
+                    .push(category(
+                        "Volumes",
+                        &summary.volumes,
+                        state.volumes,
+                        Event::Volumes,
+                    ))
+                    .push(category(
+                        "Images only used by db-mgr containers",
+                        &summary.images,
+                        state.images,
+                        Event::Images,
+                    ));
+
+                if self.running {
+                    content = content.push(badge("Removing").style(BadgeStyles::Danger));
+                } else {
+                    let mut remove = button("Remove").style(Button::Destructive);
+                    if state.confirm == CONFIRMATION {
+                        remove = remove.on_press(Event::Run);
+                    }
+
+                    content = content.push(
+                        row!(
+                            text_input(&format!("Type {CONFIRMATION} to confirm"), &state.confirm)
+                                .on_input(Event::ConfirmChanged),
+                            remove
+                        )
+                        .spacing(15),
+                    );
+                }
+            }
+        }
+
+        for result in self.results.iter() {
+            content = content.push(match result {
+                CleanupResult::Removed(message) => text(message).size(12),
+                CleanupResult::Failed(message) => text(message)
+                    .size(12)
+                    .style(Text::Color(Color::from_rgb8(220, 60, 60))),
+            });
+        }
+
+        scrollable(content).into()
+    }
+}
+
+impl<'a, Message> From<CleanupView<Message>> for Element<'a, Message, Renderer>
+where
+    Message: 'a,
+{
+    fn from(value: CleanupView<Message>) -> Self {
+        component(value)
+    }
+}
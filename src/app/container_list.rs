@@ -0,0 +1,170 @@
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{Hash, Hasher},
+};
+
+use iced::{
+    widget::{image::Handle, lazy},
+    Element, Renderer,
+};
+
+use super::{cantainer_card::container_card, stopping::StopState};
+use crate::{docker::DbContainer, exits::ExitNotes, versions::EolStatus};
+
+/// How many cards are shown at first and added by each "Show more".
+pub const PAGE_SIZE: usize = 50;
+
+/// How many cards of `total` are shown with `limit`, and how many the next page adds.
+pub fn page(total: usize, limit: usize) -> (usize, usize) {
+    let shown = total.min(limit);
+    (shown, (total - shown).min(PAGE_SIZE))
+}
+
+/// How a card is drawn besides its container, a card is only rebuilt when this or the parts of
+/// the container it shows change.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct CardLook {
+    pub tile: bool,
+    pub legacy: Option<String>,
+    pub selectable: bool,
+    pub selected: bool,
+    pub idle_days: Option<u64>,
+    pub update_available: bool,
+    /// The container just changed state.
+    pub highlighted: bool,
+    /// The container is the admin UI of the one above it.
+    pub indented: bool,
+    pub stop_state: StopState,
+    /// Who started the container and when, shown on its state badge.
+    pub started: Option<String>,
+    /// How close the major version of its image is to its end of life, unless silenced.
+    pub eol: Option<EolStatus>,
+    /// Whether it was killed for running out of memory or keeps being restarted.
+    pub exit: ExitNotes,
+}
+
+/// The messages the cards send, the buttons of `None` actions are hidden.
+pub struct CardActions<Message> {
+    pub on_view: fn(String) -> Message,
+    pub on_select: fn(String, bool) -> Message,
+    pub on_start: Option<fn(String) -> Message>,
+    pub on_stop: Option<fn(String) -> Message>,
+    pub on_force_stop: Option<fn(String) -> Message>,
+    pub on_update: Option<fn(String) -> Message>,
+    pub on_back_up: Option<fn(String) -> Message>,
+}
+
+impl<Message> Clone for CardActions<Message> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<Message> Copy for CardActions<Message> {}
+
+/// Orders `containers` so each companion follows its parent, companions whose parent isn't
+/// listed keep their place.
+pub fn with_companions<'a>(
+    containers: impl IntoIterator<Item = &'a DbContainer>,
+) -> Vec<&'a DbContainer> {
+    let containers = containers.into_iter().collect::<Vec<_>>();
+    let listed = |name: &str| {
+        containers
+            .iter()
+            .any(|container| container.name.trim_start_matches('/') == name)
+    };
+    let (companions, others): (Vec<&DbContainer>, Vec<&DbContainer>) = containers
+        .iter()
+        .copied()
+        .partition(|container| container.companion_of().is_some_and(listed));
+
+    others
+        .into_iter()
+        .flat_map(|parent| {
+            let name = parent.name.trim_start_matches('/');
+            std::iter::once(parent).chain(
+                companions
+                    .iter()
+                    .filter(move |companion| companion.companion_of() == Some(name))
+                    .copied(),
+            )
+        })
+        .collect()
+}
+
+/// A hash of the parts of `container` its card shows.
+fn container_key(container: &DbContainer) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    container.id.hash(&mut hasher);
+    container.name.hash(&mut hasher);
+    container.image.hash(&mut hasher);
+    container.state.to_string().hash(&mut hasher);
+    container
+        .health
+        .map(|health| health.to_string())
+        .hash(&mut hasher);
+    container.partial.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// The cards of the first `limit` containers. Each thumbnail is looked up once per image and
+/// cards are kept between frames until what they show changes.
+pub fn container_cards<'a, Message: 'static>(
+    containers: impl IntoIterator<Item = &'a DbContainer>,
+    limit: usize,
+    thumbnail: impl Fn(&str) -> Option<Handle>,
+    look: impl Fn(&DbContainer) -> CardLook,
+    actions: CardActions<Message>,
+) -> Vec<Element<'a, Message, Renderer>> {
+    let mut thumbnails = HashMap::new();
+    containers
+        .into_iter()
+        .take(limit)
+        .map(|container| {
+            let thumbnail = thumbnails
+                .entry(container.image.as_str())
+                .or_insert_with(|| thumbnail(&container.image))
+                .clone();
+            let dependency = (
+                container_key(container),
+                thumbnail.as_ref().map(Handle::id),
+                look(container),
+            );
+
+            lazy(dependency, move |(_, _, look)| {
+                let mut card = container_card(container, thumbnail.clone())
+                    .tile(look.tile)
+                    .legacy(look.legacy.clone())
+                    .selectable(look.selectable)
+                    .selected(look.selected)
+                    .idle(look.idle_days)
+                    .update_available(look.update_available)
+                    .highlighted(look.highlighted)
+                    .indented(look.indented)
+                    .stop_state(look.stop_state)
+                    .started(look.started.clone())
+                    .eol(look.eol)
+                    .exit(look.exit)
+                    .on_view_click(actions.on_view)
+                    .on_select(actions.on_select);
+                if let Some(on_start) = actions.on_start {
+                    card = card.on_start_click(on_start);
+                }
+                if let Some(on_stop) = actions.on_stop {
+                    card = card.on_stop_click(on_stop);
+                }
+                if let Some(on_force_stop) = actions.on_force_stop {
+                    card = card.on_force_stop_click(on_force_stop);
+                }
+                if let Some(on_update) = actions.on_update {
+                    card = card.on_update_click(on_update);
+                }
+                if let Some(on_back_up) = actions.on_back_up {
+                    card = card.on_back_up_click(on_back_up);
+                }
+                card
+            })
+            .into()
+        })
+        .collect()
+}
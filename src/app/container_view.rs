@@ -1,62 +1,2291 @@
-use crate::docker::DbContainer;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    path::PathBuf,
+    time::Duration,
+};
+
+use super::{
+    auto_stop::StopAfter,
+    avatar::thumbnail_or_avatar,
+    cleanup::format_size,
+    events::format_ago,
+    limits::{typed_cpus, LimitsForm},
+    logs::{logs_id, LogFilter, LogPanel, Severity},
+    query::QueryConsole,
+    state_badge::state_badge_tip,
+    terminal::{Terminal, TerminalStatus},
+};
+use crate::{
+    backup::{BackupOutcome, BackupSchedule, Frequency, Hour, KEEP_OPTIONS},
+    data::{is_secret, DatabaseConfig},
+    diff::Change,
+    docker::{
+        describe_gpus, log_support, ContainerEvent, ContainerEventKind, DbContainer,
+        DbContainerConfig, EnvValue, HealthProbe, LimitField, LogRange, LogSupport, Restart,
+    },
+    exits::exit_notes,
+    idle::unix_now,
+    migrate::MigrationPlan,
+    platform::{host_bind_path, is_host_path},
+    run_command::RunCommandOptions,
+    versions::EolStatus,
+};
+use bollard::service::ContainerStateStatusEnum;
+use chrono::{DateTime, Local, TimeZone, Utc};
 use iced::{
-    widget::{column, component, image::Handle, row, scrollable, text, Component, Image},
-    Element, Renderer,
+    theme::{self, Button, Text},
+    widget::{
+        button, checkbox, column, component, container, image::Handle, pick_list, row, scrollable,
+        text, text_input, Component,
+    },
+    Color, Element, Font, Length, Renderer,
 };
-use std::marker::PhantomData;
+use iced_aw::{badge, BadgeStyles, Icon, TabBar, TabLabel, ICON_FONT};
+use itertools::Itertools;
 
 #[derive(Clone)]
-pub enum Event {}
+pub enum Event {
+    LogRangeSelected(LogRange),
+    SplitLogs(bool),
+    ExportLogs,
+    LogQueryChanged(String),
+    ToggleSeverity(Severity),
+    LogsScrolled(bool),
+    JumpToLatest,
+    IncludeSecrets(bool),
+    ExportDefinition,
+    /// Whether the copied `docker run` command keeps the `db-mgr__` prefix of the name.
+    KeepNamePrefix(bool),
+    CopyRunCommand,
+    ToggleDiff,
+    Recreate,
+    ViewEnv(String),
+    Migrate,
+    QueryChanged(String),
+    RunQuery,
+    Copy(String),
+    OpenFolder(PathBuf),
+    EditLimits,
+    LimitMemoryChanged(String),
+    LimitCpusChanged(String),
+    LimitRestartSelected(Restart),
+    ApplyLimits,
+    CancelLimits,
+    /// Shows how the container differs from its template, where it can be recreated.
+    ShowTemplateDiff,
+    SelectTab(DetailTab),
+    StopAfterSelected(StopAfter),
+    KeepOnExit(bool),
+    Favourite(bool),
+    /// Whether to keep warning about the end of life of the container's major version.
+    EolWarning(bool),
+    EditProject,
+    ProjectChanged(String),
+    SaveProject,
+    RecreateWithProject,
+    CancelProject,
+    SetAutoStop,
+    CancelAutoStop,
+    TerminalCommandChanged(String),
+    TerminalInputChanged(String),
+    StartTerminal,
+    SubmitTerminal,
+    StopTerminal,
+    ChooseBackupDirectory,
+    BackupFrequency(Frequency),
+    BackupHour(Hour),
+    BackupKeep(usize),
+    StopBackups,
+    /// Shows all or only the start of the output of the health check run that started then.
+    ToggleProbeOutput(Option<DateTime<Utc>>),
+    InspectSearchChanged(String),
+}
+
+/// Characters of a health check's output shown until it is expanded.
+const PROBE_OUTPUT_PREVIEW: usize = 300;
+
+/// The tabs of the detail pane.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum DetailTab {
+    #[default]
+    Overview,
+    Logs,
+    Exec,
+    Stats,
+    Files,
+    History,
+    Events,
+    Backups,
+    /// The raw `docker inspect` of the container, only offered in developer mode.
+    Inspect,
+}
+
+impl DetailTab {
+    pub const ALL: [DetailTab; 8] = [
+        DetailTab::Overview,
+        DetailTab::Logs,
+        DetailTab::Exec,
+        DetailTab::Stats,
+        DetailTab::Files,
+        DetailTab::History,
+        DetailTab::Events,
+        DetailTab::Backups,
+    ];
+}
+
+impl Display for DetailTab {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                DetailTab::Overview => "Overview",
+                DetailTab::Logs => "Logs",
+                DetailTab::Exec => "Exec",
+                DetailTab::Stats => "Stats",
+                DetailTab::Files => "Files",
+                DetailTab::History => "History",
+                DetailTab::Events => "Events",
+                DetailTab::Backups => "Backups",
+                DetailTab::Inspect => "Inspect",
+            }
+        )
+    }
+}
+
+/// The raw inspect of a container, fetched when its tab is opened and kept until the next
+/// refresh.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RawInspect {
+    Loading,
+    /// Indented JSON with the values of secret variables masked.
+    Loaded(String),
+    Failed(String),
+}
+
+/// Requests the logs of a container to be written to a file chosen by the user.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LogExportRequest {
+    pub id: String,
+    pub name: String,
+    pub range: LogRange,
+    pub split: bool,
+}
+
+type AutoStopCallback<Message> = Box<dyn Fn(String, Option<Duration>) -> Message>;
+type BackupCallback<Message> = Box<dyn Fn(String, Option<BackupSchedule>) -> Message>;
+type LogFilterCallback<Message> = Box<dyn Fn(String, LogFilter) -> Message>;
+type LimitsCallback<Message> = Box<dyn Fn(String, Option<LimitsForm>) -> Message>;
 
 pub struct ContainerView<Message> {
     container: DbContainer,
-    image: Handle,
-    t: PhantomData<Message>,
+    /// The fetched icon of the image, an avatar is drawn without one.
+    image: Option<Handle>,
+    template: Option<DatabaseConfig>,
+    expanded_env: HashMap<String, String>,
+    migration: Option<MigrationPlan>,
+    query: Option<QueryConsole>,
+    terminal: Option<Terminal>,
+    tab: DetailTab,
+    auto_stop: Option<Duration>,
+    started: Option<String>,
+    keep_on_exit: Option<bool>,
+    favourite: Option<bool>,
+    eol: Option<EolStatus>,
+    eol_silenced: bool,
+    project: Option<String>,
+    backup: Option<BackupSchedule>,
+    events: Vec<ContainerEvent>,
+    logs: Option<LogPanel>,
+    /// The raw inspect, `None` hides its tab outside developer mode.
+    raw_inspect: Option<RawInspect>,
+    /// The limits as typed while they are edited.
+    limits: Option<LimitsForm>,
+    on_export_logs: Option<Box<dyn Fn(LogExportRequest) -> Message>>,
+    on_log_filter_change: Option<LogFilterCallback<Message>>,
+    on_logs_scroll: Option<Box<dyn Fn(String, bool) -> Message>>,
+    on_jump_to_latest: Option<Box<dyn Fn(String) -> Message>>,
+    on_export_definition: Option<Box<dyn Fn(String, bool) -> Message>>,
+    on_copy_run_command: Option<Box<dyn Fn(String, RunCommandOptions) -> Message>>,
+    on_recreate: Option<Box<dyn Fn(String, DbContainerConfig) -> Message>>,
+    on_view_env: Option<Box<dyn Fn(String, String) -> Message>>,
+    on_migrate: Option<Box<dyn Fn(String) -> Message>>,
+    on_query_change: Option<Box<dyn Fn(String, String) -> Message>>,
+    on_run_query: Option<Box<dyn Fn(String) -> Message>>,
+    on_copy_click: Option<Box<dyn Fn(String) -> Message>>,
+    on_open_folder: Option<Box<dyn Fn(PathBuf) -> Message>>,
+    on_limits_change: Option<LimitsCallback<Message>>,
+    on_limits_apply: Option<Box<dyn Fn(String) -> Message>>,
+    on_tab_select: Option<Box<dyn Fn(String, DetailTab) -> Message>>,
+    on_auto_stop: Option<AutoStopCallback<Message>>,
+    on_keep_on_exit: Option<Box<dyn Fn(String, bool) -> Message>>,
+    on_eol_silence: Option<Box<dyn Fn(String, bool) -> Message>>,
+    on_favourite_toggle: Option<Box<dyn Fn(String, bool) -> Message>>,
+    on_project_save: Option<Box<dyn Fn(String, String) -> Message>>,
+    on_project_recreate: Option<Box<dyn Fn(String, String) -> Message>>,
+    on_terminal_command_change: Option<Box<dyn Fn(String, String) -> Message>>,
+    on_terminal_input: Option<Box<dyn Fn(String, String) -> Message>>,
+    on_terminal_start: Option<Box<dyn Fn(String) -> Message>>,
+    on_terminal_submit: Option<Box<dyn Fn(String) -> Message>>,
+    on_terminal_stop: Option<Box<dyn Fn(String) -> Message>>,
+    on_backup_change: Option<BackupCallback<Message>>,
+    on_choose_backup_directory: Option<Box<dyn Fn(String) -> Message>>,
+}
+
+#[derive(Debug)]
+pub struct ContainerViewState {
+    log_range: LogRange,
+    split_logs: bool,
+    show_diff: bool,
+    stop_after: StopAfter,
+    /// Keep secret values in exported definitions and copied `docker run` commands.
+    include_secrets: bool,
+    /// Keep the `db-mgr__` prefix of the name in copied `docker run` commands.
+    keep_name_prefix: bool,
+    /// The project as typed while it is edited.
+    project: Option<String>,
+    /// The health check runs whose full output is shown, by start time.
+    expanded_probes: HashSet<Option<DateTime<Utc>>>,
+    /// Only the lines of the raw inspect containing this are shown.
+    inspect_search: String,
 }
 
-#[derive(Debug, Default)]
-pub struct AddContainerState {}
+impl Default for ContainerViewState {
+    fn default() -> Self {
+        Self {
+            log_range: LogRange::LastHour,
+            split_logs: false,
+            show_diff: false,
+            stop_after: StopAfter::default(),
+            include_secrets: false,
+            keep_name_prefix: false,
+            project: None,
+            expanded_probes: HashSet::new(),
+            inspect_search: String::new(),
+        }
+    }
+}
 
-pub fn container_view<Message>(container: DbContainer, image: Handle) -> ContainerView<Message> {
+pub fn container_view<Message>(
+    container: DbContainer,
+    image: Option<Handle>,
+) -> ContainerView<Message> {
     ContainerView::new(container, image)
 }
 
 impl<Message> ContainerView<Message> {
-    pub fn new(container: DbContainer, image: Handle) -> Self {
+    pub fn new(container: DbContainer, image: Option<Handle>) -> Self {
         Self {
             container,
             image,
-            t: PhantomData,
+            template: None,
+            expanded_env: HashMap::new(),
+            migration: None,
+            query: None,
+            terminal: None,
+            tab: DetailTab::Overview,
+            auto_stop: None,
+            started: None,
+            keep_on_exit: None,
+            eol: None,
+            eol_silenced: false,
+            favourite: None,
+            project: None,
+            backup: None,
+            events: vec![],
+            logs: None,
+            raw_inspect: None,
+            limits: None,
+            on_export_logs: None,
+            on_log_filter_change: None,
+            on_logs_scroll: None,
+            on_jump_to_latest: None,
+            on_export_definition: None,
+            on_copy_run_command: None,
+            on_recreate: None,
+            on_view_env: None,
+            on_migrate: None,
+            on_query_change: None,
+            on_run_query: None,
+            on_copy_click: None,
+            on_open_folder: None,
+            on_limits_change: None,
+            on_limits_apply: None,
+            on_tab_select: None,
+            on_auto_stop: None,
+            on_keep_on_exit: None,
+            on_eol_silence: None,
+            on_favourite_toggle: None,
+            on_project_save: None,
+            on_project_recreate: None,
+            on_terminal_command_change: None,
+            on_terminal_input: None,
+            on_terminal_start: None,
+            on_terminal_submit: None,
+            on_terminal_stop: None,
+            on_backup_change: None,
+            on_choose_backup_directory: None,
+        }
+    }
+
+    /// The config entry the container was created from, used to compare against its template.
+    pub fn template(self, template: Option<DatabaseConfig>) -> Self {
+        Self { template, ..self }
+    }
+
+    /// Full values of large or binary variables, keyed by variable name.
+    pub fn expanded_env(self, expanded_env: HashMap<String, String>) -> Self {
+        Self {
+            expanded_env,
+            ..self
+        }
+    }
+
+    /// The metadata update the container needs if it was created by an older version.
+    pub fn migration(self, migration: Option<MigrationPlan>) -> Self {
+        Self { migration, ..self }
+    }
+
+    /// The query box, only shown for containers whose config entry has a query command.
+    pub fn query(self, query: Option<QueryConsole>) -> Self {
+        Self { query, ..self }
+    }
+
+    /// The exec terminal of the container.
+    pub fn terminal(self, terminal: Option<Terminal>) -> Self {
+        Self { terminal, ..self }
+    }
+
+    /// The raw inspect in developer mode, `Some(RawInspect::Loading)` until it is fetched.
+    pub fn raw_inspect(self, raw_inspect: Option<RawInspect>) -> Self {
+        Self {
+            raw_inspect,
+            ..self
+        }
+    }
+
+    /// The limits being edited, `None` shows the ones in effect.
+    pub fn limits(self, limits: Option<LimitsForm>) -> Self {
+        Self { limits, ..self }
+    }
+
+    /// The selected tab, owned by the app so it is kept per container.
+    pub fn tab(self, tab: DetailTab) -> Self {
+        Self { tab, ..self }
+    }
+
+    /// How long until the container is stopped automatically, if a timer is set.
+    pub fn auto_stop(self, auto_stop: Option<Duration>) -> Self {
+        Self { auto_stop, ..self }
+    }
+
+    /// Who started the container and when, while it is running.
+    pub fn started(self, started: Option<String>) -> Self {
+        Self { started, ..self }
+    }
+
+    /// Whether the container is left running when db-mgr is closed, `None` unless closing stops
+    /// the running containers.
+    pub fn keep_on_exit(self, keep_on_exit: Option<bool>) -> Self {
+        Self {
+            keep_on_exit,
+            ..self
+        }
+    }
+
+    /// How close the major version of the image is to its end of life, and whether its warning
+    /// was silenced.
+    pub fn eol(self, eol: Option<EolStatus>, eol_silenced: bool) -> Self {
+        Self {
+            eol,
+            eol_silenced,
+            ..self
+        }
+    }
+
+    /// Called with the container name and whether to silence its end of life warning.
+    pub fn on_eol_silence<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: 'static + Fn(String, bool) -> Message,
+    {
+        Self {
+            on_eol_silence: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    /// Whether the container is in the tray menu, `None` without a tray.
+    pub fn favourite(self, favourite: Option<bool>) -> Self {
+        Self { favourite, ..self }
+    }
+
+    /// The project the container is in, from db-mgr's settings or its label.
+    pub fn project(self, project: Option<String>) -> Self {
+        Self { project, ..self }
+    }
+
+    /// Called with the container name and a new project kept in db-mgr's settings, an empty one
+    /// takes it out of its project.
+    pub fn on_project_save<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: 'static + Fn(String, String) -> Message,
+    {
+        Self {
+            on_project_save: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    /// Called with the container id and a new project to recreate the container with, since
+    /// labels can't be changed in place.
+    pub fn on_project_recreate<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: 'static + Fn(String, String) -> Message,
+    {
+        Self {
+            on_project_recreate: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    /// Called with the container name and whether to leave it running when db-mgr is closed.
+    pub fn on_keep_on_exit<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: 'static + Fn(String, bool) -> Message,
+    {
+        Self {
+            on_keep_on_exit: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    /// Called with the container name and whether to add it to the tray menu.
+    pub fn on_favourite_toggle<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: 'static + Fn(String, bool) -> Message,
+    {
+        Self {
+            on_favourite_toggle: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    /// What docker reported happening to the container, newest first.
+    pub fn events(self, events: Vec<ContainerEvent>) -> Self {
+        Self { events, ..self }
+    }
+
+    /// The followed logs of the container, filtered.
+    pub fn logs(self, logs: Option<LogPanel>) -> Self {
+        Self { logs, ..self }
+    }
+
+    /// The backup schedule of the container, if it has one.
+    pub fn backup(self, backup: Option<BackupSchedule>) -> Self {
+        Self { backup, ..self }
+    }
+
+    /// Called with the container id and whether secret values are kept.
+    pub fn on_export_definition<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn(String, bool) -> Message + 'static,
+    {
+        Self {
+            on_export_definition: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    pub fn on_copy_run_command<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn(String, RunCommandOptions) -> Message + 'static,
+    {
+        Self {
+            on_copy_run_command: Some(Box::new(handler)),
+            ..self
         }
     }
+
+    pub fn on_export_logs<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn(LogExportRequest) -> Message + 'static,
+    {
+        Self {
+            on_export_logs: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    /// Called with the container id and the new filter of its followed logs.
+    pub fn on_log_filter_change<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: 'static + Fn(String, LogFilter) -> Message,
+    {
+        Self {
+            on_log_filter_change: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    /// Called with the container id and whether the logs are scrolled to the newest line.
+    pub fn on_logs_scroll<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: 'static + Fn(String, bool) -> Message,
+    {
+        Self {
+            on_logs_scroll: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    pub fn on_jump_to_latest<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: 'static + Fn(String) -> Message,
+    {
+        Self {
+            on_jump_to_latest: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    pub fn on_recreate<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn(String, DbContainerConfig) -> Message + 'static,
+    {
+        Self {
+            on_recreate: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    pub fn on_view_env<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn(String, String) -> Message + 'static,
+    {
+        Self {
+            on_view_env: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    /// Called with the container name and its changed schedule, `None` to stop backing it up.
+    pub fn on_backup_change<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn(String, Option<BackupSchedule>) -> Message + 'static,
+    {
+        Self {
+            on_backup_change: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    /// Called with the container name to pick the directory its backups are saved to.
+    pub fn on_choose_backup_directory<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn(String) -> Message + 'static,
+    {
+        Self {
+            on_choose_backup_directory: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    pub fn on_migrate<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn(String) -> Message + 'static,
+    {
+        Self {
+            on_migrate: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    pub fn on_query_change<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn(String, String) -> Message + 'static,
+    {
+        Self {
+            on_query_change: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    pub fn on_run_query<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn(String) -> Message + 'static,
+    {
+        Self {
+            on_run_query: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    pub fn on_copy_click<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn(String) -> Message + 'static,
+    {
+        Self {
+            on_copy_click: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    /// Opens the host folder of a bind mount in the file manager.
+    pub fn on_open_folder<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn(PathBuf) -> Message + 'static,
+    {
+        Self {
+            on_open_folder: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    /// Called with the container id and the limits as typed, `None` when editing them is
+    /// cancelled.
+    pub fn on_limits_change<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn(String, Option<LimitsForm>) -> Message + 'static,
+    {
+        Self {
+            on_limits_change: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    /// Called with the container id to apply the typed limits to the running container.
+    pub fn on_limits_apply<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn(String) -> Message + 'static,
+    {
+        Self {
+            on_limits_apply: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    pub fn on_tab_select<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn(String, DetailTab) -> Message + 'static,
+    {
+        Self {
+            on_tab_select: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    pub fn on_terminal_command_change<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn(String, String) -> Message + 'static,
+    {
+        Self {
+            on_terminal_command_change: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    pub fn on_terminal_input<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn(String, String) -> Message + 'static,
+    {
+        Self {
+            on_terminal_input: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    pub fn on_terminal_start<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn(String) -> Message + 'static,
+    {
+        Self {
+            on_terminal_start: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    pub fn on_terminal_submit<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn(String) -> Message + 'static,
+    {
+        Self {
+            on_terminal_submit: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    pub fn on_terminal_stop<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn(String) -> Message + 'static,
+    {
+        Self {
+            on_terminal_stop: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    /// Called with the time after which the container should be stopped, `None` to cancel.
+    pub fn on_auto_stop<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn(String, Option<Duration>) -> Message + 'static,
+    {
+        Self {
+            on_auto_stop: Some(Box::new(handler)),
+            ..self
+        }
+    }
+}
+
+fn change_color(change: Change) -> Color {
+    match change {
+        Change::Added => Color::from_rgb8(80, 180, 90),
+        Change::Removed => Color::from_rgb8(220, 60, 60),
+        Change::Changed => Color::from_rgb8(230, 160, 40),
+        Change::Unchanged => Color::from_rgb8(150, 150, 150),
+    }
+}
+
+/// A run of the health check with its time, duration, result and output, cut short unless it
+/// was expanded.
+fn health_probe<'a>(probe: &'a HealthProbe, state: &ContainerViewState) -> Column<'a> {
+    let (result, color) = match probe.exit_code {
+        Some(0) => ("passed".to_string(), Color::from_rgb8(60, 180, 90)),
+        Some(1) | Some(2) => ("failed".to_string(), Color::from_rgb8(220, 60, 60)),
+        Some(code) => (
+            format!("could not run (exit code {code})"),
+            Color::from_rgb8(230, 160, 40),
+        ),
+        None => ("no result".to_string(), Color::from_rgb8(150, 150, 150)),
+    };
+    let started = probe
+        .start
+        .map(|start| {
+            start
+                .with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string()
+        })
+        .unwrap_or_else(|| "Unknown time".to_string());
+    let duration = probe
+        .duration()
+        .map(|duration| format!("{} ms", duration.as_millis()))
+        .unwrap_or_default();
+
+    let mut content = column!(row!(
+        text(started).size(12).width(Length::FillPortion(2)),
+        text(duration)
+            .size(12)
+            .style(Text::Color(Color::from_rgb8(150, 150, 150)))
+            .width(Length::FillPortion(1)),
+        text(result)
+            .size(12)
+            .style(Text::Color(color))
+            .width(Length::FillPortion(2)),
+    ))
+    .spacing(5);
+
+    if probe.output.is_empty() {
+        return content;
+    }
+    let expanded = state.expanded_probes.contains(&probe.start);
+    let long = probe.output.chars().count() > PROBE_OUTPUT_PREVIEW;
+    let output = match probe.output.char_indices().nth(PROBE_OUTPUT_PREVIEW) {
+        Some((end, _)) if !expanded => format!("{}…", &probe.output[..end]),
+        _ => probe.output.clone(),
+    };
+    content = content.push(
+        text(output)
+            .size(12)
+            .font(Font::MONOSPACE)
+            .style(Text::Color(Color::from_rgb8(150, 150, 150))),
+    );
+    if long {
+        content = content.push(
+            button(text(if expanded { "Show less" } else { "Show all" }).size(12))
+                .style(Button::Text)
+                .on_press(Event::ToggleProbeOutput(probe.start)),
+        );
+    }
+    content
 }
 
 impl<Message> Component<Message, Renderer> for ContainerView<Message> {
-    type State = AddContainerState;
+    type State = ContainerViewState;
 
     type Event = Event;
 
-    fn update(&mut self, _state: &mut Self::State, event: Self::Event) -> Option<Message> {
-        match event {}
+    fn update(&mut self, state: &mut Self::State, event: Self::Event) -> Option<Message> {
+        match event {
+            Event::LogRangeSelected(range) => {
+                state.log_range = range;
+                None
+            }
+            Event::SplitLogs(split) => {
+                state.split_logs = split;
+                None
+            }
+            Event::ExportLogs => self.on_export_logs.as_ref().map(|fun| {
+                fun(LogExportRequest {
+                    id: self.container.id.clone(),
+                    name: self.container.name.trim_start_matches('/').to_string(),
+                    range: state.log_range,
+                    split: state.split_logs,
+                })
+            }),
+            Event::LogQueryChanged(query) => self.change_log_filter(|filter| filter.query = query),
+            Event::ToggleSeverity(severity) => {
+                self.change_log_filter(|filter| filter.toggle(severity))
+            }
+            Event::LogsScrolled(at_bottom) => self
+                .on_logs_scroll
+                .as_ref()
+                .filter(|_| {
+                    self.logs
+                        .as_ref()
+                        .is_some_and(|logs| logs.following != at_bottom)
+                })
+                .map(|fun| fun(self.container.id.clone(), at_bottom)),
+            Event::JumpToLatest => self
+                .on_jump_to_latest
+                .as_ref()
+                .map(|fun| fun(self.container.id.clone())),
+            Event::IncludeSecrets(include) => {
+                state.include_secrets = include;
+                None
+            }
+            Event::ExportDefinition => self
+                .on_export_definition
+                .as_ref()
+                .map(|fun| fun(self.container.id.clone(), state.include_secrets)),
+            Event::KeepNamePrefix(keep) => {
+                state.keep_name_prefix = keep;
+                None
+            }
+            Event::CopyRunCommand => self.on_copy_run_command.as_ref().map(|fun| {
+                fun(
+                    self.container.id.clone(),
+                    RunCommandOptions {
+                        keep_prefix: state.keep_name_prefix,
+                        include_secrets: state.include_secrets,
+                    },
+                )
+            }),
+            Event::ToggleDiff => {
+                state.show_diff = !state.show_diff;
+                None
+            }
+            Event::Recreate => {
+                let config = self.template.as_ref()?.recreate_config(&self.container);
+                self.on_recreate
+                    .as_ref()
+                    .map(|fun| fun(self.container.id.clone(), config))
+            }
+            Event::ViewEnv(key) => self
+                .on_view_env
+                .as_ref()
+                .map(|fun| fun(self.container.id.clone(), key)),
+            Event::Migrate => self
+                .on_migrate
+                .as_ref()
+                .map(|fun| fun(self.container.id.clone())),
+            Event::ChooseBackupDirectory => self
+                .on_choose_backup_directory
+                .as_ref()
+                .map(|fun| fun(self.name().to_string())),
+            Event::BackupFrequency(frequency) => {
+                self.change_backup(|schedule| schedule.frequency = frequency)
+            }
+            Event::BackupHour(Hour(hour)) => self.change_backup(|schedule| schedule.hour = hour),
+            Event::BackupKeep(keep) => self.change_backup(|schedule| schedule.keep = keep),
+            Event::StopBackups => self
+                .on_backup_change
+                .as_ref()
+                .map(|fun| fun(self.name().to_string(), None)),
+            Event::QueryChanged(query) => self
+                .on_query_change
+                .as_ref()
+                .map(|fun| fun(self.container.id.clone(), query)),
+            Event::RunQuery => self
+                .on_run_query
+                .as_ref()
+                .map(|fun| fun(self.container.id.clone())),
+            Event::Copy(output) => self.on_copy_click.as_ref().map(|fun| fun(output)),
+            Event::OpenFolder(path) => self.on_open_folder.as_ref().map(|fun| fun(path)),
+            Event::EditLimits => self.on_limits_change.as_ref().map(|fun| {
+                fun(
+                    self.container.id.clone(),
+                    Some(LimitsForm::new(&self.container.limits)),
+                )
+            }),
+            Event::LimitMemoryChanged(memory) => {
+                self.change_limits(LimitField::Memory, |form| form.memory = memory)
+            }
+            Event::LimitCpusChanged(cpus) => {
+                self.change_limits(LimitField::Cpus, |form| form.cpus = cpus)
+            }
+            Event::LimitRestartSelected(restart) => {
+                self.change_limits(LimitField::Restart, |form| form.restart = restart)
+            }
+            Event::ApplyLimits => self
+                .on_limits_apply
+                .as_ref()
+                .map(|fun| fun(self.container.id.clone())),
+            Event::CancelLimits => self
+                .on_limits_change
+                .as_ref()
+                .map(|fun| fun(self.container.id.clone(), None)),
+            Event::ShowTemplateDiff => {
+                state.show_diff = true;
+                self.on_tab_select
+                    .as_ref()
+                    .map(|fun| fun(self.container.id.clone(), DetailTab::Overview))
+            }
+            Event::SelectTab(tab) => self
+                .on_tab_select
+                .as_ref()
+                .map(|fun| fun(self.container.id.clone(), tab)),
+            Event::StopAfterSelected(stop_after) => {
+                state.stop_after = stop_after;
+                None
+            }
+            Event::KeepOnExit(keep) => self
+                .on_keep_on_exit
+                .as_ref()
+                .map(|fun| fun(self.name().to_string(), keep)),
+            Event::EolWarning(warn) => self
+                .on_eol_silence
+                .as_ref()
+                .map(|fun| fun(self.name().to_string(), !warn)),
+            Event::Favourite(favourite) => self
+                .on_favourite_toggle
+                .as_ref()
+                .map(|fun| fun(self.name().to_string(), favourite)),
+            Event::EditProject => {
+                state.project = Some(self.project.clone().unwrap_or_default());
+                None
+            }
+            Event::ProjectChanged(project) => {
+                state.project = Some(project);
+                None
+            }
+            Event::SaveProject => {
+                let project = state.project.take()?;
+                self.on_project_save
+                    .as_ref()
+                    .map(|fun| fun(self.name().to_string(), project.trim().to_string()))
+            }
+            Event::RecreateWithProject => {
+                let project = state.project.take()?;
+                self.on_project_recreate
+                    .as_ref()
+                    .map(|fun| fun(self.container.id.clone(), project.trim().to_string()))
+            }
+            Event::CancelProject => {
+                state.project = None;
+                None
+            }
+            Event::InspectSearchChanged(search) => {
+                state.inspect_search = search;
+                None
+            }
+            Event::ToggleProbeOutput(start) => {
+                if !state.expanded_probes.remove(&start) {
+                    state.expanded_probes.insert(start);
+                }
+                None
+            }
+            Event::SetAutoStop => self
+                .on_auto_stop
+                .as_ref()
+                .map(|fun| fun(self.container.id.clone(), Some(state.stop_after.duration()))),
+            Event::CancelAutoStop => self
+                .on_auto_stop
+                .as_ref()
+                .map(|fun| fun(self.container.id.clone(), None)),
+            Event::TerminalCommandChanged(command) => self
+                .on_terminal_command_change
+                .as_ref()
+                .map(|fun| fun(self.container.id.clone(), command)),
+            Event::TerminalInputChanged(input) => self
+                .on_terminal_input
+                .as_ref()
+                .map(|fun| fun(self.container.id.clone(), input)),
+            Event::StartTerminal => self
+                .on_terminal_start
+                .as_ref()
+                .map(|fun| fun(self.container.id.clone())),
+            Event::SubmitTerminal => self
+                .on_terminal_submit
+                .as_ref()
+                .map(|fun| fun(self.container.id.clone())),
+            Event::StopTerminal => self
+                .on_terminal_stop
+                .as_ref()
+                .map(|fun| fun(self.container.id.clone())),
+        }
     }
 
-    fn view(&self, _state: &Self::State) -> iced_aw::Element<'_, Self::Event, Renderer> {
-        let content = column!(row!(
-            Image::new(self.image.clone()).height(35),
+    fn view(&self, state: &Self::State) -> iced_aw::Element<'_, Self::Event, Renderer> {
+        let header = row!(
+            thumbnail_or_avatar(&self.container.image, self.image.as_ref(), 35.0),
             text(
                 self.container
                     .name
                     .strip_prefix('/')
                     .unwrap_or(&self.container.name)
             )
-            .size(22)
+            .size(22),
+            state_badge_tip(
+                self.container.state,
+                self.container.health,
+                self.started.as_deref()
+            )
         )
-        .align_items(iced::Alignment::Center))
         .align_items(iced::Alignment::Center)
+        .spacing(10);
+
+        let inspect = self.raw_inspect.as_ref().map(|_| DetailTab::Inspect);
+        let tabs = DetailTab::ALL
+            .iter()
+            .chain(inspect.iter())
+            .fold(TabBar::new(Event::SelectTab), |tabs, tab| {
+                tabs.push(*tab, TabLabel::Text(tab.to_string()))
+            })
+            .set_active_tab(&self.tab);
+
+        let content = match self.tab {
+            DetailTab::Overview => self.overview(state),
+            DetailTab::Logs => self.log_viewer(state),
+            DetailTab::Exec => self.exec(),
+            DetailTab::Stats => self.stats(),
+            DetailTab::Files => self.files(),
+            DetailTab::History => self.history(),
+            DetailTab::Events => self.event_timeline(),
+            DetailTab::Backups => self.backups(),
+            DetailTab::Inspect => self.inspect(state),
+        }
         .spacing(15)
         .padding(15);
 
-        return scrollable(content).into();
+        column!(
+            container(header).padding(15),
+            tabs,
+            scrollable(content).height(Length::Fill)
+        )
+        .align_items(iced::Alignment::Center)
+        .into()
+    }
+}
+
+type Column<'a> = iced::widget::Column<'a, Event, Renderer>;
+
+impl<Message> ContainerView<Message> {
+    fn overview(&self, state: &ContainerViewState) -> Column<'_> {
+        let mut content = column!().align_items(iced::Alignment::Center);
+
+        if self.container.partial {
+            content = content.push(
+                text("Docker returned incomplete data for this container, so some details and actions are unavailable")
+                    .size(12)
+                    .style(Text::Color(Color::from_rgb8(230, 160, 40))),
+            );
+        }
+
+        if let Some(started) = self.started.as_ref() {
+            content = content.push(text(started));
+        }
+
+        content = content.push(self.exit_explanation());
+
+        if let Some(eol) = self.eol {
+            if !self.eol_silenced {
+                let color = if eol.is_past() {
+                    Color::from_rgb8(220, 60, 60)
+                } else {
+                    Color::from_rgb8(230, 160, 40)
+                };
+                content = content.push(
+                    row!(
+                        text(format!(
+                            "{}, this major version gets no more fixes. Back up the data and restore it into a container of a newer one.",
+                            eol.describe()
+                        ))
+                        .size(12)
+                        .style(Text::Color(color)),
+                        button("Back up")
+                            .style(Button::Secondary)
+                            .on_press(Event::SelectTab(DetailTab::Backups))
+                    )
+                    .align_items(iced::Alignment::Center)
+                    .spacing(10),
+                );
+            }
+            if self.on_eol_silence.is_some() {
+                content = content.push(checkbox(
+                    "Warn about the end of life of this version",
+                    !self.eol_silenced,
+                    Event::EolWarning,
+                ));
+            }
+        }
+
+        if self.container.state == ContainerStateStatusEnum::RUNNING && self.on_auto_stop.is_some()
+        {
+            content = content.push(
+                match self.auto_stop {
+                    Some(remaining) => row!(
+                        text(format!(
+                            "Stops automatically in {} minutes",
+                            remaining.as_secs().div_ceil(60)
+                        )),
+                        button("Cancel")
+                            .style(Button::Secondary)
+                            .on_press(Event::CancelAutoStop)
+                    ),
+                    None => row!(
+                        text("Stop after"),
+                        pick_list(
+                            &StopAfter::OPTIONS[..],
+                            Some(state.stop_after),
+                            Event::StopAfterSelected
+                        ),
+                        button("Set").on_press(Event::SetAutoStop)
+                    ),
+                }
+                .align_items(iced::Alignment::Center)
+                .spacing(10),
+            );
+        }
+
+        if let Some(keep) = self.keep_on_exit.filter(|_| self.on_keep_on_exit.is_some()) {
+            content = content.push(checkbox(
+                "Keep running when db-mgr is closed",
+                keep,
+                Event::KeepOnExit,
+            ));
+        }
+        if let Some(favourite) = self
+            .favourite
+            .filter(|_| self.on_favourite_toggle.is_some())
+        {
+            content = content.push(checkbox(
+                "Show in the tray menu",
+                favourite,
+                Event::Favourite,
+            ));
+        }
+
+        content = content.push(self.project_row(state));
+
+        if let Some(size) = self.container.shm_size_mb {
+            content = content.push(text(format!("Shared memory: {size} MB")).size(12));
+        }
+        if let Some(cpuset) = self.container.cpuset_cpus.as_ref() {
+            content = content.push(text(format!("Pinned to CPUs {cpuset}")).size(12));
+        }
+        if let Some(hostname) = self.container.hostname.as_ref() {
+            content = content.push(text(format!("Hostname: {hostname}")).size(12));
+        }
+        if let Some(user) = self.container.user.as_ref() {
+            content = content.push(text(format!("Runs as user {user}")).size(12));
+        }
+        if !self.container.extra_hosts.is_empty() {
+            content = content.push(
+                text(format!(
+                    "Extra hosts: {}",
+                    self.container.extra_hosts.join(", ")
+                ))
+                .size(12),
+            );
+        }
+        for ulimit in self.container.ulimits.iter() {
+            let limit = |limit: i64| match limit {
+                -1 => "unlimited".to_string(),
+                limit => limit.to_string(),
+            };
+            content = content.push(
+                text(format!(
+                    "Ulimit {}: soft {}, hard {}",
+                    ulimit.name,
+                    limit(ulimit.soft),
+                    limit(ulimit.hard)
+                ))
+                .size(12),
+            );
+        }
+        for (key, value) in self.container.sysctls.iter().sorted() {
+            content = content.push(text(format!("Sysctl {key} = {value}")).size(12));
+        }
+        if let Some(gpus) = self.container.gpus.as_ref() {
+            content = content.push(text(format!("GPU access: {}", describe_gpus(gpus))).size(12));
+        }
+        if let Some(log) = self.container.log.as_ref() {
+            content = content.push(text(format!("Logging: {log}")).size(12));
+        }
+
+        if !self.container.health_log.is_empty() {
+            content = content.push(text("Health checks").size(20));
+        }
+        for probe in self.container.health_log.iter() {
+            content = content.push(health_probe(probe, state));
+        }
+
+        if !self.container.ports.is_empty() {
+            content = content.push(text("Published ports").size(20));
+        }
+        for published in self.container.ports.iter() {
+            content = content.push(
+                row!(
+                    text(format!(
+                        "{}:{} → {}/{}",
+                        published.host_ip, published.host_port, published.port, published.protocol
+                    ))
+                    .size(12)
+                    .font(Font::MONOSPACE),
+                    button(text("Copy host port").size(12))
+                        .style(Button::Secondary)
+                        .on_press(Event::Copy(published.host_port.to_string())),
+                )
+                .spacing(10)
+                .align_items(iced::Alignment::Center),
+            );
+        }
+        // Docker binds another port when the assigned one was taken in the meantime
+        if let Some((assigned, bound)) = self
+            .container
+            .port_assignment()
+            .and_then(|assignment| {
+                let bound = self
+                    .container
+                    .ports
+                    .iter()
+                    .find(|published| published.port == assignment.container)?;
+                Some((assignment.host?, bound.host_port))
+            })
+            .filter(|(assigned, bound)| assigned != bound)
+        {
+            content = content.push(
+                text(format!(
+                    "Assigned port {assigned}, but docker published it on {bound}"
+                ))
+                .size(12)
+                .style(Text::Color(Color::from_rgb8(230, 160, 40))),
+            );
+        }
+        if let Some(connection_string) = self
+            .template
+            .as_ref()
+            .and_then(|template| template.host_connection_string(&self.container))
+        {
+            content = content.push(
+                button(text("Copy connection string from this machine").size(12))
+                    .style(Button::Secondary)
+                    .on_press(Event::Copy(connection_string)),
+            );
+        }
+
+        if !self.container.networks.is_empty() {
+            content = content.push(text("Networks").size(20));
+        }
+        for network in self.container.networks.iter() {
+            let mut address = row!(text(&network.name).size(12))
+                .spacing(10)
+                .align_items(iced::Alignment::Center);
+            address = match network.ip.as_ref() {
+                Some(ip) => address.push(text(ip).size(12).font(Font::MONOSPACE)).push(
+                    button(text("Copy IP").size(12))
+                        .style(Button::Secondary)
+                        .on_press(Event::Copy(ip.clone())),
+                ),
+                None => address.push(
+                    text("No IP while stopped")
+                        .size(12)
+                        .style(Text::Color(Color::from_rgb8(150, 150, 150))),
+                ),
+            };
+            content = content.push(address);
+
+            if !network.aliases.is_empty() {
+                content = content.push(
+                    text(format!("Aliases: {}", network.aliases.join(", ")))
+                        .size(12)
+                        .style(Text::Color(Color::from_rgb8(150, 150, 150))),
+                );
+            }
+
+            let connection_string = self.template.as_ref().and_then(|template| {
+                template.internal_connection_string(&self.container, network.host()?)
+            });
+            // Only copied, connection strings usually hold the password
+            if let Some(connection_string) = connection_string {
+                content = content.push(
+                    button(text("Copy connection string").size(12))
+                        .style(Button::Secondary)
+                        .on_press(Event::Copy(connection_string)),
+                );
+            }
+        }
+
+        if !self.container.command.is_empty() {
+            content = content.push(text("Command").size(20));
+            for arg in self.container.command.iter() {
+                content = content.push(
+                    text(arg)
+                        .size(12)
+                        .style(Text::Color(Color::from_rgb8(150, 150, 150))),
+                );
+            }
+        }
+
+        if let Some(migration) = self.migration.as_ref() {
+            content = content
+                .push(text("Legacy container").size(20))
+                .push(text(migration.explanation()).size(12));
+            for change in migration.changes.iter() {
+                content = content.push(
+                    text(format!("• {change}"))
+                        .size(12)
+                        .style(Text::Color(Color::from_rgb8(150, 150, 150))),
+                );
+            }
+            if self.on_migrate.is_some() {
+                content = content.push(button("Update metadata").on_press(Event::Migrate));
+            }
+        }
+
+        if !self.container.variables.is_empty() {
+            content = content.push(text("Environment").size(20));
+        }
+        for (key, value) in self
+            .container
+            .variables
+            .iter()
+            .sorted_by_key(|(key, _)| *key)
+        {
+            let value: Element<'_, Event, Renderer> = match (value, self.expanded_env.get(key)) {
+                (EnvValue::Text(_), _) if is_secret(key, key) => text("••••••").size(12).into(),
+                (EnvValue::Text(value), _) => text(value).size(12).into(),
+                (EnvValue::LargeOrBinary(_), Some(full)) => {
+                    text(full.escape_debug().to_string()).size(12).into()
+                }
+                (EnvValue::LargeOrBinary(length), None) => {
+                    button(text(format!("({} value — view)", format_size(*length as i64))).size(12))
+                        .style(Button::Text)
+                        .on_press(Event::ViewEnv(key.clone()))
+                        .into()
+                }
+            };
+
+            content = content.push(row!(
+                text(key)
+                    .size(12)
+                    .style(Text::Color(Color::from_rgb8(150, 150, 150)))
+                    .width(Length::FillPortion(1)),
+                container(value).width(Length::FillPortion(2))
+            ));
+        }
+
+        if let Some(template) = self.template.as_ref() {
+            content = content.push(
+                button(if state.show_diff {
+                    "Hide template comparison"
+                } else {
+                    "Compare with template"
+                })
+                .style(Button::Secondary)
+                .on_press(Event::ToggleDiff),
+            );
+
+            if state.show_diff {
+                let rows = template.template_diff(&self.container);
+                content = content.push(row!(
+                    text("Variable").width(Length::FillPortion(1)),
+                    text("Container").width(Length::FillPortion(1)),
+                    text("Template").width(Length::FillPortion(1))
+                ));
+
+                for diff_row in rows.iter() {
+                    let (container_value, template_value) = diff_row.display_values();
+                    let color = Text::Color(change_color(diff_row.change));
+                    content = content.push(row!(
+                        text(&diff_row.key)
+                            .size(12)
+                            .style(color)
+                            .width(Length::FillPortion(1)),
+                        text(container_value)
+                            .size(12)
+                            .style(color)
+                            .width(Length::FillPortion(1)),
+                        text(template_value)
+                            .size(12)
+                            .style(color)
+                            .width(Length::FillPortion(1))
+                    ));
+                }
+
+                if rows.iter().any(|row| row.change != Change::Unchanged) {
+                    if self.on_recreate.is_some() {
+                        content = content.push(
+                            button("Recreate with template values")
+                                .style(Button::Destructive)
+                                .on_press(Event::Recreate),
+                        );
+                    }
+                } else {
+                    content = content.push(text("The container matches its template"));
+                }
+            }
+        }
+
+        if let Some(query) = self.query.as_ref().filter(|_| self.on_run_query.is_some()) {
+            let mut run = button(if query.running {
+                "Running…"
+            } else {
+                "Run query"
+            });
+            if !query.running {
+                run = run.on_press(Event::RunQuery);
+            }
+
+            content = content.push(text("Query").size(20)).push(
+                row!(
+                    text_input("SELECT 1", &query.input)
+                        .font(Font::MONOSPACE)
+                        .on_input(Event::QueryChanged)
+                        .on_submit(Event::RunQuery),
+                    run
+                )
+                .align_items(iced::Alignment::Center)
+                .spacing(15),
+            );
+
+            match query.output.as_ref() {
+                None => {}
+                Some(Err(ex)) => {
+                    content = content.push(
+                        text(ex)
+                            .size(12)
+                            .style(Text::Color(Color::from_rgb8(220, 60, 60))),
+                    );
+                }
+                Some(Ok(output)) => {
+                    let (shown, color) = if output.success() {
+                        (&output.stdout, Color::from_rgb8(200, 200, 200))
+                    } else {
+                        (&output.stderr, Color::from_rgb8(220, 60, 60))
+                    };
+
+                    content = content.push(
+                        container(
+                            scrollable(
+                                text(shown)
+                                    .size(12)
+                                    .font(Font::MONOSPACE)
+                                    .style(Text::Color(color)),
+                            )
+                            .height(200),
+                        )
+                        .padding(5)
+                        .width(Length::Fill)
+                        .style(theme::Container::Box),
+                    );
+                    content = content.push(
+                        button("Copy output")
+                            .style(Button::Secondary)
+                            .on_press(Event::Copy(shown.clone())),
+                    );
+                }
+            }
+        }
+
+        if self.on_export_definition.is_some() {
+            content = content.push(text("Share").size(20)).push(
+                row!(
+                    checkbox(
+                        "Include secrets",
+                        state.include_secrets,
+                        Event::IncludeSecrets
+                    ),
+                    button("Export definition…").on_press(Event::ExportDefinition)
+                )
+                .align_items(iced::Alignment::Center)
+                .spacing(15),
+            );
+        }
+
+        if self.on_copy_run_command.is_some() {
+            content = content.push(
+                row!(
+                    checkbox(
+                        "Keep the db-mgr__ name prefix",
+                        state.keep_name_prefix,
+                        Event::KeepNamePrefix
+                    ),
+                    button("Copy as docker run")
+                        .style(Button::Secondary)
+                        .on_press(Event::CopyRunCommand)
+                )
+                .align_items(iced::Alignment::Center)
+                .spacing(15),
+            );
+            if !state.include_secrets {
+                content = content.push(
+                    text("Secrets are copied as $VARIABLE placeholders unless they are included")
+                        .size(12),
+                );
+            }
+        }
+
+        content
+    }
+
+    fn log_viewer(&self, state: &ContainerViewState) -> Column<'_> {
+        let mut content = column!().align_items(iced::Alignment::Center);
+
+        content = content.push(text("Logs").size(20)).push(
+            row!(
+                pick_list(
+                    &LogRange::ALL[..],
+                    Some(state.log_range),
+                    Event::LogRangeSelected
+                ),
+                checkbox(
+                    "Separate stdout and stderr",
+                    state.split_logs,
+                    Event::SplitLogs
+                ),
+                button("Export logs…").on_press(Event::ExportLogs)
+            )
+            .align_items(iced::Alignment::Center)
+            .spacing(15),
+        );
+
+        let driver = self
+            .container
+            .log
+            .as_ref()
+            .map(|log| log.driver.as_str())
+            .unwrap_or_default();
+        match log_support(driver) {
+            LogSupport::Full => {}
+            LogSupport::Cached => {
+                content = content.push(
+                    text(format!(
+                        "The {driver} log driver sends the logs elsewhere, only the recent lines \
+                         docker caches are shown. Recreate the container with the json-file \
+                         driver to keep them all."
+                    ))
+                    .size(14)
+                    .style(Text::Color(Color::from_rgb8(150, 150, 150))),
+                );
+            }
+            LogSupport::Unavailable => {
+                return content.push(
+                    text(format!(
+                        "The {driver} log driver keeps no logs, so there is nothing to show. \
+                         Recreate the container with the json-file driver, under the advanced \
+                         options, to see them here."
+                    ))
+                    .width(Length::Fill),
+                );
+            }
+        }
+
+        let Some(logs) = self.logs.as_ref() else {
+            return content;
+        };
+
+        let mut filters = row!(text_input("Filter lines", &logs.filter.query)
+            .on_input(Event::LogQueryChanged)
+            .width(250))
+        .align_items(iced::Alignment::Center)
+        .spacing(15);
+        for severity in Severity::ALL {
+            filters = filters.push(checkbox(
+                severity.to_string(),
+                logs.filter.severities.contains(&severity),
+                move |_| Event::ToggleSeverity(severity),
+            ));
+        }
+        if !logs.following && self.on_jump_to_latest.is_some() {
+            filters = filters.push(button("Jump to latest").on_press(Event::JumpToLatest));
+        }
+
+        let mut lines = column!().spacing(2).width(Length::Fill);
+        if logs.older > 0 {
+            lines = lines.push(
+                text(format!(
+                    "{} older matching lines are not shown, narrow the filter to see them",
+                    logs.older
+                ))
+                .size(12)
+                .style(Text::Color(Color::from_rgb8(150, 150, 150))),
+            );
+        }
+        for line in logs.lines.iter() {
+            let color = match line.severity {
+                Some(Severity::Fatal | Severity::Error) => Some(Color::from_rgb8(220, 60, 60)),
+                Some(Severity::Warning) => Some(Color::from_rgb8(230, 160, 40)),
+                None => None,
+            };
+            let mut message = text(&line.text).size(12).font(Font::MONOSPACE);
+            if let Some(color) = color {
+                message = message.style(Text::Color(color));
+            }
+            lines = lines.push(
+                row!(
+                    text(line.local_time())
+                        .size(12)
+                        .font(Font::MONOSPACE)
+                        .style(Text::Color(Color::from_rgb8(150, 150, 150))),
+                    message
+                )
+                .spacing(10),
+            );
+        }
+        if logs.lines.is_empty() && logs.older == 0 {
+            lines = lines.push(text(if logs.filter == LogFilter::default() {
+                "The container has not printed anything yet"
+            } else {
+                "No lines match the filter"
+            }));
+        }
+
+        content = content.push(filters).push(
+            scrollable(container(lines).padding(10))
+                .id(logs_id())
+                .on_scroll(|viewport| {
+                    let y = viewport.relative_offset().y;
+                    Event::LogsScrolled(y.is_nan() || y >= 0.99)
+                })
+                .height(400),
+        );
+
+        if let Some(ended) = logs.ended.as_ref() {
+            content = content.push(
+                text(ended)
+                    .size(12)
+                    .style(Text::Color(Color::from_rgb8(150, 150, 150))),
+            );
+        }
+
+        content
+    }
+
+    fn exec(&self) -> Column<'_> {
+        let content = column!(text("Exec").size(20)).align_items(iced::Alignment::Center);
+        let Some(terminal) = self.terminal.as_ref() else {
+            return content;
+        };
+        if self.container.state != ContainerStateStatusEnum::RUNNING && !terminal.is_open() {
+            return content.push(text("Start the container to open a terminal"));
+        }
+
+        let mut command = text_input("sh", &terminal.command).font(Font::MONOSPACE);
+        if !terminal.is_open() {
+            command = command.on_input(Event::TerminalCommandChanged);
+        }
+        let mut controls = row!(command)
+            .align_items(iced::Alignment::Center)
+            .spacing(10);
+        controls = match &terminal.status {
+            TerminalStatus::Connecting => {
+                controls.push(badge(text("Connecting").size(12)).style(BadgeStyles::Info))
+            }
+            TerminalStatus::Running => controls.push(
+                button("End session")
+                    .style(Button::Destructive)
+                    .on_press(Event::StopTerminal),
+            ),
+            TerminalStatus::Idle | TerminalStatus::Ended(_)
+                if self.on_terminal_start.is_some() && terminal.args().is_some() =>
+            {
+                controls.push(
+                    button(if terminal.session == 0 {
+                        "Start session"
+                    } else {
+                        "Start new session"
+                    })
+                    .on_press(Event::StartTerminal),
+                )
+            }
+            _ => controls,
+        };
+
+        let mut output = column!().spacing(2).width(Length::Fill);
+        for line in terminal.lines() {
+            output = output.push(text(line).size(12).font(Font::MONOSPACE));
+        }
+
+        let mut content = content
+            .push(controls)
+            .push(container(output).padding(10).style(theme::Container::Box));
+
+        if let TerminalStatus::Ended(reason) = &terminal.status {
+            content = content.push(
+                text(reason)
+                    .size(12)
+                    .style(Text::Color(Color::from_rgb8(150, 150, 150))),
+            );
+        }
+        if terminal.status == TerminalStatus::Running {
+            content = content.push(
+                text_input("Type a line and press enter", &terminal.input)
+                    .font(Font::MONOSPACE)
+                    .on_input(Event::TerminalInputChanged)
+                    .on_submit(Event::SubmitTerminal),
+            );
+        }
+
+        content
+    }
+
+    /// Why the container went down, with a way to the limits or logs, and how often its restart
+    /// policy brought it back.
+    fn exit_explanation(&self) -> Column<'_> {
+        let notes = exit_notes(&self.container, &self.events);
+        let mut content = column!().align_items(iced::Alignment::Center).spacing(5);
+        let warning = |message: String| {
+            text(message)
+                .size(12)
+                .style(Text::Color(Color::from_rgb8(220, 60, 60)))
+        };
+
+        if notes.out_of_memory {
+            let explanation = match self.container.limits.memory {
+                Some(memory) => format!(
+                    "Killed for running out of memory, it may use at most {}. Give it more or lower the database's own memory settings.",
+                    format_size(memory)
+                ),
+                None => "Killed for running out of memory, it has no limit so the host ran out. Limit it to keep it from taking the host down with it.".to_string(),
+            };
+            let mut explained = row!(warning(explanation))
+                .align_items(iced::Alignment::Center)
+                .spacing(10);
+            if self.on_limits_change.is_some() {
+                explained = explained.push(
+                    button("Change limits")
+                        .style(Button::Secondary)
+                        .on_press(Event::SelectTab(DetailTab::Stats)),
+                );
+            }
+            content = content.push(explained);
+        } else if let Some(code) = notes.failed {
+            content = content.push(
+                row!(
+                    warning(format!(
+                        "Its process failed with exit code {code}, its logs should say why."
+                    )),
+                    button("Logs")
+                        .style(Button::Secondary)
+                        .on_press(Event::SelectTab(DetailTab::Logs))
+                )
+                .align_items(iced::Alignment::Center)
+                .spacing(10),
+            );
+        }
+
+        if notes.policy_restarts > 0 {
+            content = content.push(
+                text(format!(
+                    "Restarted {} by its restart policy ({}) since it was last started",
+                    match notes.policy_restarts {
+                        1 => "once".to_string(),
+                        count => format!("{count} times"),
+                    },
+                    self.container.limits.restart.to_string().to_lowercase()
+                ))
+                .size(12)
+                .style(Text::Color(Color::from_rgb8(230, 160, 40))),
+            );
+        }
+
+        content
+    }
+
+    fn stats(&self) -> Column<'_> {
+        column!(
+            text("No resource usage has been collected for this container"),
+            self.limits_editor()
+        )
+    }
+
+    /// The memory, CPU and restart limits, which docker changes on the running container.
+    fn limits_editor(&self) -> Column<'_> {
+        let current = &self.container.limits;
+        let mut content = column!(text("Limits").size(20)).spacing(10);
+        let label = |label| text(label).width(Length::Fixed(80.0));
+        let field_error = |error: String| {
+            text(error)
+                .size(12)
+                .style(Text::Color(Color::from_rgb8(220, 60, 60)))
+        };
+
+        let Some(form) = self.limits.as_ref() else {
+            let memory = match current.memory {
+                Some(memory) => format_size(memory),
+                None => "No limit".to_string(),
+            };
+            let cpus = match current.nano_cpus {
+                Some(nano_cpus) => typed_cpus(nano_cpus),
+                None => "No limit".to_string(),
+            };
+            content = content
+                .push(row!(label("Memory"), text(memory)))
+                .push(row!(label("CPUs"), text(cpus)))
+                .push(row!(label("Restart"), text(current.restart.to_string())));
+            if self.on_limits_change.is_some() {
+                content = content.push(
+                    button("Edit limits")
+                        .style(Button::Secondary)
+                        .on_press(Event::EditLimits),
+                );
+            }
+            return content;
+        };
+
+        content = content.push(
+            row!(
+                label("Memory"),
+                text_input("No limit, e.g. 512m or 2g", &form.memory)
+                    .on_input(Event::LimitMemoryChanged)
+            )
+            .align_items(iced::Alignment::Center),
+        );
+        if let Some(error) = form.memory_error(current) {
+            content = content.push(field_error(error));
+        }
+        content = content.push(
+            row!(
+                label("CPUs"),
+                text_input("No limit, e.g. 0.5 or 2", &form.cpus).on_input(Event::LimitCpusChanged)
+            )
+            .align_items(iced::Alignment::Center),
+        );
+        if let Some(error) = form.cpus_error(current) {
+            content = content.push(field_error(error));
+        }
+        content = content.push(
+            row!(
+                label("Restart"),
+                pick_list(
+                    &Restart::ALL[..],
+                    Some(form.restart),
+                    Event::LimitRestartSelected
+                )
+            )
+            .align_items(iced::Alignment::Center),
+        );
+        if let Some(error) = form.restart_error() {
+            content = content.push(field_error(error));
+        }
+
+        let mut apply = button(if form.applying {
+            "Applying…"
+        } else {
+            "Apply"
+        });
+        let changes = form.changes(current);
+        if !form.applying && changes.is_some_and(|changes| !changes.is_empty()) {
+            apply = apply.on_press(Event::ApplyLimits);
+        }
+        content = content.push(
+            row!(
+                apply,
+                button("Cancel")
+                    .style(Button::Secondary)
+                    .on_press(Event::CancelLimits)
+            )
+            .spacing(5),
+        );
+
+        let mut recreate = row!(text(
+            "The image, variables and mounts can only be changed by recreating the container"
+        )
+        .size(12)
+        .style(Text::Color(Color::from_rgb8(150, 150, 150))))
+        .align_items(iced::Alignment::Center)
+        .spacing(10);
+        if self.template.is_some() && self.on_recreate.is_some() {
+            recreate = recreate.push(
+                button(text("Compare with template").size(12))
+                    .style(Button::Secondary)
+                    .on_press(Event::ShowTemplateDiff),
+            );
+        }
+        content.push(recreate)
+    }
+
+    fn files(&self) -> Column<'_> {
+        let mut content = column!(text("Mounts").size(20));
+        if self.container.volumes.is_empty() && self.container.tmpfs.is_empty() {
+            content = content.push(text("The container has no mounts"));
+        }
+
+        for (volume, path) in self.container.volumes.iter().sorted() {
+            let mut name = row!(text(volume)).spacing(5);
+            if self.container.read_only_volumes.contains(volume) {
+                name = name.push(badge(text("ro").size(12)).style(BadgeStyles::Info));
+            }
+            let mut mount = row!(
+                name.width(Length::FillPortion(1)),
+                text(path)
+                    .size(12)
+                    .style(Text::Color(Color::from_rgb8(150, 150, 150)))
+                    .width(Length::FillPortion(1))
+            )
+            .align_items(iced::Alignment::Center)
+            .spacing(15);
+            if is_host_path(volume) && self.on_open_folder.is_some() {
+                mount = mount.push(
+                    button(text("Open folder").size(12))
+                        .style(Button::Secondary)
+                        .on_press(Event::OpenFolder(host_bind_path(volume, cfg!(windows)))),
+                );
+            }
+            content = content.push(mount);
+        }
+
+        for path in self.container.tmpfs.iter().sorted() {
+            let size = match self.container.tmpfs_size {
+                Some(size) => format!("tmpfs, {}", format_size(size as i64)),
+                None => "tmpfs".to_string(),
+            };
+            content = content.push(
+                row!(
+                    badge(text(size).size(12))
+                        .style(BadgeStyles::Warning)
+                        .width(Length::FillPortion(1)),
+                    text(path)
+                        .size(12)
+                        .style(Text::Color(Color::from_rgb8(150, 150, 150)))
+                        .width(Length::FillPortion(1))
+                )
+                .align_items(iced::Alignment::Center)
+                .spacing(15),
+            );
+        }
+        if !self.container.tmpfs.is_empty() {
+            content = content.push(
+                text("Data in tmpfs mounts is lost when the container stops")
+                    .size(12)
+                    .style(Text::Color(Color::from_rgb8(150, 150, 150))),
+            );
+        }
+
+        content
+    }
+
+    fn name(&self) -> &str {
+        self.container.name.trim_start_matches('/')
+    }
+
+    /// The project of the container, with a box to change it once editing.
+    fn project_row(&self, state: &ContainerViewState) -> Element<'_, Event, Renderer> {
+        let Some(project) = state.project.as_ref() else {
+            let mut shown = row!(text(match self.project.as_ref() {
+                Some(project) => format!("Project: {project}"),
+                None => "No project".to_string(),
+            }))
+            .align_items(iced::Alignment::Center)
+            .spacing(10);
+            if self.on_project_save.is_some() {
+                shown = shown.push(
+                    button("Edit")
+                        .style(Button::Secondary)
+                        .on_press(Event::EditProject),
+                );
+            }
+            return shown.into();
+        };
+
+        let mut buttons = row!(button("Save").on_press(Event::SaveProject)).spacing(5);
+        if self.on_project_recreate.is_some() {
+            buttons = buttons.push(
+                button("Save and recreate")
+                    .style(Button::Secondary)
+                    .on_press(Event::RecreateWithProject),
+            );
+        }
+        buttons = buttons.push(
+            button("Cancel")
+                .style(Button::Secondary)
+                .on_press(Event::CancelProject),
+        );
+
+        column!(
+            row!(
+                text_input("Project, empty for none", project)
+                    .on_input(Event::ProjectChanged)
+                    .on_submit(Event::SaveProject),
+                buttons
+            )
+            .align_items(iced::Alignment::Center)
+            .spacing(10),
+            text("Saving keeps the project in db-mgr, recreating writes it to the container's label and keeps its volumes")
+                .size(12)
+                .style(Text::Color(Color::from_rgb8(150, 150, 150)))
+        )
+        .spacing(5)
+        .into()
+    }
+
+    fn change_backup(&self, change: impl FnOnce(&mut BackupSchedule)) -> Option<Message> {
+        let mut schedule = self.backup.clone()?;
+        change(&mut schedule);
+        self.on_backup_change
+            .as_ref()
+            .map(|fun| fun(self.name().to_string(), Some(schedule)))
+    }
+
+    /// The limits form with `change` made, clearing the daemon's refusal of `field`.
+    fn change_limits(
+        &self,
+        field: LimitField,
+        change: impl FnOnce(&mut LimitsForm),
+    ) -> Option<Message> {
+        let mut form = self.limits.clone()?;
+        change(&mut form);
+        if form
+            .error
+            .as_ref()
+            .is_some_and(|error| error.field == field)
+        {
+            form.error = None;
+        }
+        self.on_limits_change
+            .as_ref()
+            .map(|fun| fun(self.container.id.clone(), Some(form)))
+    }
+
+    fn change_log_filter(&self, change: impl FnOnce(&mut LogFilter)) -> Option<Message> {
+        let mut filter = self.logs.as_ref()?.filter.clone();
+        change(&mut filter);
+        self.on_log_filter_change
+            .as_ref()
+            .map(|fun| fun(self.container.id.clone(), filter))
+    }
+
+    fn event_timeline(&self) -> Column<'_> {
+        let mut content = column!(text("Events").size(20));
+        if self.events.is_empty() {
+            content = content.push(text("Docker reported no events in the last day"));
+        }
+
+        let now = unix_now() as i64;
+        for event in self.events.iter() {
+            let (icon, color) = match event.kind {
+                ContainerEventKind::Created => (Icon::PlusCircle, Color::from_rgb8(150, 150, 150)),
+                ContainerEventKind::Started | ContainerEventKind::Unpaused => {
+                    (Icon::PlayFill, Color::from_rgb8(60, 180, 90))
+                }
+                ContainerEventKind::Died(Some(0)) | ContainerEventKind::Stopped => {
+                    (Icon::StopFill, Color::from_rgb8(150, 150, 150))
+                }
+                ContainerEventKind::Died(_) | ContainerEventKind::Killed(_) => {
+                    (Icon::XOctagonFill, Color::from_rgb8(220, 60, 60))
+                }
+                ContainerEventKind::OutOfMemory => {
+                    (Icon::ExclamationTriangleFill, Color::from_rgb8(220, 60, 60))
+                }
+                ContainerEventKind::Restarted => {
+                    (Icon::ArrowRepeat, Color::from_rgb8(230, 160, 40))
+                }
+                ContainerEventKind::Paused => (Icon::PauseFill, Color::from_rgb8(150, 150, 150)),
+                ContainerEventKind::Health(ref status) if status == "healthy" => {
+                    (Icon::Heart, Color::from_rgb8(60, 180, 90))
+                }
+                ContainerEventKind::Health(_) => (Icon::Heart, Color::from_rgb8(230, 160, 40)),
+                ContainerEventKind::Removed => (Icon::Trash, Color::from_rgb8(150, 150, 150)),
+            };
+
+            content = content.push(
+                row!(
+                    text(icon).font(ICON_FONT).style(Text::Color(color)),
+                    text(event.kind.to_string()).width(Length::Fill),
+                    text(format_ago(now - event.time))
+                        .size(12)
+                        .style(Text::Color(Color::from_rgb8(150, 150, 150)))
+                )
+                .spacing(10)
+                .align_items(iced::Alignment::Center),
+            );
+        }
+
+        content
+    }
+
+    fn inspect(&self, state: &ContainerViewState) -> Column<'_> {
+        let mut content = column!(text("docker inspect").size(20));
+        let json = match self.raw_inspect.as_ref() {
+            None => return content.push(text("Turn on developer mode in the settings")),
+            Some(RawInspect::Loading) => return content.push(text("Loading…")),
+            Some(RawInspect::Failed(ex)) => {
+                return content.push(
+                    text(ex)
+                        .size(12)
+                        .style(Text::Color(Color::from_rgb8(220, 60, 60))),
+                )
+            }
+            Some(RawInspect::Loaded(json)) => json,
+        };
+
+        content = content.push(
+            row!(
+                text_input("Search", &state.inspect_search).on_input(Event::InspectSearchChanged),
+                button("Copy")
+                    .style(Button::Secondary)
+                    .on_press(Event::Copy(json.clone()))
+            )
+            .align_items(iced::Alignment::Center)
+            .spacing(15),
+        );
+
+        let search = state.inspect_search.trim().to_lowercase();
+        let shown = if search.is_empty() {
+            json.clone()
+        } else {
+            let lines = json
+                .lines()
+                .filter(|line| line.to_lowercase().contains(&search))
+                .collect::<Vec<_>>();
+            content = content.push(
+                text(format!("{} matching lines", lines.len()))
+                    .size(12)
+                    .style(Text::Color(Color::from_rgb8(150, 150, 150))),
+            );
+            lines.join("\n")
+        };
+
+        content.push(
+            container(
+                scrollable(text(shown).size(12).font(Font::MONOSPACE)).direction(
+                    scrollable::Direction::Horizontal(scrollable::Properties::default()),
+                ),
+            )
+            .padding(5)
+            .width(Length::Fill)
+            .style(theme::Container::Box),
+        )
+    }
+
+    fn backups(&self) -> Column<'_> {
+        let mut content = column!(text("Scheduled backups").size(20));
+        if self
+            .template
+            .as_ref()
+            .is_none_or(|template| template.backup_command.is_empty())
+        {
+            return content.push(text(
+                "Add a backup_command to the config entry of this database to back it up",
+            ));
+        }
+
+        let Some(schedule) = self.backup.as_ref() else {
+            content = content.push(text("Backups aren't scheduled"));
+            if self.on_choose_backup_directory.is_some() {
+                content = content
+                    .push(button("Schedule backups…").on_press(Event::ChooseBackupDirectory));
+            }
+            return content;
+        };
+
+        let mut directory = row!(text(format!("Saved to {}", schedule.directory.display())))
+            .spacing(10)
+            .align_items(iced::Alignment::Center);
+        if self.on_choose_backup_directory.is_some() {
+            directory = directory.push(
+                button("Change…")
+                    .style(Button::Secondary)
+                    .on_press(Event::ChooseBackupDirectory),
+            );
+        }
+        content = content.push(directory).push(
+            row!(
+                pick_list(
+                    Frequency::options(),
+                    Some(schedule.frequency),
+                    Event::BackupFrequency
+                ),
+                text("at"),
+                pick_list(
+                    Hour::options(),
+                    Some(Hour(schedule.hour)),
+                    Event::BackupHour
+                ),
+                text("keeping"),
+                pick_list(&KEEP_OPTIONS[..], Some(schedule.keep), Event::BackupKeep),
+                text("backups")
+            )
+            .spacing(10)
+            .align_items(iced::Alignment::Center),
+        );
+
+        content = content.push(text(match schedule.next_run(&Local::now()) {
+            Some(run) => format!("Next run: {}", run.format("%a %e %b %H:%M")),
+            None => "No upcoming run".to_string(),
+        }));
+        if self.container.state != ContainerStateStatusEnum::RUNNING {
+            content = content.push(
+                text("Runs are skipped while the container is stopped")
+                    .size(12)
+                    .style(Text::Color(Color::from_rgb8(150, 150, 150))),
+            );
+        }
+        if self.on_backup_change.is_some() {
+            content = content.push(
+                button("Stop scheduled backups")
+                    .style(Button::Destructive)
+                    .on_press(Event::StopBackups),
+            );
+        }
+
+        content = content.push(text("Results").size(20));
+        if schedule.history.is_empty() {
+            content = content.push(text("No backups have run yet"));
+        }
+        for record in schedule.history.iter().rev() {
+            let at = Local
+                .timestamp_opt(record.at, 0)
+                .single()
+                .map(|at| at.format("%a %e %b %H:%M").to_string())
+                .unwrap_or_default();
+            let color = match record.outcome {
+                BackupOutcome::Saved(_) => Color::from_rgb8(200, 200, 200),
+                BackupOutcome::Failed(_) => Color::from_rgb8(220, 60, 60),
+                BackupOutcome::Skipped(_) => Color::from_rgb8(150, 150, 150),
+            };
+            content = content.push(
+                text(format!("{at}  {}", record.outcome))
+                    .size(12)
+                    .style(Text::Color(color)),
+            );
+        }
+
+        content
+    }
+
+    fn history(&self) -> Column<'_> {
+        let mut content = column!(text("Queries").size(20));
+        let history = self
+            .query
+            .as_ref()
+            .map(QueryConsole::history)
+            .unwrap_or_default();
+        if history.is_empty() {
+            content = content.push(text("No queries have been run this session"));
+        }
+
+        for query in history.iter().rev() {
+            content = content.push(
+                button(text(query).size(12).font(Font::MONOSPACE))
+                    .style(Button::Text)
+                    .on_press(Event::QueryChanged(query.clone())),
+            );
+        }
+
+        content
     }
 }
 
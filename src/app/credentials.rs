@@ -0,0 +1,108 @@
+use iced::{
+    theme::{Button, Text},
+    widget::{button, column, component, row, text, Component},
+    Color, Element, Length, Renderer,
+};
+
+/// The values a quick started container was created with, shown once after it is created.
+#[derive(Clone, Debug)]
+pub struct QuickCredentials {
+    pub name: String,
+    /// Variable display names and their values.
+    pub values: Vec<(String, String)>,
+}
+
+#[derive(Clone)]
+pub enum Event {
+    Copy(String),
+    Done,
+}
+
+pub fn credentials_view<Message>(credentials: QuickCredentials) -> CredentialsView<Message> {
+    CredentialsView::new(credentials)
+}
+
+pub struct CredentialsView<Message> {
+    credentials: QuickCredentials,
+    on_copy_click: Option<Box<dyn Fn(String) -> Message>>,
+    on_done_click: Option<Box<dyn Fn() -> Message>>,
+}
+
+impl<Message> CredentialsView<Message> {
+    pub fn new(credentials: QuickCredentials) -> Self {
+        Self {
+            credentials,
+            on_copy_click: None,
+            on_done_click: None,
+        }
+    }
+
+    pub fn on_copy_click<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn(String) -> Message + 'static,
+    {
+        Self {
+            on_copy_click: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    pub fn on_done_click<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn() -> Message + 'static,
+    {
+        Self {
+            on_done_click: Some(Box::new(handler)),
+            ..self
+        }
+    }
+}
+
+impl<Message> Component<Message, Renderer> for CredentialsView<Message> {
+    type State = ();
+
+    type Event = Event;
+
+    fn update(&mut self, _: &mut Self::State, event: Self::Event) -> Option<Message> {
+        match event {
+            Event::Copy(value) => self.on_copy_click.as_ref().map(|fun| fun(value)),
+            Event::Done => self.on_done_click.as_ref().map(|fun| fun()),
+        }
+    }
+
+    fn view(&self, _: &Self::State) -> Element<'_, Self::Event, Renderer> {
+        let mut content = column!(
+            text(format!("{} was created", self.credentials.name)).size(22),
+            text("These values are only shown once, copy them now")
+                .size(12)
+                .style(Text::Color(Color::from_rgb8(150, 150, 150)))
+        )
+        .spacing(15)
+        .padding(15);
+
+        for (name, value) in self.credentials.values.iter() {
+            content = content.push(
+                row!(
+                    text(name.trim()).width(Length::FillPortion(1)),
+                    text(value).width(Length::FillPortion(2)),
+                    button("Copy")
+                        .style(Button::Secondary)
+                        .on_press(Event::Copy(value.clone()))
+                )
+                .align_items(iced::Alignment::Center)
+                .spacing(15),
+            );
+        }
+
+        content.push(button("Done").on_press(Event::Done)).into()
+    }
+}
+
+impl<'a, Message> From<CredentialsView<Message>> for Element<'a, Message, Renderer>
+where
+    Message: 'a,
+{
+    fn from(value: CredentialsView<Message>) -> Self {
+        component(value)
+    }
+}
@@ -0,0 +1,68 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::docker::ContainerEvent;
+
+/// How many events are kept for each container.
+const MAX_EVENTS: usize = 200;
+
+/// The docker events of the containers seen this session, keyed by container id.
+#[derive(Debug, Default)]
+pub struct EventLog {
+    events: HashMap<String, Vec<ContainerEvent>>,
+    /// Containers whose recent events were fetched from docker.
+    backfilled: HashSet<String>,
+}
+
+impl EventLog {
+    /// Adds `event` unless it is already known, dropping the oldest events over [`MAX_EVENTS`].
+    pub fn push(&mut self, event: ContainerEvent) {
+        let events = self.events.entry(event.container.clone()).or_default();
+        if events.contains(&event) {
+            return;
+        }
+
+        let at = events.partition_point(|known| known.time <= event.time);
+        events.insert(at, event);
+        let excess = events.len().saturating_sub(MAX_EVENTS);
+        events.drain(..excess);
+    }
+
+    /// The events of container `id`, newest first.
+    pub fn events(&self, id: &str) -> Vec<ContainerEvent> {
+        self.events
+            .get(id)
+            .map(|events| events.iter().rev().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// [`EventLog::events`] without copying them, for views built every frame.
+    pub fn newest_first(&self, id: &str) -> impl Iterator<Item = &ContainerEvent> {
+        self.events
+            .get(id)
+            .into_iter()
+            .flat_map(|events| events.iter().rev())
+    }
+
+    /// Marks the recent events of `id` as fetched, returns whether they still had to be.
+    pub fn start_backfill(&mut self, id: &str) -> bool {
+        self.backfilled.insert(id.to_string())
+    }
+
+    /// Lets the recent events of `id` be fetched again, after fetching them failed.
+    pub fn backfill_failed(&mut self, id: &str) {
+        self.backfilled.remove(id);
+    }
+}
+
+/// How long ago something `seconds` old happened, such as `5 min ago`.
+pub fn format_ago(seconds: i64) -> String {
+    match seconds {
+        ..=59 => "just now".to_string(),
+        60..=3599 => format!("{} min ago", seconds / 60),
+        3600..=86_399 => format!("{} h ago", seconds / 3600),
+        _ => match seconds / 86_400 {
+            1 => "1 day ago".to_string(),
+            days => format!("{days} days ago"),
+        },
+    }
+}
@@ -0,0 +1,55 @@
+use std::{collections::HashSet, time::Duration};
+
+use bollard::service::ContainerStateStatusEnum;
+
+use crate::{
+    dependencies::{graph, stop_order},
+    docker::DbContainer,
+};
+
+/// How long closing waits for the containers to stop before closing anyway, so a hanging docker
+/// can't keep the window open.
+pub const EXIT_CAP: Duration = Duration::from_secs(30);
+
+/// What closing the window does.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ExitPlan {
+    /// Close right away.
+    Close,
+    /// Stop these containers first, named in the order they are stopped in.
+    Stop(Vec<String>),
+}
+
+/// What closing the window does with `containers` running. Nothing is stopped unless
+/// `stop_on_exit` is set, in read-only mode, or when closing again while `exiting`, and the
+/// containers in `keep_on_exit` are left running. Dependents are stopped first.
+pub fn exit_plan(
+    containers: &[DbContainer],
+    stop_on_exit: bool,
+    keep_on_exit: &HashSet<String>,
+    read_only: bool,
+    exiting: bool,
+) -> ExitPlan {
+    // Closing again while the containers stop closes right away
+    if exiting || !stop_on_exit || read_only {
+        return ExitPlan::Close;
+    }
+
+    let names = containers
+        .iter()
+        .filter(|container| container.state == ContainerStateStatusEnum::RUNNING)
+        .map(|container| container.name.trim_start_matches('/').to_string())
+        .filter(|name| !keep_on_exit.contains(name))
+        .collect::<Vec<_>>();
+    if names.is_empty() {
+        return ExitPlan::Close;
+    }
+
+    match stop_order(&graph(containers), &names) {
+        Ok(order) => ExitPlan::Stop(order),
+        Err(cycle) => {
+            eprintln!("Containers depend on each other: {}", cycle.join(" -> "));
+            ExitPlan::Stop(names)
+        }
+    }
+}
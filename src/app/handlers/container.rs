@@ -0,0 +1,1233 @@
+use bollard::service::ContainerStateStatusEnum;
+use chrono::Local;
+use iced::{widget::scrollable, Command};
+use itertools::Itertools;
+use std::{
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use super::UiMsg;
+use crate::{
+    app::{
+        backup_command,
+        build_card::BuildPhase,
+        bulk::{run_bulk, summary, BulkAction, BulkResult},
+        changes::{diff_containers, Toast, HIGHLIGHT_DURATION},
+        cleanup::{format_size, CleanupResult},
+        container_view::{LogExportRequest, RawInspect},
+        error, failed, failed_with_retry,
+        limits::LimitsForm,
+        logs::{LogFilter, LogLine},
+        prefetch::PrefetchRun,
+        refresh::patch_containers,
+        run, sidebar_id,
+        sql_file::{confirm_prompt, file_name, SqlRun},
+        start_run::StartRun,
+        stopping::DEFAULT_GRACE,
+        DbMgrApp, MainViewState, Message,
+    },
+    backup::{BackupOutcome, BackupSchedule},
+    data::{is_secret_key, write_preferences},
+    definition::ContainerDefinition,
+    docker::{
+        exec, export_logs, get_cleanup_summary, get_env_value, get_image_defaults, get_volumes,
+        kill_container, redact_env, remove_volume, start_container, stop_container,
+        update_container_limits, CleanupSummary, ContainerEvent, ContainerEventKind, DbContainer,
+        DbContainerConfig, DbVolume, ExecEvent, ExecOutput, ImageDefaults, InputExecEvent,
+        LimitError, LogEvent, LogExport, PrefetchEvent,
+    },
+    errors::{explain, UserError},
+    idle::unix_now,
+    image_ref::ImageRef,
+    migrate::migration_config,
+    run_command::{docker_run_command, RunCommandOptions},
+    start_order::StartEvent,
+};
+
+/// Messages about existing containers and the docker resources around them: refreshing,
+/// starting and stopping, queries, terminals, logs, backups, updates and cleanup.
+#[derive(Clone, Debug)]
+pub enum ContainerMsg {
+    GetContainers,
+    ContainersLoaded(u64, Vec<DbContainer>),
+    ContainersFailed(u64, Box<UserError>),
+    GetVolumes,
+    VolumesLoaded(Vec<DbVolume>),
+    RemoveVolume(String),
+    StartContainer(String),
+    StopContainer(String),
+    /// Kills a container that is taking too long to stop, after confirming.
+    ForceStopContainer(String),
+    ForceStopFailed(String, Box<UserError>),
+    ExportLogs(LogExportRequest),
+    RecreateContainer(String, Box<DbContainerConfig>),
+    MigrateContainer(String),
+    QueryChanged(String, String),
+    RunQuery(String),
+    QueryFinished(String, Result<ExecOutput, String>),
+    /// Moves through the query history of the viewed container, `true` for older queries.
+    QueryHistory(bool),
+    TerminalCommandChanged(String, String),
+    TerminalInputChanged(String, String),
+    StartTerminal(String),
+    SendTerminalInput(String),
+    StopTerminal(String),
+    TerminalEvent(ExecEvent),
+    RunBulk(BulkAction),
+    BulkDone(BulkAction, Vec<BulkResult>),
+    StartProgress(StartEvent),
+    /// Cancels the ordered start while it runs, dismisses it once it is done.
+    CancelStart,
+    /// Stops the container after the duration, `None` cancels the timer.
+    SetAutoStop(String, Option<Duration>),
+    /// Looks for a running container that wasn't used for a while to offer stopping it.
+    IdleCheck(Instant),
+    RemindIdleLater,
+    /// Never asks about stopping the idle container again.
+    IgnoreIdle(String),
+    /// Asks for confirmation, then runs the SQL file in the container.
+    RunSqlFile(String, PathBuf),
+    CancelDrop,
+    SqlRunEvent(InputExecEvent),
+    /// A SQL file run as a single query finished.
+    SqlQueryFinished(Result<ExecOutput, String>),
+    ViewEnvValue(String, String),
+    EnvValueLoaded(String, String),
+    LogsExported(LogExport),
+    CleanupSummaryLoaded(CleanupSummary),
+    RunCleanup(CleanupSummary),
+    CleanupProgress(CleanupResult),
+    CleanupDone,
+    /// Pulls these images, as `image:tag`, without creating containers.
+    RunPrefetch(Vec<String>),
+    PrefetchProgress(PrefetchEvent),
+    /// Sets the backup schedule of the named container, `None` removes it.
+    SetBackupSchedule(String, Option<BackupSchedule>),
+    ChooseBackupDirectory(String),
+    BackupTick(Instant),
+    /// A scheduled backup of the named container due at the given unix time finished.
+    BackupDone(String, i64, Result<PathBuf, String>),
+    /// Backs up the named container now, after its scheduled backup failed.
+    RetryBackup(String),
+    DismissBackupFailure,
+    ContainerEvent(ContainerEvent),
+    /// The recent events of the container with the given id were fetched.
+    EventsBackfilled(String, Result<Vec<ContainerEvent>, String>),
+    LogEvent(LogEvent),
+    /// The filter of the followed logs of the container with the given id changed.
+    LogFilterChanged(String, LogFilter),
+    /// Whether the followed logs are scrolled to the newest line.
+    LogsScrolled(String, bool),
+    JumpToLatest(String),
+    /// Asks the registries for the digests of the tags not checked in the last day.
+    CheckUpdates,
+    UpdateTick(Instant),
+    /// Recreates the container with the given id labelled with the project, keeping its volumes.
+    RecreateWithProject(String, String),
+    DigestsChecked(Vec<(ImageRef, Option<String>)>),
+    /// Recreates the container from the newest image of its tag.
+    UpdateContainer(String),
+    /// The raw inspect of the container with the given id was fetched.
+    InspectLoaded(String, Result<serde_json::Value, String>),
+    /// Saves the definition of the container, with secret values if `true`.
+    ExportDefinition(String, bool),
+    /// Copies a `docker run` command creating a container like the one with the given id.
+    CopyRunCommand(String, RunCommandOptions),
+    /// The image of the container was inspected for the `docker run` command.
+    RunCommandImageLoaded(String, RunCommandOptions, Box<ImageDefaults>),
+    /// The limits of the container with the given id as typed, `None` stops editing them.
+    LimitsChanged(String, Option<Box<LimitsForm>>),
+    ApplyLimits(String),
+    LimitsApplied(String, Result<(), LimitError>),
+    /// Inspects the containers with the given ids again, after a change only they are affected by.
+    RefreshContainers(Vec<String>),
+    /// The container with the given id as docker reports it now, `None` once it was removed.
+    ContainerRefreshed(String, Option<Box<DbContainer>>),
+}
+
+impl DbMgrApp {
+    pub fn handle_container_msg(&mut self, msg: ContainerMsg) -> Command<Message> {
+        match msg {
+            ContainerMsg::GetContainers => {
+                let sequence = self.refresh.request();
+                self.refresh_containers(sequence)
+            }
+            ContainerMsg::ContainersFailed(sequence, ex) => {
+                if !self.refresh.finish(sequence) {
+                    return Command::none();
+                }
+                self.connected = Some(false);
+                let follow_up = self.refresh.follow_up();
+                Command::batch([
+                    run(Message::Ui(UiMsg::FailedWithRetry(
+                        ex,
+                        Box::new(Message::Container(ContainerMsg::GetContainers)),
+                    ))),
+                    self.refresh_containers(follow_up),
+                ])
+            }
+            ContainerMsg::ContainersLoaded(sequence, containers) => {
+                if !self.refresh.finish(sequence) {
+                    return Command::none();
+                }
+                // The first load has nothing to compare with
+                if self.last_refresh.is_some() {
+                    let now = Instant::now();
+                    let changes = diff_containers(&self.containers, &containers);
+                    self.expected_changes.prune(now);
+                    for change in changes.state_changed.iter() {
+                        self.highlights
+                            .insert(change.id.clone(), now + HIGHLIGHT_DURATION);
+                    }
+                    let announcements =
+                        changes.announcements(|name| self.expected_changes.contains(name, now));
+                    self.toasts.extend(
+                        announcements
+                            .into_iter()
+                            .map(|message| Toast::new(message, now)),
+                    );
+                }
+                self.containers = containers;
+                self.containers_changed();
+                self.last_refresh = Some(unix_now() as i64);
+                self.connected = Some(true);
+                if let Some(BuildPhase::Created) = self.build_job.as_ref().map(|job| &job.phase) {
+                    self.build_job = None;
+                }
+                let follow_up = self.refresh.follow_up();
+                let check_updates = if self.preferences.check_updates {
+                    self.check_updates()
+                } else {
+                    Command::none()
+                };
+                Command::batch([
+                    run(Message::Container(ContainerMsg::GetVolumes)),
+                    self.refresh_inspects(),
+                    check_updates,
+                    self.refresh_containers(follow_up),
+                    scrollable::scroll_to(sidebar_id(), self.sidebar_offset),
+                ])
+            }
+            ContainerMsg::InspectLoaded(id, result) => {
+                // Turning developer mode off dropped the cache, the late result isn't wanted
+                if !self.preferences.developer_mode {
+                    return Command::none();
+                }
+                let database = self
+                    .containers
+                    .iter()
+                    .find(|container| container.id == id)
+                    .and_then(|container| self.database_for(container));
+                let inspect = match result {
+                    Ok(mut json) => {
+                        redact_env(&mut json, &|key: &str| is_secret_key(database, key));
+                        match serde_json::to_string_pretty(&json) {
+                            Ok(pretty) => RawInspect::Loaded(pretty),
+                            Err(ex) => {
+                                RawInspect::Failed(format!("Could not format the inspect: {ex}"))
+                            }
+                        }
+                    }
+                    Err(ex) => RawInspect::Failed(format!("Could not inspect the container: {ex}")),
+                };
+                self.raw_inspects.insert(id, inspect);
+                Command::none()
+            }
+            ContainerMsg::LimitsChanged(id, form) => {
+                match form {
+                    Some(form) => _ = self.limit_forms.insert(id, *form),
+                    None => _ = self.limit_forms.remove(&id),
+                }
+                Command::none()
+            }
+            ContainerMsg::ApplyLimits(id) => {
+                let Some(container) = self.containers.iter().find(|c| c.id == id) else {
+                    return Command::none();
+                };
+                let Some(form) = self.limit_forms.get_mut(&id) else {
+                    return Command::none();
+                };
+                let Some(changes) = form.changes(&container.limits) else {
+                    return Command::none();
+                };
+                if changes.is_empty() {
+                    self.limit_forms.remove(&id);
+                    return Command::none();
+                }
+                form.applying = true;
+                form.error = None;
+                Command::perform(
+                    update_container_limits(id.clone(), changes, self.docker),
+                    move |result| Message::Container(ContainerMsg::LimitsApplied(id, result)),
+                )
+            }
+            ContainerMsg::LimitsApplied(id, result) => {
+                match result {
+                    Ok(()) => _ = self.limit_forms.remove(&id),
+                    Err(refused) => {
+                        if let Some(form) = self.limit_forms.get_mut(&id) {
+                            form.applying = false;
+                            form.error = Some(refused);
+                        }
+                    }
+                }
+                // The limits before a refused one were applied, so it is inspected either way
+                self.refresh_container(id)
+            }
+            ContainerMsg::RefreshContainers(ids) => {
+                Command::batch(ids.into_iter().map(|id| self.refresh_container(id)))
+            }
+            ContainerMsg::ContainerRefreshed(id, refreshed) => {
+                // The first full refresh lists it anyway
+                if self.last_refresh.is_none() {
+                    return Command::none();
+                }
+                let refreshed = refreshed.map(|container| *container);
+                let new = Vec::from_iter(refreshed.clone());
+                let old = patch_containers(
+                    &mut self.containers,
+                    self.selection.as_mut(),
+                    &id,
+                    refreshed,
+                );
+                if old.is_empty() && new.is_empty() {
+                    return Command::none();
+                }
+
+                let now = Instant::now();
+                let changes = diff_containers(&old, &new);
+                self.expected_changes.prune(now);
+                for change in changes.state_changed.iter() {
+                    self.highlights
+                        .insert(change.id.clone(), now + HIGHLIGHT_DURATION);
+                }
+                let announcements =
+                    changes.announcements(|name| self.expected_changes.contains(name, now));
+                self.toasts.extend(
+                    announcements
+                        .into_iter()
+                        .map(|message| Toast::new(message, now)),
+                );
+                self.containers_changed();
+                Command::none()
+            }
+            ContainerMsg::GetVolumes => {
+                Command::perform(get_volumes(self.docker), |result| match result {
+                    Err(ex) => failed("Could not get volumes", &ex),
+                    Ok(volumes) => Message::Container(ContainerMsg::VolumesLoaded(volumes)),
+                })
+            }
+            ContainerMsg::VolumesLoaded(volumes) => {
+                self.volumes = volumes;
+                Command::none()
+            }
+            ContainerMsg::RemoveVolume(name) => {
+                Command::perform(remove_volume(name, self.docker), |result| match result {
+                    Err(ex) => failed("Could not remove volume", &ex),
+                    Ok(_) => Message::Container(ContainerMsg::GetVolumes),
+                })
+            }
+            ContainerMsg::StartContainer(id) => {
+                self.expect_change(&self.container_name(&id));
+                self.auto_stop.cancel(&id);
+                self.touch(&id);
+                self.starts.sent(id.clone(), unix_now() as i64);
+                let retry = Message::Container(ContainerMsg::StartContainer(id.clone()));
+                Command::perform(
+                    start_container(id.clone(), self.docker),
+                    |result| match result {
+                        Err(ex) => {
+                            failed_with_retry("Could not start docker container", &ex, retry)
+                        }
+                        Ok(_) => Message::Container(ContainerMsg::RefreshContainers(vec![id])),
+                    },
+                )
+            }
+            ContainerMsg::StopContainer(id) => {
+                let name = self.container_name(&id);
+                let dependents = self.running_dependents(std::slice::from_ref(&name));
+                // Retrying asks about the dependents again, some of them may have stopped
+                let retry = Message::Container(ContainerMsg::StopContainer(id.clone()));
+                let mut ids = vec![id];
+                if !dependents.is_empty() {
+                    let list = dependents.join(", ");
+                    let stop_dependents = native_dialog::MessageDialog::new()
+                        .set_title("Stop container")
+                        .set_text(&format!("{list} depend on {name}. Stop them too?"))
+                        .set_type(native_dialog::MessageType::Warning)
+                        .show_confirm();
+                    match stop_dependents {
+                        Ok(true) => {
+                            ids = self
+                                .stop_order(dependents.into_iter().chain([name]).collect())
+                                .iter()
+                                .filter_map(|name| self.container_id(name))
+                                .collect();
+                        }
+                        Ok(false) => match native_dialog::MessageDialog::new()
+                            .set_title("Stop container")
+                            .set_text(&format!("Stop only {name}? {list} may stop working."))
+                            .set_type(native_dialog::MessageType::Warning)
+                            .show_confirm()
+                        {
+                            Ok(true) => {}
+                            Ok(false) => return Command::none(),
+                            Err(ex) => return error(format!("Could not confirm: {ex}")),
+                        },
+                        Err(ex) => return error(format!("Could not confirm: {ex}")),
+                    }
+                }
+
+                let now = Instant::now();
+                for id in ids.iter() {
+                    self.expect_change(&self.container_name(id));
+                    self.auto_stop.cancel(id);
+                    if self.idle_prompt.as_ref() == Some(id) {
+                        self.idle_prompt = None;
+                    }
+                    let grace = self.stop_grace(id).unwrap_or(DEFAULT_GRACE);
+                    self.stops.begin(id.clone(), grace, now);
+                }
+                let stops = ids
+                    .into_iter()
+                    .map(|id| {
+                        let grace = self.stop_grace(&id);
+                        (id, grace)
+                    })
+                    .collect::<Vec<_>>();
+                let docker = self.docker;
+                Command::perform(
+                    async move {
+                        let mut stopped = vec![];
+                        for (id, grace) in stops {
+                            stop_container(id.clone(), grace, docker).await?;
+                            stopped.push(id);
+                        }
+                        Ok::<_, anyhow::Error>(stopped)
+                    },
+                    |result| match result {
+                        Err(ex) => failed_with_retry("Could not stop docker container", &ex, retry),
+                        Ok(stopped) => Message::Container(ContainerMsg::RefreshContainers(stopped)),
+                    },
+                )
+            }
+            ContainerMsg::ForceStopContainer(id) => {
+                let name = self.container_name(&id);
+                let confirmed = native_dialog::MessageDialog::new()
+                    .set_title("Force stop container")
+                    .set_text(&format!(
+                        "Force stopping kills {name} without letting the database shut down. \
+                         Writes it didn't flush to disk yet can be lost and it may have to \
+                         recover when it starts again. Force stop {name}?"
+                    ))
+                    .set_type(native_dialog::MessageType::Warning)
+                    .show_confirm();
+                match confirmed {
+                    Ok(true) => {}
+                    Ok(false) => return Command::none(),
+                    Err(ex) => return error(format!("Could not confirm: {ex}")),
+                }
+
+                self.expect_change(&name);
+                self.stops.kill(&id, Instant::now());
+                Command::perform(
+                    kill_container(id.clone(), self.docker),
+                    move |result| match result {
+                        Err(ex) => Message::Container(ContainerMsg::ForceStopFailed(
+                            id.clone(),
+                            Box::new(explain("Could not force stop docker container", &ex)),
+                        )),
+                        Ok(_) => {
+                            Message::Container(ContainerMsg::RefreshContainers(vec![id.clone()]))
+                        }
+                    },
+                )
+            }
+            ContainerMsg::ForceStopFailed(id, ex) => {
+                self.stops.finish(&id);
+                run(Message::Ui(UiMsg::Failed(ex)))
+            }
+            ContainerMsg::ExportDefinition(id, include_secrets) => {
+                let Some(container) = self.containers.iter().find(|container| container.id == id)
+                else {
+                    return Command::none();
+                };
+                let definition = ContainerDefinition::new(
+                    container,
+                    self.database_for(container),
+                    include_secrets,
+                );
+                let yaml = match definition.to_yaml() {
+                    Ok(yaml) => yaml,
+                    Err(ex) => return error(format!("Could not export the definition: {ex}")),
+                };
+
+                let path = match native_dialog::FileDialog::new()
+                    .set_filename(&format!(
+                        "{}.db-mgr.yaml",
+                        container.name.trim_start_matches('/')
+                    ))
+                    .add_filter("Container definition", &["yaml", "yml"])
+                    .show_save_single_file()
+                {
+                    Ok(Some(path)) => path,
+                    Ok(None) => return Command::none(),
+                    Err(ex) => return error(format!("Could not choose a file: {ex}")),
+                };
+
+                match std::fs::write(&path, yaml) {
+                    Ok(_) => Command::none(),
+                    Err(ex) => error(format!("Could not write {}: {ex}", path.display())),
+                }
+            }
+            ContainerMsg::CopyRunCommand(id, options) => {
+                let Some(container) = self.containers.iter().find(|container| container.id == id)
+                else {
+                    return Command::none();
+                };
+                Command::perform(
+                    get_image_defaults(container.image.clone(), self.docker),
+                    move |result| match result {
+                        Err(ex) => failed("Could not inspect the image of the container", &ex),
+                        Ok(image) => Message::Container(ContainerMsg::RunCommandImageLoaded(
+                            id.clone(),
+                            options,
+                            Box::new(image),
+                        )),
+                    },
+                )
+            }
+            ContainerMsg::RunCommandImageLoaded(id, options, image) => {
+                let Some(container) = self.containers.iter().find(|container| container.id == id)
+                else {
+                    return Command::none();
+                };
+                let command =
+                    docker_run_command(container, self.database_for(container), &image, options);
+                self.toasts.push(Toast::new(
+                    format!(
+                        "Copied the docker run command of {}",
+                        container.name.trim_start_matches('/')
+                    ),
+                    Instant::now(),
+                ));
+                iced::clipboard::write(command)
+            }
+            ContainerMsg::ExportLogs(request) => {
+                let path = match native_dialog::FileDialog::new()
+                    .set_filename(&format!("{}.log", request.name))
+                    .add_filter("Log file", &["log", "txt"])
+                    .show_save_single_file()
+                {
+                    Ok(Some(path)) => path,
+                    Ok(None) => return Command::none(),
+                    Err(ex) => return error(format!("Could not choose a file: {ex}")),
+                };
+
+                Command::perform(
+                    export_logs(request.id, request.range, path, request.split, self.docker),
+                    |result| match result {
+                        Err(ex) => failed("Could not export logs", &ex),
+                        Ok(export) => Message::Container(ContainerMsg::LogsExported(export)),
+                    },
+                )
+            }
+            ContainerMsg::ViewEnvValue(id, key) => {
+                Command::perform(get_env_value(id, key.clone(), self.docker), move |result| {
+                    match result {
+                        Err(ex) => failed(&format!("Could not read {key}"), &ex),
+                        Ok(value) => {
+                            Message::Container(ContainerMsg::EnvValueLoaded(key.clone(), value))
+                        }
+                    }
+                })
+            }
+            ContainerMsg::EnvValueLoaded(key, value) => {
+                self.expanded_env.insert(key, value);
+                Command::none()
+            }
+            ContainerMsg::RecreateContainer(id, config) => {
+                let skipped = self
+                    .containers
+                    .iter()
+                    .find(|container| container.id == id)
+                    .and_then(|container| {
+                        Some(self.database_for(container)?.skipped_variables(container))
+                    })
+                    .unwrap_or_default();
+                let warning = if skipped.is_empty() {
+                    String::new()
+                } else {
+                    format!(
+                        "\n\n{} will not be copied, their values are too large or binary.",
+                        skipped.join(", ")
+                    )
+                };
+
+                if self.block_version_jump(Some(&id), &config) {
+                    return Command::none();
+                }
+                self.replace_container(
+                    id,
+                    format!(
+                        "Recreate {} with the template values? The container will be removed, its volumes are kept.{warning}",
+                        config.name
+                    ),
+                    *config,
+                )
+            }
+            ContainerMsg::QueryChanged(id, query) => {
+                self.query_consoles.entry(id).or_default().set_input(query);
+                Command::none()
+            }
+            ContainerMsg::RunQuery(id) => {
+                let Some(container) = self.containers.iter().find(|container| container.id == id)
+                else {
+                    return Command::none();
+                };
+                let Some(database) = self.database_for(container).cloned() else {
+                    return Command::none();
+                };
+                let console = self.query_consoles.entry(id.clone()).or_default();
+                let Some(query) = console.submit() else {
+                    return Command::none();
+                };
+                let Some(args) = database.query_args(container, &query) else {
+                    console.running = false;
+                    return Command::none();
+                };
+
+                Command::perform(exec(id.clone(), args, self.docker), move |result| {
+                    Message::Container(ContainerMsg::QueryFinished(
+                        id.clone(),
+                        result.map_err(|ex| explain("Could not run the query", &ex).to_string()),
+                    ))
+                })
+            }
+            ContainerMsg::QueryFinished(id, output) => {
+                self.query_consoles.entry(id).or_default().finish(output);
+                Command::none()
+            }
+            ContainerMsg::LogEvent(event) => {
+                let Some(follow) = self.log_follow.as_mut() else {
+                    return Command::none();
+                };
+                match event {
+                    LogEvent::Line { stderr, line } => {
+                        follow.push(LogLine::parse(&line, stderr));
+                        self.snap_logs()
+                    }
+                    LogEvent::Ended(reason) => {
+                        follow.ended.get_or_insert(match reason {
+                            Some(ex) => format!("Stopped following the logs: {ex}"),
+                            None => "The container stopped, open the tab again to follow it once it is started".to_string(),
+                        });
+                        Command::none()
+                    }
+                }
+            }
+            ContainerMsg::LogFilterChanged(id, filter) => {
+                match self.log_follow.as_mut() {
+                    Some(follow) if follow.container == id => follow.filter = filter,
+                    _ => return Command::none(),
+                }
+                self.snap_logs()
+            }
+            ContainerMsg::LogsScrolled(id, at_bottom) => {
+                if let Some(follow) = self
+                    .log_follow
+                    .as_mut()
+                    .filter(|follow| follow.container == id)
+                {
+                    follow.following = at_bottom;
+                }
+                Command::none()
+            }
+            ContainerMsg::JumpToLatest(id) => {
+                if let Some(follow) = self
+                    .log_follow
+                    .as_mut()
+                    .filter(|follow| follow.container == id)
+                {
+                    follow.following = true;
+                }
+                self.snap_logs()
+            }
+            ContainerMsg::ContainerEvent(event) => {
+                match event.kind {
+                    ContainerEventKind::Died(_) | ContainerEventKind::Stopped => {
+                        self.stops.finish(&event.container);
+                        self.starts.stopped(&event.container);
+                    }
+                    ContainerEventKind::Started => {
+                        self.starts
+                            .reported(event.container.clone(), unix_now() as i64);
+                    }
+                    _ => {}
+                }
+                // A kill is followed by the container dying, which is refreshed instead
+                let refresh = match event.kind {
+                    ContainerEventKind::Killed(_) => Command::none(),
+                    _ => self.refresh_container(event.container.clone()),
+                };
+                self.events.push(event);
+                refresh
+            }
+            ContainerMsg::EventsBackfilled(id, result) => {
+                match result {
+                    Ok(events) => events.into_iter().for_each(|event| self.events.push(event)),
+                    Err(ex) => {
+                        eprintln!("Could not get the events of {id}: {ex}");
+                        self.events.backfill_failed(&id);
+                    }
+                }
+                Command::none()
+            }
+            ContainerMsg::SetAutoStop(id, after) => {
+                match after {
+                    Some(after) => self.auto_stop.set(id, after, Instant::now()),
+                    None => self.auto_stop.cancel(&id),
+                }
+                Command::none()
+            }
+            ContainerMsg::CancelDrop => {
+                self.pending_drop = None;
+                Command::none()
+            }
+            ContainerMsg::RunSqlFile(id, path) => {
+                self.pending_drop = None;
+                let Some(container) = self.containers.iter().find(|container| container.id == id)
+                else {
+                    return Command::none();
+                };
+                let Some(database) = self.database_for(container).cloned() else {
+                    return Command::none();
+                };
+                let name = container.name.trim_start_matches('/').to_string();
+
+                let confirmed = native_dialog::MessageDialog::new()
+                    .set_title("Run SQL file")
+                    .set_text(&confirm_prompt(&path, &name))
+                    .set_type(native_dialog::MessageType::Info)
+                    .show_confirm()
+                    .unwrap_or(false);
+                if !confirmed {
+                    return Command::none();
+                }
+
+                self.sql_runs += 1;
+                if let Some(args) = database.restore_args(container) {
+                    self.sql_run = Some(SqlRun::new(id, name, path, Some(args), self.sql_runs));
+                    return Command::none();
+                }
+
+                let sql = match std::fs::read_to_string(&path) {
+                    Ok(sql) => sql,
+                    Err(ex) => return error(format!("Could not read {}: {ex}", path.display())),
+                };
+                let Some(args) = database.query_args(container, &sql) else {
+                    return Command::none();
+                };
+                self.sql_run = Some(SqlRun::new(id.clone(), name, path, None, self.sql_runs));
+                Command::perform(exec(id, args, self.docker), |result| {
+                    Message::Container(ContainerMsg::SqlQueryFinished(
+                        result.map_err(|ex| format!("{ex}")),
+                    ))
+                })
+            }
+            ContainerMsg::SqlRunEvent(event) => {
+                let Some(sql_run) = self.sql_run.as_mut() else {
+                    return Command::none();
+                };
+                match event {
+                    InputExecEvent::Progress(written, total) => {
+                        sql_run.progress = (written, total);
+                        Command::none()
+                    }
+                    InputExecEvent::Output(output) => {
+                        sql_run.push_output(&output);
+                        Command::none()
+                    }
+                    InputExecEvent::Ended(exit_code) => self.finish_sql_run(exit_code),
+                    InputExecEvent::Error(ex) => {
+                        let file = file_name(&sql_run.file);
+                        self.sql_run = None;
+                        error(format!("Could not run {file}: {ex}"))
+                    }
+                }
+            }
+            ContainerMsg::SqlQueryFinished(result) => match result {
+                Ok(output) => {
+                    if let Some(sql_run) = self.sql_run.as_mut() {
+                        sql_run.push_output(&output.stdout);
+                        sql_run.push_output(&output.stderr);
+                    }
+                    self.finish_sql_run(output.exit_code)
+                }
+                Err(ex) => {
+                    let file = self
+                        .sql_run
+                        .take()
+                        .map(|sql_run| file_name(&sql_run.file))
+                        .unwrap_or_default();
+                    error(format!("Could not run {file}: {ex}"))
+                }
+            },
+            ContainerMsg::IdleCheck(_) => {
+                self.check_idle();
+                Command::none()
+            }
+            ContainerMsg::RemindIdleLater => {
+                self.idle_prompt = None;
+                Command::none()
+            }
+            ContainerMsg::IgnoreIdle(id) => {
+                self.preferences.idle.ignore(&id);
+                write_preferences(self.paths.as_ref(), &self.preferences);
+                self.idle_prompt = None;
+                Command::none()
+            }
+            ContainerMsg::QueryHistory(older) => {
+                if let MainViewState::ViewContainer(id) = &self.main_view {
+                    let console = self.query_consoles.entry(id.clone()).or_default();
+                    if older {
+                        console.previous();
+                    } else {
+                        console.next();
+                    }
+                }
+                Command::none()
+            }
+            ContainerMsg::MigrateContainer(id) => {
+                let Some(container) = self.containers.iter().find(|container| container.id == id)
+                else {
+                    return Command::none();
+                };
+                let config = migration_config(container, self.database_for(container));
+                let changes = self
+                    .migrations
+                    .get(&id)
+                    .map(|plan| plan.changes.join("\n"))
+                    .unwrap_or_default();
+
+                self.replace_container(
+                    id,
+                    format!(
+                        "Update the metadata of {}? The container will be removed and recreated, its volumes are kept.\n\n{changes}",
+                        config.name
+                    ),
+                    config,
+                )
+            }
+            ContainerMsg::LogsExported(export) => {
+                let files = export
+                    .files
+                    .iter()
+                    .map(|file| file.display().to_string())
+                    .join(", ");
+                if let Err(dialog_err) = native_dialog::MessageDialog::new()
+                    .set_text(&format!(
+                        "Wrote {} lines ({}) to {files}",
+                        export.lines,
+                        format_size(export.bytes as i64)
+                    ))
+                    .set_type(native_dialog::MessageType::Info)
+                    .show_alert()
+                {
+                    eprintln!("Dialog Error: {dialog_err}");
+                }
+                Command::none()
+            }
+            ContainerMsg::RecreateWithProject(id, project) => {
+                let Some(container) = self.containers.iter().find(|container| container.id == id)
+                else {
+                    return Command::none();
+                };
+                let mut config = migration_config(container, self.database_for(container));
+                config.project = Some(project).filter(|project| !project.is_empty());
+                let prompt = match config.project.as_ref() {
+                    Some(project) => format!("Recreate {} in project {project}?", config.name),
+                    None => format!("Recreate {} without a project?", config.name),
+                };
+                match native_dialog::MessageDialog::new()
+                    .set_title("Change project")
+                    .set_text(&format!(
+                        "{prompt} The project is a label, so the container is removed and recreated, its volumes are kept."
+                    ))
+                    .set_type(native_dialog::MessageType::Warning)
+                    .show_confirm()
+                {
+                    Ok(true) => {}
+                    Ok(false) => return Command::none(),
+                    Err(ex) => return error(format!("Could not confirm: {ex}")),
+                }
+
+                // The label is the project from now on
+                if self.preferences.projects.remove(&config.name).is_some() {
+                    write_preferences(self.paths.as_ref(), &self.preferences);
+                }
+                self.recreate_with(id, config)
+            }
+            ContainerMsg::RunBulk(action) => {
+                let mut selected = self
+                    .containers
+                    .iter()
+                    .filter(|container| {
+                        self.selection
+                            .as_ref()
+                            .is_some_and(|selection| selection.contains(&container.id))
+                    })
+                    .map(|container| {
+                        (
+                            container.id.clone(),
+                            container.name.trim_start_matches('/').to_string(),
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                if selected.is_empty() {
+                    return Command::none();
+                }
+
+                let selected_names = selected
+                    .iter()
+                    .map(|(_, name)| name.clone())
+                    .collect::<Vec<_>>();
+                let mut dependents = self.running_dependents(&selected_names);
+                if action == BulkAction::Stop {
+                    let mut names = selected_names;
+                    if !dependents.is_empty() {
+                        match native_dialog::MessageDialog::new()
+                            .set_title("Stop containers")
+                            .set_text(&format!(
+                                "{} depend on the selected containers. Stop them too?",
+                                dependents.join(", ")
+                            ))
+                            .set_type(native_dialog::MessageType::Warning)
+                            .show_confirm()
+                        {
+                            Ok(true) => names.extend(dependents.iter().cloned()),
+                            Ok(false) => {}
+                            Err(ex) => return error(format!("Could not confirm: {ex}")),
+                        }
+                    }
+                    selected = self
+                        .stop_order(names)
+                        .into_iter()
+                        .filter_map(|name| Some((self.container_id(&name)?, name)))
+                        .collect();
+                }
+
+                if action == BulkAction::Remove {
+                    let companions = self
+                        .containers
+                        .iter()
+                        .filter(|container| {
+                            container.companion_of().is_some_and(|parent| {
+                                selected.iter().any(|(_, name)| name == parent)
+                            }) && !selected.iter().any(|(id, _)| *id == container.id)
+                        })
+                        .map(|container| {
+                            (
+                                container.id.clone(),
+                                container.name.trim_start_matches('/').to_string(),
+                            )
+                        })
+                        .collect::<Vec<_>>();
+                    if !companions.is_empty() {
+                        match native_dialog::MessageDialog::new()
+                            .set_title("Remove containers")
+                            .set_text(&format!(
+                                "{} are admin UIs of the selected containers. Remove them too?",
+                                companions.iter().map(|(_, name)| name).join(", ")
+                            ))
+                            .set_type(native_dialog::MessageType::Warning)
+                            .show_confirm()
+                        {
+                            Ok(true) => {
+                                dependents.retain(|dependent| {
+                                    !companions.iter().any(|(_, name)| name == dependent)
+                                });
+                                selected.extend(companions);
+                            }
+                            Ok(false) => {}
+                            Err(ex) => return error(format!("Could not confirm: {ex}")),
+                        }
+                    }
+
+                    let names = selected.iter().map(|(_, name)| name.as_str()).join("\n");
+                    let warning = match dependents.is_empty() {
+                        true => String::new(),
+                        false => format!(
+                            "\n\n{} depend on them and may stop working.",
+                            dependents.join(", ")
+                        ),
+                    };
+                    match native_dialog::MessageDialog::new()
+                        .set_title("Remove containers")
+                        .set_text(&format!(
+                            "Remove these {} containers? Their volumes are kept.\n\n{names}{warning}",
+                            selected.len()
+                        ))
+                        .set_type(native_dialog::MessageType::Warning)
+                        .show_confirm()
+                    {
+                        Ok(true) => {}
+                        Ok(false) => return Command::none(),
+                        Err(ex) => return error(format!("Could not confirm: {ex}")),
+                    }
+                }
+
+                for (id, name) in selected.iter() {
+                    self.expected_changes.expect(name, Instant::now());
+                    self.auto_stop.cancel(id);
+                    if action == BulkAction::Start {
+                        self.starts.sent(id.clone(), unix_now() as i64);
+                    }
+                }
+                // Dependents wait for their databases to be healthy
+                if action == BulkAction::Start {
+                    self.selection = None;
+                    let run = self.start_run.as_ref().map_or(0, |run| run.run + 1);
+                    let names = selected.into_iter().map(|(_, name)| name).collect();
+                    self.start_run = Some(StartRun::new(run, names));
+                    return Command::none();
+                }
+                Command::perform(run_bulk(self.docker, action, selected), move |results| {
+                    Message::Container(ContainerMsg::BulkDone(action, results))
+                })
+            }
+            ContainerMsg::BulkDone(action, results) => {
+                self.selection = None;
+                if let Err(dialog_err) = native_dialog::MessageDialog::new()
+                    .set_text(&summary(action, &results))
+                    .set_type(if results.iter().all(|result| result.error.is_none()) {
+                        native_dialog::MessageType::Info
+                    } else {
+                        native_dialog::MessageType::Warning
+                    })
+                    .show_alert()
+                {
+                    eprintln!("Dialog Error: {dialog_err}");
+                }
+                let ids = results.into_iter().map(|result| result.id).collect();
+                run(Message::Container(ContainerMsg::RefreshContainers(ids)))
+            }
+            ContainerMsg::StartProgress(event) => {
+                let Some(start_run) = self.start_run.as_mut() else {
+                    return Command::none();
+                };
+                // The run names its containers, the rest of the app goes by id
+                let names = match &event {
+                    StartEvent::Started(name) | StartEvent::Waiting(name) => vec![name.clone()],
+                    StartEvent::Done => start_run.names(),
+                    _ => vec![],
+                };
+                start_run.apply(event, Instant::now());
+                if !start_run.running && start_run.succeeded() && start_run.warning.is_none() {
+                    self.start_run = None;
+                }
+                match names.is_empty() {
+                    true => Command::none(),
+                    false => run(Message::Container(ContainerMsg::RefreshContainers(
+                        self.ids_of(&names),
+                    ))),
+                }
+            }
+            ContainerMsg::CancelStart => {
+                let names = self.start_run.take().map(|run| run.names());
+                let ids = self.ids_of(&names.unwrap_or_default());
+                run(Message::Container(ContainerMsg::RefreshContainers(ids)))
+            }
+            ContainerMsg::TerminalCommandChanged(id, command) => {
+                self.terminal_for(id).command = command;
+                Command::none()
+            }
+            ContainerMsg::TerminalInputChanged(id, input) => {
+                self.terminal_for(id).input = input;
+                Command::none()
+            }
+            ContainerMsg::StartTerminal(id) => {
+                self.terminal_for(id).start();
+                Command::none()
+            }
+            ContainerMsg::SendTerminalInput(id) => match self.terminal_for(id).submit() {
+                Err(ex) => error(ex),
+                Ok(_) => Command::none(),
+            },
+            ContainerMsg::StopTerminal(id) => {
+                self.terminal_for(id)
+                    .end("The session was ended".to_string());
+                Command::none()
+            }
+            ContainerMsg::TerminalEvent(event) => {
+                let Some(terminal) = self.terminal.as_mut() else {
+                    return Command::none();
+                };
+
+                match event {
+                    ExecEvent::Started(sender) => terminal.started(sender),
+                    ExecEvent::Output(output) => terminal.push_output(&output),
+                    ExecEvent::Ended(Some(code)) => {
+                        terminal.end(format!("The session exited with code {code}"))
+                    }
+                    ExecEvent::Ended(None) => terminal.end("The session exited".to_string()),
+                    ExecEvent::Error(ex) => terminal.end(format!("The session failed: {ex}")),
+                }
+                Command::none()
+            }
+            ContainerMsg::CleanupSummaryLoaded(summary) => {
+                self.cleanup_summary = Some(summary);
+                Command::none()
+            }
+            ContainerMsg::RunCleanup(summary) => {
+                for item in summary.containers.iter() {
+                    self.expect_change(&item.name);
+                }
+                self.cleanup_results.clear();
+                self.cleanup_subscription = Some(summary);
+                Command::none()
+            }
+            ContainerMsg::CleanupProgress(result) => {
+                self.cleanup_results.push(result);
+                Command::none()
+            }
+            ContainerMsg::SetBackupSchedule(name, schedule) => {
+                match schedule {
+                    // Runs due before the change are not made up for
+                    Some(schedule) => self.preferences.backups.insert(
+                        name,
+                        BackupSchedule {
+                            checked: Local::now().timestamp(),
+                            ..schedule
+                        },
+                    ),
+                    None => self.preferences.backups.remove(&name),
+                };
+                write_preferences(self.paths.as_ref(), &self.preferences);
+                Command::none()
+            }
+            ContainerMsg::ChooseBackupDirectory(name) => {
+                let directory = match native_dialog::FileDialog::new().show_open_single_dir() {
+                    Ok(Some(directory)) => directory,
+                    Ok(None) => return Command::none(),
+                    Err(ex) => return error(format!("Could not choose a directory: {ex}")),
+                };
+
+                let schedule = match self.preferences.backups.get(&name) {
+                    Some(schedule) => BackupSchedule {
+                        directory,
+                        ..schedule.clone()
+                    },
+                    None => BackupSchedule::new(directory, Local::now().timestamp()),
+                };
+                run(Message::Container(ContainerMsg::SetBackupSchedule(
+                    name,
+                    Some(schedule),
+                )))
+            }
+            ContainerMsg::BackupTick(_) => self.run_due_backups(),
+            ContainerMsg::BackupDone(name, at, result) => {
+                self.backups_running.remove(&name);
+                let outcome = match result {
+                    Ok(path) => BackupOutcome::Saved(path),
+                    Err(ex) => {
+                        self.backup_failure =
+                            Some((name.clone(), format!("The backup of {name} failed: {ex}")));
+                        BackupOutcome::Failed(ex)
+                    }
+                };
+                if let Some(schedule) = self.preferences.backups.get_mut(&name) {
+                    schedule.record(at, outcome);
+                    write_preferences(self.paths.as_ref(), &self.preferences);
+                }
+                Command::none()
+            }
+            ContainerMsg::RetryBackup(name) => {
+                // A second click while the retry runs does nothing
+                if self.backups_running.contains(&name) {
+                    return Command::none();
+                }
+                let Some(schedule) = self.preferences.backups.get(&name) else {
+                    return Command::none();
+                };
+                let Some(container) = self
+                    .containers
+                    .iter()
+                    .find(|container| container.name.trim_start_matches('/') == name)
+                else {
+                    return error(format!("{name} no longer exists"));
+                };
+                if container.state != ContainerStateStatusEnum::RUNNING {
+                    return error(format!("Start {name} to back it up"));
+                }
+                let Some(backup) = backup_command(
+                    self.docker,
+                    &self.images,
+                    container,
+                    schedule,
+                    Local::now().timestamp(),
+                ) else {
+                    return error(format!("The config entry of {name} has no backup command"));
+                };
+                self.backup_failure = None;
+                self.backups_running.insert(name);
+                backup
+            }
+            ContainerMsg::DismissBackupFailure => {
+                self.backup_failure = None;
+                Command::none()
+            }
+            ContainerMsg::CheckUpdates | ContainerMsg::UpdateTick(_) => self.check_updates(),
+            ContainerMsg::DigestsChecked(digests) => {
+                self.checking_updates = false;
+                let now = unix_now();
+                for (image, digest) in digests {
+                    self.preferences.digests.insert(&image, digest, now);
+                }
+                write_preferences(self.paths.as_ref(), &self.preferences);
+                Command::none()
+            }
+            ContainerMsg::UpdateContainer(id) => {
+                let Some(container) = self.containers.iter().find(|container| container.id == id)
+                else {
+                    return Command::none();
+                };
+                let config = migration_config(container, self.database_for(container));
+                let prompt = format!(
+                    "Update {} to the newest {}? The image is pulled again and the container is removed and recreated, its volumes are kept.",
+                    config.name, container.image
+                );
+                if self.block_version_jump(Some(&id), &config) {
+                    return Command::none();
+                }
+
+                self.replace_container(id, prompt, config)
+            }
+            ContainerMsg::RunPrefetch(images) => {
+                if !self.prefetch.as_ref().is_some_and(|run| run.running) {
+                    self.prefetch = Some(PrefetchRun::new(images));
+                }
+                Command::none()
+            }
+            ContainerMsg::PrefetchProgress(event) => {
+                if let Some(run) = self.prefetch.as_mut() {
+                    run.apply(event, Instant::now());
+                }
+                Command::none()
+            }
+            ContainerMsg::CleanupDone => {
+                self.cleanup_subscription = None;
+                Command::batch([
+                    run(Message::Container(ContainerMsg::GetContainers)),
+                    Command::perform(get_cleanup_summary(self.docker), |result| match result {
+                        Err(ex) => failed("Could not get db-mgr resources", &ex),
+                        Ok(summary) => {
+                            Message::Container(ContainerMsg::CleanupSummaryLoaded(summary))
+                        }
+                    }),
+                ])
+            }
+        }
+    }
+}
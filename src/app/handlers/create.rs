@@ -0,0 +1,515 @@
+use futures::future;
+use iced::Command;
+use itertools::Itertools;
+use std::time::Instant;
+
+use super::{ContainerMsg, UiMsg};
+use crate::{
+    app::{
+        add_container::{prepare_config, AddContainerState},
+        build_card::{BuildJob, BuildPhase},
+        cleanup::format_size,
+        credentials::QuickCredentials,
+        error, failed,
+        pull_progress::PullProgress,
+        quick_start::QuickStartState,
+        run, DbMgrApp, MainViewState, Message,
+    },
+    data::{is_secret_key, write_preferences},
+    definition::ContainerDefinition,
+    docker::{
+        check_started, disk_status, remove_container, remove_created, DbContainerConfig, DiskSpace,
+        DiskStatus, PlannedOp, StartFailure,
+    },
+    env_file::parse_env_file,
+    errors::{explain_message, rejected_sysctl},
+    idle::unix_now,
+    names::generate_name,
+    ports::{is_free, pick_port, taken_ports},
+};
+
+/// Messages of creating containers, from the forms to the pull, build and start of the new
+/// container.
+#[derive(Clone, Debug)]
+pub enum CreateMsg {
+    /// The free disk space, checked on start and before creating the container.
+    DiskChecked(Option<DiskSpace>, Option<Box<DbContainerConfig>>),
+    OverrideDiskCheck(bool),
+    ShowCreateContainer,
+    CreateContainer(Box<DbContainerConfig>),
+    DraftChanged(Box<AddContainerState>),
+    /// Saves the create form if it changed since it was last saved.
+    SaveDraft(Instant),
+    /// Opens the create form saved before db-mgr was last closed.
+    ResumeDraft,
+    DiscardDraft,
+    /// Empties the create form and deletes its saved draft.
+    DiscardForm,
+    PullingContainer,
+    BuildingContainer,
+    StartingContainer,
+    BuildError(String),
+    /// A container with the name of the new one exists, with its id and whether it may be
+    /// replaced.
+    NameConflict(String, bool),
+    /// Removes the conflicting container and creates the new one in its place.
+    ReplaceConflict,
+    ConflictReplaced,
+    RetryBuild,
+    DismissBuild,
+    /// The container with the given id was created.
+    CreatedContainer(String),
+    /// Whether the new container stayed up, `None` if it did.
+    StartChecked(Option<Box<StartFailure>>),
+    KeepFailedContainer,
+    DeleteFailedContainer,
+    FailedContainerDeleted,
+    /// A layer id with its downloaded and total bytes.
+    ImageDownload(String, u64, u64),
+    /// Redraws the pull progress so stalled pulls are noticed.
+    PullTick(Instant),
+    /// A line printed while building a custom image.
+    BuildOutput(String),
+    /// The docker calls a dry run would have made.
+    Planned(Vec<PlannedOp>),
+    ShowQuickStart,
+    QuickStartChanged(Box<QuickStartState>),
+    QuickCreate,
+    /// Creates the recorded container of this name again.
+    RecreateDeleted(String),
+    ForgetDeleted(String),
+    DismissVersionWarning,
+    /// Creates the companion at this index of the offered ones.
+    CreateCompanion(usize),
+    DismissCompanionOffer,
+    /// Opens the create form filled in from a definition file.
+    ImportDefinition,
+    /// Fills the create form's variables in from a `.env` file.
+    ImportEnvFile,
+}
+
+impl DbMgrApp {
+    pub fn handle_create_msg(&mut self, msg: CreateMsg) -> Command<Message> {
+        match msg {
+            CreateMsg::ShowCreateContainer => {
+                self.main_view = MainViewState::CreateContainer;
+                self.create_draft = AddContainerState::default();
+                self.quick_credentials = None;
+                Command::batch([self.write_state(Instant::now()), self.load_engine_info()])
+            }
+            CreateMsg::ImportDefinition => {
+                let path = match native_dialog::FileDialog::new()
+                    .add_filter("Container definition", &["yaml", "yml"])
+                    .show_open_single_file()
+                {
+                    Ok(Some(path)) => path,
+                    Ok(None) => return Command::none(),
+                    Err(ex) => return error(format!("Could not choose a file: {ex}")),
+                };
+                let definition = std::fs::read_to_string(&path)
+                    .map_err(anyhow::Error::from)
+                    .and_then(|yaml| ContainerDefinition::from_yaml(&yaml));
+                let definition = match definition {
+                    Ok(definition) => definition,
+                    Err(ex) => {
+                        return error(format!("Could not import {}: {ex:#}", path.display()))
+                    }
+                };
+
+                let (config, database, warnings) = definition.into_config(&self.images);
+                self.create_draft = AddContainerState::imported(config, database, warnings);
+                self.main_view = MainViewState::CreateContainer;
+                self.quick_credentials = None;
+                self.load_engine_info()
+            }
+            CreateMsg::ImportEnvFile => {
+                // .env files often have no extension, so every file is offered
+                let path = match native_dialog::FileDialog::new().show_open_single_file() {
+                    Ok(Some(path)) => path,
+                    Ok(None) => return Command::none(),
+                    Err(ex) => return error(format!("Could not choose a file: {ex}")),
+                };
+                match std::fs::read_to_string(&path) {
+                    Ok(contents) => self.create_draft.import_env(parse_env_file(&contents)),
+                    Err(ex) => return error(format!("Could not read {}: {ex}", path.display())),
+                }
+                Command::none()
+            }
+            CreateMsg::ShowQuickStart => {
+                self.main_view = MainViewState::QuickStart;
+                self.quick_draft = QuickStartState::new(&self.images);
+                Command::none()
+            }
+            CreateMsg::QuickStartChanged(draft) => {
+                self.quick_draft = *draft;
+                Command::none()
+            }
+            CreateMsg::QuickCreate => {
+                let Some(database) = self.quick_draft.database.as_ref() else {
+                    return Command::none();
+                };
+
+                if self.quick_draft.name.trim().is_empty() {
+                    self.quick_draft.name =
+                        generate_name(&mut rand::thread_rng(), &self.container_names());
+                }
+
+                let config = prepare_config(&database.quick_config(&self.quick_draft.name));
+                self.quick_credentials = Some(QuickCredentials {
+                    name: self.quick_draft.name.clone(),
+                    values: database
+                        .variables
+                        .iter()
+                        .filter_map(|(name, key)| {
+                            Some((name.clone(), config.variables.get(key)?.clone()))
+                        })
+                        .sorted()
+                        .collect(),
+                });
+                self.create_draft = AddContainerState::default();
+                run(Message::Create(CreateMsg::CreateContainer(Box::new(
+                    config,
+                ))))
+            }
+            CreateMsg::DraftChanged(draft) => {
+                self.create_draft = *draft;
+                self.save_draft(Instant::now());
+                Command::none()
+            }
+            CreateMsg::SaveDraft(now) => {
+                if self.draft_pending {
+                    self.save_draft(now);
+                }
+                Command::none()
+            }
+            CreateMsg::ResumeDraft => {
+                let Some(draft) = self.draft_offer.take() else {
+                    return Command::none();
+                };
+                self.create_draft = AddContainerState::resumed(draft, &self.images);
+                self.main_view = MainViewState::CreateContainer;
+                self.quick_credentials = None;
+                self.load_engine_info()
+            }
+            CreateMsg::DiscardDraft => {
+                self.forget_draft();
+                Command::none()
+            }
+            CreateMsg::DiscardForm => {
+                self.create_draft = AddContainerState::default();
+                self.forget_draft();
+                Command::none()
+            }
+            CreateMsg::CreateContainer(mut container_config) => {
+                if self.block_version_jump(None, &container_config) {
+                    return Command::none();
+                }
+                if let Some(assignment) = container_config
+                    .host_port
+                    .as_mut()
+                    .filter(|assignment| assignment.host.is_none())
+                {
+                    let taken = taken_ports(&self.containers);
+                    match pick_port(self.port_range, &taken, is_free) {
+                        Some(port) => assignment.host = Some(port),
+                        None => {
+                            return error(format!(
+                                "No port in {} is free to publish {} on, free one up or widen the ports range in config.yaml",
+                                self.port_range, container_config.name
+                            ))
+                        }
+                    }
+                }
+                if !container_config.dry_run {
+                    self.expect_change(&container_config.name);
+                }
+                self.check_disk_space(Some(*container_config))
+            }
+            CreateMsg::DiskChecked(space, container_config) => {
+                self.disk_space = space;
+                let Some(container_config) = container_config else {
+                    return Command::none();
+                };
+
+                if let Some(space) = space.filter(|space| {
+                    disk_status(*space, self.low_disk_space) == DiskStatus::Critical
+                }) {
+                    if !self.disk_override {
+                        return error(format!(
+                            "Only {} is free on the docker disk, creating {} would most likely fail. Free up some space or tick \"Create containers anyway\".",
+                            format_size(space.available as i64),
+                            container_config.name
+                        ));
+                    }
+                }
+
+                self.build_job = Some(BuildJob::new(*container_config, self.create_draft.clone()));
+                Command::none()
+            }
+            CreateMsg::OverrideDiskCheck(disk_override) => {
+                self.disk_override = disk_override;
+                Command::none()
+            }
+            CreateMsg::BuildError(ex) => {
+                if let Some(job) = self.build_job.as_mut() {
+                    job.phase = BuildPhase::Failed(ex.clone());
+                    // Shown next to the sysctls when the form is reopened with Retry
+                    if let Some(key) = rejected_sysctl(&ex, job.config.sysctls.keys()) {
+                        job.draft.reject_sysctl(&key, &ex);
+                    }
+                }
+                run(Message::Ui(UiMsg::Failed(Box::new(explain_message(
+                    "Could not create the container",
+                    &ex,
+                )))))
+            }
+            CreateMsg::NameConflict(id, can_replace) => {
+                let Some(job) = self.build_job.as_mut() else {
+                    return Command::none();
+                };
+
+                if !can_replace {
+                    let ex = format!("Container name conflict {}", job.config.name);
+                    return run(Message::Create(CreateMsg::BuildError(ex)));
+                }
+                job.phase = BuildPhase::Conflict(id);
+                Command::none()
+            }
+            CreateMsg::ReplaceConflict => {
+                let Some(BuildPhase::Conflict(id)) = self.build_job.as_ref().map(|job| &job.phase)
+                else {
+                    return Command::none();
+                };
+
+                let id = id.clone();
+                self.expect_change(&self.container_name(&id));
+                Command::perform(remove_container(id, self.docker), |result| match result {
+                    Err(ex) => failed("Could not remove the existing container", &ex),
+                    Ok(_) => Message::Create(CreateMsg::ConflictReplaced),
+                })
+            }
+            CreateMsg::ConflictReplaced => {
+                if let Some(job) = self.build_job.as_mut() {
+                    // The volumes of the removed container are mounted again
+                    job.config.reuse_volumes = true;
+                    job.phase = BuildPhase::Creating;
+                }
+                run(Message::Container(ContainerMsg::GetContainers))
+            }
+            CreateMsg::RetryBuild => {
+                if let Some(job) = self.build_job.take() {
+                    self.create_draft = job.draft;
+                    self.main_view = MainViewState::CreateContainer;
+                }
+                Command::none()
+            }
+            CreateMsg::DismissBuild => {
+                self.build_job = None;
+                self.quick_credentials = None;
+                Command::none()
+            }
+            CreateMsg::ImageDownload(layer, current, total) => {
+                if let Some(job) = self.build_job.as_mut() {
+                    if !matches!(job.phase, BuildPhase::Pulling(_)) {
+                        job.phase = BuildPhase::Pulling(PullProgress::default());
+                    }
+                    if let BuildPhase::Pulling(progress) = &mut job.phase {
+                        progress.update(layer, current, total, Instant::now());
+                    }
+                }
+
+                Command::none()
+            }
+            CreateMsg::PullTick(_) => Command::none(),
+            CreateMsg::CreateCompanion(index) => {
+                let Some((parent, companions)) = self.companion_offer.take() else {
+                    return Command::none();
+                };
+                let Some(companion) = companions.get(index) else {
+                    return Command::none();
+                };
+
+                self.create_draft = AddContainerState::default();
+                run(Message::Create(CreateMsg::CreateContainer(Box::new(
+                    companion.config(&parent),
+                ))))
+            }
+            CreateMsg::DismissCompanionOffer => {
+                self.companion_offer = None;
+                Command::none()
+            }
+            CreateMsg::BuildOutput(line) => {
+                if let Some(job) = self.build_job.as_mut() {
+                    job.push_build_output(line);
+                }
+                Command::none()
+            }
+            CreateMsg::Planned(plan) => {
+                let Some(job) = self.build_job.as_mut() else {
+                    return Command::none();
+                };
+
+                let database = self.images.iter().find(|db| db.name == job.config.database);
+                let secret = |key: &str| is_secret_key(database, key);
+                job.phase =
+                    BuildPhase::Planned(plan.into_iter().map(|op| op.redacted(secret)).collect());
+                Command::none()
+            }
+            CreateMsg::BuildingContainer => {
+                if let Some(job) = self.build_job.as_mut() {
+                    job.phase = BuildPhase::Creating;
+                }
+                Command::none()
+            }
+            CreateMsg::StartingContainer => {
+                if let Some(job) = self.build_job.as_mut() {
+                    job.phase = BuildPhase::Starting;
+                }
+                Command::none()
+            }
+            CreateMsg::CreatedContainer(id) => {
+                let mut check = Command::none();
+                if let Some(job) = self.build_job.as_mut() {
+                    if job.config.start_after_create {
+                        self.starts.sent(id, unix_now() as i64);
+                        job.phase = BuildPhase::Checking;
+                        check = Command::perform(
+                            check_started(job.config.name.clone(), self.docker),
+                            |result| {
+                                Message::Create(CreateMsg::StartChecked(
+                                    result.ok().flatten().map(Box::new),
+                                ))
+                            },
+                        );
+                    } else {
+                        job.phase = BuildPhase::Created;
+                    }
+                    let created = matches!(job.phase, BuildPhase::Created);
+                    let database = self.images.iter().find(|db| db.name == job.config.database);
+                    if let Some(database) = database {
+                        self.preferences.remember(database, &job.config);
+                    }
+                    self.preferences
+                        .record_created(database, &job.config, unix_now() as i64);
+                    write_preferences(self.paths.as_ref(), &self.preferences);
+                    // Quick starts and companions don't come from the form
+                    if !job.draft.is_empty() {
+                        self.forget_draft();
+                    }
+                    if created {
+                        self.offer_companions();
+                    }
+                }
+                if self.quick_credentials.is_some() {
+                    self.main_view = MainViewState::Credentials;
+                }
+                Command::batch([
+                    check,
+                    Command::perform(future::ready(()), |_| {
+                        Message::Container(ContainerMsg::GetContainers)
+                    }),
+                ])
+            }
+            CreateMsg::StartChecked(failure) => {
+                let Some(job) = self.build_job.as_mut() else {
+                    return Command::none();
+                };
+
+                match failure {
+                    Some(failure) => {
+                        job.phase = BuildPhase::Exited(*failure);
+                        self.main_view = MainViewState::StartFailed;
+                    }
+                    None => {
+                        job.phase = BuildPhase::Created;
+                        self.offer_companions();
+                    }
+                }
+                run(Message::Container(ContainerMsg::GetContainers))
+            }
+            CreateMsg::KeepFailedContainer => {
+                let Some(job) = self.build_job.take() else {
+                    return Command::none();
+                };
+
+                self.main_view = MainViewState::None;
+                match job.phase {
+                    BuildPhase::Exited(failure) => {
+                        run(Message::Ui(UiMsg::ViewContainer(failure.id)))
+                    }
+                    _ => Command::none(),
+                }
+            }
+            CreateMsg::DeleteFailedContainer => match self.build_job.as_ref() {
+                Some(job) => {
+                    self.expected_changes
+                        .expect(&job.config.name, Instant::now());
+                    Command::perform(remove_created(job.config.clone(), self.docker), |result| {
+                        match result {
+                            Err(ex) => failed("Could not delete the container", &ex),
+                            Ok(_) => Message::Create(CreateMsg::FailedContainerDeleted),
+                        }
+                    })
+                }
+                None => Command::none(),
+            },
+            CreateMsg::FailedContainerDeleted => {
+                if let Some(job) = self.build_job.take() {
+                    let name = format!("/{}", job.config.name);
+                    self.containers.retain(|container| container.name != name);
+                    self.create_draft = job.draft;
+                    self.quick_credentials = None;
+                    self.main_view = MainViewState::CreateContainer;
+                }
+                Command::none()
+            }
+            CreateMsg::PullingContainer => {
+                if let Some(job) = self.build_job.as_mut() {
+                    if !matches!(job.phase, BuildPhase::Pulling(_)) {
+                        job.phase = BuildPhase::Pulling(PullProgress::default());
+                    }
+                }
+                Command::none()
+            }
+            CreateMsg::RecreateDeleted(name) => {
+                let Some(created) = self
+                    .preferences
+                    .created
+                    .iter()
+                    .find(|created| created.name == name)
+                else {
+                    return Command::none();
+                };
+
+                let complete = created.definition.omitted.is_empty();
+                let (mut config, database, warnings) =
+                    created.definition.clone().into_config(&self.images);
+                config.name = name;
+                // Removing a container keeps its volumes
+                config.reuse_volumes = true;
+                self.create_draft = AddContainerState::imported(config.clone(), database, warnings);
+                self.quick_credentials = None;
+                if complete {
+                    return run(Message::Create(CreateMsg::CreateContainer(Box::new(
+                        prepare_config(&config),
+                    ))));
+                }
+
+                // Secrets that weren't recorded are filled in on the form first
+                self.main_view = MainViewState::CreateContainer;
+                self.load_engine_info()
+            }
+            CreateMsg::ForgetDeleted(name) => {
+                self.preferences
+                    .created
+                    .retain(|created| created.name != name);
+                write_preferences(self.paths.as_ref(), &self.preferences);
+                Command::none()
+            }
+            CreateMsg::DismissVersionWarning => {
+                self.version_block = None;
+                Command::none()
+            }
+        }
+    }
+}
@@ -0,0 +1,6 @@
+mod container;
+mod create;
+mod thumbnail;
+mod ui;
+
+pub use self::{container::ContainerMsg, create::CreateMsg, thumbnail::ThumbnailMsg, ui::UiMsg};
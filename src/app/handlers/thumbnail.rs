@@ -0,0 +1,100 @@
+use anyhow::anyhow;
+use iced::Command;
+
+use crate::{
+    app::{
+        failed_with_retry, run,
+        thumbnails::{icon_sources, IconSource},
+        DbMgrApp, Message,
+    },
+    data::Paths,
+    http::{fetch_icon_cached, validate_cached_icon},
+};
+
+/// Messages of fetching the icons shown on the container cards.
+#[derive(Clone, Debug)]
+pub enum ThumbnailMsg {
+    /// Fetches the icons of the images that have none yet.
+    GetThumbnails,
+    /// The fetched icon of an image, added to the cards on the next `FlushThumbnails`.
+    ThumbnailLoaded(String, Vec<u8>),
+    FlushThumbnails,
+    /// An icon could not be fetched after retrying, keyed by image.
+    ThumbnailFailed(String, String),
+}
+
+impl DbMgrApp {
+    pub fn handle_thumbnail_msg(&mut self, msg: ThumbnailMsg) -> Command<Message> {
+        match msg {
+            ThumbnailMsg::ThumbnailLoaded(image, bytes) => {
+                self.icon_errors.remove(&image);
+                self.thumbnails.queue(image, bytes);
+                Command::none()
+            }
+            ThumbnailMsg::FlushThumbnails => {
+                self.thumbnails.flush();
+                Command::none()
+            }
+            ThumbnailMsg::ThumbnailFailed(image, ex) => {
+                eprintln!("Could not load the icon for {image}: {ex}");
+                // The default icon is drawn in its place
+                self.thumbnails.remove(&image);
+                self.icon_errors.insert(image, ex);
+                // One toast covers every icon, retrying fetches the ones still missing
+                run(failed_with_retry(
+                    "Could not load the image icons",
+                    &anyhow!(
+                        "some icons could not be fetched, the default one is drawn in their place"
+                    ),
+                    Message::Thumbnail(ThumbnailMsg::GetThumbnails),
+                ))
+            }
+            ThumbnailMsg::GetThumbnails => {
+                let cache_dir = self.paths.as_ref().map(Paths::icon_cache_dir);
+                // Handles from a path are only read when drawn, so cached icons cost nothing
+                // here and need no fetch
+                let sources = icon_sources(
+                    &self.images,
+                    |image| self.thumbnails.get(image).is_some(),
+                    cache_dir.as_deref(),
+                    |file| match validate_cached_icon(file) {
+                        Ok(()) => true,
+                        Err(ex) => {
+                            eprintln!("Discarding the cached icon {}: {ex}", file.display());
+                            if let Err(ex) = std::fs::remove_file(file) {
+                                eprintln!("Could not remove {}: {ex}", file.display());
+                            }
+                            false
+                        }
+                    },
+                );
+
+                let mut fetches = vec![];
+                for (image, source) in sources {
+                    let (url, cache_file) = match source {
+                        IconSource::Cached(file) => {
+                            self.thumbnails.insert_path(image, &file);
+                            continue;
+                        }
+                        IconSource::Fetch { url, cache_file } => (url, cache_file),
+                    };
+
+                    fetches.push(Command::perform(
+                        fetch_icon_cached(self.http.clone(), url, cache_file),
+                        move |result| match result {
+                            Ok(bytes) => {
+                                Message::Thumbnail(ThumbnailMsg::ThumbnailLoaded(image, bytes))
+                            }
+                            Err(ex) => Message::Thumbnail(ThumbnailMsg::ThumbnailFailed(
+                                image,
+                                format!("{ex:#}"),
+                            )),
+                        },
+                    ));
+                }
+
+                Command::batch(fetches)
+            }
+        }
+    }
+}
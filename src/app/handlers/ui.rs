@@ -0,0 +1,728 @@
+use iced::{font, widget::scrollable, window, Command};
+use itertools::Itertools;
+use std::{
+    collections::{BTreeSet, HashSet},
+    path::PathBuf,
+    time::Instant,
+};
+
+use super::ContainerMsg;
+use crate::{
+    app::{
+        auto_stop::AutoStopAction,
+        bulk::BulkResult,
+        changes::Toast,
+        container_list::PAGE_SIZE,
+        container_view::DetailTab,
+        error, failed, run,
+        settings::{ConnectionTest, TlsFile, TlsTest},
+        sql_file::{drop_target, file_name, is_sql_file, DropTarget},
+        status_bar::docker_endpoint,
+        tray::{Tray, TrayUpdate},
+        welcome::WelcomeFlow,
+        DbMgrApp, MainViewState, Message,
+    },
+    daemon::{query_timeout, with_timeout},
+    data::{
+        open_config_file, open_in_file_manager, remove_databases, write_preferences, AppTheme,
+        ViewMode,
+    },
+    docker::{get_cleanup_summary, get_engine_info, EngineInfo},
+    errors::{explain, UserError},
+    http::test_connection,
+    idle::IdleAfter,
+    tls::connect,
+    validate::{validate_config, DockerImages, EntryReport},
+};
+
+/// Messages that change what is shown, the settings and the window.
+#[derive(Clone, Debug)]
+pub enum UiMsg {
+    FontLoaded(Result<(), font::Error>),
+    Error(String),
+    Failed(Box<UserError>),
+    /// A failure the message can retry, shown as a toast with a "Retry" button.
+    FailedWithRetry(Box<UserError>, Box<Message>),
+    /// The "Retry" button of the toast with the retry of this id was pressed.
+    Retry(u64),
+    ShowVolumes(bool),
+    DismissOrphans,
+    ViewContainer(String),
+    ToggleSelection,
+    SelectContainer(String, bool),
+    SelectTab(String, DetailTab),
+    Tick(Instant),
+    FileDropped(PathBuf),
+    CopyToClipboard(String),
+    OpenPath(PathBuf),
+    OpenConfig,
+    DismissCredentials,
+    ShowSettings,
+    ShowAbout,
+    EngineInfoLoaded(Box<EngineInfo>),
+    ValidateConfig,
+    ConfigValidated(Vec<EntryReport>),
+    TestConnection,
+    ConnectionTested(ConnectionTest),
+    SetViewMode(ViewMode),
+    RememberValues(bool),
+    RememberPasswords(bool),
+    NeverDefaultLatest(bool),
+    SkipCreateReview(bool),
+    IdleAfter(IdleAfter),
+    ReadOnly(bool),
+    ClearRemembered(String),
+    ShowCleanup,
+    ShowPrefetch,
+    PrefetchSelectionChanged(BTreeSet<String>),
+    SidebarScrolled(scrollable::AbsoluteOffset),
+    /// Shows the backups of the container with the given id, from the major version warning.
+    OpenBackups(String),
+    /// Redraws the time since the last refresh in the status bar and expires highlights and
+    /// toasts.
+    ClockTick(Instant),
+    DismissToast(usize),
+    /// Lists only the containers of the project, all of them with `None`.
+    FilterProject(Option<String>),
+    /// Keeps the project of the named container in the settings, an empty one takes it out of
+    /// its project.
+    SetProject(String, String),
+    CheckUpdatesDaily(bool),
+    ShowMoreContainers,
+    StopOnExit(bool),
+    /// Turns exporting the state file on, choosing where, or off.
+    StateFile(bool),
+    /// Chooses another state file.
+    ChooseStateFile,
+    /// Writes the state file if a refresh is waiting to be written.
+    WriteStateFile(Instant),
+    StateFileWritten(Result<(), String>),
+    /// Whether the named container is left running when db-mgr is closed.
+    KeepOnExit(String, bool),
+    /// The window is being closed, running containers may be stopped first.
+    CloseRequested,
+    /// Another db-mgr was started, this window is brought to the front instead.
+    FocusWindow,
+    SingleInstance(bool),
+    /// Whether the tools for looking into db-mgr itself, such as the raw inspect tab, are shown.
+    DeveloperMode(bool),
+    /// The docker endpoint to connect to from the next start, empty for the default.
+    DockerHost(String),
+    /// Whether to connect with the TLS client certificate instead of the endpoint.
+    TlsEnabled(bool),
+    /// The `host:port` of the daemon requiring TLS.
+    TlsHost(String),
+    ChooseTlsFile(TlsFile),
+    TestTls,
+    TlsTested(TlsTest),
+    /// Connects again with the endpoint or client certificate as they are set now.
+    Reconnect,
+    /// Whether the named container is in the tray menu.
+    Favourite(String, bool),
+    /// Whether to stop warning about the end of life of the named container's version.
+    SilenceEol(String, bool),
+    TrayEnabled(bool),
+    /// Quit was picked in the tray menu, which exits like closing without a tray.
+    QuitFromTray,
+    SetTheme(AppTheme),
+    WelcomeChanged(Box<WelcomeFlow>),
+    /// The last step of the welcome flow was confirmed, its choices are applied.
+    FinishWelcome(Box<WelcomeFlow>),
+    /// The welcome flow was left, the defaults are kept.
+    SkipWelcome,
+    /// Checks the connection to docker again from the welcome flow.
+    RetryDocker,
+    /// The containers stopped on closing, the window closes now.
+    ExitStopped(Vec<BulkResult>),
+    /// Closes the window without waiting for the containers to stop.
+    ForceExit,
+}
+
+impl DbMgrApp {
+    pub fn handle_ui_msg(&mut self, msg: UiMsg) -> Command<Message> {
+        match msg {
+            UiMsg::ShowVolumes(orphans_only) => {
+                self.main_view = MainViewState::Volumes(orphans_only);
+                Command::none()
+            }
+            UiMsg::DismissOrphans => {
+                self.dismissed_orphans = Some(
+                    self.orphans()
+                        .into_iter()
+                        .map(|volume| volume.name)
+                        .sorted()
+                        .collect(),
+                );
+                Command::none()
+            }
+            UiMsg::Error(ex) => {
+                if let Err(dialog_err) = native_dialog::MessageDialog::new()
+                    .set_text(&ex)
+                    .set_type(native_dialog::MessageType::Error)
+                    .show_alert()
+                {
+                    eprintln!("Application Error: {ex}");
+                    eprintln!("Dialog Error: {dialog_err}");
+                }
+                Command::none()
+            }
+            UiMsg::Failed(ex) => {
+                if let Err(dialog_err) = native_dialog::MessageDialog::new()
+                    .set_title(&ex.title)
+                    .set_text(&ex.body())
+                    .set_type(native_dialog::MessageType::Error)
+                    .show_alert()
+                {
+                    eprintln!("Application Error: {ex}");
+                    eprintln!("Dialog Error: {dialog_err}");
+                }
+                Command::none()
+            }
+            UiMsg::FailedWithRetry(ex, retry) => {
+                eprintln!("Application Error: {ex}");
+                let message = match ex.suggestion.as_ref() {
+                    Some(suggestion) => format!("{}. {suggestion}", ex.title),
+                    None => ex.to_string(),
+                };
+                // A failure that keeps happening replaces its last toast instead of piling up
+                self.toasts.retain(|toast| toast.message != message);
+                let id = self.retries.register(*retry);
+                self.toasts
+                    .push(Toast::with_retry(message, id, Instant::now()));
+                self.forget_retries();
+                Command::none()
+            }
+            UiMsg::Retry(id) => {
+                let Some(retry) = self.retries.take(id) else {
+                    return Command::none();
+                };
+                self.toasts.retain(|toast| toast.retry != Some(id));
+                run(retry)
+            }
+            UiMsg::ViewContainer(container_name) => {
+                self.expanded_env.clear();
+                self.touch(&container_name);
+                self.main_view = if self.containers.iter().any(|c| c.id == container_name) {
+                    MainViewState::ViewContainer(container_name.clone())
+                } else {
+                    MainViewState::None
+                };
+
+                println!("{:?}", self.main_view);
+
+                match self.detail_tabs.get(&container_name) {
+                    Some(DetailTab::Events) => self.backfill_events(&container_name),
+                    Some(DetailTab::Inspect) => self.load_inspect(&container_name),
+                    Some(DetailTab::Logs) => {
+                        self.open_logs(&container_name);
+                        Command::none()
+                    }
+                    _ => Command::none(),
+                }
+            }
+            UiMsg::SelectTab(id, tab) => {
+                self.detail_tabs.insert(id.clone(), tab);
+                match tab {
+                    DetailTab::Events => self.backfill_events(&id),
+                    DetailTab::Inspect => self.load_inspect(&id),
+                    DetailTab::Logs => {
+                        self.open_logs(&id);
+                        Command::none()
+                    }
+                    _ => Command::none(),
+                }
+            }
+            UiMsg::Tick(now) => {
+                let mut commands = vec![];
+                for (id, action) in self.auto_stop.tick(now) {
+                    match action {
+                        AutoStopAction::Stop => {
+                            commands.push(run(Message::Container(ContainerMsg::StopContainer(id))))
+                        }
+                        AutoStopAction::Warn => {
+                            let name = self
+                                .containers
+                                .iter()
+                                .find(|container| container.id == id)
+                                .map(|container| container.name.trim_start_matches('/'))
+                                .unwrap_or(&id);
+                            let keep_running = native_dialog::MessageDialog::new()
+                                .set_title("Stopping soon")
+                                .set_text(&format!(
+                                    "{name} will be stopped in 5 minutes. Keep it running for another 30 minutes?"
+                                ))
+                                .set_type(native_dialog::MessageType::Warning)
+                                .show_confirm()
+                                .unwrap_or(false);
+                            if keep_running {
+                                self.auto_stop.snooze(&id, Instant::now());
+                            }
+                        }
+                    }
+                }
+                Command::batch(commands)
+            }
+            UiMsg::FileDropped(path) => {
+                if !is_sql_file(&path) {
+                    return error(format!(
+                        "{} is not a SQL file, only .sql files can be run",
+                        file_name(&path)
+                    ));
+                }
+                if self.sql_run.is_some() {
+                    return error("Wait for the running SQL file to finish");
+                }
+
+                let viewed = self
+                    .visible_tab()
+                    .map(|(container, _)| container.id.clone());
+                match drop_target(viewed.as_deref(), &self.sql_candidates()) {
+                    DropTarget::None => error(
+                        "No running container can run SQL files, its config needs a restore_command or query_command",
+                    ),
+                    DropTarget::Container(id) => run(Message::Container(ContainerMsg::RunSqlFile(id, path))),
+                    DropTarget::Pick(ids) => {
+                        self.pending_drop = Some((path, ids));
+                        Command::none()
+                    }
+                }
+            }
+            UiMsg::StopOnExit(stop) => {
+                self.preferences.stop_on_exit = stop;
+                write_preferences(self.paths.as_ref(), &self.preferences);
+                Command::none()
+            }
+            UiMsg::StateFile(false) => {
+                self.preferences.state_file = None;
+                write_preferences(self.paths.as_ref(), &self.preferences);
+                Command::none()
+            }
+            UiMsg::StateFile(true) | UiMsg::ChooseStateFile => {
+                let path = match native_dialog::FileDialog::new()
+                    .add_filter("JSON", &["json"])
+                    .show_save_single_file()
+                {
+                    Ok(Some(path)) => path,
+                    Ok(None) => return Command::none(),
+                    Err(ex) => return error(format!("Could not choose a file: {ex}")),
+                };
+                self.preferences.state_file = Some(path);
+                self.state_file_written = None;
+                self.state_file_warned = false;
+                write_preferences(self.paths.as_ref(), &self.preferences);
+                self.write_state(Instant::now())
+            }
+            UiMsg::WriteStateFile(now) => self.write_state(now),
+            UiMsg::StateFileWritten(Ok(())) => Command::none(),
+            UiMsg::StateFileWritten(Err(ex)) => {
+                eprintln!("Could not write the state file: {ex}");
+                if self.state_file_warned {
+                    return Command::none();
+                }
+                self.state_file_warned = true;
+                error(format!("Could not write the state file: {ex}"))
+            }
+            UiMsg::KeepOnExit(name, keep) => {
+                if keep {
+                    self.preferences.keep_on_exit.insert(name);
+                } else {
+                    self.preferences.keep_on_exit.remove(&name);
+                }
+                write_preferences(self.paths.as_ref(), &self.preferences);
+                Command::none()
+            }
+            // The window may also be hidden in the tray
+            UiMsg::FocusWindow => Command::batch([
+                window::change_mode(window::Mode::Windowed),
+                window::minimize(false),
+                window::gain_focus(),
+            ]),
+            UiMsg::SingleInstance(single_instance) => {
+                self.preferences.single_instance = single_instance;
+                write_preferences(self.paths.as_ref(), &self.preferences);
+                Command::none()
+            }
+            UiMsg::DeveloperMode(developer_mode) => {
+                self.preferences.developer_mode = developer_mode;
+                write_preferences(self.paths.as_ref(), &self.preferences);
+                if !developer_mode {
+                    self.raw_inspects.clear();
+                }
+                Command::none()
+            }
+            UiMsg::DockerHost(host) => {
+                self.preferences.docker_host = Some(host).filter(|host| !host.trim().is_empty());
+                write_preferences(self.paths.as_ref(), &self.preferences);
+                Command::none()
+            }
+            UiMsg::TlsEnabled(enabled) => {
+                self.preferences.docker_tls.enabled = enabled;
+                self.tls_test = None;
+                write_preferences(self.paths.as_ref(), &self.preferences);
+                Command::none()
+            }
+            UiMsg::TlsHost(host) => {
+                self.preferences.docker_tls.host = host;
+                self.tls_test = None;
+                write_preferences(self.paths.as_ref(), &self.preferences);
+                Command::none()
+            }
+            UiMsg::ChooseTlsFile(file) => {
+                let path = match native_dialog::FileDialog::new()
+                    .add_filter("PEM", &["pem", "crt", "cert", "key"])
+                    .show_open_single_file()
+                {
+                    Ok(Some(path)) => path,
+                    Ok(None) => return Command::none(),
+                    Err(ex) => return error(format!("Could not choose a file: {ex}")),
+                };
+                let tls = &mut self.preferences.docker_tls;
+                match file {
+                    TlsFile::Ca => tls.ca = Some(path),
+                    TlsFile::Cert => tls.cert = Some(path),
+                    TlsFile::Key => tls.key = Some(path),
+                }
+                self.tls_test = None;
+                write_preferences(self.paths.as_ref(), &self.preferences);
+                Command::none()
+            }
+            UiMsg::TestTls => {
+                self.tls_test = Some(TlsTest::Running);
+                let tls = self.preferences.docker_tls.clone();
+                Command::perform(
+                    async move {
+                        // Checks the files before connecting, the TLS errors come with the request
+                        let docker = tls.connect()?;
+                        with_timeout("get the docker version", query_timeout(), docker.version())
+                            .await
+                    },
+                    |result| {
+                        Message::Ui(UiMsg::TlsTested(match result {
+                            Ok(version) => TlsTest::Connected(version.version.unwrap_or_default()),
+                            Err(ex) => TlsTest::Failed(explain(
+                                "Could not connect with the client certificate",
+                                &ex,
+                            )),
+                        }))
+                    },
+                )
+            }
+            UiMsg::TlsTested(result) => {
+                self.tls_test = Some(result);
+                Command::none()
+            }
+            UiMsg::Reconnect => {
+                let docker = match connect(
+                    &self.preferences.docker_tls,
+                    self.preferences.docker_host.as_deref(),
+                ) {
+                    Ok(docker) => docker,
+                    Err(ex) => return run(failed("Could not connect to docker", &ex)),
+                };
+                // Like the first connection it lives as long as db-mgr, commands that are still
+                // running keep using the one they started with
+                self.docker = Box::leak(Box::new(docker));
+                self.endpoint = docker_endpoint(
+                    self.preferences.docker_host.as_deref(),
+                    &self.preferences.docker_tls,
+                );
+                self.connected = None;
+                self.engine_info = None;
+                // The containers of another daemon aren't changes to announce
+                self.last_refresh = None;
+                Command::batch([
+                    run(Message::Container(ContainerMsg::GetContainers)),
+                    self.load_engine_info(),
+                    self.check_disk_space(None),
+                ])
+            }
+            UiMsg::Favourite(name, favourite) => {
+                if favourite {
+                    self.preferences.favourites.insert(name);
+                } else {
+                    self.preferences.favourites.remove(&name);
+                }
+                write_preferences(self.paths.as_ref(), &self.preferences);
+                self.refresh_tray();
+                Command::none()
+            }
+            UiMsg::SilenceEol(name, silenced) => {
+                if silenced {
+                    self.preferences.eol_silenced.insert(name);
+                } else {
+                    self.preferences.eol_silenced.remove(&name);
+                }
+                write_preferences(self.paths.as_ref(), &self.preferences);
+                Command::none()
+            }
+            UiMsg::TrayEnabled(enabled) => {
+                self.preferences.tray = enabled;
+                write_preferences(self.paths.as_ref(), &self.preferences);
+                match self.tray {
+                    Some(tray) => tray.update(TrayUpdate::Visible(enabled)),
+                    None if enabled => match Tray::spawn() {
+                        Ok(tray) => {
+                            self.tray = Some(Box::leak(Box::new(tray)));
+                            self.tray_entries.clear();
+                            self.refresh_tray();
+                        }
+                        Err(ex) => return error(format!("Could not show the tray icon: {ex}")),
+                    },
+                    None => {}
+                }
+                Command::none()
+            }
+            UiMsg::SetTheme(theme) => {
+                self.preferences.theme = theme;
+                write_preferences(self.paths.as_ref(), &self.preferences);
+                Command::none()
+            }
+            UiMsg::WelcomeChanged(welcome) => {
+                self.welcome = Some(*welcome);
+                Command::none()
+            }
+            UiMsg::FinishWelcome(welcome) => {
+                self.welcome = None;
+                self.main_view = MainViewState::None;
+                self.preferences.theme = welcome.theme;
+                write_preferences(self.paths.as_ref(), &self.preferences);
+
+                let hidden = welcome.hidden();
+                if hidden.is_empty() {
+                    return Command::none();
+                }
+                let Some(paths) = self.paths.as_ref() else {
+                    return Command::none();
+                };
+                match remove_databases(paths, &hidden) {
+                    Ok(()) => {
+                        self.images.retain(|image| !hidden.contains(&image.name));
+                        Command::none()
+                    }
+                    Err(ex) => run(failed("Could not update the config file", &ex)),
+                }
+            }
+            UiMsg::SkipWelcome => {
+                self.welcome = None;
+                self.main_view = MainViewState::None;
+                write_preferences(self.paths.as_ref(), &self.preferences);
+                Command::none()
+            }
+            UiMsg::RetryDocker => {
+                self.engine_info = None;
+                self.load_engine_info()
+            }
+            UiMsg::CloseRequested => {
+                if self.exiting.is_none() && self.tray.is_some() && self.preferences.tray {
+                    return window::change_mode(window::Mode::Hidden);
+                }
+                self.exit()
+            }
+            UiMsg::QuitFromTray => self.exit(),
+            UiMsg::ExitStopped(results) => {
+                for result in results {
+                    if let Some(error) = result.error {
+                        eprintln!("Could not stop {} on exit: {error}", result.name);
+                    }
+                }
+                window::close()
+            }
+            UiMsg::ForceExit => {
+                eprintln!("Closed before the containers stopped");
+                window::close()
+            }
+            UiMsg::CopyToClipboard(value) => iced::clipboard::write(value),
+            UiMsg::OpenPath(path) => match open_in_file_manager(&path) {
+                Err(ex) => run(failed("Could not open the file manager", &ex)),
+                Ok(_) => Command::none(),
+            },
+            UiMsg::OpenConfig => match self.paths.as_ref().map(open_config_file) {
+                None => error("No config directory was found"),
+                Some(Err(ex)) => run(failed("Could not open the config file", &ex)),
+                Some(Ok(_)) => Command::none(),
+            },
+            UiMsg::DismissCredentials => {
+                self.quick_credentials = None;
+                self.main_view = MainViewState::None;
+                Command::none()
+            }
+            UiMsg::FontLoaded(_) => Command::none(),
+            UiMsg::ClockTick(now) => {
+                self.highlights.retain(|_, until| *until > now);
+                self.toasts.retain(|toast| toast.until > now);
+                self.forget_retries();
+                Command::none()
+            }
+            UiMsg::FilterProject(project) => {
+                self.project_filter = project;
+                Command::none()
+            }
+            UiMsg::SetProject(name, project) => {
+                let labelled = self
+                    .containers
+                    .iter()
+                    .find(|container| container.name.trim_start_matches('/') == name)
+                    .and_then(|container| container.project())
+                    .unwrap_or_default();
+                // A project matching the label needs nothing kept
+                if project == labelled {
+                    self.preferences.projects.remove(&name);
+                } else {
+                    self.preferences.projects.insert(name, project);
+                }
+                write_preferences(self.paths.as_ref(), &self.preferences);
+                self.retain_project_filter();
+                Command::none()
+            }
+            UiMsg::DismissToast(index) => {
+                if index < self.toasts.len() {
+                    self.toasts.remove(index);
+                }
+                self.forget_retries();
+                Command::none()
+            }
+            UiMsg::ShowSettings => {
+                self.main_view = MainViewState::Settings;
+                Command::none()
+            }
+            UiMsg::ShowAbout => {
+                self.main_view = MainViewState::About;
+                self.engine_info = None;
+                Command::perform(get_engine_info(self.docker), |engine| {
+                    Message::Ui(UiMsg::EngineInfoLoaded(Box::new(engine)))
+                })
+            }
+            UiMsg::EngineInfoLoaded(engine) => {
+                self.engine_info = Some(*engine);
+                Command::none()
+            }
+            UiMsg::ToggleSelection => {
+                self.selection = match self.selection {
+                    Some(_) => None,
+                    None => Some(HashSet::new()),
+                };
+                Command::none()
+            }
+            UiMsg::SelectContainer(id, selected) => {
+                if let Some(selection) = self.selection.as_mut() {
+                    if selected {
+                        selection.insert(id);
+                    } else {
+                        selection.remove(&id);
+                    }
+                }
+                Command::none()
+            }
+            UiMsg::ValidateConfig => {
+                self.validating = true;
+                let (docker, images) = (self.docker, self.images.clone());
+                Command::perform(
+                    async move { validate_config(&DockerImages { docker }, images).await },
+                    |reports| Message::Ui(UiMsg::ConfigValidated(reports)),
+                )
+            }
+            UiMsg::ConfigValidated(reports) => {
+                self.validating = false;
+                self.validation = Some(reports);
+                Command::none()
+            }
+            UiMsg::TestConnection => {
+                let Some(url) = self.images.first().map(|image| image.icon_url.clone()) else {
+                    return error("There are no icon urls in the config to test with");
+                };
+
+                self.connection_test = Some(ConnectionTest::Running);
+                Command::perform(test_connection(self.http.clone(), url), |result| {
+                    Message::Ui(UiMsg::ConnectionTested(match result {
+                        Ok(latency) => ConnectionTest::Succeeded(latency),
+                        Err(ex) => ConnectionTest::Failed(format!("{ex}")),
+                    }))
+                })
+            }
+            UiMsg::ConnectionTested(result) => {
+                self.connection_test = Some(result);
+                Command::none()
+            }
+            UiMsg::SetViewMode(view_mode) => {
+                self.preferences.view_mode = view_mode;
+                write_preferences(self.paths.as_ref(), &self.preferences);
+                Command::none()
+            }
+            UiMsg::RememberValues(remember) => {
+                self.preferences.remember_values = remember;
+                write_preferences(self.paths.as_ref(), &self.preferences);
+                Command::none()
+            }
+            UiMsg::RememberPasswords(remember) => {
+                self.preferences.remember_passwords = remember;
+                write_preferences(self.paths.as_ref(), &self.preferences);
+                Command::none()
+            }
+            UiMsg::NeverDefaultLatest(never) => {
+                self.preferences.never_default_latest = never;
+                write_preferences(self.paths.as_ref(), &self.preferences);
+                Command::none()
+            }
+            UiMsg::SkipCreateReview(skip) => {
+                self.preferences.skip_create_review = skip;
+                write_preferences(self.paths.as_ref(), &self.preferences);
+                Command::none()
+            }
+            UiMsg::IdleAfter(idle_after) => {
+                self.preferences.idle_after = idle_after;
+                write_preferences(self.paths.as_ref(), &self.preferences);
+                self.check_idle();
+                Command::none()
+            }
+            UiMsg::ReadOnly(read_only) => {
+                self.preferences.read_only = read_only;
+                write_preferences(self.paths.as_ref(), &self.preferences);
+                Command::none()
+            }
+            UiMsg::ClearRemembered(name) => {
+                self.preferences.remembered.remove(&name);
+                write_preferences(self.paths.as_ref(), &self.preferences);
+                Command::none()
+            }
+            UiMsg::ShowCleanup => {
+                self.main_view = MainViewState::Cleanup;
+                self.cleanup_summary = None;
+                self.cleanup_results.clear();
+                Command::perform(get_cleanup_summary(self.docker), |result| match result {
+                    Err(ex) => failed("Could not get db-mgr resources", &ex),
+                    Ok(summary) => Message::Container(ContainerMsg::CleanupSummaryLoaded(summary)),
+                })
+            }
+            UiMsg::OpenBackups(id) => {
+                self.version_block = None;
+                self.detail_tabs.insert(id.clone(), DetailTab::Backups);
+                run(Message::Ui(UiMsg::ViewContainer(id)))
+            }
+            UiMsg::CheckUpdatesDaily(check_updates) => {
+                self.preferences.check_updates = check_updates;
+                write_preferences(self.paths.as_ref(), &self.preferences);
+                if check_updates {
+                    self.check_updates()
+                } else {
+                    Command::none()
+                }
+            }
+            UiMsg::ShowMoreContainers => {
+                self.list_limit += PAGE_SIZE;
+                Command::none()
+            }
+            UiMsg::SidebarScrolled(offset) => {
+                self.sidebar_offset = offset;
+                Command::none()
+            }
+            UiMsg::ShowPrefetch => {
+                self.main_view = MainViewState::Prefetch;
+                Command::none()
+            }
+            UiMsg::PrefetchSelectionChanged(selection) => {
+                self.prefetch_selection = selection;
+                Command::none()
+            }
+        }
+    }
+}
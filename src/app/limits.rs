@@ -0,0 +1,129 @@
+use super::cleanup::parse_size;
+use crate::docker::{ContainerLimits, LimitChanges, LimitError, LimitField, Restart};
+
+/// The least memory docker lets a container be limited to.
+const MIN_MEMORY: u64 = 6 * 1024 * 1024;
+
+const NANOS_PER_CPU: f64 = 1_000_000_000.0;
+
+/// The limits of a container as typed while they are edited, kept on the app so a refusal from
+/// the daemon can be shown next to the value that caused it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LimitsForm {
+    /// Such as `512m` or `2g`, empty for no limit.
+    pub memory: String,
+    /// Such as `0.5` or `2`, empty for no limit.
+    pub cpus: String,
+    pub restart: Restart,
+    /// The changes are being applied.
+    pub applying: bool,
+    /// Why the daemon refused the last change.
+    pub error: Option<LimitError>,
+}
+
+impl LimitsForm {
+    /// The form filled in with the limits the container has.
+    pub fn new(limits: &ContainerLimits) -> Self {
+        Self {
+            memory: limits.memory.map(typed_memory).unwrap_or_default(),
+            cpus: limits.nano_cpus.map(typed_cpus).unwrap_or_default(),
+            restart: limits.restart,
+            applying: false,
+            error: None,
+        }
+    }
+
+    /// What is wrong with the typed memory limit, or the daemon's refusal of it.
+    pub fn memory_error(&self, current: &ContainerLimits) -> Option<String> {
+        match parse_memory(&self.memory) {
+            None => Some("Use a size such as 512m or 2g".to_string()),
+            Some(None) if current.memory.is_some() => Some(
+                "Docker can't remove a memory limit from a container, recreate it instead"
+                    .to_string(),
+            ),
+            Some(Some(memory)) if (memory as u64) < MIN_MEMORY => {
+                Some("Docker needs at least 6m".to_string())
+            }
+            _ => self.daemon_error(LimitField::Memory),
+        }
+    }
+
+    /// What is wrong with the typed CPU limit, or the daemon's refusal of it.
+    pub fn cpus_error(&self, current: &ContainerLimits) -> Option<String> {
+        match parse_cpus(&self.cpus) {
+            None => Some("Use a number of CPUs such as 0.5 or 2".to_string()),
+            Some(None) if current.nano_cpus.is_some() => Some(
+                "Docker can't remove a CPU limit from a container, recreate it instead".to_string(),
+            ),
+            _ => self.daemon_error(LimitField::Cpus),
+        }
+    }
+
+    /// The daemon's refusal of the restart policy.
+    pub fn restart_error(&self) -> Option<String> {
+        self.daemon_error(LimitField::Restart)
+    }
+
+    fn daemon_error(&self, field: LimitField) -> Option<String> {
+        self.error
+            .as_ref()
+            .filter(|error| error.field == field)
+            .map(|error| error.message.clone())
+    }
+
+    /// The limits that differ from `current`, `None` while one of them is invalid.
+    pub fn changes(&self, current: &ContainerLimits) -> Option<LimitChanges> {
+        let memory = parse_memory(&self.memory)?;
+        let nano_cpus = parse_cpus(&self.cpus)?;
+        if (memory.is_none() && current.memory.is_some())
+            || memory.is_some_and(|memory| (memory as u64) < MIN_MEMORY)
+            || (nano_cpus.is_none() && current.nano_cpus.is_some())
+        {
+            return None;
+        }
+
+        Some(LimitChanges {
+            memory: memory.filter(|memory| Some(*memory) != current.memory),
+            nano_cpus: nano_cpus.filter(|nano_cpus| Some(*nano_cpus) != current.nano_cpus),
+            restart: Some(self.restart).filter(|restart| *restart != current.restart),
+        })
+    }
+}
+
+/// A memory limit as it is typed, such as `512m`.
+fn typed_memory(bytes: i64) -> String {
+    [(1 << 30, "g"), (1 << 20, "m"), (1 << 10, "k")]
+        .iter()
+        .find(|(unit, _)| bytes % unit == 0)
+        .map(|(unit, suffix)| format!("{}{suffix}", bytes / unit))
+        .unwrap_or_else(|| bytes.to_string())
+}
+
+/// A CPU limit in billionths as it is typed, such as `1.5`.
+pub fn typed_cpus(nano_cpus: i64) -> String {
+    let cpus = format!("{:.2}", nano_cpus as f64 / NANOS_PER_CPU);
+    cpus.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+/// The typed memory limit in bytes, `Some(None)` when it is empty.
+fn parse_memory(typed: &str) -> Option<Option<i64>> {
+    if typed.trim().is_empty() {
+        return Some(None);
+    }
+    parse_size(typed)
+        .and_then(|bytes| i64::try_from(bytes).ok())
+        .map(Some)
+}
+
+/// The typed CPU limit in billionths, `Some(None)` when it is empty.
+fn parse_cpus(typed: &str) -> Option<Option<i64>> {
+    let typed = typed.trim();
+    if typed.is_empty() {
+        return Some(None);
+    }
+    let cpus = typed.parse::<f64>().ok()?;
+    let nano_cpus = (cpus * NANOS_PER_CPU).round();
+    // A hundredth of a CPU is the finest docker takes
+    (cpus.is_finite() && nano_cpus >= NANOS_PER_CPU / 100.0 && nano_cpus < i64::MAX as f64)
+        .then_some(Some(nano_cpus as i64))
+}
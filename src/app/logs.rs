@@ -0,0 +1,195 @@
+use std::{collections::VecDeque, fmt::Display};
+
+use chrono::{DateTime, Local, Utc};
+use iced::widget::scrollable;
+
+/// How many lines are kept while following the logs of a container.
+pub const MAX_LINES: usize = 2000;
+
+/// How many of the matching lines are drawn, older ones stay in the buffer for filtering.
+const SHOWN_LINES: usize = 500;
+
+/// How many words into a line its level is looked for, past the timestamp and process id.
+const LEVEL_WORDS: usize = 12;
+
+/// The scrollable the followed lines are shown in.
+pub fn logs_id() -> scrollable::Id {
+    scrollable::Id::new("logs")
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Severity {
+    Fatal,
+    Error,
+    Warning,
+}
+
+impl Severity {
+    pub const ALL: [Severity; 3] = [Severity::Error, Severity::Fatal, Severity::Warning];
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Fatal => write!(f, "FATAL"),
+            Severity::Error => write!(f, "ERROR"),
+            Severity::Warning => write!(f, "WARN"),
+        }
+    }
+}
+
+/// The severity of a log line from its first level word, such as postgres' `ERROR:` or mysql's
+/// `[Warning]`. Lines logged at other levels such as `LOG` or `[Note]` have none.
+pub fn severity(text: &str) -> Option<Severity> {
+    text.split(|c: char| !c.is_ascii_alphabetic())
+        .filter(|word| !word.is_empty())
+        .take(LEVEL_WORDS)
+        .find_map(|word| match word.to_ascii_uppercase().as_str() {
+            "FATAL" | "PANIC" => Some(Some(Severity::Fatal)),
+            "ERROR" => Some(Some(Severity::Error)),
+            "WARNING" | "WARN" => Some(Some(Severity::Warning)),
+            "LOG" | "INFO" | "NOTICE" | "NOTE" | "DEBUG" | "SYSTEM" | "STATEMENT" | "DETAIL"
+            | "HINT" | "CONTEXT" => Some(None),
+            _ => None,
+        })
+        .flatten()
+}
+
+/// Splits the RFC 3339 timestamp docker prefixes each line with from the line.
+pub fn split_timestamp(line: &str) -> (Option<DateTime<Utc>>, &str) {
+    let parsed = line.split_once(' ').and_then(|(time, text)| {
+        let time = DateTime::parse_from_rfc3339(time).ok()?;
+        Some((time.with_timezone(&Utc), text))
+    });
+
+    match parsed {
+        Some((time, text)) => (Some(time), text),
+        None => (None, line),
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LogLine {
+    pub time: Option<DateTime<Utc>>,
+    pub text: String,
+    pub stderr: bool,
+    pub severity: Option<Severity>,
+}
+
+impl LogLine {
+    pub fn parse(line: &str, stderr: bool) -> Self {
+        let (time, text) = split_timestamp(line.trim_end_matches(['\r', '\n']));
+        Self {
+            time,
+            text: text.to_string(),
+            stderr,
+            severity: severity(text),
+        }
+    }
+
+    /// When the line was printed in local time, such as `14:03:27`.
+    pub fn local_time(&self) -> String {
+        self.time
+            .map(|time| time.with_timezone(&Local).format("%H:%M:%S").to_string())
+            .unwrap_or_default()
+    }
+}
+
+/// Which lines are shown, all of them when the query is empty and no severity is picked.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LogFilter {
+    pub query: String,
+    pub severities: Vec<Severity>,
+}
+
+impl LogFilter {
+    /// Whether `line` contains the query, ignoring case, and has one of the picked severities.
+    pub fn matches(&self, line: &LogLine) -> bool {
+        let query = self.query.trim().to_lowercase();
+        (query.is_empty() || line.text.to_lowercase().contains(&query))
+            && (self.severities.is_empty()
+                || line
+                    .severity
+                    .is_some_and(|severity| self.severities.contains(&severity)))
+    }
+
+    pub fn toggle(&mut self, severity: Severity) {
+        match self
+            .severities
+            .iter()
+            .position(|picked| *picked == severity)
+        {
+            Some(index) => _ = self.severities.remove(index),
+            None => self.severities.push(severity),
+        }
+    }
+}
+
+/// The logs of the viewed container as they are printed. Filtering works on the kept lines, so
+/// changing the filter never fetches them again.
+#[derive(Clone, Debug)]
+pub struct LogFollow {
+    /// The id of the container followed.
+    pub container: String,
+    /// Counts the follows so opening the tab again starts a new stream.
+    pub session: u64,
+    pub filter: LogFilter,
+    /// Scroll to new lines, paused while the user scrolled up.
+    pub following: bool,
+    /// Why the stream ended.
+    pub ended: Option<String>,
+    lines: VecDeque<LogLine>,
+}
+
+/// What the logs tab draws.
+#[derive(Clone, Debug, Default)]
+pub struct LogPanel {
+    /// The newest matching lines, oldest first.
+    pub lines: Vec<LogLine>,
+    /// Matching lines not drawn because they are older.
+    pub older: usize,
+    pub filter: LogFilter,
+    pub following: bool,
+    pub ended: Option<String>,
+}
+
+impl LogFollow {
+    pub fn new(container: String, session: u64, filter: LogFilter) -> Self {
+        Self {
+            container,
+            session,
+            filter,
+            following: true,
+            ended: None,
+            lines: VecDeque::new(),
+        }
+    }
+
+    /// Adds a line, dropping the oldest one over [`MAX_LINES`].
+    pub fn push(&mut self, line: LogLine) {
+        if self.lines.len() == MAX_LINES {
+            self.lines.pop_front();
+        }
+        self.lines.push_back(line);
+    }
+
+    pub fn panel(&self) -> LogPanel {
+        let matching = self
+            .lines
+            .iter()
+            .filter(|line| self.filter.matches(line))
+            .collect::<Vec<_>>();
+        let older = matching.len().saturating_sub(SHOWN_LINES);
+
+        LogPanel {
+            lines: matching[older..]
+                .iter()
+                .map(|line| (*line).clone())
+                .collect(),
+            older,
+            filter: self.filter.clone(),
+            following: self.following,
+            ended: self.ended.clone(),
+        }
+    }
+}
@@ -1,71 +1,1517 @@
+mod about;
 mod add_container;
+mod auto_stop;
+mod avatar;
+pub mod build_card;
+mod bulk;
 mod cantainer_card;
+mod changes;
+mod cleanup;
+mod container_list;
 mod container_view;
+mod credentials;
+mod events;
+mod exit;
+pub mod handlers;
+mod limits;
+mod logs;
+mod notes;
+mod prefetch;
+mod provenance;
+mod pull_progress;
+mod query;
+mod quick_start;
+mod refresh;
+mod retry;
+mod settings;
+mod sql_file;
+mod start_failure;
+pub mod start_run;
+mod state_badge;
+mod status_bar;
+mod stopping;
 mod subscription;
+mod terminal;
+mod thumbnails;
+mod tray;
+pub mod view_state;
+mod volumes;
+mod welcome;
 
 use self::{
-    add_container::{add_container, ButtonState},
-    cantainer_card::container_card,
-    container_view::container_view,
-    subscription::create_container,
+    about::about,
+    add_container::{add_container, AddContainerState, ButtonState},
+    auto_stop::AutoStopTimers,
+    build_card::{build_card, BuildJob, BuildPhase},
+    bulk::{run_bulk, BulkAction},
+    changes::{ExpectedChanges, Toast},
+    cleanup::{cleanup_view, format_size, CleanupResult},
+    container_list::{container_cards, page, with_companions, CardActions, CardLook, PAGE_SIZE},
+    container_view::{container_view, DetailTab, RawInspect},
+    credentials::{credentials_view, QuickCredentials},
+    events::EventLog,
+    exit::{exit_plan, ExitPlan, EXIT_CAP},
+    handlers::{ContainerMsg, CreateMsg, ThumbnailMsg, UiMsg},
+    limits::LimitsForm,
+    logs::{logs_id, LogFollow},
+    prefetch::{default_prefetch, prefetch_choices, prefetch_view, PrefetchRun},
+    provenance::StartProvenance,
+    query::QueryConsole,
+    quick_start::{quick_start, QuickStartState},
+    refresh::RefreshQueue,
+    retry::Retries,
+    settings::{settings, ConnectionTest, TlsTest},
+    sql_file::{file_name, SqlRun},
+    start_failure::start_failure_view,
+    start_run::StartRun,
+    status_bar::{docker_endpoint, status_bar},
+    stopping::StopAttempts,
+    subscription::{
+        cleanup, create_container, docker_events, exec_session, exec_with_input, focus_requests,
+        follow_logs, ordered_start, prefetch, tray_actions,
+    },
+    terminal::Terminal,
+    thumbnails::{Thumbnails, FLUSH_INTERVAL},
+    tray::{tray_entries, Tray, TrayAction, TrayEntry, TrayUpdate, SUPPORTED as TRAY_SUPPORTED},
+    view_state::MainViewState,
+    volumes::volumes_view,
+    welcome::{welcome, WelcomeFlow},
 };
 use crate::{
-    data::{ConfigFile, DatabaseConfig},
-    docker::{get_containers, start_container, stop_container, DbContainer, DbContainerConfig},
+    backup::{run_backup, split_missed, BackupOutcome, BackupSchedule},
+    data::{
+        persistence_problem, read_draft, remove_draft, write_draft, write_preferences, AppTheme,
+        CompanionConfig, ConfigFile, DatabaseConfig, Draft, Paths, Preferences, ViewMode,
+    },
+    dependencies::{dependents, graph, stop_order},
+    docker::{
+        disk_status, find_orphans, get_container_events, get_containers, get_disk_space,
+        get_engine_info, inspect_json, refresh_container, remove_container, running_count,
+        CleanupEvent, CleanupSummary, CreateContainerEvent, DbContainer, DbContainerConfig,
+        DbVolume, DiskSpace, DiskStatus, EngineInfo,
+    },
+    errors::explain,
+    exits::exit_notes,
+    http::build_client,
+    idle::{format_days, unix_now},
+    image_ref::ImageRef,
+    migrate::{plan_migration, MigrationPlan},
+    ports::PortRange,
+    registry::{remote_digests, update_available},
+    state_file::{write_state_file, StateDocument, STATE_FILE_INTERVAL},
+    validate::EntryReport,
+    versions::{eol_status, major_jump, EolStatus, MajorJump},
 };
-use bollard::Docker;
-use futures::{future, stream, StreamExt};
+use bollard::{service::ContainerStateStatusEnum, Docker};
+use chrono::{Local, NaiveDate};
+use futures::future;
 use iced::{
     alignment::{Horizontal, Vertical},
     executor::Default as DefaultExector,
     font,
-    widget::{button, column, container, image::Handle, row, scrollable, vertical_rule},
-    Application, Command, Length, Subscription, Theme,
+    keyboard::{self, KeyCode},
+    theme,
+    widget::{
+        button, checkbox, column, container, horizontal_rule, image::Handle, progress_bar, row,
+        scrollable, text, vertical_rule,
+    },
+    window, Application, Command, Length, Subscription, Theme,
 };
-use iced_aw::graphics::icons::ICON_FONT_BYTES;
+use iced_aw::{badge, graphics::icons::ICON_FONT_BYTES, BadgeStyles, Icon, Wrap, ICON_FONT};
 use itertools::Itertools;
-use std::collections::HashMap;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    net::TcpListener,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
 
+/// Every message belongs to a domain whose handler in [`handlers`] updates the app.
 #[derive(Clone, Debug)]
 pub enum Message {
-    GetContainers,
-    GetThumbnails,
-    FontLoaded(Result<(), font::Error>),
-    Error(String),
-    ContainersLoaded(Vec<DbContainer>),
-    StartContainer(String),
-    StopContainer(String),
-    ViewContainer(String),
-    LoadedThumbnails(HashMap<String, Handle>),
-    ShowCreateContainer,
-    CreateContainer(DbContainerConfig),
-    PullingContainer,
-    BuildingContainer,
-    BuildError(String),
-    CreatedContainer,
-    ImageDownload(String, f32),
+    Container(ContainerMsg),
+    Create(CreateMsg),
+    Thumbnail(ThumbnailMsg),
+    Ui(UiMsg),
+}
+
+impl Message {
+    /// Whether the message changes containers, volumes or images, these are ignored in read-only
+    /// mode.
+    fn is_mutating(&self) -> bool {
+        matches!(
+            self,
+            Message::Container(ContainerMsg::StartContainer(_))
+                | Message::Container(ContainerMsg::StopContainer(_))
+                | Message::Container(ContainerMsg::ForceStopContainer(_))
+                | Message::Container(ContainerMsg::RemoveVolume(_))
+                | Message::Container(ContainerMsg::RecreateContainer(..))
+                | Message::Container(ContainerMsg::MigrateContainer(_))
+                | Message::Container(ContainerMsg::RecreateWithProject(..))
+                | Message::Container(ContainerMsg::UpdateContainer(_))
+                | Message::Container(ContainerMsg::ApplyLimits(_))
+                | Message::Container(ContainerMsg::RunQuery(_))
+                | Message::Container(ContainerMsg::RunSqlFile(..))
+                | Message::Container(ContainerMsg::StartTerminal(_))
+                | Message::Container(ContainerMsg::RunBulk(_))
+                | Message::Container(ContainerMsg::SendTerminalInput(_))
+                | Message::Container(ContainerMsg::SetAutoStop(..))
+                | Message::Container(ContainerMsg::SetBackupSchedule(..))
+                | Message::Container(ContainerMsg::ChooseBackupDirectory(_))
+                | Message::Container(ContainerMsg::RetryBackup(_))
+                | Message::Create(CreateMsg::ShowCreateContainer)
+                | Message::Create(CreateMsg::ImportDefinition)
+                | Message::Create(CreateMsg::CreateContainer(_))
+                | Message::Create(CreateMsg::CreateCompanion(_))
+                | Message::Create(CreateMsg::ReplaceConflict)
+                | Message::Create(CreateMsg::RecreateDeleted(_))
+                | Message::Create(CreateMsg::RetryBuild)
+                | Message::Create(CreateMsg::DeleteFailedContainer)
+                | Message::Create(CreateMsg::ShowQuickStart)
+                | Message::Create(CreateMsg::QuickCreate)
+                | Message::Create(CreateMsg::ResumeDraft)
+                | Message::Ui(UiMsg::ShowCleanup)
+                | Message::Container(ContainerMsg::RunCleanup(_))
+                | Message::Container(ContainerMsg::RunPrefetch(_))
+        )
+    }
 }
 
-#[derive(Debug)]
-pub enum MainViewState {
-    CreateContainer(ButtonState),
-    ViewContainer(usize),
-    None,
+/// The create form is saved as a draft at most this often while it is filled in.
+const DRAFT_INTERVAL: Duration = Duration::from_secs(2);
+
+fn sidebar_id() -> scrollable::Id {
+    scrollable::Id::new("sidebar")
 }
 
 pub struct DbMgrApp {
-    containers: Vec<DbContainer>,
+    pub containers: Vec<DbContainer>,
+    /// Metadata updates for containers created by older versions, keyed by container id.
+    migrations: HashMap<String, MigrationPlan>,
+    /// The end of life status of the containers whose major version is past or near it, keyed
+    /// by id, updated with each refresh.
+    end_of_life: HashMap<String, EolStatus>,
+    /// The raw inspects fetched in developer mode, keyed by container id and dropped on refresh.
+    raw_inspects: HashMap<String, RawInspect>,
+    /// The query boxes of containers, keyed by container id and kept for the session.
+    query_consoles: HashMap<String, QueryConsole>,
+    /// The limits being edited, keyed by container id.
+    limit_forms: HashMap<String, LimitsForm>,
+    /// The detail tab last selected for each container id, kept for the session.
+    detail_tabs: HashMap<String, DetailTab>,
+    auto_stop: AutoStopTimers,
+    /// The containers sent a stop that didn't exit yet.
+    stops: StopAttempts,
+    /// Who started the containers that started while db-mgr was running.
+    starts: StartProvenance,
+    refresh: RefreshQueue,
+    /// When the containers were last loaded, in unix seconds.
+    last_refresh: Option<i64>,
+    /// Whether the last refresh reached docker, unknown before the first one.
+    connected: Option<bool>,
+    /// Where docker is reached, shown in the status bar.
+    endpoint: String,
+    /// Until when the cards of containers that changed state in a refresh are highlighted,
+    /// keyed by container id.
+    highlights: HashMap<String, Instant>,
+    /// Containers created or removed outside the app and failed operations that can be retried,
+    /// shown above the status bar.
+    toasts: Vec<Toast>,
+    /// The retries offered by the toasts, dropped with them.
+    retries: Retries<Message>,
+    /// The containers the user just acted on, whose changes aren't toasted.
+    expected_changes: ExpectedChanges,
+    /// Only the containers of this project are listed.
+    project_filter: Option<String>,
+    /// When the state file was last written, it is written at most once per
+    /// `STATE_FILE_INTERVAL`.
+    state_file_written: Option<Instant>,
+    /// Whether a refresh came in too soon after the last write and is still to be written.
+    state_file_pending: bool,
+    /// Whether a failed write was shown, later failures are only logged.
+    state_file_warned: bool,
+    volumes: Vec<DbVolume>,
+    dismissed_orphans: Option<Vec<String>>,
     images: Vec<DatabaseConfig>,
     docker: &'static Docker,
-    thumbnails: HashMap<String, Handle>,
-    main_view: MainViewState,
-    default_thumbnail: Handle,
-    build_subscription: Option<DbContainerConfig>,
+    thumbnails: Thumbnails,
+    /// Why the icons of these images could not be loaded, shown in the about view.
+    icon_errors: BTreeMap<String, String>,
+    /// Full values of large env variables of the viewed container, fetched on demand.
+    expanded_env: HashMap<String, String>,
+    pub main_view: MainViewState,
+    pub build_job: Option<BuildJob>,
+    /// The admin UIs offered for the container just created, with the config it was created with.
+    companion_offer: Option<(DbContainerConfig, Vec<CompanionConfig>)>,
+    /// A container that wasn't created or recreated because it would reuse data of another major
+    /// version, with the id of the container holding the data if there is one, shown as a banner.
+    version_block: Option<(String, MajorJump, Option<String>)>,
+    disk_space: Option<DiskSpace>,
+    /// Free bytes below which the low disk space banner is shown.
+    low_disk_space: u64,
+    /// The host ports database ports are assigned from.
+    port_range: PortRange,
+    /// Create containers even when the disk is critically full.
+    disk_override: bool,
+    create_draft: AddContainerState,
+    /// A create form saved before db-mgr was last closed, offered until it is resumed or
+    /// discarded.
+    draft_offer: Option<Draft>,
+    /// When the create form was last saved, it is saved at most once per `DRAFT_INTERVAL`.
+    draft_written: Option<Instant>,
+    /// Whether the create form changed since it was last saved.
+    draft_pending: bool,
+    quick_draft: QuickStartState,
+    /// Shown once the quick started container is created, then dropped.
+    quick_credentials: Option<QuickCredentials>,
+    validation: Option<Vec<EntryReport>>,
+    validating: bool,
+    preferences: Preferences,
+    /// Where db-mgr instances started while this one runs ask it to focus.
+    focus_listener: Option<&'static TcpListener>,
+    http: reqwest::Client,
+    connection_test: Option<ConnectionTest>,
+    tls_test: Option<TlsTest>,
+    cleanup_summary: Option<CleanupSummary>,
+    cleanup_results: Vec<CleanupResult>,
+    cleanup_subscription: Option<CleanupSummary>,
+    /// The images to prefetch, as `image:tag`, starting with the default tag of each entry.
+    prefetch_selection: BTreeSet<String>,
+    /// The last prefetch, pulling while it is running.
+    prefetch: Option<PrefetchRun>,
+    /// The last start of several containers in the order of their dependencies, kept after it
+    /// finished until it is dismissed if one of them didn't come up.
+    pub start_run: Option<StartRun>,
+    /// Loaded when the about view is opened.
+    engine_info: Option<EngineInfo>,
+    /// The exec terminal of the viewed container, closed when its tab is left.
+    terminal: Option<Terminal>,
+    /// The containers selected for bulk actions, `None` outside selection mode.
+    selection: Option<HashSet<String>>,
+    /// The idle container the user is asked to stop.
+    idle_prompt: Option<String>,
+    /// A dropped SQL file and the ids of the containers the user picks from to run it.
+    pending_drop: Option<(PathBuf, Vec<String>)>,
+    sql_run: Option<SqlRun>,
+    /// Counts the SQL files run, so each starts a new exec.
+    sql_runs: u64,
+    /// The logs of the viewed container, followed while its logs tab is shown.
+    log_follow: Option<LogFollow>,
+    /// Counts the log follows, so opening the tab again starts a new one.
+    log_follows: u64,
+    /// How many containers are being stopped before the window closes.
+    exiting: Option<usize>,
+    /// The tray icon, kept once shown since its thread can't be stopped.
+    tray: Option<&'static Tray>,
+    /// The favourites last shown in the tray menu, so it is only rebuilt when they change.
+    tray_entries: Vec<TrayEntry>,
+    /// The welcome flow shown on the first start, dropped once it is finished or skipped.
+    welcome: Option<WelcomeFlow>,
+    /// Started with `--read-only`, actions that change containers are hidden and ignored.
+    read_only: bool,
+    paths: Option<Paths>,
+    /// Why settings can't be saved, shown as a banner.
+    persistence_problem: Option<String>,
+    /// How far the container list is scrolled, restored after it is refreshed.
+    sidebar_offset: scrollable::AbsoluteOffset,
+    /// Names of the containers being backed up.
+    backups_running: HashSet<String>,
+    /// The name of the container whose last scheduled backup failed and why, shown as a banner.
+    backup_failure: Option<(String, String)>,
+    events: EventLog,
+    /// The registries are being asked for newer images.
+    checking_updates: bool,
+    /// How many containers the sidebar shows, grows a page at a time.
+    list_limit: usize,
+}
+
+impl DbMgrApp {
+    fn refresh_containers(&self, sequence: Option<u64>) -> Command<Message> {
+        match sequence {
+            Some(sequence) => {
+                Command::perform(get_containers(self.docker), move |result| match result {
+                    Err(ex) => Message::Container(ContainerMsg::ContainersFailed(
+                        sequence,
+                        Box::new(explain("Could not get containers", &ex)),
+                    )),
+                    Ok(containers) => {
+                        Message::Container(ContainerMsg::ContainersLoaded(sequence, containers))
+                    }
+                })
+            }
+            None => Command::none(),
+        }
+    }
+
+    /// Inspects only the container `id` again, falling back to a full refresh if that fails.
+    fn refresh_container(&self, id: String) -> Command<Message> {
+        let docker = self.docker;
+        Command::perform(
+            async move {
+                let result = refresh_container(docker, &id).await;
+                (id, result)
+            },
+            |(id, result)| match result {
+                Err(ex) => {
+                    eprintln!("Could not refresh container {id}: {ex}");
+                    Message::Container(ContainerMsg::GetContainers)
+                }
+                Ok(container) => Message::Container(ContainerMsg::ContainerRefreshed(
+                    id,
+                    container.map(Box::new),
+                )),
+            },
+        )
+    }
+
+    /// The ids of the listed containers named `names`, given without the leading `/` docker
+    /// adds. Names that aren't listed are left out.
+    fn ids_of(&self, names: &[String]) -> Vec<String> {
+        self.containers
+            .iter()
+            .filter(|container| {
+                names
+                    .iter()
+                    .any(|name| name == container.name.trim_start_matches('/'))
+            })
+            .map(|container| container.id.clone())
+            .collect()
+    }
+
+    /// Brings everything kept about the containers in line with the list after it changed.
+    fn containers_changed(&mut self) {
+        self.stops.retain(
+            self.containers
+                .iter()
+                .filter(|c| c.state == ContainerStateStatusEnum::RUNNING)
+                .map(|c| c.id.as_str()),
+        );
+        self.retain_project_filter();
+        self.refresh_tray();
+        if let Some(selection) = self.selection.as_mut() {
+            selection.retain(|id| self.containers.iter().any(|c| &c.id == id));
+        }
+        let ids = self.containers.iter().map(|c| c.id.as_str());
+        if self.preferences.idle.sync(ids, unix_now()) {
+            write_preferences(self.paths.as_ref(), &self.preferences);
+        }
+        self.check_idle();
+        self.migrations = self
+            .containers
+            .iter()
+            .filter_map(|container| {
+                let plan = plan_migration(container, self.database_for(container))?;
+                Some((container.id.clone(), plan))
+            })
+            .collect();
+        let today = Local::now().date_naive();
+        self.end_of_life = self
+            .containers
+            .iter()
+            .filter_map(|container| {
+                Some((container.id.clone(), self.eol_status(container, today)?))
+            })
+            .collect();
+        self.main_view = std::mem::replace(&mut self.main_view, MainViewState::None)
+            .after_refresh(&self.containers);
+    }
+
+    /// The names of the containers as entered when they were created.
+    fn container_names(&self) -> Vec<String> {
+        self.containers
+            .iter()
+            .map(|container| {
+                let name = container.name.trim_start_matches('/');
+                name.strip_prefix("db-mgr__").unwrap_or(name).to_string()
+            })
+            .collect()
+    }
+
+    fn orphans(&self) -> Vec<DbVolume> {
+        find_orphans(&self.volumes, &self.containers)
+    }
+
+    fn show_orphan_banner(&self, orphans: &[DbVolume]) -> bool {
+        if orphans.is_empty() {
+            return false;
+        }
+
+        let names = orphans
+            .iter()
+            .map(|volume| volume.name.clone())
+            .sorted()
+            .collect::<Vec<_>>();
+        self.dismissed_orphans.as_ref() != Some(&names)
+    }
+
+    /// The terminal of the container `id`, replacing the terminal of any other container.
+    fn terminal_for(&mut self, id: String) -> &mut Terminal {
+        if self
+            .terminal
+            .as_ref()
+            .is_none_or(|terminal| terminal.container != id)
+        {
+            self.terminal = None;
+        }
+
+        self.terminal.get_or_insert_with(|| Terminal::new(id))
+    }
+
+    /// Ends the terminal session once its tab is no longer shown. The subscription was dropped
+    /// with the tab, which closed the connection.
+    fn close_hidden_terminal(&mut self) {
+        let visible = match self.visible_tab() {
+            Some((container, DetailTab::Exec)) => Some(container.id.clone()),
+            _ => None,
+        };
+
+        if let Some(terminal) = self.terminal.as_mut() {
+            if terminal.is_open() && visible.as_ref() != Some(&terminal.container) {
+                terminal.end("The session was closed when leaving the tab".to_string());
+            }
+        }
+    }
+
+    /// The container shown in the detail pane and its selected tab, subscriptions that feed a tab
+    /// only run while it is visible.
+    fn visible_tab(&self) -> Option<(&DbContainer, DetailTab)> {
+        let MainViewState::ViewContainer(id) = &self.main_view else {
+            return None;
+        };
+        let container = self.containers.iter().find(|c| &c.id == id)?;
+
+        Some((
+            container,
+            self.detail_tabs
+                .get(&container.id)
+                .copied()
+                .unwrap_or_default(),
+        ))
+    }
+
+    /// How many days the container has been running untouched, `None` unless it counts as idle.
+    fn idle_days(&self, container: &DbContainer) -> Option<u64> {
+        self.preferences.idle.idle_days(
+            &container.id,
+            container.state == ContainerStateStatusEnum::RUNNING,
+            self.preferences.idle_after,
+            unix_now(),
+        )
+    }
+
+    /// Whether the registry has a newer image for the tag of the container, unknown without a
+    /// recorded digest or a successful lookup.
+    fn update_available(&self, container: &DbContainer) -> bool {
+        let image = ImageRef::parse(&container.image);
+        update_available(
+            container.image_digest(),
+            self.preferences.digests.digest(&image),
+        )
+    }
+
+    /// The end of life status of the tag of `container` on `today`, `None` when its config entry
+    /// has no date for its major version.
+    fn eol_status(&self, container: &DbContainer, today: NaiveDate) -> Option<EolStatus> {
+        let database = self.database_for(container)?;
+        let tag = ImageRef::parse(&container.image).tag?;
+        eol_status(
+            database.version_pattern.as_deref(),
+            &tag,
+            &database.eol_dates,
+            today,
+        )
+    }
+
+    /// The end of life warning shown for `container`, unless it was silenced.
+    fn eol_warning(&self, container: &DbContainer) -> Option<EolStatus> {
+        if self
+            .preferences
+            .eol_silenced
+            .contains(container.name.trim_start_matches('/'))
+        {
+            return None;
+        }
+        self.end_of_life.get(&container.id).copied()
+    }
+
+    /// Looks up the digests of the container tags not checked in the last day.
+    fn check_updates(&mut self) -> Command<Message> {
+        if self.checking_updates {
+            return Command::none();
+        }
+
+        let images = self
+            .containers
+            .iter()
+            .filter(|container| container.image_digest().is_some())
+            .map(|container| ImageRef::parse(&container.image))
+            .collect::<Vec<_>>();
+        let stale = self.preferences.digests.stale(&images, unix_now());
+        if stale.is_empty() {
+            return Command::none();
+        }
+
+        self.checking_updates = true;
+        Command::perform(remote_digests(self.http.clone(), stale), |digests| {
+            Message::Container(ContainerMsg::DigestsChecked(digests))
+        })
+    }
+
+    /// Records that the container was used, which restarts its idle time.
+    fn touch(&mut self, id: &str) {
+        self.preferences.idle.touch(id, unix_now());
+        write_preferences(self.paths.as_ref(), &self.preferences);
+    }
+
+    /// Asks about the next idle container once the current one is answered or no longer idle.
+    fn check_idle(&mut self) {
+        let current = self
+            .idle_prompt
+            .as_ref()
+            .and_then(|id| self.containers.iter().find(|c| &c.id == id));
+        if current.is_some_and(|container| self.idle_days(container).is_some()) {
+            return;
+        }
+
+        self.idle_prompt = None;
+        if self.read_only {
+            return;
+        }
+
+        let now = unix_now();
+        let Some(id) = self
+            .containers
+            .iter()
+            .filter(|container| self.idle_days(container).is_some())
+            .map(|container| container.id.clone())
+            .find(|id| self.preferences.idle.may_prompt(id, now))
+        else {
+            return;
+        };
+
+        self.preferences.idle.prompted(&id, now);
+        write_preferences(self.paths.as_ref(), &self.preferences);
+        self.idle_prompt = Some(id);
+    }
+
+    /// The ids of the running containers whose config can run SQL files.
+    fn sql_candidates(&self) -> Vec<String> {
+        self.containers
+            .iter()
+            .filter(|container| container.state == ContainerStateStatusEnum::RUNNING)
+            .filter(|container| {
+                self.database_for(container)
+                    .is_some_and(DatabaseConfig::runs_sql)
+            })
+            .map(|container| container.id.clone())
+            .collect()
+    }
+
+    fn container_id(&self, name: &str) -> Option<String> {
+        self.containers
+            .iter()
+            .find(|container| container.name.trim_start_matches('/') == name)
+            .map(|container| container.id.clone())
+    }
+
+    /// The running containers outside of `names` that depend on any of `names`.
+    fn running_dependents(&self, names: &[String]) -> Vec<String> {
+        let graph = graph(&self.containers);
+        names
+            .iter()
+            .flat_map(|name| dependents(&graph, name))
+            .filter(|dependent| !names.contains(dependent))
+            .filter(|dependent| {
+                self.containers.iter().any(|container| {
+                    container.name.trim_start_matches('/') == dependent
+                        && container.state == ContainerStateStatusEnum::RUNNING
+                })
+            })
+            .unique()
+            .sorted()
+            .collect()
+    }
+
+    /// `names` ordered so dependents are stopped first, as given if they depend on each other in
+    /// a cycle.
+    fn stop_order(&self, names: Vec<String>) -> Vec<String> {
+        match stop_order(&graph(&self.containers), &names) {
+            Ok(order) => order,
+            Err(cycle) => {
+                eprintln!("Containers depend on each other: {}", cycle.join(" -> "));
+                names
+            }
+        }
+    }
+
+    /// Closes the window, stopping the running containers first when db-mgr is set to.
+    fn exit(&mut self) -> Command<Message> {
+        let plan = exit_plan(
+            &self.containers,
+            self.preferences.stop_on_exit,
+            &self.preferences.keep_on_exit,
+            self.read_only,
+            self.exiting.is_some(),
+        );
+        let ExitPlan::Stop(names) = plan else {
+            return window::close();
+        };
+
+        let containers = names
+            .into_iter()
+            .filter_map(|name| Some((self.container_id(&name)?, name)))
+            .collect::<Vec<_>>();
+        self.exiting = Some(containers.len());
+        Command::perform(
+            run_bulk(self.docker, BulkAction::Stop, containers),
+            |results| Message::Ui(UiMsg::ExitStopped(results)),
+        )
+    }
+
+    /// Rebuilds the tray menu if the favourites or their states changed.
+    fn refresh_tray(&mut self) {
+        let Some(tray) = self.tray else {
+            return;
+        };
+        let entries = tray_entries(
+            &self.containers,
+            &self.preferences.favourites,
+            self.read_only,
+        );
+        if entries != self.tray_entries {
+            self.tray_entries = entries.clone();
+            tray.update(TrayUpdate::Entries(entries));
+        }
+    }
+
+    /// The distinct projects of the containers, sorted.
+    fn projects(&self) -> Vec<String> {
+        self.containers
+            .iter()
+            .filter_map(|container| self.preferences.project(container))
+            .unique()
+            .sorted()
+            .collect()
+    }
+
+    /// Whether `container` is listed with the project filter.
+    fn in_project_filter(&self, container: &DbContainer) -> bool {
+        self.project_filter
+            .as_ref()
+            .is_none_or(|project| self.preferences.project(container).as_ref() == Some(project))
+    }
+
+    /// Clears the project filter once no container is in the project anymore.
+    fn retain_project_filter(&mut self) {
+        if let Some(project) = self.project_filter.as_ref() {
+            if !self.projects().contains(project) {
+                self.project_filter = None;
+            }
+        }
+    }
+
+    fn project_chips(&self) -> Option<iced::Element<'_, Message>> {
+        let projects = self.projects();
+        if projects.is_empty() {
+            return None;
+        }
+
+        let chips = projects.into_iter().map(|project| {
+            let selected = self.project_filter.as_ref() == Some(&project);
+            button(text(&project).size(12))
+                .style(if selected {
+                    theme::Button::Primary
+                } else {
+                    theme::Button::Secondary
+                })
+                .padding([2, 8])
+                .on_press(Message::Ui(UiMsg::FilterProject(
+                    (!selected).then_some(project),
+                )))
+                .into()
+        });
+
+        Some(
+            Wrap::with_elements(chips.collect())
+                .spacing(5.0)
+                .line_spacing(5.0)
+                .padding(5.0)
+                .into(),
+        )
+    }
+
+    /// Marks the container as changed by the user, so refreshes don't announce the change.
+    fn expect_change(&mut self, name: &str) {
+        self.expected_changes.expect(name, Instant::now());
+    }
+
+    /// Writes the loaded containers to the state file, or leaves them pending when the last write
+    /// was less than `STATE_FILE_INTERVAL` ago.
+    fn write_state(&mut self, now: Instant) -> Command<Message> {
+        let Some(path) = self.preferences.state_file.clone() else {
+            self.state_file_pending = false;
+            return Command::none();
+        };
+        // Nothing is written until the first refresh
+        let Some(updated_at) = self.last_refresh else {
+            return Command::none();
+        };
+        if self
+            .state_file_written
+            .is_some_and(|written| now.duration_since(written) < STATE_FILE_INTERVAL)
+        {
+            self.state_file_pending = true;
+            return Command::none();
+        }
+
+        self.state_file_written = Some(now);
+        self.state_file_pending = false;
+        let document = StateDocument::new(
+            &self.containers,
+            |container| self.preferences.project(container),
+            updated_at,
+        );
+        Command::perform(
+            async move {
+                tokio::task::spawn_blocking(move || write_state_file(&path, &document))
+                    .await
+                    .map_err(anyhow::Error::from)
+                    .and_then(|result| result)
+                    .map_err(|ex| format!("{ex:#}"))
+            },
+            |result| Message::Ui(UiMsg::StateFileWritten(result)),
+        )
+    }
+
+    /// Saves the create form as a draft, or leaves it pending when it was saved less than
+    /// `DRAFT_INTERVAL` ago. Passwords are only saved when they are remembered.
+    fn save_draft(&mut self, now: Instant) {
+        if self
+            .draft_written
+            .is_some_and(|written| now.duration_since(written) < DRAFT_INTERVAL)
+        {
+            self.draft_pending = true;
+            return;
+        }
+
+        self.draft_written = Some(now);
+        self.draft_pending = false;
+        if let Some(draft) = self.create_draft.draft(self.preferences.remember_passwords) {
+            write_draft(self.paths.as_ref(), &draft);
+            // The saved draft was replaced
+            self.draft_offer = None;
+        }
+    }
+
+    /// Deletes the saved draft, once its container was created or it was discarded.
+    fn forget_draft(&mut self) {
+        self.draft_pending = false;
+        self.draft_offer = None;
+        remove_draft(self.paths.as_ref());
+    }
+
+    fn container_name(&self, id: &str) -> String {
+        self.containers
+            .iter()
+            .find(|container| container.id == id)
+            .map(|container| container.name.trim_start_matches('/').to_string())
+            .unwrap_or_else(|| id.to_string())
+    }
+
+    /// Shows how the SQL file ran and clears it.
+    fn finish_sql_run(&mut self, exit_code: Option<i64>) -> Command<Message> {
+        let Some(sql_run) = self.sql_run.take() else {
+            return Command::none();
+        };
+
+        if let Err(dialog_err) = native_dialog::MessageDialog::new()
+            .set_title("SQL file")
+            .set_text(&sql_run.summary(exit_code))
+            .set_type(if exit_code == Some(0) {
+                native_dialog::MessageType::Info
+            } else {
+                native_dialog::MessageType::Warning
+            })
+            .show_alert()
+        {
+            eprintln!("Dialog Error: {dialog_err}");
+        }
+        Command::none()
+    }
+
+    fn sql_banner(&self) -> Option<iced::Element<'_, Message>> {
+        let content = if let Some(sql_run) = self.sql_run.as_ref() {
+            let status = text(format!(
+                "Running {} against {}",
+                file_name(&sql_run.file),
+                sql_run.name
+            ));
+            match sql_run.cmd {
+                Some(_) => column!(
+                    status,
+                    progress_bar(0.0..=1.0, sql_run.fraction()).height(8)
+                ),
+                None => column!(status),
+            }
+        } else {
+            let (file, ids) = self.pending_drop.as_ref()?;
+            let targets = ids.iter().map(|id| {
+                button(text(self.container_name(id)))
+                    .on_press(Message::Container(ContainerMsg::RunSqlFile(
+                        id.clone(),
+                        file.clone(),
+                    )))
+                    .into()
+            });
+            column!(
+                text(format!("Run {} against", file_name(file))),
+                Wrap::with_elements(
+                    targets
+                        .chain([button("Cancel")
+                            .style(theme::Button::Secondary)
+                            .on_press(Message::Container(ContainerMsg::CancelDrop))
+                            .into()])
+                        .collect()
+                )
+                .spacing(5.0)
+                .line_spacing(5.0)
+            )
+        };
+
+        Some(column!(content.spacing(5).padding(5), horizontal_rule(2)).into())
+    }
+
+    /// Fetches the events docker reported for container `id` in the last day, once per session.
+    fn backfill_events(&mut self, id: &str) -> Command<Message> {
+        if !self.events.start_backfill(id) {
+            return Command::none();
+        }
+
+        let (id, until) = (id.to_string(), unix_now() as i64);
+        Command::perform(
+            get_container_events(id.clone(), until - 24 * 60 * 60, until, self.docker),
+            move |result| {
+                Message::Container(ContainerMsg::EventsBackfilled(
+                    id,
+                    result.map_err(|ex| format!("{ex}")),
+                ))
+            },
+        )
+    }
+
+    /// Fetches the raw inspect of container `id` in developer mode unless it is cached.
+    fn load_inspect(&mut self, id: &str) -> Command<Message> {
+        if !self.preferences.developer_mode || self.raw_inspects.contains_key(id) {
+            return Command::none();
+        }
+
+        self.raw_inspects
+            .insert(id.to_string(), RawInspect::Loading);
+        self.fetch_inspect(id.to_string())
+    }
+
+    fn fetch_inspect(&self, id: String) -> Command<Message> {
+        Command::perform(inspect_json(id.clone(), self.docker), move |result| {
+            Message::Container(ContainerMsg::InspectLoaded(
+                id,
+                result.map_err(|ex| format!("{ex:#}")),
+            ))
+        })
+    }
+
+    /// Drops the cached raw inspects after a refresh, the one shown is fetched again and kept
+    /// until it arrives.
+    fn refresh_inspects(&mut self) -> Command<Message> {
+        let shown = match self.visible_tab() {
+            Some((container, DetailTab::Inspect)) => Some(container.id.clone()),
+            _ => None,
+        };
+        self.raw_inspects.retain(|id, _| shown.as_ref() == Some(id));
+        match shown {
+            Some(id) if self.preferences.developer_mode => self.fetch_inspect(id),
+            _ => Command::none(),
+        }
+    }
+
+    /// Follows the logs of container `id` from the recent lines again, keeping the filter if it
+    /// was the container followed last.
+    fn open_logs(&mut self, id: &str) {
+        let filter = self
+            .log_follow
+            .take()
+            .filter(|follow| follow.container == id)
+            .map(|follow| follow.filter)
+            .unwrap_or_default();
+        self.log_follows += 1;
+        self.log_follow = Some(LogFollow::new(id.to_string(), self.log_follows, filter));
+    }
+
+    /// Scrolls the followed logs to the newest line unless the user scrolled up.
+    fn snap_logs(&self) -> Command<Message> {
+        match self.log_follow.as_ref() {
+            Some(follow) if follow.following => {
+                scrollable::snap_to(logs_id(), scrollable::RelativeOffset::END)
+            }
+            _ => Command::none(),
+        }
+    }
+
+    /// Runs the scheduled backups that are due, noting the ones missed while db-mgr was closed
+    /// or read-only, or the container was stopped, as skipped.
+    fn run_due_backups(&mut self) -> Command<Message> {
+        let now = Local::now();
+        let mut changed = false;
+        let mut commands = vec![];
+        for (name, schedule) in self.preferences.backups.iter_mut() {
+            let (missed, due) = split_missed(schedule.due_runs(&now), &now);
+            if missed.is_empty() && due.is_none() {
+                continue;
+            }
+            changed = true;
+            schedule.checked = now.timestamp();
+            for run in missed {
+                schedule.record(
+                    run.timestamp(),
+                    BackupOutcome::Skipped("db-mgr was not running".to_string()),
+                );
+            }
+            let Some(due) = due else {
+                continue;
+            };
+
+            let container = self
+                .containers
+                .iter()
+                .find(|container| container.name.trim_start_matches('/') == name);
+            let skipped = match container {
+                _ if self.read_only => Some("db-mgr was in read-only mode"),
+                None => Some("the container no longer exists"),
+                Some(container) if container.state != ContainerStateStatusEnum::RUNNING => {
+                    Some("the container was stopped")
+                }
+                Some(_) if self.backups_running.contains(name) => {
+                    Some("the previous backup was still running")
+                }
+                Some(_) => None,
+            };
+            if let Some(reason) = skipped {
+                schedule.record(due.timestamp(), BackupOutcome::Skipped(reason.to_string()));
+                continue;
+            }
+            let Some(container) = container else {
+                continue;
+            };
+
+            let Some(backup) = backup_command(
+                self.docker,
+                &self.images,
+                container,
+                schedule,
+                due.timestamp(),
+            ) else {
+                schedule.record(
+                    due.timestamp(),
+                    BackupOutcome::Failed("its config entry has no backup command".to_string()),
+                );
+                continue;
+            };
+            self.backups_running.insert(name.clone());
+            commands.push(backup);
+        }
+
+        if changed {
+            write_preferences(self.paths.as_ref(), &self.preferences);
+        }
+        Command::batch(commands)
+    }
+
+    fn start_banner(&self) -> Option<iced::Element<'_, Message>> {
+        let run = self.start_run.as_ref()?;
+
+        let mut content = column!().spacing(5);
+        if let Some(warning) = run.warning.as_ref() {
+            content = content.push(
+                text(format!("{warning}, starting them all at once"))
+                    .size(12)
+                    .style(theme::Text::Color(iced::Color::from_rgb8(230, 160, 40))),
+            );
+        }
+        for line in run.lines(Instant::now()) {
+            content = content.push(text(line).size(12));
+        }
+        content = content.push(match run.running {
+            true => button("Cancel")
+                .style(theme::Button::Secondary)
+                .on_press(Message::Container(ContainerMsg::CancelStart)),
+            false => button("Dismiss")
+                .style(theme::Button::Secondary)
+                .on_press(Message::Container(ContainerMsg::CancelStart)),
+        });
+
+        Some(column!(content.padding(5), horizontal_rule(2)).into())
+    }
+
+    fn backup_banner(&self) -> Option<iced::Element<'_, Message>> {
+        let (name, failure) = self.backup_failure.as_ref()?;
+
+        Some(
+            column!(
+                column!(
+                    text(failure),
+                    row!(
+                        // Retrying dumps the database, which read-only mode doesn't allow
+                        button("Retry").on_press_maybe((!self.read_only).then(|| {
+                            Message::Container(ContainerMsg::RetryBackup(name.clone()))
+                        })),
+                        button("Dismiss")
+                            .style(theme::Button::Secondary)
+                            .on_press(Message::Container(ContainerMsg::DismissBackupFailure))
+                    )
+                    .spacing(5)
+                )
+                .spacing(5)
+                .padding(5),
+                horizontal_rule(2)
+            )
+            .into(),
+        )
+    }
+
+    fn version_banner(&self) -> Option<iced::Element<'_, Message>> {
+        let (name, jump, id) = self.version_block.as_ref()?;
+
+        let mut actions = row!().spacing(5);
+        if let Some(id) = id {
+            actions = actions
+                .push(button("Open backups").on_press(Message::Ui(UiMsg::OpenBackups(id.clone()))));
+        }
+        actions = actions.push(
+            button("Dismiss")
+                .style(theme::Button::Secondary)
+                .on_press(Message::Create(CreateMsg::DismissVersionWarning)),
+        );
+
+        Some(
+            column!(
+                column!(
+                    text(format!(
+                        "{name} was not created: its data was written by version {} and version {} can't start on it. \
+                         Back up the data with version {}, create {name} on new volumes and restore the backup by \
+                         dropping it onto the new container.",
+                        jump.from, jump.to, jump.from
+                    )),
+                    actions
+                )
+                .spacing(5)
+                .padding(5),
+                horizontal_rule(2)
+            )
+            .into(),
+        )
+    }
+
+    /// The major version jump `config` would make over the data of the container `id` or the
+    /// volumes it reuses, `None` when its config entry has no version pattern.
+    fn version_jump(&self, id: Option<&str>, config: &DbContainerConfig) -> Option<MajorJump> {
+        let pattern = self
+            .images
+            .iter()
+            .find(|database| database.name == config.database)?
+            .version_pattern
+            .as_deref();
+        let container_tag = id
+            .and_then(|id| self.containers.iter().find(|container| container.id == id))
+            .and_then(|container| ImageRef::parse(&container.image).tag);
+        let volume_tags = self
+            .volumes
+            .iter()
+            .filter(|volume| config.reuse_volumes && config.volumes.contains_key(&volume.name))
+            .filter_map(|volume| volume.tag.clone());
+
+        container_tag
+            .into_iter()
+            .chain(volume_tags)
+            .find_map(|tag| major_jump(pattern, &tag, &config.tag))
+    }
+
+    /// Shows the major version warning instead of creating `config` when it would reuse data of
+    /// another major, returns whether it did.
+    fn block_version_jump(&mut self, id: Option<&str>, config: &DbContainerConfig) -> bool {
+        let Some(jump) = self.version_jump(id, config) else {
+            return false;
+        };
+        let holder = id.map(str::to_string).or_else(|| {
+            self.containers
+                .iter()
+                .find(|container| container.name.trim_start_matches('/') == config.name)
+                .map(|container| container.id.clone())
+        });
+        self.version_block = Some((config.name.clone(), jump, holder));
+        true
+    }
+
+    fn idle_banner(&self) -> Option<iced::Element<'_, Message>> {
+        let id = self.idle_prompt.as_ref()?;
+        let container = self.containers.iter().find(|c| &c.id == id)?;
+        let days = self.idle_days(container)?;
+
+        Some(
+            column!(
+                column!(
+                    text(format!(
+                        "{} has been running untouched for {} — stop it?",
+                        container.name.trim_start_matches('/'),
+                        format_days(days)
+                    )),
+                    row!(
+                        button("Stop")
+                            .on_press(Message::Container(ContainerMsg::StopContainer(id.clone()))),
+                        button("Remind me later")
+                            .style(theme::Button::Secondary)
+                            .on_press(Message::Container(ContainerMsg::RemindIdleLater)),
+                        button("Ignore this container")
+                            .style(theme::Button::Secondary)
+                            .on_press(Message::Container(ContainerMsg::IgnoreIdle(id.clone())))
+                    )
+                    .spacing(5)
+                )
+                .spacing(5)
+                .padding(5),
+                horizontal_rule(2)
+            )
+            .into(),
+        )
+    }
+
+    fn check_disk_space(&self, container_config: Option<DbContainerConfig>) -> Command<Message> {
+        Command::perform(get_disk_space(self.docker), move |space| {
+            Message::Create(CreateMsg::DiskChecked(
+                space,
+                container_config.map(Box::new),
+            ))
+        })
+    }
+
+    fn persistence_banner(&self) -> Option<iced::Element<'_, Message>> {
+        let problem = self.persistence_problem.as_ref()?;
+
+        Some(
+            column!(
+                container(
+                    text(problem)
+                        .size(12)
+                        .style(theme::Text::Color(iced::Color::from_rgb8(230, 160, 40)))
+                )
+                .padding(5),
+                horizontal_rule(2)
+            )
+            .into(),
+        )
+    }
+
+    fn bulk_bar(&self) -> Option<iced::Element<'_, Message>> {
+        let selection = self.selection.as_ref()?;
+
+        let mut bar = row!(text(format!("{} selected", selection.len())).width(Length::Fill))
+            .align_items(iced::Alignment::Center)
+            .spacing(5);
+        if !selection.is_empty() {
+            bar = bar
+                .push(
+                    button("Start")
+                        .on_press(Message::Container(ContainerMsg::RunBulk(BulkAction::Start))),
+                )
+                .push(
+                    button("Stop")
+                        .on_press(Message::Container(ContainerMsg::RunBulk(BulkAction::Stop))),
+                )
+                .push(button("Remove").style(theme::Button::Destructive).on_press(
+                    Message::Container(ContainerMsg::RunBulk(BulkAction::Remove)),
+                ));
+        }
+        bar = bar.push(
+            button("Clear")
+                .style(theme::Button::Secondary)
+                .on_press(Message::Ui(UiMsg::ToggleSelection)),
+        );
+
+        Some(column!(bar.padding(5), horizontal_rule(2)).into())
+    }
+
+    fn disk_banner(&self) -> Option<iced::Element<'_, Message>> {
+        let space = self.disk_space?;
+        let status = disk_status(space, self.low_disk_space);
+        if status == DiskStatus::Ok {
+            return None;
+        }
+
+        let mut summary = row!(text(format!(
+            "Only {} of {} free on the docker disk",
+            format_size(space.available as i64),
+            format_size(space.total as i64)
+        ))
+        .width(Length::Fill))
+        .align_items(iced::Alignment::Center)
+        .spacing(5);
+        if !self.read_only {
+            summary = summary.push(button("Clean up").on_press(Message::Ui(UiMsg::ShowCleanup)));
+        }
+
+        let mut banner = column!(summary).spacing(5).padding(5);
+
+        if status == DiskStatus::Critical {
+            banner = banner.push(checkbox(
+                "Create containers anyway",
+                self.disk_override,
+                |disk_override| Message::Create(CreateMsg::OverrideDiskCheck(disk_override)),
+            ));
+        }
+
+        Some(column!(banner, horizontal_rule(2)).into())
+    }
+
+    fn toasts_view(&self) -> Option<iced::Element<'_, Message>> {
+        if self.toasts.is_empty() {
+            return None;
+        }
+
+        let toasts = self.toasts.iter().enumerate().map(|(index, toast)| {
+            let mut toast_row = row!(
+                text(Icon::InfoCircle).font(ICON_FONT),
+                text(&toast.message).width(Length::Fill)
+            )
+            .align_items(iced::Alignment::Center)
+            .spacing(10);
+            if let Some(retry) = toast.retry {
+                toast_row =
+                    toast_row.push(button("Retry").on_press(Message::Ui(UiMsg::Retry(retry))));
+            }
+            toast_row
+                .push(
+                    button("Dismiss")
+                        .style(theme::Button::Secondary)
+                        .on_press(Message::Ui(UiMsg::DismissToast(index))),
+                )
+                .into()
+        });
+
+        Some(
+            column!(
+                horizontal_rule(2),
+                column(toasts.collect()).spacing(5).padding([5, 10])
+            )
+            .into(),
+        )
+    }
+
+    /// Fetches the engine info unless it is loaded, the create form needs it to offer GPUs.
+    fn load_engine_info(&self) -> Command<Message> {
+        match self.engine_info {
+            Some(_) => Command::none(),
+            None => Command::perform(get_engine_info(self.docker), |engine| {
+                Message::Ui(UiMsg::EngineInfoLoaded(Box::new(engine)))
+            }),
+        }
+    }
+
+    fn companion_offer_view(&self) -> Option<iced::Element<'_, Message>> {
+        let (_, companions) = self.companion_offer.as_ref()?;
+
+        let offers = companions.iter().enumerate().map(|(index, companion)| {
+            row!(
+                text(Icon::Window).font(ICON_FONT),
+                text(format!(
+                    "Also start {} connected to this database?",
+                    companion.name
+                ))
+                .width(Length::Fill),
+                button("Start").on_press(Message::Create(CreateMsg::CreateCompanion(index)))
+            )
+            .align_items(iced::Alignment::Center)
+            .spacing(10)
+            .into()
+        });
+
+        Some(
+            column!(
+                horizontal_rule(2),
+                row!(
+                    column(offers.collect()).spacing(5).width(Length::Fill),
+                    button("Dismiss")
+                        .style(theme::Button::Secondary)
+                        .on_press(Message::Create(CreateMsg::DismissCompanionOffer))
+                )
+                .align_items(iced::Alignment::Center)
+                .spacing(10)
+                .padding([5, 10])
+            )
+            .into(),
+        )
+    }
+
+    fn draft_offer_view(&self) -> Option<iced::Element<'_, Message>> {
+        // Kept until read-only mode is turned off, resuming it opens the create form
+        let draft = self.draft_offer.as_ref().filter(|_| !self.read_only)?;
+
+        Some(
+            column!(
+                horizontal_rule(2),
+                row!(
+                    text(Icon::FileEarmarkText).font(ICON_FONT),
+                    text(format!("Resume draft: {}", draft.describe())).width(Length::Fill),
+                    button("Resume").on_press(Message::Create(CreateMsg::ResumeDraft)),
+                    button("Discard")
+                        .style(theme::Button::Secondary)
+                        .on_press(Message::Create(CreateMsg::DiscardDraft))
+                )
+                .align_items(iced::Alignment::Center)
+                .spacing(10)
+                .padding([5, 10])
+            )
+            .into(),
+        )
+    }
+
+    /// Offers the companions of the entry the build job created a container of, once it is up.
+    fn offer_companions(&mut self) {
+        let Some(job) = self
+            .build_job
+            .as_ref()
+            .filter(|job| job.config.companion_of.is_none())
+        else {
+            return;
+        };
+
+        let companions = self
+            .images
+            .iter()
+            .find(|db| db.name == job.config.database)
+            .map(|db| db.companions.clone())
+            .unwrap_or_default();
+        if !companions.is_empty() && !self.read_only {
+            self.companion_offer = Some((job.config.clone(), companions));
+        }
+    }
+
+    /// Asks for confirmation with `prompt`, then removes the container `id` and creates `config`
+    /// in its place.
+    fn replace_container(
+        &mut self,
+        id: String,
+        prompt: String,
+        config: DbContainerConfig,
+    ) -> Command<Message> {
+        match native_dialog::MessageDialog::new()
+            .set_text(&prompt)
+            .set_type(native_dialog::MessageType::Warning)
+            .show_confirm()
+        {
+            Ok(true) => {}
+            Ok(false) => return Command::none(),
+            Err(ex) => return error(format!("Could not confirm: {ex}")),
+        }
+
+        self.recreate_with(id, config)
+    }
+
+    /// Removes the container `id` and creates `config` in its place.
+    fn recreate_with(&mut self, id: String, config: DbContainerConfig) -> Command<Message> {
+        self.expect_change(&self.container_name(&id));
+        self.expect_change(&config.name);
+        self.create_draft = AddContainerState::default();
+        Command::perform(
+            remove_container(id, self.docker),
+            move |result| match result {
+                Err(ex) => failed("Could not remove container", &ex),
+                Ok(_) => Message::Create(CreateMsg::CreateContainer(Box::new(config))),
+            },
+        )
+    }
+
+    fn database_for(&self, container: &DbContainer) -> Option<&DatabaseConfig> {
+        let image = ImageRef::parse(&container.image);
+        self.images.iter().find(|database| database.matches(&image))
+    }
+
+    /// The stop timeout of the config entry of container `id`, `None` for docker's default.
+    /// Who started `container` and when, if it is running.
+    fn started(&self, container: &DbContainer) -> Option<String> {
+        if container.state != ContainerStateStatusEnum::RUNNING {
+            return None;
+        }
+        Some(match self.starts.get(&container.id) {
+            Some(provenance) => provenance.describe(unix_now() as i64),
+            None => "Started before db-mgr was opened".to_string(),
+        })
+    }
+
+    /// Drops the retries of toasts that are no longer shown.
+    fn forget_retries(&mut self) {
+        self.retries
+            .retain(self.toasts.iter().filter_map(|toast| toast.retry));
+    }
+
+    fn stop_grace(&self, id: &str) -> Option<Duration> {
+        let container = self.containers.iter().find(|c| c.id == id)?;
+        let seconds = self.database_for(container)?.stop_timeout_secs?;
+        Some(Duration::from_secs(seconds))
+    }
+
+    fn thumbnail(&self, image: &str) -> Option<Handle> {
+        self.thumbnails.get(&ImageRef::parse(image).key()).cloned()
+    }
 }
 
 fn error(message: impl Into<String>) -> Command<Message> {
     let str = message.into();
-    Command::perform(future::ready(()), move |_| Message::Error(str))
+    Command::perform(future::ready(()), move |_| Message::Ui(UiMsg::Error(str)))
+}
+
+/// Reports a failed docker call, explaining the common causes.
+fn failed(context: &str, ex: &anyhow::Error) -> Message {
+    Message::Ui(UiMsg::Failed(Box::new(explain(context, ex))))
+}
+
+/// Reports a failed docker call that may only have hit a hiccup of the daemon, as a toast
+/// offering to send `retry`.
+fn failed_with_retry(context: &str, ex: &anyhow::Error, retry: Message) -> Message {
+    Message::Ui(UiMsg::FailedWithRetry(
+        Box::new(explain(context, ex)),
+        Box::new(retry),
+    ))
+}
+
+/// Backs `container` up into the directory of its schedule, recorded as the run due at `at`.
+/// `None` when its config entry has no backup command.
+fn backup_command(
+    docker: &'static Docker,
+    images: &[DatabaseConfig],
+    container: &DbContainer,
+    schedule: &BackupSchedule,
+    at: i64,
+) -> Option<Command<Message>> {
+    let args = images
+        .iter()
+        .find(|database| database.matches(&ImageRef::parse(&container.image)))
+        .and_then(|database| database.backup_args(container))?;
+    let name = container.name.trim_start_matches('/').to_string();
+    Some(Command::perform(
+        run_backup(
+            docker,
+            container.id.clone(),
+            args,
+            name.clone(),
+            schedule.directory.clone(),
+            schedule.keep,
+        ),
+        move |result| {
+            Message::Container(ContainerMsg::BackupDone(
+                name,
+                at,
+                result.map_err(|ex| format!("{ex}")),
+            ))
+        },
+    ))
 }
 
 fn run(message: Message) -> Command<Message> {
@@ -81,230 +1527,1008 @@ impl Application for DbMgrApp {
 
     type Theme = Theme;
 
-    type Flags = (Docker, ConfigFile);
+    type Flags = (
+        Docker,
+        ConfigFile,
+        Preferences,
+        bool,
+        Option<Paths>,
+        Option<TcpListener>,
+    );
 
     fn theme(&self) -> Self::Theme {
-        Theme::Dark
+        // The welcome flow previews the theme before it is saved
+        let theme = match self.welcome.as_ref() {
+            Some(welcome) => welcome.theme,
+            None => self.preferences.theme,
+        };
+        match theme {
+            AppTheme::Dark => Theme::Dark,
+            AppTheme::Light => Theme::Light,
+        }
     }
 
-    fn new((docker, config_file): Self::Flags) -> (Self, iced::Command<Self::Message>) {
+    fn new(
+        (docker, config_file, preferences, read_only, paths, focus_listener): Self::Flags,
+    ) -> (Self, iced::Command<Self::Message>) {
+        let http = build_client(&config_file.proxy).unwrap_or_else(|ex| {
+            eprintln!("Could not configure http client {ex:#}");
+            reqwest::Client::new()
+        });
+
+        // Desktops without a tray start without one, it is only missed in the logs
+        let tray = if TRAY_SUPPORTED && preferences.tray {
+            match Tray::spawn() {
+                Ok(tray) => Some(&*Box::leak(Box::new(tray))),
+                Err(ex) => {
+                    eprintln!("No tray icon: {ex}");
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        // Without a preferences file db-mgr hasn't been used before, or can't save anything
+        let draft_offer = read_draft(paths.as_ref());
+        let welcome = paths
+            .as_ref()
+            .filter(|paths| !paths.preferences_file().exists())
+            .map(|_| WelcomeFlow::new(&config_file.databases, preferences.theme));
+        let prefetch_selection =
+            default_prefetch(&config_file.databases, preferences.never_default_latest);
+
         let this = Self {
             containers: vec![],
+            migrations: HashMap::new(),
+            end_of_life: HashMap::new(),
+            raw_inspects: HashMap::new(),
+            query_consoles: HashMap::new(),
+            limit_forms: HashMap::new(),
+            detail_tabs: HashMap::new(),
+            auto_stop: AutoStopTimers::default(),
+            stops: StopAttempts::default(),
+            starts: StartProvenance::default(),
+            refresh: RefreshQueue::default(),
+            last_refresh: None,
+            connected: None,
+            endpoint: docker_endpoint(preferences.docker_host.as_deref(), &preferences.docker_tls),
+            highlights: HashMap::new(),
+            toasts: vec![],
+            retries: Retries::default(),
+            expected_changes: ExpectedChanges::default(),
+            project_filter: None,
+            state_file_written: None,
+            state_file_pending: false,
+            state_file_warned: false,
+            volumes: vec![],
+            dismissed_orphans: None,
             docker: Box::leak(Box::new(docker)),
-            thumbnails: HashMap::with_capacity(config_file.databases.len()),
+            focus_listener: focus_listener.map(|listener| &*Box::leak(Box::new(listener))),
+            thumbnails: Thumbnails::with_capacity(config_file.databases.len()),
+            icon_errors: BTreeMap::new(),
+            expanded_env: HashMap::new(),
             images: config_file.databases,
-            main_view: MainViewState::None,
-            default_thumbnail: Handle::from_memory(include_bytes!("../../default_image.png")),
-            build_subscription: None,
+            main_view: match welcome {
+                Some(_) => MainViewState::Welcome,
+                None => MainViewState::None,
+            },
+            build_job: None,
+            companion_offer: None,
+            version_block: None,
+            disk_space: None,
+            low_disk_space: config_file.low_disk_space_mb * 1024 * 1024,
+            port_range: config_file.ports,
+            disk_override: false,
+            create_draft: AddContainerState::default(),
+            draft_offer,
+            draft_written: None,
+            draft_pending: false,
+            quick_draft: QuickStartState::default(),
+            quick_credentials: None,
+            validation: None,
+            validating: false,
+            preferences,
+            http,
+            connection_test: None,
+            tls_test: None,
+            cleanup_summary: None,
+            cleanup_results: vec![],
+            cleanup_subscription: None,
+            prefetch_selection,
+            prefetch: None,
+            start_run: None,
+            engine_info: None,
+            terminal: None,
+            selection: None,
+            idle_prompt: None,
+            pending_drop: None,
+            sql_run: None,
+            sql_runs: 0,
+            log_follow: None,
+            log_follows: 0,
+            exiting: None,
+            tray,
+            tray_entries: vec![],
+            welcome,
+            read_only,
+            persistence_problem: persistence_problem(paths.as_ref()),
+            sidebar_offset: scrollable::AbsoluteOffset::default(),
+            backups_running: HashSet::new(),
+            backup_failure: None,
+            events: EventLog::default(),
+            checking_updates: false,
+            list_limit: PAGE_SIZE,
+            paths,
+        };
+
+        let check_disk_space = this.check_disk_space(None);
+        let load_engine_info = match this.welcome {
+            Some(_) => this.load_engine_info(),
+            None => Command::none(),
         };
 
         (
             this,
             Command::batch([
-                font::load(ICON_FONT_BYTES).map(Message::FontLoaded),
-                Command::perform(future::ready(()), |_| Message::GetContainers),
-                Command::perform(future::ready(()), |_| Message::GetThumbnails),
+                font::load(ICON_FONT_BYTES).map(|result| Message::Ui(UiMsg::FontLoaded(result))),
+                Command::perform(future::ready(()), |_| {
+                    Message::Container(ContainerMsg::GetContainers)
+                }),
+                Command::perform(future::ready(()), |_| {
+                    Message::Thumbnail(ThumbnailMsg::GetThumbnails)
+                }),
+                check_disk_space,
+                load_engine_info,
             ]),
         )
     }
 
     fn subscription(&self) -> Subscription<Self::Message> {
-        match self.build_subscription.as_ref() {
-            Some(container_config) => create_container(self.docker, container_config.to_owned())
-                .map(|event| match event {
-                    crate::docker::CreateContainerEvent::Pulling => Message::PullingContainer,
-                    crate::docker::CreateContainerEvent::Building => Message::BuildingContainer,
-                    crate::docker::CreateContainerEvent::Done => Message::CreatedContainer,
-                    crate::docker::CreateContainerEvent::Error(ex) => Message::BuildError(ex),
-                    crate::docker::CreateContainerEvent::Download(key, value) => {
-                        Message::ImageDownload(key, value)
+        let build = match self.build_job.as_ref().filter(|job| job.is_running()) {
+            Some(job) => {
+                create_container(self.docker, job.config.to_owned()).map(|event| match event {
+                    CreateContainerEvent::Pulling => Message::Create(CreateMsg::PullingContainer),
+                    CreateContainerEvent::Building => Message::Create(CreateMsg::BuildingContainer),
+                    CreateContainerEvent::Starting => Message::Create(CreateMsg::StartingContainer),
+                    CreateContainerEvent::Done(id) => {
+                        Message::Create(CreateMsg::CreatedContainer(id))
                     }
-                }),
+                    CreateContainerEvent::Error(ex) => Message::Create(CreateMsg::BuildError(ex)),
+                    CreateContainerEvent::Conflict { id, can_replace } => {
+                        Message::Create(CreateMsg::NameConflict(id, can_replace))
+                    }
+                    CreateContainerEvent::Download {
+                        layer,
+                        current,
+                        total,
+                    } => Message::Create(CreateMsg::ImageDownload(layer, current, total)),
+                    CreateContainerEvent::BuildOutput(line) => {
+                        Message::Create(CreateMsg::BuildOutput(line))
+                    }
+                    CreateContainerEvent::Plan(plan) => Message::Create(CreateMsg::Planned(plan)),
+                })
+            }
             None => Subscription::none(),
-        }
-    }
+        };
 
-    fn title(&self) -> String {
-        "DB Manage".into()
-    }
+        let cleanup = match self.cleanup_subscription.as_ref() {
+            Some(summary) => cleanup(self.docker, summary.to_owned()).map(|event| match event {
+                CleanupEvent::Removed(message) => Message::Container(
+                    ContainerMsg::CleanupProgress(CleanupResult::Removed(message)),
+                ),
+                CleanupEvent::Failed(message) => Message::Container(ContainerMsg::CleanupProgress(
+                    CleanupResult::Failed(message),
+                )),
+                CleanupEvent::Done => Message::Container(ContainerMsg::CleanupDone),
+            }),
+            None => Subscription::none(),
+        };
 
-    fn update(&mut self, message: Self::Message) -> iced::Command<Self::Message> {
-        match message {
-            Message::GetContainers => {
-                Command::perform(get_containers(self.docker), |result| match result {
-                    Err(ex) => Message::Error(format!("Could not get containers: {ex}")),
-                    Ok(containers) => Message::ContainersLoaded(containers),
+        let prefetch = match self.prefetch.as_ref().filter(|run| run.running) {
+            Some(run) => prefetch(self.docker, run.names())
+                .map(|event| Message::Container(ContainerMsg::PrefetchProgress(event))),
+            None => Subscription::none(),
+        };
+
+        let start_run = match self.start_run.as_ref().filter(|run| run.running) {
+            Some(run) => ordered_start(self.docker, graph(&self.containers), run.names(), run.run)
+                .map(|event| Message::Container(ContainerMsg::StartProgress(event))),
+            None => Subscription::none(),
+        };
+
+        let query_history = match self.visible_tab() {
+            Some((container, DetailTab::Overview))
+                if self
+                    .database_for(container)
+                    .is_some_and(|database| !database.query_command.is_empty()) =>
+            {
+                iced::subscription::events_with(|event, _| match event {
+                    iced::Event::Keyboard(keyboard::Event::KeyPressed {
+                        key_code: KeyCode::Up,
+                        ..
+                    }) => Some(Message::Container(ContainerMsg::QueryHistory(true))),
+                    iced::Event::Keyboard(keyboard::Event::KeyPressed {
+                        key_code: KeyCode::Down,
+                        ..
+                    }) => Some(Message::Container(ContainerMsg::QueryHistory(false))),
+                    _ => None,
                 })
             }
-            Message::LoadedThumbnails(images) => {
-                self.thumbnails = images;
-                Command::none()
-            }
-            Message::ContainersLoaded(containers) => {
-                self.containers = containers;
-                self.main_view = MainViewState::None;
-                Command::none()
-            }
-            Message::GetThumbnails => Command::perform(
-                stream::iter(
-                    self.images
-                        .clone()
-                        .into_iter()
-                        .unique_by(|item| item.name.clone()),
+            _ => Subscription::none(),
+        };
+
+        let terminal = match (self.terminal.as_ref(), self.visible_tab()) {
+            (Some(terminal), Some((container, DetailTab::Exec)))
+                if terminal.is_open() && terminal.container == container.id =>
+            {
+                exec_session(
+                    self.docker,
+                    terminal.container.clone(),
+                    terminal.args().unwrap_or_default(),
+                    terminal.session,
                 )
-                .filter_map(|item| async move {
-                    Some((
-                        item.image.clone(),
-                        Handle::from_memory(
-                            reqwest::get(item.icon_url).await.ok()?.bytes().await.ok()?,
-                        ),
-                    ))
-                })
-                .collect(),
-                Message::LoadedThumbnails,
-            ),
-            Message::Error(ex) => {
-                if let Err(dialog_err) = native_dialog::MessageDialog::new()
-                    .set_text(&ex)
-                    .set_type(native_dialog::MessageType::Error)
-                    .show_alert()
-                {
-                    eprintln!("Application Error: {ex}");
-                    eprintln!("Dialog Error: {dialog_err}");
-                }
-                Command::none()
+                .map(|event| Message::Container(ContainerMsg::TerminalEvent(event)))
             }
-            Message::StartContainer(id) => {
-                Command::perform(start_container(id, self.docker), |result| match result {
-                    Err(ex) => Message::Error(format!("Could not start docker container: {ex}")),
-                    Ok(_) => Message::GetContainers,
-                })
+            _ => Subscription::none(),
+        };
+
+        let logs = match (self.log_follow.as_ref(), self.visible_tab()) {
+            (Some(follow), Some((container, DetailTab::Logs)))
+                if follow.container == container.id =>
+            {
+                follow_logs(self.docker, follow.container.clone(), follow.session)
+                    .map(|event| Message::Container(ContainerMsg::LogEvent(event)))
             }
-            Message::StopContainer(id) => {
-                Command::perform(stop_container(id, self.docker), |result| match result {
-                    Err(ex) => Message::Error(format!("Could not stop docker container: {ex}")),
-                    Ok(_) => Message::GetContainers,
-                })
+            _ => Subscription::none(),
+        };
+
+        let pull_tick = match self.build_job.as_ref().map(|job| &job.phase) {
+            Some(BuildPhase::Pulling(_)) => iced::time::every(Duration::from_secs(1))
+                .map(|now| Message::Create(CreateMsg::PullTick(now))),
+            _ if self.prefetch.as_ref().is_some_and(|run| run.running) => {
+                iced::time::every(Duration::from_secs(1))
+                    .map(|now| Message::Create(CreateMsg::PullTick(now)))
             }
-            Message::ViewContainer(container_name) => {
-                self.main_view = self
-                    .containers
-                    .iter()
-                    .enumerate()
-                    .find_map(|(i, container)| {
-                        if container.id == container_name {
-                            Some(MainViewState::ViewContainer(i))
-                        } else {
-                            None
-                        }
-                    })
-                    .unwrap_or(MainViewState::None);
+            _ => Subscription::none(),
+        };
 
-                println!("{:?}", self.main_view);
+        let sql_run = match self.sql_run.as_ref() {
+            Some(SqlRun {
+                container,
+                file,
+                cmd: Some(cmd),
+                run,
+                ..
+            }) => exec_with_input(
+                self.docker,
+                container.clone(),
+                cmd.clone(),
+                file.clone(),
+                *run,
+            )
+            .map(|event| Message::Container(ContainerMsg::SqlRunEvent(event))),
+            _ => Subscription::none(),
+        };
 
-                Command::none()
+        let window_events = iced::subscription::events_with(|event, _| match event {
+            iced::Event::Window(window::Event::FileDropped(path)) => {
+                Some(Message::Ui(UiMsg::FileDropped(path)))
             }
-            Message::ShowCreateContainer => {
-                self.main_view = MainViewState::CreateContainer(ButtonState::Ready);
-                Command::none()
+            iced::Event::Window(window::Event::CloseRequested) => {
+                Some(Message::Ui(UiMsg::CloseRequested))
             }
-            Message::FontLoaded(_) => Command::none(),
-            Message::CreateContainer(container_config) => {
-                self.main_view = MainViewState::CreateContainer(ButtonState::Creating);
-                // let rx = create_container(self.docker, container_config);
+            _ => None,
+        });
 
-                self.build_subscription = Some(container_config);
+        let exit_cap = match self.exiting {
+            Some(_) => iced::time::every(EXIT_CAP).map(|_| Message::Ui(UiMsg::ForceExit)),
+            None => Subscription::none(),
+        };
 
-                Command::none()
-            }
-            Message::BuildError(ex) => {
-                self.main_view = MainViewState::CreateContainer(ButtonState::Ready);
-                self.build_subscription = None;
-                error(ex)
-            }
-            Message::ImageDownload(key, value) => {
-                // self.main_view = MainViewState::CreateContainer(ButtonState::Pulling);
-                match self.main_view {
-                    MainViewState::CreateContainer(ButtonState::Pulling(ref mut status)) => {
-                        status.insert(key, value);
-                    }
-                    MainViewState::ViewContainer(_)
-                    | MainViewState::None
-                    | MainViewState::CreateContainer(_) => {
-                        self.main_view =
-                            MainViewState::CreateContainer(ButtonState::Pulling(HashMap::new()))
-                    }
-                }
+        let idle_check = if self.read_only || self.preferences.idle_after.0 == 0 {
+            Subscription::none()
+        } else {
+            iced::time::every(Duration::from_secs(30 * 60))
+                .map(|now| Message::Container(ContainerMsg::IdleCheck(now)))
+        };
 
-                Command::none()
-            }
-            Message::BuildingContainer => {
-                self.main_view = MainViewState::CreateContainer(ButtonState::Creating);
-                Command::none()
-            }
-            Message::CreatedContainer => {
-                self.build_subscription = None;
-                Command::perform(future::ready(()), |_| Message::GetContainers)
-            }
-            Message::PullingContainer => {
-                self.main_view =
-                    MainViewState::CreateContainer(ButtonState::Pulling(HashMap::new()));
-                Command::none()
-            }
+        let backup_tick = if self.preferences.backups.is_empty() {
+            Subscription::none()
+        } else {
+            iced::time::every(Duration::from_secs(60))
+                .map(|now| Message::Container(ContainerMsg::BackupTick(now)))
+        };
+
+        let update_tick = if self.preferences.check_updates {
+            iced::time::every(Duration::from_secs(60 * 60))
+                .map(|now| Message::Container(ContainerMsg::UpdateTick(now)))
+        } else {
+            Subscription::none()
+        };
+
+        let tick = if self.auto_stop.is_empty() {
+            Subscription::none()
+        } else {
+            iced::time::every(Duration::from_secs(15)).map(|now| Message::Ui(UiMsg::Tick(now)))
+        };
+
+        // Ticks faster while highlights and toasts wait to expire and stopping containers count
+        let clock_tick = if self.highlights.is_empty()
+            && self.toasts.is_empty()
+            && self.stops.is_empty()
+            && !self.start_run.as_ref().is_some_and(|run| run.running)
+        {
+            iced::time::every(Duration::from_secs(30))
+        } else {
+            iced::time::every(Duration::from_secs(1))
+        }
+        .map(|now| Message::Ui(UiMsg::ClockTick(now)));
+
+        let thumbnail_flush = if self.thumbnails.has_pending() {
+            iced::time::every(FLUSH_INTERVAL)
+                .map(|_| Message::Thumbnail(ThumbnailMsg::FlushThumbnails))
+        } else {
+            Subscription::none()
+        };
+
+        let draft_tick = if self.draft_pending {
+            iced::time::every(DRAFT_INTERVAL).map(|now| Message::Create(CreateMsg::SaveDraft(now)))
+        } else {
+            Subscription::none()
+        };
+
+        let state_file_tick = if self.state_file_pending {
+            iced::time::every(STATE_FILE_INTERVAL)
+                .map(|now| Message::Ui(UiMsg::WriteStateFile(now)))
+        } else {
+            Subscription::none()
+        };
+
+        let tray = match self.tray {
+            Some(tray) => tray_actions(tray).map(|action| match action {
+                TrayAction::Start(id) => Message::Container(ContainerMsg::StartContainer(id)),
+                TrayAction::Stop(id) => Message::Container(ContainerMsg::StopContainer(id)),
+                TrayAction::ShowWindow => Message::Ui(UiMsg::FocusWindow),
+                TrayAction::Quit => Message::Ui(UiMsg::QuitFromTray),
+            }),
+            None => Subscription::none(),
+        };
+
+        let focus = match self.focus_listener {
+            Some(listener) => focus_requests(listener).map(|_| Message::Ui(UiMsg::FocusWindow)),
+            None => Subscription::none(),
+        };
+
+        Subscription::batch([
+            docker_events(self.docker)
+                .map(|event| Message::Container(ContainerMsg::ContainerEvent(event))),
+            focus,
+            tray,
+            build,
+            cleanup,
+            prefetch,
+            start_run,
+            query_history,
+            terminal,
+            logs,
+            pull_tick,
+            sql_run,
+            window_events,
+            exit_cap,
+            idle_check,
+            backup_tick,
+            update_tick,
+            clock_tick,
+            state_file_tick,
+            draft_tick,
+            thumbnail_flush,
+            tick,
+        ])
+    }
+
+    fn title(&self) -> String {
+        match running_count(&self.containers) {
+            0 => "DB Manage".into(),
+            running => format!("DB Manage — {running} running"),
+        }
+    }
+
+    fn update(&mut self, message: Self::Message) -> iced::Command<Self::Message> {
+        if self.read_only && message.is_mutating() {
+            return error("db-mgr is in read-only mode, containers can't be changed");
+        }
+        self.close_hidden_terminal();
+
+        match message {
+            Message::Container(msg) => self.handle_container_msg(msg),
+            Message::Create(msg) => self.handle_create_msg(msg),
+            Message::Thumbnail(msg) => self.handle_thumbnail_msg(msg),
+            Message::Ui(msg) => self.handle_ui_msg(msg),
         }
     }
 
     fn view(&self) -> iced::Element<'_, Self::Message, iced::Renderer<Self::Theme>> {
-        let containers = scrollable(
-            column(
+        if let Some(count) = self.exiting {
+            return container(
+                column!(
+                    text(format!("Stopping {count} containers…")).size(20),
+                    text(format!(
+                        "db-mgr closes once they are stopped, or after {} seconds",
+                        EXIT_CAP.as_secs()
+                    ))
+                    .size(12),
+                    button("Close now")
+                        .style(theme::Button::Secondary)
+                        .on_press(Message::Ui(UiMsg::ForceExit))
+                )
+                .align_items(iced::Alignment::Center)
+                .spacing(10),
+            )
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .center_x()
+            .center_y()
+            .into();
+        }
+
+        let orphans = self.orphans();
+        let banner = if self.show_orphan_banner(&orphans) {
+            let size = orphans.iter().filter_map(|volume| volume.size).sum::<i64>();
+            column!(
+                row!(
+                    text(format!(
+                        "{} orphaned volumes using ~{}",
+                        orphans.len(),
+                        format_size(size)
+                    ))
+                    .width(Length::Fill),
+                    button("Review").on_press(Message::Ui(UiMsg::ShowVolumes(true))),
+                    button("Dismiss")
+                        .style(theme::Button::Secondary)
+                        .on_press(Message::Ui(UiMsg::DismissOrphans))
+                )
+                .align_items(iced::Alignment::Center)
+                .spacing(5)
+                .padding(5),
+                horizontal_rule(2)
+            )
+        } else {
+            column!()
+        };
+
+        let grid = self.preferences.view_mode == ViewMode::Grid;
+        let actions = CardActions {
+            on_view: |id| Message::Ui(UiMsg::ViewContainer(id)),
+            on_select: |id, selected| Message::Ui(UiMsg::SelectContainer(id, selected)),
+            on_start: (!self.read_only).then_some(
+                (|id| Message::Container(ContainerMsg::StartContainer(id))) as fn(_) -> _,
+            ),
+            on_stop: (!self.read_only).then_some(
+                (|id| Message::Container(ContainerMsg::StopContainer(id))) as fn(_) -> _,
+            ),
+            on_force_stop: (!self.read_only).then_some(
+                (|id| Message::Container(ContainerMsg::ForceStopContainer(id))) as fn(_) -> _,
+            ),
+            on_update: (!self.read_only).then_some(
+                (|id| Message::Container(ContainerMsg::UpdateContainer(id))) as fn(_) -> _,
+            ),
+            on_back_up: Some(|id| Message::Ui(UiMsg::OpenBackups(id))),
+        };
+        let cards = container_cards(
+            with_companions(
                 self.containers
                     .iter()
-                    .map(|item| {
-                        container_card(
-                            item,
-                            self.thumbnails
-                                .get(item.image.split(':').next().unwrap_or(item.image.as_str()))
-                                .cloned()
-                                .unwrap_or_else(|| self.default_thumbnail.clone()),
-                        )
-                        .on_start_click(Message::StartContainer)
-                        .on_stop_click(Message::StopContainer)
-                        .on_view_click(Message::ViewContainer)
-                        .into()
-                    })
-                    .collect(),
-            )
-            .push(
-                container(button("Add container").on_press(Message::ShowCreateContainer))
-                    .padding([5, 0]),
+                    .filter(|container| self.in_project_filter(container)),
+            ),
+            self.list_limit,
+            |image| self.thumbnail(image),
+            |item| CardLook {
+                tile: grid,
+                legacy: self
+                    .migrations
+                    .get(&item.id)
+                    .map(MigrationPlan::explanation),
+                selectable: self.selection.is_some(),
+                selected: self
+                    .selection
+                    .as_ref()
+                    .is_some_and(|selection| selection.contains(&item.id)),
+                idle_days: self.idle_days(item),
+                update_available: self.update_available(item),
+                highlighted: self.highlights.contains_key(&item.id),
+                indented: item
+                    .companion_of()
+                    .is_some_and(|parent| self.container_id(parent).is_some()),
+                stop_state: self.stops.state(&item.id, Instant::now()),
+                started: self.started(item),
+                eol: self.eol_warning(item),
+                exit: exit_notes(item, self.events.newest_first(&item.id)),
+            },
+            actions,
+        )
+        .into_iter()
+        .chain(self.build_job.iter().map(|job| {
+            build_card(job)
+                .on_retry_click(|| Message::Create(CreateMsg::RetryBuild))
+                .on_dismiss_click(|| Message::Create(CreateMsg::DismissBuild))
+                .on_replace_click(|| Message::Create(CreateMsg::ReplaceConflict))
+                .into()
+        }))
+        .collect();
+        let cards: iced::Element<'_, Message> = if grid {
+            Wrap::with_elements(cards)
+                .spacing(10.0)
+                .line_spacing(10.0)
+                .padding(5.0)
+                .into()
+        } else {
+            column(cards).into()
+        };
+        let listed = self
+            .containers
+            .iter()
+            .filter(|container| self.in_project_filter(container))
+            .count();
+        let (_, more) = page(listed, self.list_limit);
+        let show_more = if more > 0 {
+            row!(button(text(format!("Show {more} more")))
+                .style(theme::Button::Secondary)
+                .on_press(Message::Ui(UiMsg::ShowMoreContainers)))
+            .padding(5)
+        } else {
+            row!()
+        };
+
+        let (toggle_label, next_mode) = match self.preferences.view_mode {
+            ViewMode::List => ("Grid view", ViewMode::Grid),
+            ViewMode::Grid => ("List view", ViewMode::List),
+        };
+        let detail_open = !matches!(self.main_view, MainViewState::None);
+        let sidebar_width = if grid && !detail_open {
+            Length::Fill
+        } else {
+            Length::FillPortion(1)
+        };
+
+        let containers = scrollable(
+            column!(
+                self.project_chips().unwrap_or_else(|| row!().into()),
+                cards,
+                show_more,
+                container(
+                    row!(
+                        if self.read_only {
+                            row!(badge(text("Read-only")).style(BadgeStyles::Warning))
+                        } else {
+                            row!(
+                                button("Add container")
+                                    .on_press(Message::Create(CreateMsg::ShowCreateContainer)),
+                                button("Import definition…")
+                                    .style(theme::Button::Secondary)
+                                    .on_press(Message::Create(CreateMsg::ImportDefinition)),
+                                button(text(Icon::LightningFill).font(ICON_FONT))
+                                    .on_press(Message::Create(CreateMsg::ShowQuickStart)),
+                                button("Select")
+                                    .style(theme::Button::Secondary)
+                                    .on_press(Message::Ui(UiMsg::ToggleSelection))
+                            )
+                            .spacing(5)
+                        }
+                        .align_items(iced::Alignment::Center),
+                        button("Settings").on_press(Message::Ui(UiMsg::ShowSettings)),
+                        button(toggle_label)
+                            .style(theme::Button::Secondary)
+                            .on_press(Message::Ui(UiMsg::SetViewMode(next_mode)))
+                    )
+                    .spacing(5),
+                )
+                .padding([5, 0])
             )
             .align_items(iced::Alignment::Center)
             .width(Length::Fill),
         )
-        .width(Length::FillPortion(1));
-
-        let main_windown = match self.main_view {
-            MainViewState::CreateContainer(ref state) => container(add_container(
-                self.images.clone(),
-                state.clone(),
-                Message::CreateContainer,
-            )),
+        .id(sidebar_id())
+        .on_scroll(|viewport| Message::Ui(UiMsg::SidebarScrolled(viewport.absolute_offset())))
+        .width(sidebar_width);
+        let banners = self
+            .persistence_banner()
+            .into_iter()
+            .chain(self.disk_banner())
+            .chain(self.sql_banner())
+            .chain(self.version_banner())
+            .chain(self.start_banner())
+            .chain(self.backup_banner())
+            .chain(self.idle_banner());
+        let containers = column(banners.chain(self.bulk_bar()).collect())
+            .push(banner)
+            .push(containers)
+            .width(sidebar_width);
+
+        if !detail_open && grid {
+            return containers.into();
+        }
+
+        let main_windown = match &self.main_view {
+            MainViewState::CreateContainer => container(
+                add_container(
+                    self.images.clone(),
+                    self.images
+                        .iter()
+                        .filter_map(|image| {
+                            let recalled = self.preferences.recall(image)?;
+                            Some((image.name.clone(), recalled.clone()))
+                        })
+                        .collect(),
+                    self.create_draft.clone(),
+                    match self.build_job.as_ref().map(|job| &job.phase) {
+                        None
+                        | Some(
+                            BuildPhase::Failed(_)
+                            | BuildPhase::Conflict(_)
+                            | BuildPhase::Planned(_),
+                        ) => ButtonState::Ready,
+                        Some(BuildPhase::Pulling(status)) => ButtonState::Pulling(status.clone()),
+                        Some(BuildPhase::BuildingImage(output)) => {
+                            ButtonState::BuildingImage(output.clone())
+                        }
+                        Some(_) => ButtonState::Creating,
+                    },
+                    |container_config| {
+                        Message::Create(CreateMsg::CreateContainer(Box::new(container_config)))
+                    },
+                )
+                .never_default_latest(self.preferences.never_default_latest)
+                .skip_review(self.preferences.skip_create_review)
+                .taken_names(self.container_names())
+                .dependency_options(
+                    self.containers
+                        .iter()
+                        .map(|container| container.name.trim_start_matches('/').to_string())
+                        .sorted()
+                        .collect(),
+                )
+                .project_options(self.projects())
+                .gpu_support(
+                    self.engine_info
+                        .as_ref()
+                        .map(|engine| engine.nvidia_runtime),
+                    self.engine_info
+                        .as_ref()
+                        .map(|engine| engine.gpus.clone())
+                        .unwrap_or_default(),
+                )
+                .on_import_env(|| Message::Create(CreateMsg::ImportEnvFile))
+                .on_discard(|| Message::Create(CreateMsg::DiscardForm))
+                .on_change(|draft| Message::Create(CreateMsg::DraftChanged(Box::new(draft)))),
+            ),
+            MainViewState::QuickStart => container(
+                quick_start(
+                    self.images.clone(),
+                    self.quick_draft.clone(),
+                    self.build_job.as_ref().is_some_and(BuildJob::is_running),
+                )
+                .on_change(|draft| Message::Create(CreateMsg::QuickStartChanged(Box::new(draft))))
+                .on_submit(|| Message::Create(CreateMsg::QuickCreate)),
+            ),
+            MainViewState::StartFailed => match self.build_job.as_ref() {
+                Some(BuildJob {
+                    config,
+                    phase: BuildPhase::Exited(failure),
+                    ..
+                }) => container(
+                    start_failure_view(config.name.clone(), failure.clone())
+                        .on_keep_click(|| Message::Create(CreateMsg::KeepFailedContainer))
+                        .on_delete_click(|| Message::Create(CreateMsg::DeleteFailedContainer)),
+                ),
+                _ => container(row!()),
+            },
+            MainViewState::Credentials => match self.quick_credentials.clone() {
+                Some(credentials) => container(
+                    credentials_view(credentials)
+                        .on_copy_click(|text| Message::Ui(UiMsg::CopyToClipboard(text)))
+                        .on_done_click(|| Message::Ui(UiMsg::DismissCredentials)),
+                ),
+                None => container(row!()),
+            },
+            MainViewState::Settings => container({
+                let settings = settings(
+                    self.validation.clone(),
+                    self.validating,
+                    self.preferences.clone(),
+                    self.connection_test.clone(),
+                )
+                .on_validate_click(|| Message::Ui(UiMsg::ValidateConfig))
+                .on_test_connection_click(|| Message::Ui(UiMsg::TestConnection))
+                .on_remember_values_toggle(|remember| Message::Ui(UiMsg::RememberValues(remember)))
+                .on_remember_passwords_toggle(|remember| {
+                    Message::Ui(UiMsg::RememberPasswords(remember))
+                })
+                .on_never_default_latest_toggle(|never| {
+                    Message::Ui(UiMsg::NeverDefaultLatest(never))
+                })
+                .on_skip_create_review_toggle(|skip| Message::Ui(UiMsg::SkipCreateReview(skip)))
+                .on_stop_on_exit_toggle(|stop| Message::Ui(UiMsg::StopOnExit(stop)))
+                .on_single_instance_toggle(|single_instance| {
+                    Message::Ui(UiMsg::SingleInstance(single_instance))
+                })
+                .on_developer_mode_toggle(|developer_mode| {
+                    Message::Ui(UiMsg::DeveloperMode(developer_mode))
+                })
+                .on_docker_host_change(|host| Message::Ui(UiMsg::DockerHost(host)))
+                .on_tls_toggle(|enabled| Message::Ui(UiMsg::TlsEnabled(enabled)))
+                .on_tls_host_change(|host| Message::Ui(UiMsg::TlsHost(host)))
+                .on_choose_tls_file(|file| Message::Ui(UiMsg::ChooseTlsFile(file)))
+                .on_test_tls_click(|| Message::Ui(UiMsg::TestTls))
+                .on_reconnect_click(|| Message::Ui(UiMsg::Reconnect))
+                .tls_test(self.tls_test.clone())
+                .on_state_file_toggle(|enabled| Message::Ui(UiMsg::StateFile(enabled)))
+                .on_choose_state_file(|| Message::Ui(UiMsg::ChooseStateFile))
+                .on_read_only_toggle(|read_only| Message::Ui(UiMsg::ReadOnly(read_only)))
+                .on_idle_after_change(|idle_after| Message::Ui(UiMsg::IdleAfter(idle_after)))
+                .on_theme_change(|theme| Message::Ui(UiMsg::SetTheme(theme)))
+                .checking_updates(self.checking_updates)
+                .on_check_updates_click(|| Message::Container(ContainerMsg::CheckUpdates))
+                .on_check_updates_daily_toggle(|check_updates| {
+                    Message::Ui(UiMsg::CheckUpdatesDaily(check_updates))
+                })
+                .on_clear_remembered_click(|name| Message::Ui(UiMsg::ClearRemembered(name)))
+                .on_volumes_click(|| Message::Ui(UiMsg::ShowVolumes(false)))
+                .on_about_click(|| Message::Ui(UiMsg::ShowAbout))
+                .paths(
+                    self.paths
+                        .as_ref()
+                        .map(Paths::app_paths)
+                        .unwrap_or_default(),
+                )
+                .on_copy_path_click(|text| Message::Ui(UiMsg::CopyToClipboard(text)))
+                .on_open_path_click(|path| Message::Ui(UiMsg::OpenPath(path)))
+                .on_open_config_click(|| Message::Ui(UiMsg::OpenConfig))
+                .deleted(
+                    self.preferences
+                        .deleted(&self.container_names())
+                        .into_iter()
+                        .cloned()
+                        .collect(),
+                )
+                .on_forget_click(|name| Message::Create(CreateMsg::ForgetDeleted(name)));
+                let settings = if TRAY_SUPPORTED {
+                    settings.on_tray_toggle(|enabled| Message::Ui(UiMsg::TrayEnabled(enabled)))
+                } else {
+                    settings
+                };
+                if self.read_only {
+                    settings
+                } else {
+                    settings
+                        .on_cleanup_click(|| Message::Ui(UiMsg::ShowCleanup))
+                        .on_prefetch_click(|| Message::Ui(UiMsg::ShowPrefetch))
+                        .on_recreate_click(|name| Message::Create(CreateMsg::RecreateDeleted(name)))
+                }
+            }),
+            MainViewState::About => container(
+                about(self.engine_info.clone(), self.images.clone())
+                    .icon_errors(
+                        self.icon_errors
+                            .iter()
+                            .map(|(image, error)| (image.clone(), error.clone()))
+                            .collect(),
+                    )
+                    .on_copy_click(|text| Message::Ui(UiMsg::CopyToClipboard(text))),
+            ),
+            MainViewState::Cleanup => container(
+                cleanup_view(
+                    self.cleanup_summary.clone(),
+                    self.cleanup_results.clone(),
+                    self.cleanup_subscription.is_some(),
+                )
+                .on_run(|summary| Message::Container(ContainerMsg::RunCleanup(summary))),
+            ),
+            MainViewState::Prefetch => container({
+                let prefetch = prefetch_view(
+                    prefetch_choices(&self.images),
+                    self.prefetch_selection.clone(),
+                    self.prefetch.clone(),
+                )
+                .on_change(|selection| Message::Ui(UiMsg::PrefetchSelectionChanged(selection)));
+                if self.read_only {
+                    prefetch
+                } else {
+                    prefetch.on_run(|images| Message::Container(ContainerMsg::RunPrefetch(images)))
+                }
+            }),
+            MainViewState::Volumes(orphans_only) => container({
+                let volumes = volumes_view(self.volumes.clone(), self.orphans(), *orphans_only)
+                    .on_filter_toggle(|orphans_only| Message::Ui(UiMsg::ShowVolumes(orphans_only)));
+                if self.read_only {
+                    volumes
+                } else {
+                    volumes.on_remove_click(|name| {
+                        Message::Container(ContainerMsg::RemoveVolume(name))
+                    })
+                }
+            }),
+            MainViewState::Welcome => match self.welcome.clone() {
+                Some(flow) => container(
+                    welcome(flow, self.engine_info.clone())
+                        .on_change(|flow| Message::Ui(UiMsg::WelcomeChanged(Box::new(flow))))
+                        .on_finish(|flow| Message::Ui(UiMsg::FinishWelcome(Box::new(flow))))
+                        .on_skip(|| Message::Ui(UiMsg::SkipWelcome))
+                        .on_retry_click(|| Message::Ui(UiMsg::RetryDocker)),
+                ),
+                None => container(row!()),
+            },
             MainViewState::None => container(row!()),
-            MainViewState::ViewContainer(index) => {
-                let ctr = &self.containers[index];
-                container(container_view(
-                    ctr.to_owned(),
-                    self.thumbnails
-                        .get(&ctr.name)
-                        .unwrap_or(&self.default_thumbnail)
-                        .to_owned(),
-                ))
+            MainViewState::ViewContainer(id) => {
+                let Some(ctr) = self.containers.iter().find(|c| &c.id == id) else {
+                    return containers.into();
+                };
+                let view = container_view(ctr.to_owned(), self.thumbnail(&ctr.image))
+                    .template(self.database_for(ctr).cloned())
+                    .expanded_env(self.expanded_env.clone())
+                    .on_view_env(|id, key| Message::Container(ContainerMsg::ViewEnvValue(id, key)))
+                    .migration(self.migrations.get(&ctr.id).cloned())
+                    .query(
+                        self.database_for(ctr)
+                            .filter(|database| !database.query_command.is_empty())
+                            .map(|_| {
+                                self.query_consoles
+                                    .get(&ctr.id)
+                                    .cloned()
+                                    .unwrap_or_default()
+                            }),
+                    )
+                    .on_query_change(|id, query| {
+                        Message::Container(ContainerMsg::QueryChanged(id, query))
+                    })
+                    .on_copy_click(|text| Message::Ui(UiMsg::CopyToClipboard(text)))
+                    .on_open_folder(|path| Message::Ui(UiMsg::OpenPath(path)))
+                    .limits(self.limit_forms.get(&ctr.id).cloned())
+                    .tab(
+                        self.detail_tabs
+                            .get(&ctr.id)
+                            .copied()
+                            .filter(|tab| {
+                                *tab != DetailTab::Inspect || self.preferences.developer_mode
+                            })
+                            .unwrap_or_default(),
+                    )
+                    .raw_inspect(self.preferences.developer_mode.then(|| {
+                        self.raw_inspects
+                            .get(&ctr.id)
+                            .cloned()
+                            .unwrap_or(RawInspect::Loading)
+                    }))
+                    .on_tab_select(|id, tab| Message::Ui(UiMsg::SelectTab(id, tab)))
+                    .auto_stop(self.auto_stop.remaining(&ctr.id, Instant::now()))
+                    .started(self.started(ctr))
+                    .keep_on_exit(self.preferences.stop_on_exit.then(|| {
+                        self.preferences
+                            .keep_on_exit
+                            .contains(ctr.name.trim_start_matches('/'))
+                    }))
+                    .on_keep_on_exit(|name, keep| Message::Ui(UiMsg::KeepOnExit(name, keep)))
+                    .favourite(self.tray.is_some().then(|| {
+                        self.preferences
+                            .favourites
+                            .contains(ctr.name.trim_start_matches('/'))
+                    }))
+                    .on_favourite_toggle(|name, favourite| {
+                        Message::Ui(UiMsg::Favourite(name, favourite))
+                    })
+                    .eol(
+                        self.end_of_life.get(&ctr.id).copied(),
+                        self.preferences
+                            .eol_silenced
+                            .contains(ctr.name.trim_start_matches('/')),
+                    )
+                    .on_eol_silence(|name, silenced| Message::Ui(UiMsg::SilenceEol(name, silenced)))
+                    .project(self.preferences.project(ctr))
+                    .on_project_save(|name, project| Message::Ui(UiMsg::SetProject(name, project)))
+                    .on_export_logs(|request| Message::Container(ContainerMsg::ExportLogs(request)))
+                    .logs(
+                        self.log_follow
+                            .as_ref()
+                            .filter(|follow| follow.container == ctr.id)
+                            .map(LogFollow::panel),
+                    )
+                    .on_log_filter_change(|id, filter| {
+                        Message::Container(ContainerMsg::LogFilterChanged(id, filter))
+                    })
+                    .on_logs_scroll(|id, at_bottom| {
+                        Message::Container(ContainerMsg::LogsScrolled(id, at_bottom))
+                    })
+                    .on_jump_to_latest(|id| Message::Container(ContainerMsg::JumpToLatest(id)))
+                    .on_export_definition(|id, include_secrets| {
+                        Message::Container(ContainerMsg::ExportDefinition(id, include_secrets))
+                    })
+                    .on_copy_run_command(|id, options| {
+                        Message::Container(ContainerMsg::CopyRunCommand(id, options))
+                    })
+                    .terminal(Some(
+                        self.terminal
+                            .clone()
+                            .filter(|terminal| terminal.container == ctr.id)
+                            .unwrap_or_else(|| Terminal::new(ctr.id.clone())),
+                    ))
+                    .on_terminal_command_change(|id, command| {
+                        Message::Container(ContainerMsg::TerminalCommandChanged(id, command))
+                    })
+                    .on_terminal_input(|id, input| {
+                        Message::Container(ContainerMsg::TerminalInputChanged(id, input))
+                    })
+                    .on_terminal_submit(|id| {
+                        Message::Container(ContainerMsg::SendTerminalInput(id))
+                    })
+                    .on_terminal_stop(|id| Message::Container(ContainerMsg::StopTerminal(id)))
+                    .events(self.events.events(&ctr.id))
+                    .backup(
+                        self.preferences
+                            .backups
+                            .get(ctr.name.trim_start_matches('/'))
+                            .cloned(),
+                    );
+                let view = if self.read_only {
+                    view
+                } else {
+                    view.on_backup_change(|name, schedule| {
+                        Message::Container(ContainerMsg::SetBackupSchedule(name, schedule))
+                    })
+                    .on_choose_backup_directory(|name| {
+                        Message::Container(ContainerMsg::ChooseBackupDirectory(name))
+                    })
+                };
+                container(if self.read_only || ctr.partial {
+                    view
+                } else {
+                    view.on_migrate(|id| Message::Container(ContainerMsg::MigrateContainer(id)))
+                        .on_run_query(|id| Message::Container(ContainerMsg::RunQuery(id)))
+                        .on_auto_stop(|id, after| {
+                            Message::Container(ContainerMsg::SetAutoStop(id, after))
+                        })
+                        .on_recreate(|id, config| {
+                            Message::Container(ContainerMsg::RecreateContainer(
+                                id,
+                                Box::new(config),
+                            ))
+                        })
+                        .on_project_recreate(|id, project| {
+                            Message::Container(ContainerMsg::RecreateWithProject(id, project))
+                        })
+                        .on_limits_change(|id, form| {
+                            Message::Container(ContainerMsg::LimitsChanged(id, form.map(Box::new)))
+                        })
+                        .on_limits_apply(|id| Message::Container(ContainerMsg::ApplyLimits(id)))
+                        .on_terminal_start(|id| Message::Container(ContainerMsg::StartTerminal(id)))
+                })
             }
         }
         .width(Length::FillPortion(2))
         .align_x(Horizontal::Center)
         .align_y(Vertical::Center);
 
-        row!(containers, vertical_rule(2), main_windown).into()
+        let status = status_bar(
+            &self.endpoint,
+            self.connected,
+            running_count(&self.containers),
+            self.containers.len(),
+        )
+        .last_refresh(self.last_refresh, unix_now() as i64)
+        .refreshing(self.refresh.in_flight())
+        .on_connection_click(|| Message::Ui(UiMsg::ShowAbout))
+        .on_refresh_click(|| Message::Container(ContainerMsg::GetContainers));
+
+        let mut content =
+            column!(row!(containers, vertical_rule(2), main_windown).height(Length::Fill));
+        if let Some(offer) = self.companion_offer_view() {
+            content = content.push(offer);
+        }
+        if let Some(offer) = self.draft_offer_view() {
+            content = content.push(offer);
+        }
+        if let Some(toasts) = self.toasts_view() {
+            content = content.push(toasts);
+        }
+        content.push(horizontal_rule(2)).push(status).into()
     }
 }
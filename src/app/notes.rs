@@ -0,0 +1,54 @@
+/// A line of the notes of a config entry, styled by the markdown it starts with.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NoteLine {
+    /// A line starting with one or more `#`.
+    Heading(String),
+    /// A line starting with `-` or `*`.
+    Bullet(String),
+    /// A paragraph, its lines joined with spaces.
+    Text(String),
+    /// One or more empty lines between paragraphs.
+    Gap,
+}
+
+/// Drops the markers of inline markdown, which can't be styled within a line.
+fn plain(line: &str) -> String {
+    line.replace("**", "").replace("__", "").replace('`', "")
+}
+
+/// The lines of `notes` to show, with the headings and list items of markdown told apart and the
+/// lines of a paragraph joined. Empty lines at the start and end are left out and runs of them
+/// become a single [`NoteLine::Gap`].
+pub fn note_lines(notes: &str) -> Vec<NoteLine> {
+    let mut lines: Vec<NoteLine> = vec![];
+    for line in notes.lines().map(str::trim) {
+        let parsed = if line.is_empty() {
+            NoteLine::Gap
+        } else if let Some(heading) = line
+            .strip_prefix('#')
+            .map(|rest| rest.trim_start_matches('#'))
+            .and_then(|rest| rest.strip_prefix(' '))
+        {
+            NoteLine::Heading(plain(heading.trim()))
+        } else if let Some(item) = line.strip_prefix("- ").or_else(|| line.strip_prefix("* ")) {
+            NoteLine::Bullet(plain(item.trim()))
+        } else {
+            NoteLine::Text(plain(line))
+        };
+
+        match (lines.last_mut(), parsed) {
+            (None | Some(NoteLine::Gap), NoteLine::Gap) => {}
+            // As in markdown, lines of a paragraph are wrapped as one
+            (Some(NoteLine::Text(paragraph)), NoteLine::Text(line)) => {
+                paragraph.push(' ');
+                paragraph.push_str(&line);
+            }
+            (_, parsed) => lines.push(parsed),
+        }
+    }
+    if lines.last() == Some(&NoteLine::Gap) {
+        lines.pop();
+    }
+
+    lines
+}
@@ -0,0 +1,315 @@
+use std::{collections::BTreeSet, time::Instant};
+
+use iced::{
+    theme::Text,
+    widget::{button, checkbox, column, component, progress_bar, row, scrollable, text, Component},
+    Color, Element, Length, Renderer,
+};
+use iced_aw::{badge, BadgeStyles};
+
+use super::{cleanup::format_size, pull_progress::PullProgress};
+use crate::{
+    data::DatabaseConfig,
+    docker::{PrefetchEvent, PullEvent},
+};
+
+#[derive(Clone)]
+pub enum Event {
+    Toggle(String, bool),
+    Run,
+}
+
+/// How far the prefetch of one image got.
+#[derive(Clone, Debug)]
+pub enum PrefetchStatus {
+    Waiting,
+    /// Already present locally with this size, so it wasn't pulled.
+    Present(i64),
+    Pulling(PullProgress),
+    /// Pulled, with its size if it could be inspected afterwards.
+    Pulled(Option<i64>),
+    Failed(String),
+}
+
+/// The images of a prefetch, as `image:tag`, in the order they are pulled.
+#[derive(Clone, Debug)]
+pub struct PrefetchRun {
+    pub images: Vec<(String, PrefetchStatus)>,
+    pub running: bool,
+}
+
+impl PrefetchRun {
+    pub fn new(images: Vec<String>) -> Self {
+        Self {
+            images: images
+                .into_iter()
+                .map(|image| (image, PrefetchStatus::Waiting))
+                .collect(),
+            running: true,
+        }
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.images.iter().map(|(image, _)| image.clone()).collect()
+    }
+
+    pub fn apply(&mut self, event: PrefetchEvent, now: Instant) {
+        let (image, status) = match event {
+            PrefetchEvent::Done => {
+                self.running = false;
+                return;
+            }
+            PrefetchEvent::Present(image, size) => (image, PrefetchStatus::Present(size)),
+            PrefetchEvent::Pulled(image, size) => (image, PrefetchStatus::Pulled(size)),
+            PrefetchEvent::Failed(image, message) => (image, PrefetchStatus::Failed(message)),
+            PrefetchEvent::Pulling(image, event) => {
+                let Some((_, status)) = self.images.iter_mut().find(|(name, _)| *name == image)
+                else {
+                    return;
+                };
+                if !matches!(status, PrefetchStatus::Pulling(_)) {
+                    *status = PrefetchStatus::Pulling(PullProgress::default());
+                }
+                if let (
+                    PrefetchStatus::Pulling(progress),
+                    PullEvent::Download {
+                        layer,
+                        current,
+                        total,
+                    },
+                ) = (status, event)
+                {
+                    progress.update(layer, current, total, now);
+                }
+                return;
+            }
+        };
+
+        if let Some((_, current)) = self.images.iter_mut().find(|(name, _)| *name == image) {
+            *current = status;
+        }
+    }
+}
+
+/// The `image:tag` of every tag of the config entries that name an image, the ones to offer.
+pub fn prefetch_choices(databases: &[DatabaseConfig]) -> Vec<(String, Vec<String>)> {
+    databases
+        .iter()
+        .filter(|database| !database.image.is_empty())
+        .map(|database| {
+            let tags = if database.tags.is_empty() {
+                vec![format!("{}:latest", database.image)]
+            } else {
+                database
+                    .tags
+                    .iter()
+                    .map(|tag| format!("{}:{tag}", database.image))
+                    .collect()
+            };
+            (database.name.clone(), tags)
+        })
+        .collect()
+}
+
+/// The default tag of each config entry that names an image, what is selected at first.
+pub fn default_prefetch(databases: &[DatabaseConfig], never_latest: bool) -> BTreeSet<String> {
+    databases
+        .iter()
+        .filter(|database| !database.image.is_empty())
+        .filter_map(|database| {
+            let tag = database.default_tag(never_latest);
+            (!tag.is_empty()).then(|| format!("{}:{tag}", database.image))
+        })
+        .collect()
+}
+
+pub fn prefetch_view<Message>(
+    choices: Vec<(String, Vec<String>)>,
+    selection: BTreeSet<String>,
+    run: Option<PrefetchRun>,
+) -> PrefetchView<Message> {
+    PrefetchView::new(choices, selection, run)
+}
+
+pub struct PrefetchView<Message> {
+    choices: Vec<(String, Vec<String>)>,
+    selection: BTreeSet<String>,
+    run: Option<PrefetchRun>,
+    on_change: Option<Box<dyn Fn(BTreeSet<String>) -> Message>>,
+    on_run: Option<Box<dyn Fn(Vec<String>) -> Message>>,
+}
+
+impl<Message> PrefetchView<Message> {
+    pub fn new(
+        choices: Vec<(String, Vec<String>)>,
+        selection: BTreeSet<String>,
+        run: Option<PrefetchRun>,
+    ) -> Self {
+        Self {
+            choices,
+            selection,
+            run,
+            on_change: None,
+            on_run: None,
+        }
+    }
+
+    pub fn on_change<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn(BTreeSet<String>) -> Message + 'static,
+    {
+        Self {
+            on_change: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    /// Called with the selected images, in the order they are listed.
+    pub fn on_run<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn(Vec<String>) -> Message + 'static,
+    {
+        Self {
+            on_run: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    fn is_running(&self) -> bool {
+        self.run.as_ref().is_some_and(|run| run.running)
+    }
+}
+
+fn status_row<'a>(image: &'a str, status: &'a PrefetchStatus) -> Element<'a, Event, Renderer> {
+    let grey = Text::Color(Color::from_rgb8(150, 150, 150));
+    let detail: Element<'a, Event, Renderer> = match status {
+        PrefetchStatus::Waiting => text("Waiting").size(12).style(grey).into(),
+        PrefetchStatus::Present(size) => text(format!("Already present ({})", format_size(*size)))
+            .size(12)
+            .style(grey)
+            .into(),
+        PrefetchStatus::Pulling(progress) => {
+            let mut detail = row!(progress_bar(0.0..=1.0, progress.fraction())
+                .height(8)
+                .width(Length::FillPortion(3)))
+            .spacing(10)
+            .align_items(iced::Alignment::Center);
+            if progress.total() > 0 {
+                detail = detail.push(
+                    text(format!(
+                        "{} of {}",
+                        format_size(progress.downloaded() as i64),
+                        format_size(progress.total() as i64)
+                    ))
+                    .size(12),
+                );
+            }
+            if let Some(summary) = progress.summary(Instant::now()) {
+                detail = detail.push(text(summary).size(12).style(grey));
+            }
+            detail.into()
+        }
+        PrefetchStatus::Pulled(size) => text(match size {
+            Some(size) => format!("Pulled ({})", format_size(*size)),
+            None => "Pulled".to_string(),
+        })
+        .size(12)
+        .into(),
+        PrefetchStatus::Failed(message) => text(format!("Failed: {message}"))
+            .size(12)
+            .style(Text::Color(Color::from_rgb8(220, 60, 60)))
+            .into(),
+    };
+
+    column!(text(image), detail).spacing(4).into()
+}
+
+impl<Message> Component<Message, Renderer> for PrefetchView<Message> {
+    type State = ();
+
+    type Event = Event;
+
+    fn update(&mut self, _: &mut Self::State, event: Self::Event) -> Option<Message> {
+        match event {
+            Event::Toggle(image, checked) => {
+                if checked {
+                    self.selection.insert(image);
+                } else {
+                    self.selection.remove(&image);
+                }
+                self.on_change
+                    .as_ref()
+                    .map(|fun| fun(self.selection.clone()))
+            }
+            Event::Run => {
+                let images = self
+                    .choices
+                    .iter()
+                    .flat_map(|(_, images)| images.iter())
+                    .filter(|image| self.selection.contains(*image))
+                    .cloned()
+                    .collect();
+                self.on_run.as_ref().map(|fun| fun(images))
+            }
+        }
+    }
+
+    fn view(&self, _: &Self::State) -> Element<'_, Self::Event, Renderer> {
+        let mut content = column!(
+            text("Prefetch images").size(22),
+            text(
+                "Pulls the selected images without creating containers, images already present \
+                 are skipped."
+            )
+            .size(14)
+            .style(Text::Color(Color::from_rgb8(150, 150, 150))),
+        )
+        .spacing(15)
+        .padding(15);
+
+        if self.choices.is_empty() {
+            content = content.push(text("No database in config.yaml names an image to pull"));
+        }
+
+        let running = self.is_running();
+        for (name, images) in self.choices.iter() {
+            let mut entry = column!(text(name).size(18)).spacing(5);
+            for image in images {
+                let toggle = image.clone();
+                entry = entry.push(checkbox(
+                    image.clone(),
+                    self.selection.contains(image),
+                    move |checked| Event::Toggle(toggle.clone(), checked),
+                ));
+            }
+            content = content.push(entry);
+        }
+
+        if running {
+            content = content.push(badge("Pulling").style(BadgeStyles::Info));
+        } else if self.on_run.is_some() {
+            let mut prefetch = button("Prefetch");
+            if !self.selection.is_empty() {
+                prefetch = prefetch.on_press(Event::Run);
+            }
+            content = content.push(prefetch);
+        }
+
+        if let Some(run) = self.run.as_ref() {
+            for (image, status) in run.images.iter() {
+                content = content.push(status_row(image, status));
+            }
+        }
+
+        scrollable(content).into()
+    }
+}
+
+impl<'a, Message> From<PrefetchView<Message>> for Element<'a, Message, Renderer>
+where
+    Message: 'a,
+{
+    fn from(value: PrefetchView<Message>) -> Self {
+        component(value)
+    }
+}
@@ -0,0 +1,78 @@
+use std::collections::HashMap;
+
+use super::events::format_ago;
+
+/// How many seconds a start db-mgr sent and the start docker reported may be apart to be taken as
+/// the same start, either may arrive first.
+pub const MATCH_WINDOW: i64 = 30;
+
+/// Who started a container.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum StartSource {
+    App,
+    /// Started with the docker CLI, another tool or a restart policy.
+    External,
+}
+
+/// A start docker reported while db-mgr was running.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Provenance {
+    pub source: StartSource,
+    /// Seconds since the unix epoch.
+    pub at: i64,
+}
+
+impl Provenance {
+    pub fn describe(&self, now: i64) -> String {
+        let by = match self.source {
+            StartSource::App => "by db-mgr",
+            StartSource::External => "outside db-mgr",
+        };
+        format!("Started {by} {}", format_ago(now - self.at))
+    }
+}
+
+/// Who started the running containers during this session, keyed by container id. Containers
+/// started before db-mgr was opened have no entry.
+#[derive(Debug, Default)]
+pub struct StartProvenance {
+    /// Starts sent by db-mgr that docker didn't report yet, with when they were sent.
+    sent: HashMap<String, i64>,
+    started: HashMap<String, Provenance>,
+}
+
+impl StartProvenance {
+    /// Records that db-mgr is starting `id`. A start of it reported in the last
+    /// [`MATCH_WINDOW`] seconds was this one, its event arrived first.
+    pub fn sent(&mut self, id: String, now: i64) {
+        match self.started.get_mut(&id) {
+            Some(started)
+                if started.source == StartSource::External && now - started.at <= MATCH_WINDOW =>
+            {
+                started.source = StartSource::App;
+            }
+            _ => {
+                self.sent.insert(id, now);
+            }
+        }
+    }
+
+    /// Records that docker reported `id` started, by db-mgr if it sent a start in the last
+    /// [`MATCH_WINDOW`] seconds.
+    pub fn reported(&mut self, id: String, now: i64) {
+        let source = match self.sent.remove(&id) {
+            Some(sent) if now - sent <= MATCH_WINDOW => StartSource::App,
+            _ => StartSource::External,
+        };
+        self.started.insert(id, Provenance { source, at: now });
+    }
+
+    /// Forgets the start of `id` once it stopped.
+    pub fn stopped(&mut self, id: &str) {
+        self.started.remove(id);
+    }
+
+    pub fn get(&self, id: &str) -> Option<Provenance> {
+        self.started.get(id).copied()
+    }
+}
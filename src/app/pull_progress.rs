@@ -0,0 +1,127 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use itertools::Itertools;
+
+use super::cleanup::format_size;
+
+/// Time constant of the moving average of the transfer rate.
+pub const SMOOTHING: Duration = Duration::from_secs(5);
+
+/// How long without new bytes before a pull is shown as stalled.
+pub const STALLED_AFTER: Duration = Duration::from_secs(15);
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct Layer {
+    current: u64,
+    total: u64,
+}
+
+/// The byte counts of the layers of an image pull and its smoothed transfer rate.
+#[derive(Clone, Debug, Default)]
+pub struct PullProgress {
+    layers: HashMap<String, Layer>,
+    /// Bytes per second, an exponential moving average over [`SMOOTHING`].
+    rate: Option<f64>,
+    /// When the last update arrived and how many bytes were downloaded by then.
+    last_sample: Option<(Instant, u64)>,
+    /// When the downloaded bytes last went up.
+    last_progress: Option<Instant>,
+}
+
+impl PullProgress {
+    pub fn update(&mut self, layer: String, current: u64, total: u64, now: Instant) {
+        self.layers.insert(layer, Layer { current, total });
+
+        let downloaded = self.downloaded();
+        match self.last_sample {
+            None => self.last_progress = Some(now),
+            Some((at, previous)) => {
+                if downloaded > previous {
+                    self.last_progress = Some(now);
+                }
+
+                let elapsed = now.saturating_duration_since(at).as_secs_f64();
+                if elapsed > 0.0 {
+                    let sample = downloaded.saturating_sub(previous) as f64 / elapsed;
+                    let weight = 1.0 - (-elapsed / SMOOTHING.as_secs_f64()).exp();
+                    self.rate = Some(match self.rate {
+                        None => sample,
+                        Some(rate) => rate + weight * (sample - rate),
+                    });
+                }
+            }
+        }
+        self.last_sample = Some((now, downloaded));
+    }
+
+    pub fn downloaded(&self) -> u64 {
+        self.layers.values().map(|layer| layer.current).sum()
+    }
+
+    pub fn total(&self) -> u64 {
+        self.layers.values().map(|layer| layer.total).sum()
+    }
+
+    /// The progress of each layer from 0 to 1, by layer id.
+    pub fn layers(&self) -> Vec<(&String, f32)> {
+        self.layers
+            .iter()
+            .map(|(id, layer)| {
+                let fraction = match layer.total {
+                    0 => 1.0,
+                    total => layer.current as f32 / total as f32,
+                };
+                (id, fraction)
+            })
+            .sorted_by_key(|(id, _)| *id)
+            .collect()
+    }
+
+    /// The progress of all layers together from 0 to 1.
+    pub fn fraction(&self) -> f32 {
+        match self.total() {
+            0 => 0.0,
+            total => self.downloaded() as f32 / total as f32,
+        }
+    }
+
+    /// Whether bytes are left to download but none arrived for [`STALLED_AFTER`].
+    pub fn is_stalled(&self, now: Instant) -> bool {
+        self.downloaded() < self.total()
+            && self
+                .last_progress
+                .is_some_and(|at| now.saturating_duration_since(at) >= STALLED_AFTER)
+    }
+
+    /// Such as `42.0 MB/s — about 1 min left`, `None` until a rate is known.
+    pub fn summary(&self, now: Instant) -> Option<String> {
+        if self.is_stalled(now) {
+            return Some("stalled".to_string());
+        }
+
+        let rate = self.rate.filter(|rate| *rate > 0.0)?;
+        let remaining = self.total().saturating_sub(self.downloaded());
+        Some(format!(
+            "{} — {}",
+            format_rate(rate),
+            format_eta(Duration::from_secs_f64(remaining as f64 / rate))
+        ))
+    }
+}
+
+pub fn format_rate(bytes_per_second: f64) -> String {
+    format!("{}/s", format_size(bytes_per_second as i64))
+}
+
+/// Rounds up to a readable unit, such as `about 40 s left` or `about 3 min left`.
+pub fn format_eta(eta: Duration) -> String {
+    match eta.as_secs() {
+        0 => "almost done".to_string(),
+        seconds @ 1..=59 => format!("about {} s left", seconds.div_ceil(10) * 10),
+        seconds @ 60..=3599 => format!("about {} min left", seconds.div_ceil(60)),
+        seconds => format!("about {} h left", seconds.div_ceil(3600)),
+    }
+}
@@ -0,0 +1,79 @@
+use crate::docker::ExecOutput;
+
+/// How many queries are kept per container.
+pub const HISTORY_LEN: usize = 10;
+
+/// The query box of a container, kept on the app for the session.
+#[derive(Clone, Debug, Default)]
+pub struct QueryConsole {
+    pub input: String,
+    pub running: bool,
+    pub output: Option<Result<ExecOutput, String>>,
+    /// Most recent query last.
+    history: Vec<String>,
+    /// The history entry shown in the input while navigating with up and down.
+    cursor: Option<usize>,
+}
+
+impl QueryConsole {
+    /// The queries run this session, most recent last.
+    pub fn history(&self) -> &[String] {
+        &self.history
+    }
+
+    pub fn set_input(&mut self, input: String) {
+        self.input = input;
+        self.cursor = None;
+    }
+
+    /// Records the current input in the history and returns it, `None` if it is empty or a query
+    /// is already running.
+    pub fn submit(&mut self) -> Option<String> {
+        let query = self.input.trim().to_string();
+        if query.is_empty() || self.running {
+            return None;
+        }
+
+        self.history.retain(|previous| previous != &query);
+        self.history.push(query.clone());
+        if self.history.len() > HISTORY_LEN {
+            self.history.remove(0);
+        }
+        self.cursor = None;
+        self.running = true;
+
+        Some(query)
+    }
+
+    pub fn finish(&mut self, output: Result<ExecOutput, String>) {
+        self.running = false;
+        self.output = Some(output);
+    }
+
+    /// Shows the query before the one in the input.
+    pub fn previous(&mut self) {
+        let cursor = match self.cursor {
+            None if self.history.is_empty() => return,
+            None => self.history.len() - 1,
+            Some(cursor) => cursor.saturating_sub(1),
+        };
+
+        self.cursor = Some(cursor);
+        self.input = self.history[cursor].clone();
+    }
+
+    /// Shows the query after the one in the input, clearing the input past the newest one.
+    pub fn next(&mut self) {
+        let Some(cursor) = self.cursor else {
+            return;
+        };
+
+        if cursor + 1 < self.history.len() {
+            self.cursor = Some(cursor + 1);
+            self.input = self.history[cursor + 1].clone();
+        } else {
+            self.cursor = None;
+            self.input.clear();
+        }
+    }
+}
@@ -0,0 +1,181 @@
+use std::io::IsTerminal;
+
+use iced::{
+    theme::Text,
+    widget::{button, column, component, pick_list, row, text, text_input, Component},
+    Color, Element, Renderer,
+};
+
+use crate::data::DatabaseConfig;
+
+#[derive(Clone)]
+pub enum Event {
+    NameChanged(String),
+    SelectDatabase(Box<DatabaseConfig>),
+    Submit,
+}
+
+/// The contents of the quick start form, owned by the app like the create form.
+#[derive(Clone, Debug, Default)]
+pub struct QuickStartState {
+    pub name: String,
+    pub database: Option<DatabaseConfig>,
+}
+
+impl QuickStartState {
+    pub fn new(images: &[DatabaseConfig]) -> Self {
+        Self {
+            name: default_name(),
+            database: images.first().cloned(),
+        }
+    }
+}
+
+/// The name of the current directory when started from a terminal, reduced to the characters
+/// docker allows in container names.
+pub fn default_name() -> String {
+    if !std::io::stdin().is_terminal() {
+        return String::new();
+    }
+
+    std::env::current_dir()
+        .ok()
+        .and_then(|dir| Some(sanitize_name(dir.file_name()?.to_str()?)))
+        .unwrap_or_default()
+}
+
+pub fn sanitize_name(name: &str) -> String {
+    name.to_lowercase()
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | '0'..='9' | '_' | '.' | '-' => c,
+            _ => '-',
+        })
+        .collect::<String>()
+        .trim_matches(|c: char| !c.is_ascii_alphanumeric())
+        .to_string()
+}
+
+pub fn quick_start<Message>(
+    images: Vec<DatabaseConfig>,
+    draft: QuickStartState,
+    creating: bool,
+) -> QuickStart<Message> {
+    QuickStart::new(images, draft, creating)
+}
+
+pub struct QuickStart<Message> {
+    images: Vec<DatabaseConfig>,
+    draft: QuickStartState,
+    creating: bool,
+    on_change: Option<Box<dyn Fn(QuickStartState) -> Message>>,
+    on_submit: Option<Box<dyn Fn() -> Message>>,
+}
+
+impl<Message> QuickStart<Message> {
+    pub fn new(images: Vec<DatabaseConfig>, draft: QuickStartState, creating: bool) -> Self {
+        Self {
+            images,
+            draft,
+            creating,
+            on_change: None,
+            on_submit: None,
+        }
+    }
+
+    pub fn on_change<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn(QuickStartState) -> Message + 'static,
+    {
+        Self {
+            on_change: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    pub fn on_submit<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn() -> Message + 'static,
+    {
+        Self {
+            on_submit: Some(Box::new(handler)),
+            ..self
+        }
+    }
+}
+
+impl<Message> Component<Message, Renderer> for QuickStart<Message> {
+    type State = ();
+
+    type Event = Event;
+
+    fn update(&mut self, _: &mut Self::State, event: Self::Event) -> Option<Message> {
+        match event {
+            Event::NameChanged(name) => {
+                self.draft.name = name.replace(' ', "-");
+            }
+            Event::SelectDatabase(database) => {
+                self.draft.database = Some(*database);
+            }
+            Event::Submit => return self.on_submit.as_ref().map(|fun| fun()),
+        }
+
+        self.on_change.as_ref().map(|fun| fun(self.draft.clone()))
+    }
+
+    fn view(&self, _: &Self::State) -> Element<'_, Self::Event, Renderer> {
+        let mut content = column!(
+            text("Quick start").size(22),
+            row!(
+                text_input("name, or blank for a generated one", &self.draft.name)
+                    .on_input(Event::NameChanged),
+                pick_list(self.images.clone(), self.draft.database.clone(), |image| {
+                    Event::SelectDatabase(Box::new(image))
+                })
+                .placeholder("Choose image")
+            )
+            .spacing(15)
+        )
+        .align_items(iced::Alignment::Center)
+        .spacing(15)
+        .padding(15);
+
+        if let Some(database) = self.draft.database.as_ref() {
+            let generated = database
+                .generated_variables()
+                .map(|(name, _)| name.trim())
+                .collect::<Vec<_>>();
+
+            if !generated.is_empty() {
+                content = content.push(
+                    text(format!(
+                        "A random value will be generated for {}",
+                        generated.join(", ")
+                    ))
+                    .size(12)
+                    .style(Text::Color(Color::from_rgb8(150, 150, 150))),
+                );
+            }
+        }
+
+        let mut create = button(if self.creating {
+            "Creating…"
+        } else {
+            "Create"
+        });
+        if !self.creating && self.draft.database.is_some() {
+            create = create.on_press(Event::Submit);
+        }
+
+        content.push(create).into()
+    }
+}
+
+impl<'a, Message> From<QuickStart<Message>> for Element<'a, Message, Renderer>
+where
+    Message: 'a,
+{
+    fn from(value: QuickStart<Message>) -> Self {
+        component(value)
+    }
+}
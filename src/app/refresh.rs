@@ -0,0 +1,93 @@
+use std::collections::HashSet;
+
+use crate::docker::DbContainer;
+
+/// Keeps at most one container refresh running, further requests are folded into one follow-up.
+#[derive(Debug, Default)]
+pub struct RefreshQueue {
+    in_flight: bool,
+    requested: bool,
+    sequence: u64,
+}
+
+impl RefreshQueue {
+    /// Returns the sequence number of the refresh to start, or `None` if one is already running.
+    pub fn request(&mut self) -> Option<u64> {
+        if self.in_flight {
+            self.requested = true;
+            return None;
+        }
+
+        self.in_flight = true;
+        self.sequence += 1;
+        Some(self.sequence)
+    }
+
+    /// Whether a refresh is running.
+    pub fn in_flight(&self) -> bool {
+        self.in_flight
+    }
+
+    /// Marks the refresh `sequence` as finished, returns false if its result is stale.
+    pub fn finish(&mut self, sequence: u64) -> bool {
+        if sequence != self.sequence {
+            return false;
+        }
+
+        self.in_flight = false;
+        true
+    }
+
+    /// Starts the coalesced follow-up refresh if one was requested while the last one was running.
+    pub fn follow_up(&mut self) -> Option<u64> {
+        if !self.requested || self.in_flight {
+            return None;
+        }
+
+        self.requested = false;
+        self.request()
+    }
+}
+
+/// Puts the refreshed container `id` into `containers`, which are in the order docker lists them,
+/// newest first. It keeps its place when it is listed, is removed when `refreshed` is `None` and
+/// goes first otherwise, in place of a container of the same name it was recreated from. A
+/// selected container it replaces stays selected under its new id. Returns the containers taken
+/// out of the list or replaced, by their state before the refresh.
+pub fn patch_containers(
+    containers: &mut Vec<DbContainer>,
+    selection: Option<&mut HashSet<String>>,
+    id: &str,
+    refreshed: Option<DbContainer>,
+) -> Vec<DbContainer> {
+    let Some(refreshed) = refreshed else {
+        let removed = containers
+            .iter()
+            .position(|container| container.id == id)
+            .map(|index| containers.remove(index));
+        if let Some(selection) = selection {
+            selection.remove(id);
+        }
+        return removed.into_iter().collect();
+    };
+
+    if let Some(existing) = containers.iter_mut().find(|container| container.id == id) {
+        return vec![std::mem::replace(existing, refreshed)];
+    }
+
+    let (recreated, kept) = std::mem::take(containers)
+        .into_iter()
+        .partition::<Vec<_>, _>(|container| container.name == refreshed.name);
+    if let Some(selection) = selection {
+        // Every replaced one is deselected, not only up to the first selected one
+        let deselected = recreated
+            .iter()
+            .filter(|old| selection.remove(&old.id))
+            .count();
+        if deselected > 0 {
+            selection.insert(refreshed.id.clone());
+        }
+    }
+    *containers = std::iter::once(refreshed).chain(kept).collect();
+    recreated
+}
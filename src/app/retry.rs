@@ -0,0 +1,39 @@
+use std::collections::HashMap;
+
+/// The messages that redo failed operations, kept by id for as long as the toast offering them
+/// is shown.
+#[derive(Debug)]
+pub struct Retries<M> {
+    next: u64,
+    pending: HashMap<u64, M>,
+}
+
+impl<M> Default for Retries<M> {
+    fn default() -> Self {
+        Self {
+            next: 0,
+            pending: HashMap::new(),
+        }
+    }
+}
+
+impl<M> Retries<M> {
+    /// Keeps `retry` and returns the id a toast refers to it by.
+    pub fn register(&mut self, retry: M) -> u64 {
+        self.next += 1;
+        self.pending.insert(self.next, retry);
+        self.next
+    }
+
+    /// Hands out the retry once, a second click on the same button gets `None`.
+    pub fn take(&mut self, id: u64) -> Option<M> {
+        self.pending.remove(&id)
+    }
+
+    /// Drops the retries whose id isn't in `live`, the ones of toasts that expired or were
+    /// dismissed.
+    pub fn retain(&mut self, live: impl IntoIterator<Item = u64>) {
+        let live = live.into_iter().collect::<Vec<_>>();
+        self.pending.retain(|id, _| live.contains(id));
+    }
+}
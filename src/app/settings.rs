@@ -0,0 +1,1023 @@
+use iced::{
+    theme::{Button, Text},
+    widget::{
+        button, checkbox, column, component, pick_list, row, scrollable, text, text_input,
+        Component,
+    },
+    Color, Element, Length, Renderer,
+};
+use iced_aw::{badge, BadgeStyles};
+use std::{path::PathBuf, time::Duration};
+
+use super::events::format_ago;
+use crate::{
+    data::{AppPath, AppTheme, CreatedDefinition, Preferences},
+    errors::UserError,
+    idle::{unix_now, IdleAfter},
+    platform::WINDOWS_PIPE,
+    validate::{EntryReport, Severity},
+};
+
+#[derive(Clone)]
+pub enum Event {
+    Validate,
+    TestConnection,
+    Volumes,
+    About,
+    Cleanup,
+    Prefetch,
+    RememberValues(bool),
+    RememberPasswords(bool),
+    NeverDefaultLatest(bool),
+    SkipCreateReview(bool),
+    StopOnExit(bool),
+    SingleInstance(bool),
+    DeveloperMode(bool),
+    DockerHost(String),
+    TlsEnabled(bool),
+    TlsHost(String),
+    ChooseTlsFile(TlsFile),
+    TestTls,
+    Reconnect,
+    Tray(bool),
+    StateFile(bool),
+    ChooseStateFile,
+    ReadOnly(bool),
+    IdleAfter(IdleAfter),
+    Theme(AppTheme),
+    CheckUpdates,
+    CheckUpdatesDaily(bool),
+    ClearRemembered(String),
+    CopyPath(String),
+    OpenPath(PathBuf),
+    OpenConfig,
+    Recreate(String),
+    Forget(String),
+}
+
+#[derive(Clone, Debug)]
+pub enum ConnectionTest {
+    Running,
+    Succeeded(Duration),
+    Failed(String),
+}
+
+/// One of the files of the TLS client certificate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TlsFile {
+    Ca,
+    Cert,
+    Key,
+}
+
+impl TlsFile {
+    pub const ALL: [TlsFile; 3] = [TlsFile::Ca, TlsFile::Cert, TlsFile::Key];
+}
+
+impl std::fmt::Display for TlsFile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TlsFile::Ca => write!(f, "CA certificate"),
+            TlsFile::Cert => write!(f, "Client certificate"),
+            TlsFile::Key => write!(f, "Client key"),
+        }
+    }
+}
+
+/// Connecting with the TLS client certificate from the settings.
+#[derive(Clone, Debug)]
+pub enum TlsTest {
+    Running,
+    /// The daemon answered, with its version.
+    Connected(String),
+    Failed(UserError),
+}
+
+pub fn settings<Message>(
+    validation: Option<Vec<EntryReport>>,
+    validating: bool,
+    preferences: Preferences,
+    connection_test: Option<ConnectionTest>,
+) -> Settings<Message> {
+    Settings::new(validation, validating, preferences, connection_test)
+}
+
+pub struct Settings<Message> {
+    validation: Option<Vec<EntryReport>>,
+    validating: bool,
+    preferences: Preferences,
+    connection_test: Option<ConnectionTest>,
+    tls_test: Option<TlsTest>,
+    paths: Vec<AppPath>,
+    checking_updates: bool,
+    deleted: Vec<CreatedDefinition>,
+    on_validate_click: Option<Box<dyn Fn() -> Message>>,
+    on_test_connection_click: Option<Box<dyn Fn() -> Message>>,
+    on_volumes_click: Option<Box<dyn Fn() -> Message>>,
+    on_about_click: Option<Box<dyn Fn() -> Message>>,
+    on_cleanup_click: Option<Box<dyn Fn() -> Message>>,
+    on_prefetch_click: Option<Box<dyn Fn() -> Message>>,
+    on_remember_values_toggle: Option<Box<dyn Fn(bool) -> Message>>,
+    on_remember_passwords_toggle: Option<Box<dyn Fn(bool) -> Message>>,
+    on_never_default_latest_toggle: Option<Box<dyn Fn(bool) -> Message>>,
+    on_skip_create_review_toggle: Option<Box<dyn Fn(bool) -> Message>>,
+    on_stop_on_exit_toggle: Option<Box<dyn Fn(bool) -> Message>>,
+    on_single_instance_toggle: Option<Box<dyn Fn(bool) -> Message>>,
+    on_developer_mode_toggle: Option<Box<dyn Fn(bool) -> Message>>,
+    on_docker_host_change: Option<Box<dyn Fn(String) -> Message>>,
+    on_tls_toggle: Option<Box<dyn Fn(bool) -> Message>>,
+    on_tls_host_change: Option<Box<dyn Fn(String) -> Message>>,
+    on_choose_tls_file: Option<Box<dyn Fn(TlsFile) -> Message>>,
+    on_test_tls_click: Option<Box<dyn Fn() -> Message>>,
+    on_reconnect_click: Option<Box<dyn Fn() -> Message>>,
+    on_tray_toggle: Option<Box<dyn Fn(bool) -> Message>>,
+    on_state_file_toggle: Option<Box<dyn Fn(bool) -> Message>>,
+    on_choose_state_file: Option<Box<dyn Fn() -> Message>>,
+    on_read_only_toggle: Option<Box<dyn Fn(bool) -> Message>>,
+    on_idle_after_change: Option<Box<dyn Fn(IdleAfter) -> Message>>,
+    on_theme_change: Option<Box<dyn Fn(AppTheme) -> Message>>,
+    on_check_updates_click: Option<Box<dyn Fn() -> Message>>,
+    on_check_updates_daily_toggle: Option<Box<dyn Fn(bool) -> Message>>,
+    on_clear_remembered_click: Option<Box<dyn Fn(String) -> Message>>,
+    on_copy_path_click: Option<Box<dyn Fn(String) -> Message>>,
+    on_open_path_click: Option<Box<dyn Fn(PathBuf) -> Message>>,
+    on_open_config_click: Option<Box<dyn Fn() -> Message>>,
+    on_recreate_click: Option<Box<dyn Fn(String) -> Message>>,
+    on_forget_click: Option<Box<dyn Fn(String) -> Message>>,
+}
+
+impl<Message> Settings<Message> {
+    pub fn new(
+        validation: Option<Vec<EntryReport>>,
+        validating: bool,
+        preferences: Preferences,
+        connection_test: Option<ConnectionTest>,
+    ) -> Self {
+        Self {
+            validation,
+            validating,
+            preferences,
+            connection_test,
+            tls_test: None,
+            paths: vec![],
+            checking_updates: false,
+            deleted: vec![],
+            on_validate_click: None,
+            on_test_connection_click: None,
+            on_volumes_click: None,
+            on_about_click: None,
+            on_cleanup_click: None,
+            on_prefetch_click: None,
+            on_remember_values_toggle: None,
+            on_remember_passwords_toggle: None,
+            on_never_default_latest_toggle: None,
+            on_skip_create_review_toggle: None,
+            on_stop_on_exit_toggle: None,
+            on_single_instance_toggle: None,
+            on_developer_mode_toggle: None,
+            on_docker_host_change: None,
+            on_tls_toggle: None,
+            on_tls_host_change: None,
+            on_choose_tls_file: None,
+            on_test_tls_click: None,
+            on_reconnect_click: None,
+            on_tray_toggle: None,
+            on_state_file_toggle: None,
+            on_choose_state_file: None,
+            on_read_only_toggle: None,
+            on_idle_after_change: None,
+            on_theme_change: None,
+            on_check_updates_click: None,
+            on_check_updates_daily_toggle: None,
+            on_clear_remembered_click: None,
+            on_copy_path_click: None,
+            on_open_path_click: None,
+            on_open_config_click: None,
+            on_recreate_click: None,
+            on_forget_click: None,
+        }
+    }
+
+    /// The recorded containers that no longer exist, newest first.
+    pub fn deleted(self, deleted: Vec<CreatedDefinition>) -> Self {
+        Self { deleted, ..self }
+    }
+
+    /// The files and directories db-mgr uses, shown so they can be copied or opened.
+    pub fn paths(self, paths: Vec<AppPath>) -> Self {
+        Self { paths, ..self }
+    }
+
+    /// The result of connecting with the TLS client certificate, shown next to its button.
+    pub fn tls_test(self, tls_test: Option<TlsTest>) -> Self {
+        Self { tls_test, ..self }
+    }
+
+    /// Shows that the registries are being asked for newer images.
+    pub fn checking_updates(self, checking_updates: bool) -> Self {
+        Self {
+            checking_updates,
+            ..self
+        }
+    }
+
+    pub fn on_validate_click<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn() -> Message + 'static,
+    {
+        Self {
+            on_validate_click: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    pub fn on_test_connection_click<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn() -> Message + 'static,
+    {
+        Self {
+            on_test_connection_click: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    pub fn on_volumes_click<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn() -> Message + 'static,
+    {
+        Self {
+            on_volumes_click: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    pub fn on_about_click<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn() -> Message + 'static,
+    {
+        Self {
+            on_about_click: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    pub fn on_cleanup_click<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn() -> Message + 'static,
+    {
+        Self {
+            on_cleanup_click: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    pub fn on_prefetch_click<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn() -> Message + 'static,
+    {
+        Self {
+            on_prefetch_click: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    pub fn on_remember_values_toggle<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn(bool) -> Message + 'static,
+    {
+        Self {
+            on_remember_values_toggle: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    pub fn on_remember_passwords_toggle<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn(bool) -> Message + 'static,
+    {
+        Self {
+            on_remember_passwords_toggle: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    pub fn on_never_default_latest_toggle<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn(bool) -> Message + 'static,
+    {
+        Self {
+            on_never_default_latest_toggle: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    pub fn on_skip_create_review_toggle<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn(bool) -> Message + 'static,
+    {
+        Self {
+            on_skip_create_review_toggle: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    pub fn on_stop_on_exit_toggle<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn(bool) -> Message + 'static,
+    {
+        Self {
+            on_stop_on_exit_toggle: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    /// Called when running one instance at a time is turned on or off, it applies from the next
+    /// start.
+    pub fn on_single_instance_toggle<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn(bool) -> Message + 'static,
+    {
+        Self {
+            on_single_instance_toggle: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    /// Called when developer mode, which adds the raw inspect tab to containers, is turned on or
+    /// off.
+    pub fn on_developer_mode_toggle<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn(bool) -> Message + 'static,
+    {
+        Self {
+            on_developer_mode_toggle: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    /// Called when the docker endpoint is edited, empty to use `DOCKER_HOST` or the platform's
+    /// default.
+    pub fn on_docker_host_change<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn(String) -> Message + 'static,
+    {
+        Self {
+            on_docker_host_change: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    /// Called when connecting with the TLS client certificate is turned on or off.
+    pub fn on_tls_toggle<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn(bool) -> Message + 'static,
+    {
+        Self {
+            on_tls_toggle: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    /// Called when the `host:port` of the daemon requiring TLS is edited.
+    pub fn on_tls_host_change<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn(String) -> Message + 'static,
+    {
+        Self {
+            on_tls_host_change: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    /// Called with the file of the client certificate to pick.
+    pub fn on_choose_tls_file<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn(TlsFile) -> Message + 'static,
+    {
+        Self {
+            on_choose_tls_file: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    pub fn on_test_tls_click<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn() -> Message + 'static,
+    {
+        Self {
+            on_test_tls_click: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    /// Called to switch to the endpoint or client certificate as they are set now, without
+    /// restarting.
+    pub fn on_reconnect_click<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn() -> Message + 'static,
+    {
+        Self {
+            on_reconnect_click: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    /// Called when the tray icon is turned on or off, hidden in builds without tray support.
+    pub fn on_tray_toggle<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn(bool) -> Message + 'static,
+    {
+        Self {
+            on_tray_toggle: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    /// Called when exporting the state file is turned on or off, turning it on picks the file.
+    pub fn on_state_file_toggle<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn(bool) -> Message + 'static,
+    {
+        Self {
+            on_state_file_toggle: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    pub fn on_choose_state_file<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn() -> Message + 'static,
+    {
+        Self {
+            on_choose_state_file: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    pub fn on_read_only_toggle<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn(bool) -> Message + 'static,
+    {
+        Self {
+            on_read_only_toggle: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    pub fn on_idle_after_change<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn(IdleAfter) -> Message + 'static,
+    {
+        Self {
+            on_idle_after_change: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    pub fn on_theme_change<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn(AppTheme) -> Message + 'static,
+    {
+        Self {
+            on_theme_change: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    pub fn on_check_updates_click<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn() -> Message + 'static,
+    {
+        Self {
+            on_check_updates_click: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    pub fn on_check_updates_daily_toggle<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn(bool) -> Message + 'static,
+    {
+        Self {
+            on_check_updates_daily_toggle: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    pub fn on_clear_remembered_click<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn(String) -> Message + 'static,
+    {
+        Self {
+            on_clear_remembered_click: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    pub fn on_copy_path_click<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn(String) -> Message + 'static,
+    {
+        Self {
+            on_copy_path_click: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    pub fn on_open_path_click<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn(PathBuf) -> Message + 'static,
+    {
+        Self {
+            on_open_path_click: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    pub fn on_open_config_click<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn() -> Message + 'static,
+    {
+        Self {
+            on_open_config_click: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    /// Called with the name of a deleted container to create it again as it was.
+    pub fn on_recreate_click<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn(String) -> Message + 'static,
+    {
+        Self {
+            on_recreate_click: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    pub fn on_forget_click<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn(String) -> Message + 'static,
+    {
+        Self {
+            on_forget_click: Some(Box::new(handler)),
+            ..self
+        }
+    }
+}
+
+fn severity_color(severity: Severity) -> Color {
+    match severity {
+        Severity::Info => Color::from_rgb8(150, 150, 150),
+        Severity::Warning => Color::from_rgb8(230, 160, 40),
+        Severity::Error => Color::from_rgb8(220, 60, 60),
+    }
+}
+
+impl<Message> Settings<Message> {
+    /// The client certificate for a daemon that requires mutual TLS, and connecting again with
+    /// it or the endpoint.
+    fn tls_settings(&self) -> Element<'_, Event, Renderer> {
+        let tls = &self.preferences.docker_tls;
+        let grey = Text::Color(Color::from_rgb8(150, 150, 150));
+        let mut section = column!(checkbox(
+            "Connect with a TLS client certificate instead",
+            tls.enabled,
+            Event::TlsEnabled,
+        ))
+        .spacing(5);
+
+        if tls.enabled {
+            section = section.push(
+                text_input("docker.example.com:2376", &tls.host)
+                    .on_input(Event::TlsHost)
+                    .width(Length::Fixed(300.0)),
+            );
+            for file in TlsFile::ALL {
+                let path = match file {
+                    TlsFile::Ca => &tls.ca,
+                    TlsFile::Cert => &tls.cert,
+                    TlsFile::Key => &tls.key,
+                };
+                section = section.push(
+                    row!(
+                        text(file.to_string()).width(Length::Fixed(140.0)),
+                        text(path.as_ref().map_or("Not chosen".to_string(), |path| {
+                            path.display().to_string()
+                        }))
+                        .size(12)
+                        .style(grey),
+                        button("Choose…")
+                            .style(Button::Secondary)
+                            .on_press(Event::ChooseTlsFile(file)),
+                    )
+                    .align_items(iced::Alignment::Center)
+                    .spacing(10),
+                );
+            }
+
+            let test = button("Test TLS connection").on_press(Event::TestTls);
+            section = section.push(
+                match self.tls_test.as_ref() {
+                    Some(TlsTest::Running) => row!(badge("Connecting").style(BadgeStyles::Info)),
+                    Some(TlsTest::Connected(version)) => row!(
+                        test,
+                        text(format!("Connected to Docker {version}")).style(grey)
+                    ),
+                    Some(TlsTest::Failed(ex)) => row!(
+                        test,
+                        column!(
+                            text(&ex.title).style(Text::Color(severity_color(Severity::Error))),
+                            text(ex.body()).size(12).style(grey),
+                        )
+                        .spacing(2)
+                    ),
+                    None => row!(test),
+                }
+                .align_items(iced::Alignment::Center)
+                .spacing(15),
+            );
+        }
+
+        match self.on_reconnect_click {
+            Some(_) => section
+                .push(
+                    button("Connect again now")
+                        .style(Button::Secondary)
+                        .on_press(Event::Reconnect),
+                )
+                .into(),
+            None => section.into(),
+        }
+    }
+}
+
+impl<Message> Component<Message, Renderer> for Settings<Message> {
+    type State = ();
+
+    type Event = Event;
+
+    fn update(&mut self, _: &mut Self::State, event: Self::Event) -> Option<Message> {
+        match event {
+            Event::Validate => self.on_validate_click.as_ref().map(|fun| fun()),
+            Event::TestConnection => self.on_test_connection_click.as_ref().map(|fun| fun()),
+            Event::Volumes => self.on_volumes_click.as_ref().map(|fun| fun()),
+            Event::About => self.on_about_click.as_ref().map(|fun| fun()),
+            Event::Cleanup => self.on_cleanup_click.as_ref().map(|fun| fun()),
+            Event::Prefetch => self.on_prefetch_click.as_ref().map(|fun| fun()),
+            Event::RememberValues(remember) => self
+                .on_remember_values_toggle
+                .as_ref()
+                .map(|fun| fun(remember)),
+            Event::RememberPasswords(remember) => self
+                .on_remember_passwords_toggle
+                .as_ref()
+                .map(|fun| fun(remember)),
+            Event::NeverDefaultLatest(never) => self
+                .on_never_default_latest_toggle
+                .as_ref()
+                .map(|fun| fun(never)),
+            Event::SkipCreateReview(skip) => self
+                .on_skip_create_review_toggle
+                .as_ref()
+                .map(|fun| fun(skip)),
+            Event::StopOnExit(stop) => self.on_stop_on_exit_toggle.as_ref().map(|fun| fun(stop)),
+            Event::SingleInstance(single) => self
+                .on_single_instance_toggle
+                .as_ref()
+                .map(|fun| fun(single)),
+            Event::DeveloperMode(developer_mode) => self
+                .on_developer_mode_toggle
+                .as_ref()
+                .map(|fun| fun(developer_mode)),
+            Event::DockerHost(host) => self.on_docker_host_change.as_ref().map(|fun| fun(host)),
+            Event::TlsEnabled(enabled) => self.on_tls_toggle.as_ref().map(|fun| fun(enabled)),
+            Event::TlsHost(host) => self.on_tls_host_change.as_ref().map(|fun| fun(host)),
+            Event::ChooseTlsFile(file) => self.on_choose_tls_file.as_ref().map(|fun| fun(file)),
+            Event::TestTls => self.on_test_tls_click.as_ref().map(|fun| fun()),
+            Event::Reconnect => self.on_reconnect_click.as_ref().map(|fun| fun()),
+            Event::Tray(tray) => self.on_tray_toggle.as_ref().map(|fun| fun(tray)),
+            Event::StateFile(export) => self.on_state_file_toggle.as_ref().map(|fun| fun(export)),
+            Event::ChooseStateFile => self.on_choose_state_file.as_ref().map(|fun| fun()),
+            Event::ReadOnly(read_only) => {
+                self.on_read_only_toggle.as_ref().map(|fun| fun(read_only))
+            }
+            Event::IdleAfter(days) => self.on_idle_after_change.as_ref().map(|fun| fun(days)),
+            Event::Theme(theme) => self.on_theme_change.as_ref().map(|fun| fun(theme)),
+            Event::CheckUpdates => self.on_check_updates_click.as_ref().map(|fun| fun()),
+            Event::CheckUpdatesDaily(daily) => self
+                .on_check_updates_daily_toggle
+                .as_ref()
+                .map(|fun| fun(daily)),
+            Event::ClearRemembered(name) => {
+                self.on_clear_remembered_click.as_ref().map(|fun| fun(name))
+            }
+            Event::CopyPath(path) => self.on_copy_path_click.as_ref().map(|fun| fun(path)),
+            Event::OpenPath(path) => self.on_open_path_click.as_ref().map(|fun| fun(path)),
+            Event::OpenConfig => self.on_open_config_click.as_ref().map(|fun| fun()),
+            Event::Recreate(name) => self.on_recreate_click.as_ref().map(|fun| fun(name)),
+            Event::Forget(name) => self.on_forget_click.as_ref().map(|fun| fun(name)),
+        }
+    }
+
+    fn view(&self, _: &Self::State) -> Element<'_, Self::Event, Renderer> {
+        let mut content = column!(text("Settings").size(22))
+            .align_items(iced::Alignment::Center)
+            .spacing(15)
+            .padding(15);
+
+        if self.validating {
+            content = content.push(badge("Validating").style(BadgeStyles::Success));
+        } else {
+            content = content.push(button("Validate config").on_press(Event::Validate));
+        }
+
+        for report in self.validation.iter().flatten() {
+            let status = match report.severity() {
+                None => badge("Ok").style(BadgeStyles::Success),
+                Some(Severity::Info) => badge("Info").style(BadgeStyles::Info),
+                Some(Severity::Warning) => badge("Warning").style(BadgeStyles::Warning),
+                Some(Severity::Error) => badge("Error").style(BadgeStyles::Danger),
+            };
+
+            content = content.push(
+                row!(text(&report.name).size(20), status)
+                    .align_items(iced::Alignment::Center)
+                    .spacing(5),
+            );
+
+            for finding in report.findings.iter() {
+                content = content.push(
+                    text(format!("{}: {}", finding.severity, finding.message))
+                        .size(12)
+                        .style(Text::Color(severity_color(finding.severity))),
+                );
+            }
+        }
+
+        content = content.push(
+            match self.connection_test.as_ref() {
+                Some(ConnectionTest::Running) => {
+                    row!(badge("Testing connection").style(BadgeStyles::Info))
+                }
+                Some(ConnectionTest::Succeeded(latency)) => row!(
+                    button("Test connection").on_press(Event::TestConnection),
+                    text(format!("Connected in {}ms", latency.as_millis()))
+                        .style(Text::Color(Color::from_rgb8(150, 150, 150)))
+                ),
+                Some(ConnectionTest::Failed(ex)) => row!(
+                    button("Test connection").on_press(Event::TestConnection),
+                    text(ex)
+                        .size(12)
+                        .style(Text::Color(severity_color(Severity::Error)))
+                ),
+                None => row!(button("Test connection").on_press(Event::TestConnection)),
+            }
+            .align_items(iced::Alignment::Center)
+            .spacing(15),
+        );
+
+        content = content
+            .push(checkbox(
+                "Remember the last used values for each database",
+                self.preferences.remember_values,
+                Event::RememberValues,
+            ))
+            .push(checkbox(
+                "Include passwords in remembered values and the saved create form draft",
+                self.preferences.remember_passwords,
+                Event::RememberPasswords,
+            ))
+            .push(checkbox(
+                "Never default to the latest tag",
+                self.preferences.never_default_latest,
+                Event::NeverDefaultLatest,
+            ))
+            .push(checkbox(
+                "Create containers without reviewing the final config",
+                self.preferences.skip_create_review,
+                Event::SkipCreateReview,
+            ))
+            .push(checkbox(
+                "Stop running containers when db-mgr is closed",
+                self.preferences.stop_on_exit,
+                Event::StopOnExit,
+            ))
+            .push(checkbox(
+                "Focus this window when db-mgr is started again",
+                self.preferences.single_instance,
+                Event::SingleInstance,
+            ))
+            .push(checkbox(
+                "Developer mode, adds the raw docker inspect of containers as a tab",
+                self.preferences.developer_mode,
+                Event::DeveloperMode,
+            ))
+            .push(
+                column!(
+                    text("Docker endpoint, used from the next start or when connecting again"),
+                    text_input(
+                        if cfg!(windows) {
+                            WINDOWS_PIPE
+                        } else {
+                            "unix:///var/run/docker.sock"
+                        },
+                        self.preferences.docker_host.as_deref().unwrap_or_default(),
+                    )
+                    .on_input(Event::DockerHost),
+                    text("Empty to use DOCKER_HOST or the default, tcp:// endpoints are also accepted")
+                        .size(12)
+                        .style(Text::Color(Color::from_rgb8(150, 150, 150))),
+                )
+                .spacing(5),
+            )
+            .push(self.tls_settings())
+            .push(match self.on_tray_toggle {
+                Some(_) => Element::from(checkbox(
+                    "Show a tray icon, closing the window keeps db-mgr running in the tray",
+                    self.preferences.tray,
+                    Event::Tray,
+                )),
+                None => row!().into(),
+            })
+            .push(checkbox(
+                "Export the container state to a JSON file",
+                self.preferences.state_file.is_some(),
+                Event::StateFile,
+            ))
+            .push(match self.preferences.state_file.as_ref() {
+                Some(path) => Element::from(
+                    row!(
+                        text(path.display().to_string())
+                            .size(12)
+                            .style(Text::Color(Color::from_rgb8(150, 150, 150))),
+                        button("Change…")
+                            .style(Button::Secondary)
+                            .on_press(Event::ChooseStateFile)
+                    )
+                    .align_items(iced::Alignment::Center)
+                    .spacing(10),
+                ),
+                None => row!().into(),
+            })
+            .push(
+                row!(
+                    text("Offer to stop running containers untouched for"),
+                    pick_list(
+                        &IdleAfter::OPTIONS[..],
+                        Some(self.preferences.idle_after),
+                        Event::IdleAfter
+                    )
+                )
+                .align_items(iced::Alignment::Center)
+                .spacing(15),
+            )
+            .push(
+                row!(
+                    text("Theme"),
+                    pick_list(
+                        &AppTheme::ALL[..],
+                        Some(self.preferences.theme),
+                        Event::Theme
+                    )
+                )
+                .align_items(iced::Alignment::Center)
+                .spacing(15),
+            )
+            .push(checkbox(
+                "Check the registries for newer images once a day",
+                self.preferences.check_updates,
+                Event::CheckUpdatesDaily,
+            ))
+            .push(if self.checking_updates {
+                Element::from(badge("Checking for updates").style(BadgeStyles::Info))
+            } else {
+                button("Check for image updates")
+                    .style(Button::Secondary)
+                    .on_press(Event::CheckUpdates)
+                    .into()
+            });
+
+        let mut remembered = self.preferences.remembered.keys().collect::<Vec<_>>();
+        remembered.sort();
+        for name in remembered {
+            content = content.push(
+                row!(
+                    text(name),
+                    button("Clear remembered values")
+                        .style(Button::Secondary)
+                        .on_press(Event::ClearRemembered(name.clone()))
+                )
+                .align_items(iced::Alignment::Center)
+                .spacing(15),
+            );
+        }
+
+        if !self.deleted.is_empty() {
+            content = content.push(text("Recently deleted").size(20));
+        }
+        let now = unix_now() as i64;
+        for deleted in self.deleted.iter() {
+            let mut actions = row!().spacing(5);
+            if self.on_recreate_click.is_some() {
+                actions = actions
+                    .push(button("Recreate").on_press(Event::Recreate(deleted.name.clone())));
+            }
+            actions = actions.push(
+                button("Forget")
+                    .style(Button::Secondary)
+                    .on_press(Event::Forget(deleted.name.clone())),
+            );
+
+            content = content.push(
+                row!(
+                    column!(
+                        text(&deleted.name),
+                        text(format!(
+                            "{}:{}, created {}",
+                            deleted.definition.image,
+                            deleted.definition.tag,
+                            format_ago(now - deleted.created_at)
+                        ))
+                        .size(12)
+                        .style(Text::Color(Color::from_rgb8(150, 150, 150)))
+                    )
+                    .width(Length::Fill),
+                    actions
+                )
+                .align_items(iced::Alignment::Center)
+                .spacing(15),
+            );
+        }
+
+        if !self.paths.is_empty() {
+            content = content
+                .push(text("Files").size(20))
+                .push(button("Open config file in editor").on_press(Event::OpenConfig));
+        }
+
+        for app_path in self.paths.iter() {
+            let path = app_path.path.display().to_string();
+            content = content.push(
+                row!(
+                    column!(
+                        text(app_path.name),
+                        text(&path)
+                            .size(12)
+                            .style(Text::Color(Color::from_rgb8(150, 150, 150)))
+                    )
+                    .width(Length::Fill),
+                    button("Copy path")
+                        .style(Button::Secondary)
+                        .on_press(Event::CopyPath(path)),
+                    button("Open in file manager")
+                        .style(Button::Secondary)
+                        .on_press(Event::OpenPath(app_path.path.clone()))
+                )
+                .align_items(iced::Alignment::Center)
+                .spacing(15),
+            );
+        }
+
+        content = content
+            .push(button("Manage volumes").on_press(Event::Volumes))
+            .push(
+                button("About and diagnostics")
+                    .style(Button::Secondary)
+                    .on_press(Event::About),
+            );
+
+        content = content.push(checkbox(
+            "Start in read-only mode (takes effect after a restart)",
+            self.preferences.read_only,
+            Event::ReadOnly,
+        ));
+
+        if self.on_prefetch_click.is_some() {
+            content = content.push(
+                button("Prefetch images…")
+                    .style(Button::Secondary)
+                    .on_press(Event::Prefetch),
+            );
+        }
+
+        if self.on_cleanup_click.is_some() {
+            content = content.push(
+                button("Clean up…")
+                    .style(Button::Destructive)
+                    .on_press(Event::Cleanup),
+            );
+        }
+
+        scrollable(content).into()
+    }
+}
+
+impl<'a, Message> From<Settings<Message>> for Element<'a, Message, Renderer>
+where
+    Message: 'a,
+{
+    fn from(value: Settings<Message>) -> Self {
+        component(value)
+    }
+}
@@ -0,0 +1,113 @@
+use std::path::{Path, PathBuf};
+
+/// How many lines of output are shown once a SQL file has run.
+const SUMMARY_LINES: usize = 20;
+
+/// Whether `path` is a SQL file, judged by its extension.
+pub fn is_sql_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("sql"))
+}
+
+pub fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.display().to_string())
+}
+
+/// The container a dropped file runs in.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DropTarget {
+    Container(String),
+    /// More than one container could run it, so the user picks one.
+    Pick(Vec<String>),
+    None,
+}
+
+/// The window reports drops without a position, so the viewed container is used if it can run
+/// SQL, otherwise the only one that can. `candidates` are the ids of the running containers that
+/// can run SQL.
+pub fn drop_target(viewed: Option<&str>, candidates: &[String]) -> DropTarget {
+    if let Some(viewed) = viewed.filter(|viewed| candidates.iter().any(|id| id == viewed)) {
+        return DropTarget::Container(viewed.to_string());
+    }
+
+    match candidates {
+        [] => DropTarget::None,
+        [id] => DropTarget::Container(id.clone()),
+        ids => DropTarget::Pick(ids.to_vec()),
+    }
+}
+
+pub fn confirm_prompt(file: &Path, container: &str) -> String {
+    format!("Run {} against {container}?", file_name(file))
+}
+
+/// A SQL file running in a container.
+#[derive(Clone, Debug)]
+pub struct SqlRun {
+    /// The id of the container it runs in.
+    pub container: String,
+    /// The name of the container, for messages.
+    pub name: String,
+    pub file: PathBuf,
+    /// The restore command the file is written to, `None` when it runs as a single query.
+    pub cmd: Option<Vec<String>>,
+    /// Counts the runs so running the same file again starts a new exec.
+    pub run: u64,
+    /// Bytes of the file written so far and in total.
+    pub progress: (u64, u64),
+    output: String,
+}
+
+impl SqlRun {
+    pub fn new(
+        container: String,
+        name: String,
+        file: PathBuf,
+        cmd: Option<Vec<String>>,
+        run: u64,
+    ) -> Self {
+        Self {
+            container,
+            name,
+            file,
+            cmd,
+            run,
+            progress: (0, 0),
+            output: String::new(),
+        }
+    }
+
+    pub fn push_output(&mut self, output: &str) {
+        self.output.push_str(output);
+    }
+
+    /// How much of the file was written from 0 to 1.
+    pub fn fraction(&self) -> f32 {
+        match self.progress {
+            (_, 0) => 0.0,
+            (written, total) => written as f32 / total as f32,
+        }
+    }
+
+    /// The message shown once the file ran, with the last lines of its output.
+    pub fn summary(&self, exit_code: Option<i64>) -> String {
+        let file = file_name(&self.file);
+        let mut out = match exit_code {
+            Some(0) => format!("{file} ran against {}", self.name),
+            Some(code) => format!("{file} failed against {} with exit code {code}", self.name),
+            None => format!("{file} ran against {}, its exit code is unknown", self.name),
+        };
+
+        let lines = self.output.lines().collect::<Vec<_>>();
+        let tail = &lines[lines.len().saturating_sub(SUMMARY_LINES)..];
+        if !tail.is_empty() {
+            out.push_str("\n\n");
+            out.push_str(&tail.join("\n"));
+        }
+
+        out
+    }
+}
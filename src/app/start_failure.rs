@@ -0,0 +1,118 @@
+use iced::{
+    theme::{Button, Text},
+    widget::{button, column, component, container, row, scrollable, text, Component},
+    Color, Element, Font, Length, Renderer,
+};
+
+use crate::docker::StartFailure;
+
+#[derive(Clone)]
+pub enum Event {
+    Keep,
+    Delete,
+}
+
+pub fn start_failure_view<Message>(
+    name: String,
+    failure: StartFailure,
+) -> StartFailureView<Message> {
+    StartFailureView::new(name, failure)
+}
+
+/// Shown in place of the create form when the new container exits right after starting.
+pub struct StartFailureView<Message> {
+    name: String,
+    failure: StartFailure,
+    on_keep_click: Option<Box<dyn Fn() -> Message>>,
+    on_delete_click: Option<Box<dyn Fn() -> Message>>,
+}
+
+impl<Message> StartFailureView<Message> {
+    pub fn new(name: String, failure: StartFailure) -> Self {
+        Self {
+            name,
+            failure,
+            on_keep_click: None,
+            on_delete_click: None,
+        }
+    }
+
+    pub fn on_keep_click<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn() -> Message + 'static,
+    {
+        Self {
+            on_keep_click: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    pub fn on_delete_click<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn() -> Message + 'static,
+    {
+        Self {
+            on_delete_click: Some(Box::new(handler)),
+            ..self
+        }
+    }
+}
+
+impl<Message> Component<Message, Renderer> for StartFailureView<Message> {
+    type State = ();
+
+    type Event = Event;
+
+    fn update(&mut self, _: &mut Self::State, event: Self::Event) -> Option<Message> {
+        match event {
+            Event::Keep => self.on_keep_click.as_ref().map(|fun| fun()),
+            Event::Delete => self.on_delete_click.as_ref().map(|fun| fun()),
+        }
+    }
+
+    fn view(&self, _: &Self::State) -> Element<'_, Self::Event, Renderer> {
+        let mut logs = column!().spacing(2);
+        if self.failure.logs.is_empty() {
+            logs = logs.push(text("The container did not log anything").size(12));
+        }
+        for line in self.failure.logs.iter() {
+            logs = logs.push(text(line).size(12).font(Font::MONOSPACE));
+        }
+
+        column!(
+            text(format!(
+                "{} exited with code {} right after starting",
+                self.name.trim_start_matches("db-mgr__"),
+                self.failure.exit_code
+            ))
+            .size(22)
+            .style(Text::Color(Color::from_rgb8(220, 60, 60))),
+            text("This is usually caused by a missing or conflicting variable, its last log lines are below")
+                .size(12)
+                .style(Text::Color(Color::from_rgb8(150, 150, 150))),
+            container(scrollable(logs)).height(Length::Fill),
+            row!(
+                button("Keep container")
+                    .style(Button::Secondary)
+                    .on_press(Event::Keep),
+                button("Delete container and volumes and return to form")
+                    .style(Button::Destructive)
+                    .on_press(Event::Delete)
+            )
+            .spacing(15)
+        )
+        .align_items(iced::Alignment::Center)
+        .spacing(15)
+        .padding(15)
+        .into()
+    }
+}
+
+impl<'a, Message> From<StartFailureView<Message>> for Element<'a, Message, Renderer>
+where
+    Message: 'a,
+{
+    fn from(value: StartFailureView<Message>) -> Self {
+        component(value)
+    }
+}
@@ -0,0 +1,103 @@
+use std::time::{Duration, Instant};
+
+use crate::start_order::StartEvent;
+
+/// How far one container of an ordered start got.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StartStatus {
+    /// Waiting for the containers it depends on.
+    Queued,
+    Starting,
+    /// Started, its dependents wait until it is healthy, since then.
+    Waiting(Instant),
+    Started,
+    /// Healthy or accepting connections after this long.
+    Ready(Duration),
+    /// Started but not healthy, for this reason.
+    NotReady(String),
+    Failed(String),
+}
+
+/// The containers of an ordered start, named without the leading `/`, started while it is
+/// running. Dropping it cancels the start.
+#[derive(Clone, Debug)]
+pub struct StartRun {
+    /// Tells starts of the same containers apart, so a new one restarts the subscription.
+    pub run: u64,
+    pub containers: Vec<(String, StartStatus)>,
+    /// Why the containers were started at once rather than in the order of their dependencies.
+    pub warning: Option<String>,
+    pub running: bool,
+}
+
+impl StartRun {
+    pub fn new(run: u64, names: Vec<String>) -> Self {
+        Self {
+            run,
+            containers: names
+                .into_iter()
+                .map(|name| (name, StartStatus::Queued))
+                .collect(),
+            warning: None,
+            running: true,
+        }
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.containers
+            .iter()
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    pub fn apply(&mut self, event: StartEvent, now: Instant) {
+        let (name, status) = match event {
+            StartEvent::Done => {
+                self.running = false;
+                return;
+            }
+            StartEvent::Unordered(warning) => {
+                self.warning = Some(warning);
+                return;
+            }
+            StartEvent::Starting(name) => (name, StartStatus::Starting),
+            StartEvent::Started(name) => (name, StartStatus::Started),
+            StartEvent::Waiting(name) => (name, StartStatus::Waiting(now)),
+            StartEvent::Ready(name, after) => (name, StartStatus::Ready(after)),
+            StartEvent::NotReady(name, reason) => (name, StartStatus::NotReady(reason)),
+            StartEvent::Failed(name, reason) => (name, StartStatus::Failed(reason)),
+        };
+
+        if let Some((_, current)) = self.containers.iter_mut().find(|(known, _)| *known == name) {
+            *current = status;
+        }
+    }
+
+    /// Whether every container is up, so the run can be forgotten once it is done.
+    pub fn succeeded(&self) -> bool {
+        self.containers
+            .iter()
+            .all(|(_, status)| matches!(status, StartStatus::Started | StartStatus::Ready(_)))
+    }
+
+    /// One line per container, such as `waiting for db-mgr__pg (12s)`.
+    pub fn lines(&self, now: Instant) -> Vec<String> {
+        self.containers
+            .iter()
+            .map(|(name, status)| match status {
+                StartStatus::Queued => format!("{name} waits for its dependencies"),
+                StartStatus::Starting => format!("starting {name}"),
+                StartStatus::Waiting(since) => format!(
+                    "waiting for {name} ({}s)",
+                    now.saturating_duration_since(*since).as_secs()
+                ),
+                StartStatus::Started => format!("{name} started"),
+                StartStatus::Ready(after) => {
+                    format!("{name} healthy in {}s", after.as_secs())
+                }
+                StartStatus::NotReady(reason) => format!("{name} started, but: {reason}"),
+                StartStatus::Failed(reason) => format!("{name} failed: {reason}"),
+            })
+            .collect()
+    }
+}
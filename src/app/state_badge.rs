@@ -0,0 +1,104 @@
+use bollard::service::{ContainerStateStatusEnum, HealthStatusEnum};
+use iced::{
+    theme,
+    widget::{text, tooltip},
+    Element, Renderer,
+};
+use iced_aw::{badge, BadgeStyles};
+
+/// The short label of a container state, the same everywhere a state is shown.
+pub fn state_label(state: ContainerStateStatusEnum) -> &'static str {
+    // No catch-all arm, so a state added to bollard has to be given a label
+    match state {
+        ContainerStateStatusEnum::EMPTY => "unknown",
+        ContainerStateStatusEnum::CREATED => "created",
+        ContainerStateStatusEnum::RUNNING => "running",
+        ContainerStateStatusEnum::PAUSED => "paused",
+        ContainerStateStatusEnum::RESTARTING => "restarting",
+        ContainerStateStatusEnum::REMOVING => "removing",
+        ContainerStateStatusEnum::EXITED => "exited",
+        ContainerStateStatusEnum::DEAD => "dead",
+    }
+}
+
+/// The colour of a container state, green while running, amber while paused or on the way out,
+/// grey while stopped and red when something is wrong.
+fn state_style(state: ContainerStateStatusEnum) -> BadgeStyles {
+    match state {
+        ContainerStateStatusEnum::RUNNING => BadgeStyles::Success,
+        ContainerStateStatusEnum::PAUSED | ContainerStateStatusEnum::REMOVING => {
+            BadgeStyles::Warning
+        }
+        ContainerStateStatusEnum::CREATED => BadgeStyles::Info,
+        ContainerStateStatusEnum::EXITED | ContainerStateStatusEnum::EMPTY => {
+            BadgeStyles::Secondary
+        }
+        ContainerStateStatusEnum::DEAD | ContainerStateStatusEnum::RESTARTING => {
+            BadgeStyles::Danger
+        }
+    }
+}
+
+/// The label and colour of a container, a failing health check overrides the state.
+pub fn state_look(
+    state: ContainerStateStatusEnum,
+    health: Option<HealthStatusEnum>,
+) -> (&'static str, BadgeStyles) {
+    match health {
+        Some(HealthStatusEnum::UNHEALTHY) => ("unhealthy", BadgeStyles::Danger),
+        _ => (state_label(state), state_style(state)),
+    }
+}
+
+pub fn state_badge<'a, Message: Clone + 'a>(
+    state: ContainerStateStatusEnum,
+    health: Option<HealthStatusEnum>,
+) -> Element<'a, Message, Renderer> {
+    let (label, style) = state_look(state, health);
+    badge(text(label).size(12)).style(style).into()
+}
+
+/// [`state_badge`] with `tip`, such as who started the container, shown on hover.
+pub fn state_badge_tip<'a, Message: Clone + 'a>(
+    state: ContainerStateStatusEnum,
+    health: Option<HealthStatusEnum>,
+    tip: Option<&str>,
+) -> Element<'a, Message, Renderer> {
+    match tip {
+        Some(tip) => tooltip(state_badge(state, health), tip, tooltip::Position::Bottom)
+            .style(theme::Container::Box)
+            .into(),
+        None => state_badge(state, health),
+    }
+}
+
+/// The states in the order the legend explains them, with what each means.
+pub const LEGEND: &[(ContainerStateStatusEnum, &str)] = &[
+    (
+        ContainerStateStatusEnum::RUNNING,
+        "Up and accepting connections",
+    ),
+    (
+        ContainerStateStatusEnum::CREATED,
+        "Created but never started",
+    ),
+    (
+        ContainerStateStatusEnum::PAUSED,
+        "Frozen, its processes are suspended",
+    ),
+    (
+        ContainerStateStatusEnum::EXITED,
+        "Stopped, its data is kept",
+    ),
+    (
+        ContainerStateStatusEnum::RESTARTING,
+        "Crashed and being restarted by docker",
+    ),
+    (
+        ContainerStateStatusEnum::DEAD,
+        "Could not be stopped or removed cleanly",
+    ),
+];
+
+/// What the unhealthy badge means in the legend.
+pub const UNHEALTHY_LEGEND: &str = "Running, but its health check is failing";
@@ -0,0 +1,192 @@
+use iced::{
+    theme::{self, Text},
+    widget::{button, component, container, row, text, Component},
+    Color, Element, Length, Renderer,
+};
+use iced_aw::{Icon, ICON_FONT};
+
+use super::events::format_ago;
+use crate::{
+    platform::{configured_host, WINDOWS_PIPE},
+    tls::TlsClient,
+};
+
+#[derive(Clone)]
+pub enum Event {
+    ShowDiagnostics,
+    Refresh,
+}
+
+/// Where docker is reached, from the TLS client certificate when it is enabled, else the
+/// `docker_host` setting, `DOCKER_HOST` or the platform's default socket.
+pub fn docker_endpoint(setting: Option<&str>, tls: &TlsClient) -> String {
+    if tls.enabled {
+        return format!("https://{}", tls.address());
+    }
+    match configured_host(setting) {
+        Some(host) => host,
+        None if cfg!(windows) => WINDOWS_PIPE.to_string(),
+        None => "unix:///var/run/docker.sock".to_string(),
+    }
+}
+
+/// A docker endpoint short enough for the status bar, the socket file or pipe name for local
+/// endpoints and the host for remote ones.
+pub fn short_endpoint(endpoint: &str) -> String {
+    let (scheme, rest) = endpoint.split_once("://").unwrap_or(("", endpoint));
+    match scheme {
+        "unix" | "npipe" => rest
+            .rsplit(['/', '\\'])
+            .find(|part| !part.is_empty())
+            .unwrap_or(rest)
+            .to_string(),
+        _ => {
+            let host = rest.rsplit_once('@').map_or(rest, |(_, host)| host);
+            host.split('/').next().unwrap_or(host).to_string()
+        }
+    }
+}
+
+/// When the containers were last loaded, relative to `now`, both in unix seconds.
+pub fn refreshed_label(last_refresh: Option<i64>, now: i64) -> String {
+    match last_refresh {
+        Some(refreshed) => format!("Refreshed {}", format_ago(now - refreshed)),
+        None => "Not refreshed yet".to_string(),
+    }
+}
+
+pub struct StatusBar<Message> {
+    endpoint: String,
+    /// Whether the last refresh reached docker, unknown before the first one.
+    connected: Option<bool>,
+    running: usize,
+    total: usize,
+    refreshed: String,
+    refreshing: bool,
+    on_connection_click: Option<Box<dyn Fn() -> Message>>,
+    on_refresh_click: Option<Box<dyn Fn() -> Message>>,
+}
+
+pub fn status_bar<Message>(
+    endpoint: &str,
+    connected: Option<bool>,
+    running: usize,
+    total: usize,
+) -> StatusBar<Message> {
+    StatusBar::new(endpoint, connected, running, total)
+}
+
+impl<Message> StatusBar<Message> {
+    pub fn new(endpoint: &str, connected: Option<bool>, running: usize, total: usize) -> Self {
+        Self {
+            endpoint: short_endpoint(endpoint),
+            connected,
+            running,
+            total,
+            refreshed: refreshed_label(None, 0),
+            refreshing: false,
+            on_connection_click: None,
+            on_refresh_click: None,
+        }
+    }
+
+    /// When the containers were last loaded and now, in unix seconds.
+    pub fn last_refresh(self, last_refresh: Option<i64>, now: i64) -> Self {
+        Self {
+            refreshed: refreshed_label(last_refresh, now),
+            ..self
+        }
+    }
+
+    /// A refresh of the containers is in flight.
+    pub fn refreshing(self, refreshing: bool) -> Self {
+        Self { refreshing, ..self }
+    }
+
+    pub fn on_connection_click<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn() -> Message + 'static,
+    {
+        Self {
+            on_connection_click: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    pub fn on_refresh_click<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn() -> Message + 'static,
+    {
+        Self {
+            on_refresh_click: Some(Box::new(handler)),
+            ..self
+        }
+    }
+}
+
+impl<Message> Component<Message, Renderer> for StatusBar<Message> {
+    type State = ();
+
+    type Event = Event;
+
+    fn update(&mut self, _: &mut Self::State, event: Self::Event) -> Option<Message> {
+        match event {
+            Event::ShowDiagnostics => self.on_connection_click.as_ref().map(|fun| fun()),
+            Event::Refresh => self.on_refresh_click.as_ref().map(|fun| fun()),
+        }
+    }
+
+    fn view(&self, _: &Self::State) -> iced_aw::Element<'_, Self::Event, Renderer> {
+        let grey = Color::from_rgb8(150, 150, 150);
+        let (dot, hint) = match self.connected {
+            Some(true) => (Color::from_rgb8(60, 180, 90), ""),
+            Some(false) => (Color::from_rgb8(220, 60, 60), " (unreachable)"),
+            None => (grey, ""),
+        };
+
+        let connection = button(
+            row!(
+                text("●").size(12).style(Text::Color(dot)),
+                text(format!("{}{hint}", self.endpoint)).size(12)
+            )
+            .spacing(5),
+        )
+        .style(theme::Button::Text)
+        .padding(0)
+        .on_press(Event::ShowDiagnostics);
+
+        let mut refresh = row!(text(&self.refreshed).size(12).style(Text::Color(grey)))
+            .align_items(iced::Alignment::Center)
+            .spacing(5);
+        if self.refreshing {
+            refresh = refresh.push(text(Icon::ArrowRepeat).font(ICON_FONT).size(12));
+        }
+
+        container(
+            row!(
+                connection,
+                text(format!("{} running / {} total", self.running, self.total))
+                    .size(12)
+                    .width(Length::Fill),
+                button(refresh)
+                    .style(theme::Button::Text)
+                    .padding(0)
+                    .on_press(Event::Refresh)
+            )
+            .align_items(iced::Alignment::Center)
+            .spacing(15),
+        )
+        .width(Length::Fill)
+        .padding([3, 10])
+        .into()
+    }
+}
+
+impl<'a, Message> From<StatusBar<Message>> for Element<'a, Message, Renderer>
+where
+    Message: 'a,
+{
+    fn from(value: StatusBar<Message>) -> Self {
+        component(value)
+    }
+}
@@ -0,0 +1,97 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+/// How long docker waits after `SIGTERM` before killing a container when the config doesn't say.
+pub const DEFAULT_GRACE: Duration = Duration::from_secs(10);
+
+/// Where stopping a container got, shown on its card.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum StopState {
+    /// Not being stopped.
+    #[default]
+    Idle,
+    /// Sent `SIGTERM` this many seconds ago, force stopping is available.
+    Stopping(u64),
+    /// Still running this many seconds after `SIGTERM`, longer than its grace period, so force
+    /// stopping is suggested.
+    ForceOffered(u64),
+    /// Sent `SIGKILL`, waiting for the exit to be reported.
+    Killing,
+}
+
+impl StopState {
+    /// Whether a stop or kill was sent and the container didn't exit yet.
+    pub fn is_stopping(&self) -> bool {
+        *self != StopState::Idle
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Attempt {
+    since: Instant,
+    grace: Duration,
+    killed: bool,
+}
+
+/// The stops sent to containers that didn't exit yet, keyed by container id. Only kept for the
+/// session.
+#[derive(Debug, Default)]
+pub struct StopAttempts {
+    attempts: HashMap<String, Attempt>,
+}
+
+impl StopAttempts {
+    /// Records that `SIGTERM` was sent to `id`, keeping the time of an earlier attempt.
+    pub fn begin(&mut self, id: String, grace: Duration, now: Instant) {
+        self.attempts.entry(id).or_insert(Attempt {
+            since: now,
+            grace,
+            killed: false,
+        });
+    }
+
+    /// Records that `SIGKILL` was sent to `id`.
+    pub fn kill(&mut self, id: &str, now: Instant) {
+        self.attempts
+            .entry(id.to_string())
+            .or_insert(Attempt {
+                since: now,
+                grace: Duration::ZERO,
+                killed: false,
+            })
+            .killed = true;
+    }
+
+    /// Forgets the attempt on `id`, once it exited or the stop failed.
+    pub fn finish(&mut self, id: &str) {
+        self.attempts.remove(id);
+    }
+
+    /// Forgets the attempts on containers that aren't among the `running` ones anymore.
+    pub fn retain<'a>(&mut self, running: impl IntoIterator<Item = &'a str>) {
+        let running = running.into_iter().collect::<Vec<_>>();
+        self.attempts.retain(|id, _| running.contains(&id.as_str()));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.attempts.is_empty()
+    }
+
+    pub fn state(&self, id: &str, now: Instant) -> StopState {
+        let Some(attempt) = self.attempts.get(id) else {
+            return StopState::Idle;
+        };
+        if attempt.killed {
+            return StopState::Killing;
+        }
+
+        let elapsed = now.saturating_duration_since(attempt.since);
+        if elapsed >= attempt.grace {
+            StopState::ForceOffered(elapsed.as_secs())
+        } else {
+            StopState::Stopping(elapsed.as_secs())
+        }
+    }
+}
@@ -1,10 +1,22 @@
+use std::{net::TcpListener, path::PathBuf};
+
 use bollard::Docker;
 use futures::StreamExt;
 use iced::Subscription;
 use iced_futures::{core::Hasher, subscription::Recipe};
 
-use crate::docker::{
-    create_container as docker_create_container, CreateContainerEvent, DbContainerConfig,
+use super::tray::{Tray, TrayAction};
+use crate::{
+    dependencies::Graph,
+    docker::{
+        cleanup as docker_cleanup, container_events, create_container as docker_create_container,
+        exec_session as docker_exec_session, exec_with_input as docker_exec_with_input,
+        follow_logs as docker_follow_logs, prefetch_images, CleanupEvent, CleanupSummary,
+        ContainerEvent, CreateContainerEvent, DbContainerConfig, ExecEvent, InputExecEvent,
+        LogEvent, PrefetchEvent,
+    },
+    instance::focus_requests as instance_focus_requests,
+    start_order::{ordered_start as docker_ordered_start, StartEvent},
 };
 
 pub fn create_container(
@@ -39,3 +51,310 @@ impl Recipe for DockerSpawn {
         docker_create_container(self.docker, self.container_config).boxed()
     }
 }
+
+pub fn cleanup(docker: &'static Docker, summary: CleanupSummary) -> Subscription<CleanupEvent> {
+    Subscription::from_recipe(DockerCleanup { summary, docker })
+}
+
+struct DockerCleanup {
+    docker: &'static Docker,
+    summary: CleanupSummary,
+}
+
+impl Recipe for DockerCleanup {
+    type Output = CleanupEvent;
+
+    fn hash(&self, state: &mut Hasher) {
+        use std::hash::Hash;
+
+        std::any::TypeId::of::<Self>().hash(state);
+        for item in self
+            .summary
+            .containers
+            .iter()
+            .chain(self.summary.volumes.iter())
+            .chain(self.summary.images.iter())
+        {
+            item.id.hash(state);
+        }
+    }
+
+    fn stream(
+        self: Box<Self>,
+        _input: iced_futures::subscription::EventStream,
+    ) -> iced_futures::BoxStream<Self::Output> {
+        docker_cleanup(self.docker, self.summary).boxed()
+    }
+}
+
+/// Pulls `images`, given as `image:tag`, without creating containers.
+pub fn prefetch(docker: &'static Docker, images: Vec<String>) -> Subscription<PrefetchEvent> {
+    Subscription::from_recipe(DockerPrefetch { images, docker })
+}
+
+struct DockerPrefetch {
+    docker: &'static Docker,
+    images: Vec<String>,
+}
+
+impl Recipe for DockerPrefetch {
+    type Output = PrefetchEvent;
+
+    fn hash(&self, state: &mut Hasher) {
+        use std::hash::Hash;
+
+        std::any::TypeId::of::<Self>().hash(state);
+        self.images.hash(state);
+    }
+
+    fn stream(
+        self: Box<Self>,
+        _input: iced_futures::subscription::EventStream,
+    ) -> iced_futures::BoxStream<Self::Output> {
+        prefetch_images(self.docker, self.images).boxed()
+    }
+}
+
+/// Starts `names` in the order of their dependencies in `graph`, run `run` of them. Dropping
+/// the subscription cancels the start.
+pub fn ordered_start(
+    docker: &'static Docker,
+    graph: Graph,
+    names: Vec<String>,
+    run: u64,
+) -> Subscription<StartEvent> {
+    Subscription::from_recipe(DockerOrderedStart {
+        docker,
+        graph,
+        names,
+        run,
+    })
+}
+
+struct DockerOrderedStart {
+    docker: &'static Docker,
+    graph: Graph,
+    names: Vec<String>,
+    run: u64,
+}
+
+impl Recipe for DockerOrderedStart {
+    type Output = StartEvent;
+
+    fn hash(&self, state: &mut Hasher) {
+        use std::hash::Hash;
+
+        std::any::TypeId::of::<Self>().hash(state);
+        self.names.hash(state);
+        self.run.hash(state);
+    }
+
+    fn stream(
+        self: Box<Self>,
+        _input: iced_futures::subscription::EventStream,
+    ) -> iced_futures::BoxStream<Self::Output> {
+        docker_ordered_start(self.docker, self.graph, self.names).boxed()
+    }
+}
+
+/// The exec session `session` of the container `id`. Dropping the subscription closes it.
+pub fn exec_session(
+    docker: &'static Docker,
+    id: String,
+    cmd: Vec<String>,
+    session: u64,
+) -> Subscription<ExecEvent> {
+    Subscription::from_recipe(DockerExec {
+        docker,
+        id,
+        cmd,
+        session,
+    })
+}
+
+struct DockerExec {
+    docker: &'static Docker,
+    id: String,
+    cmd: Vec<String>,
+    session: u64,
+}
+
+impl Recipe for DockerExec {
+    type Output = ExecEvent;
+
+    fn hash(&self, state: &mut Hasher) {
+        use std::hash::Hash;
+
+        std::any::TypeId::of::<Self>().hash(state);
+        self.id.hash(state);
+        self.session.hash(state);
+    }
+
+    fn stream(
+        self: Box<Self>,
+        _input: iced_futures::subscription::EventStream,
+    ) -> iced_futures::BoxStream<Self::Output> {
+        docker_exec_session(self.docker, self.id, self.cmd).boxed()
+    }
+}
+
+/// Run `run` of the SQL file `file` written to `cmd` in the container `id`.
+pub fn exec_with_input(
+    docker: &'static Docker,
+    id: String,
+    cmd: Vec<String>,
+    file: PathBuf,
+    run: u64,
+) -> Subscription<InputExecEvent> {
+    Subscription::from_recipe(DockerInputExec {
+        docker,
+        id,
+        cmd,
+        file,
+        run,
+    })
+}
+
+struct DockerInputExec {
+    docker: &'static Docker,
+    id: String,
+    cmd: Vec<String>,
+    file: PathBuf,
+    run: u64,
+}
+
+impl Recipe for DockerInputExec {
+    type Output = InputExecEvent;
+
+    fn hash(&self, state: &mut Hasher) {
+        use std::hash::Hash;
+
+        std::any::TypeId::of::<Self>().hash(state);
+        self.id.hash(state);
+        self.run.hash(state);
+    }
+
+    fn stream(
+        self: Box<Self>,
+        _input: iced_futures::subscription::EventStream,
+    ) -> iced_futures::BoxStream<Self::Output> {
+        docker_exec_with_input(self.docker, self.id, self.cmd, self.file).boxed()
+    }
+}
+
+/// The events docker reports for db-mgr containers.
+pub fn docker_events(docker: &'static Docker) -> Subscription<ContainerEvent> {
+    Subscription::from_recipe(DockerEvents { docker })
+}
+
+struct DockerEvents {
+    docker: &'static Docker,
+}
+
+impl Recipe for DockerEvents {
+    type Output = ContainerEvent;
+
+    fn hash(&self, state: &mut Hasher) {
+        use std::hash::Hash;
+
+        std::any::TypeId::of::<Self>().hash(state);
+        // Connecting again gives another client, whose events are listened to instead
+        std::ptr::hash(self.docker, state);
+    }
+
+    fn stream(
+        self: Box<Self>,
+        _input: iced_futures::subscription::EventStream,
+    ) -> iced_futures::BoxStream<Self::Output> {
+        container_events(self.docker).boxed()
+    }
+}
+
+/// The focus requests of db-mgr instances started while this one runs.
+pub fn focus_requests(listener: &'static TcpListener) -> Subscription<()> {
+    Subscription::from_recipe(FocusRequests { listener })
+}
+
+struct FocusRequests {
+    listener: &'static TcpListener,
+}
+
+impl Recipe for FocusRequests {
+    type Output = ();
+
+    fn hash(&self, state: &mut Hasher) {
+        use std::hash::Hash;
+
+        std::any::TypeId::of::<Self>().hash(state);
+    }
+
+    fn stream(
+        self: Box<Self>,
+        _input: iced_futures::subscription::EventStream,
+    ) -> iced_futures::BoxStream<Self::Output> {
+        instance_focus_requests(self.listener).boxed()
+    }
+}
+
+/// The actions picked in the tray menu.
+pub fn tray_actions(tray: &'static Tray) -> Subscription<TrayAction> {
+    Subscription::from_recipe(TrayActions { tray })
+}
+
+struct TrayActions {
+    tray: &'static Tray,
+}
+
+impl Recipe for TrayActions {
+    type Output = TrayAction;
+
+    fn hash(&self, state: &mut Hasher) {
+        use std::hash::Hash;
+
+        std::any::TypeId::of::<Self>().hash(state);
+    }
+
+    fn stream(
+        self: Box<Self>,
+        _input: iced_futures::subscription::EventStream,
+    ) -> iced_futures::BoxStream<Self::Output> {
+        match self.tray.take_actions() {
+            Some(actions) => actions.boxed(),
+            None => futures::stream::empty().boxed(),
+        }
+    }
+}
+
+/// Follow `session` of the logs of the container `id`.
+pub fn follow_logs(docker: &'static Docker, id: String, session: u64) -> Subscription<LogEvent> {
+    Subscription::from_recipe(DockerLogs {
+        docker,
+        id,
+        session,
+    })
+}
+
+struct DockerLogs {
+    docker: &'static Docker,
+    id: String,
+    session: u64,
+}
+
+impl Recipe for DockerLogs {
+    type Output = LogEvent;
+
+    fn hash(&self, state: &mut Hasher) {
+        use std::hash::Hash;
+
+        std::any::TypeId::of::<Self>().hash(state);
+        self.id.hash(state);
+        self.session.hash(state);
+    }
+
+    fn stream(
+        self: Box<Self>,
+        _input: iced_futures::subscription::EventStream,
+    ) -> iced_futures::BoxStream<Self::Output> {
+        docker_follow_logs(self.docker, self.id).boxed()
+    }
+}
@@ -0,0 +1,154 @@
+use futures::channel::mpsc::Sender;
+
+/// How many lines of output are kept in the scrollback.
+pub const SCROLLBACK: usize = 1000;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum TerminalStatus {
+    #[default]
+    Idle,
+    Connecting,
+    Running,
+    /// Why the last session ended.
+    Ended(String),
+}
+
+/// The exec terminal of the detail view. Only one session runs at a time and it is closed when
+/// its tab is left.
+#[derive(Clone, Debug)]
+pub struct Terminal {
+    /// The id of the container the session runs in.
+    pub container: String,
+    /// The command started for each session, such as `sh` or `psql -U postgres`.
+    pub command: String,
+    pub input: String,
+    pub status: TerminalStatus,
+    /// Counts the sessions so a new one can be started after the last ended.
+    pub session: u64,
+    lines: Vec<String>,
+    /// Output after the last newline.
+    pending: String,
+    sender: Option<Sender<String>>,
+}
+
+impl Terminal {
+    pub fn new(container: String) -> Self {
+        Self {
+            container,
+            command: "sh".to_string(),
+            input: String::new(),
+            status: TerminalStatus::Idle,
+            session: 0,
+            lines: vec![],
+            pending: String::new(),
+            sender: None,
+        }
+    }
+
+    /// The scrollback, with the unfinished last line.
+    pub fn lines(&self) -> impl Iterator<Item = &String> {
+        self.lines
+            .iter()
+            .chain(Some(&self.pending).filter(|pending| !pending.is_empty()))
+    }
+
+    pub fn is_open(&self) -> bool {
+        matches!(
+            self.status,
+            TerminalStatus::Connecting | TerminalStatus::Running
+        )
+    }
+
+    /// The command split into arguments, `None` if it is empty.
+    pub fn args(&self) -> Option<Vec<String>> {
+        let args = self
+            .command
+            .split_whitespace()
+            .map(String::from)
+            .collect::<Vec<_>>();
+
+        (!args.is_empty()).then_some(args)
+    }
+
+    pub fn start(&mut self) {
+        self.session += 1;
+        self.status = TerminalStatus::Connecting;
+        self.sender = None;
+    }
+
+    pub fn started(&mut self, sender: Sender<String>) {
+        self.status = TerminalStatus::Running;
+        self.sender = Some(sender);
+    }
+
+    pub fn push_output(&mut self, output: &str) {
+        self.pending.push_str(&strip_ansi(output));
+        while let Some(index) = self.pending.find('\n') {
+            let line = self.pending[..index].to_string();
+            self.pending.drain(..=index);
+            self.lines.push(line);
+        }
+
+        if self.lines.len() > SCROLLBACK {
+            self.lines.drain(..self.lines.len() - SCROLLBACK);
+        }
+    }
+
+    /// Sends the input to the session and clears it. Errors if the session can't take input.
+    pub fn submit(&mut self) -> Result<(), String> {
+        let Some(sender) = self.sender.as_mut() else {
+            return Err("The session is not running".to_string());
+        };
+
+        sender
+            .try_send(std::mem::take(&mut self.input))
+            .map_err(|ex| format!("Could not send the input {ex}"))
+    }
+
+    /// Marks the session as ended, dropping the sender closes its connection.
+    pub fn end(&mut self, reason: String) {
+        self.status = TerminalStatus::Ended(reason);
+        self.sender = None;
+    }
+}
+
+/// Removes ANSI escape sequences, carriage returns and bells, which the line based terminal
+/// can't show. Backspaces remove the character before them.
+pub fn strip_ansi(output: &str) -> String {
+    let mut out = String::with_capacity(output.len());
+    let mut chars = output.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\u{1b}' => match chars.next() {
+                // Control sequences end with a byte from @ to ~
+                Some('[') => {
+                    for c in chars.by_ref() {
+                        if ('@'..='~').contains(&c) {
+                            break;
+                        }
+                    }
+                }
+                // Operating system commands end with a bell or ESC \
+                Some(']') => {
+                    while let Some(c) = chars.next() {
+                        if c == '\u{7}' {
+                            break;
+                        }
+                        if c == '\u{1b}' {
+                            chars.next();
+                            break;
+                        }
+                    }
+                }
+                _ => {}
+            },
+            '\u{8}' => {
+                out.pop();
+            }
+            '\r' | '\u{7}' => {}
+            c => out.push(c),
+        }
+    }
+
+    out
+}
@@ -0,0 +1,139 @@
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use iced::widget::image::Handle;
+use itertools::Itertools;
+
+use crate::{data::DatabaseConfig, http::icon_cache_file, image_ref::ImageRef};
+
+/// Icons that arrive in between are added to the cards together, so a slow connection delivering
+/// them one by one doesn't relayout the list for each.
+pub const FLUSH_INTERVAL: Duration = Duration::from_millis(250);
+
+fn content_hash(value: impl Hash) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[derive(Clone, Debug)]
+struct Thumbnail {
+    /// A hash of the bytes or path the handle was made from.
+    hash: u64,
+    handle: Handle,
+}
+
+/// The icons of the images, keyed by image, with the fetched ones waiting for the next flush.
+#[derive(Clone, Debug, Default)]
+pub struct Thumbnails {
+    loaded: HashMap<String, Thumbnail>,
+    pending: Vec<(String, Vec<u8>)>,
+}
+
+impl Thumbnails {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            loaded: HashMap::with_capacity(capacity),
+            pending: vec![],
+        }
+    }
+
+    pub fn get(&self, image: &str) -> Option<&Handle> {
+        self.loaded.get(image).map(|thumbnail| &thumbnail.handle)
+    }
+
+    /// Uses the icon cached at `path`, read only when it is drawn.
+    pub fn insert_path(&mut self, image: String, path: &Path) {
+        let hash = content_hash(path);
+        if self
+            .loaded
+            .get(&image)
+            .is_some_and(|loaded| loaded.hash == hash)
+        {
+            return;
+        }
+
+        let handle = Handle::from_path(path);
+        self.loaded.insert(image, Thumbnail { hash, handle });
+    }
+
+    /// Keeps a fetched icon until the next [`Thumbnails::flush`].
+    pub fn queue(&mut self, image: String, bytes: Vec<u8>) {
+        self.pending.push((image, bytes));
+    }
+
+    pub fn has_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// Adds the queued icons, returning how many handles changed. Icons whose bytes are the ones
+    /// already shown keep their handle, so their cards aren't rebuilt.
+    pub fn flush(&mut self) -> usize {
+        let mut changed = 0;
+        for (image, bytes) in std::mem::take(&mut self.pending) {
+            let hash = content_hash(bytes.as_slice());
+            if self
+                .loaded
+                .get(&image)
+                .is_some_and(|loaded| loaded.hash == hash)
+            {
+                continue;
+            }
+
+            let handle = Handle::from_memory(bytes);
+            self.loaded.insert(image, Thumbnail { hash, handle });
+            changed += 1;
+        }
+
+        changed
+    }
+
+    /// Forgets the icon of `image`, including one waiting to be flushed.
+    pub fn remove(&mut self, image: &str) {
+        self.loaded.remove(image);
+        self.pending.retain(|(pending, _)| pending != image);
+    }
+}
+
+/// Where the icon of an image comes from when the icons are loaded.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum IconSource {
+    /// A valid copy is cached in this file, nothing is fetched.
+    Cached(PathBuf),
+    /// Fetched from `url`, and cached in `cache_file` if there is a data directory.
+    Fetch {
+        url: String,
+        cache_file: Option<PathBuf>,
+    },
+}
+
+/// The icons of `images` that `loaded` doesn't have yet, keyed by image, and where each comes
+/// from. `valid_cache` checks a cached file, the ones it rejects are fetched again.
+pub fn icon_sources(
+    images: &[DatabaseConfig],
+    loaded: impl Fn(&str) -> bool,
+    cache_dir: Option<&Path>,
+    valid_cache: impl Fn(&Path) -> bool,
+) -> Vec<(String, IconSource)> {
+    images
+        .iter()
+        .unique_by(|item| item.name.clone())
+        .map(|item| (ImageRef::parse(&item.image).key(), item))
+        .filter(|(image, _)| !loaded(image))
+        .map(|(image, item)| {
+            let cache_file = cache_dir.map(|dir| icon_cache_file(dir, &item.icon_url));
+            let source = match cache_file {
+                Some(file) if file.is_file() && valid_cache(&file) => IconSource::Cached(file),
+                cache_file => IconSource::Fetch {
+                    url: item.icon_url.clone(),
+                    cache_file,
+                },
+            };
+            (image, source)
+        })
+        .collect()
+}
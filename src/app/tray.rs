@@ -0,0 +1,295 @@
+use std::{
+    collections::HashSet,
+    sync::{mpsc, Mutex},
+};
+
+use bollard::service::ContainerStateStatusEnum;
+use futures::channel::mpsc::UnboundedReceiver;
+
+use super::state_badge::state_label;
+use crate::docker::DbContainer;
+
+/// Whether this build can show a tray icon, only Linux builds with the `tray` feature can.
+pub const SUPPORTED: bool = cfg!(all(feature = "tray", target_os = "linux"));
+
+/// What picking an item of the tray menu does.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TrayAction {
+    /// Starts the container with this id.
+    Start(String),
+    /// Stops the container with this id.
+    Stop(String),
+    ShowWindow,
+    Quit,
+}
+
+impl TrayAction {
+    /// The id of the menu item, which is all a menu event carries.
+    pub fn menu_id(&self) -> String {
+        match self {
+            TrayAction::Start(id) => format!("start:{id}"),
+            TrayAction::Stop(id) => format!("stop:{id}"),
+            TrayAction::ShowWindow => "show".to_string(),
+            TrayAction::Quit => "quit".to_string(),
+        }
+    }
+
+    pub fn parse(menu_id: &str) -> Option<Self> {
+        match menu_id.split_once(':') {
+            Some(("start", id)) => Some(TrayAction::Start(id.to_string())),
+            Some(("stop", id)) => Some(TrayAction::Stop(id.to_string())),
+            Some(_) => None,
+            None => match menu_id {
+                "show" => Some(TrayAction::ShowWindow),
+                "quit" => Some(TrayAction::Quit),
+                _ => None,
+            },
+        }
+    }
+}
+
+/// A favourite container in the tray menu, its item is disabled without an action.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TrayEntry {
+    pub label: String,
+    pub action: Option<TrayAction>,
+}
+
+/// The menu entries of the favourite containers, by name. Containers can't be started or stopped
+/// from the tray in read-only mode or while they change state.
+pub fn tray_entries(
+    containers: &[DbContainer],
+    favourites: &HashSet<String>,
+    read_only: bool,
+) -> Vec<TrayEntry> {
+    let mut favourite = containers
+        .iter()
+        .map(|container| (container.name.trim_start_matches('/'), container))
+        .filter(|(name, _)| favourites.contains(*name))
+        .collect::<Vec<_>>();
+    favourite.sort_by_key(|(name, _)| *name);
+
+    favourite
+        .into_iter()
+        .map(|(name, container)| {
+            let id = container.id.clone();
+            let action = match container.state {
+                ContainerStateStatusEnum::RUNNING => Some(TrayAction::Stop(id)),
+                ContainerStateStatusEnum::CREATED | ContainerStateStatusEnum::EXITED => {
+                    Some(TrayAction::Start(id))
+                }
+                _ => None,
+            }
+            .filter(|_| !read_only);
+
+            let state = state_label(container.state);
+            let label = match action {
+                Some(TrayAction::Stop(_)) => format!("Stop {name} ({state})"),
+                Some(TrayAction::Start(_)) => format!("Start {name} ({state})"),
+                _ => format!("{name} ({state})"),
+            };
+            TrayEntry { label, action }
+        })
+        .collect()
+}
+
+/// What the app tells the thread that owns the tray icon.
+#[derive(Clone, Debug)]
+pub enum TrayUpdate {
+    Entries(Vec<TrayEntry>),
+    Visible(bool),
+}
+
+/// The tray icon, which lives on its own thread running the event loop of the desktop's tray.
+pub struct Tray {
+    updates: mpsc::Sender<TrayUpdate>,
+    /// Taken by the first subscription to the menu actions.
+    actions: Mutex<Option<UnboundedReceiver<TrayAction>>>,
+}
+
+impl Tray {
+    pub fn update(&self, update: TrayUpdate) {
+        // The thread only stops when the tray couldn't be kept up
+        let _ = self.updates.send(update);
+    }
+
+    pub fn take_actions(&self) -> Option<UnboundedReceiver<TrayAction>> {
+        self.actions.lock().ok()?.take()
+    }
+}
+
+#[cfg(not(all(feature = "tray", target_os = "linux")))]
+impl Tray {
+    pub fn spawn() -> Result<Self, String> {
+        Err("db-mgr was built without tray support".to_string())
+    }
+}
+
+#[cfg(all(feature = "tray", target_os = "linux"))]
+impl Tray {
+    /// Shows the tray icon, failing on desktops without a tray.
+    pub fn spawn() -> Result<Self, String> {
+        use tray_icon::menu::MenuEvent;
+
+        let icon = platform::icon()?;
+        let (updates, update_receiver) = mpsc::channel();
+        let (action_sender, actions) = futures::channel::mpsc::unbounded();
+        let (ready_sender, ready) = mpsc::channel();
+
+        MenuEvent::set_event_handler(Some(move |event: MenuEvent| {
+            if let Some(action) = TrayAction::parse(&event.id.0) {
+                let _ = action_sender.unbounded_send(action);
+            }
+        }));
+        std::thread::spawn(move || platform::run(icon, update_receiver, ready_sender));
+
+        match ready.recv() {
+            Ok(Ok(())) => Ok(Self {
+                updates,
+                actions: Mutex::new(Some(actions)),
+            }),
+            Ok(Err(ex)) => Err(ex),
+            Err(_) => Err("the tray thread stopped".to_string()),
+        }
+    }
+}
+
+#[cfg(all(feature = "tray", target_os = "linux"))]
+mod platform {
+    use std::{sync::mpsc, time::Duration};
+
+    use gtk::{
+        gio::{self, BusType, Cancellable, DBusCallFlags},
+        glib::{self, ControlFlow, ToVariant, VariantTy},
+    };
+    use tray_icon::{
+        menu::{Menu, MenuItem, PredefinedMenuItem},
+        Icon, TrayIcon, TrayIconBuilder,
+    };
+
+    use super::{TrayAction, TrayEntry, TrayUpdate};
+
+    /// How often the gtk thread looks for updates from the app.
+    const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+    const ICON_SIZE: u32 = 32;
+
+    pub fn icon() -> Result<Icon, String> {
+        let image = image::load_from_memory(include_bytes!("../../default_image.png"))
+            .map_err(|ex| format!("Could not decode the tray icon: {ex}"))?
+            .resize(ICON_SIZE, ICON_SIZE, image::imageops::FilterType::Triangle)
+            .into_rgba8();
+        let (width, height) = image.dimensions();
+        Icon::from_rgba(image.into_raw(), width, height)
+            .map_err(|ex| format!("Could not use the tray icon: {ex}"))
+    }
+
+    fn menu(entries: &[TrayEntry]) -> Menu {
+        let menu = Menu::new();
+        let mut items = entries
+            .iter()
+            .map(|entry| {
+                let id = entry
+                    .action
+                    .as_ref()
+                    .map(TrayAction::menu_id)
+                    .unwrap_or_else(|| format!("entry:{}", entry.label));
+                MenuItem::with_id(id, &entry.label, entry.action.is_some(), None)
+            })
+            .collect::<Vec<_>>();
+        if items.is_empty() {
+            items.push(MenuItem::with_id(
+                "entry:",
+                "Favourite containers in their details",
+                false,
+                None,
+            ));
+        }
+        for item in items.iter() {
+            let _ = menu.append(item);
+        }
+
+        let _ = menu.append_items(&[
+            &PredefinedMenuItem::separator(),
+            &MenuItem::with_id(TrayAction::ShowWindow.menu_id(), "Show window", true, None),
+            &MenuItem::with_id(TrayAction::Quit.menu_id(), "Quit", true, None),
+        ]);
+        menu
+    }
+
+    fn apply(tray: &TrayIcon, update: TrayUpdate) {
+        match update {
+            TrayUpdate::Entries(entries) => tray.set_menu(Some(Box::new(menu(&entries)))),
+            TrayUpdate::Visible(visible) => {
+                if let Err(ex) = tray.set_visible(visible) {
+                    eprintln!("Could not change the tray icon: {ex}");
+                }
+            }
+        }
+    }
+
+    /// Whether something on the session bus shows tray icons. Without one the icon would be built
+    /// but never shown, and closing the window would hide db-mgr where it can't be found.
+    fn has_tray() -> Result<bool, glib::Error> {
+        let reply = gio::bus_get_sync(BusType::Session, None::<&Cancellable>)?.call_sync(
+            Some("org.freedesktop.DBus"),
+            "/org/freedesktop/DBus",
+            "org.freedesktop.DBus",
+            "NameHasOwner",
+            Some(&("org.kde.StatusNotifierWatcher",).to_variant()),
+            Some(VariantTy::new("(b)").expect("A valid variant type")),
+            DBusCallFlags::NONE,
+            1000,
+            None::<&Cancellable>,
+        )?;
+        Ok(reply.get::<(bool,)>().is_some_and(|(owned,)| owned))
+    }
+
+    /// Runs the gtk event loop the tray icon needs, telling `ready` whether the icon was shown.
+    pub fn run(
+        icon: Icon,
+        updates: mpsc::Receiver<TrayUpdate>,
+        ready: mpsc::Sender<Result<(), String>>,
+    ) {
+        if let Err(ex) = gtk::init() {
+            let _ = ready.send(Err(format!("Could not start gtk for the tray: {ex}")));
+            return;
+        }
+        match has_tray() {
+            Ok(true) => {}
+            Ok(false) => {
+                let _ = ready.send(Err("the desktop has no tray".to_string()));
+                return;
+            }
+            Err(ex) => {
+                let _ = ready.send(Err(format!("Could not look for a tray: {ex}")));
+                return;
+            }
+        }
+        let tray = TrayIconBuilder::new()
+            .with_tooltip("db-mgr")
+            .with_icon(icon)
+            .with_menu(Box::new(menu(&[])))
+            .build();
+        let tray = match tray {
+            Ok(tray) => tray,
+            Err(ex) => {
+                let _ = ready.send(Err(format!("Could not show the tray icon: {ex}")));
+                return;
+            }
+        };
+        let _ = ready.send(Ok(()));
+
+        glib::timeout_add_local(POLL_INTERVAL, move || loop {
+            match updates.try_recv() {
+                Ok(update) => apply(&tray, update),
+                Err(mpsc::TryRecvError::Empty) => return ControlFlow::Continue,
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    gtk::main_quit();
+                    return ControlFlow::Break;
+                }
+            }
+        });
+        gtk::main();
+    }
+}
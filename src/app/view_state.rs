@@ -0,0 +1,33 @@
+use crate::docker::DbContainer;
+
+#[derive(Debug)]
+pub enum MainViewState {
+    /// The first start, see [`WelcomeFlow`](super::welcome::WelcomeFlow).
+    Welcome,
+    CreateContainer,
+    QuickStart,
+    Credentials,
+    /// The new container exited right after starting.
+    StartFailed,
+    /// The container with this id.
+    ViewContainer(String),
+    Settings,
+    About,
+    Cleanup,
+    Prefetch,
+    Volumes(bool),
+    None,
+}
+
+impl MainViewState {
+    /// The view to keep once the container list was refreshed, only reset if the viewed container
+    /// is gone.
+    pub fn after_refresh(self, containers: &[DbContainer]) -> Self {
+        match self {
+            MainViewState::ViewContainer(id) if !containers.iter().any(|c| c.id == id) => {
+                MainViewState::None
+            }
+            view => view,
+        }
+    }
+}
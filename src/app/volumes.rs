@@ -0,0 +1,138 @@
+use iced::{
+    theme::{Button, Text},
+    widget::{button, checkbox, column, component, row, scrollable, text, Component},
+    Color, Element, Length, Renderer,
+};
+
+use super::cleanup::format_size;
+use crate::docker::DbVolume;
+
+#[derive(Clone)]
+pub enum Event {
+    OrphansOnly(bool),
+    Remove(String),
+}
+
+pub fn volumes_view<Message>(
+    volumes: Vec<DbVolume>,
+    orphans: Vec<DbVolume>,
+    orphans_only: bool,
+) -> VolumesView<Message> {
+    VolumesView::new(volumes, orphans, orphans_only)
+}
+
+pub struct VolumesView<Message> {
+    volumes: Vec<DbVolume>,
+    orphans: Vec<DbVolume>,
+    orphans_only: bool,
+    on_filter_toggle: Option<Box<dyn Fn(bool) -> Message>>,
+    on_remove_click: Option<Box<dyn Fn(String) -> Message>>,
+}
+
+impl<Message> VolumesView<Message> {
+    pub fn new(volumes: Vec<DbVolume>, orphans: Vec<DbVolume>, orphans_only: bool) -> Self {
+        Self {
+            volumes,
+            orphans,
+            orphans_only,
+            on_filter_toggle: None,
+            on_remove_click: None,
+        }
+    }
+
+    pub fn on_filter_toggle<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn(bool) -> Message + 'static,
+    {
+        Self {
+            on_filter_toggle: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    pub fn on_remove_click<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn(String) -> Message + 'static,
+    {
+        Self {
+            on_remove_click: Some(Box::new(handler)),
+            ..self
+        }
+    }
+}
+
+impl<Message> Component<Message, Renderer> for VolumesView<Message> {
+    type State = ();
+
+    type Event = Event;
+
+    fn update(&mut self, _: &mut Self::State, event: Self::Event) -> Option<Message> {
+        match event {
+            Event::OrphansOnly(orphans_only) => {
+                self.on_filter_toggle.as_ref().map(|fun| fun(orphans_only))
+            }
+            Event::Remove(name) => self.on_remove_click.as_ref().map(|fun| fun(name)),
+        }
+    }
+
+    fn view(&self, _: &Self::State) -> Element<'_, Self::Event, Renderer> {
+        let mut content = column!(
+            text("Volumes").size(22),
+            checkbox(
+                "Only show orphaned volumes",
+                self.orphans_only,
+                Event::OrphansOnly
+            )
+        )
+        .spacing(15)
+        .padding(15);
+
+        let volumes = if self.orphans_only {
+            &self.orphans
+        } else {
+            &self.volumes
+        };
+
+        if volumes.is_empty() {
+            content = content.push(text("There are no volumes to show"));
+        }
+
+        for volume in volumes {
+            let orphaned = self.orphans.contains(volume);
+            let mut details = column!(text(&volume.name));
+            let mut detail = volume.size.map(format_size).unwrap_or_default();
+            if orphaned {
+                detail = format!("{detail} not used by any container");
+            }
+            details = details.push(
+                text(detail.trim())
+                    .size(12)
+                    .style(Text::Color(Color::from_rgb8(150, 150, 150))),
+            );
+
+            let mut row = row!(details.width(Length::Fill))
+                .align_items(iced::Alignment::Center)
+                .spacing(15);
+            if self.on_remove_click.is_some() {
+                let mut remove = button("Remove").style(Button::Destructive);
+                if orphaned {
+                    remove = remove.on_press(Event::Remove(volume.name.clone()));
+                }
+                row = row.push(remove);
+            }
+
+            content = content.push(row);
+        }
+
+        scrollable(content).into()
+    }
+}
+
+impl<'a, Message> From<VolumesView<Message>> for Element<'a, Message, Renderer>
+where
+    Message: 'a,
+{
+    fn from(value: VolumesView<Message>) -> Self {
+        component(value)
+    }
+}
@@ -0,0 +1,379 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use iced::{
+    theme::{Button, Text},
+    widget::{button, checkbox, column, component, pick_list, row, scrollable, text, Component},
+    Color, Element, Length, Renderer,
+};
+
+use super::avatar::avatar;
+use crate::{
+    data::{AppTheme, DatabaseConfig},
+    docker::{EngineInfo, LABEL},
+    errors::explain_message,
+};
+
+#[derive(Clone)]
+pub enum Event {
+    Next,
+    Back,
+    Skip,
+    RetryDocker,
+    Theme(AppTheme),
+    ShowDatabase(String, bool),
+}
+
+/// The steps of the welcome flow shown on the first start, in order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WelcomeStep {
+    /// Checks that docker can be reached.
+    Docker,
+    /// Explains that only containers db-mgr created are listed.
+    Model,
+    Theme,
+    /// Chooses which databases of the config file the picker offers.
+    Databases,
+}
+
+impl WelcomeStep {
+    const ORDER: [WelcomeStep; 4] = [
+        WelcomeStep::Docker,
+        WelcomeStep::Model,
+        WelcomeStep::Theme,
+        WelcomeStep::Databases,
+    ];
+
+    fn index(self) -> usize {
+        Self::ORDER
+            .iter()
+            .position(|step| *step == self)
+            .unwrap_or_default()
+    }
+
+    /// The step after this one, `None` on the last step.
+    pub fn next(self) -> Option<Self> {
+        Self::ORDER.get(self.index() + 1).copied()
+    }
+
+    /// The step before this one, `None` on the first step.
+    pub fn previous(self) -> Option<Self> {
+        self.index().checked_sub(1).map(|index| Self::ORDER[index])
+    }
+
+    fn title(self) -> &'static str {
+        match self {
+            WelcomeStep::Docker => "Connecting to Docker",
+            WelcomeStep::Model => "Which containers db-mgr shows",
+            WelcomeStep::Theme => "Theme",
+            WelcomeStep::Databases => "Databases to offer",
+        }
+    }
+}
+
+/// Where the welcome flow is and what was chosen so far, owned by the app.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WelcomeFlow {
+    pub step: WelcomeStep,
+    pub theme: AppTheme,
+    /// The names of the config entries, in config order.
+    pub databases: Vec<String>,
+    /// The image of each config entry, whose avatar is drawn next to its name.
+    pub images: BTreeMap<String, String>,
+    /// The names of the config entries the picker keeps offering.
+    pub shown: BTreeSet<String>,
+}
+
+impl WelcomeFlow {
+    /// Starts at the first step with every database shown.
+    pub fn new(databases: &[DatabaseConfig], theme: AppTheme) -> Self {
+        let images = databases
+            .iter()
+            .map(|database| (database.name.clone(), database.image.clone()))
+            .collect();
+        let databases = databases
+            .iter()
+            .map(|database| database.name.clone())
+            .collect::<Vec<_>>();
+        Self {
+            step: WelcomeStep::Docker,
+            theme,
+            shown: databases.iter().cloned().collect(),
+            databases,
+            images,
+        }
+    }
+
+    /// Moves to the next step, returning false on the last step, where the flow is finished.
+    pub fn advance(&mut self) -> bool {
+        match self.step.next() {
+            Some(step) => {
+                self.step = step;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn back(&mut self) {
+        if let Some(step) = self.step.previous() {
+            self.step = step;
+        }
+    }
+
+    pub fn show_database(&mut self, name: String, shown: bool) {
+        if shown {
+            self.shown.insert(name);
+        } else {
+            self.shown.remove(&name);
+        }
+    }
+
+    /// The databases to remove from the config file, in config order.
+    pub fn hidden(&self) -> Vec<String> {
+        self.databases
+            .iter()
+            .filter(|name| !self.shown.contains(*name))
+            .cloned()
+            .collect()
+    }
+}
+
+pub fn welcome<Message>(flow: WelcomeFlow, engine: Option<EngineInfo>) -> Welcome<Message> {
+    Welcome::new(flow, engine)
+}
+
+pub struct Welcome<Message> {
+    flow: WelcomeFlow,
+    engine: Option<EngineInfo>,
+    on_change: Option<Box<dyn Fn(WelcomeFlow) -> Message>>,
+    on_finish: Option<Box<dyn Fn(WelcomeFlow) -> Message>>,
+    on_skip: Option<Box<dyn Fn() -> Message>>,
+    on_retry_click: Option<Box<dyn Fn() -> Message>>,
+}
+
+impl<Message> Welcome<Message> {
+    pub fn new(flow: WelcomeFlow, engine: Option<EngineInfo>) -> Self {
+        Self {
+            flow,
+            engine,
+            on_change: None,
+            on_finish: None,
+            on_skip: None,
+            on_retry_click: None,
+        }
+    }
+
+    pub fn on_change<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn(WelcomeFlow) -> Message + 'static,
+    {
+        Self {
+            on_change: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    /// Called with the choices once the last step is confirmed.
+    pub fn on_finish<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn(WelcomeFlow) -> Message + 'static,
+    {
+        Self {
+            on_finish: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    /// Called when the flow is left without applying any of its choices.
+    pub fn on_skip<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn() -> Message + 'static,
+    {
+        Self {
+            on_skip: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    pub fn on_retry_click<Callback>(self, handler: Callback) -> Self
+    where
+        Callback: Fn() -> Message + 'static,
+    {
+        Self {
+            on_retry_click: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    fn docker_step(&self) -> Element<'_, Event, Renderer> {
+        let Some(engine) = self.engine.as_ref() else {
+            return text("Checking the connection to Docker…").into();
+        };
+
+        match engine.version.as_ref() {
+            Some(version) => text(format!(
+                "Connected to Docker {version}, API version {}.",
+                engine.api_version.as_deref().unwrap_or("unknown")
+            ))
+            .into(),
+            None => {
+                let problem = engine
+                    .errors
+                    .first()
+                    .map(String::as_str)
+                    .unwrap_or("no version was reported");
+                let explained = explain_message("Could not reach Docker", problem);
+                column!(
+                    text(&explained.title).size(18),
+                    text(explained.body()),
+                    button("Check again")
+                        .style(Button::Secondary)
+                        .on_press(Event::RetryDocker)
+                )
+                .spacing(10)
+                .into()
+            }
+        }
+    }
+
+    fn model_step(&self) -> Element<'_, Event, Renderer> {
+        column!(
+            text(format!(
+                "db-mgr only lists the containers it created, which carry the `{LABEL}` label. \
+                 Containers started with `docker run` or compose are left alone and don't show \
+                 up, so an existing database won't appear until it is created again from db-mgr."
+            )),
+            text(
+                "Removing a container keeps its volumes, so its data is still there when it is \
+                 created again with the same volumes."
+            )
+            .size(14)
+            .style(Text::Color(Color::from_rgb8(150, 150, 150))),
+        )
+        .spacing(10)
+        .into()
+    }
+
+    fn theme_step(&self) -> Element<'_, Event, Renderer> {
+        row!(
+            text("Theme"),
+            pick_list(&AppTheme::ALL[..], Some(self.flow.theme), Event::Theme)
+        )
+        .align_items(iced::Alignment::Center)
+        .spacing(10)
+        .into()
+    }
+
+    fn databases_step(&self) -> Element<'_, Event, Renderer> {
+        let databases = self.flow.databases.iter().map(|name| {
+            let image = self.flow.images.get(name).map_or("", String::as_str);
+            let name = name.clone();
+            row!(
+                avatar(image, 20.0),
+                checkbox(
+                    name.clone(),
+                    self.flow.shown.contains(&name),
+                    move |shown| Event::ShowDatabase(name.clone(), shown),
+                )
+            )
+            .align_items(iced::Alignment::Center)
+            .spacing(8)
+            .into()
+        });
+
+        column!(
+            text(
+                "Unticked databases are removed from config.yaml, the original is kept as \
+                 config.yaml.bak."
+            )
+            .size(14)
+            .style(Text::Color(Color::from_rgb8(150, 150, 150))),
+            scrollable(column(databases.collect()).spacing(8)).height(Length::Fill),
+        )
+        .spacing(10)
+        .into()
+    }
+}
+
+impl<Message> Component<Message, Renderer> for Welcome<Message> {
+    type State = ();
+
+    type Event = Event;
+
+    fn update(&mut self, _: &mut Self::State, event: Self::Event) -> Option<Message> {
+        match event {
+            Event::Next => {
+                if !self.flow.advance() {
+                    return self.on_finish.as_ref().map(|fun| fun(self.flow.clone()));
+                }
+            }
+            Event::Back => self.flow.back(),
+            Event::Skip => return self.on_skip.as_ref().map(|fun| fun()),
+            Event::RetryDocker => return self.on_retry_click.as_ref().map(|fun| fun()),
+            Event::Theme(theme) => self.flow.theme = theme,
+            Event::ShowDatabase(name, shown) => self.flow.show_database(name, shown),
+        }
+
+        self.on_change.as_ref().map(|fun| fun(self.flow.clone()))
+    }
+
+    fn view(&self, _: &Self::State) -> Element<'_, Self::Event, Renderer> {
+        let step = self.flow.step;
+        let body = match step {
+            WelcomeStep::Docker => self.docker_step(),
+            WelcomeStep::Model => self.model_step(),
+            WelcomeStep::Theme => self.theme_step(),
+            WelcomeStep::Databases => self.databases_step(),
+        };
+
+        let mut buttons = row!().spacing(10);
+        if step.previous().is_some() {
+            buttons = buttons.push(
+                button("Back")
+                    .style(Button::Secondary)
+                    .on_press(Event::Back),
+            );
+        }
+        buttons = buttons
+            .push(
+                button("Skip")
+                    .style(Button::Secondary)
+                    .on_press(Event::Skip),
+            )
+            .push(
+                button(if step.next().is_some() {
+                    "Next"
+                } else {
+                    "Finish"
+                })
+                .on_press(Event::Next),
+            );
+
+        column!(
+            text("Welcome to db-mgr").size(26),
+            text(format!(
+                "Step {} of {}: {}",
+                step.index() + 1,
+                WelcomeStep::ORDER.len(),
+                step.title()
+            ))
+            .size(14)
+            .style(Text::Color(Color::from_rgb8(150, 150, 150))),
+            body,
+            buttons,
+        )
+        .spacing(15)
+        .padding(15)
+        .max_width(640)
+        .into()
+    }
+}
+
+impl<'a, Message> From<Welcome<Message>> for Element<'a, Message, Renderer>
+where
+    Message: 'a,
+{
+    fn from(value: Welcome<Message>) -> Self {
+        component(value)
+    }
+}
@@ -0,0 +1,238 @@
+use std::{fmt::Display, path::PathBuf};
+
+use bollard::Docker;
+use chrono::{DateTime, Datelike, Duration, Local, TimeZone, Weekday};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+use crate::docker::exec_to_file;
+
+/// Due runs noticed later than this were missed while db-mgr was closed.
+const GRACE_MINUTES: i64 = 15;
+
+/// How many results are kept for each schedule.
+const HISTORY_LEN: usize = 20;
+
+const FILE_TIME_FORMAT: &str = "%Y%m%d-%H%M%S";
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Frequency {
+    #[default]
+    Daily,
+    Weekly(Weekday),
+}
+
+impl Frequency {
+    pub fn options() -> Vec<Frequency> {
+        [Frequency::Daily]
+            .into_iter()
+            .chain(
+                [
+                    Weekday::Mon,
+                    Weekday::Tue,
+                    Weekday::Wed,
+                    Weekday::Thu,
+                    Weekday::Fri,
+                    Weekday::Sat,
+                    Weekday::Sun,
+                ]
+                .map(Frequency::Weekly),
+            )
+            .collect()
+    }
+}
+
+impl Display for Frequency {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Frequency::Daily => write!(f, "Every day"),
+            Frequency::Weekly(weekday) => write!(f, "Every {weekday}"),
+        }
+    }
+}
+
+/// A local hour of the day, from 0 to 23.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Hour(pub u32);
+
+impl Hour {
+    pub fn options() -> Vec<Hour> {
+        (0..24).map(Hour).collect()
+    }
+}
+
+impl Display for Hour {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:02}:00", self.0)
+    }
+}
+
+/// The choices for how many backups are kept.
+pub const KEEP_OPTIONS: [usize; 5] = [1, 3, 7, 14, 30];
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum BackupOutcome {
+    Saved(PathBuf),
+    Failed(String),
+    Skipped(String),
+}
+
+impl Display for BackupOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BackupOutcome::Saved(path) => write!(f, "Saved {}", path.display()),
+            BackupOutcome::Failed(ex) => write!(f, "Failed: {ex}"),
+            BackupOutcome::Skipped(reason) => write!(f, "Skipped, {reason}"),
+        }
+    }
+}
+
+/// The result of a scheduled backup due at `at`, in seconds since the unix epoch.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct BackupRecord {
+    pub at: i64,
+    pub outcome: BackupOutcome,
+}
+
+/// When a container is backed up, where to and what came of the previous runs.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct BackupSchedule {
+    #[serde(default)]
+    pub frequency: Frequency,
+    pub hour: u32,
+    pub directory: PathBuf,
+    /// How many backups are kept in `directory`, older ones are deleted.
+    pub keep: usize,
+    /// Runs due up to this time, in seconds since the unix epoch, were run or skipped.
+    pub checked: i64,
+    /// The latest results, oldest first.
+    #[serde(default)]
+    pub history: Vec<BackupRecord>,
+}
+
+impl BackupSchedule {
+    /// Daily backups at 2 in the night, keeping a week of them.
+    pub fn new(directory: PathBuf, now: i64) -> Self {
+        Self {
+            frequency: Frequency::Daily,
+            hour: 2,
+            directory,
+            keep: 7,
+            checked: now,
+            history: vec![],
+        }
+    }
+
+    /// The first run after `after`, `None` only if the hour never exists on the following days.
+    pub fn next_run<Tz: TimeZone>(&self, after: &DateTime<Tz>) -> Option<DateTime<Tz>> {
+        let today = after.date_naive();
+        (0..15)
+            .filter_map(|days| today.checked_add_signed(Duration::days(days)))
+            .filter(|date| match self.frequency {
+                Frequency::Daily => true,
+                Frequency::Weekly(weekday) => date.weekday() == weekday,
+            })
+            .filter_map(|date| {
+                let time = date.and_hms_opt(self.hour, 0, 0)?;
+                after.timezone().from_local_datetime(&time).earliest()
+            })
+            .find(|run| run > after)
+    }
+
+    /// The runs due since the last check up to `now`, oldest first.
+    pub fn due_runs<Tz: TimeZone>(&self, now: &DateTime<Tz>) -> Vec<DateTime<Tz>> {
+        let Some(checked) = now.timezone().timestamp_opt(self.checked, 0).single() else {
+            return vec![];
+        };
+
+        let mut runs = vec![];
+        let mut after = checked;
+        while let Some(run) = self.next_run(&after).filter(|run| run <= now) {
+            after = run.clone();
+            runs.push(run);
+        }
+
+        runs
+    }
+
+    pub fn record(&mut self, at: i64, outcome: BackupOutcome) {
+        self.history.push(BackupRecord { at, outcome });
+        let excess = self.history.len().saturating_sub(HISTORY_LEN);
+        self.history.drain(..excess);
+    }
+}
+
+/// Splits due `runs` into the ones missed while db-mgr was closed and the latest one if it is
+/// recent enough to run now.
+pub fn split_missed<Tz: TimeZone>(
+    mut runs: Vec<DateTime<Tz>>,
+    now: &DateTime<Tz>,
+) -> (Vec<DateTime<Tz>>, Option<DateTime<Tz>>) {
+    let grace = Duration::minutes(GRACE_MINUTES);
+    match runs.pop() {
+        Some(run) if now.clone() - run.clone() <= grace => (runs, Some(run)),
+        Some(run) => {
+            runs.push(run);
+            (runs, None)
+        }
+        None => (runs, None),
+    }
+}
+
+/// The name of the backup of `container` taken at `at`, these sort by time.
+pub fn file_name<Tz: TimeZone>(container: &str, at: &DateTime<Tz>) -> String
+where
+    Tz::Offset: Display,
+{
+    format!("{container}-{}.sql", at.format(FILE_TIME_FORMAT))
+}
+
+/// The backups of `container` among `file_names` beyond the newest `keep`, which are deleted.
+pub fn prune(file_names: &[String], container: &str, keep: usize) -> Vec<String> {
+    let prefix = format!("{container}-");
+    let backups = file_names
+        .iter()
+        .filter(|name| {
+            name.strip_prefix(&prefix)
+                .and_then(|rest| rest.strip_suffix(".sql"))
+                .is_some_and(|time| {
+                    chrono::NaiveDateTime::parse_from_str(time, FILE_TIME_FORMAT).is_ok()
+                })
+        })
+        .sorted()
+        .collect::<Vec<_>>();
+
+    let excess = backups.len().saturating_sub(keep);
+    backups[..excess]
+        .iter()
+        .map(|name| name.to_string())
+        .collect()
+}
+
+/// Writes the output of `cmd` run in container `id` to a new backup of `container` in
+/// `directory`, then deletes the backups beyond the newest `keep`.
+pub async fn run_backup(
+    docker: &Docker,
+    id: String,
+    cmd: Vec<String>,
+    container: String,
+    directory: PathBuf,
+    keep: usize,
+) -> anyhow::Result<PathBuf> {
+    tokio::fs::create_dir_all(&directory).await?;
+    let path = directory.join(file_name(&container, &Local::now()));
+    exec_to_file(id, cmd, path.clone(), docker).await?;
+
+    let mut file_names = vec![];
+    let mut entries = tokio::fs::read_dir(&directory).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        file_names.push(entry.file_name().to_string_lossy().to_string());
+    }
+    for name in prune(&file_names, &container, keep) {
+        if let Err(ex) = tokio::fs::remove_file(directory.join(&name)).await {
+            eprintln!("Could not delete the old backup {name}: {ex}");
+        }
+    }
+
+    Ok(path)
+}
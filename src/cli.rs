@@ -0,0 +1,74 @@
+use itertools::Itertools;
+
+use crate::{
+    data::{read_preferences, Paths},
+    docker::get_containers,
+    errors::explain,
+    idle::unix_now,
+    state_file::StateDocument,
+    tls::connect,
+};
+
+/// Runs `db-mgr list`, printing the managed containers, or with `--json` the same document the
+/// state file holds. Returns the exit code.
+pub fn list(args: &[String], paths: Option<&Paths>) -> i32 {
+    let json = args.iter().any(|arg| arg == "--json");
+    let preferences = read_preferences(paths);
+    let docker = match connect(&preferences.docker_tls, preferences.docker_host.as_deref()) {
+        Ok(docker) => docker,
+        Err(ex) => {
+            let explained = explain("Could not connect to docker", &ex);
+            eprintln!("{}\n{}", explained.title, explained.body());
+            return 1;
+        }
+    };
+    let runtime = match tokio::runtime::Runtime::new() {
+        Ok(runtime) => runtime,
+        Err(ex) => {
+            eprintln!("Could not start the runtime: {ex}");
+            return 1;
+        }
+    };
+    let containers = match runtime.block_on(get_containers(&docker)) {
+        Ok(containers) => containers,
+        Err(ex) => {
+            eprintln!("Could not get containers: {ex}");
+            return 1;
+        }
+    };
+
+    let document = StateDocument::new(
+        &containers,
+        |container| preferences.project(container),
+        unix_now() as i64,
+    );
+
+    if json {
+        match serde_json::to_string_pretty(&document) {
+            Ok(json) => println!("{json}"),
+            Err(ex) => {
+                eprintln!("Could not write the containers as JSON: {ex}");
+                return 1;
+            }
+        }
+        return 0;
+    }
+
+    for container in document.containers {
+        let ports = container
+            .ports
+            .iter()
+            .map(|port| {
+                format!(
+                    "{}:{}->{}/{}",
+                    port.host_ip, port.host_port, port.container_port, port.protocol
+                )
+            })
+            .join(", ");
+        println!(
+            "{:<32} {:<10} {:<32} {}",
+            container.name, container.state, container.image, ports
+        );
+    }
+    0
+}
@@ -0,0 +1,138 @@
+use std::{error::Error, fmt::Display, future::Future, sync::OnceLock, time::Duration};
+
+use bollard::errors::Error as DockerError;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Semaphore;
+
+/// How long calls to the docker daemon may take and how many may run at once, from the `daemon`
+/// section of config.yaml. A timeout of 0 waits for as long as the call takes.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DaemonLimits {
+    /// Seconds for listing and inspecting.
+    #[serde(default = "default_query_secs")]
+    pub query_secs: u64,
+    /// Seconds for creating, starting, stopping and removing.
+    #[serde(default = "default_change_secs")]
+    pub change_secs: u64,
+    /// Calls in flight at once, so refreshing many containers doesn't open as many connections.
+    #[serde(default = "default_max_concurrent")]
+    pub max_concurrent: usize,
+}
+
+fn default_query_secs() -> u64 {
+    10
+}
+
+fn default_change_secs() -> u64 {
+    60
+}
+
+fn default_max_concurrent() -> usize {
+    8
+}
+
+impl Default for DaemonLimits {
+    fn default() -> Self {
+        Self {
+            query_secs: default_query_secs(),
+            change_secs: default_change_secs(),
+            max_concurrent: default_max_concurrent(),
+        }
+    }
+}
+
+fn seconds(secs: u64) -> Option<Duration> {
+    (secs > 0).then(|| Duration::from_secs(secs))
+}
+
+struct Limits {
+    limits: DaemonLimits,
+    permits: Semaphore,
+}
+
+static LIMITS: OnceLock<Limits> = OnceLock::new();
+
+fn limits() -> &'static Limits {
+    LIMITS.get_or_init(|| Limits::new(DaemonLimits::default()))
+}
+
+impl Limits {
+    fn new(limits: DaemonLimits) -> Self {
+        Self {
+            permits: Semaphore::new(limits.max_concurrent.max(1)),
+            limits,
+        }
+    }
+}
+
+/// Applies the limits of the config file, calls made before use the defaults.
+pub fn configure(limits: DaemonLimits) {
+    if LIMITS.set(Limits::new(limits)).is_err() {
+        eprintln!("The daemon limits were already in use, the configured ones are ignored");
+    }
+}
+
+/// The timeout of listing and inspecting.
+pub fn query_timeout() -> Option<Duration> {
+    seconds(limits().limits.query_secs)
+}
+
+/// The timeout of creating, starting, stopping and removing.
+pub fn change_timeout() -> Option<Duration> {
+    seconds(limits().limits.change_secs)
+}
+
+/// A daemon call that took longer than its timeout.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TimedOut {
+    pub operation: &'static str,
+    pub after: Duration,
+}
+
+impl Display for TimedOut {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Docker did not answer {} within {}s",
+            self.operation,
+            self.after.as_secs()
+        )
+    }
+}
+
+impl Error for TimedOut {}
+
+/// The status code the daemon answered with, `None` for timeouts and errors that aren't an
+/// answer.
+pub fn daemon_status(ex: &anyhow::Error) -> Option<u16> {
+    match ex.downcast_ref::<DockerError>() {
+        Some(DockerError::DockerResponseServerError { status_code, .. }) => Some(*status_code),
+        _ => None,
+    }
+}
+
+/// Runs the daemon call `operation` once one of the concurrent calls is free, failing with
+/// [`TimedOut`] if waiting and the call together take longer than `timeout`. Without a timeout
+/// the call runs until it finishes or is dropped, which is how pulls are cancelled.
+pub async fn with_timeout<T, E>(
+    operation: &'static str,
+    timeout: Option<Duration>,
+    call: impl Future<Output = Result<T, E>>,
+) -> anyhow::Result<T>
+where
+    E: Error + Send + Sync + 'static,
+{
+    let limited = async {
+        // The semaphore is never closed
+        let _permit = limits().permits.acquire().await;
+        call.await
+    };
+
+    match timeout {
+        Some(after) => match tokio::time::timeout(after, limited).await {
+            Ok(result) => Ok(result?),
+            Err(_) => Err(TimedOut { operation, after }.into()),
+        },
+        None => Ok(limited.await?),
+    }
+}
@@ -1,25 +1,518 @@
+use anyhow::Context;
+use bollard::service::ContainerStateStatusEnum;
+use chrono::NaiveDate;
+use itertools::Itertools;
+use rand::{distributions::Alphanumeric, Rng};
 use serde::{Deserialize, Serialize};
+use serde_yaml::{Mapping, Value};
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     fmt::Display,
     fs::{self, File},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    backup::BackupSchedule,
+    daemon::DaemonLimits,
+    definition::ContainerDefinition,
+    diff::{diff, DiffRow},
+    docker::{DbContainer, DbContainerConfig, Dockerfile, ImageBuild, LogSettings, Ulimit},
+    idle::{IdleAfter, IdleTracker},
+    image_ref::{built_image_name, ImageRef},
+    names::default_hostname,
+    ports::{PortAssignment, PortRange},
+    registry::DigestCache,
+    template::{render, render_args},
+    tls::TlsClient,
 };
 
 const DEFAULT_CONFIG: &str = include_str!("../config.yaml");
 
+/// The config schema version this build reads and writes.
+pub const CONFIG_VERSION: u32 = 1;
+
+/// Upgrades a config from the version at its index to the next one.
+const CONFIG_MIGRATIONS: [fn(&mut Mapping); CONFIG_VERSION as usize] = [add_version];
+
 #[derive(Serialize, Deserialize)]
 pub struct ConfigFile {
+    /// Files without a version predate versioning and are version 0.
+    #[serde(default)]
+    pub version: u32,
     pub databases: Vec<DatabaseConfig>,
+    #[serde(default)]
+    pub proxy: ProxyConfig,
+    /// Free space in MB on the docker data root below which a warning is shown.
+    #[serde(default = "default_low_disk_space")]
+    pub low_disk_space_mb: u64,
+    #[serde(default)]
+    pub daemon: DaemonLimits,
+    #[serde(default)]
+    pub ports: PortRange,
+}
+
+fn default_low_disk_space() -> u64 {
+    5 * 1024
+}
+
+/// Proxy settings for icon and registry requests, unset values fall back to the `HTTP_PROXY`,
+/// `HTTPS_PROXY` and `NO_PROXY` environment variables.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProxyConfig {
+    pub http: Option<String>,
+    pub https: Option<String>,
+    pub no_proxy: Option<String>,
+    pub extra_ca_cert: Option<PathBuf>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 pub struct DatabaseConfig {
     pub name: String,
+    /// The image to pull. Entries with a `dockerfile` may leave it out, otherwise it is passed to
+    /// the build as the `IMAGE` argument.
+    #[serde(default)]
     pub image: String,
     pub icon_url: String,
     pub tags: Vec<String>,
     pub variables: HashMap<String, String>,
     pub volumes: HashMap<String, String>,
+    #[serde(default)]
+    pub command: Vec<String>,
+    /// Default values for variables, keyed by environment variable name.
+    #[serde(default)]
+    pub defaults: HashMap<String, String>,
+    /// Arguments run inside the container to execute a query, `{QUERY}` is replaced with the
+    /// query and other placeholders with the container's variables.
+    #[serde(default)]
+    pub query_command: Vec<String>,
+    /// Arguments run inside the container that read SQL from stdin, used to run dropped SQL
+    /// files. Placeholders are replaced with the container's variables.
+    #[serde(default)]
+    pub restore_command: Vec<String>,
+    /// Recommended size of `/dev/shm` in MB, pre-filled when creating a container.
+    #[serde(default)]
+    pub shm_size_mb: Option<u64>,
+    /// Recommended CPUs to pin the container to, such as `0-3`, pre-filled when creating a
+    /// container.
+    #[serde(default)]
+    pub cpuset_cpus: Option<String>,
+    /// Builds the image as `db-mgr/<name>` from this Dockerfile instead of pulling `image`.
+    #[serde(default)]
+    pub dockerfile: Option<Dockerfile>,
+    /// Whether the image runs as any uid, so containers may be created with another user.
+    /// `false` warns when a user is set, unknown when left out.
+    #[serde(default)]
+    pub arbitrary_user: Option<bool>,
+    /// How other containers on the same network connect, `{HOST}` is replaced with the
+    /// container's alias or IP, `{PORT}` with `port` and other placeholders with its variables.
+    /// Connecting from the host replaces them with the published address instead.
+    #[serde(default)]
+    pub connection_string: Option<String>,
+    /// Arguments run inside the container that write a backup to stdout, used by scheduled
+    /// backups. Placeholders are replaced with the container's variables.
+    #[serde(default)]
+    pub backup_command: Vec<String>,
+    /// Warn that data is lost when creating a container without persistence, and make the user
+    /// acknowledge it before creating one. Turn it off for throwaway images.
+    #[serde(default = "default_true")]
+    pub data_loss_warning: bool,
+    /// Keys of `.env` files that fill in one of the variables, such as `DATABASE_PASSWORD` for
+    /// `POSTGRES_PASSWORD`, keyed by the `.env` key.
+    #[serde(default)]
+    pub env_aliases: HashMap<String, String>,
+    /// Volumes mounted read-only by default, such as ones holding seed data.
+    #[serde(default)]
+    pub read_only_volumes: Vec<String>,
+    /// Driver options of the volumes, such as `type: nfs`, keyed by volume name.
+    #[serde(default)]
+    pub volume_options: HashMap<String, HashMap<String, String>>,
+    /// Resource limits the image needs, such as `nofile` for Elasticsearch, pre-filled when
+    /// creating a container.
+    #[serde(default)]
+    pub ulimits: Vec<Ulimit>,
+    /// Namespaced kernel parameters the image needs, such as `net.core.somaxconn`, pre-filled
+    /// when creating a container.
+    #[serde(default)]
+    pub sysctls: HashMap<String, String>,
+    /// Admin UIs offered after a container of this entry is created, such as Adminer.
+    #[serde(default)]
+    pub companions: Vec<CompanionConfig>,
+    /// Whether the image can use GPUs, which offers to give containers access to them.
+    #[serde(default)]
+    pub gpu_capable: bool,
+    /// The port the database listens on inside the container. Containers publish it on a port
+    /// of `127.0.0.1`, picked from the `ports` range unless one is typed.
+    #[serde(default)]
+    pub port: Option<u16>,
+    /// Seconds docker waits for the database to shut down after `SIGTERM` before killing it,
+    /// docker's default of 10 when left out. Force stopping is suggested once it passed.
+    #[serde(default)]
+    pub stop_timeout_secs: Option<u64>,
+    /// How the major version is read from a tag, `{MAJOR}` stands for its digits and `*` for
+    /// anything, such as `{MAJOR}*`. Reusing volumes across majors is refused when it is set.
+    #[serde(default)]
+    pub version_pattern: Option<String>,
+    /// The end of life date of each major version read with `version_pattern`, such as
+    /// `"12": 2024-11-21`. Containers of a version past or near it are warned about.
+    #[serde(default)]
+    pub eol_dates: HashMap<String, NaiveDate>,
+    /// Shown next to the tag picker when creating a container, such as which user the image
+    /// creates. Plain text, `#` headings and `-` lists are styled.
+    #[serde(default)]
+    pub notes: Option<String>,
+    /// Notes on single tags, such as what the `alpine` variant leaves out, keyed by tag and shown
+    /// after `notes`.
+    #[serde(default)]
+    pub tag_notes: HashMap<String, String>,
+}
+
+/// An admin UI run next to a database container and connected to it, such as Adminer or pgAdmin.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct CompanionConfig {
+    pub name: String,
+    pub image: String,
+    #[serde(default = "default_companion_tag")]
+    pub tag: String,
+    /// The port the UI listens on inside the container, published on a free local port.
+    #[serde(default)]
+    pub port: Option<u16>,
+    /// `{HOST}` is replaced with the name of the database container and other placeholders with
+    /// its variables, so credentials can be passed through.
+    #[serde(default)]
+    pub variables: HashMap<String, String>,
+}
+
+fn default_companion_tag() -> String {
+    "latest".to_string()
+}
+
+impl CompanionConfig {
+    /// The config to create this companion of `parent` with, `parent` being the prepared config
+    /// the database container was created with. The companion is named after its parent and
+    /// joins its project.
+    pub fn config(&self, parent: &DbContainerConfig) -> DbContainerConfig {
+        let mut parent_variables = parent.variables.clone();
+        parent_variables.insert("HOST".to_string(), parent.name.clone());
+
+        DbContainerConfig {
+            name: format!("{}-{}", parent.name, default_hostname(&self.name)),
+            database: self.name.clone(),
+            variables: self
+                .variables
+                .iter()
+                .map(|(key, value)| (key.clone(), render(value, &parent_variables)))
+                .collect(),
+            image: self.image.clone(),
+            volumes: HashMap::new(),
+            tag: self.tag.clone(),
+            command: vec![],
+            reuse_volumes: false,
+            force_shared_volume: false,
+            start_after_create: true,
+            dry_run: false,
+            shm_size_mb: None,
+            cpuset_cpus: None,
+            tmpfs: vec![],
+            tmpfs_size: None,
+            hostname: None,
+            extra_hosts: vec![],
+            user: None,
+            build: None,
+            depends_on: vec![parent.name.clone()],
+            extra_variables: vec![],
+            read_only_volumes: vec![],
+            volume_options: HashMap::new(),
+            project: parent.project.clone(),
+            ulimits: vec![],
+            sysctls: HashMap::new(),
+            companion_of: Some(parent.name.clone()),
+            published_ports: self.port.into_iter().collect(),
+            host_port: None,
+            gpus: None,
+            log: LogSettings::default(),
+        }
+    }
+}
+
+impl DatabaseConfig {
+    /// The image containers are created from, the built one for entries with a Dockerfile.
+    pub fn image_name(&self) -> String {
+        match self.dockerfile {
+            Some(_) => built_image_name(&self.name),
+            None => self.image.clone(),
+        }
+    }
+
+    /// How the image is built, `None` for entries that pull it.
+    pub fn image_build(&self) -> Option<ImageBuild> {
+        let dockerfile = self.dockerfile.clone()?;
+        let mut args = HashMap::new();
+        if !self.image.is_empty() {
+            args.insert("IMAGE".to_string(), self.image.clone());
+        }
+
+        Some(ImageBuild { dockerfile, args })
+    }
+
+    /// Whether there are notes to show for any of the tags.
+    pub fn has_notes(&self) -> bool {
+        self.notes.is_some() || !self.tag_notes.is_empty()
+    }
+
+    /// The notes of the image and of `tag`, in that order, leaving out empty ones.
+    pub fn notes_for(&self, tag: &str) -> Vec<&str> {
+        self.notes
+            .iter()
+            .chain(self.tag_notes.get(tag))
+            .map(|notes| notes.trim())
+            .filter(|notes| !notes.is_empty())
+            .collect()
+    }
+
+    /// The image reference for the first configured tag, which may also be a digest.
+    pub fn image_ref(&self) -> ImageRef {
+        let image = ImageRef::parse(&self.image_name());
+        match self.tags.first() {
+            Some(reference) => image.with_reference(reference),
+            None => image,
+        }
+    }
+
+    /// The tag a new container starts with, the first configured tag or `latest` if none are
+    /// configured. With `never_latest` it is left empty instead so a tag has to be chosen.
+    pub fn default_tag(&self, never_latest: bool) -> String {
+        match self.tags.first() {
+            Some(tag) => tag.clone(),
+            None if never_latest => String::new(),
+            None => "latest".to_string(),
+        }
+    }
+
+    pub fn matches(&self, image: &ImageRef) -> bool {
+        ImageRef::parse(&self.image_name()).key() == image.key()
+    }
+
+    pub fn declares(&self, key: &str) -> bool {
+        self.variables.values().any(|declared| declared == key) || self.defaults.contains_key(key)
+    }
+
+    /// The variable the `.env` key `key` fills in, itself or through `env_aliases`.
+    pub fn env_variable(&self, key: &str) -> Option<String> {
+        if self.declares(key) {
+            return Some(key.to_string());
+        }
+
+        self.env_aliases.get(key).cloned()
+    }
+
+    /// The variables of `container` this entry declares or the user added, ignoring the ones
+    /// baked into the image and values too large to copy.
+    fn declared_variables(&self, container: &DbContainer) -> HashMap<String, String> {
+        let extra = container.extra_variables();
+        container
+            .variables
+            .iter()
+            .filter(|(key, _)| self.declares(key) || extra.contains(key))
+            .filter_map(|(key, value)| Some((key.clone(), value.as_text()?.to_string())))
+            .collect()
+    }
+
+    /// The declared variables of `container` that are left out when recreating it, because their
+    /// values are too large or binary.
+    pub fn skipped_variables(&self, container: &DbContainer) -> Vec<String> {
+        container
+            .variables
+            .iter()
+            .filter(|(key, value)| self.declares(key) && value.as_text().is_none())
+            .map(|(key, _)| key.clone())
+            .sorted()
+            .collect()
+    }
+
+    /// The secret variables without a default, which the quick start fills with random values.
+    pub fn generated_variables(&self) -> impl Iterator<Item = (&String, &String)> {
+        self.variables
+            .iter()
+            .filter(|(name, key)| is_secret(name, key) && !self.defaults.contains_key(*key))
+    }
+
+    /// A config named `name` using only the defaults of this entry, secrets without a default get
+    /// a random value.
+    pub fn quick_config(&self, name: &str) -> DbContainerConfig {
+        let mut variables = self.defaults.clone();
+        variables.extend(
+            self.generated_variables()
+                .map(|(_, key)| (key.clone(), generate_password())),
+        );
+
+        DbContainerConfig {
+            name: name.to_string(),
+            database: self.name.clone(),
+            variables,
+            image: self.image_name(),
+            volumes: self.volumes.clone(),
+            tag: self.default_tag(false),
+            command: self.command.clone(),
+            reuse_volumes: false,
+            force_shared_volume: false,
+            start_after_create: true,
+            dry_run: false,
+            shm_size_mb: self.shm_size_mb,
+            cpuset_cpus: self.cpuset_cpus.clone(),
+            tmpfs: vec![],
+            tmpfs_size: None,
+            hostname: None,
+            extra_hosts: vec![],
+            user: None,
+            build: self.image_build(),
+            depends_on: vec![],
+            extra_variables: vec![],
+            read_only_volumes: self.read_only_volumes.clone(),
+            volume_options: self.volume_options.clone(),
+            project: None,
+            ulimits: self.ulimits.clone(),
+            sysctls: self.sysctls.clone(),
+            companion_of: None,
+            published_ports: vec![],
+            host_port: self.port.map(PortAssignment::auto),
+            gpus: None,
+            log: LogSettings::default(),
+        }
+    }
+
+    /// The arguments to run `query` in `container`, `None` if this entry has no query command.
+    pub fn query_args(&self, container: &DbContainer, query: &str) -> Option<Vec<String>> {
+        if self.query_command.is_empty() {
+            return None;
+        }
+
+        let mut variables = text_variables(container);
+        variables.insert("QUERY".to_string(), query.to_string());
+
+        Some(render_args(&self.query_command, &variables))
+    }
+
+    /// The arguments that read SQL from stdin in `container`, `None` if this entry has no
+    /// restore command.
+    pub fn restore_args(&self, container: &DbContainer) -> Option<Vec<String>> {
+        if self.restore_command.is_empty() {
+            return None;
+        }
+
+        Some(render_args(
+            &self.restore_command,
+            &text_variables(container),
+        ))
+    }
+
+    /// The connection string for containers on the same network as `container` reaching it at
+    /// `host`, `None` if this entry has no connection string.
+    pub fn internal_connection_string(
+        &self,
+        container: &DbContainer,
+        host: &str,
+    ) -> Option<String> {
+        let mut variables = text_variables(container);
+        variables.insert("HOST".to_string(), host.to_string());
+        if let Some(port) = self.port {
+            variables.insert("PORT".to_string(), port.to_string());
+        }
+
+        Some(render(self.connection_string.as_ref()?, &variables))
+    }
+
+    /// The connection string for clients on this machine, using the host port docker bound
+    /// `port` to, which may not be the one assigned. `None` while it isn't published.
+    pub fn host_connection_string(&self, container: &DbContainer) -> Option<String> {
+        let port = self.port?;
+        let binding = container
+            .ports
+            .iter()
+            .find(|published| published.port == port)?;
+        let host = match binding.host_ip.as_str() {
+            "" | "0.0.0.0" | "::" => "127.0.0.1",
+            ip => ip,
+        };
+
+        let mut variables = text_variables(container);
+        variables.insert("HOST".to_string(), host.to_string());
+        variables.insert("PORT".to_string(), binding.host_port.to_string());
+        Some(render(self.connection_string.as_ref()?, &variables))
+    }
+
+    /// The arguments that write a backup of `container` to stdout, `None` if this entry has no
+    /// backup command.
+    pub fn backup_args(&self, container: &DbContainer) -> Option<Vec<String>> {
+        if self.backup_command.is_empty() {
+            return None;
+        }
+
+        Some(render_args(
+            &self.backup_command,
+            &text_variables(container),
+        ))
+    }
+
+    /// Whether SQL files can be run in containers of this entry.
+    pub fn runs_sql(&self) -> bool {
+        !self.restore_command.is_empty() || !self.query_command.is_empty()
+    }
+
+    pub fn template_diff(&self, container: &DbContainer) -> Vec<DiffRow> {
+        diff(&self.declared_variables(container), &self.defaults)
+    }
+
+    /// The config to recreate `container` with, keeping its volumes and image but resetting the
+    /// variables that have template defaults.
+    pub fn recreate_config(&self, container: &DbContainer) -> DbContainerConfig {
+        let image = ImageRef::parse(&container.image);
+        let mut variables = self.declared_variables(container);
+        variables.extend(self.defaults.clone());
+
+        DbContainerConfig {
+            name: container.name.trim_start_matches('/').to_string(),
+            database: self.name.clone(),
+            variables,
+            image: image.name(),
+            tag: image.reference().to_string(),
+            volumes: container.volumes.clone(),
+            command: container.command.clone(),
+            reuse_volumes: true,
+            force_shared_volume: false,
+            start_after_create: container.state == ContainerStateStatusEnum::RUNNING,
+            dry_run: false,
+            shm_size_mb: container.shm_size_mb,
+            cpuset_cpus: container.cpuset_cpus.clone(),
+            tmpfs: container.tmpfs.clone(),
+            tmpfs_size: container.tmpfs_size,
+            hostname: container.hostname.clone(),
+            extra_hosts: container.extra_hosts.clone(),
+            user: container.user.clone(),
+            build: None,
+            depends_on: container.depends_on.clone(),
+            extra_variables: container.extra_variables(),
+            read_only_volumes: container.read_only_volumes.clone(),
+            volume_options: HashMap::new(),
+            project: container.project().map(String::from),
+            ulimits: container.ulimits.clone(),
+            sysctls: container.sysctls.clone(),
+            companion_of: container.companion_of().map(String::from),
+            published_ports: container.published_ports(),
+            host_port: container.port_assignment(),
+            gpus: container.gpus.clone(),
+            log: container.log.clone().unwrap_or_default(),
+        }
+    }
+}
+
+/// The variables of `container` that are text, the values placeholders are replaced with.
+fn text_variables(container: &DbContainer) -> HashMap<String, String> {
+    container
+        .variables
+        .iter()
+        .filter_map(|(key, value)| Some((key.clone(), value.as_text()?.to_string())))
+        .collect()
 }
 
 impl Display for DatabaseConfig {
@@ -28,18 +521,133 @@ impl Display for DatabaseConfig {
     }
 }
 
-pub fn get_file() -> Option<File> {
-    let project_dirs = directories::ProjectDirs::from("nz", "laspruca", "db-mgr")?;
-    let config_path = project_dirs.config_dir();
+/// Environment variable naming the directory db-mgr keeps its files in.
+pub const CONFIG_DIR_VAR: &str = "DB_MGR_CONFIG_DIR";
 
-    if !config_path.exists() {
-        if let Err(ex) = fs::create_dir_all(config_path) {
-            eprintln!("Could not create config directory {ex}");
-            return None;
+/// The directories db-mgr keeps its files in, resolved once at startup.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Paths {
+    pub config_dir: PathBuf,
+    pub data_dir: PathBuf,
+}
+
+impl Paths {
+    /// Uses the `--config-dir` flag over the environment variable over the platform directories.
+    /// An overridden directory holds both the config and the data.
+    pub fn resolve(flag: Option<PathBuf>, env: Option<PathBuf>) -> Option<Self> {
+        if let Some(dir) = flag.or(env).filter(|dir| !dir.as_os_str().is_empty()) {
+            return Some(Self {
+                config_dir: dir.clone(),
+                data_dir: dir,
+            });
         }
+
+        let project_dirs = directories::ProjectDirs::from("nz", "laspruca", "db-mgr")?;
+        Some(Self {
+            config_dir: project_dirs.config_dir().to_path_buf(),
+            data_dir: project_dirs.data_dir().to_path_buf(),
+        })
+    }
+
+    /// Resolves the paths from the command line `args` and [`CONFIG_DIR_VAR`].
+    pub fn from_env(args: &[String]) -> Option<Self> {
+        Self::resolve(
+            config_dir_flag(args),
+            std::env::var_os(CONFIG_DIR_VAR).map(PathBuf::from),
+        )
+    }
+
+    pub fn config_file(&self) -> PathBuf {
+        self.config_dir.join("config.yaml")
     }
 
-    let config_file = config_path.join("config.yaml");
+    pub fn preferences_file(&self) -> PathBuf {
+        self.data_dir.join("prefs.yaml")
+    }
+
+    /// The create form as it was last left, see [`Draft`].
+    pub fn draft_file(&self) -> PathBuf {
+        self.data_dir.join("draft.yaml")
+    }
+
+    /// Held by the running instance so starting db-mgr again focuses it instead.
+    pub fn lock_file(&self) -> PathBuf {
+        self.data_dir.join("db-mgr.lock")
+    }
+
+    /// Where fetched database icons are kept, so they show right away on the next start.
+    pub fn icon_cache_dir(&self) -> PathBuf {
+        self.data_dir.join("icons")
+    }
+
+    /// Creates the directories and checks that files can be written to them.
+    pub fn check_writable(&self) -> anyhow::Result<()> {
+        for dir in [&self.config_dir, &self.data_dir] {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("Could not create {}", dir.display()))?;
+
+            let probe = dir.join(".db-mgr-write-check");
+            fs::write(&probe, "").with_context(|| format!("Can't write to {}", dir.display()))?;
+            let _ = fs::remove_file(probe);
+        }
+
+        Ok(())
+    }
+
+    /// The files and directories shown in the settings.
+    pub fn app_paths(&self) -> Vec<AppPath> {
+        vec![
+            AppPath {
+                name: "Config file",
+                path: self.config_file(),
+            },
+            AppPath {
+                name: "Preferences",
+                path: self.preferences_file(),
+            },
+            AppPath {
+                name: "Data directory",
+                path: self.data_dir.clone(),
+            },
+        ]
+    }
+}
+
+/// The value of `--config-dir <dir>` or `--config-dir=<dir>` in `args`.
+fn config_dir_flag(args: &[String]) -> Option<PathBuf> {
+    args.iter()
+        .enumerate()
+        .find_map(|(index, arg)| match arg.as_str() {
+            "--config-dir" => args.get(index + 1).map(PathBuf::from),
+            arg => arg.strip_prefix("--config-dir=").map(PathBuf::from),
+        })
+}
+
+/// Why nothing db-mgr changes can be saved, if that is the case. Files already in a read-only
+/// directory are still read.
+pub fn persistence_problem(paths: Option<&Paths>) -> Option<String> {
+    match paths {
+        None => Some(format!(
+            "No config directory was found, set {CONFIG_DIR_VAR} or pass --config-dir to save settings"
+        )),
+        Some(paths) => paths
+            .check_writable()
+            .err()
+            .map(|ex| format!("Settings won't be saved: {ex:#}")),
+    }
+}
+
+/// The path of the config file, written with the default config if it doesn't exist yet.
+fn config_path(paths: &Paths) -> Option<PathBuf> {
+    let config_file = paths.config_file();
+    if config_file.exists() {
+        return Some(config_file);
+    }
+
+    if let Err(ex) = fs::create_dir_all(&paths.config_dir) {
+        eprintln!("Could not create config directory {ex}");
+        return None;
+    }
 
     if !config_file.exists() {
         if let Err(ex) = fs::write(&config_file, DEFAULT_CONFIG) {
@@ -48,22 +656,597 @@ pub fn get_file() -> Option<File> {
         }
     }
 
-    match File::open(config_file) {
+    Some(config_file)
+}
+
+/// Version 0 files predate versioning, they only need the version, which goes first.
+fn add_version(config: &mut Mapping) {
+    let mut versioned = Mapping::new();
+    versioned.insert("version".into(), 1.into());
+    versioned.extend(std::mem::take(config));
+    *config = versioned;
+}
+
+fn config_version(config: &Value) -> u64 {
+    config
+        .get("version")
+        .and_then(Value::as_u64)
+        .unwrap_or_default()
+}
+
+/// Upgrades `config` step by step to [`CONFIG_VERSION`], keeping the fields it doesn't know.
+/// Files written by a newer version are refused.
+pub fn migrate_config(mut config: Value) -> anyhow::Result<Value> {
+    let version = config_version(&config);
+    if version > CONFIG_VERSION as u64 {
+        anyhow::bail!(
+            "The config file is version {version} but this version of db-mgr only understands up to version {CONFIG_VERSION}, update db-mgr to use it"
+        );
+    }
+
+    let Value::Mapping(mapping) = &mut config else {
+        anyhow::bail!("The config file is not a mapping");
+    };
+    for (step, migration) in CONFIG_MIGRATIONS.iter().enumerate().skip(version as usize) {
+        migration(mapping);
+        mapping.insert("version".into(), (step as u64 + 1).into());
+    }
+
+    Ok(config)
+}
+
+/// Reads the config at `path`, migrating it to the current version first. The original of a
+/// migrated file is kept next to it as `config.yaml.bak`.
+fn load_config(path: &Path) -> anyhow::Result<ConfigFile> {
+    let contents =
+        fs::read_to_string(path).with_context(|| format!("Could not read {}", path.display()))?;
+    let config: Value = serde_yaml::from_str(&contents)
+        .with_context(|| format!("Could not parse {}", path.display()))?;
+    if config_version(&config) == CONFIG_VERSION as u64 {
+        return Ok(serde_yaml::from_value(config)?);
+    }
+
+    let migrated = migrate_config(config)?;
+    let backup = path.with_extension("yaml.bak");
+    match fs::write(&backup, &contents) {
+        Err(ex) => {
+            eprintln!("Could not back up the config file, it is migrated in memory only {ex}")
+        }
+        Ok(_) => match serde_yaml::to_string(&migrated) {
+            Err(ex) => eprintln!("Could not serialize the migrated config file {ex}"),
+            Ok(yaml) => {
+                if let Err(ex) = fs::write(path, yaml) {
+                    eprintln!("Could not write the migrated config file {ex}");
+                }
+            }
+        },
+    }
+
+    Ok(serde_yaml::from_value(migrated)?)
+}
+
+/// Makes the Dockerfile paths of the entries relative to `dir`, the directory of the config file.
+fn resolve_dockerfiles(config: &mut ConfigFile, dir: &Path) {
+    for database in config.databases.iter_mut() {
+        if let Some(Dockerfile::File { path }) = database.dockerfile.as_mut() {
+            if path.is_relative() {
+                *path = dir.join(&*path);
+            }
+        }
+    }
+}
+
+/// Reads the config file, falling back to the default config with a message to show if it
+/// can't be used.
+pub fn read_config_file(paths: Option<&Paths>) -> (ConfigFile, Option<String>) {
+    let default = || serde_yaml::from_str(DEFAULT_CONFIG).unwrap();
+    let Some(path) = paths.and_then(config_path) else {
+        return (default(), None);
+    };
+
+    match load_config(&path) {
+        Err(ex) => {
+            eprintln!("Could not read config file {ex:#}");
+            (default(), Some(format!("{ex:#}")))
+        }
+        Ok(mut config) => {
+            if let Some(dir) = path.parent() {
+                resolve_dockerfiles(&mut config, dir);
+            }
+            (config, None)
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Preferences {
+    #[serde(default = "default_true")]
+    pub remember_values: bool,
+    #[serde(default)]
+    pub remember_passwords: bool,
+    #[serde(default)]
+    pub remembered: HashMap<String, RememberedValues>,
+    #[serde(default)]
+    pub view_mode: ViewMode,
+    /// Leave the tag empty for entries without configured tags instead of using `latest`.
+    #[serde(default)]
+    pub never_default_latest: bool,
+    /// Start without the actions that change containers, as with `--read-only`.
+    #[serde(default)]
+    pub read_only: bool,
+    /// After how many untouched days a running container is shown as idle.
+    #[serde(default)]
+    pub idle_after: IdleAfter,
+    #[serde(default)]
+    pub idle: IdleTracker,
+    /// Scheduled backups, keyed by container name so they survive recreating the container.
+    #[serde(default)]
+    pub backups: HashMap<String, BackupSchedule>,
+    /// Create containers right away instead of reviewing the final config first.
+    #[serde(default)]
+    pub skip_create_review: bool,
+    /// Check the registries for newer images of the containers' tags once a day.
+    #[serde(default)]
+    pub check_updates: bool,
+    /// The digests the registries last reported, so each tag is looked up at most once a day.
+    #[serde(default)]
+    pub digests: DigestCache,
+    /// Stop the running containers when db-mgr is closed.
+    #[serde(default)]
+    pub stop_on_exit: bool,
+    /// Names of the containers left running when db-mgr is closed.
+    #[serde(default)]
+    pub keep_on_exit: HashSet<String>,
+    /// Projects set in db-mgr instead of recreating the container with a new label, keyed by
+    /// container name. An empty project takes the container out of its labelled project.
+    #[serde(default)]
+    pub projects: HashMap<String, String>,
+    /// Where the state of the containers is written as JSON after each refresh, for scripts.
+    #[serde(default)]
+    pub state_file: Option<PathBuf>,
+    /// Starting db-mgr while it is running focuses the running window instead, unless
+    /// `--new-instance` is given.
+    #[serde(default = "default_true")]
+    pub single_instance: bool,
+    /// How the last [`CREATED_HISTORY`] containers were created, newest last, so removed ones
+    /// can be created again.
+    #[serde(default)]
+    pub created: Vec<CreatedDefinition>,
+    /// Names of the containers whose end of life warning was silenced.
+    #[serde(default)]
+    pub eol_silenced: HashSet<String>,
+    /// Names of the containers started and stopped from the tray menu.
+    #[serde(default)]
+    pub favourites: HashSet<String>,
+    /// Show a tray icon where the build and desktop support one, closing the window then keeps
+    /// db-mgr running in the tray.
+    #[serde(default = "default_true")]
+    pub tray: bool,
+    #[serde(default)]
+    pub theme: AppTheme,
+    /// Show tools for looking into db-mgr itself, such as the raw inspect of containers.
+    #[serde(default)]
+    pub developer_mode: bool,
+    /// The docker endpoint connected to on start, such as `npipe:////./pipe/docker_engine` or
+    /// `tcp://10.0.0.5:2375`. `DOCKER_HOST` or the platform's default when `None`.
+    #[serde(default)]
+    pub docker_host: Option<String>,
+    /// The client certificate for a daemon that requires mutual TLS, used instead of
+    /// `docker_host` when it is enabled.
+    #[serde(default)]
+    pub docker_tls: TlsClient,
+}
+
+/// How many of the created containers are kept in [`Preferences::created`].
+pub const CREATED_HISTORY: usize = 20;
+
+/// The definition a container was created with.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct CreatedDefinition {
+    /// The container name without the `db-mgr__` prefix.
+    pub name: String,
+    /// The unix time it was created at.
+    pub created_at: i64,
+    pub definition: ContainerDefinition,
+}
+
+/// The create form as it was left, saved so it can be picked up again after db-mgr is closed.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Draft {
+    /// The name typed for the container, empty if none was.
+    pub name: String,
+    /// Whether the data is kept in volumes.
+    pub persist: bool,
+    pub definition: ContainerDefinition,
+}
+
+impl Draft {
+    /// The draft of the create form filled in with `config` for `database`. Secret values are
+    /// only kept with `include_secrets`.
+    pub fn new(
+        config: &DbContainerConfig,
+        database: &DatabaseConfig,
+        persist: bool,
+        include_secrets: bool,
+    ) -> Self {
+        Self {
+            name: config.name.clone(),
+            persist,
+            definition: ContainerDefinition::from_config(config, Some(database), include_secrets),
+        }
+    }
+
+    /// Such as `Postgres 'myapp-db'`.
+    pub fn describe(&self) -> String {
+        let database = self
+            .definition
+            .database
+            .clone()
+            .unwrap_or_else(|| self.definition.image.clone());
+        match self.name.trim() {
+            "" => database,
+            name => format!("{database} '{name}'"),
+        }
+    }
+
+    /// The create form contents the draft was saved from, matched against `databases` like an
+    /// imported definition so an entry removed since is reported rather than failing.
+    pub fn restore(
+        self,
+        databases: &[DatabaseConfig],
+    ) -> (DbContainerConfig, DatabaseConfig, Vec<String>) {
+        let (mut config, database, warnings) = self.definition.into_config(databases);
+        config.name = self.name;
+        if !self.persist {
+            config.volumes.clear();
+            config.read_only_volumes.clear();
+        }
+
+        (config, database, warnings)
+    }
+}
+
+/// How the containers are laid out in the sidebar.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ViewMode {
+    #[default]
+    List,
+    Grid,
+}
+
+/// The colours of the window.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AppTheme {
+    #[default]
+    Dark,
+    Light,
+}
+
+impl AppTheme {
+    pub const ALL: [AppTheme; 2] = [AppTheme::Dark, AppTheme::Light];
+}
+
+impl Display for AppTheme {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppTheme::Dark => write!(f, "Dark"),
+            AppTheme::Light => write!(f, "Light"),
+        }
+    }
+}
+
+impl Default for Preferences {
+    fn default() -> Self {
+        Self {
+            remember_values: true,
+            remember_passwords: false,
+            remembered: HashMap::new(),
+            view_mode: ViewMode::List,
+            never_default_latest: false,
+            read_only: false,
+            idle_after: IdleAfter::default(),
+            idle: IdleTracker::default(),
+            backups: HashMap::new(),
+            skip_create_review: false,
+            check_updates: false,
+            digests: DigestCache::default(),
+            stop_on_exit: false,
+            keep_on_exit: HashSet::new(),
+            projects: HashMap::new(),
+            state_file: None,
+            single_instance: true,
+            created: vec![],
+            eol_silenced: HashSet::new(),
+            favourites: HashSet::new(),
+            tray: true,
+            theme: AppTheme::Dark,
+            developer_mode: false,
+            docker_host: None,
+            docker_tls: TlsClient::default(),
+        }
+    }
+}
+
+impl Preferences {
+    /// The project of `container`, the one set in db-mgr or else the one it was labelled with.
+    pub fn project(&self, container: &DbContainer) -> Option<String> {
+        match self.projects.get(container.name.trim_start_matches('/')) {
+            Some(project) => Some(project.clone()).filter(|project| !project.is_empty()),
+            None => container.project().map(String::from),
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct RememberedValues {
+    pub tag: String,
+    pub variables: HashMap<String, String>,
+}
+
+/// A random alphanumeric password, safe to pass on a command line.
+pub fn generate_password() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(24)
+        .map(char::from)
+        .collect()
+}
+
+/// Whether a variable holds a secret, based on either its display name or its key.
+pub fn is_secret(name: &str, key: &str) -> bool {
+    [name, key].iter().any(|value| {
+        let value = value.to_lowercase();
+        ["password", "passwd", "secret", "token"]
+            .iter()
+            .any(|kind| value.contains(kind))
+    })
+}
+
+/// Whether the variable `key` of a container created from `database` holds a secret, keys the
+/// entry declares are checked by their display name as well.
+pub fn is_secret_key(database: Option<&DatabaseConfig>, key: &str) -> bool {
+    match database.and_then(|db| db.variables.iter().find(|(_, declared)| *declared == key)) {
+        Some((name, _)) => is_secret(name, key),
+        None => is_secret(key, key),
+    }
+}
+
+impl Preferences {
+    /// Stores the values of a successfully created container so they can be recalled the next
+    /// time `database` is selected.
+    pub fn remember(&mut self, database: &DatabaseConfig, config: &DbContainerConfig) {
+        if !self.remember_values {
+            return;
+        }
+
+        let variables = database
+            .variables
+            .iter()
+            .filter(|(name, key)| self.remember_passwords || !is_secret(name, key))
+            .filter_map(|(_, key)| Some((key.clone(), config.variables.get(key)?.clone())))
+            .collect();
+
+        self.remembered.insert(
+            database.name.clone(),
+            RememberedValues {
+                tag: config.tag.clone(),
+                variables,
+            },
+        );
+    }
+
+    /// Records how the container of `config` was created, replacing the record of an earlier
+    /// container of the same name and dropping the oldest beyond [`CREATED_HISTORY`]. Secrets
+    /// are kept only when passwords are remembered.
+    pub fn record_created(
+        &mut self,
+        database: Option<&DatabaseConfig>,
+        config: &DbContainerConfig,
+        created_at: i64,
+    ) {
+        let name = config.name.strip_prefix("db-mgr__").unwrap_or(&config.name);
+        self.created.retain(|created| created.name != name);
+        self.created.push(CreatedDefinition {
+            name: name.to_string(),
+            created_at,
+            definition: ContainerDefinition::from_config(config, database, self.remember_passwords),
+        });
+
+        let dropped = self.created.len().saturating_sub(CREATED_HISTORY);
+        self.created.drain(..dropped);
+    }
+
+    /// The recorded containers that no longer exist, newest first. `names` are the names of the
+    /// containers without the `db-mgr__` prefix.
+    pub fn deleted(&self, names: &[String]) -> Vec<&CreatedDefinition> {
+        self.created
+            .iter()
+            .rev()
+            .filter(|created| !names.contains(&created.name))
+            .collect()
+    }
+
+    /// The values remembered for `database`, none when remembering values is turned off.
+    pub fn recall(&self, database: &DatabaseConfig) -> Option<&RememberedValues> {
+        if !self.remember_values {
+            return None;
+        }
+
+        self.remembered.get(&database.name)
+    }
+}
+
+impl RememberedValues {
+    /// Fills `config` with the remembered values that still apply to `database`, returning the keys
+    /// of the variables that were filled in.
+    pub fn apply(&self, database: &DatabaseConfig, config: &mut DbContainerConfig) -> Vec<String> {
+        if database.tags.contains(&self.tag) {
+            config.tag = self.tag.clone();
+        }
+
+        database
+            .variables
+            .values()
+            .filter_map(|key| {
+                let value = self.variables.get(key)?;
+                config.variables.insert(key.clone(), value.clone());
+                Some(key.clone())
+            })
+            .collect()
+    }
+}
+
+pub fn read_preferences(paths: Option<&Paths>) -> Preferences {
+    let Some(path) = paths
+        .map(Paths::preferences_file)
+        .filter(|path| path.exists())
+    else {
+        return Preferences::default();
+    };
+
+    match File::open(path).map(serde_yaml::from_reader) {
         Err(ex) => {
-            eprintln!("Could not open config file {ex}");
+            eprintln!("Could not open preferences file {ex}");
+            Preferences::default()
+        }
+        Ok(Err(ex)) => {
+            eprintln!("Could not read preferences file {ex}");
+            Preferences::default()
+        }
+        Ok(Ok(preferences)) => preferences,
+    }
+}
+
+pub fn write_preferences(paths: Option<&Paths>, preferences: &Preferences) {
+    let Some(paths) = paths else {
+        return;
+    };
+
+    if let Err(ex) = fs::create_dir_all(&paths.data_dir) {
+        eprintln!("Could not create data directory {ex}");
+        return;
+    }
+
+    match serde_yaml::to_string(preferences) {
+        Err(ex) => eprintln!("Could not serialize preferences {ex}"),
+        Ok(contents) => {
+            if let Err(ex) = fs::write(paths.preferences_file(), contents) {
+                eprintln!("Could not write preferences file {ex}");
+            }
+        }
+    }
+}
+
+/// The saved create form, `None` without one or if it can't be read.
+pub fn read_draft(paths: Option<&Paths>) -> Option<Draft> {
+    let path = paths.map(Paths::draft_file).filter(|path| path.exists())?;
+
+    match File::open(path).map(serde_yaml::from_reader) {
+        Err(ex) => {
+            eprintln!("Could not open draft file {ex}");
+            None
+        }
+        Ok(Err(ex)) => {
+            eprintln!("Could not read draft file {ex}");
             None
         }
-        Ok(s) => Some(s),
+        Ok(Ok(draft)) => Some(draft),
     }
 }
 
-pub fn read_config_file() -> ConfigFile {
-    match get_file().map(serde_yaml::from_reader) {
-        None => serde_yaml::from_str(DEFAULT_CONFIG).unwrap(),
-        Some(Err(ex)) => {
-            eprintln!("Could not open read file {ex}");
-            serde_yaml::from_str(DEFAULT_CONFIG).unwrap()
+pub fn write_draft(paths: Option<&Paths>, draft: &Draft) {
+    let Some(paths) = paths else {
+        return;
+    };
+
+    if let Err(ex) = fs::create_dir_all(&paths.data_dir) {
+        eprintln!("Could not create data directory {ex}");
+        return;
+    }
+
+    match serde_yaml::to_string(draft) {
+        Err(ex) => eprintln!("Could not serialize draft {ex}"),
+        Ok(contents) => {
+            if let Err(ex) = fs::write(paths.draft_file(), contents) {
+                eprintln!("Could not write draft file {ex}");
+            }
         }
-        Some(Ok(file)) => file,
     }
 }
+
+/// Deletes the saved create form, after the container was created or the draft discarded.
+pub fn remove_draft(paths: Option<&Paths>) {
+    let Some(path) = paths.map(Paths::draft_file).filter(|path| path.exists()) else {
+        return;
+    };
+
+    if let Err(ex) = fs::remove_file(path) {
+        eprintln!("Could not remove draft file {ex}");
+    }
+}
+
+/// A file or directory db-mgr keeps its data in, shown in the settings.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AppPath {
+    pub name: &'static str,
+    pub path: PathBuf,
+}
+
+/// Opens `path` in the file manager, or the directory holding it if it is a file. The directory
+/// is created first if it doesn't exist yet.
+pub fn open_in_file_manager(path: &Path) -> anyhow::Result<()> {
+    let directory = match path.extension() {
+        Some(_) => path.parent().unwrap_or(path),
+        None => path,
+    };
+
+    fs::create_dir_all(directory)
+        .with_context(|| format!("Could not create {}", directory.display()))?;
+    // Explorer opens drive and network share folders alike, where the shell association can
+    // pick another program
+    #[cfg(windows)]
+    let opened = std::process::Command::new("explorer")
+        .arg(directory)
+        .spawn()
+        .map(|_| ());
+    #[cfg(not(windows))]
+    let opened = opener::open(directory);
+    opened.with_context(|| format!("Could not open {}", directory.display()))
+}
+
+/// Removes the entries named in `names` from the config file, so the picker only offers the
+/// rest. Rewriting the file drops its comments, so the original is kept as `config.yaml.bak`.
+pub fn remove_databases(paths: &Paths, names: &[String]) -> anyhow::Result<()> {
+    let path = config_path(paths).context("Could not create the config file")?;
+    let contents =
+        fs::read_to_string(&path).with_context(|| format!("Could not read {}", path.display()))?;
+    let mut config: Value = serde_yaml::from_str(&contents)
+        .with_context(|| format!("Could not parse {}", path.display()))?;
+    let Some(Value::Sequence(databases)) = config.get_mut("databases") else {
+        anyhow::bail!("{} has no databases", path.display());
+    };
+    databases.retain(|database| {
+        database
+            .get("name")
+            .and_then(Value::as_str)
+            .is_none_or(|name| !names.iter().any(|removed| removed == name))
+    });
+
+    let backup = path.with_extension("yaml.bak");
+    fs::write(&backup, &contents)
+        .with_context(|| format!("Could not back up {}", path.display()))?;
+    fs::write(&path, serde_yaml::to_string(&config)?)
+        .with_context(|| format!("Could not write {}", path.display()))
+}
+
+/// Opens the config file in the default editor, writing the default config first if needed.
+pub fn open_config_file(paths: &Paths) -> anyhow::Result<()> {
+    let path = config_path(paths).context("Could not create the config file")?;
+    opener::open(&path).with_context(|| format!("Could not open {}", path.display()))
+}
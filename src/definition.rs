@@ -0,0 +1,376 @@
+use std::collections::{BTreeMap, HashMap};
+
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    data::{is_secret, DatabaseConfig},
+    docker::{DbContainer, DbContainerConfig, LogSettings, Ulimit},
+    image_ref::ImageRef,
+    ports::PortAssignment,
+};
+
+/// The definition schema version this build reads and writes, kept apart from the config schema
+/// so either can change on its own.
+pub const DEFINITION_VERSION: u32 = 1;
+
+/// A container's setup without its name or data, shared as a file so someone else can create
+/// the same database.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ContainerDefinition {
+    pub version: u32,
+    /// The config entry the container was created from, matched again on import.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub database: Option<String>,
+    pub image: String,
+    pub tag: String,
+    #[serde(default)]
+    pub variables: BTreeMap<String, String>,
+    /// Variables whose values were left out, secrets unless included and values too large to
+    /// export. They have to be filled in on import.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub omitted: Vec<String>,
+    /// Volume names without the container prefix, with the paths they are mounted at.
+    #[serde(default)]
+    pub volumes: BTreeMap<String, String>,
+    /// The volumes mounted read-only, without the container prefix.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub read_only_volumes: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tmpfs: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tmpfs_size: Option<u64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub command: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shm_size_mb: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpuset_cpus: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extra_hosts: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ulimits: Vec<Ulimit>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub sysctls: BTreeMap<String, String>,
+    /// The GPUs given to the container, all of them when empty.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gpus: Option<Vec<String>>,
+    /// The hostname, container names and project below are only kept in the definitions recorded
+    /// when a container is created. An exported one may be created under another name or on
+    /// another machine, where they would be wrong.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hostname: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub companion_of: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub published_ports: Vec<u16>,
+    /// The published database port. Exported definitions leave the host port to be assigned
+    /// again, it may be taken on another machine.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub host_port: Option<PortAssignment>,
+}
+
+/// The name `database` shows the variable `key` under, the key itself without an entry.
+pub fn display_name(database: Option<&DatabaseConfig>, key: &str) -> String {
+    database
+        .and_then(|database| {
+            database
+                .variables
+                .iter()
+                .find(|(_, declared)| declared.as_str() == key)
+        })
+        .map(|(name, _)| name.clone())
+        .unwrap_or_else(|| key.to_string())
+}
+
+impl ContainerDefinition {
+    /// The definition of `container`, with the variables `database` declares or all of them for
+    /// containers without an entry. Secret values are only kept with `include_secrets`.
+    pub fn new(
+        container: &DbContainer,
+        database: Option<&DatabaseConfig>,
+        include_secrets: bool,
+    ) -> Self {
+        let name = container.name.trim_start_matches('/');
+        let image = ImageRef::parse(&container.image);
+
+        let extra = container.extra_variables();
+        let mut variables = BTreeMap::new();
+        let mut omitted = vec![];
+        for (key, value) in container.variables.iter() {
+            if database.is_some_and(|database| !database.declares(key)) && !extra.contains(key) {
+                continue;
+            }
+
+            match value.as_text() {
+                Some(_) if !include_secrets && is_secret(&display_name(database, key), key) => {
+                    omitted.push(key.clone())
+                }
+                Some(value) => _ = variables.insert(key.clone(), value.to_string()),
+                None => omitted.push(key.clone()),
+            }
+        }
+        omitted.sort();
+
+        let prefix = format!("db-mgr__{name}__");
+        let volumes = container
+            .volumes
+            .iter()
+            .map(|(volume, path)| {
+                let volume = volume.strip_prefix(&prefix).unwrap_or(volume);
+                (volume.to_string(), path.clone())
+            })
+            .collect();
+        let mut read_only_volumes = container
+            .read_only_volumes
+            .iter()
+            .map(|volume| volume.strip_prefix(&prefix).unwrap_or(volume).to_string())
+            .collect::<Vec<_>>();
+        read_only_volumes.sort();
+
+        Self {
+            version: DEFINITION_VERSION,
+            database: database.map(|database| database.name.clone()),
+            image: image.name(),
+            tag: image.reference().to_string(),
+            variables,
+            omitted,
+            volumes,
+            read_only_volumes,
+            tmpfs: container.tmpfs.clone(),
+            tmpfs_size: container.tmpfs_size,
+            command: container.command.clone(),
+            shm_size_mb: container.shm_size_mb,
+            cpuset_cpus: container.cpuset_cpus.clone(),
+            extra_hosts: container.extra_hosts.clone(),
+            user: container.user.clone(),
+            ulimits: container.ulimits.clone(),
+            sysctls: container.sysctls.clone().into_iter().collect(),
+            gpus: container.gpus.clone(),
+            hostname: None,
+            depends_on: vec![],
+            project: None,
+            companion_of: None,
+            published_ports: vec![],
+            host_port: container
+                .port_assignment()
+                .map(|assignment| PortAssignment::auto(assignment.container)),
+        }
+    }
+
+    /// The definition a container is created with from `config`, prepared as by the create form,
+    /// keeping everything needed to create it again. Secret values are only kept with
+    /// `include_secrets`.
+    pub fn from_config(
+        config: &DbContainerConfig,
+        database: Option<&DatabaseConfig>,
+        include_secrets: bool,
+    ) -> Self {
+        let image = ImageRef::parse(&config.image).with_reference(&config.tag);
+        let mut variables = BTreeMap::new();
+        let mut omitted = vec![];
+        for (key, value) in config.variables.iter() {
+            match include_secrets || !is_secret(&display_name(database, key), key) {
+                true => _ = variables.insert(key.clone(), value.clone()),
+                false => omitted.push(key.clone()),
+            }
+        }
+        omitted.sort();
+
+        let prefix = format!("{}__", config.name);
+        let unprefixed =
+            |volume: &String| volume.strip_prefix(&prefix).unwrap_or(volume).to_string();
+        let mut read_only_volumes = config
+            .read_only_volumes
+            .iter()
+            .map(unprefixed)
+            .collect::<Vec<_>>();
+        read_only_volumes.sort();
+
+        Self {
+            version: DEFINITION_VERSION,
+            database: Some(config.database.clone()),
+            image: image.name(),
+            tag: config.tag.clone(),
+            variables,
+            omitted,
+            volumes: config
+                .volumes
+                .iter()
+                .map(|(volume, path)| (unprefixed(volume), path.clone()))
+                .collect(),
+            read_only_volumes,
+            tmpfs: config.tmpfs.clone(),
+            tmpfs_size: config.tmpfs_size,
+            command: config.command.clone(),
+            shm_size_mb: config.shm_size_mb,
+            cpuset_cpus: config.cpuset_cpus.clone(),
+            extra_hosts: config.extra_hosts.clone(),
+            user: config.user.clone(),
+            ulimits: config.ulimits.clone(),
+            sysctls: config.sysctls.clone().into_iter().collect(),
+            gpus: config.gpus.clone(),
+            hostname: config.hostname.clone(),
+            depends_on: config.depends_on.clone(),
+            project: config.project.clone(),
+            companion_of: config.companion_of.clone(),
+            published_ports: config.published_ports.clone(),
+            host_port: config.host_port,
+        }
+    }
+
+    pub fn to_yaml(&self) -> anyhow::Result<String> {
+        Ok(serde_yaml::to_string(self)?)
+    }
+
+    /// Reads a definition, refusing ones written by a newer db-mgr.
+    pub fn from_yaml(yaml: &str) -> anyhow::Result<Self> {
+        let definition = serde_yaml::from_str::<Self>(yaml)
+            .map_err(|ex| anyhow!("This is not a container definition: {ex}"))?;
+        if definition.version > DEFINITION_VERSION {
+            return Err(anyhow!(
+                "This definition is version {}, this db-mgr reads up to version {DEFINITION_VERSION}, update db-mgr to import it",
+                definition.version
+            ));
+        }
+
+        Ok(definition)
+    }
+
+    /// The config entry for this definition's image, preferring the entry it was exported from.
+    pub fn database<'a>(&self, databases: &'a [DatabaseConfig]) -> Option<&'a DatabaseConfig> {
+        let image = ImageRef::parse(&self.image);
+        let matching = databases
+            .iter()
+            .filter(|database| database.matches(&image))
+            .collect::<Vec<_>>();
+
+        matching
+            .iter()
+            .find(|database| Some(&database.name) == self.database.as_ref())
+            .or(matching.first())
+            .copied()
+    }
+
+    /// An entry for images no config entry knows, showing every variable of the definition.
+    fn unknown_database(&self) -> DatabaseConfig {
+        let variables = self
+            .variables
+            .keys()
+            .chain(self.omitted.iter())
+            .map(|key| (key.clone(), key.clone()))
+            .collect::<HashMap<_, _>>();
+
+        DatabaseConfig {
+            name: self.database.clone().unwrap_or_else(|| self.image.clone()),
+            image: self.image.clone(),
+            icon_url: String::new(),
+            tags: vec![],
+            variables,
+            volumes: self.volumes.clone().into_iter().collect(),
+            command: self.command.clone(),
+            defaults: HashMap::new(),
+            query_command: vec![],
+            restore_command: vec![],
+            shm_size_mb: None,
+            cpuset_cpus: None,
+            dockerfile: None,
+            arbitrary_user: None,
+            connection_string: None,
+            backup_command: vec![],
+            data_loss_warning: true,
+            env_aliases: HashMap::new(),
+            read_only_volumes: vec![],
+            volume_options: HashMap::new(),
+            ulimits: vec![],
+            sysctls: HashMap::new(),
+            companions: vec![],
+            gpu_capable: false,
+            port: self.host_port.map(|assignment| assignment.container),
+            stop_timeout_secs: None,
+            version_pattern: None,
+            eol_dates: HashMap::new(),
+            notes: None,
+            tag_notes: HashMap::new(),
+        }
+    }
+
+    /// The create form contents for this definition, unnamed, with the entry it matched or one
+    /// made up for an unknown image. Warnings about the mismatches come last.
+    pub fn into_config(
+        self,
+        databases: &[DatabaseConfig],
+    ) -> (DbContainerConfig, DatabaseConfig, Vec<String>) {
+        let mut warnings = vec![];
+        let database = match self.database(databases) {
+            Some(database) => database.clone(),
+            None => {
+                warnings.push(format!(
+                    "{} is not in config.yaml, the definition is used as is",
+                    self.image
+                ));
+                self.unknown_database()
+            }
+        };
+        if !database.tags.is_empty() && !database.tags.contains(&self.tag) {
+            warnings.push(format!(
+                "{} is not one of the tags configured for {}",
+                self.tag, database.name
+            ));
+        }
+        if !self.omitted.is_empty() {
+            warnings.push(format!(
+                "Fill in {}, their values were not exported",
+                self.omitted.join(", ")
+            ));
+        }
+
+        let extra_variables = self
+            .variables
+            .keys()
+            .filter(|key| !database.declares(key))
+            .cloned()
+            .collect();
+        let config = DbContainerConfig {
+            name: String::new(),
+            database: database.name.clone(),
+            variables: self.variables.into_iter().collect(),
+            image: database.image_name(),
+            volumes: self.volumes.into_iter().collect(),
+            tag: self.tag,
+            command: self.command,
+            reuse_volumes: false,
+            force_shared_volume: false,
+            start_after_create: true,
+            dry_run: false,
+            shm_size_mb: self.shm_size_mb,
+            cpuset_cpus: self.cpuset_cpus,
+            tmpfs: self.tmpfs,
+            tmpfs_size: self.tmpfs_size,
+            hostname: self.hostname,
+            extra_hosts: self.extra_hosts,
+            user: self.user,
+            build: database.image_build(),
+            depends_on: self.depends_on,
+            extra_variables,
+            read_only_volumes: self.read_only_volumes,
+            volume_options: HashMap::new(),
+            project: self.project,
+            ulimits: self.ulimits,
+            sysctls: self.sysctls.into_iter().collect(),
+            companion_of: self.companion_of,
+            published_ports: self.published_ports,
+            host_port: self.host_port,
+            gpus: self.gpus,
+            log: LogSettings::default(),
+        };
+
+        (config, database, warnings)
+    }
+}
@@ -0,0 +1,128 @@
+use std::collections::{HashMap, HashSet};
+
+use itertools::Itertools;
+
+use crate::docker::DbContainer;
+
+/// The containers each container depends on, keyed by container name without the leading `/`.
+pub type Graph = HashMap<String, Vec<String>>;
+
+pub fn graph(containers: &[DbContainer]) -> Graph {
+    containers
+        .iter()
+        .map(|container| {
+            (
+                container.name.trim_start_matches('/').to_string(),
+                container.depends_on.clone(),
+            )
+        })
+        .collect()
+}
+
+/// The containers that depend on `name`, directly or through other containers, sorted by name.
+pub fn dependents(graph: &Graph, name: &str) -> Vec<String> {
+    let mut found = HashSet::new();
+    let mut queue = vec![name.to_string()];
+    while let Some(current) = queue.pop() {
+        for (dependent, depends_on) in graph.iter() {
+            if depends_on.contains(&current) && dependent != name && found.insert(dependent) {
+                queue.push(dependent.clone());
+            }
+        }
+    }
+
+    found.into_iter().cloned().sorted().collect()
+}
+
+/// Orders `names` so every container comes after the containers of `names` that depend on it,
+/// the order they can be stopped in. Fails with the names forming a cycle if there is one.
+pub fn stop_order(graph: &Graph, names: &[String]) -> Result<Vec<String>, Vec<String>> {
+    fn visit(
+        graph: &Graph,
+        names: &[String],
+        name: &String,
+        path: &mut Vec<String>,
+        order: &mut Vec<String>,
+    ) -> Result<(), Vec<String>> {
+        if order.contains(name) {
+            return Ok(());
+        }
+        if let Some(start) = path.iter().position(|visiting| visiting == name) {
+            return Err(path[start..].to_vec());
+        }
+
+        path.push(name.clone());
+        for dependent in names.iter().filter(|dependent| {
+            graph
+                .get(*dependent)
+                .is_some_and(|depends_on| depends_on.contains(name))
+        }) {
+            visit(graph, names, dependent, path, order)?;
+        }
+        path.pop();
+        order.push(name.clone());
+
+        Ok(())
+    }
+
+    let mut order = vec![];
+    for name in names.iter().sorted() {
+        visit(graph, names, name, &mut vec![], &mut order)?;
+    }
+
+    Ok(order)
+}
+
+/// Groups `names` into layers that can be started one after the other, every container after
+/// the containers of `names` it depends on. Containers it depends on outside of `names` are
+/// left to the user. Fails with why the order can't be trusted, when they depend on each other
+/// in a cycle or on a container that doesn't exist.
+pub fn start_layers(graph: &Graph, names: &[String]) -> Result<Vec<Vec<String>>, String> {
+    let missing = names
+        .iter()
+        .sorted()
+        .flat_map(|name| {
+            graph
+                .get(name)
+                .into_iter()
+                .flatten()
+                .filter(|dependency| !graph.contains_key(*dependency))
+                .map(move |dependency| {
+                    format!("{name} depends on {dependency}, which doesn't exist")
+                })
+        })
+        .collect::<Vec<_>>();
+    if !missing.is_empty() {
+        return Err(missing.join(", "));
+    }
+
+    // Stopping in reverse starts the dependencies first
+    let order = stop_order(graph, names)
+        .map_err(|cycle| format!("Containers depend on each other: {}", cycle.join(" -> ")))?;
+    let mut layers: HashMap<&String, usize> = HashMap::new();
+    for name in order.iter().rev() {
+        let layer = graph
+            .get(name)
+            .into_iter()
+            .flatten()
+            .filter_map(|dependency| layers.get(dependency))
+            .map(|layer| layer + 1)
+            .max()
+            .unwrap_or(0);
+        layers.insert(name, layer);
+    }
+
+    Ok(layers
+        .into_iter()
+        .into_group_map_by(|(_, layer)| *layer)
+        .into_iter()
+        .sorted()
+        .map(|(_, names)| {
+            names
+                .into_iter()
+                .map(|(name, _)| name.clone())
+                .sorted()
+                .collect()
+        })
+        .collect())
+}
@@ -0,0 +1,66 @@
+use std::collections::{BTreeSet, HashMap};
+
+use crate::data::is_secret;
+
+const MASK: &str = "••••••";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Change {
+    /// Set by the template but missing from the container.
+    Added,
+    /// Set on the container but not by the template.
+    Removed,
+    Changed,
+    Unchanged,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiffRow {
+    pub key: String,
+    pub container: Option<String>,
+    pub template: Option<String>,
+    pub change: Change,
+}
+
+impl DiffRow {
+    /// The container and template values as they should be shown, secrets are masked.
+    pub fn display_values(&self) -> (String, String) {
+        let display = |value: &Option<String>| match value {
+            None => "-".to_string(),
+            Some(_) if is_secret(&self.key, &self.key) => MASK.to_string(),
+            Some(value) => value.clone(),
+        };
+
+        (display(&self.container), display(&self.template))
+    }
+}
+
+/// Compares the variables of a container with the ones its template would produce, sorted by key.
+pub fn diff(
+    container: &HashMap<String, String>,
+    template: &HashMap<String, String>,
+) -> Vec<DiffRow> {
+    container
+        .keys()
+        .chain(template.keys())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .map(|key| {
+            let container = container.get(key).cloned();
+            let template = template.get(key).cloned();
+            let change = match (&container, &template) {
+                (None, _) => Change::Added,
+                (_, None) => Change::Removed,
+                (Some(a), Some(b)) if a == b => Change::Unchanged,
+                _ => Change::Changed,
+            };
+
+            DiffRow {
+                key: key.clone(),
+                container,
+                template,
+                change,
+            }
+        })
+        .collect()
+}
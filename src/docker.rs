@@ -1,25 +1,388 @@
 use anyhow::anyhow;
 use bollard::{
-    container::{Config, CreateContainerOptions, ListContainersOptions},
+    container::{
+        Config, CreateContainerOptions, KillContainerOptions, ListContainersOptions, LogOutput,
+        LogsOptions, RemoveContainerOptions, StopContainerOptions, UpdateContainerOptions,
+    },
     errors::Error,
-    image::CreateImageOptions,
-    service::{ContainerStateStatusEnum, HostConfig, Mount, MountTypeEnum},
-    volume::CreateVolumeOptions,
+    exec::{CreateExecOptions, StartExecResults},
+    image::{BuildImageOptions, CreateImageOptions, ListImagesOptions, RemoveImageOptions},
+    network::{ConnectNetworkOptions, CreateNetworkOptions},
+    service::{
+        BuildInfo, ContainerInspectResponse, ContainerState, ContainerStateStatusEnum,
+        ContainerSummary, CreateImageInfo, DeviceRequest, EndpointSettings, EventMessage,
+        EventMessageTypeEnum, GenericResources, Health, HealthStatusEnum, HostConfig,
+        HostConfigLogConfig, ImageInspect, Mount, MountPointTypeEnum, MountTmpfsOptions,
+        MountTypeEnum, PortBinding, PortMap, ResourcesUlimits, RestartPolicy,
+        RestartPolicyNameEnum, Volume,
+    },
+    system::EventsOptions,
+    volume::{CreateVolumeOptions, ListVolumesOptions},
     Docker,
 };
+use chrono::{DateTime, Utc};
 use futures::{
-    channel::mpsc::{channel, Receiver},
-    stream, FutureExt, SinkExt, StreamExt,
+    channel::mpsc::{channel, Receiver, Sender},
+    executor,
+    future::{self, BoxFuture, Either},
+    sink,
+    stream::{self, BoxStream},
+    FutureExt, Sink, SinkExt, Stream, StreamExt,
+};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    pin::Pin,
+    sync::Mutex,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+use crate::{
+    daemon::{change_timeout, daemon_status, query_timeout, with_timeout},
+    image_ref::ImageRef,
+    platform::{daemon_bind_source, is_host_path, is_windows_path, DaemonPlatform},
+    ports::PortAssignment,
 };
-use std::collections::HashMap;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct DbContainerConfig {
     pub name: String,
+    pub database: String,
     pub variables: HashMap<String, String>,
     pub image: String,
-    pub voluems: HashMap<String, String>,
+    pub volumes: HashMap<String, String>,
     pub tag: String,
+    pub command: Vec<String>,
+    /// Mount existing db-mgr volumes instead of failing, used when recreating a container.
+    pub reuse_volumes: bool,
+    /// Create the container even if one of its volumes is mounted by another container.
+    pub force_shared_volume: bool,
+    /// Start the container once it is created, otherwise it is left stopped.
+    pub start_after_create: bool,
+    /// Only lists the docker calls creating the container would make, see [`plan_container`].
+    pub dry_run: bool,
+    /// Size of `/dev/shm` in MB, docker's default when `None`.
+    pub shm_size_mb: Option<u64>,
+    /// CPUs the container may run on, such as `0-3`.
+    pub cpuset_cpus: Option<String>,
+    /// Paths mounted as tmpfs, for throwaway containers whose data should live in memory.
+    pub tmpfs: Vec<String>,
+    /// Size limit of each tmpfs mount in bytes, docker's default when `None`.
+    pub tmpfs_size: Option<u64>,
+    pub hostname: Option<String>,
+    /// Extra `/etc/hosts` entries such as `api:10.0.0.5`.
+    pub extra_hosts: Vec<String>,
+    /// The user the container runs as, a name or `uid:gid`, the image's user when `None`.
+    pub user: Option<String>,
+    /// Builds the image instead of pulling it.
+    pub build: Option<ImageBuild>,
+    /// Names of the containers this one needs running.
+    pub depends_on: Vec<String>,
+    /// Variables set by the user beyond the ones the config entry declares.
+    pub extra_variables: Vec<String>,
+    /// Names of the volumes mounted read-only, such as ones holding seed data.
+    pub read_only_volumes: Vec<String>,
+    /// Driver options of the volumes, keyed by volume name, given to docker when they are created.
+    pub volume_options: HashMap<String, HashMap<String, String>>,
+    /// The project the container belongs to, written as [`PROJECT_LABEL`].
+    pub project: Option<String>,
+    /// Resource limits such as `nofile`, docker's defaults for the ones left out.
+    pub ulimits: Vec<Ulimit>,
+    /// Namespaced kernel parameters such as `net.core.somaxconn`.
+    pub sysctls: HashMap<String, String>,
+    /// The container this one is the admin UI of, written as [`COMPANION_LABEL`]. Both are put on
+    /// the [`companion_network`] of the parent.
+    pub companion_of: Option<String>,
+    /// Ports inside the container published on a free port of `127.0.0.1`.
+    pub published_ports: Vec<u16>,
+    /// The database port, published on the same host port every time the container is created.
+    pub host_port: Option<PortAssignment>,
+    /// The GPUs given to the container through the nvidia runtime, all of them when empty and
+    /// none when `None`.
+    pub gpus: Option<Vec<String>>,
+    /// Where docker writes the container's output, rotated json-file logs by default.
+    pub log: LogSettings,
+}
+
+/// The logging driver of a container and its options, such as `max-size`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LogSettings {
+    pub driver: String,
+    pub options: HashMap<String, String>,
+}
+
+/// The drivers offered when creating a container.
+pub const LOG_DRIVERS: [&str; 5] = ["json-file", "local", "journald", "syslog", "none"];
+
+/// `json-file` rotated at 10 MB with 3 files kept, so logs can't fill the disk.
+impl Default for LogSettings {
+    fn default() -> Self {
+        Self {
+            driver: "json-file".to_string(),
+            options: HashMap::from([
+                ("max-size".to_string(), "10m".to_string()),
+                ("max-file".to_string(), "3".to_string()),
+            ]),
+        }
+    }
+}
+
+impl LogSettings {
+    /// Whether the driver understands the `max-size` and `max-file` options.
+    pub fn rotates(&self) -> bool {
+        matches!(self.driver.as_str(), "json-file" | "local")
+    }
+
+    pub fn option(&self, key: &str) -> Option<&str> {
+        self.options.get(key).map(String::as_str)
+    }
+
+    /// Sets `key`, removing it when `value` is empty.
+    pub fn set_option(&mut self, key: &str, value: &str) {
+        match value.trim() {
+            "" => self.options.remove(key),
+            value => self.options.insert(key.to_string(), value.to_string()),
+        };
+    }
+}
+
+/// Such as `json-file, max-file 3, max-size 10m`.
+impl Display for LogSettings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.driver)?;
+        for (key, value) in self.options.iter().sorted() {
+            write!(f, ", {key} {value}")?;
+        }
+        Ok(())
+    }
+}
+
+/// How much of a container's output docker can read back for the logs tab.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogSupport {
+    /// The driver stores the logs where docker reads them.
+    Full,
+    /// The driver sends the logs elsewhere, docker only reads the recent ones from its dual
+    /// logging cache, which can be turned off in the daemon config.
+    Cached,
+    /// Nothing is kept.
+    Unavailable,
+}
+
+/// Whether the logs of containers using `driver` can be read through the logs API.
+pub fn log_support(driver: &str) -> LogSupport {
+    match driver {
+        // Empty when docker didn't report it, the daemon's default is json-file
+        "" | "json-file" | "local" | "journald" => LogSupport::Full,
+        "none" => LogSupport::Unavailable,
+        _ => LogSupport::Cached,
+    }
+}
+
+/// What docker does when a container exits, `--restart` on the command line.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Restart {
+    #[default]
+    Never,
+    Always,
+    /// Always, unless it was stopped by hand.
+    UnlessStopped,
+    /// When it exits with a non-zero code.
+    OnFailure,
+}
+
+impl Restart {
+    pub const ALL: [Restart; 4] = [
+        Restart::Never,
+        Restart::Always,
+        Restart::UnlessStopped,
+        Restart::OnFailure,
+    ];
+
+    fn from_policy(policy: &RestartPolicy) -> Self {
+        match policy.name {
+            Some(RestartPolicyNameEnum::ALWAYS) => Restart::Always,
+            Some(RestartPolicyNameEnum::UNLESS_STOPPED) => Restart::UnlessStopped,
+            Some(RestartPolicyNameEnum::ON_FAILURE) => Restart::OnFailure,
+            _ => Restart::Never,
+        }
+    }
+
+    fn policy(self) -> RestartPolicy {
+        RestartPolicy {
+            name: Some(match self {
+                Restart::Never => RestartPolicyNameEnum::NO,
+                Restart::Always => RestartPolicyNameEnum::ALWAYS,
+                Restart::UnlessStopped => RestartPolicyNameEnum::UNLESS_STOPPED,
+                Restart::OnFailure => RestartPolicyNameEnum::ON_FAILURE,
+            }),
+            maximum_retry_count: None,
+        }
+    }
+}
+
+impl Display for Restart {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Restart::Never => "Never",
+                Restart::Always => "Always",
+                Restart::UnlessStopped => "Unless stopped",
+                Restart::OnFailure => "On failure",
+            }
+        )
+    }
+}
+
+/// The settings of a running container `docker update` changes without recreating it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ContainerLimits {
+    /// In bytes, `None` without a limit.
+    pub memory: Option<i64>,
+    /// In billionths of a CPU, `None` without a limit.
+    pub nano_cpus: Option<i64>,
+    pub restart: Restart,
+}
+
+/// The limits of an inspect's host config, docker reports 0 for the ones that aren't set.
+fn parse_limits(host_config: &HostConfig) -> ContainerLimits {
+    ContainerLimits {
+        memory: host_config.memory.filter(|memory| *memory > 0),
+        nano_cpus: host_config.nano_cpus.filter(|nano_cpus| *nano_cpus > 0),
+        restart: host_config
+            .restart_policy
+            .as_ref()
+            .map(Restart::from_policy)
+            .unwrap_or_default(),
+    }
+}
+
+/// How a container last exited and how often its restart policy brought it back, from its
+/// inspect.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ContainerExit {
+    /// The kernel killed it for using more memory than it may.
+    pub oom_killed: bool,
+    /// The exit code of its last run, `None` if it never exited.
+    pub exit_code: Option<i64>,
+    /// The restarts by its restart policy since it was last started by hand.
+    pub restart_count: i64,
+}
+
+/// The exit of an inspected container, docker reports the year 1 as the end of runs that never
+/// ended.
+fn parse_exit(state: Option<&ContainerState>, restart_count: Option<i64>) -> ContainerExit {
+    ContainerExit {
+        oom_killed: state.and_then(|state| state.oom_killed).unwrap_or(false),
+        exit_code: state
+            .filter(|state| {
+                state
+                    .finished_at
+                    .as_deref()
+                    .is_some_and(|at| !at.starts_with("0001-"))
+            })
+            .and_then(|state| state.exit_code),
+        restart_count: restart_count.unwrap_or(0),
+    }
+}
+
+/// The logging settings of an inspect's host config, `None` if docker left them out.
+fn parse_log_config(log_config: &HostConfigLogConfig) -> Option<LogSettings> {
+    Some(LogSettings {
+        driver: log_config.typ.clone()?,
+        options: log_config.config.clone().unwrap_or_default(),
+    })
+}
+
+/// The request giving a container the GPUs `device_ids` through the nvidia runtime, all of them
+/// when it is empty. The same as `--gpus` on the command line.
+fn gpu_request(device_ids: Vec<String>) -> DeviceRequest {
+    DeviceRequest {
+        driver: Some("nvidia".to_string()),
+        count: device_ids.is_empty().then_some(-1),
+        device_ids: Some(device_ids).filter(|device_ids| !device_ids.is_empty()),
+        capabilities: Some(vec![vec!["gpu".to_string()]]),
+        options: None,
+    }
+}
+
+/// The GPUs granted by the device requests of an inspect's host config, all of them when empty,
+/// `None` without a GPU request.
+pub fn parse_gpu_requests(requests: &[DeviceRequest]) -> Option<Vec<String>> {
+    requests
+        .iter()
+        .find(|request| {
+            request
+                .capabilities
+                .iter()
+                .flatten()
+                .any(|capabilities| capabilities.iter().any(|capability| capability == "gpu"))
+        })
+        .map(|request| request.device_ids.clone().unwrap_or_default())
+}
+
+/// The GPUs as shown to the user, `all` when every GPU is given.
+pub fn describe_gpus(gpus: &[String]) -> String {
+    match gpus.is_empty() {
+        true => "all".to_string(),
+        false => gpus.join(", "),
+    }
+}
+
+/// The GPUs the daemon advertises as generic resources, by UUID or else by index.
+pub fn parse_gpus(resources: &GenericResources) -> Vec<String> {
+    let is_gpu = |kind: &Option<String>| {
+        kind.as_deref()
+            .is_some_and(|kind| kind.to_lowercase().contains("gpu"))
+    };
+
+    let named = resources
+        .named_resource_spec
+        .iter()
+        .filter(|spec| is_gpu(&spec.kind))
+        .filter_map(|spec| spec.value.clone());
+    let discrete = resources
+        .discrete_resource_spec
+        .iter()
+        .filter(|spec| is_gpu(&spec.kind))
+        .flat_map(|spec| 0..spec.value.unwrap_or_default())
+        .map(|index| index.to_string());
+    named.chain(discrete).collect()
+}
+
+/// A resource limit of a container, such as `nofile` with its soft and hard limits. `-1` means
+/// unlimited.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Ulimit {
+    pub name: String,
+    pub soft: i64,
+    pub hard: i64,
+}
+
+/// A Dockerfile to build a custom image from, such as one adding extensions to the stock image.
+/// Either written inline or as `path:`, relative paths are resolved against the config directory.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum Dockerfile {
+    Inline(String),
+    File { path: PathBuf },
+}
+
+/// A custom image built before the container is created and tagged with its image and tag.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ImageBuild {
+    pub dockerfile: Dockerfile,
+    /// Build arguments, `TAG` is added from the container's tag.
+    pub args: HashMap<String, String>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -27,219 +390,3491 @@ pub struct DbContainer {
     pub id: String,
     pub name: String,
     pub state: ContainerStateStatusEnum,
-    pub variables: HashMap<String, String>,
+    pub variables: HashMap<String, EnvValue>,
     pub image: String,
     pub volumes: HashMap<String, String>,
+    pub command: Vec<String>,
+    pub labels: HashMap<String, String>,
+    pub shm_size_mb: Option<u64>,
+    pub cpuset_cpus: Option<String>,
+    pub tmpfs: Vec<String>,
+    pub tmpfs_size: Option<u64>,
+    /// The hostname, unless it is the one docker generates from the id.
+    pub hostname: Option<String>,
+    pub extra_hosts: Vec<String>,
+    /// The user it runs as, unless it uses the image's user.
+    pub user: Option<String>,
+    /// Docker returned incomplete data, so the state, env or mounts may be missing.
+    pub partial: bool,
+    /// Names of the containers this one needs running, from [`DEPENDS_ON_LABEL`].
+    pub depends_on: Vec<String>,
+    /// The networks the container is attached to, sorted by name.
+    pub networks: Vec<NetworkAttachment>,
+    /// Names of the volumes mounted read-only.
+    pub read_only_volumes: Vec<String>,
+    /// The ports published on the host, sorted, empty while it is stopped.
+    pub ports: Vec<PublishedPort>,
+    /// Sorted by name.
+    pub ulimits: Vec<Ulimit>,
+    pub sysctls: HashMap<String, String>,
+    /// The result of the health check, `None` for images without one.
+    pub health: Option<HealthStatusEnum>,
+    /// The last runs of the health check, newest first, see [`parse_health_log`].
+    pub health_log: Vec<HealthProbe>,
+    /// The GPUs it was given, all of them when empty and none when `None`.
+    pub gpus: Option<Vec<String>>,
+    /// Its logging driver, `None` when docker didn't report it.
+    pub log: Option<LogSettings>,
+    pub limits: ContainerLimits,
+    pub exit: ContainerExit,
 }
 
-const LABEL: &str = "db-mgr-resource";
+/// The health of a container from the status of a container summary, such as
+/// `Up 5 minutes (unhealthy)`.
+pub fn parse_summary_health(status: &str) -> Option<HealthStatusEnum> {
+    if status.ends_with("(unhealthy)") {
+        Some(HealthStatusEnum::UNHEALTHY)
+    } else if status.ends_with("(healthy)") {
+        Some(HealthStatusEnum::HEALTHY)
+    } else if status.ends_with("(health: starting)") {
+        Some(HealthStatusEnum::STARTING)
+    } else {
+        None
+    }
+}
+
+/// A run of the health check, from the few docker keeps in the container's state.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HealthProbe {
+    pub start: Option<DateTime<Utc>>,
+    pub end: Option<DateTime<Utc>>,
+    /// 0 passed, 1 and 2 failed, anything else means the probe itself couldn't run.
+    pub exit_code: Option<i64>,
+    pub output: String,
+}
 
-async fn create_volume(docker: &Docker, name: &str) -> anyhow::Result<()> {
-    match docker.inspect_volume(name).await {
-        Err(Error::DockerResponseServerError {
-            status_code: 404, ..
-        }) => {}
-        Ok(_) => return Err(anyhow!("Container name conflict {}", name)),
-        Err(resp) => return Err(anyhow!(resp)),
+impl HealthProbe {
+    pub fn passed(&self) -> bool {
+        self.exit_code == Some(0)
+    }
+
+    pub fn duration(&self) -> Option<Duration> {
+        (self.end? - self.start?).to_std().ok()
+    }
+}
+
+/// The health check runs of an inspected container, newest first. Empty for images without a
+/// health check and containers it hasn't run in yet.
+pub fn parse_health_log(health: Option<&Health>) -> Vec<HealthProbe> {
+    let parse_time = |time: Option<&String>| {
+        DateTime::parse_from_rfc3339(time?)
+            .ok()
+            .map(|time| time.with_timezone(&Utc))
     };
 
-    docker
-        .create_volume(CreateVolumeOptions {
-            name,
-            labels: HashMap::from([(LABEL, "volume")]),
-            ..Default::default()
+    let mut probes = health
+        .and_then(|health| health.log.as_ref())
+        .into_iter()
+        .flatten()
+        .map(|result| HealthProbe {
+            start: parse_time(result.start.as_ref()),
+            end: parse_time(result.end.as_ref()),
+            exit_code: result.exit_code,
+            output: result
+                .output
+                .clone()
+                .unwrap_or_default()
+                .trim_end()
+                .to_string(),
         })
-        .await?;
+        .collect::<Vec<_>>();
+    // Docker appends to the log, reversing first keeps runs without a start newest first too
+    probes.reverse();
+    probes.sort_by_key(|probe| Reverse(probe.start));
+    probes
+}
 
-    Ok(())
+/// A port of a container published on the host.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct PublishedPort {
+    /// The port inside the container.
+    pub port: u16,
+    pub protocol: String,
+    pub host_ip: String,
+    pub host_port: u16,
+}
+
+/// The published ports of an inspect's port map, keyed by `port/protocol`, sorted. Ports exposed
+/// without a host binding are left out.
+pub fn parse_ports(ports: &PortMap) -> Vec<PublishedPort> {
+    ports
+        .iter()
+        .flat_map(|(key, bindings)| {
+            let (port, protocol) = key.split_once('/').unwrap_or((key, "tcp"));
+            let port = port.parse::<u16>().ok();
+            bindings.iter().flatten().filter_map(move |binding| {
+                Some(PublishedPort {
+                    port: port?,
+                    protocol: protocol.to_string(),
+                    host_ip: binding.host_ip.clone().unwrap_or_default(),
+                    host_port: binding.host_port.as_ref()?.parse().ok()?,
+                })
+            })
+        })
+        .sorted()
+        .collect()
+}
+
+impl DbContainer {
+    /// The registry digest of the image it was created from, see [`IMAGE_DIGEST_LABEL`].
+    pub fn image_digest(&self) -> Option<&str> {
+        self.labels.get(IMAGE_DIGEST_LABEL).map(String::as_str)
+    }
+
+    /// The variables set beyond the declared ones, see [`EXTRA_VARIABLES_LABEL`].
+    pub fn extra_variables(&self) -> Vec<String> {
+        self.labels
+            .get(EXTRA_VARIABLES_LABEL)
+            .map(|keys| {
+                keys.split(',')
+                    .filter(|key| !key.is_empty())
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// The project it was created in, see [`PROJECT_LABEL`].
+    pub fn project(&self) -> Option<&str> {
+        self.labels
+            .get(PROJECT_LABEL)
+            .map(String::as_str)
+            .filter(|project| !project.is_empty())
+    }
+
+    /// Whether it was created without volumes, see [`EPHEMERAL_LABEL`].
+    pub fn ephemeral(&self) -> bool {
+        self.labels.contains_key(EPHEMERAL_LABEL)
+    }
+
+    /// The ports inside the container that are published, empty while it is stopped.
+    pub fn published_ports(&self) -> Vec<u16> {
+        self.ports.iter().map(|port| port.port).dedup().collect()
+    }
+
+    /// The name of the container it is the admin UI of, see [`COMPANION_LABEL`].
+    pub fn companion_of(&self) -> Option<&str> {
+        self.labels
+            .get(COMPANION_LABEL)
+            .map(String::as_str)
+            .filter(|parent| !parent.is_empty())
+    }
+
+    /// The host port its database port was assigned, see [`HOST_PORT_LABEL`]. Docker may have
+    /// bound another one, the bound ports are in [`DbContainer::ports`].
+    pub fn port_assignment(&self) -> Option<PortAssignment> {
+        self.labels.get(HOST_PORT_LABEL)?.parse().ok()
+    }
+}
+
+/// A network a container is attached to and how other containers on it reach it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NetworkAttachment {
+    pub name: String,
+    /// `None` while the container is stopped.
+    pub ip: Option<String>,
+    /// The DNS names of the container on the network, empty on the default bridge.
+    pub aliases: Vec<String>,
+}
+
+impl NetworkAttachment {
+    /// The host other containers on the network connect to, the first alias or else the IP.
+    pub fn host(&self) -> Option<&str> {
+        self.aliases
+            .first()
+            .or(self.ip.as_ref())
+            .map(String::as_str)
+    }
+}
+
+/// How many of `containers` are running.
+pub fn running_count(containers: &[DbContainer]) -> usize {
+    containers
+        .iter()
+        .filter(|container| container.state == ContainerStateStatusEnum::RUNNING)
+        .count()
+}
+
+/// Values longer than this are not kept in memory, they are fetched again when asked for.
+pub const MAX_ENV_VALUE_LEN: usize = 4 * 1024;
+
+/// The value of an environment variable read back from a container.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EnvValue {
+    Text(String),
+    /// A value too large or too binary to show inline, holding its length in bytes.
+    LargeOrBinary(usize),
+}
+
+impl EnvValue {
+    pub fn new(value: &str) -> Self {
+        let binary = value
+            .chars()
+            .any(|c| c == char::REPLACEMENT_CHARACTER || (c.is_control() && !c.is_whitespace()));
+
+        if binary || value.len() > MAX_ENV_VALUE_LEN {
+            Self::LargeOrBinary(value.len())
+        } else {
+            Self::Text(value.to_string())
+        }
+    }
+
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            Self::Text(value) => Some(value),
+            Self::LargeOrBinary(_) => None,
+        }
+    }
+}
+
+/// Splits a `KEY=value` entry of a container's env.
+///
+/// Only the first `=` separates the name, so `KEY=a=b` has the value `a=b`. `KEY` and `KEY=` both
+/// give an empty value, entries without a name such as `=value` are skipped.
+pub fn split_env_entry(entry: &str) -> Option<(&str, &str)> {
+    let (name, value) = entry.split_once('=').unwrap_or((entry, ""));
+    if name.is_empty() {
+        return None;
+    }
+
+    Some((name, value))
+}
+
+/// Reads a `KEY=value` entry of a container's env as [`split_env_entry`] splits it.
+pub fn parse_env_entry(entry: &str) -> Option<(String, EnvValue)> {
+    let (name, value) = split_env_entry(entry)?;
+
+    Some((name.to_string(), EnvValue::new(value)))
+}
+
+pub const LABEL: &str = "db-mgr-resource";
+
+/// Records the version of the label scheme a container was created with, see
+/// [`LABEL_SCHEMA_VERSION`].
+pub const SCHEMA_LABEL: &str = "db-mgr-schema";
+
+/// The name of the config entry a container was created from.
+pub const DATABASE_LABEL: &str = "db-mgr-database";
+
+/// The comma separated names of the containers a container needs running.
+pub const DEPENDS_ON_LABEL: &str = "db-mgr-depends-on";
+
+/// A hash of the Dockerfile and arguments a built image was created from, it is only rebuilt
+/// when they change.
+pub const DOCKERFILE_HASH_LABEL: &str = "db-mgr-dockerfile-hash";
+
+/// The registry digest of the image a container was created from, the tag is checked against it
+/// for updates. Built images have none.
+pub const IMAGE_DIGEST_LABEL: &str = "db-mgr-image-digest";
+
+/// The comma separated variables set beyond the ones the config entry declares, they are kept
+/// when the container is recreated.
+pub const EXTRA_VARIABLES_LABEL: &str = "db-mgr-extra-variables";
+
+/// Set on containers created without volumes, whose data is lost when they are removed.
+pub const EPHEMERAL_LABEL: &str = "db-mgr-ephemeral";
+
+/// The project a container belongs to, such as the app it is the database of.
+pub const PROJECT_LABEL: &str = "db-mgr-project";
+
+/// The name of the container a companion, such as Adminer, is the admin UI of.
+pub const COMPANION_LABEL: &str = "db-mgr-companion-of";
+
+/// The database port of a container and the host port it was assigned, such as `5432:54000`,
+/// the port is kept when the container is recreated.
+pub const HOST_PORT_LABEL: &str = "db-mgr-host-port";
+
+/// The tag of the image whose container created a volume, to tell when its data directory is
+/// reused by another major version.
+pub const VOLUME_TAG_LABEL: &str = "db-mgr-image-tag";
+
+/// The network a container shares with its companions, which reach it by its name.
+pub fn companion_network(parent: &str) -> String {
+    format!("{parent}__companions")
+}
+
+/// Bumped whenever the labels written at creation change, containers with an older version are
+/// offered a metadata update.
+pub const LABEL_SCHEMA_VERSION: u32 = 1;
+
+/// A volume a new container would mount that is already mounted by another container.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VolumeConflict {
+    pub volume: String,
+    pub container: String,
+}
+
+impl Display for VolumeConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Volume {} is already mounted by {}, sharing a data volume corrupts most databases",
+            self.volume,
+            self.container.trim_start_matches('/')
+        )
+    }
+}
+
+/// Finds the volumes in `mounts` (volume name to mount path) that one of `containers` already
+/// mounts, sorted by volume name.
+pub fn shared_volumes(
+    mounts: &HashMap<String, String>,
+    containers: &[DbContainer],
+) -> Vec<VolumeConflict> {
+    mounts
+        .keys()
+        .sorted()
+        .flat_map(|volume| {
+            containers
+                .iter()
+                .filter(|container| container.volumes.contains_key(volume))
+                .map(|container| VolumeConflict {
+                    volume: volume.clone(),
+                    container: container.name.clone(),
+                })
+        })
+        .collect()
+}
+
+/// The values of [`LABEL`] given to the containers and volumes db-mgr creates.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Labels {
+    pub container: &'static str,
+    pub volume: &'static str,
+}
+
+pub const MANAGED_LABELS: Labels = Labels {
+    container: "container",
+    volume: "volume",
+};
+
+/// The body of the request creating the volume `name` of a container with the image `tag`.
+fn volume_request(
+    name: &str,
+    label: &str,
+    tag: &str,
+    driver_opts: Option<&HashMap<String, String>>,
+) -> CreateVolumeOptions<String> {
+    CreateVolumeOptions {
+        name: name.to_string(),
+        labels: HashMap::from([
+            (LABEL.to_string(), label.to_string()),
+            (VOLUME_TAG_LABEL.to_string(), tag.to_string()),
+        ]),
+        driver_opts: driver_opts.cloned().unwrap_or_default(),
+        ..Default::default()
+    }
+}
+
+/// Creates the volume of `request`, or keeps it when it exists already, was created with `label`
+/// and `reuse` is set.
+async fn create_volume(
+    runtime: &impl CreateRuntime,
+    request: CreateVolumeOptions<String>,
+    label: &str,
+    reuse: bool,
+) -> anyhow::Result<()> {
+    match runtime.volume(&request.name).await? {
+        None => {}
+        Some(volume) if reuse && volume.labels.get(LABEL).map(String::as_str) == Some(label) => {
+            return Ok(())
+        }
+        Some(_) => return Err(anyhow!("Container name conflict {}", request.name)),
+    };
+
+    runtime.new_volume(request).await
 }
 
 #[derive(Clone, Debug)]
 pub enum CreateContainerEvent {
     Pulling,
     Building,
-    Done,
+    Starting,
+    /// The container was created, and started if asked, with its id.
+    Done(String),
     Error(String),
-    Download(String, f32),
+    /// Bytes downloaded of a layer of the image.
+    Download {
+        layer: String,
+        current: u64,
+        total: u64,
+    },
+    /// A line printed while building a custom image.
+    BuildOutput(String),
+    /// A container with the name exists already, `can_replace` when it is one that may be
+    /// removed to make way, see [`can_replace_conflict`].
+    Conflict {
+        id: String,
+        can_replace: bool,
+    },
+    /// The calls a dry run would have made, nothing was created.
+    Plan(Vec<PlannedOp>),
 }
 
-pub fn create_container(
-    docker: &'static Docker,
-    container_config: DbContainerConfig,
-) -> Receiver<CreateContainerEvent> {
-    let (mut tx, rx) = channel(5);
-    let mut tx2 = tx.clone();
+/// Whether the container `existing`, whose name a new container of `image` wants, may be removed
+/// to make way for it. Only stopped containers of the same image created with `labels` may, so
+/// nothing running or unmanaged is removed and the new container can take over their volumes.
+pub fn can_replace_conflict(
+    existing: &ContainerInspectResponse,
+    labels: Labels,
+    image: &ImageRef,
+) -> bool {
+    let config = existing.config.as_ref();
+    let managed = config
+        .and_then(|config| config.labels.as_ref())
+        .and_then(|container_labels| container_labels.get(LABEL))
+        .is_some_and(|label| label == labels.container);
+    let stopped = existing
+        .state
+        .as_ref()
+        .and_then(|state| state.status)
+        .is_some_and(|status| {
+            matches!(
+                status,
+                ContainerStateStatusEnum::CREATED
+                    | ContainerStateStatusEnum::EXITED
+                    | ContainerStateStatusEnum::DEAD
+            )
+        });
+    let same_image = config
+        .and_then(|config| config.image.as_deref())
+        .is_some_and(|existing_image| ImageRef::parse(existing_image).name() == image.name());
 
-    tokio::spawn(
-        async move {
-            match docker.inspect_container(&container_config.name, None).await {
-                Err(Error::DockerResponseServerError {
-                    status_code: 404, ..
-                }) => {}
-                Ok(_) => return Err(anyhow!("Container name conflict {}", container_config.name)),
-                Err(resp) => return Err(anyhow!(resp)),
-            };
+    managed && stopped && same_image
+}
 
-            let mut image_pull_stream = docker.create_image(
-                Some(CreateImageOptions {
-                    from_image: container_config.image.as_str(),
-                    tag: container_config.tag.as_str(),
-                    ..Default::default()
-                }),
-                None,
-                None,
-            );
+/// Progress of an image pull.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PullEvent {
+    /// An update without a status, such as the pull starting.
+    Pulling,
+    /// Bytes downloaded of a layer of the image.
+    Download {
+        layer: String,
+        current: u64,
+        total: u64,
+    },
+}
 
-            while let Some(result) = image_pull_stream.next().await {
-                let result = result?;
+impl From<PullEvent> for CreateContainerEvent {
+    fn from(event: PullEvent) -> Self {
+        match event {
+            PullEvent::Pulling => CreateContainerEvent::Pulling,
+            PullEvent::Download {
+                layer,
+                current,
+                total,
+            } => CreateContainerEvent::Download {
+                layer,
+                current,
+                total,
+            },
+        }
+    }
+}
 
-                if let Some(status) = result.status {
-                    if status == "Downloading" {
-                        let f = result.progress_detail.and_then(|progress| {
-                            Some((
-                                result.id?,
-                                progress.current? as f32 / progress.total? as f32,
-                            ))
-                        });
+/// The progress an update of a pull reports, `totals` keeps the size of each layer since
+/// completed layers are reported without one.
+fn pull_event(totals: &mut HashMap<String, u64>, info: CreateImageInfo) -> Option<PullEvent> {
+    match info.status.as_deref() {
+        Some("Downloading") => {
+            let (layer, current, total) = info
+                .progress_detail
+                .and_then(|progress| Some((info.id?, progress.current?, progress.total?)))?;
+            totals.insert(layer.clone(), total.max(0) as u64);
+            Some(PullEvent::Download {
+                layer,
+                current: current.max(0) as u64,
+                total: total.max(0) as u64,
+            })
+        }
+        Some("Download complete") => {
+            let layer = info.id?;
+            let total = totals.get(&layer).copied().unwrap_or_default();
+            Some(PullEvent::Download {
+                layer,
+                current: total,
+                total,
+            })
+        }
+        Some(_) => None,
+        None => Some(PullEvent::Pulling),
+    }
+}
 
-                        if let Some((id, progress)) = f {
-                            tx.send(CreateContainerEvent::Download(id, progress))
-                                .await?;
-                        }
-                    } else if status == "Download complete" {
-                        if let Some(id) = result.id {
-                            tx.send(CreateContainerEvent::Download(id, 1.0)).await?;
-                        }
-                    }
-                } else {
-                    tx.send(CreateContainerEvent::Pulling).await?;
-                }
+/// The progress reported by the updates of a pull. Ends after the first error.
+pub fn pull_events<'a, E>(
+    updates: impl Stream<Item = Result<CreateImageInfo, E>> + 'a,
+) -> impl Stream<Item = anyhow::Result<PullEvent>> + 'a
+where
+    E: Into<anyhow::Error>,
+{
+    updates
+        .scan((HashMap::new(), false), |(totals, failed), result| {
+            if *failed {
+                return future::ready(None);
             }
+            let event = match result {
+                Ok(info) => Ok(pull_event(totals, info)),
+                Err(ex) => {
+                    *failed = true;
+                    Err(ex.into())
+                }
+            };
+            future::ready(Some(event))
+        })
+        .filter_map(|event| future::ready(event.transpose()))
+}
 
-            let env = container_config
-                .variables
-                .into_iter()
-                .map(|(name, value)| format!("{name}=\"{value}\""))
-                .collect::<Vec<_>>();
+/// Pulls the image, reporting the bytes downloaded of each layer. Ends after the first error.
+pub fn pull_image<'a>(
+    docker: &'a Docker,
+    image_ref: &ImageRef,
+) -> impl Stream<Item = anyhow::Result<PullEvent>> + 'a {
+    pull_events(docker.create_image(
+        Some(CreateImageOptions {
+            from_image: image_ref.name(),
+            tag: image_ref.reference().to_string(),
+            ..Default::default()
+        }),
+        None,
+        None,
+    ))
+}
 
-            tx.send(CreateContainerEvent::Building).await?;
+/// The size of the image `image_ref` if it is present locally, `None` if it has to be pulled.
+pub async fn local_image_size(
+    docker: &Docker,
+    image_ref: &ImageRef,
+) -> anyhow::Result<Option<i64>> {
+    let inspected = with_timeout(
+        "inspect image",
+        query_timeout(),
+        docker.inspect_image(&image_ref.to_string()),
+    )
+    .await;
+    match inspected {
+        Ok(image) => Ok(Some(image.size.unwrap_or_default())),
+        Err(ex) if daemon_status(&ex) == Some(404) => Ok(None),
+        Err(ex) => Err(ex),
+    }
+}
 
-            for (name, _) in container_config.voluems.iter() {
-                create_volume(docker, name).await?;
-            }
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PrefetchEvent {
+    /// The image is present locally with this size, it isn't pulled again.
+    Present(String, i64),
+    Pulling(String, PullEvent),
+    /// The image was pulled, with its size if it could be inspected afterwards.
+    Pulled(String, Option<i64>),
+    Failed(String, String),
+    Done,
+}
 
-            let container_name = container_config.name.clone();
-            let image = format!("{}:{}", container_config.image, container_config.tag);
-            let container = docker
-                .create_container(
-                    Some(CreateContainerOptions {
-                        name: container_config.name,
-                        ..Default::default()
-                    }),
-                    Config {
-                        labels: Some(HashMap::from([(LABEL, "container")])),
-                        env: Some(env.iter().map(|x| x.as_str()).collect()),
-                        image: Some(&image),
-
-                        host_config: Some(HostConfig {
-                            mounts: Some(
-                                container_config
-                                    .voluems
-                                    .into_iter()
-                                    .map(|(name, path)| Mount {
-                                        read_only: Some(false),
-                                        target: Some(path),
-                                        source: Some(name),
-                                        typ: Some(MountTypeEnum::VOLUME),
-                                        ..Default::default()
-                                    })
-                                    .collect(),
-                            ),
-                            ..Default::default()
-                        }),
-                        ..Default::default()
-                    },
-                )
-                .await?;
+/// What prefetching needs from docker, so it can be run against a scripted one.
+pub trait PrefetchRuntime: Sync {
+    /// The size of `image` if it is present locally, `None` if it has to be pulled.
+    fn local_size<'a>(&'a self, image: &'a ImageRef) -> BoxFuture<'a, anyhow::Result<Option<i64>>>;
+    fn pull<'a>(&'a self, image: &'a ImageRef) -> BoxStream<'a, anyhow::Result<PullEvent>>;
+}
+
+impl PrefetchRuntime for Docker {
+    fn local_size<'a>(&'a self, image: &'a ImageRef) -> BoxFuture<'a, anyhow::Result<Option<i64>>> {
+        local_image_size(self, image).boxed()
+    }
+
+    fn pull<'a>(&'a self, image: &'a ImageRef) -> BoxStream<'a, anyhow::Result<PullEvent>> {
+        pull_image(self, image).boxed()
+    }
+}
+
+/// Pulls the `images` one after the other, skipping the ones present locally. A failed pull is
+/// reported and the rest are still pulled. Returns early if the receiver of `events` is gone.
+pub async fn prefetch(
+    runtime: &impl PrefetchRuntime,
+    images: Vec<String>,
+    events: &mut Sender<PrefetchEvent>,
+) {
+    for image in images {
+        let image_ref = ImageRef::parse(&image);
+        let result = async {
+            if let Some(size) = runtime.local_size(&image_ref).await? {
+                events
+                    .send(PrefetchEvent::Present(image.clone(), size))
+                    .await?;
+                return Ok(());
+            }
 
-            docker
-                .start_container::<String>(&container_name, None)
+            let mut pull = runtime.pull(&image_ref);
+            while let Some(event) = pull.next().await {
+                events
+                    .send(PrefetchEvent::Pulling(image.clone(), event?))
+                    .await?;
+            }
+            let size = runtime.local_size(&image_ref).await.ok().flatten();
+            events
+                .send(PrefetchEvent::Pulled(image.clone(), size))
                 .await?;
-            println!("{container:?}");
-            Ok(())
+            anyhow::Ok(())
         }
-        .then(|result: Result<(), anyhow::Error>| async move {
-            match result.err() {
-                Some(ex) => tx2.send(CreateContainerEvent::Error(format!("{ex}"))).await,
-                None => tx2.send(CreateContainerEvent::Done).await,
+        .await;
+
+        if let Err(ex) = result {
+            if events.is_closed() {
+                return;
             }
-        }),
-    );
+            let _ = events
+                .send(PrefetchEvent::Failed(image.clone(), format!("{ex:#}")))
+                .await;
+        }
+    }
 
-    rx
+    let _ = events.send(PrefetchEvent::Done).await;
 }
 
-pub async fn get_containers(docker: &Docker) -> anyhow::Result<Vec<DbContainer>> {
-    Ok(stream::iter(
-        docker
-            .list_containers(Some(ListContainersOptions {
-                filters: HashMap::from([("label".into(), vec![format!("{LABEL}=container")])]),
-                all: true,
-                ..Default::default()
-            }))
-            .await?,
-    )
-    .filter_map(|summary| async {
-        let out = docker.inspect_container(summary.id?.as_ref(), None).await;
+/// Prefetches the `images` with the daemon, see [`prefetch`].
+pub fn prefetch_images(docker: &'static Docker, images: Vec<String>) -> Receiver<PrefetchEvent> {
+    let (mut tx, rx) = channel(5);
 
-        out.ok()
-    })
-    .filter_map(|result| async {
-        Some(DbContainer {
-            id: result.id?,
-            name: result.name?,
-            image: result
-                .config
-                .as_ref()
-                .map(|config| config.image.clone())??,
-            state: result.state?.status?,
-            volumes: result
-                .mounts
-                .map(|mounts| {
-                    mounts
-                        .into_iter()
-                        .filter_map(|mount| {
-                            Some((mount.name.or(mount.source)?, mount.destination?))
-                        })
-                        .collect()
-                })
-                .unwrap_or_default(),
-            variables: result
-                .config?
-                .env
-                .map(|e| {
-                    e.into_iter()
-                        .map(|entry| match entry.split_once('=') {
-                            Some((name, var)) => (name.into(), var.into()),
-                            None => (entry, "".into()),
-                        })
-                        .collect()
-                })
-                .unwrap_or_default(),
-        })
+    tokio::spawn(async move {
+        prefetch(docker, images, &mut tx).await;
+    });
+
+    rx
+}
+
+/// The registry digest of the pulled `image_ref`, `None` for images that never came from a
+/// registry.
+async fn local_digest(runtime: &impl CreateRuntime, image_ref: &ImageRef) -> Option<String> {
+    let image = runtime.image(image_ref).await.ok()??;
+    image.repo_digests?.into_iter().find_map(|repo_digest| {
+        let pulled = ImageRef::parse(&repo_digest);
+        (pulled.key() == image_ref.key())
+            .then_some(pulled.digest)
+            .flatten()
     })
-    .collect()
-    .await)
 }
 
-pub async fn start_container(id: String, docker: &Docker) -> anyhow::Result<()> {
-    docker.start_container::<String>(&id, None).await?;
+/// Builds the image from its Dockerfile unless an image built from the same Dockerfile and
+/// arguments exists. A failed build names the step it failed at.
+async fn build_image<S>(
+    runtime: &impl CreateRuntime,
+    build: &ImageBuild,
+    image_ref: &ImageRef,
+    events: &mut S,
+) -> anyhow::Result<()>
+where
+    S: Sink<CreateContainerEvent> + Unpin,
+    S::Error: std::error::Error + Send + Sync + 'static,
+{
+    let (dockerfile, context) = match &build.dockerfile {
+        Dockerfile::Inline(dockerfile) => (dockerfile.clone(), None),
+        Dockerfile::File { path } => (
+            fs::read_to_string(path)
+                .map_err(|ex| anyhow!("Could not read {}: {ex}", path.display()))?,
+            path.parent(),
+        ),
+    };
+
+    let mut args = build.args.clone();
+    args.insert("TAG".to_string(), image_ref.reference().to_string());
+    let hash = dockerfile_hash(&dockerfile, &args);
+    let tag = image_ref.to_string();
+
+    let built = runtime
+        .image(image_ref)
+        .await
+        .ok()
+        .flatten()
+        .and_then(|image| image.config?.labels?.remove(DOCKERFILE_HASH_LABEL));
+    if built.as_ref() == Some(&hash) {
+        events
+            .send(CreateContainerEvent::BuildOutput(format!(
+                "Using {tag}, the Dockerfile is unchanged"
+            )))
+            .await?;
+        return Ok(());
+    }
+
+    let mut build_stream = runtime.build(
+        BuildImageOptions {
+            dockerfile: BUILD_DOCKERFILE.to_string(),
+            t: tag,
+            buildargs: args,
+            labels: HashMap::from([(DOCKERFILE_HASH_LABEL.to_string(), hash)]),
+            rm: true,
+            ..Default::default()
+        },
+        &dockerfile,
+        context,
+    );
+
+    let mut step = None;
+    while let Some(result) = build_stream.next().await {
+        let info = result.map_err(|ex| build_failed(ex, step.as_deref()))?;
+        if let Some(error) = info.error {
+            return Err(build_failed(error, step.as_deref()));
+        }
+
+        for line in info.stream.iter().flat_map(|output| output.lines()) {
+            if line.trim().is_empty() {
+                continue;
+            }
+            if line.starts_with("Step ") {
+                step = Some(line.to_string());
+            }
+            events
+                .send(CreateContainerEvent::BuildOutput(line.to_string()))
+                .await?;
+        }
+    }
 
     Ok(())
 }
 
-pub async fn stop_container(id: String, docker: &Docker) -> anyhow::Result<()> {
-    docker.stop_container(&id, None).await?;
+fn build_failed(error: impl Display, step: Option<&str>) -> anyhow::Error {
+    match step {
+        Some(step) => anyhow!("Could not build the image, {step} failed: {error}"),
+        None => anyhow!("Could not build the image: {error}"),
+    }
+}
+
+/// The name the Dockerfile is added to the build context as, so it doesn't replace a
+/// `Dockerfile` in the context directory.
+const BUILD_DOCKERFILE: &str = ".db-mgr.Dockerfile";
+
+/// The build context as a tar archive, the directory `context` if given with the Dockerfile
+/// added as [`BUILD_DOCKERFILE`].
+fn build_context(dockerfile: &str, context: Option<&Path>) -> anyhow::Result<Vec<u8>> {
+    let mut archive = tar::Builder::new(Vec::new());
+    if let Some(context) = context {
+        archive.append_dir_all(".", context)?;
+    }
+
+    let mut header = tar::Header::new_gnu();
+    header.set_size(dockerfile.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    archive.append_data(&mut header, BUILD_DOCKERFILE, dockerfile.as_bytes())?;
+
+    Ok(archive.into_inner()?)
+}
+
+/// An FNV-1a hash of the Dockerfile and the sorted arguments, which unlike the std hasher stays
+/// the same across releases.
+fn dockerfile_hash(dockerfile: &str, args: &HashMap<String, String>) -> String {
+    let input = args
+        .iter()
+        .sorted()
+        .fold(dockerfile.to_string(), |input, (name, value)| {
+            format!("{input}\0{name}={value}")
+        });
+
+    let hash = input.bytes().fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    });
+    format!("{hash:016x}")
+}
+
+/// Creates the [`companion_network`] of `parent` if it is missing and connects `parent` to it.
+async fn join_companion_network(
+    runtime: &impl CreateRuntime,
+    parent: &str,
+    labels: Labels,
+) -> Result<(), anyhow::Error> {
+    let network = companion_network(parent);
+    if !runtime.has_network(&network).await? {
+        runtime
+            .new_network(network_request(&network, labels))
+            .await?;
+    }
+
+    // A missing parent is left to the connect call to report
+    let connected = runtime
+        .container(parent)
+        .await?
+        .and_then(|parent| parent.network_settings?.networks)
+        .is_some_and(|networks| networks.contains_key(&network));
+    if !connected {
+        runtime.join_network(&network, parent).await?;
+    }
 
     Ok(())
 }
+
+/// The system the daemon runs on, unknown parts are left out.
+pub async fn daemon_platform(docker: &Docker) -> DaemonPlatform {
+    match with_timeout("get system info", query_timeout(), docker.info()).await {
+        Ok(info) => DaemonPlatform {
+            os_type: info.os_type,
+            operating_system: info.operating_system,
+        },
+        Err(ex) => {
+            eprintln!("Could not get the daemon's platform: {ex}");
+            DaemonPlatform::default()
+        }
+    }
+}
+
+/// `config` with the host folders it binds written the way `daemon` expects them.
+pub fn bind_for_daemon(
+    mut config: DbContainerConfig,
+    daemon: &DaemonPlatform,
+) -> DbContainerConfig {
+    let source = |name: String| {
+        if is_host_path(&name) {
+            daemon_bind_source(&name, cfg!(windows), daemon)
+        } else {
+            name
+        }
+    };
+    config.volumes = config
+        .volumes
+        .into_iter()
+        .map(|(name, path)| (source(name), path))
+        .collect();
+    config.read_only_volumes = config.read_only_volumes.into_iter().map(source).collect();
+    config
+}
+
+pub fn create_container(
+    docker: &'static Docker,
+    container_config: DbContainerConfig,
+) -> Receiver<CreateContainerEvent> {
+    create_labelled_container(docker, container_config, MANAGED_LABELS)
+}
+
+/// The docker calls creating a container makes, so a dry run can record them instead of making
+/// them, see [`PlanRecorder`].
+pub trait CreateRuntime: Sync {
+    /// The container `name`, `None` if there is none.
+    fn container<'a>(
+        &'a self,
+        name: &'a str,
+    ) -> BoxFuture<'a, anyhow::Result<Option<ContainerInspectResponse>>>;
+    /// The containers created with `labels`.
+    fn containers(&self, labels: Labels) -> BoxFuture<'_, anyhow::Result<Vec<DbContainer>>>;
+    fn pull<'a>(&'a self, image: &'a ImageRef) -> BoxStream<'a, anyhow::Result<PullEvent>>;
+    /// The local `image`, `None` if it has to be pulled or built.
+    fn image<'a>(
+        &'a self,
+        image: &'a ImageRef,
+    ) -> BoxFuture<'a, anyhow::Result<Option<ImageInspect>>>;
+    /// Builds an image from `dockerfile`, with the directory `context` if given.
+    fn build<'a>(
+        &'a self,
+        options: BuildImageOptions<String>,
+        dockerfile: &'a str,
+        context: Option<&'a Path>,
+    ) -> BoxStream<'a, anyhow::Result<BuildInfo>>;
+    fn platform(&self) -> BoxFuture<'_, DaemonPlatform>;
+    /// The volume `name`, `None` if there is none.
+    fn volume<'a>(&'a self, name: &'a str) -> BoxFuture<'a, anyhow::Result<Option<Volume>>>;
+    fn new_volume(&self, request: CreateVolumeOptions<String>)
+        -> BoxFuture<'_, anyhow::Result<()>>;
+    fn has_network<'a>(&'a self, name: &'a str) -> BoxFuture<'a, anyhow::Result<bool>>;
+    fn new_network<'a>(
+        &'a self,
+        request: CreateNetworkOptions<&'a str>,
+    ) -> BoxFuture<'a, anyhow::Result<()>>;
+    /// Connects the container `container` to `network`.
+    fn join_network<'a>(
+        &'a self,
+        network: &'a str,
+        container: &'a str,
+    ) -> BoxFuture<'a, anyhow::Result<()>>;
+    /// Creates the container `name`, returning its id.
+    fn new_container<'a>(
+        &'a self,
+        name: &'a str,
+        request: Config<String>,
+    ) -> BoxFuture<'a, anyhow::Result<String>>;
+    fn start<'a>(&'a self, name: &'a str) -> BoxFuture<'a, anyhow::Result<()>>;
+}
+
+/// `result` with the daemon's "not found" as `None`.
+fn found<T>(result: anyhow::Result<T>) -> anyhow::Result<Option<T>> {
+    match result {
+        Err(ex) if daemon_status(&ex) == Some(404) => Ok(None),
+        result => result.map(Some),
+    }
+}
+
+impl CreateRuntime for Docker {
+    fn container<'a>(
+        &'a self,
+        name: &'a str,
+    ) -> BoxFuture<'a, anyhow::Result<Option<ContainerInspectResponse>>> {
+        with_timeout(
+            "inspect container",
+            query_timeout(),
+            self.inspect_container(name, None),
+        )
+        .map(found)
+        .boxed()
+    }
+
+    fn containers(&self, labels: Labels) -> BoxFuture<'_, anyhow::Result<Vec<DbContainer>>> {
+        get_labelled_containers(self, labels).boxed()
+    }
+
+    fn pull<'a>(&'a self, image: &'a ImageRef) -> BoxStream<'a, anyhow::Result<PullEvent>> {
+        pull_image(self, image).boxed()
+    }
+
+    fn image<'a>(
+        &'a self,
+        image: &'a ImageRef,
+    ) -> BoxFuture<'a, anyhow::Result<Option<ImageInspect>>> {
+        async move {
+            found(
+                with_timeout(
+                    "inspect image",
+                    query_timeout(),
+                    self.inspect_image(&image.to_string()),
+                )
+                .await,
+            )
+        }
+        .boxed()
+    }
+
+    fn build<'a>(
+        &'a self,
+        options: BuildImageOptions<String>,
+        dockerfile: &'a str,
+        context: Option<&'a Path>,
+    ) -> BoxStream<'a, anyhow::Result<BuildInfo>> {
+        match build_context(dockerfile, context) {
+            Ok(context) => self
+                .build_image(options, None, Some(context.into()))
+                .map(|result| result.map_err(anyhow::Error::from))
+                .boxed(),
+            Err(ex) => stream::once(future::ready(Err(ex))).boxed(),
+        }
+    }
+
+    fn platform(&self) -> BoxFuture<'_, DaemonPlatform> {
+        daemon_platform(self).boxed()
+    }
+
+    fn volume<'a>(&'a self, name: &'a str) -> BoxFuture<'a, anyhow::Result<Option<Volume>>> {
+        with_timeout("inspect volume", query_timeout(), self.inspect_volume(name))
+            .map(found)
+            .boxed()
+    }
+
+    fn new_volume(
+        &self,
+        request: CreateVolumeOptions<String>,
+    ) -> BoxFuture<'_, anyhow::Result<()>> {
+        with_timeout(
+            "create volume",
+            change_timeout(),
+            self.create_volume(request),
+        )
+        .map(|result| result.map(|_| ()))
+        .boxed()
+    }
+
+    fn has_network<'a>(&'a self, name: &'a str) -> BoxFuture<'a, anyhow::Result<bool>> {
+        with_timeout(
+            "inspect network",
+            query_timeout(),
+            self.inspect_network::<String>(name, None),
+        )
+        .map(|result| found(result).map(|network| network.is_some()))
+        .boxed()
+    }
+
+    fn new_network<'a>(
+        &'a self,
+        request: CreateNetworkOptions<&'a str>,
+    ) -> BoxFuture<'a, anyhow::Result<()>> {
+        with_timeout(
+            "create network",
+            change_timeout(),
+            self.create_network(request),
+        )
+        .map(|result| result.map(|_| ()))
+        .boxed()
+    }
+
+    fn join_network<'a>(
+        &'a self,
+        network: &'a str,
+        container: &'a str,
+    ) -> BoxFuture<'a, anyhow::Result<()>> {
+        with_timeout(
+            "connect network",
+            change_timeout(),
+            self.connect_network(
+                network,
+                ConnectNetworkOptions {
+                    container,
+                    ..Default::default()
+                },
+            ),
+        )
+        .boxed()
+    }
+
+    fn new_container<'a>(
+        &'a self,
+        name: &'a str,
+        request: Config<String>,
+    ) -> BoxFuture<'a, anyhow::Result<String>> {
+        with_timeout(
+            "create container",
+            change_timeout(),
+            self.create_container(
+                Some(CreateContainerOptions {
+                    name,
+                    ..Default::default()
+                }),
+                request,
+            ),
+        )
+        .map(|result| result.map(|container| container.id))
+        .boxed()
+    }
+
+    fn start<'a>(&'a self, name: &'a str) -> BoxFuture<'a, anyhow::Result<()>> {
+        with_timeout(
+            "start container",
+            change_timeout(),
+            self.start_container::<String>(name, None),
+        )
+        .boxed()
+    }
+}
+
+/// Records the calls creating a container would make instead of making them, answering like a
+/// daemon that has none of the container's resources yet. Dry runs list what it recorded.
+#[derive(Debug, Default)]
+pub struct PlanRecorder {
+    plan: Mutex<Vec<PlannedOp>>,
+}
+
+impl PlanRecorder {
+    fn record(&self, call: String, purpose: impl Into<String>, body: Option<serde_json::Value>) {
+        if let Ok(mut plan) = self.plan.lock() {
+            plan.push(PlannedOp::new(call, purpose, body));
+        }
+    }
+
+    /// The calls recorded so far, in order.
+    pub fn into_plan(self) -> Vec<PlannedOp> {
+        self.plan.into_inner().unwrap_or_default()
+    }
+}
+
+impl CreateRuntime for PlanRecorder {
+    fn container<'a>(
+        &'a self,
+        name: &'a str,
+    ) -> BoxFuture<'a, anyhow::Result<Option<ContainerInspectResponse>>> {
+        self.record(
+            format!("GET /containers/{name}/json"),
+            format!("Check whether a container named {name} exists"),
+            None,
+        );
+        future::ready(Ok(None)).boxed()
+    }
+
+    fn containers(&self, _: Labels) -> BoxFuture<'_, anyhow::Result<Vec<DbContainer>>> {
+        self.record(
+            "GET /containers/json".to_string(),
+            "Check that no other container mounts its volumes",
+            None,
+        );
+        future::ready(Ok(vec![])).boxed()
+    }
+
+    fn pull<'a>(&'a self, image: &'a ImageRef) -> BoxStream<'a, anyhow::Result<PullEvent>> {
+        self.record(
+            format!(
+                "POST /images/create?fromImage={}&tag={}",
+                image.name(),
+                image.reference()
+            ),
+            "Pull the image",
+            None,
+        );
+        stream::empty().boxed()
+    }
+
+    fn image<'a>(
+        &'a self,
+        image: &'a ImageRef,
+    ) -> BoxFuture<'a, anyhow::Result<Option<ImageInspect>>> {
+        self.record(
+            format!("GET /images/{image}/json"),
+            "Inspect the image, for the digest it was pulled with or the Dockerfile it was built from",
+            None,
+        );
+        future::ready(Ok(None)).boxed()
+    }
+
+    fn build<'a>(
+        &'a self,
+        options: BuildImageOptions<String>,
+        _: &'a str,
+        _: Option<&'a Path>,
+    ) -> BoxStream<'a, anyhow::Result<BuildInfo>> {
+        self.record(
+            format!("POST /build?t={}", options.t),
+            "Build the image, skipped when it is up to date",
+            None,
+        );
+        stream::empty().boxed()
+    }
+
+    fn platform(&self) -> BoxFuture<'_, DaemonPlatform> {
+        self.record(
+            "GET /info".to_string(),
+            "Read the daemon's platform, to bind the host folders",
+            None,
+        );
+        future::ready(DaemonPlatform::default()).boxed()
+    }
+
+    fn volume<'a>(&'a self, name: &'a str) -> BoxFuture<'a, anyhow::Result<Option<Volume>>> {
+        self.record(
+            format!("GET /volumes/{name}"),
+            "Check whether the volume exists, only a db-mgr volume is reused",
+            None,
+        );
+        future::ready(Ok(None)).boxed()
+    }
+
+    fn new_volume(
+        &self,
+        request: CreateVolumeOptions<String>,
+    ) -> BoxFuture<'_, anyhow::Result<()>> {
+        self.record(
+            "POST /volumes/create".to_string(),
+            "Create the volume, skipped when it is reused",
+            json_body(&request),
+        );
+        future::ready(Ok(())).boxed()
+    }
+
+    fn has_network<'a>(&'a self, name: &'a str) -> BoxFuture<'a, anyhow::Result<bool>> {
+        self.record(
+            format!("GET /networks/{name}"),
+            "Check whether the companion network exists",
+            None,
+        );
+        future::ready(Ok(false)).boxed()
+    }
+
+    fn new_network<'a>(
+        &'a self,
+        request: CreateNetworkOptions<&'a str>,
+    ) -> BoxFuture<'a, anyhow::Result<()>> {
+        self.record(
+            "POST /networks/create".to_string(),
+            "Create the companion network, skipped when it exists",
+            json_body(&request),
+        );
+        future::ready(Ok(())).boxed()
+    }
+
+    fn join_network<'a>(
+        &'a self,
+        network: &'a str,
+        container: &'a str,
+    ) -> BoxFuture<'a, anyhow::Result<()>> {
+        self.record(
+            format!("POST /networks/{network}/connect"),
+            format!("Connect {container}, skipped when it is connected"),
+            json_body(&ConnectNetworkOptions {
+                container,
+                ..Default::default()
+            }),
+        );
+        future::ready(Ok(())).boxed()
+    }
+
+    fn new_container<'a>(
+        &'a self,
+        name: &'a str,
+        request: Config<String>,
+    ) -> BoxFuture<'a, anyhow::Result<String>> {
+        self.record(
+            format!("POST /containers/create?name={name}"),
+            "Create the container",
+            json_body(&request),
+        );
+        future::ready(Ok(name.to_string())).boxed()
+    }
+
+    fn start<'a>(&'a self, name: &'a str) -> BoxFuture<'a, anyhow::Result<()>> {
+        self.record(
+            format!("POST /containers/{name}/start"),
+            "Start the container",
+            None,
+        );
+        future::ready(Ok(())).boxed()
+    }
+}
+
+/// Creates the container of `config` with `runtime`, sending the progress to `events`. Ends with
+/// [`CreateContainerEvent::Done`], or [`CreateContainerEvent::Conflict`] when the name is taken.
+pub async fn create_with<S>(
+    runtime: &impl CreateRuntime,
+    container_config: DbContainerConfig,
+    labels: Labels,
+    events: &mut S,
+) -> anyhow::Result<CreateContainerEvent>
+where
+    S: Sink<CreateContainerEvent> + Unpin,
+    S::Error: std::error::Error + Send + Sync + 'static,
+{
+    let image_ref = ImageRef::parse(&container_config.image).with_reference(&container_config.tag);
+
+    // Checked before pulling, so replacing the conflict and retrying pulls only once
+    if let Some(existing) = runtime.container(&container_config.name).await? {
+        return Ok(CreateContainerEvent::Conflict {
+            can_replace: can_replace_conflict(&existing, labels, &image_ref),
+            id: existing.id.unwrap_or_else(|| container_config.name.clone()),
+        });
+    }
+
+    if !container_config.force_shared_volume {
+        let containers = runtime.containers(labels).await?;
+        if let Some(conflict) = shared_volumes(&container_config.volumes, &containers).first() {
+            return Err(anyhow!("{conflict}"));
+        }
+    }
+
+    let digest = match container_config.build.as_ref() {
+        Some(build) => {
+            build_image(runtime, build, &image_ref, events).await?;
+            None
+        }
+        None => {
+            let mut pull = runtime.pull(&image_ref);
+            while let Some(event) = pull.next().await {
+                events.send(event?.into()).await?;
+            }
+            local_digest(runtime, &image_ref).await
+        }
+    };
+
+    events.send(CreateContainerEvent::Building).await?;
+
+    // Host folders are bound as they are, only named volumes are created
+    let windows_paths = container_config
+        .volumes
+        .keys()
+        .any(|source| is_windows_path(source));
+    let container_config = if windows_paths && cfg!(windows) {
+        let daemon = runtime.platform().await;
+        bind_for_daemon(container_config, &daemon)
+    } else {
+        container_config
+    };
+    for name in container_config
+        .volumes
+        .keys()
+        .filter(|name| !is_host_path(name))
+        .sorted()
+    {
+        create_volume(
+            runtime,
+            volume_request(
+                name,
+                labels.volume,
+                &container_config.tag,
+                container_config.volume_options.get(name),
+            ),
+            labels.volume,
+            container_config.reuse_volumes,
+        )
+        .await?;
+    }
+
+    if let Some(parent) = container_config.companion_of.as_deref() {
+        join_companion_network(runtime, parent, labels).await?;
+    }
+    let id = runtime
+        .new_container(
+            &container_config.name,
+            container_request(&container_config, labels, &image_ref, digest.as_deref()),
+        )
+        .await?;
+
+    if container_config.start_after_create {
+        events.send(CreateContainerEvent::Starting).await?;
+        runtime.start(&container_config.name).await?;
+    }
+    Ok(CreateContainerEvent::Done(id))
+}
+
+pub fn create_labelled_container(
+    docker: &'static Docker,
+    container_config: DbContainerConfig,
+    labels: Labels,
+) -> Receiver<CreateContainerEvent> {
+    let (mut tx, rx) = channel(5);
+    let mut tx2 = tx.clone();
+
+    tokio::spawn(
+        async move {
+            if container_config.dry_run {
+                let recorder = PlanRecorder::default();
+                create_with(&recorder, container_config, labels, &mut sink::drain()).await?;
+                return Ok(CreateContainerEvent::Plan(recorder.into_plan()));
+            }
+
+            create_with(docker, container_config, labels, &mut tx).await
+        }
+        .then(
+            |result: Result<CreateContainerEvent, anyhow::Error>| async move {
+                match result {
+                    Ok(event) => tx2.send(event).await,
+                    Err(ex) => tx2.send(CreateContainerEvent::Error(format!("{ex}"))).await,
+                }
+            },
+        ),
+    );
+
+    rx
+}
+
+/// The body of the request creating the container of `config` from `image`, with the `digest` of
+/// the pulled image as a label. Creating the container and [`plan_container`] both use it, so a
+/// dry run shows what would be sent.
+fn container_request(
+    config: &DbContainerConfig,
+    labels: Labels,
+    image: &ImageRef,
+    digest: Option<&str>,
+) -> Config<String> {
+    let mut container_labels = HashMap::from([
+        (LABEL.to_string(), labels.container.to_string()),
+        (SCHEMA_LABEL.to_string(), LABEL_SCHEMA_VERSION.to_string()),
+        (DATABASE_LABEL.to_string(), config.database.clone()),
+    ]);
+    if !config.depends_on.is_empty() {
+        container_labels.insert(DEPENDS_ON_LABEL.to_string(), config.depends_on.join(","));
+    }
+    if let Some(digest) = digest {
+        container_labels.insert(IMAGE_DIGEST_LABEL.to_string(), digest.to_string());
+    }
+    if !config.extra_variables.is_empty() {
+        container_labels.insert(
+            EXTRA_VARIABLES_LABEL.to_string(),
+            config.extra_variables.join(","),
+        );
+    }
+    if config.volumes.is_empty() {
+        container_labels.insert(EPHEMERAL_LABEL.to_string(), "true".to_string());
+    }
+    if let Some(project) = config.project.as_ref() {
+        container_labels.insert(PROJECT_LABEL.to_string(), project.clone());
+    }
+    if let Some(assignment) = config
+        .host_port
+        .filter(|assignment| assignment.host.is_some())
+    {
+        container_labels.insert(HOST_PORT_LABEL.to_string(), assignment.to_string());
+    }
+    if let Some(parent) = config.companion_of.as_ref() {
+        container_labels.insert(COMPANION_LABEL.to_string(), parent.clone());
+    }
+
+    // The database port is bound to its assigned host port, the others to any free one
+    let assigned = config.host_port;
+    let published_ports = config
+        .published_ports
+        .iter()
+        .filter(|port| assigned.is_none_or(|assigned| assigned.container != **port))
+        .map(|port| (format!("{port}/tcp"), None))
+        .chain(assigned.map(|assigned| {
+            (
+                format!("{}/tcp", assigned.container),
+                assigned.host.map(|host| host.to_string()),
+            )
+        }))
+        .collect::<Vec<_>>();
+
+    Config {
+        labels: Some(container_labels),
+        env: Some(
+            config
+                .variables
+                .iter()
+                .map(|(name, value)| format!("{name}={value}"))
+                .collect(),
+        ),
+        image: Some(image.to_string()),
+        hostname: config.hostname.clone(),
+        user: config.user.clone(),
+        exposed_ports: Some(
+            published_ports
+                .iter()
+                .map(|(port, _)| (port.clone(), HashMap::new()))
+                .collect(),
+        )
+        .filter(|ports: &HashMap<_, _>| !ports.is_empty()),
+        cmd: Some(config.command.clone()).filter(|command| !command.is_empty()),
+
+        host_config: Some(HostConfig {
+            mounts: Some(
+                config
+                    .volumes
+                    .iter()
+                    .map(|(name, path)| Mount {
+                        read_only: Some(config.read_only_volumes.contains(name)),
+                        target: Some(path.clone()),
+                        source: Some(name.clone()),
+                        typ: Some(if is_host_path(name) {
+                            MountTypeEnum::BIND
+                        } else {
+                            MountTypeEnum::VOLUME
+                        }),
+                        ..Default::default()
+                    })
+                    .chain(config.tmpfs.iter().map(|path| Mount {
+                        target: Some(path.clone()),
+                        typ: Some(MountTypeEnum::TMPFS),
+                        tmpfs_options: Some(MountTmpfsOptions {
+                            size_bytes: config.tmpfs_size.map(|size| size as i64),
+                            ..Default::default()
+                        }),
+                        ..Default::default()
+                    }))
+                    .collect(),
+            ),
+            shm_size: config.shm_size_mb.map(|size| (size * 1024 * 1024) as i64),
+            cpuset_cpus: config.cpuset_cpus.clone(),
+            extra_hosts: Some(config.extra_hosts.clone()).filter(|hosts| !hosts.is_empty()),
+            ulimits: Some(
+                config
+                    .ulimits
+                    .iter()
+                    .map(|ulimit| ResourcesUlimits {
+                        name: Some(ulimit.name.clone()),
+                        soft: Some(ulimit.soft),
+                        hard: Some(ulimit.hard),
+                    })
+                    .collect::<Vec<_>>(),
+            )
+            .filter(|ulimits| !ulimits.is_empty()),
+            sysctls: Some(config.sysctls.clone()).filter(|sysctls| !sysctls.is_empty()),
+            port_bindings: Some(
+                published_ports
+                    .into_iter()
+                    .map(|(port, host_port)| {
+                        (
+                            port,
+                            Some(vec![PortBinding {
+                                host_ip: Some("127.0.0.1".to_string()),
+                                host_port,
+                            }]),
+                        )
+                    })
+                    .collect::<HashMap<_, _>>(),
+            )
+            .filter(|ports| !ports.is_empty()),
+            network_mode: config.companion_of.as_deref().map(companion_network),
+            device_requests: config
+                .gpus
+                .clone()
+                .map(|device_ids| vec![gpu_request(device_ids)]),
+            log_config: Some(HostConfigLogConfig {
+                typ: Some(config.log.driver.clone()),
+                config: Some(config.log.options.clone()).filter(|options| !options.is_empty()),
+            }),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+/// The body of the request creating the companion `network`.
+fn network_request(network: &str, labels: Labels) -> CreateNetworkOptions<&str> {
+    CreateNetworkOptions {
+        name: network,
+        labels: HashMap::from([(LABEL, labels.container)]),
+        ..Default::default()
+    }
+}
+
+/// A docker API call creating a container would make, listed by a dry run instead of made.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PlannedOp {
+    /// The method and path, such as `POST /volumes/create`.
+    pub call: String,
+    /// What the call is for, and when it is skipped.
+    pub purpose: String,
+    /// The JSON body of the request, `None` for calls without one.
+    pub body: Option<serde_json::Value>,
+}
+
+impl PlannedOp {
+    fn new(call: String, purpose: impl Into<String>, body: Option<serde_json::Value>) -> Self {
+        Self {
+            call,
+            purpose: purpose.into(),
+            body,
+        }
+    }
+
+    /// Masks the values of the variables in the body whose key `is_secret`, so the plan can be
+    /// shown and copied without leaking passwords.
+    pub fn redacted(mut self, is_secret: impl Fn(&str) -> bool) -> Self {
+        if let Some(body) = self.body.as_mut() {
+            redact_env(body, &is_secret);
+        }
+        self
+    }
+
+    /// The body as indented JSON.
+    pub fn body_text(&self) -> Option<String> {
+        serde_json::to_string_pretty(self.body.as_ref()?).ok()
+    }
+}
+
+/// Masks the values of the `KEY=value` entries of every `Env` array in `json` whose key
+/// `is_secret`, such as the one of a create request or the `Config` of an inspect.
+pub fn redact_env(json: &mut serde_json::Value, is_secret: &impl Fn(&str) -> bool) {
+    match json {
+        serde_json::Value::Object(fields) => {
+            for (field, value) in fields.iter_mut() {
+                let Some(variables) = value.as_array_mut().filter(|_| field == "Env") else {
+                    redact_env(value, is_secret);
+                    continue;
+                };
+                for variable in variables {
+                    let Some((key, _)) = variable.as_str().and_then(|pair| pair.split_once('='))
+                    else {
+                        continue;
+                    };
+                    if is_secret(key) {
+                        *variable = format!("{key}=••••••").into();
+                    }
+                }
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for item in items {
+                redact_env(item, is_secret);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn json_body(request: &impl Serialize) -> Option<serde_json::Value> {
+    serde_json::to_value(request).ok()
+}
+
+/// The docker calls creating the container of `config` would make, in order, without making any,
+/// as recorded by a [`PlanRecorder`]. Calls that depend on what docker has, such as creating a
+/// volume that may exist already, are listed with when they are skipped. The plan ends early at a
+/// call that can't be made, such as building from a Dockerfile that can't be read.
+pub fn plan_container(config: &DbContainerConfig, labels: Labels) -> Vec<PlannedOp> {
+    let recorder = PlanRecorder::default();
+    // The recorder answers every call right away, so nothing waits on a runtime
+    let _ = executor::block_on(create_with(
+        &recorder,
+        config.clone(),
+        labels,
+        &mut sink::drain(),
+    ));
+    recorder.into_plan()
+}
+
+pub async fn get_containers(docker: &Docker) -> anyhow::Result<Vec<DbContainer>> {
+    get_labelled_containers(docker, MANAGED_LABELS).await
+}
+
+pub async fn get_labelled_containers(
+    docker: &Docker,
+    labels: Labels,
+) -> anyhow::Result<Vec<DbContainer>> {
+    let mut containers = stream::iter(
+        with_timeout(
+            "list containers",
+            query_timeout(),
+            docker.list_containers(Some(ListContainersOptions {
+                filters: HashMap::from([(
+                    "label".into(),
+                    vec![format!("{LABEL}={}", labels.container)],
+                )]),
+                all: true,
+                ..Default::default()
+            })),
+        )
+        .await?,
+    )
+    .filter_map(|summary| async move {
+        let id = summary.id.clone()?;
+        let inspected = with_timeout(
+            "inspect container",
+            query_timeout(),
+            docker.inspect_container(&id, None),
+        )
+        .await;
+        match inspected {
+            Ok(result) => inspected_container(result),
+            Err(ex) => {
+                eprintln!("Could not inspect container {id} {ex}");
+                summary_container(summary)
+            }
+        }
+    })
+    .collect::<Vec<_>>()
+    .await;
+    drop_image_commands(docker, &mut containers).await;
+
+    Ok(containers)
+}
+
+/// The command a container was created with, empty when it is `image_command`. Docker copies the
+/// `Cmd` of the image into containers created without one, which isn't worth showing or carrying
+/// over to a recreated container.
+pub fn given_command(command: Vec<String>, image_command: &[String]) -> Vec<String> {
+    match command == image_command {
+        true => vec![],
+        false => command,
+    }
+}
+
+/// Applies [`given_command`] to `containers`, inspecting each of their images once. Containers
+/// whose image can't be inspected keep their command.
+async fn drop_image_commands(docker: &Docker, containers: &mut [DbContainer]) {
+    let mut image_commands = HashMap::new();
+    for container in containers
+        .iter_mut()
+        .filter(|container| !container.command.is_empty())
+    {
+        if !image_commands.contains_key(&container.image) {
+            let defaults = get_image_defaults(container.image.clone(), docker).await;
+            image_commands.insert(
+                container.image.clone(),
+                defaults.ok().map(|defaults| defaults.command),
+            );
+        }
+        if let Some(Some(image_command)) = image_commands.get(&container.image) {
+            container.command =
+                given_command(std::mem::take(&mut container.command), image_command);
+        }
+    }
+}
+
+/// The name shown for a container docker returned no name for.
+fn fallback_name(id: &str) -> String {
+    format!("/{}", &id[..id.len().min(12)])
+}
+
+/// Maps an inspected container, falling back to defaults for anything missing and marking the
+/// container as partial if the name, image, state or config were. Only containers without an id
+/// are skipped.
+pub fn inspected_container(result: ContainerInspectResponse) -> Option<DbContainer> {
+    let id = result.id?;
+    let hostname = result
+        .config
+        .as_ref()
+        .and_then(|config| config.hostname.clone())
+        .filter(|hostname| !hostname.is_empty() && !id.starts_with(hostname.as_str()));
+    let image = result
+        .config
+        .as_ref()
+        .and_then(|config| config.image.clone());
+    let health = result
+        .state
+        .as_ref()
+        .and_then(|state| state.health.as_ref())
+        .and_then(|health| health.status)
+        .filter(|status| !matches!(status, HealthStatusEnum::EMPTY | HealthStatusEnum::NONE));
+    let health_log = parse_health_log(
+        result
+            .state
+            .as_ref()
+            .and_then(|state| state.health.as_ref()),
+    );
+    let exit = parse_exit(result.state.as_ref(), result.restart_count);
+    let state = result.state.and_then(|state| state.status);
+    let partial =
+        result.name.is_none() || image.is_none() || state.is_none() || result.config.is_none();
+    let networks = result
+        .network_settings
+        .as_ref()
+        .and_then(|settings| settings.networks.as_ref())
+        .map(|networks| parse_networks(&id, networks))
+        .unwrap_or_default();
+    let ports = result
+        .network_settings
+        .as_ref()
+        .and_then(|settings| settings.ports.as_ref())
+        .map(parse_ports)
+        .unwrap_or_default();
+
+    Some(DbContainer {
+        name: result.name.unwrap_or_else(|| fallback_name(&id)),
+        id,
+        hostname,
+        user: result
+            .config
+            .as_ref()
+            .and_then(|config| config.user.clone())
+            .filter(|user| !user.is_empty()),
+        extra_hosts: result
+            .host_config
+            .as_ref()
+            .and_then(|host_config| host_config.extra_hosts.clone())
+            .unwrap_or_default(),
+        ulimits: result
+            .host_config
+            .as_ref()
+            .and_then(|host_config| host_config.ulimits.as_ref())
+            .into_iter()
+            .flatten()
+            .filter_map(|ulimit| {
+                Some(Ulimit {
+                    name: ulimit.name.clone()?,
+                    soft: ulimit.soft.unwrap_or(-1),
+                    hard: ulimit.hard.unwrap_or(-1),
+                })
+            })
+            .sorted()
+            .collect(),
+        sysctls: result
+            .host_config
+            .as_ref()
+            .and_then(|host_config| host_config.sysctls.clone())
+            .unwrap_or_default(),
+        gpus: result
+            .host_config
+            .as_ref()
+            .and_then(|host_config| host_config.device_requests.as_deref())
+            .and_then(parse_gpu_requests),
+        log: result
+            .host_config
+            .as_ref()
+            .and_then(|host_config| host_config.log_config.as_ref())
+            .and_then(parse_log_config),
+        limits: result
+            .host_config
+            .as_ref()
+            .map(parse_limits)
+            .unwrap_or_default(),
+        exit,
+        image: image.unwrap_or_default(),
+        state: state.unwrap_or(ContainerStateStatusEnum::EMPTY),
+        health,
+        health_log,
+        partial,
+        tmpfs: result
+            .mounts
+            .iter()
+            .flatten()
+            .filter(|mount| mount.typ == Some(MountPointTypeEnum::TMPFS))
+            .filter_map(|mount| mount.destination.clone())
+            .collect(),
+        tmpfs_size: result
+            .host_config
+            .as_ref()
+            .and_then(|host_config| host_config.mounts.as_ref())
+            .into_iter()
+            .flatten()
+            .filter(|mount| mount.typ == Some(MountTypeEnum::TMPFS))
+            .find_map(|mount| mount.tmpfs_options.as_ref()?.size_bytes)
+            .map(|size| size as u64),
+        read_only_volumes: result
+            .mounts
+            .iter()
+            .flatten()
+            .filter(|mount| mount.typ != Some(MountPointTypeEnum::TMPFS) && mount.rw == Some(false))
+            .filter_map(|mount| mount.name.clone().or(mount.source.clone()))
+            .collect(),
+        volumes: result
+            .mounts
+            .map(|mounts| {
+                mounts
+                    .into_iter()
+                    .filter(|mount| mount.typ != Some(MountPointTypeEnum::TMPFS))
+                    .filter_map(|mount| Some((mount.name.or(mount.source)?, mount.destination?)))
+                    .collect()
+            })
+            .unwrap_or_default(),
+        command: result
+            .config
+            .as_ref()
+            .and_then(|config| config.cmd.clone())
+            .unwrap_or_default(),
+        shm_size_mb: result
+            .host_config
+            .as_ref()
+            .and_then(|host_config| host_config.shm_size)
+            .map(|size| size as u64 / 1024 / 1024),
+        cpuset_cpus: result
+            .host_config
+            .as_ref()
+            .and_then(|host_config| host_config.cpuset_cpus.clone())
+            .filter(|cpuset| !cpuset.is_empty()),
+        labels: result
+            .config
+            .as_ref()
+            .and_then(|config| config.labels.clone())
+            .unwrap_or_default(),
+        depends_on: result
+            .config
+            .as_ref()
+            .and_then(|config| config.labels.as_ref())
+            .map(parse_depends_on)
+            .unwrap_or_default(),
+        networks,
+        ports,
+        variables: result
+            .config
+            .and_then(|config| config.env)
+            .map(|env| {
+                env.iter()
+                    .filter_map(|entry| parse_env_entry(entry))
+                    .collect()
+            })
+            .unwrap_or_default(),
+    })
+}
+
+/// The attachments of container `id` from the networks of its inspect or list response. Docker
+/// aliases the short container id on user defined networks, that alias is left out.
+pub fn parse_networks(
+    id: &str,
+    networks: &HashMap<String, EndpointSettings>,
+) -> Vec<NetworkAttachment> {
+    networks
+        .iter()
+        .map(|(name, endpoint)| NetworkAttachment {
+            name: name.clone(),
+            ip: endpoint.ip_address.clone().filter(|ip| !ip.is_empty()),
+            aliases: endpoint
+                .aliases
+                .iter()
+                .flatten()
+                .filter(|alias| !alias.is_empty() && !id.starts_with(alias.as_str()))
+                .unique()
+                .cloned()
+                .collect(),
+        })
+        .sorted_by(|a, b| a.name.cmp(&b.name))
+        .collect()
+}
+
+/// The container names in the [`DEPENDS_ON_LABEL`] of `labels`.
+pub fn parse_depends_on(labels: &HashMap<String, String>) -> Vec<String> {
+    labels
+        .get(DEPENDS_ON_LABEL)
+        .map(|names| {
+            names
+                .split(',')
+                .map(str::trim)
+                .filter(|name| !name.is_empty())
+                .map(String::from)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Maps a listed container that could not be inspected. It is always partial since the list
+/// lacks the env, mounts and host config.
+pub fn summary_container(summary: ContainerSummary) -> Option<DbContainer> {
+    let id = summary.id?;
+
+    Some(DbContainer {
+        name: summary
+            .names
+            .and_then(|names| names.into_iter().next())
+            .unwrap_or_else(|| fallback_name(&id)),
+        networks: summary
+            .network_settings
+            .as_ref()
+            .and_then(|settings| settings.networks.as_ref())
+            .map(|networks| parse_networks(&id, networks))
+            .unwrap_or_default(),
+        id,
+        image: summary.image.unwrap_or_default(),
+        state: summary
+            .state
+            .and_then(|state| state.parse().ok())
+            .unwrap_or(ContainerStateStatusEnum::EMPTY),
+        health: summary.status.as_deref().and_then(parse_summary_health),
+        health_log: vec![],
+        partial: true,
+        volumes: HashMap::new(),
+        read_only_volumes: vec![],
+        ports: summary
+            .ports
+            .iter()
+            .flatten()
+            .filter_map(|port| {
+                Some(PublishedPort {
+                    port: port.private_port,
+                    protocol: port
+                        .typ
+                        .map(|typ| typ.to_string())
+                        .unwrap_or_else(|| "tcp".to_string()),
+                    host_ip: port.ip.clone().unwrap_or_default(),
+                    host_port: port.public_port?,
+                })
+            })
+            .sorted()
+            .collect(),
+        variables: HashMap::new(),
+        command: vec![],
+        depends_on: summary
+            .labels
+            .as_ref()
+            .map(parse_depends_on)
+            .unwrap_or_default(),
+        labels: summary.labels.unwrap_or_default(),
+        shm_size_mb: None,
+        cpuset_cpus: None,
+        tmpfs: vec![],
+        tmpfs_size: None,
+        hostname: None,
+        extra_hosts: vec![],
+        ulimits: vec![],
+        sysctls: HashMap::new(),
+        gpus: None,
+        log: None,
+        user: None,
+        limits: ContainerLimits::default(),
+        exit: ContainerExit::default(),
+    })
+}
+
+/// What docker reported happening to a container.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ContainerEventKind {
+    Created,
+    Started,
+    /// The main process exited, with its exit code if docker reported it.
+    Died(Option<i64>),
+    OutOfMemory,
+    Restarted,
+    /// Sent a signal, such as `SIGKILL` or `15`.
+    Killed(Option<String>),
+    Stopped,
+    Paused,
+    Unpaused,
+    /// The health check result changed, such as to `unhealthy`.
+    Health(String),
+    Removed,
+}
+
+impl Display for ContainerEventKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ContainerEventKind::Created => write!(f, "Created"),
+            ContainerEventKind::Started => write!(f, "Started"),
+            ContainerEventKind::Died(Some(code)) => write!(f, "Exited with code {code}"),
+            ContainerEventKind::Died(None) => write!(f, "Exited"),
+            ContainerEventKind::OutOfMemory => write!(f, "Ran out of memory"),
+            ContainerEventKind::Restarted => write!(f, "Restarted"),
+            ContainerEventKind::Killed(Some(signal)) => write!(f, "Sent signal {signal}"),
+            ContainerEventKind::Killed(None) => write!(f, "Killed"),
+            ContainerEventKind::Stopped => write!(f, "Stopped"),
+            ContainerEventKind::Paused => write!(f, "Paused"),
+            ContainerEventKind::Unpaused => write!(f, "Resumed"),
+            ContainerEventKind::Health(status) => write!(f, "Health check: {status}"),
+            ContainerEventKind::Removed => write!(f, "Removed"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ContainerEvent {
+    /// The id of the container.
+    pub container: String,
+    /// Seconds since the unix epoch.
+    pub time: i64,
+    pub kind: ContainerEventKind,
+}
+
+/// The container event of a docker event, `None` for other objects and for the actions left out
+/// of the timeline, such as execs and attaches.
+pub fn parse_event(message: EventMessage) -> Option<ContainerEvent> {
+    if message.typ != Some(EventMessageTypeEnum::CONTAINER) {
+        return None;
+    }
+    let actor = message.actor?;
+    let attributes = actor.attributes.unwrap_or_default();
+
+    let kind = match message.action?.as_str() {
+        "create" => ContainerEventKind::Created,
+        "start" => ContainerEventKind::Started,
+        "die" => ContainerEventKind::Died(
+            attributes
+                .get("exitCode")
+                .and_then(|code| code.parse().ok()),
+        ),
+        "oom" => ContainerEventKind::OutOfMemory,
+        "restart" => ContainerEventKind::Restarted,
+        "kill" => ContainerEventKind::Killed(attributes.get("signal").cloned()),
+        "stop" => ContainerEventKind::Stopped,
+        "pause" => ContainerEventKind::Paused,
+        "unpause" => ContainerEventKind::Unpaused,
+        "destroy" => ContainerEventKind::Removed,
+        action => {
+            ContainerEventKind::Health(action.strip_prefix("health_status:")?.trim().to_string())
+        }
+    };
+
+    Some(ContainerEvent {
+        container: actor.id?,
+        time: message.time?,
+        kind,
+    })
+}
+
+fn events_options(
+    container: Option<String>,
+    since: Option<i64>,
+    until: Option<i64>,
+) -> EventsOptions<String> {
+    let mut filters = HashMap::from([
+        ("type".to_string(), vec!["container".to_string()]),
+        (
+            "label".to_string(),
+            vec![format!("{LABEL}={}", MANAGED_LABELS.container)],
+        ),
+    ]);
+    if let Some(container) = container {
+        filters.insert("container".to_string(), vec![container]);
+    }
+
+    EventsOptions {
+        since: since.map(|since| since.to_string()),
+        until: until.map(|until| until.to_string()),
+        filters,
+    }
+}
+
+/// The events of db-mgr containers as docker reports them, ending when the connection drops.
+pub fn container_events(docker: &Docker) -> impl Stream<Item = ContainerEvent> + '_ {
+    docker
+        .events(Some(events_options(None, None, None)))
+        .take_while(|message| future::ready(message.is_ok()))
+        .filter_map(|message| future::ready(message.ok().and_then(parse_event)))
+}
+
+/// The events of container `id` between the unix times `since` and `until`.
+pub async fn get_container_events(
+    id: String,
+    since: i64,
+    until: i64,
+    docker: &Docker,
+) -> anyhow::Result<Vec<ContainerEvent>> {
+    let read = async {
+        let mut events = vec![];
+        let mut messages = docker.events(Some(events_options(Some(id), Some(since), Some(until))));
+        while let Some(message) = messages.next().await {
+            events.extend(parse_event(message?));
+        }
+        Ok::<_, Error>(events)
+    };
+
+    with_timeout("read events", query_timeout(), read).await
+}
+
+pub async fn start_container(id: String, docker: &Docker) -> anyhow::Result<()> {
+    with_timeout(
+        "start container",
+        change_timeout(),
+        docker.start_container::<String>(&id, None),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Inspects container `id` alone, for when only it changed and listing every container again
+/// isn't needed.
+pub async fn get_container(id: String, docker: &Docker) -> anyhow::Result<DbContainer> {
+    let result = with_timeout(
+        "inspect container",
+        query_timeout(),
+        docker.inspect_container(&id, None),
+    )
+    .await?;
+
+    let mut container =
+        inspected_container(result).ok_or_else(|| anyhow!("Docker returned no id for {id}"))?;
+    drop_image_commands(docker, std::slice::from_mut(&mut container)).await;
+
+    Ok(container)
+}
+
+/// The container `id` as docker reports it now, `None` once it was removed.
+pub async fn refresh_container(docker: &Docker, id: &str) -> anyhow::Result<Option<DbContainer>> {
+    let inspected = with_timeout(
+        "inspect container",
+        query_timeout(),
+        docker.inspect_container(id, None),
+    )
+    .await;
+    let mut container = match inspected {
+        Ok(result) => inspected_container(result),
+        Err(ex) if daemon_status(&ex) == Some(404) => None,
+        Err(ex) => return Err(ex),
+    };
+    drop_image_commands(docker, container.as_mut_slice()).await;
+
+    Ok(container)
+}
+
+/// The limits to change on a running container, the ones left `None` are kept.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LimitChanges {
+    pub memory: Option<i64>,
+    pub nano_cpus: Option<i64>,
+    pub restart: Option<Restart>,
+}
+
+impl LimitChanges {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// A limit of [`LimitChanges`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LimitField {
+    Memory,
+    Cpus,
+    Restart,
+}
+
+/// Why the daemon refused to change a limit, such as a memory limit below what the container
+/// already uses.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LimitError {
+    pub field: LimitField,
+    pub message: String,
+}
+
+/// Changes the limits of container `id` that are set in `changes` while it keeps running. Each
+/// is a call of its own, so a refusal is reported against the limit it is about and the ones
+/// before it stay applied.
+pub async fn update_container_limits(
+    id: String,
+    changes: LimitChanges,
+    docker: &Docker,
+) -> Result<(), LimitError> {
+    let updates = [
+        (
+            LimitField::Memory,
+            changes
+                .memory
+                .map(|memory| UpdateContainerOptions::<String> {
+                    memory: Some(memory),
+                    // Swap is left unlimited, a memory limit above the swap limit is refused
+                    memory_swap: Some(-1),
+                    ..Default::default()
+                }),
+        ),
+        (
+            LimitField::Cpus,
+            changes.nano_cpus.map(|nano_cpus| UpdateContainerOptions {
+                nano_cp_us: Some(nano_cpus),
+                ..Default::default()
+            }),
+        ),
+        (
+            LimitField::Restart,
+            changes.restart.map(|restart| UpdateContainerOptions {
+                restart_policy: Some(restart.policy()),
+                ..Default::default()
+            }),
+        ),
+    ];
+
+    for (field, update) in updates {
+        let Some(update) = update else {
+            continue;
+        };
+        with_timeout(
+            "update container",
+            change_timeout(),
+            docker.update_container(&id, update),
+        )
+        .await
+        .map_err(|ex| LimitError {
+            field,
+            message: match ex.downcast_ref::<Error>() {
+                Some(Error::DockerResponseServerError { message, .. }) => message.clone(),
+                _ => format!("{ex:#}"),
+            },
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Below this many bytes free on the docker data root, creating containers is blocked.
+pub const CRITICAL_DISK_SPACE: u64 = 500 * 1024 * 1024;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DiskSpace {
+    pub available: u64,
+    pub total: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DiskStatus {
+    Ok,
+    /// Less than the configured threshold is free.
+    Low,
+    /// Less than [`CRITICAL_DISK_SPACE`] is free, pulls are likely to fail.
+    Critical,
+}
+
+pub fn disk_status(space: DiskSpace, low_below: u64) -> DiskStatus {
+    if space.available < CRITICAL_DISK_SPACE {
+        DiskStatus::Critical
+    } else if space.available < low_below {
+        DiskStatus::Low
+    } else {
+        DiskStatus::Ok
+    }
+}
+
+/// The free space on the filesystem holding the docker data root.
+///
+/// Only works when the daemon runs on this machine, engines running in a VM report a data root
+/// that doesn't exist here and give `None`.
+pub async fn get_disk_space(docker: &Docker) -> Option<DiskSpace> {
+    let root = with_timeout("get system info", query_timeout(), docker.info())
+        .await
+        .ok()?
+        .docker_root_dir?;
+    tokio::task::spawn_blocking(move || statvfs(Path::new(&root)))
+        .await
+        .ok()?
+}
+
+/// What the docker engine reports about itself. Fields it could not report are `None` and the
+/// reasons are kept in `errors`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EngineInfo {
+    pub version: Option<String>,
+    pub api_version: Option<String>,
+    pub client_api_version: String,
+    pub os: Option<String>,
+    pub arch: Option<String>,
+    pub kernel: Option<String>,
+    pub storage_driver: Option<String>,
+    pub cpus: Option<i64>,
+    pub memory: Option<i64>,
+    /// Whether the nvidia runtime is installed, which GPUs are given to containers through.
+    pub nvidia_runtime: bool,
+    /// The GPUs the engine advertises, see [`parse_gpus`].
+    pub gpus: Vec<String>,
+    pub errors: Vec<String>,
+}
+
+/// Asks the engine for its version and system info, keeping whatever part succeeded.
+pub async fn get_engine_info(docker: &Docker) -> EngineInfo {
+    let mut engine = EngineInfo {
+        client_api_version: docker.client_version().to_string(),
+        ..Default::default()
+    };
+
+    match with_timeout("get version", query_timeout(), docker.version()).await {
+        Ok(version) => {
+            engine.version = version.version;
+            engine.api_version = version.api_version;
+            engine.arch = version.arch;
+            engine.kernel = version.kernel_version;
+        }
+        Err(ex) => engine
+            .errors
+            .push(format!("Could not get the engine version: {ex}")),
+    }
+
+    match with_timeout("get system info", query_timeout(), docker.info()).await {
+        Ok(info) => {
+            engine.os = info.operating_system;
+            engine.storage_driver = info.driver;
+            engine.cpus = info.ncpu;
+            engine.memory = info.mem_total;
+            engine.nvidia_runtime = info
+                .runtimes
+                .is_some_and(|runtimes| runtimes.contains_key("nvidia"));
+            engine.gpus = info
+                .generic_resources
+                .as_ref()
+                .map(parse_gpus)
+                .unwrap_or_default();
+        }
+        Err(ex) => engine
+            .errors
+            .push(format!("Could not get the engine info: {ex}")),
+    }
+
+    engine
+}
+
+#[cfg(unix)]
+fn statvfs(path: &Path) -> Option<DiskSpace> {
+    use std::{ffi::CString, mem::MaybeUninit, os::unix::ffi::OsStrExt};
+
+    let path = CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut stat = MaybeUninit::<libc::statvfs>::uninit();
+    // SAFETY: `path` is a valid C string and `stat` is only read after statvfs succeeded.
+    let stat = unsafe {
+        if libc::statvfs(path.as_ptr(), stat.as_mut_ptr()) != 0 {
+            return None;
+        }
+        stat.assume_init()
+    };
+
+    let block_size = stat.f_frsize as u64;
+    Some(DiskSpace {
+        available: stat.f_bavail as u64 * block_size,
+        total: stat.f_blocks as u64 * block_size,
+    })
+}
+
+#[cfg(not(unix))]
+fn statvfs(_: &Path) -> Option<DiskSpace> {
+    None
+}
+
+/// The output of a command run inside a container.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ExecOutput {
+    pub stdout: String,
+    pub stderr: String,
+    pub exit_code: Option<i64>,
+}
+
+impl ExecOutput {
+    pub fn success(&self) -> bool {
+        self.exit_code == Some(0)
+    }
+}
+
+/// Runs `cmd` inside the container `id` without a shell, so every element is passed as is.
+pub async fn exec(id: String, cmd: Vec<String>, docker: &Docker) -> anyhow::Result<ExecOutput> {
+    let created = with_timeout(
+        "create exec",
+        change_timeout(),
+        docker.create_exec(
+            &id,
+            CreateExecOptions {
+                cmd: Some(cmd),
+                attach_stdout: Some(true),
+                attach_stderr: Some(true),
+                ..Default::default()
+            },
+        ),
+    )
+    .await?;
+
+    let mut output = ExecOutput::default();
+    if let StartExecResults::Attached {
+        output: mut stream, ..
+    } = with_timeout(
+        "start exec",
+        change_timeout(),
+        docker.start_exec(&created.id, None),
+    )
+    .await?
+    {
+        while let Some(chunk) = stream.next().await {
+            match chunk? {
+                LogOutput::StdErr { message } => {
+                    output.stderr.push_str(&String::from_utf8_lossy(&message))
+                }
+                LogOutput::StdOut { message } | LogOutput::Console { message } => {
+                    output.stdout.push_str(&String::from_utf8_lossy(&message))
+                }
+                LogOutput::StdIn { .. } => {}
+            }
+        }
+    }
+
+    output.exit_code = with_timeout(
+        "inspect exec",
+        query_timeout(),
+        docker.inspect_exec(&created.id),
+    )
+    .await?
+    .exit_code;
+
+    Ok(output)
+}
+
+/// Runs `cmd` inside the container `id` and writes its stdout to `path`, which is only created
+/// once the command succeeded.
+pub async fn exec_to_file(
+    id: String,
+    cmd: Vec<String>,
+    path: PathBuf,
+    docker: &Docker,
+) -> anyhow::Result<()> {
+    let created = with_timeout(
+        "create exec",
+        change_timeout(),
+        docker.create_exec(
+            &id,
+            CreateExecOptions {
+                cmd: Some(cmd),
+                attach_stdout: Some(true),
+                attach_stderr: Some(true),
+                ..Default::default()
+            },
+        ),
+    )
+    .await?;
+
+    let partial = path.with_extension("partial");
+    let mut file = tokio::fs::File::create(&partial).await?;
+    let mut stderr = String::new();
+    let written = async {
+        if let StartExecResults::Attached {
+            output: mut stream, ..
+        } = with_timeout(
+            "start exec",
+            change_timeout(),
+            docker.start_exec(&created.id, None),
+        )
+        .await?
+        {
+            while let Some(chunk) = stream.next().await {
+                match chunk? {
+                    LogOutput::StdErr { message } => {
+                        stderr.push_str(&String::from_utf8_lossy(&message))
+                    }
+                    LogOutput::StdOut { message } | LogOutput::Console { message } => {
+                        file.write_all(&message).await?
+                    }
+                    LogOutput::StdIn { .. } => {}
+                }
+            }
+        }
+        file.flush().await?;
+
+        match with_timeout(
+            "inspect exec",
+            query_timeout(),
+            docker.inspect_exec(&created.id),
+        )
+        .await?
+        .exit_code
+        {
+            Some(0) => Ok(()),
+            code => Err(anyhow!(
+                "The command exited with code {}: {}",
+                code.map(|code| code.to_string())
+                    .unwrap_or_else(|| "unknown".to_string()),
+                stderr.trim()
+            )),
+        }
+    }
+    .await;
+    drop(file);
+
+    match written {
+        Ok(()) => Ok(tokio::fs::rename(&partial, &path).await?),
+        Err(ex) => {
+            let _ = tokio::fs::remove_file(&partial).await;
+            Err(ex)
+        }
+    }
+}
+
+/// Events of an interactive exec session.
+#[derive(Clone, Debug)]
+pub enum ExecEvent {
+    /// The session is attached, lines sent to the sender are written to its stdin.
+    Started(Sender<String>),
+    Output(String),
+    /// The process exited, with its exit code if docker knows it.
+    Ended(Option<i64>),
+    Error(String),
+}
+
+struct AttachedExec {
+    id: String,
+    output: Pin<Box<dyn Stream<Item = Result<LogOutput, Error>> + Send>>,
+    input: Pin<Box<dyn AsyncWrite + Send>>,
+}
+
+enum ExecSession {
+    Connecting { id: String, cmd: Vec<String> },
+    Attached(AttachedExec, Receiver<String>),
+    Done,
+}
+
+async fn attach_exec(
+    docker: &Docker,
+    id: &str,
+    cmd: Vec<String>,
+    tty: bool,
+) -> anyhow::Result<AttachedExec> {
+    let created = with_timeout(
+        "create exec",
+        change_timeout(),
+        docker.create_exec(
+            id,
+            CreateExecOptions {
+                cmd: Some(cmd),
+                attach_stdin: Some(true),
+                attach_stdout: Some(true),
+                attach_stderr: Some(true),
+                tty: Some(tty),
+                ..Default::default()
+            },
+        ),
+    )
+    .await?;
+
+    match with_timeout(
+        "start exec",
+        change_timeout(),
+        docker.start_exec(&created.id, None),
+    )
+    .await?
+    {
+        StartExecResults::Attached { output, input } => Ok(AttachedExec {
+            id: created.id,
+            output,
+            input,
+        }),
+        StartExecResults::Detached => Err(anyhow!("The exec session did not attach")),
+    }
+}
+
+/// Runs `cmd` in the container `id` with a tty, forwarding the lines sent to the sender from
+/// [`ExecEvent::Started`] to its stdin. The connection is closed when the stream is dropped or
+/// the sender is.
+pub fn exec_session(
+    docker: &'static Docker,
+    id: String,
+    cmd: Vec<String>,
+) -> impl Stream<Item = ExecEvent> + Send {
+    stream::unfold(
+        ExecSession::Connecting { id, cmd },
+        move |session| async move {
+            match session {
+                ExecSession::Connecting { id, cmd } => {
+                    match attach_exec(docker, &id, cmd, true).await {
+                        Ok(attached) => {
+                            let (tx, lines) = channel(16);
+                            Some((
+                                ExecEvent::Started(tx),
+                                ExecSession::Attached(attached, lines),
+                            ))
+                        }
+                        Err(ex) => Some((ExecEvent::Error(format!("{ex}")), ExecSession::Done)),
+                    }
+                }
+                ExecSession::Attached(mut attached, mut lines) => loop {
+                    let next = match future::select(attached.output.next(), lines.next()).await {
+                        Either::Left((chunk, _)) => Either::Left(chunk),
+                        Either::Right((line, _)) => Either::Right(line),
+                    };
+
+                    match next {
+                        Either::Left(Some(Ok(chunk))) => {
+                            let output = String::from_utf8_lossy(&chunk.into_bytes()).to_string();
+                            return Some((
+                                ExecEvent::Output(output),
+                                ExecSession::Attached(attached, lines),
+                            ));
+                        }
+                        Either::Left(Some(Err(ex))) => {
+                            return Some((ExecEvent::Error(format!("{ex}")), ExecSession::Done))
+                        }
+                        Either::Left(None) => {
+                            let exit_code = with_timeout(
+                                "inspect exec",
+                                query_timeout(),
+                                docker.inspect_exec(&attached.id),
+                            )
+                            .await
+                            .ok()
+                            .and_then(|exec| exec.exit_code);
+                            return Some((ExecEvent::Ended(exit_code), ExecSession::Done));
+                        }
+                        Either::Right(Some(line)) => {
+                            if let Err(ex) = attached
+                                .input
+                                .write_all(format!("{line}\n").as_bytes())
+                                .await
+                            {
+                                return Some((
+                                    ExecEvent::Error(format!("{ex}")),
+                                    ExecSession::Done,
+                                ));
+                            }
+                        }
+                        Either::Right(None) => return None,
+                    }
+                },
+                ExecSession::Done => None,
+            }
+        },
+    )
+}
+
+/// Events of a command run with a file written to its stdin.
+#[derive(Clone, Debug)]
+pub enum InputExecEvent {
+    /// Bytes of the file written so far and in total.
+    Progress(u64, u64),
+    Output(String),
+    /// The process exited, with its exit code if docker knows it.
+    Ended(Option<i64>),
+    Error(String),
+}
+
+/// How much of the file is written to the process at once.
+const INPUT_CHUNK: usize = 64 * 1024;
+
+enum InputExec {
+    Connecting {
+        id: String,
+        cmd: Vec<String>,
+        file: PathBuf,
+    },
+    Running {
+        attached: AttachedExec,
+        input: Vec<u8>,
+        written: usize,
+    },
+    Done,
+}
+
+/// Runs `cmd` in the container `id` without a tty and writes the contents of `file` to its
+/// stdin, closing it once the whole file is written. Output is read while writing so a chatty
+/// process can't block the input.
+pub fn exec_with_input(
+    docker: &'static Docker,
+    id: String,
+    cmd: Vec<String>,
+    file: PathBuf,
+) -> impl Stream<Item = InputExecEvent> + Send {
+    stream::unfold(
+        InputExec::Connecting { id, cmd, file },
+        move |state| async move {
+            match state {
+                InputExec::Connecting { id, cmd, file } => {
+                    let input = match fs::read(&file) {
+                        Ok(input) => input,
+                        Err(ex) => {
+                            let error = format!("Could not read {}: {ex}", file.display());
+                            return Some((InputExecEvent::Error(error), InputExec::Done));
+                        }
+                    };
+
+                    match attach_exec(docker, &id, cmd, false).await {
+                        Ok(mut attached) => {
+                            if input.is_empty() {
+                                if let Err(ex) = attached.input.shutdown().await {
+                                    let error = format!("{ex}");
+                                    return Some((InputExecEvent::Error(error), InputExec::Done));
+                                }
+                            }
+                            Some((
+                                InputExecEvent::Progress(0, input.len() as u64),
+                                InputExec::Running {
+                                    attached,
+                                    input,
+                                    written: 0,
+                                },
+                            ))
+                        }
+                        Err(ex) => Some((InputExecEvent::Error(format!("{ex}")), InputExec::Done)),
+                    }
+                }
+                InputExec::Running {
+                    mut attached,
+                    input,
+                    mut written,
+                } => {
+                    let next = if written < input.len() {
+                        let chunk = &input[written..input.len().min(written + INPUT_CHUNK)];
+                        match future::select(
+                            attached.output.next(),
+                            Box::pin(attached.input.write(chunk)),
+                        )
+                        .await
+                        {
+                            Either::Left((chunk, _)) => Either::Left(chunk),
+                            Either::Right((result, _)) => Either::Right(result),
+                        }
+                    } else {
+                        Either::Left(attached.output.next().await)
+                    };
+
+                    match next {
+                        Either::Left(Some(Ok(chunk))) => {
+                            let output = String::from_utf8_lossy(&chunk.into_bytes()).to_string();
+                            Some((
+                                InputExecEvent::Output(output),
+                                InputExec::Running {
+                                    attached,
+                                    input,
+                                    written,
+                                },
+                            ))
+                        }
+                        Either::Left(Some(Err(ex))) => {
+                            Some((InputExecEvent::Error(format!("{ex}")), InputExec::Done))
+                        }
+                        Either::Left(None) => {
+                            let exit_code = with_timeout(
+                                "inspect exec",
+                                query_timeout(),
+                                docker.inspect_exec(&attached.id),
+                            )
+                            .await
+                            .ok()
+                            .and_then(|exec| exec.exit_code);
+                            Some((InputExecEvent::Ended(exit_code), InputExec::Done))
+                        }
+                        Either::Right(Ok(count)) => {
+                            written += count;
+                            // Closing stdin tells the process the file has ended
+                            if written == input.len() {
+                                if let Err(ex) = attached.input.shutdown().await {
+                                    return Some((
+                                        InputExecEvent::Error(format!("{ex}")),
+                                        InputExec::Done,
+                                    ));
+                                }
+                            }
+                            Some((
+                                InputExecEvent::Progress(written as u64, input.len() as u64),
+                                InputExec::Running {
+                                    attached,
+                                    input,
+                                    written,
+                                },
+                            ))
+                        }
+                        Either::Right(Err(ex)) => {
+                            Some((InputExecEvent::Error(format!("{ex}")), InputExec::Done))
+                        }
+                    }
+                }
+                InputExec::Done => None,
+            }
+        },
+    )
+}
+
+/// Reads the full value of the variable `key` of a container, for values kept as
+/// [`EnvValue::LargeOrBinary`].
+pub async fn get_env_value(id: String, key: String, docker: &Docker) -> anyhow::Result<String> {
+    with_timeout(
+        "inspect container",
+        query_timeout(),
+        docker.inspect_container(&id, None),
+    )
+    .await?
+    .config
+    .and_then(|config| config.env)
+    .unwrap_or_default()
+    .into_iter()
+    .find_map(|entry| {
+        let (name, value) = split_env_entry(&entry)?;
+        (name == key).then(|| value.to_string())
+    })
+    .ok_or_else(|| anyhow::anyhow!("{key} is not set on the container"))
+}
+
+/// What an image sets itself, which containers created from it get without asking.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ImageDefaults {
+    /// `KEY=value` entries, as in the image's inspect.
+    pub env: Vec<String>,
+    pub labels: HashMap<String, String>,
+    pub command: Vec<String>,
+}
+
+/// The defaults of `image`, none for images that were removed since.
+pub async fn get_image_defaults(image: String, docker: &Docker) -> anyhow::Result<ImageDefaults> {
+    let config = match with_timeout(
+        "inspect image",
+        query_timeout(),
+        docker.inspect_image(&image),
+    )
+    .await
+    {
+        Err(ex) if daemon_status(&ex) == Some(404) => return Ok(ImageDefaults::default()),
+        result => result?.config.unwrap_or_default(),
+    };
+
+    Ok(ImageDefaults {
+        env: config.env.unwrap_or_default(),
+        labels: config.labels.unwrap_or_default(),
+        command: config.cmd.unwrap_or_default(),
+    })
+}
+
+/// What `docker inspect` shows for the container, as JSON.
+pub async fn inspect_json(id: String, docker: &Docker) -> anyhow::Result<serde_json::Value> {
+    let inspect = with_timeout(
+        "inspect container",
+        query_timeout(),
+        docker.inspect_container(&id, None),
+    )
+    .await?;
+    Ok(serde_json::to_value(inspect)?)
+}
+
+pub async fn remove_container(id: String, docker: &Docker) -> anyhow::Result<()> {
+    with_timeout(
+        "remove container",
+        change_timeout(),
+        docker.remove_container(
+            &id,
+            Some(RemoveContainerOptions {
+                force: true,
+                ..Default::default()
+            }),
+        ),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// How long a new container has to stay up before it counts as started.
+pub const START_GRACE_PERIOD: Duration = Duration::from_secs(3);
+
+/// How many log lines are kept from a container that exited right after it was started.
+const START_FAILURE_LOG_LINES: usize = 20;
+
+/// A container that exited with an error right after it was created and started.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StartFailure {
+    pub id: String,
+    pub exit_code: i64,
+    /// The last lines it logged, oldest first.
+    pub logs: Vec<String>,
+}
+
+/// Waits for [`START_GRACE_PERIOD`] and checks that the container `name` is still running,
+/// returning why it stopped if it exited with an error.
+pub async fn check_started(name: String, docker: &Docker) -> anyhow::Result<Option<StartFailure>> {
+    tokio::time::sleep(START_GRACE_PERIOD).await;
+
+    let inspect = with_timeout(
+        "inspect container",
+        query_timeout(),
+        docker.inspect_container(&name, None),
+    )
+    .await?;
+    let state = inspect.state.unwrap_or_default();
+    let exit_code = state.exit_code.unwrap_or_default();
+    if state.status != Some(ContainerStateStatusEnum::EXITED) || exit_code == 0 {
+        return Ok(None);
+    }
+
+    let read = async {
+        let mut logs = docker.logs(
+            &name,
+            Some(LogsOptions::<String> {
+                stdout: true,
+                stderr: true,
+                tail: START_FAILURE_LOG_LINES.to_string(),
+                ..Default::default()
+            }),
+        );
+        let mut lines = vec![];
+        while let Some(output) = logs.next().await {
+            let message = output?.into_bytes();
+            lines.extend(
+                String::from_utf8_lossy(&message)
+                    .lines()
+                    .map(|line| line.to_string()),
+            );
+        }
+        Ok::<_, Error>(lines)
+    };
+    let lines = with_timeout("read logs", query_timeout(), read).await?;
+
+    Ok(Some(StartFailure {
+        id: inspect.id.unwrap_or(name),
+        exit_code,
+        logs: lines,
+    }))
+}
+
+/// Removes a container created from `config` along with the volumes created for it, volumes it
+/// reused are kept.
+pub async fn remove_created(config: DbContainerConfig, docker: &Docker) -> anyhow::Result<()> {
+    remove_container(config.name, docker).await?;
+    if !config.reuse_volumes {
+        for name in config.volumes.into_keys() {
+            remove_volume(name, docker).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Sends `SIGTERM` to the container, docker kills it once `grace` passed, after 10 seconds when
+/// `None`.
+pub async fn stop_container(
+    id: String,
+    grace: Option<Duration>,
+    docker: &Docker,
+) -> anyhow::Result<()> {
+    let options = grace.map(|grace| StopContainerOptions {
+        t: grace.as_secs() as i64,
+    });
+    // Waiting for the shutdown doesn't count against the timeout
+    let timeout = change_timeout().map(|limit| limit + grace.unwrap_or_default());
+    with_timeout(
+        "stop container",
+        timeout,
+        docker.stop_container(&id, options),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Sends `SIGKILL` to the container, which ends it without a clean shutdown.
+pub async fn kill_container(id: String, docker: &Docker) -> anyhow::Result<()> {
+    with_timeout(
+        "kill container",
+        change_timeout(),
+        docker.kill_container(&id, Some(KillContainerOptions { signal: "SIGKILL" })),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DbVolume {
+    pub name: String,
+    pub size: Option<i64>,
+    /// The tag of the image it was created for, from [`VOLUME_TAG_LABEL`].
+    pub tag: Option<String>,
+}
+
+/// Lists every volume created by db-mgr along with its size on disk, if docker knows it.
+pub async fn get_volumes(docker: &Docker) -> anyhow::Result<Vec<DbVolume>> {
+    let volume_sizes = with_timeout("get disk usage", query_timeout(), docker.df())
+        .await?
+        .volumes
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|volume| Some((volume.name, volume.usage_data?.size)))
+        .collect::<HashMap<_, _>>();
+
+    let volumes = with_timeout(
+        "list volumes",
+        query_timeout(),
+        docker.list_volumes(Some(ListVolumesOptions {
+            filters: HashMap::from([(
+                "label".to_string(),
+                vec![format!("{LABEL}={}", MANAGED_LABELS.volume)],
+            )]),
+        })),
+    )
+    .await?
+    .volumes
+    .unwrap_or_default();
+
+    Ok(managed_volumes(volumes, &volume_sizes))
+}
+
+/// Maps the listed `volumes` that carry the db-mgr volume label, with their size from `sizes`
+/// when docker measured it.
+pub fn managed_volumes(volumes: Vec<Volume>, sizes: &HashMap<String, i64>) -> Vec<DbVolume> {
+    volumes
+        .into_iter()
+        .filter(|volume| {
+            volume.labels.get(LABEL).map(String::as_str) == Some(MANAGED_LABELS.volume)
+        })
+        .map(|volume| DbVolume {
+            size: sizes.get(&volume.name).copied().filter(|size| *size >= 0),
+            tag: volume.labels.get(VOLUME_TAG_LABEL).cloned(),
+            name: volume.name,
+        })
+        .collect()
+}
+
+/// Returns the volumes that are not mounted by any of `containers`.
+pub fn find_orphans(volumes: &[DbVolume], containers: &[DbContainer]) -> Vec<DbVolume> {
+    let mounted = containers
+        .iter()
+        .flat_map(|container| container.volumes.keys())
+        .collect::<HashSet<_>>();
+
+    volumes
+        .iter()
+        .filter(|volume| !mounted.contains(&volume.name))
+        .cloned()
+        .collect()
+}
+
+pub async fn remove_volume(name: String, docker: &Docker) -> anyhow::Result<()> {
+    with_timeout(
+        "remove volume",
+        change_timeout(),
+        docker.remove_volume(&name, None),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CleanupItem {
+    pub id: String,
+    pub name: String,
+    pub size: Option<i64>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CleanupSummary {
+    pub containers: Vec<CleanupItem>,
+    pub volumes: Vec<CleanupItem>,
+    pub images: Vec<CleanupItem>,
+}
+
+impl CleanupSummary {
+    pub fn is_empty(&self) -> bool {
+        self.containers.is_empty() && self.volumes.is_empty() && self.images.is_empty()
+    }
+}
+
+/// Total size of `items`, items with unknown sizes are skipped.
+pub fn total_size(items: &[CleanupItem]) -> i64 {
+    items.iter().filter_map(|item| item.size).sum()
+}
+
+/// Collects every container and volume created by db-mgr, along with the images that are only
+/// used by those containers.
+pub async fn get_cleanup_summary(docker: &Docker) -> anyhow::Result<CleanupSummary> {
+    let all_containers = with_timeout(
+        "list containers",
+        query_timeout(),
+        docker.list_containers(Some(ListContainersOptions::<String> {
+            all: true,
+            size: true,
+            ..Default::default()
+        })),
+    )
+    .await?;
+
+    let (managed, unmanaged): (Vec<_>, Vec<_>) = all_containers.into_iter().partition(|summary| {
+        summary
+            .labels
+            .as_ref()
+            .and_then(|labels| labels.get(LABEL))
+            .map(|value| value == "container")
+            .unwrap_or(false)
+    });
+
+    let shared_images = unmanaged
+        .iter()
+        .filter_map(|summary| summary.image_id.clone())
+        .collect::<HashSet<_>>();
+    let managed_images = managed
+        .iter()
+        .filter_map(|summary| summary.image_id.clone())
+        .filter(|id| !shared_images.contains(id))
+        .collect::<HashSet<_>>();
+
+    let containers = managed
+        .into_iter()
+        .filter_map(|summary| {
+            Some(CleanupItem {
+                name: summary
+                    .names
+                    .and_then(|names| names.into_iter().next())
+                    .unwrap_or_else(|| summary.id.clone().unwrap_or_default()),
+                id: summary.id?,
+                size: summary.size_rw,
+            })
+        })
+        .collect();
+
+    let volumes = get_volumes(docker)
+        .await?
+        .into_iter()
+        .map(|volume| CleanupItem {
+            id: volume.name.clone(),
+            name: volume.name,
+            size: volume.size,
+        })
+        .collect();
+
+    let images = with_timeout(
+        "list images",
+        query_timeout(),
+        docker.list_images(Some(ListImagesOptions::<String> {
+            ..Default::default()
+        })),
+    )
+    .await?
+    .into_iter()
+    .filter(|image| managed_images.contains(&image.id))
+    .map(|image| CleanupItem {
+        name: image
+            .repo_tags
+            .first()
+            .cloned()
+            .unwrap_or_else(|| image.id.clone()),
+        id: image.id,
+        size: Some(image.size),
+    })
+    .collect();
+
+    Ok(CleanupSummary {
+        containers,
+        volumes,
+        images,
+    })
+}
+
+#[derive(Clone, Debug)]
+pub enum CleanupEvent {
+    Removed(String),
+    Failed(String),
+    Done,
+}
+
+/// Removes everything in `summary`, containers are stopped and removed first, then volumes and
+/// then images. A failure to remove one item is reported and the rest are still attempted.
+pub fn cleanup(docker: &'static Docker, summary: CleanupSummary) -> Receiver<CleanupEvent> {
+    let (mut tx, rx) = channel(5);
+
+    tokio::spawn(async move {
+        for item in summary.containers {
+            let result = async {
+                let stopped = with_timeout(
+                    "stop container",
+                    change_timeout(),
+                    docker.stop_container(&item.id, None),
+                )
+                .await;
+                match stopped {
+                    Ok(_) => {}
+                    Err(ex) if daemon_status(&ex) == Some(304) => {}
+                    Err(ex) => return Err(ex),
+                };
+
+                remove_container(item.id.clone(), docker).await
+            }
+            .await;
+
+            let event = match result {
+                Ok(_) => CleanupEvent::Removed(format!("Removed container {}", item.name)),
+                Err(ex) => CleanupEvent::Failed(format!("Container {}: {ex}", item.name)),
+            };
+            if tx.send(event).await.is_err() {
+                return;
+            }
+        }
+
+        for item in summary.volumes {
+            let event = match remove_volume(item.id.clone(), docker).await {
+                Ok(_) => CleanupEvent::Removed(format!("Removed volume {}", item.name)),
+                Err(ex) => CleanupEvent::Failed(format!("Volume {}: {ex}", item.name)),
+            };
+            if tx.send(event).await.is_err() {
+                return;
+            }
+        }
+
+        for item in summary.images {
+            let removed = with_timeout(
+                "remove image",
+                change_timeout(),
+                docker.remove_image(&item.id, Some(RemoveImageOptions::default()), None),
+            )
+            .await;
+            let event = match removed {
+                Ok(_) => CleanupEvent::Removed(format!("Removed image {}", item.name)),
+                Err(ex) => CleanupEvent::Failed(format!("Image {}: {ex}", item.name)),
+            };
+            if tx.send(event).await.is_err() {
+                return;
+            }
+        }
+
+        _ = tx.send(CleanupEvent::Done).await;
+    });
+
+    rx
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogRange {
+    Last15Minutes,
+    LastHour,
+    LastDay,
+    All,
+}
+
+impl LogRange {
+    pub const ALL: [LogRange; 4] = [
+        LogRange::Last15Minutes,
+        LogRange::LastHour,
+        LogRange::LastDay,
+        LogRange::All,
+    ];
+
+    /// The unix timestamp logs should start from, `0` for the whole history.
+    pub fn since(&self, now: i64) -> i64 {
+        match self {
+            LogRange::Last15Minutes => now - 15 * 60,
+            LogRange::LastHour => now - 60 * 60,
+            LogRange::LastDay => now - 24 * 60 * 60,
+            LogRange::All => 0,
+        }
+    }
+}
+
+impl Display for LogRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LogRange::Last15Minutes => write!(f, "Last 15 minutes"),
+            LogRange::LastHour => write!(f, "Last hour"),
+            LogRange::LastDay => write!(f, "Last 24 hours"),
+            LogRange::All => write!(f, "All"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LogExport {
+    pub lines: usize,
+    pub bytes: u64,
+    pub files: Vec<PathBuf>,
+}
+
+/// `logs.txt` becomes `logs.stdout.txt` when stdout and stderr are written to separate files.
+fn split_path(path: &Path, stream: &str) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    path.with_file_name(match path.extension() {
+        Some(extension) => format!("{stem}.{stream}.{}", extension.to_string_lossy()),
+        None => format!("{stem}.{stream}"),
+    })
+}
+
+/// Where exported logs go, the paths of the files or their writers.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LogFiles<T> {
+    /// Both streams in one file, each line prefixed with the stream it was printed on.
+    Interleaved(T),
+    Split {
+        stdout: T,
+        stderr: T,
+    },
+}
+
+impl LogFiles<PathBuf> {
+    /// The files for an export to `path`, see [`split_path`].
+    pub fn new(path: PathBuf, split: bool) -> Self {
+        match split {
+            true => LogFiles::Split {
+                stdout: split_path(&path, "stdout"),
+                stderr: split_path(&path, "stderr"),
+            },
+            false => LogFiles::Interleaved(path),
+        }
+    }
+}
+
+impl<T> LogFiles<T> {
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        let (first, second) = match self {
+            LogFiles::Interleaved(file) => (file, None),
+            LogFiles::Split { stdout, stderr } => (stdout, Some(stderr)),
+        };
+        std::iter::once(first).chain(second)
+    }
+
+    fn try_map<U, E>(&self, mut map: impl FnMut(&T) -> Result<U, E>) -> Result<LogFiles<U>, E> {
+        Ok(match self {
+            LogFiles::Interleaved(file) => LogFiles::Interleaved(map(file)?),
+            LogFiles::Split { stdout, stderr } => LogFiles::Split {
+                stdout: map(stdout)?,
+                stderr: map(stderr)?,
+            },
+        })
+    }
+}
+
+impl<W: Write> LogFiles<W> {
+    /// Writes one chunk of log output, returning how many lines and bytes it added.
+    pub fn write_output(&mut self, output: LogOutput) -> std::io::Result<(usize, u64)> {
+        let (stream, message) = match output {
+            LogOutput::StdErr { message } => ("stderr", message),
+            LogOutput::StdOut { message } => ("stdout", message),
+            LogOutput::StdIn { message } => ("stdin", message),
+            LogOutput::Console { message } => ("console", message),
+        };
+
+        let written = match self {
+            LogFiles::Split { stderr, .. } if stream == "stderr" => {
+                stderr.write_all(&message)?;
+                message.len()
+            }
+            LogFiles::Split { stdout, .. } => {
+                stdout.write_all(&message)?;
+                message.len()
+            }
+            LogFiles::Interleaved(writer) => {
+                let prefix = format!("{stream} | ");
+                writer.write_all(prefix.as_bytes())?;
+                writer.write_all(&message)?;
+                prefix.len() + message.len()
+            }
+        };
+        let lines = message.iter().filter(|byte| **byte == b'\n').count().max(1);
+
+        Ok((lines, written as u64))
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            LogFiles::Interleaved(writer) => writer.flush(),
+            LogFiles::Split { stdout, stderr } => {
+                stdout.flush()?;
+                stderr.flush()
+            }
+        }
+    }
+}
+
+/// Streams the logs of the container `id` to `path` with timestamps, either interleaved with a
+/// stream prefix or split into one file per stream. Partially written files are removed if the
+/// export fails.
+pub async fn export_logs(
+    id: String,
+    range: LogRange,
+    path: PathBuf,
+    split: bool,
+    docker: &Docker,
+) -> anyhow::Result<LogExport> {
+    let files = LogFiles::new(path, split);
+
+    let result = write_logs(docker, &id, range, &files).await;
+    if result.is_err() {
+        for file in files.iter() {
+            _ = fs::remove_file(file);
+        }
+    }
+
+    result.map(|(lines, bytes)| LogExport {
+        lines,
+        bytes,
+        files: files.iter().cloned().collect(),
+    })
+}
+
+async fn write_logs(
+    docker: &Docker,
+    id: &str,
+    range: LogRange,
+    files: &LogFiles<PathBuf>,
+) -> anyhow::Result<(usize, u64)> {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+    let mut writers = files.try_map(|file| File::create(file).map(BufWriter::new))?;
+
+    // Bounded by when the export started, lines printed while it runs are left out
+    let mut logs = docker.logs(
+        id,
+        Some(LogsOptions::<String> {
+            follow: false,
+            stdout: true,
+            stderr: true,
+            since: range.since(now),
+            until: now,
+            timestamps: true,
+            tail: "all".into(),
+        }),
+    );
+
+    let (mut lines, mut bytes) = (0, 0);
+    while let Some(output) = logs.next().await {
+        let (written_lines, written_bytes) = writers.write_output(output?)?;
+        lines += written_lines;
+        bytes += written_bytes;
+    }
+    writers.flush()?;
+
+    Ok((lines, bytes))
+}
+
+/// A line printed by a followed container, or the end of the follow.
+#[derive(Clone, Debug)]
+pub enum LogEvent {
+    /// A line with the timestamp docker prefixed it with.
+    Line { stderr: bool, line: String },
+    /// The container stopped, or the follow failed with the error.
+    Ended(Option<String>),
+}
+
+/// How many of the lines printed before the follow started are shown.
+const FOLLOW_TAIL: usize = 500;
+
+/// The lines the container `id` prints from now on, after the last [`FOLLOW_TAIL`] printed
+/// before. Dropping the stream stops following.
+pub fn follow_logs(docker: &'static Docker, id: String) -> impl Stream<Item = LogEvent> + Send {
+    docker
+        .logs(
+            &id,
+            Some(LogsOptions::<String> {
+                follow: true,
+                stdout: true,
+                stderr: true,
+                timestamps: true,
+                tail: FOLLOW_TAIL.to_string(),
+                ..Default::default()
+            }),
+        )
+        .flat_map(|output| {
+            let events = match output {
+                Ok(output) => {
+                    let stderr = matches!(output, LogOutput::StdErr { .. });
+                    String::from_utf8_lossy(&output.into_bytes())
+                        .lines()
+                        .filter(|line| !line.is_empty())
+                        .map(|line| LogEvent::Line {
+                            stderr,
+                            line: line.to_string(),
+                        })
+                        .collect()
+                }
+                Err(ex) => vec![LogEvent::Ended(Some(format!("{ex}")))],
+            };
+            stream::iter(events)
+        })
+        .chain(stream::once(async { LogEvent::Ended(None) }))
+}
@@ -0,0 +1,147 @@
+/// The variables set in the contents of a `.env` file, in file order. Comments, blank lines and
+/// `export` prefixes are skipped, values may be single or double quoted and double quoted ones
+/// may contain `\n` escapes. Values spanning several lines are not supported.
+pub fn parse_env_file(contents: &str) -> Vec<(String, String)> {
+    contents
+        .lines()
+        .filter_map(|line| parse_line(line).ok().flatten())
+        .collect()
+}
+
+/// A line of `KEY=VALUE` text that can't be used, by its index from 0.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LineError {
+    pub line: usize,
+    pub message: String,
+}
+
+/// Parses `KEY=VALUE` lines like [`parse_env_file`], but reports the malformed lines and keys
+/// set more than once instead of skipping them.
+pub fn parse_env_lines<'a>(
+    lines: impl IntoIterator<Item = &'a str>,
+) -> Result<Vec<(String, String)>, Vec<LineError>> {
+    let mut variables: Vec<(String, String)> = vec![];
+    let mut errors = vec![];
+    for (index, line) in lines.into_iter().enumerate() {
+        let message = match parse_line(line) {
+            Ok(None) => continue,
+            Ok(Some((key, _))) if variables.iter().any(|(known, _)| *known == key) => {
+                format!("{key} is set more than once")
+            }
+            Ok(Some(variable)) => {
+                variables.push(variable);
+                continue;
+            }
+            Err(message) => message,
+        };
+        errors.push(LineError {
+            line: index,
+            message,
+        });
+    }
+
+    match errors.is_empty() {
+        true => Ok(variables),
+        false => Err(errors),
+    }
+}
+
+/// Writes `variables` as `KEY=VALUE` lines that [`parse_env_file`] reads back unchanged, quoting
+/// the values that need it.
+pub fn format_env_file<'a>(variables: impl IntoIterator<Item = (&'a str, &'a str)>) -> String {
+    variables
+        .into_iter()
+        .map(|(key, value)| format!("{key}={}\n", format_value(value)))
+        .collect()
+}
+
+fn format_value(value: &str) -> String {
+    let plain = value.trim() == value
+        && !value.starts_with(['"', '\''])
+        && !value.contains(['\n', '\t', '#']);
+    if plain {
+        return value.to_string();
+    }
+
+    let mut quoted = String::from('"');
+    for c in value.chars() {
+        match c {
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            '"' | '\\' => {
+                quoted.push('\\');
+                quoted.push(c);
+            }
+            _ => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// The variable set on `line`, `None` for blank lines and comments.
+fn parse_line(line: &str) -> Result<Option<(String, String)>, String> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return Ok(None);
+    }
+
+    let line = line.strip_prefix("export ").map_or(line, str::trim_start);
+    let (key, value) = line
+        .split_once('=')
+        .ok_or_else(|| "Expected KEY=VALUE".to_string())?;
+    let key = key.trim();
+    if key.is_empty() {
+        return Err("The name before = is missing".to_string());
+    }
+    let valid = key
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.');
+    if !valid {
+        return Err(format!(
+            "{key} may only contain letters, digits, underscores and dots"
+        ));
+    }
+
+    Ok(Some((key.to_string(), parse_value(value.trim_start()))))
+}
+
+fn parse_value(value: &str) -> String {
+    let mut chars = value.chars();
+    let quote = match chars.next() {
+        Some(quote @ ('"' | '\'')) => quote,
+        // An unquoted value ends where a comment starts
+        _ => {
+            let end = value
+                .match_indices('#')
+                .find(|(index, _)| value[..*index].ends_with([' ', '\t']))
+                .map_or(value.len(), |(index, _)| index);
+            return value[..end].trim_end().to_string();
+        }
+    };
+
+    let mut parsed = String::new();
+    let mut escaped = false;
+    for c in chars {
+        match c {
+            _ if escaped => {
+                escaped = false;
+                match c {
+                    'n' => parsed.push('\n'),
+                    't' => parsed.push('\t'),
+                    '"' | '\\' => parsed.push(c),
+                    _ => {
+                        parsed.push('\\');
+                        parsed.push(c);
+                    }
+                }
+            }
+            '\\' if quote == '"' => escaped = true,
+            _ if c == quote => return parsed,
+            _ => parsed.push(c),
+        }
+    }
+
+    // Unterminated quotes take the rest of the line
+    parsed
+}
@@ -0,0 +1,274 @@
+use std::{fmt::Display, io::ErrorKind};
+
+use bollard::errors::Error;
+
+use crate::daemon::TimedOut;
+
+/// An error explained for the person using the app, the raw error is kept in `detail`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UserError {
+    pub title: String,
+    pub detail: String,
+    pub suggestion: Option<String>,
+}
+
+impl UserError {
+    fn new(title: &str, detail: impl Into<String>, suggestion: Option<&str>) -> Self {
+        Self {
+            title: title.to_string(),
+            detail: detail.into(),
+            suggestion: suggestion.map(str::to_string),
+        }
+    }
+
+    /// The dialog text, the suggestion first and the raw error last.
+    pub fn body(&self) -> String {
+        match self.suggestion.as_ref() {
+            Some(suggestion) => format!("{suggestion}\n\nDetails: {}", self.detail),
+            None => self.detail.clone(),
+        }
+    }
+}
+
+impl Display for UserError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.title, self.detail)
+    }
+}
+
+/// A title and a suggestion.
+type Explanation = (&'static str, &'static str);
+
+const PERMISSION_DENIED: Explanation = (
+    "No permission to use Docker",
+    "Add your user to the docker group with `sudo usermod -aG docker $USER` and log in again.",
+);
+
+const NOT_RUNNING: Explanation = (
+    "Docker isn't running",
+    "Start Docker (or Docker Desktop) and try again.",
+);
+
+const TIMED_OUT: Explanation = (
+    "Docker did not respond in time",
+    "Check that the Docker daemon and your network connection are working.",
+);
+
+/// Known failures matched by status code and lowercase message, checked in order.
+const KNOWN_ERRORS: &[(Option<u16>, &[&str], Explanation)] = &[
+    (
+        None,
+        &[
+            "error trying to connect: permission denied",
+            "permission denied while trying to connect",
+        ],
+        PERMISSION_DENIED,
+    ),
+    // TLS errors surface as connection errors, so they are matched first
+    (
+        None,
+        &[
+            "invalid peer certificate: expired",
+            "alert: certificateexpired",
+        ],
+        (
+            "A certificate has expired",
+            "Renew the expired certificate, or check the clock of this machine and the daemon.",
+        ),
+    ),
+    (
+        None,
+        &["invalid peer certificate: notvalidforname"],
+        (
+            "The daemon's certificate is for another host",
+            "Connect with a host name the daemon's certificate was issued for.",
+        ),
+    ),
+    (
+        None,
+        &[
+            "invalid peer certificate: unknownissuer",
+            "alert: unknownca",
+        ],
+        (
+            "The certificate isn't signed by the CA",
+            "Choose the CA certificate that signed the daemon's and the client certificate.",
+        ),
+    ),
+    (
+        None,
+        &[
+            "alert: badcertificate",
+            "alert: decrypterror",
+            "alert: certificaterequired",
+            "could not parse key",
+            "expected one:",
+        ],
+        (
+            "The daemon refused the client certificate",
+            "Check that the client key belongs to the client certificate and that both were \
+             issued by the daemon's CA.",
+        ),
+    ),
+    (
+        None,
+        &[
+            "invalid peer certificate",
+            "received fatal alert",
+            "certificate with path",
+        ],
+        (
+            "The TLS connection failed",
+            "Check the certificates and that the daemon listens for TLS on that port.",
+        ),
+    ),
+    (
+        None,
+        &[
+            "error trying to connect",
+            "cannot connect to the docker daemon",
+        ],
+        NOT_RUNNING,
+    ),
+    (
+        None,
+        &[
+            "pull access denied",
+            "unauthorized",
+            "authentication required",
+            "denied: requested access",
+        ],
+        (
+            "The registry refused the pull",
+            "Check the image name, or run `docker login` for the registry.",
+        ),
+    ),
+    (
+        Some(404),
+        &["no such container"],
+        (
+            "The container no longer exists",
+            "It was removed outside of db-mgr, refresh the container list.",
+        ),
+    ),
+    (
+        Some(404),
+        &["no such image", "manifest unknown", "not found"],
+        (
+            "The image could not be found",
+            "Check the image name and tag in config.yaml.",
+        ),
+    ),
+    (
+        Some(409),
+        &[""],
+        (
+            "The name is already in use",
+            "Pick another name or remove the existing container.",
+        ),
+    ),
+    (
+        Some(500),
+        &["port is already allocated", "address already in use"],
+        (
+            "A port is already in use",
+            "Stop whatever is using the port or choose another one.",
+        ),
+    ),
+    (
+        None,
+        &[
+            "operation timed out",
+            "deadline exceeded",
+            "timeout error",
+            "docker did not answer",
+        ],
+        TIMED_OUT,
+    ),
+];
+
+fn classify(status_code: Option<u16>, message: &str) -> Option<Explanation> {
+    let message = message.to_lowercase();
+    KNOWN_ERRORS
+        .iter()
+        .find(|(code, needles, _)| {
+            (code.is_none() || *code == status_code)
+                && needles.iter().any(|needle| message.contains(needle))
+        })
+        .map(|(_, _, explanation)| *explanation)
+}
+
+pub fn explain_docker_error(err: &Error) -> UserError {
+    let detail = err.to_string();
+    let known = match err {
+        Error::DockerResponseServerError {
+            status_code,
+            message,
+        } => classify(Some(*status_code), message),
+        Error::RequestTimeoutError => Some(TIMED_OUT),
+        Error::IOError { err } if err.kind() == ErrorKind::PermissionDenied => {
+            Some(PERMISSION_DENIED)
+        }
+        Error::IOError { err }
+            if matches!(
+                err.kind(),
+                ErrorKind::NotFound | ErrorKind::ConnectionRefused
+            ) =>
+        {
+            Some(NOT_RUNNING)
+        }
+        _ => classify(None, &detail),
+    };
+
+    match known {
+        Some((title, suggestion)) => UserError::new(title, detail, Some(suggestion)),
+        None => UserError::new("Docker returned an error", detail, None),
+    }
+}
+
+/// Explains an error from one of the docker helpers, `context` says what was being done.
+pub fn explain(context: &str, err: &anyhow::Error) -> UserError {
+    if let Some(timed_out) = err.downcast_ref::<TimedOut>() {
+        let (title, suggestion) = TIMED_OUT;
+        return UserError::new(title, format!("{context}: {timed_out}"), Some(suggestion));
+    }
+
+    match err.downcast_ref::<Error>() {
+        Some(docker_err) => {
+            let mut explained = explain_docker_error(docker_err);
+            if explained.suggestion.is_none() {
+                explained.title = context.to_string();
+            } else {
+                explained.detail = format!("{context}: {}", explained.detail);
+            }
+            explained
+        }
+        None => explain_message(context, &format!("{err:#}")),
+    }
+}
+
+/// The sysctl of `sysctls` the daemon refused in `message`, such as one that is not namespaced or
+/// one a rootless daemon may not set. The daemon names it dotted or as its `/proc/sys` path.
+pub fn rejected_sysctl<'a>(
+    message: &str,
+    sysctls: impl IntoIterator<Item = &'a String>,
+) -> Option<String> {
+    if !message.to_lowercase().contains("sysctl") && !message.contains("/proc/sys/") {
+        return None;
+    }
+
+    sysctls
+        .into_iter()
+        .find(|key| message.contains(key.as_str()) || message.contains(&key.replace('.', "/")))
+        .cloned()
+}
+
+/// Explains an error that only survived as text, such as the ones sent by background tasks.
+pub fn explain_message(context: &str, message: &str) -> UserError {
+    match classify(None, message) {
+        Some((title, suggestion)) => {
+            UserError::new(title, format!("{context}: {message}"), Some(suggestion))
+        }
+        None => UserError::new(context, message, None),
+    }
+}
@@ -0,0 +1,57 @@
+use bollard::service::ContainerStateStatusEnum;
+
+use crate::docker::{ContainerEvent, ContainerEventKind, DbContainer};
+
+/// The exit codes of a process ended by `SIGINT`, `SIGKILL` or `SIGTERM`, which is how docker
+/// stops containers rather than them failing.
+const SIGNAL_EXIT_CODES: [i64; 3] = [130, 137, 143];
+
+/// What is worth pointing out about how a container last went down and came back up.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ExitNotes {
+    /// It is down because the kernel killed it for using more memory than it may.
+    pub out_of_memory: bool,
+    /// It is down because its process failed with this exit code.
+    pub failed: Option<i64>,
+    /// The times its restart policy brought it back since it was last started by hand.
+    pub policy_restarts: i64,
+}
+
+/// The notes on `container` from its inspect and its `events`, newest first. Containers that
+/// were stopped get none, even though docker reports the exit code of the signal they were
+/// stopped with.
+pub fn exit_notes<'a>(
+    container: &DbContainer,
+    events: impl IntoIterator<Item = &'a ContainerEvent>,
+) -> ExitNotes {
+    let down = matches!(
+        container.state,
+        ContainerStateStatusEnum::EXITED
+            | ContainerStateStatusEnum::DEAD
+            | ContainerStateStatusEnum::RESTARTING
+    );
+    // What happened since it was last started
+    let last_run = events
+        .into_iter()
+        .take_while(|event| event.kind != ContainerEventKind::Started)
+        .map(|event| &event.kind)
+        .collect::<Vec<_>>();
+    let stopped = last_run.iter().any(|kind| {
+        matches!(
+            kind,
+            ContainerEventKind::Stopped | ContainerEventKind::Killed(_)
+        )
+    });
+
+    let out_of_memory =
+        down && (container.exit.oom_killed || last_run.contains(&&ContainerEventKind::OutOfMemory));
+    let failed = container.exit.exit_code.filter(|code| {
+        down && !out_of_memory && !stopped && *code != 0 && !SIGNAL_EXIT_CODES.contains(code)
+    });
+
+    ExitNotes {
+        out_of_memory,
+        failed,
+        policy_restarts: container.exit.restart_count,
+    }
+}
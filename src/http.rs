@@ -0,0 +1,179 @@
+use std::{
+    fs,
+    io::Cursor,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use anyhow::{bail, Context};
+use image::io::Reader;
+use reqwest::{Certificate, Client, NoProxy, Proxy};
+
+use crate::data::ProxyConfig;
+
+/// The proxies the client ends up using, explicit config entries win over the environment.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProxySettings {
+    pub http: Option<String>,
+    pub https: Option<String>,
+    pub no_proxy: Option<String>,
+}
+
+impl ProxySettings {
+    pub fn resolve(config: &ProxyConfig, env: impl Fn(&str) -> Option<String>) -> Self {
+        // An empty variable counts as unset, so it doesn't hide the other spelling
+        let set = |name: &str| env(name).filter(|value| !value.is_empty());
+        let from_env = |name: &str| set(&name.to_uppercase()).or_else(|| set(name));
+
+        Self {
+            http: config.http.clone().or_else(|| from_env("http_proxy")),
+            https: config.https.clone().or_else(|| from_env("https_proxy")),
+            no_proxy: config.no_proxy.clone().or_else(|| from_env("no_proxy")),
+        }
+    }
+}
+
+pub fn build_client(config: &ProxyConfig) -> anyhow::Result<Client> {
+    let settings = ProxySettings::resolve(config, |name| std::env::var(name).ok());
+    let no_proxy = settings.no_proxy.as_deref().and_then(NoProxy::from_string);
+
+    let mut builder = Client::builder().no_proxy();
+
+    if let Some(http) = settings.http.as_ref() {
+        builder = builder.proxy(
+            Proxy::http(http)
+                .with_context(|| format!("Invalid http proxy {http}"))?
+                .no_proxy(no_proxy.clone()),
+        );
+    }
+
+    if let Some(https) = settings.https.as_ref() {
+        builder = builder.proxy(
+            Proxy::https(https)
+                .with_context(|| format!("Invalid https proxy {https}"))?
+                .no_proxy(no_proxy),
+        );
+    }
+
+    if let Some(path) = config.extra_ca_cert.as_ref() {
+        let pem = fs::read(path)
+            .with_context(|| format!("Could not read CA certificate {}", path.display()))?;
+        builder = builder.add_root_certificate(
+            Certificate::from_pem(&pem)
+                .with_context(|| format!("Invalid CA certificate {}", path.display()))?,
+        );
+    }
+
+    Ok(builder.build()?)
+}
+
+/// How many times a failed icon fetch is retried.
+pub const ICON_RETRIES: u32 = 3;
+
+/// How long a single icon request may take, the default icon is shown until then anyway.
+pub const ICON_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// How long to wait before retry number `attempt`, counting from 1 and doubling from a second,
+/// `None` once `max_retries` have been used up.
+pub fn retry_delay(attempt: u32, max_retries: u32) -> Option<Duration> {
+    (1..=max_retries)
+        .contains(&attempt)
+        .then(|| Duration::from_secs(1 << (attempt - 1)))
+}
+
+async fn fetch_once(client: &Client, url: &str) -> reqwest::Result<Vec<u8>> {
+    Ok(client
+        .get(url)
+        .timeout(ICON_TIMEOUT)
+        .send()
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?
+        .to_vec())
+}
+
+/// Fetches the icon at `url`, retrying failed requests with [`retry_delay`].
+pub async fn fetch_icon(client: Client, url: String) -> anyhow::Result<Vec<u8>> {
+    let mut attempt = 0;
+    loop {
+        match fetch_once(&client, &url).await {
+            Ok(bytes) => return Ok(bytes),
+            Err(ex) => {
+                attempt += 1;
+                match retry_delay(attempt, ICON_RETRIES) {
+                    Some(delay) => tokio::time::sleep(delay).await,
+                    None => return Err(ex).with_context(|| format!("Could not fetch {url}")),
+                }
+            }
+        }
+    }
+}
+
+/// The file the icon at `url` is cached in, named by an FNV-1a hash of the url so it stays the
+/// same across releases.
+pub fn icon_cache_file(dir: &Path, url: &str) -> PathBuf {
+    let hash = url.bytes().fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    });
+    dir.join(format!("{hash:016x}"))
+}
+
+/// Checks that `bytes` are an image iced can draw by decoding its header. SVG logos are rejected
+/// since only raster images are drawn, as are error pages served with a success status.
+pub fn validate_icon(bytes: &[u8]) -> anyhow::Result<()> {
+    let start = String::from_utf8_lossy(&bytes[..bytes.len().min(512)]).to_lowercase();
+    let start = start.trim_start_matches('\u{feff}').trim_start();
+    if start.starts_with("<svg") || (start.starts_with("<?xml") && start.contains("<svg")) {
+        bail!("The icon is an SVG, only PNG, JPEG, GIF and other raster images are supported");
+    }
+    if start.starts_with("<!doctype html") || start.starts_with("<html") {
+        bail!("The icon url returned a web page instead of an image");
+    }
+
+    Reader::new(Cursor::new(bytes))
+        .with_guessed_format()?
+        .into_dimensions()
+        .context("The icon is not an image")?;
+    Ok(())
+}
+
+/// Checks a cached icon by decoding its header, caches written before icons were validated may
+/// hold error pages.
+pub fn validate_cached_icon(file: &Path) -> anyhow::Result<()> {
+    Reader::open(file)?
+        .with_guessed_format()?
+        .into_dimensions()?;
+    Ok(())
+}
+
+/// Fetches the icon at `url` like [`fetch_icon`] and keeps a copy in `cache_file`, failing to
+/// cache it only means it is fetched again next time. Bytes that aren't an image are never
+/// cached.
+pub async fn fetch_icon_cached(
+    client: Client,
+    url: String,
+    cache_file: Option<PathBuf>,
+) -> anyhow::Result<Vec<u8>> {
+    let bytes = fetch_icon(client, url.clone()).await?;
+    validate_icon(&bytes).with_context(|| format!("Invalid icon at {url}"))?;
+    if let Some(file) = cache_file {
+        let written = match file.parent() {
+            Some(dir) => fs::create_dir_all(dir).and_then(|_| fs::write(&file, &bytes)),
+            None => fs::write(&file, &bytes),
+        };
+        if let Err(ex) = written {
+            eprintln!("Could not cache the icon in {}: {ex}", file.display());
+        }
+    }
+
+    Ok(bytes)
+}
+
+/// Fetches `url` and returns how long the request took.
+pub async fn test_connection(client: Client, url: String) -> anyhow::Result<Duration> {
+    let start = Instant::now();
+    client.get(&url).send().await?.error_for_status()?;
+
+    Ok(start.elapsed())
+}
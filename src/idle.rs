@@ -0,0 +1,121 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+const DAY: u64 = 24 * 60 * 60;
+
+/// After how many untouched days a running container counts as idle, `0` for never.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct IdleAfter(pub u64);
+
+impl IdleAfter {
+    pub const OPTIONS: [IdleAfter; 5] = [
+        IdleAfter(0),
+        IdleAfter(3),
+        IdleAfter(7),
+        IdleAfter(14),
+        IdleAfter(30),
+    ];
+
+    fn seconds(&self) -> Option<u64> {
+        (self.0 > 0).then_some(self.0 * DAY)
+    }
+}
+
+impl Default for IdleAfter {
+    fn default() -> Self {
+        IdleAfter(7)
+    }
+}
+
+impl Display for IdleAfter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            0 => write!(f, "Never"),
+            days => write!(f, "{}", format_days(days)),
+        }
+    }
+}
+
+/// Such as `1 day` or `12 days`.
+pub fn format_days(days: u64) -> String {
+    match days {
+        1 => "1 day".to_string(),
+        days => format!("{days} days"),
+    }
+}
+
+/// Seconds since the unix epoch, the unit of the times kept by [`IdleTracker`].
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|since| since.as_secs())
+        .unwrap_or_default()
+}
+
+/// When each container was last started or viewed and when the user was last asked to stop it,
+/// keyed by container id.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct IdleTracker {
+    #[serde(default)]
+    last_used: HashMap<String, u64>,
+    #[serde(default)]
+    prompted: HashMap<String, u64>,
+    /// Containers the user never wants to be asked about.
+    #[serde(default)]
+    ignored: HashSet<String>,
+}
+
+impl IdleTracker {
+    pub fn touch(&mut self, id: &str, now: u64) {
+        self.last_used.insert(id.to_string(), now);
+    }
+
+    /// Starts the clock for containers seen for the first time and forgets removed ones.
+    /// Returns whether anything changed.
+    pub fn sync<'a>(&mut self, ids: impl IntoIterator<Item = &'a str>, now: u64) -> bool {
+        let ids = ids.into_iter().collect::<HashSet<_>>();
+        let before = self.clone();
+
+        self.last_used.retain(|id, _| ids.contains(id.as_str()));
+        self.prompted.retain(|id, _| ids.contains(id.as_str()));
+        self.ignored.retain(|id| ids.contains(id.as_str()));
+        for id in ids {
+            self.last_used.entry(id.to_string()).or_insert(now);
+        }
+
+        *self != before
+    }
+
+    /// How many whole days the container has been running untouched, `None` unless that is at
+    /// least `after` and the container isn't ignored.
+    pub fn idle_days(&self, id: &str, running: bool, after: IdleAfter, now: u64) -> Option<u64> {
+        let after = after.seconds()?;
+        if !running || self.ignored.contains(id) {
+            return None;
+        }
+
+        let untouched = now.saturating_sub(*self.last_used.get(id)?);
+        (untouched >= after).then_some(untouched / DAY)
+    }
+
+    /// Whether the user may be asked about the container again, at most once a day.
+    pub fn may_prompt(&self, id: &str, now: u64) -> bool {
+        self.prompted
+            .get(id)
+            .is_none_or(|at| now.saturating_sub(*at) >= DAY)
+    }
+
+    pub fn prompted(&mut self, id: &str, now: u64) {
+        self.prompted.insert(id.to_string(), now);
+    }
+
+    pub fn ignore(&mut self, id: &str) {
+        self.ignored.insert(id.to_string());
+    }
+}
@@ -0,0 +1,137 @@
+use std::fmt::Display;
+
+const DEFAULT_REGISTRY: &str = "docker.io";
+const OFFICIAL_NAMESPACE: &str = "library/";
+
+/// A parsed docker image reference such as `ghcr.io:5000/org/pg:16` or `postgres@sha256:...`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ImageRef {
+    /// The registry host, `None` for docker hub.
+    pub registry: Option<String>,
+    /// The repository path, official docker hub images get the implicit `library/` namespace.
+    pub repository: String,
+    pub tag: Option<String>,
+    pub digest: Option<String>,
+}
+
+impl ImageRef {
+    pub fn parse(reference: &str) -> Self {
+        let (rest, digest) = match reference.split_once('@') {
+            Some((rest, digest)) => (rest, Some(digest.to_string())),
+            None => (reference, None),
+        };
+
+        // A `:` after the last `/` separates the tag, any earlier `:` belongs to a registry port.
+        let (name, tag) = match rest.rsplit_once(':') {
+            Some((name, tag)) if !tag.contains('/') => (name, Some(tag.to_string())),
+            _ => (rest, None),
+        };
+
+        let (registry, repository) = match name.split_once('/') {
+            Some((host, path))
+                if host.contains('.') || host.contains(':') || host == "localhost" =>
+            {
+                (Some(host.to_string()), path.to_string())
+            }
+            _ => (None, name.to_string()),
+        };
+
+        let registry = registry.filter(|registry| registry != DEFAULT_REGISTRY);
+        let repository = if registry.is_none() && !repository.contains('/') {
+            format!("{OFFICIAL_NAMESPACE}{repository}")
+        } else {
+            repository
+        };
+
+        let tag = match (tag, &digest) {
+            (None, None) => Some("latest".to_string()),
+            (tag, _) => tag,
+        };
+
+        Self {
+            registry,
+            repository,
+            tag,
+            digest,
+        }
+    }
+
+    /// Returns a copy of this reference pinned to `reference`, which is either a tag or a digest
+    /// such as `sha256:...`.
+    pub fn with_reference(&self, reference: &str) -> Self {
+        let reference = reference.strip_prefix('@').unwrap_or(reference);
+        let (tag, digest) = if reference.contains(':') {
+            (None, Some(reference.to_string()))
+        } else {
+            (Some(reference.to_string()), None)
+        };
+
+        Self {
+            tag,
+            digest,
+            ..self.clone()
+        }
+    }
+
+    /// The image name without a tag or digest, as passed to `from_image` when pulling.
+    pub fn name(&self) -> String {
+        let repository = match self.registry {
+            None => self
+                .repository
+                .strip_prefix(OFFICIAL_NAMESPACE)
+                .unwrap_or(&self.repository),
+            Some(_) => &self.repository,
+        };
+
+        match &self.registry {
+            Some(registry) => format!("{registry}/{repository}"),
+            None => repository.to_string(),
+        }
+    }
+
+    /// The digest if the reference is pinned, otherwise the tag.
+    pub fn reference(&self) -> &str {
+        self.digest
+            .as_deref()
+            .or(self.tag.as_deref())
+            .unwrap_or("latest")
+    }
+
+    /// A key that identifies the image regardless of tag or digest.
+    pub fn key(&self) -> String {
+        format!(
+            "{}/{}",
+            self.registry.as_deref().unwrap_or(DEFAULT_REGISTRY),
+            self.repository
+        )
+    }
+}
+
+impl Display for ImageRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())?;
+        if let Some(tag) = &self.tag {
+            write!(f, ":{tag}")?;
+        }
+        if let Some(digest) = &self.digest {
+            write!(f, "@{digest}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The repository an image built for the config entry `database` is tagged with, such as
+/// `db-mgr/postgres-pgvector`.
+pub fn built_image_name(database: &str) -> String {
+    let name = database
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect::<String>();
+
+    match name.trim_matches('-') {
+        "" => "db-mgr/custom".to_string(),
+        name => format!("db-mgr/{name}"),
+    }
+}
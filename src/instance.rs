@@ -0,0 +1,201 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, ErrorKind, Write},
+    net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use futures::{future, stream, Stream, StreamExt};
+use tokio::io::{AsyncBufReadExt, BufReader};
+
+/// Starts another instance even when one is already running, for people who want two profiles
+/// open.
+pub const NEW_INSTANCE_FLAG: &str = "--new-instance";
+
+/// What a second instance sends the running one before exiting.
+const FOCUS_REQUEST: &str = "focus";
+
+/// How long a second instance waits for the running one to take its focus request.
+const FOCUS_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// The instance recorded in the lock file, as `<pid> <port>`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LockOwner {
+    pub pid: u32,
+    /// The local port the instance takes focus requests on.
+    pub port: u16,
+}
+
+impl LockOwner {
+    pub fn parse(contents: &str) -> Option<Self> {
+        let (pid, port) = contents.trim().split_once(' ')?;
+        Some(Self {
+            pid: pid.parse().ok()?,
+            port: port.parse().ok()?,
+        })
+    }
+
+    fn to_line(self) -> String {
+        format!("{} {}\n", self.pid, self.port)
+    }
+}
+
+/// The lock file of the running instance, removed when it is dropped.
+#[derive(Debug)]
+pub struct InstanceLock {
+    path: PathBuf,
+    owner: LockOwner,
+}
+
+impl Drop for InstanceLock {
+    fn drop(&mut self) {
+        // Another instance may have taken over a lock it thought stale
+        let owned = fs::read_to_string(&self.path)
+            .ok()
+            .and_then(|contents| LockOwner::parse(&contents))
+            == Some(self.owner);
+        if owned {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum Acquired {
+    /// This is the only instance.
+    Lock(InstanceLock),
+    /// A live instance holds the lock.
+    Running(LockOwner),
+}
+
+/// Takes the lock at `path` for `owner` unless a live instance holds it, `is_alive` says whether
+/// a pid is still running. Locks of dead processes and unreadable locks are stale and taken over.
+pub fn acquire(
+    path: &Path,
+    owner: LockOwner,
+    is_alive: impl Fn(u32) -> bool,
+) -> io::Result<Acquired> {
+    // The second attempt follows removing a stale lock
+    for _ in 0..2 {
+        match OpenOptions::new().write(true).create_new(true).open(path) {
+            Ok(mut file) => {
+                file.write_all(owner.to_line().as_bytes())?;
+                return Ok(Acquired::Lock(InstanceLock {
+                    path: path.to_path_buf(),
+                    owner,
+                }));
+            }
+            Err(ex) if ex.kind() == ErrorKind::AlreadyExists => {
+                let existing = fs::read_to_string(path)
+                    .ok()
+                    .and_then(|contents| LockOwner::parse(&contents));
+                match existing {
+                    Some(existing) if existing.pid != owner.pid && is_alive(existing.pid) => {
+                        return Ok(Acquired::Running(existing))
+                    }
+                    _ => fs::remove_file(path)?,
+                }
+            }
+            Err(ex) => return Err(ex),
+        }
+    }
+
+    Err(io::Error::new(
+        ErrorKind::AlreadyExists,
+        format!("{} was taken by another instance", path.display()),
+    ))
+}
+
+/// Whether the process `pid` is running. Other platforms can't tell, so a lock whose owner
+/// doesn't take focus requests is treated as stale instead.
+#[cfg(unix)]
+pub fn is_process_alive(pid: u32) -> bool {
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return false;
+    };
+    // SAFETY: signal 0 only checks that the process exists and may be signalled
+    let result = unsafe { libc::kill(pid, 0) };
+    result == 0 || io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+#[cfg(not(unix))]
+pub fn is_process_alive(_pid: u32) -> bool {
+    true
+}
+
+/// Asks the instance listening on `port` to bring its window to the front.
+pub fn request_focus(port: u16) -> io::Result<()> {
+    let address = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
+    let mut stream = TcpStream::connect_timeout(&address, FOCUS_TIMEOUT)?;
+    stream.set_write_timeout(Some(FOCUS_TIMEOUT))?;
+    stream.write_all(format!("{FOCUS_REQUEST}\n").as_bytes())
+}
+
+/// The outcome of making this the only running instance.
+#[derive(Debug)]
+pub enum Claim {
+    /// This is the only instance, focus requests arrive on the listener.
+    Single(InstanceLock, TcpListener),
+    /// Another instance was asked to focus, this one should exit.
+    Focused,
+    /// The check could not be done, such as in a read-only data directory, so it is skipped.
+    Unchecked(String),
+}
+
+/// Makes this the only running instance by holding the lock at `path`, or asks the running one to
+/// focus. An instance that holds the lock but doesn't take the request is stale.
+pub fn claim(path: &Path) -> Claim {
+    let attempt = || -> io::Result<Claim> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+        listener.set_nonblocking(true)?;
+        let owner = LockOwner {
+            pid: std::process::id(),
+            port: listener.local_addr()?.port(),
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let running = match acquire(path, owner, is_process_alive)? {
+            Acquired::Lock(lock) => return Ok(Claim::Single(lock, listener)),
+            Acquired::Running(running) => running,
+        };
+        if request_focus(running.port).is_ok() {
+            return Ok(Claim::Focused);
+        }
+
+        fs::remove_file(path)?;
+        match acquire(path, owner, is_process_alive)? {
+            Acquired::Lock(lock) => Ok(Claim::Single(lock, listener)),
+            Acquired::Running(_) => Ok(Claim::Unchecked(
+                "another instance took the lock at the same time".to_string(),
+            )),
+        }
+    };
+
+    attempt().unwrap_or_else(|ex| Claim::Unchecked(ex.to_string()))
+}
+
+/// Emits once for each focus request a second instance sends to `listener`.
+pub fn focus_requests(listener: &TcpListener) -> impl Stream<Item = ()> {
+    let listener = listener.try_clone().ok();
+    // The tokio listener has to be made inside the runtime
+    stream::once(async move { tokio::net::TcpListener::from_std(listener?).ok() })
+        .filter_map(future::ready)
+        .flat_map(|listener| stream::unfold(listener, next_focus_request))
+}
+
+async fn next_focus_request(
+    listener: tokio::net::TcpListener,
+) -> Option<((), tokio::net::TcpListener)> {
+    loop {
+        let (socket, _) = listener.accept().await.ok()?;
+        let mut line = String::new();
+        let read =
+            tokio::time::timeout(FOCUS_TIMEOUT, BufReader::new(socket).read_line(&mut line)).await;
+        if matches!(read, Ok(Ok(_))) && line.trim() == FOCUS_REQUEST {
+            return Some(((), listener));
+        }
+    }
+}
@@ -3,21 +3,87 @@
 use std::process::exit;
 
 use app::DbMgrApp;
-use bollard::Docker;
-use data::read_config_file;
+use data::{read_config_file, read_preferences, Paths};
+use errors::explain;
 use iced::{Application, Font, Settings};
+use instance::{Claim, NEW_INSTANCE_FLAG};
 
 mod app;
+mod backup;
+mod cli;
+mod daemon;
 mod data;
+mod definition;
+mod dependencies;
+mod diff;
 mod docker;
+mod env_file;
+mod errors;
+mod exits;
+mod http;
+mod idle;
+mod image_ref;
+mod instance;
+mod migrate;
+mod names;
+mod platform;
+mod ports;
+mod registry;
+mod run_command;
+mod start_order;
+mod state_file;
+mod template;
+mod tls;
+mod validate;
+mod versions;
 
 fn main() {
-    let config = read_config_file();
-    let docker = match Docker::connect_with_local_defaults() {
+    let args = std::env::args().collect::<Vec<_>>();
+    let paths = Paths::from_env(&args);
+    if args.get(1).map(String::as_str) == Some("list") {
+        exit(cli::list(&args, paths.as_ref()));
+    }
+    let preferences = read_preferences(paths.as_ref());
+    let single_instance =
+        preferences.single_instance && !args.iter().any(|arg| arg == NEW_INSTANCE_FLAG);
+    let claim = paths
+        .as_ref()
+        .filter(|_| single_instance)
+        .map(|paths| instance::claim(&paths.lock_file()));
+    // The lock is held until the app exits, so starting db-mgr again focuses this one
+    let (_instance_lock, focus_listener) = match claim {
+        Some(Claim::Single(lock, listener)) => (Some(lock), Some(listener)),
+        Some(Claim::Focused) => {
+            println!("db-mgr is already running, focusing its window");
+            exit(0);
+        }
+        Some(Claim::Unchecked(reason)) => {
+            eprintln!("Could not check for a running db-mgr: {reason}");
+            (None, None)
+        }
+        None => (None, None),
+    };
+
+    let (config, config_problem) = read_config_file(paths.as_ref());
+    daemon::configure(config.daemon);
+    if let Some(problem) = config_problem {
+        if let Err(dialog_err) = native_dialog::MessageDialog::new()
+            .set_title("Could not use the config file")
+            .set_text(&format!("{problem}\n\nThe default config is used instead."))
+            .set_type(native_dialog::MessageType::Warning)
+            .show_alert()
+        {
+            eprintln!("Dialog Error: {dialog_err}");
+        }
+    }
+    let read_only = preferences.read_only || args.iter().any(|arg| arg == "--read-only");
+    let docker = match tls::connect(&preferences.docker_tls, preferences.docker_host.as_deref()) {
         Ok(val) => val,
         Err(ex) => {
+            let explained = explain("Could not connect to docker", &ex);
             if let Err(dialog_err) = native_dialog::MessageDialog::new()
-                .set_text(&format!("Error connecting to docker {ex}"))
+                .set_title(&explained.title)
+                .set_text(&explained.body())
                 .set_type(native_dialog::MessageType::Error)
                 .show_alert()
             {
@@ -33,11 +99,19 @@ fn main() {
         antialiasing: true,
         default_font: Font::DEFAULT,
         default_text_size: 16.0,
-        exit_on_close_request: true,
+        // Closing may stop the running containers first, see UiMsg::CloseRequested
+        exit_on_close_request: false,
         window: iced::window::Settings {
             ..Default::default()
         },
-        flags: (docker, config),
+        flags: (
+            docker,
+            config,
+            preferences,
+            read_only,
+            paths,
+            focus_listener,
+        ),
     }) {
         Ok(val) => val,
         Err(ex) => {
@@ -0,0 +1,129 @@
+use std::collections::HashMap;
+
+use bollard::service::ContainerStateStatusEnum;
+use itertools::Itertools;
+
+use crate::{
+    data::DatabaseConfig,
+    docker::{DbContainer, DbContainerConfig, DATABASE_LABEL, LABEL_SCHEMA_VERSION, SCHEMA_LABEL},
+    image_ref::ImageRef,
+};
+
+/// What updating the metadata of a container created by an older version would change.
+///
+/// Docker can't change the labels of an existing container, so every plan is carried out by
+/// recreating the container with its volumes kept.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MigrationPlan {
+    pub from: Option<u32>,
+    pub changes: Vec<String>,
+}
+
+impl MigrationPlan {
+    pub fn explanation(&self) -> String {
+        let created_by = match self.from {
+            Some(version) => format!("label schema v{version}"),
+            None => "an older version of db-mgr".to_string(),
+        };
+
+        format!(
+            "Created by {created_by}, some features may not work. Updating its metadata recreates the container and keeps its volumes."
+        )
+    }
+}
+
+/// The label schema version a container was created with, `None` for containers from before
+/// the schema was versioned.
+pub fn schema_version(container: &DbContainer) -> Option<u32> {
+    container.labels.get(SCHEMA_LABEL)?.parse().ok()
+}
+
+/// Works out what has to change to bring `container` up to [`LABEL_SCHEMA_VERSION`], `None` if it
+/// is up to date. `database` is the config entry the container was created from, if known.
+pub fn plan_migration(
+    container: &DbContainer,
+    database: Option<&DatabaseConfig>,
+) -> Option<MigrationPlan> {
+    let from = schema_version(container);
+    if from.is_some_and(|version| version >= LABEL_SCHEMA_VERSION) {
+        return None;
+    }
+
+    let mut changes = vec![match from {
+        Some(version) => {
+            format!("Update the label schema from v{version} to v{LABEL_SCHEMA_VERSION}")
+        }
+        None => format!("Record the label schema version v{LABEL_SCHEMA_VERSION}"),
+    }];
+
+    if !container.labels.contains_key(DATABASE_LABEL) {
+        changes.push(match database {
+            Some(database) => format!("Record that it was created from {}", database.name),
+            None => "Its config entry can't be found, it will be recorded as unknown".to_string(),
+        });
+    }
+
+    let skipped = container
+        .variables
+        .iter()
+        .filter(|(_, value)| value.as_text().is_none())
+        .map(|(key, _)| key)
+        .sorted()
+        .join(", ");
+    if !skipped.is_empty() {
+        changes.push(format!(
+            "{skipped} will not be copied, their values are too large or binary"
+        ));
+    }
+
+    Some(MigrationPlan { from, changes })
+}
+
+/// The config to recreate `container` with unchanged apart from its labels.
+pub fn migration_config(
+    container: &DbContainer,
+    database: Option<&DatabaseConfig>,
+) -> DbContainerConfig {
+    let image = ImageRef::parse(&container.image);
+
+    DbContainerConfig {
+        name: container.name.trim_start_matches('/').to_string(),
+        database: database
+            .map(|database| database.name.clone())
+            .or_else(|| container.labels.get(DATABASE_LABEL).cloned())
+            .unwrap_or_default(),
+        variables: container
+            .variables
+            .iter()
+            .filter_map(|(key, value)| Some((key.clone(), value.as_text()?.to_string())))
+            .collect(),
+        image: image.name(),
+        tag: image.reference().to_string(),
+        volumes: container.volumes.clone(),
+        command: container.command.clone(),
+        reuse_volumes: true,
+        force_shared_volume: false,
+        start_after_create: container.state == ContainerStateStatusEnum::RUNNING,
+        dry_run: false,
+        shm_size_mb: container.shm_size_mb,
+        cpuset_cpus: container.cpuset_cpus.clone(),
+        tmpfs: container.tmpfs.clone(),
+        tmpfs_size: container.tmpfs_size,
+        hostname: container.hostname.clone(),
+        extra_hosts: container.extra_hosts.clone(),
+        user: container.user.clone(),
+        build: None,
+        depends_on: container.depends_on.clone(),
+        extra_variables: container.extra_variables(),
+        read_only_volumes: container.read_only_volumes.clone(),
+        volume_options: HashMap::new(),
+        ulimits: container.ulimits.clone(),
+        sysctls: container.sysctls.clone(),
+        project: container.project().map(String::from),
+        companion_of: container.companion_of().map(String::from),
+        published_ports: container.published_ports(),
+        host_port: container.port_assignment(),
+        gpus: container.gpus.clone(),
+        log: container.log.clone().unwrap_or_default(),
+    }
+}
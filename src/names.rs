@@ -0,0 +1,62 @@
+use rand::{seq::SliceRandom, Rng};
+
+const ADJECTIVES: &[&str] = &[
+    "agile", "bold", "brave", "bright", "calm", "clever", "cosy", "crisp", "daring", "eager",
+    "fancy", "fierce", "gentle", "glad", "happy", "hardy", "humble", "jolly", "keen", "kind",
+    "lively", "lucky", "mellow", "merry", "nimble", "noble", "plucky", "proud", "quick", "quiet",
+    "rapid", "shiny", "silent", "sleepy", "snappy", "steady", "sunny", "swift", "tidy", "witty",
+];
+
+const NOUNS: &[&str] = &[
+    "badger", "beaver", "bison", "crane", "dingo", "dolphin", "falcon", "ferret", "gecko", "heron",
+    "ibis", "jackal", "kiwi", "koala", "lemur", "lynx", "marten", "moose", "narwhal", "otter",
+    "panda", "pelican", "penguin", "puffin", "quokka", "raven", "robin", "salmon", "seal",
+    "sparrow", "stoat", "tapir", "tiger", "toucan", "turtle", "walrus", "weasel", "wombat", "yak",
+    "zebra",
+];
+
+/// How many random names are tried before a number is added to one of them.
+const ATTEMPTS: usize = 10;
+
+fn random_name(rng: &mut impl Rng) -> String {
+    format!(
+        "{}-{}",
+        ADJECTIVES.choose(rng).unwrap_or(&"scratch"),
+        NOUNS.choose(rng).unwrap_or(&"db")
+    )
+}
+
+/// The hostname a container named `name` gets when none is given: lowercase letters, digits and
+/// dashes, at most 63 characters.
+pub fn default_hostname(name: &str) -> String {
+    let hostname = name
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .take(63)
+        .collect::<String>();
+
+    match hostname.trim_matches('-') {
+        "" => "db".to_string(),
+        hostname => hostname.to_string(),
+    }
+}
+
+/// Generates a readable `adjective-noun` name that isn't one of `taken`, numbering it if every
+/// attempt collides.
+pub fn generate_name(rng: &mut impl Rng, taken: &[String]) -> String {
+    let is_taken = |name: &str| taken.iter().any(|taken| taken == name);
+
+    let mut name = String::new();
+    for _ in 0..ATTEMPTS {
+        name = random_name(rng);
+        if !is_taken(&name) {
+            return name;
+        }
+    }
+
+    (2..)
+        .map(|number| format!("{name}-{number}"))
+        .find(|numbered| !is_taken(numbered))
+        .unwrap_or(name)
+}
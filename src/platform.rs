@@ -0,0 +1,130 @@
+use std::path::PathBuf;
+
+use bollard::{errors::Error, Docker, API_DEFAULT_VERSION};
+
+/// The named pipe Docker Desktop and the Windows engine listen on.
+pub const WINDOWS_PIPE: &str = "npipe:////./pipe/docker_engine";
+
+/// Seconds bollard waits for a response, the same as its own defaults.
+const CONNECT_TIMEOUT_SECS: u64 = 120;
+
+/// The endpoint to connect to: the `docker_host` setting, then `DOCKER_HOST`, `None` for the
+/// platform's default.
+pub fn configured_host(setting: Option<&str>) -> Option<String> {
+    setting
+        .map(str::trim)
+        .filter(|host| !host.is_empty())
+        .map(String::from)
+        .or_else(|| {
+            std::env::var("DOCKER_HOST")
+                .ok()
+                .filter(|host| !host.trim().is_empty())
+        })
+}
+
+/// Connects to the daemon at the `docker_host` setting or `DOCKER_HOST`, picking the transport
+/// by its scheme. Without either the named pipe is used on Windows and the socket elsewhere.
+pub fn connect(setting: Option<&str>) -> Result<Docker, Error> {
+    match configured_host(setting) {
+        Some(host) if host.starts_with("tcp://") || host.starts_with("http://") => {
+            Docker::connect_with_http(&host, CONNECT_TIMEOUT_SECS, API_DEFAULT_VERSION)
+        }
+        // Strips `unix://` or `npipe://` itself
+        Some(host) => Docker::connect_with_local(&host, CONNECT_TIMEOUT_SECS, API_DEFAULT_VERSION),
+        None if cfg!(windows) => {
+            Docker::connect_with_local(WINDOWS_PIPE, CONNECT_TIMEOUT_SECS, API_DEFAULT_VERSION)
+        }
+        None => Docker::connect_with_local_defaults(),
+    }
+}
+
+/// What the daemon reports about the system it runs on, from `docker info`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DaemonPlatform {
+    /// `linux` or `windows`.
+    pub os_type: Option<String>,
+    /// Such as `Docker Desktop` or `Ubuntu 22.04.3 LTS`.
+    pub operating_system: Option<String>,
+}
+
+impl DaemonPlatform {
+    pub fn is_linux(&self) -> bool {
+        self.os_type
+            .as_deref()
+            .is_some_and(|os| os.eq_ignore_ascii_case("linux"))
+    }
+
+    /// A Linux engine installed in a WSL2 distro rather than Docker Desktop, it sees the Windows
+    /// drives under `/mnt`.
+    pub fn is_wsl(&self) -> bool {
+        self.is_linux()
+            && !self
+                .operating_system
+                .as_deref()
+                .is_some_and(|os| os.contains("Docker Desktop"))
+    }
+}
+
+/// Whether `path` is a Windows path such as `C:\Users\me\data`, `C:/data` or `\\server\share`.
+pub fn is_windows_path(path: &str) -> bool {
+    let bytes = path.as_bytes();
+    let drive = bytes.len() >= 3
+        && bytes[0].is_ascii_alphabetic()
+        && bytes[1] == b':'
+        && matches!(bytes[2], b'\\' | b'/');
+    drive || path.starts_with("\\\\")
+}
+
+/// Whether the source of a mount is a host folder to bind rather than the name of a volume,
+/// which can't contain slashes.
+pub fn is_host_path(source: &str) -> bool {
+    source.starts_with('/') || is_windows_path(source)
+}
+
+/// The source of the bind mount of the host folder `path` as the daemon expects it. Docker
+/// Desktop and Windows engines take Windows paths as they are, an engine in WSL2 needs them as
+/// `/mnt/c/...`. `windows_host` is whether db-mgr runs on Windows.
+pub fn daemon_bind_source(path: &str, windows_host: bool, daemon: &DaemonPlatform) -> String {
+    if !windows_host || !daemon.is_wsl() || !is_windows_path(path) || path.starts_with("\\\\") {
+        return path.to_string();
+    }
+
+    let (drive, rest) = path.split_at(2);
+    let rest = rest
+        .split(['\\', '/'])
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("/");
+    let drive = drive[..1].to_ascii_lowercase();
+    if rest.is_empty() {
+        format!("/mnt/{drive}")
+    } else {
+        format!("/mnt/{drive}/{rest}")
+    }
+}
+
+/// The host folder of the bind mount source `source` the daemon reports, turning the
+/// `/mnt/c/...` of WSL2 and the `/run/desktop/mnt/host/c/...` of Docker Desktop back into
+/// `C:\...` on Windows.
+pub fn host_bind_path(source: &str, windows_host: bool) -> PathBuf {
+    if !windows_host {
+        return PathBuf::from(source);
+    }
+
+    let drive_path = source
+        .strip_prefix("/run/desktop/mnt/host/")
+        .or_else(|| source.strip_prefix("/mnt/"));
+    let Some(drive_path) = drive_path else {
+        return PathBuf::from(source);
+    };
+    let (drive, rest) = drive_path.split_once('/').unwrap_or((drive_path, ""));
+    if drive.len() != 1 || !drive.as_bytes()[0].is_ascii_alphabetic() {
+        return PathBuf::from(source);
+    }
+
+    PathBuf::from(format!(
+        "{}:\\{}",
+        drive.to_ascii_uppercase(),
+        rest.replace('/', "\\")
+    ))
+}
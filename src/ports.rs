@@ -0,0 +1,111 @@
+use std::{collections::HashSet, fmt::Display, net::TcpListener, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::docker::DbContainer;
+
+/// The host ports automatically assigned ones are picked from, the `ports` section of
+/// config.yaml.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PortRange {
+    #[serde(default = "default_first")]
+    pub first: u16,
+    #[serde(default = "default_last")]
+    pub last: u16,
+}
+
+fn default_first() -> u16 {
+    54000
+}
+
+fn default_last() -> u16 {
+    54999
+}
+
+impl Default for PortRange {
+    fn default() -> Self {
+        Self {
+            first: default_first(),
+            last: default_last(),
+        }
+    }
+}
+
+impl Display for PortRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}-{}", self.first, self.last)
+    }
+}
+
+/// The database port of a container published on a port of `127.0.0.1`, recorded as
+/// [`HOST_PORT_LABEL`](crate::docker::HOST_PORT_LABEL) so recreating the container keeps it.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PortAssignment {
+    /// The port inside the container.
+    pub container: u16,
+    /// Picked from the [`PortRange`] when the container is created if `None`.
+    pub host: Option<u16>,
+}
+
+impl PortAssignment {
+    pub fn auto(container: u16) -> Self {
+        Self {
+            container,
+            host: None,
+        }
+    }
+}
+
+/// Written as `container:host`, such as `5432:54000`, or `5432:auto` before one is picked.
+impl Display for PortAssignment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.host {
+            Some(host) => write!(f, "{}:{host}", self.container),
+            None => write!(f, "{}:auto", self.container),
+        }
+    }
+}
+
+impl FromStr for PortAssignment {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("{value} is not a container:host port pair");
+        let (container, host) = value.split_once(':').ok_or_else(invalid)?;
+        Ok(Self {
+            container: container.parse().map_err(|_| invalid())?,
+            host: match host {
+                "auto" => None,
+                host => Some(host.parse().map_err(|_| invalid())?),
+            },
+        })
+    }
+}
+
+/// The host ports of the managed containers, both the assigned ones, which stopped containers
+/// keep, and the ones docker bound.
+pub fn taken_ports(containers: &[DbContainer]) -> HashSet<u16> {
+    containers
+        .iter()
+        .filter_map(|container| container.port_assignment()?.host)
+        .chain(
+            containers
+                .iter()
+                .flat_map(|container| container.ports.iter().map(|port| port.host_port)),
+        )
+        .collect()
+}
+
+/// Whether nothing listens on `port` of `127.0.0.1`, where published ports are bound.
+pub fn is_free(port: u16) -> bool {
+    TcpListener::bind(("127.0.0.1", port)).is_ok()
+}
+
+/// The first port of `range` that isn't `taken` and passes `is_free`, `None` when all are used.
+pub fn pick_port(
+    range: PortRange,
+    taken: &HashSet<u16>,
+    is_free: impl Fn(u16) -> bool,
+) -> Option<u16> {
+    (range.first..=range.last).find(|port| !taken.contains(port) && is_free(*port))
+}
@@ -0,0 +1,294 @@
+use std::{collections::HashMap, path::PathBuf, time::Duration};
+
+use anyhow::{anyhow, Context};
+use itertools::Itertools;
+use reqwest::{
+    header::{HeaderMap, ACCEPT, AUTHORIZATION, WWW_AUTHENTICATE},
+    Client, RequestBuilder, StatusCode,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::image_ref::ImageRef;
+
+const DAY: u64 = 24 * 60 * 60;
+
+/// How many registries are asked per check, the other images wait for the next check.
+const MAX_LOOKUPS: usize = 10;
+
+const LOOKUP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Multi-platform indexes first, their digest is the one docker records when pulling a tag.
+const MANIFEST_TYPES: [&str; 4] = [
+    "application/vnd.oci.image.index.v1+json",
+    "application/vnd.docker.distribution.manifest.list.v2+json",
+    "application/vnd.docker.distribution.manifest.v2+json",
+    "application/vnd.oci.image.manifest.v1+json",
+];
+
+/// The docker hub host in `docker login` credentials.
+const HUB_AUTH_KEY: &str = "https://index.docker.io/v1/";
+
+/// Whether the registry has a different digest for the tag than the one the container runs,
+/// unknown digests never count as an update.
+pub fn update_available(local: Option<&str>, remote: Option<&str>) -> bool {
+    matches!((local, remote), (Some(local), Some(remote)) if local != remote)
+}
+
+fn cache_key(image: &ImageRef) -> String {
+    format!("{}:{}", image.key(), image.reference())
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+struct CachedDigest {
+    /// `None` if the lookup failed.
+    digest: Option<String>,
+    /// Seconds since the unix epoch.
+    checked: u64,
+}
+
+/// The digests registries reported for image tags, keyed by image and tag and kept for a day.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct DigestCache {
+    entries: HashMap<String, CachedDigest>,
+}
+
+impl DigestCache {
+    pub fn digest(&self, image: &ImageRef) -> Option<&str> {
+        self.entries.get(&cache_key(image))?.digest.as_deref()
+    }
+
+    /// The tags among `images` not looked up in the last day, at most [`MAX_LOOKUPS`]. Images
+    /// pinned to a digest can't be updated and are left out.
+    pub fn stale<'a>(
+        &self,
+        images: impl IntoIterator<Item = &'a ImageRef>,
+        now: u64,
+    ) -> Vec<ImageRef> {
+        images
+            .into_iter()
+            .filter(|image| image.digest.is_none())
+            .unique_by(|image| cache_key(image))
+            .filter(|image| {
+                self.entries
+                    .get(&cache_key(image))
+                    .is_none_or(|cached| now.saturating_sub(cached.checked) >= DAY)
+            })
+            .take(MAX_LOOKUPS)
+            .cloned()
+            .collect()
+    }
+
+    pub fn insert(&mut self, image: &ImageRef, digest: Option<String>, now: u64) {
+        self.entries.insert(
+            cache_key(image),
+            CachedDigest {
+                digest,
+                checked: now,
+            },
+        );
+    }
+}
+
+/// Where the manifest of `image` is fetched from.
+pub fn manifest_url(image: &ImageRef) -> String {
+    let host = image.registry.as_deref().unwrap_or("registry-1.docker.io");
+    format!(
+        "https://{host}/v2/{}/manifests/{}",
+        image.repository,
+        image.reference()
+    )
+}
+
+/// A `WWW-Authenticate` challenge, such as `Bearer realm="https://auth.docker.io/token",
+/// service="registry.docker.io",scope="repository:library/postgres:pull"`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Challenge {
+    /// `bearer` or `basic`, lowercase.
+    pub scheme: String,
+    pub params: HashMap<String, String>,
+}
+
+pub fn parse_challenge(header: &str) -> Option<Challenge> {
+    let (scheme, rest) = header.trim().split_once(' ').unwrap_or((header.trim(), ""));
+    let mut params = HashMap::new();
+    let mut chars = rest.chars().peekable();
+    loop {
+        let name = chars
+            .by_ref()
+            .skip_while(|c| *c == ',' || c.is_whitespace())
+            .take_while(|c| *c != '=')
+            .collect::<String>();
+        if name.is_empty() {
+            break;
+        }
+
+        let value = if chars.peek() == Some(&'"') {
+            chars.next();
+            let value = chars.by_ref().take_while(|c| *c != '"').collect();
+            chars.next_if_eq(&',');
+            value
+        } else {
+            chars.by_ref().take_while(|c| *c != ',').collect()
+        };
+        params.insert(name.trim().to_lowercase(), value);
+    }
+
+    (!scheme.is_empty()).then(|| Challenge {
+        scheme: scheme.to_lowercase(),
+        params,
+    })
+}
+
+#[derive(Deserialize)]
+struct DockerConfig {
+    #[serde(default)]
+    auths: HashMap<String, DockerAuth>,
+}
+
+#[derive(Deserialize)]
+struct DockerAuth {
+    auth: Option<String>,
+}
+
+/// The base64 `user:password` that `docker login` stored in `config` for `registry`, `None` for
+/// docker hub. Credentials kept by a credential helper are not found.
+pub fn registry_auth(config: &str, registry: Option<&str>) -> Option<String> {
+    // JSON is valid YAML, which saves a JSON dependency for this one file
+    let config = serde_yaml::from_str::<DockerConfig>(config).ok()?;
+    let key = registry.unwrap_or(HUB_AUTH_KEY);
+    config
+        .auths
+        .iter()
+        .find(|(host, _)| {
+            host.trim_start_matches("https://")
+                .trim_start_matches("http://")
+                .trim_end_matches('/')
+                == key.trim_start_matches("https://").trim_end_matches('/')
+        })
+        .and_then(|(_, auth)| auth.auth.clone())
+        .filter(|auth| !auth.is_empty())
+}
+
+/// The docker CLI config file, from `DOCKER_CONFIG` or in the home directory.
+pub fn docker_config_file() -> Option<PathBuf> {
+    match std::env::var_os("DOCKER_CONFIG") {
+        Some(dir) => Some(PathBuf::from(dir).join("config.json")),
+        None => Some(
+            directories::BaseDirs::new()?
+                .home_dir()
+                .join(".docker")
+                .join("config.json"),
+        ),
+    }
+}
+
+/// The digest in the headers of a manifest response.
+pub fn digest_header(headers: &HeaderMap) -> anyhow::Result<String> {
+    headers
+        .get("docker-content-digest")
+        .and_then(|digest| digest.to_str().ok())
+        .map(String::from)
+        .ok_or_else(|| anyhow!("The registry did not report a digest"))
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    token: Option<String>,
+    access_token: Option<String>,
+}
+
+async fn fetch_token(
+    client: &Client,
+    challenge: &Challenge,
+    auth: Option<&str>,
+) -> anyhow::Result<String> {
+    let realm = challenge
+        .params
+        .get("realm")
+        .ok_or_else(|| anyhow!("The registry did not say where to authenticate"))?;
+    let query = ["service", "scope"]
+        .into_iter()
+        .filter_map(|name| Some((name, challenge.params.get(name)?)))
+        .collect::<Vec<_>>();
+
+    let mut request = client.get(realm).query(&query).timeout(LOOKUP_TIMEOUT);
+    if let Some(auth) = auth {
+        request = request.header(AUTHORIZATION, format!("Basic {auth}"));
+    }
+    let body = request.send().await?.error_for_status()?.text().await?;
+    let response = serde_yaml::from_str::<TokenResponse>(&body)
+        .context("The registry returned an invalid token")?;
+
+    response
+        .token
+        .or(response.access_token)
+        .ok_or_else(|| anyhow!("The registry returned no token"))
+}
+
+/// The digest the registry currently has for the tag of `image`, authenticating with the
+/// `docker login` credentials in `auth` if the registry asks for them.
+pub async fn remote_digest(
+    client: &Client,
+    image: &ImageRef,
+    auth: Option<&str>,
+) -> anyhow::Result<String> {
+    let url = manifest_url(image);
+    let head = |authorization: Option<String>| -> RequestBuilder {
+        let request = client
+            .head(&url)
+            .header(ACCEPT, MANIFEST_TYPES.join(", "))
+            .timeout(LOOKUP_TIMEOUT);
+        match authorization {
+            Some(authorization) => request.header(AUTHORIZATION, authorization),
+            None => request,
+        }
+    };
+
+    let mut response = head(None).send().await?;
+    if response.status() == StatusCode::UNAUTHORIZED {
+        let challenge = response
+            .headers()
+            .get(WWW_AUTHENTICATE)
+            .and_then(|header| header.to_str().ok())
+            .and_then(parse_challenge)
+            .ok_or_else(|| anyhow!("The registry asked for unsupported authentication"))?;
+        let authorization = match challenge.scheme.as_str() {
+            "bearer" => format!("Bearer {}", fetch_token(client, &challenge, auth).await?),
+            "basic" => format!(
+                "Basic {}",
+                auth.ok_or_else(|| anyhow!("The registry needs a docker login"))?
+            ),
+            scheme => return Err(anyhow!("The registry asked for {scheme} authentication")),
+        };
+        response = head(Some(authorization)).send().await?;
+    }
+
+    digest_header(response.error_for_status()?.headers())
+}
+
+/// Looks up the digests of `images` one after another, to go easy on the registries. Failed
+/// lookups are `None`.
+pub async fn remote_digests(
+    client: Client,
+    images: Vec<ImageRef>,
+) -> Vec<(ImageRef, Option<String>)> {
+    let config = docker_config_file().and_then(|file| std::fs::read_to_string(file).ok());
+
+    let mut digests = vec![];
+    for image in images {
+        let auth = config
+            .as_deref()
+            .and_then(|config| registry_auth(config, image.registry.as_deref()));
+        let digest = match remote_digest(&client, &image, auth.as_deref()).await {
+            Ok(digest) => Some(digest),
+            Err(ex) => {
+                eprintln!("Could not check {image} for updates: {ex:#}");
+                None
+            }
+        };
+        digests.push((image, digest));
+    }
+
+    digests
+}
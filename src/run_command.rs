@@ -0,0 +1,181 @@
+use itertools::Itertools;
+
+use crate::{
+    data::{is_secret, DatabaseConfig},
+    definition::display_name,
+    docker::{DbContainer, ImageDefaults, Restart},
+};
+
+/// How a container is written out as a `docker run` command.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RunCommandOptions {
+    /// Keep the `db-mgr__` prefix of the container name.
+    pub keep_prefix: bool,
+    /// Write out the values of secret variables instead of `$KEY` placeholders.
+    pub include_secrets: bool,
+}
+
+/// Characters a shell word can hold without quotes.
+fn is_plain(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "_-./:=@%+,".contains(c)
+}
+
+/// `value` as a single POSIX shell word, in single quotes unless it only has plain characters.
+/// A single quote can't be escaped inside them, so it closes the quotes, is escaped and opens
+/// them again.
+pub fn shell_quote(value: &str) -> String {
+    if !value.is_empty() && value.chars().all(is_plain) {
+        return value.to_string();
+    }
+
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// `-e KEY=$KEY`, expanded from the environment of whoever runs the command. Keys that aren't
+/// shell variable names can't be expanded and are passed on as they are with `-e KEY`.
+fn placeholder(key: &str) -> String {
+    let is_name = key.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    match is_name {
+        true => format!("-e \"{key}=${key}\""),
+        false => format!("-e {}", shell_quote(key)),
+    }
+}
+
+/// The variables of `container` to pass with `-e`, sorted by key. With a config entry these are
+/// the ones it declares or the user added, otherwise the ones that differ from the image's.
+fn variables<'a>(
+    container: &'a DbContainer,
+    database: Option<&DatabaseConfig>,
+    image: &ImageDefaults,
+) -> Vec<(&'a String, Option<&'a str>)> {
+    let extra = container.extra_variables();
+    container
+        .variables
+        .iter()
+        .filter(|(key, value)| match database {
+            Some(database) => database.declares(key) || extra.contains(key),
+            None => !value
+                .as_text()
+                .is_some_and(|value| image.env.contains(&format!("{key}={value}"))),
+        })
+        .map(|(key, value)| (key, value.as_text()))
+        .sorted()
+        .collect()
+}
+
+/// A `docker run` command creating a container like `container`, one option per line. Secret
+/// values, and values too large to keep, are replaced with placeholders unless included.
+pub fn docker_run_command(
+    container: &DbContainer,
+    database: Option<&DatabaseConfig>,
+    image: &ImageDefaults,
+    options: RunCommandOptions,
+) -> String {
+    let name = container.name.trim_start_matches('/');
+    let mut args = vec!["docker run -d".to_string()];
+
+    let shown_name = match options.keep_prefix {
+        true => name,
+        false => name.strip_prefix("db-mgr__").unwrap_or(name),
+    };
+    args.push(format!("--name {}", shell_quote(shown_name)));
+    if let Some(hostname) = &container.hostname {
+        args.push(format!("--hostname {}", shell_quote(hostname)));
+    }
+    if let Some(user) = &container.user {
+        args.push(format!("--user {}", shell_quote(user)));
+    }
+
+    let restart = match container.limits.restart {
+        Restart::Never => None,
+        Restart::Always => Some("always"),
+        Restart::UnlessStopped => Some("unless-stopped"),
+        Restart::OnFailure => Some("on-failure"),
+    };
+    if let Some(restart) = restart {
+        args.push(format!("--restart {restart}"));
+    }
+    if let Some(memory) = container.limits.memory {
+        args.push(format!("--memory {memory}"));
+    }
+    if let Some(nano_cpus) = container.limits.nano_cpus {
+        args.push(format!("--cpus {}", nano_cpus as f64 / 1e9));
+    }
+    if let Some(shm_size) = container.shm_size_mb {
+        args.push(format!("--shm-size {shm_size}m"));
+    }
+
+    // Stopped containers have no bound ports, their assigned database port is used instead
+    match container.port_assignment() {
+        Some(assignment) if container.ports.is_empty() => {
+            let host = assignment.host.unwrap_or(assignment.container);
+            args.push(format!("-p 127.0.0.1:{host}:{}", assignment.container));
+        }
+        _ => {
+            for port in container.ports.iter().dedup_by(|a, b| {
+                a.port == b.port && a.protocol == b.protocol && a.host_port == b.host_port
+            }) {
+                let host = match port.host_ip.as_str() {
+                    "" | "0.0.0.0" | "::" => port.host_port.to_string(),
+                    ip if ip.contains(':') => format!("[{ip}]:{}", port.host_port),
+                    ip => format!("{ip}:{}", port.host_port),
+                };
+                let protocol = match port.protocol.as_str() {
+                    "tcp" => String::new(),
+                    protocol => format!("/{protocol}"),
+                };
+                args.push(format!("-p {host}:{}{protocol}", port.port));
+            }
+        }
+    }
+
+    for (key, value) in variables(container, database, image) {
+        let secret = is_secret(&display_name(database, key), key);
+        match value {
+            Some(value) if options.include_secrets || !secret => {
+                args.push(format!("-e {}", shell_quote(&format!("{key}={value}"))))
+            }
+            _ => args.push(placeholder(key)),
+        }
+    }
+
+    for (source, path) in container.volumes.iter().sorted() {
+        let mode = match container.read_only_volumes.contains(source) {
+            true => ":ro",
+            false => "",
+        };
+        args.push(format!(
+            "-v {}",
+            shell_quote(&format!("{source}:{path}{mode}"))
+        ));
+    }
+    for path in &container.tmpfs {
+        let mount = match container.tmpfs_size {
+            Some(size) => format!("{path}:size={size}"),
+            None => path.clone(),
+        };
+        args.push(format!("--tmpfs {}", shell_quote(&mount)));
+    }
+
+    for (key, value) in container.labels.iter().sorted() {
+        if image.labels.get(key) == Some(value) {
+            continue;
+        }
+        args.push(format!(
+            "--label {}",
+            shell_quote(&format!("{key}={value}"))
+        ));
+    }
+
+    let mut run = shell_quote(&container.image);
+    if container.command != image.command {
+        for arg in &container.command {
+            run.push(' ');
+            run.push_str(&shell_quote(arg));
+        }
+    }
+    args.push(run);
+
+    args.join(" \\\n  ")
+}
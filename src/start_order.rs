@@ -0,0 +1,242 @@
+use std::time::Duration;
+
+use bollard::{
+    service::{ContainerStateStatusEnum, HealthStatusEnum},
+    Docker,
+};
+use futures::{
+    channel::mpsc::{channel, Receiver, Sender},
+    future::BoxFuture,
+    FutureExt, SinkExt,
+};
+
+use crate::{
+    dependencies::{start_layers, Graph},
+    docker::{get_container, start_container},
+};
+
+/// How long a container may take to become healthy before its dependents are started anyway.
+pub const READY_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// How often a started container is checked while it is waited for.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How far a started container got towards accepting its dependents.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Readiness {
+    pub running: bool,
+    /// The result of its health check, `None` for images without one.
+    pub health: Option<HealthStatusEnum>,
+    /// Whether its published database port accepts connections on this machine, checked when it
+    /// has no health check. `None` without a published port.
+    pub port_open: Option<bool>,
+}
+
+/// What the ordered start needs from docker, so it can be run against a scripted one.
+pub trait StartRuntime: Sync {
+    fn start<'a>(&'a self, name: &'a str) -> BoxFuture<'a, anyhow::Result<()>>;
+    fn readiness<'a>(&'a self, name: &'a str) -> BoxFuture<'a, anyhow::Result<Readiness>>;
+    fn sleep(&self, duration: Duration) -> BoxFuture<'_, ()>;
+}
+
+/// The progress of an ordered start, containers are named without the leading `/`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StartEvent {
+    /// The order can't be trusted for this reason, all of them are started at once.
+    Unordered(String),
+    Starting(String),
+    /// Started, nothing waits for it.
+    Started(String),
+    /// Started, its dependents wait until it is healthy.
+    Waiting(String),
+    /// Healthy or accepting connections after this long.
+    Ready(String, Duration),
+    /// It didn't become healthy, for this reason. Its dependents are started anyway.
+    NotReady(String, String),
+    Failed(String, String),
+    Done,
+}
+
+/// Waits for `name` to become healthy, or to accept connections on its port without a health
+/// check, for up to `timeout`. Returns [`StartEvent::Done`] early if the receiver of `events` is
+/// gone.
+async fn wait_ready(
+    runtime: &impl StartRuntime,
+    name: &str,
+    timeout: Duration,
+    events: &Sender<StartEvent>,
+) -> StartEvent {
+    let mut waited = Duration::ZERO;
+    loop {
+        let ready = match runtime.readiness(name).await {
+            Err(ex) => return StartEvent::Failed(name.to_string(), format!("{ex:#}")),
+            Ok(readiness) if !readiness.running => {
+                return StartEvent::Failed(
+                    name.to_string(),
+                    "It exited while it was waited for".to_string(),
+                )
+            }
+            Ok(Readiness {
+                health: Some(HealthStatusEnum::UNHEALTHY),
+                ..
+            }) => {
+                return StartEvent::NotReady(
+                    name.to_string(),
+                    "Its health check failed".to_string(),
+                )
+            }
+            Ok(Readiness {
+                health: Some(health),
+                ..
+            }) => health == HealthStatusEnum::HEALTHY,
+            Ok(readiness) => readiness.port_open.unwrap_or(true),
+        };
+        if ready {
+            return StartEvent::Ready(name.to_string(), waited);
+        }
+        if waited >= timeout {
+            return StartEvent::NotReady(
+                name.to_string(),
+                format!("It wasn't healthy after {} seconds", timeout.as_secs()),
+            );
+        }
+        if events.is_closed() {
+            return StartEvent::Done;
+        }
+
+        runtime.sleep(POLL_INTERVAL).await;
+        waited += POLL_INTERVAL;
+    }
+}
+
+/// Starts `names` in the order of their dependencies in `graph`: each layer is started at
+/// once, and the next one after all of them are healthy or waiting for them timed out. Cycles
+/// and missing dependencies start all of them at once instead. Stops once the receiver of
+/// `events` is dropped, which is how it is cancelled.
+pub async fn start_in_order(
+    runtime: &impl StartRuntime,
+    graph: &Graph,
+    names: Vec<String>,
+    timeout: Duration,
+    events: &mut Sender<StartEvent>,
+) {
+    let layers = match start_layers(graph, &names) {
+        Ok(layers) => layers,
+        Err(warning) => {
+            if events.send(StartEvent::Unordered(warning)).await.is_err() {
+                return;
+            }
+            vec![names]
+        }
+    };
+
+    for (index, layer) in layers.iter().enumerate() {
+        let last = index + 1 == layers.len();
+        let mut started = vec![];
+        for name in layer {
+            if events
+                .send(StartEvent::Starting(name.clone()))
+                .await
+                .is_err()
+            {
+                return;
+            }
+            let event = match runtime.start(name).await {
+                Err(ex) => StartEvent::Failed(name.clone(), format!("{ex:#}")),
+                Ok(_) if last => StartEvent::Started(name.clone()),
+                Ok(_) => {
+                    started.push(name);
+                    StartEvent::Waiting(name.clone())
+                }
+            };
+            if events.send(event).await.is_err() {
+                return;
+            }
+        }
+
+        for name in started {
+            let event = wait_ready(runtime, name, timeout, events).await;
+            if event == StartEvent::Done || events.send(event).await.is_err() {
+                return;
+            }
+        }
+    }
+
+    let _ = events.send(StartEvent::Done).await;
+}
+
+/// The containers of the daemon, by name.
+pub struct DockerRuntime {
+    pub docker: &'static Docker,
+}
+
+impl StartRuntime for DockerRuntime {
+    fn start<'a>(&'a self, name: &'a str) -> BoxFuture<'a, anyhow::Result<()>> {
+        start_container(name.to_string(), self.docker).boxed()
+    }
+
+    fn readiness<'a>(&'a self, name: &'a str) -> BoxFuture<'a, anyhow::Result<Readiness>> {
+        async move {
+            let container = get_container(name.to_string(), self.docker).await?;
+            let port_open = match container.health {
+                Some(_) => None,
+                None => {
+                    // The database port if it was assigned one, else the first published port
+                    let port = container
+                        .port_assignment()
+                        .and_then(|assignment| {
+                            container
+                                .ports
+                                .iter()
+                                .find(|port| port.port == assignment.container)
+                        })
+                        .or(container.ports.first());
+                    match port {
+                        Some(port) => Some(
+                            tokio::time::timeout(
+                                POLL_INTERVAL,
+                                tokio::net::TcpStream::connect(("127.0.0.1", port.host_port)),
+                            )
+                            .await
+                            .is_ok_and(|connected| connected.is_ok()),
+                        ),
+                        None => None,
+                    }
+                }
+            };
+
+            Ok(Readiness {
+                running: container.state == ContainerStateStatusEnum::RUNNING,
+                health: container.health,
+                port_open,
+            })
+        }
+        .boxed()
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxFuture<'_, ()> {
+        tokio::time::sleep(duration).boxed()
+    }
+}
+
+/// Starts `names` in the order of their dependencies, see [`start_in_order`].
+pub fn ordered_start(
+    docker: &'static Docker,
+    graph: Graph,
+    names: Vec<String>,
+) -> Receiver<StartEvent> {
+    let (mut tx, rx) = channel(5);
+
+    tokio::spawn(async move {
+        start_in_order(
+            &DockerRuntime { docker },
+            &graph,
+            names,
+            READY_TIMEOUT,
+            &mut tx,
+        )
+        .await;
+    });
+
+    rx
+}
@@ -0,0 +1,102 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::docker::{DbContainer, PublishedPort};
+
+/// The version of [`StateDocument`]. Fields may be added without bumping it, renaming or removing
+/// one bumps it.
+pub const STATE_VERSION: u32 = 1;
+
+/// The state file is written at most this often, refreshes in between are written together.
+pub const STATE_FILE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The managed containers as written to the state file and printed by `db-mgr list --json`, for
+/// shell prompts and scripts that shouldn't have to ask docker.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct StateDocument {
+    pub version: u32,
+    /// When the containers were loaded, in unix seconds.
+    pub updated_at: i64,
+    /// Sorted by name.
+    pub containers: Vec<ContainerState>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ContainerState {
+    /// The name without the leading `/`, such as `db-mgr__orders`.
+    pub name: String,
+    /// Docker's state, such as `running` or `exited`.
+    pub state: String,
+    pub image: String,
+    /// The ports published on the host, empty while the container is stopped.
+    pub ports: Vec<PortState>,
+    /// `null` for containers without a project.
+    pub project: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct PortState {
+    /// The port inside the container.
+    pub container_port: u16,
+    /// `tcp`, `udp` or `sctp`.
+    pub protocol: String,
+    /// The host address the port is bound to, such as `0.0.0.0`.
+    pub host_ip: String,
+    pub host_port: u16,
+}
+
+impl From<&PublishedPort> for PortState {
+    fn from(port: &PublishedPort) -> Self {
+        Self {
+            container_port: port.port,
+            protocol: port.protocol.clone(),
+            host_ip: port.host_ip.clone(),
+            host_port: port.host_port,
+        }
+    }
+}
+
+impl StateDocument {
+    /// The document for `containers` loaded at `updated_at`, with the projects `project` resolves.
+    pub fn new(
+        containers: &[DbContainer],
+        project: impl Fn(&DbContainer) -> Option<String>,
+        updated_at: i64,
+    ) -> Self {
+        let mut containers = containers
+            .iter()
+            .map(|container| ContainerState {
+                name: container.name.trim_start_matches('/').to_string(),
+                state: container.state.to_string(),
+                image: container.image.clone(),
+                ports: container.ports.iter().map(PortState::from).collect(),
+                project: project(container),
+            })
+            .collect::<Vec<_>>();
+        containers.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Self {
+            version: STATE_VERSION,
+            updated_at,
+            containers,
+        }
+    }
+}
+
+/// Writes `document` to `path` through a temporary file renamed over it, so readers never see a
+/// partly written file.
+pub fn write_state_file(path: &Path, document: &StateDocument) -> anyhow::Result<()> {
+    let json = serde_json::to_string_pretty(document)?;
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(".tmp");
+    let temp = PathBuf::from(temp);
+
+    fs::write(&temp, json)?;
+    fs::rename(&temp, path)?;
+    Ok(())
+}
@@ -0,0 +1,55 @@
+use std::collections::HashMap;
+
+/// Replaces every `{NAME}` placeholder in `template` with the matching entry from `variables`.
+/// Placeholders that have no matching variable are left untouched, `{{` and `}}` produce literal
+/// braces.
+pub fn render(template: &str, variables: &HashMap<String, String>) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut chars = template.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                out.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                out.push('}');
+            }
+            '{' => {
+                let mut name = String::new();
+                let mut closed = false;
+                for c in chars.by_ref() {
+                    if c == '}' {
+                        closed = true;
+                        break;
+                    }
+                    name.push(c);
+                }
+
+                match (closed, variables.get(&name)) {
+                    (true, Some(value)) => out.push_str(value),
+                    (true, None) => {
+                        out.push('{');
+                        out.push_str(&name);
+                        out.push('}');
+                    }
+                    (false, _) => {
+                        out.push('{');
+                        out.push_str(&name);
+                    }
+                }
+            }
+            c => out.push(c),
+        }
+    }
+
+    out
+}
+
+/// Renders each argument of `args` on its own, an argument is never split or joined so values
+/// containing spaces or quotes are passed through to docker as-is.
+pub fn render_args(args: &[String], variables: &HashMap<String, String>) -> Vec<String> {
+    args.iter().map(|arg| render(arg, variables)).collect()
+}
@@ -0,0 +1,169 @@
+use std::{
+    fs::File,
+    io::BufReader,
+    path::{Path, PathBuf},
+};
+
+use anyhow::anyhow;
+use bollard::{Docker, API_DEFAULT_VERSION};
+use rustls_pemfile::Item;
+use serde::{Deserialize, Serialize};
+
+use crate::platform;
+
+/// Seconds bollard waits for a response, the same as [`platform::connect`].
+const CONNECT_TIMEOUT_SECS: u64 = 120;
+
+/// The client certificate for a daemon that requires mutual TLS, as picked in the settings. The
+/// files are read where they are, only their paths are kept.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct TlsClient {
+    /// Connect with the certificate on start instead of through the `docker_host` setting.
+    #[serde(default)]
+    pub enabled: bool,
+    /// The daemon as `host:port`, such as `docker.example.com:2376`.
+    #[serde(default)]
+    pub host: String,
+    /// The CA certificate the daemon's certificate is signed with.
+    #[serde(default)]
+    pub ca: Option<PathBuf>,
+    #[serde(default)]
+    pub cert: Option<PathBuf>,
+    #[serde(default)]
+    pub key: Option<PathBuf>,
+}
+
+/// What a PEM file is expected to hold.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Expected {
+    Certificate,
+    PrivateKey,
+}
+
+/// Checks that `path` exists and holds what `what` needs, returning the problem in a sentence
+/// such as `The client key /certs/key.pem doesn't exist`.
+fn check_pem(what: &str, path: &Path, expected: Expected) -> Result<(), String> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(ex) if ex.kind() == std::io::ErrorKind::NotFound => {
+            return Err(format!("The {what} {} doesn't exist", path.display()))
+        }
+        Err(ex) => {
+            return Err(format!(
+                "Could not read the {what} {}: {ex}",
+                path.display()
+            ))
+        }
+    };
+    let items = match rustls_pemfile::read_all(&mut BufReader::new(file)) {
+        Ok(items) if !items.is_empty() => items,
+        Ok(_) => return Err(format!("The {what} {} isn't a PEM file", path.display())),
+        Err(ex) => {
+            return Err(format!(
+                "The {what} {} isn't a valid PEM file: {ex}",
+                path.display()
+            ))
+        }
+    };
+
+    let certificates = items
+        .iter()
+        .filter(|item| matches!(item, Item::X509Certificate(_)))
+        .count();
+    // Docker's client only signs with RSA keys
+    let keys = items
+        .iter()
+        .filter(|item| matches!(item, Item::RSAKey(_) | Item::PKCS8Key(_)))
+        .count();
+    match expected {
+        Expected::Certificate if certificates == 0 => Err(format!(
+            "The {what} {} holds no certificate",
+            path.display()
+        )),
+        Expected::PrivateKey if keys == 0 && items.iter().any(|i| matches!(i, Item::ECKey(_))) => {
+            Err(format!(
+                "The {what} {} is an EC key, only RSA keys are supported",
+                path.display()
+            ))
+        }
+        Expected::PrivateKey if keys == 0 => Err(format!(
+            "The {what} {} holds no private key",
+            path.display()
+        )),
+        Expected::PrivateKey if keys > 1 => Err(format!(
+            "The {what} {} holds {keys} private keys, it must hold one",
+            path.display()
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// The file `path` after [`check_pem`], asking to choose one if there is none.
+fn chosen<'a>(
+    what: &str,
+    path: &'a Option<PathBuf>,
+    expected: Expected,
+) -> Result<&'a Path, String> {
+    match path {
+        Some(path) => check_pem(what, path, expected).map(|_| path.as_path()),
+        None => Err(format!("Choose the {what}")),
+    }
+}
+
+impl TlsClient {
+    /// The daemon as given to docker, without a `tcp://` or `https://` in front.
+    pub fn address(&self) -> &str {
+        let host = self.host.trim();
+        host.strip_prefix("tcp://")
+            .or_else(|| host.strip_prefix("https://"))
+            .unwrap_or(host)
+    }
+
+    /// Checks that the host has a port and that the files exist and parse as PEM, so a
+    /// connection isn't attempted with files that can't work. Returns the first problem.
+    pub fn validate(&self) -> Result<(), String> {
+        self.files().map(|_| ())
+    }
+
+    /// The CA certificate, client certificate and client key, see [`TlsClient::validate`].
+    fn files(&self) -> Result<(&Path, &Path, &Path), String> {
+        let port = self
+            .address()
+            .rsplit_once(':')
+            .filter(|(host, _)| !host.is_empty())
+            .and_then(|(_, port)| port.parse::<u16>().ok());
+        if port.is_none() {
+            return Err("Enter the daemon as host:port, such as docker.example.com:2376".into());
+        }
+
+        Ok((
+            chosen("CA certificate", &self.ca, Expected::Certificate)?,
+            chosen("client certificate", &self.cert, Expected::Certificate)?,
+            chosen("client key", &self.key, Expected::PrivateKey)?,
+        ))
+    }
+
+    /// Connects to the daemon with the certificate, after [`TlsClient::validate`]. Nothing is
+    /// sent until the first request, which is when TLS errors show up.
+    pub fn connect(&self) -> anyhow::Result<Docker> {
+        let (ca, cert, key) = self.files().map_err(|problem| anyhow!(problem))?;
+
+        Ok(Docker::connect_with_ssl(
+            self.address(),
+            key,
+            cert,
+            ca,
+            CONNECT_TIMEOUT_SECS,
+            API_DEFAULT_VERSION,
+        )?)
+    }
+}
+
+/// Connects with the client certificate when it is enabled, otherwise to the `docker_host`
+/// setting as [`platform::connect`] does.
+pub fn connect(tls: &TlsClient, docker_host: Option<&str>) -> anyhow::Result<Docker> {
+    match tls.enabled {
+        true => tls.connect(),
+        false => Ok(platform::connect(docker_host)?),
+    }
+}
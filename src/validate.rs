@@ -0,0 +1,388 @@
+use anyhow::anyhow;
+use bollard::{image::CreateImageOptions, Docker};
+use futures::{future::BoxFuture, stream, FutureExt, StreamExt};
+use itertools::Itertools;
+use std::{fmt::Display, net::IpAddr};
+
+use crate::{
+    daemon::{daemon_status, query_timeout, with_timeout},
+    data::DatabaseConfig,
+    docker::{split_env_entry, Dockerfile},
+    image_ref::ImageRef,
+    versions::MAJOR,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Info => write!(f, "Info"),
+            Severity::Warning => write!(f, "Warning"),
+            Severity::Error => write!(f, "Error"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Finding {
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl Finding {
+    fn new(severity: Severity, message: impl Into<String>) -> Self {
+        Self {
+            severity,
+            message: message.into(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EntryReport {
+    pub name: String,
+    pub findings: Vec<Finding>,
+}
+
+impl EntryReport {
+    pub fn severity(&self) -> Option<Severity> {
+        self.findings.iter().map(|finding| finding.severity).max()
+    }
+}
+
+/// Names closer than this are reported as probable typos.
+const NEAR_MISS_DISTANCE: usize = 2;
+
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, a_char) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, b_char) in b.iter().enumerate() {
+            let cost = if a_char == *b_char { 0 } else { 1 };
+            current[j + 1] = (previous[j] + cost)
+                .min(previous[j + 1] + 1)
+                .min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[b.len()]
+}
+
+/// Compares the declared variable names against the `KEY=value` defaults baked into the image.
+pub fn check_variables<'a>(
+    declared: impl IntoIterator<Item = &'a str>,
+    image_env: &[String],
+) -> Vec<Finding> {
+    let image_keys = image_env
+        .iter()
+        .filter_map(|entry| split_env_entry(entry).map(|(key, _)| key))
+        .collect::<Vec<_>>();
+
+    declared
+        .into_iter()
+        .filter(|key| !image_keys.contains(key))
+        .map(|key| {
+            let closest = image_keys
+                .iter()
+                .map(|image_key| (levenshtein(key, image_key), image_key))
+                .min_by_key(|(distance, _)| *distance);
+
+            match closest {
+                Some((distance, image_key)) if distance <= NEAR_MISS_DISTANCE => Finding::new(
+                    Severity::Warning,
+                    format!("{key} is not set by the image, did you mean {image_key}?"),
+                ),
+                _ => Finding::new(
+                    Severity::Info,
+                    format!("{key} has no default in the image, check the image documentation"),
+                ),
+            }
+        })
+        .collect()
+}
+
+/// Volume targets are paths inside a linux container so they must start with `/`.
+pub fn check_volumes<'a>(volumes: impl IntoIterator<Item = (&'a str, &'a str)>) -> Vec<Finding> {
+    volumes
+        .into_iter()
+        .filter(|(_, target)| !target.starts_with('/'))
+        .map(|(name, target)| {
+            Finding::new(
+                Severity::Error,
+                format!("Volume {name} targets {target:?} which is not an absolute path"),
+            )
+        })
+        .collect()
+}
+
+/// Whether `cpuset` is a docker cpuset such as `0-3` or `0,2,4-5`.
+pub fn is_valid_cpuset(cpuset: &str) -> bool {
+    let is_cpu = |cpu: &str| !cpu.is_empty() && cpu.chars().all(|c| c.is_ascii_digit());
+
+    cpuset.split(',').all(|part| match part.split_once('-') {
+        Some((first, last)) => {
+            is_cpu(first) && is_cpu(last) && first.parse::<u32>().ok() <= last.parse::<u32>().ok()
+        }
+        None => is_cpu(part),
+    })
+}
+
+/// Whether `hostname` is a valid hostname label, at most 63 letters, digits and inner dashes.
+pub fn is_valid_hostname(hostname: &str) -> bool {
+    !hostname.is_empty()
+        && hostname.len() <= 63
+        && !hostname.starts_with('-')
+        && !hostname.ends_with('-')
+        && hostname
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-')
+}
+
+/// Whether `user` is a docker user, a name or uid optionally followed by `:` and a group name or
+/// gid.
+pub fn is_valid_user(user: &str) -> bool {
+    let is_name = |name: &str| {
+        !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+    };
+
+    match user.split_once(':') {
+        Some((user, group)) => is_name(user) && is_name(group),
+        None => is_name(user),
+    }
+}
+
+/// Whether `name` is a portable environment variable name, letters, digits and underscores not
+/// starting with a digit.
+pub fn is_valid_variable_name(name: &str) -> bool {
+    name.chars()
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Whether `entry` is an extra `/etc/hosts` entry such as `api:10.0.0.5`. Docker also accepts
+/// `host-gateway` in place of the address.
+pub fn is_valid_extra_host(entry: &str) -> bool {
+    let Some((host, address)) = entry.split_once(':') else {
+        return false;
+    };
+
+    !host.is_empty()
+        && host.split('.').all(is_valid_hostname)
+        && (address == "host-gateway" || address.parse::<IpAddr>().is_ok())
+}
+
+/// Checks the recommended host config of an entry.
+pub fn check_host_config(database: &DatabaseConfig) -> Vec<Finding> {
+    let mut findings = vec![];
+    if let Some(cpuset) = database.cpuset_cpus.as_ref() {
+        if !is_valid_cpuset(cpuset) {
+            findings.push(Finding::new(
+                Severity::Error,
+                format!("cpuset_cpus {cpuset:?} is not a list of CPUs such as 0-3 or 0,2"),
+            ));
+        }
+    }
+    if database.shm_size_mb == Some(0) {
+        findings.push(Finding::new(
+            Severity::Warning,
+            "shm_size_mb is 0, docker will use its default of 64 MB",
+        ));
+    }
+
+    findings
+}
+
+/// Checks that the version pattern of the entry has a single `{MAJOR}`.
+pub fn check_version_pattern(database: &DatabaseConfig) -> Vec<Finding> {
+    match database.version_pattern.as_ref() {
+        Some(pattern) if pattern.matches(MAJOR).count() != 1 => vec![Finding::new(
+            Severity::Warning,
+            format!("version_pattern {pattern:?} needs exactly one {MAJOR}, major versions are not checked"),
+        )],
+        _ => vec![],
+    }
+}
+
+/// Checks that the end of life dates of the entry are keyed by major versions it can read.
+pub fn check_eol_dates(database: &DatabaseConfig) -> Vec<Finding> {
+    if database.eol_dates.is_empty() {
+        return vec![];
+    }
+    if database.version_pattern.is_none() {
+        return vec![Finding::new(
+            Severity::Warning,
+            "eol_dates need a version_pattern to read the major version of tags, they are ignored",
+        )];
+    }
+    database
+        .eol_dates
+        .keys()
+        .filter(|major| major.parse::<u64>().is_err())
+        .sorted()
+        .map(|major| {
+            Finding::new(
+                Severity::Warning,
+                format!("eol_dates key {major:?} is not a major version number, it is ignored"),
+            )
+        })
+        .collect()
+}
+
+/// Checks that the entry has an image to pull or a Dockerfile that can be read.
+pub fn check_image_source(database: &DatabaseConfig) -> Vec<Finding> {
+    match database.dockerfile.as_ref() {
+        None if database.image.is_empty() => vec![Finding::new(
+            Severity::Error,
+            "No image or dockerfile is configured",
+        )],
+        Some(Dockerfile::File { path }) if !path.is_file() => vec![Finding::new(
+            Severity::Error,
+            format!("The dockerfile {} does not exist", path.display()),
+        )],
+        Some(_) => vec![Finding::new(
+            Severity::Info,
+            "The image is built from a dockerfile, its variables are checked once it is built",
+        )],
+        None => vec![],
+    }
+}
+
+/// What validating the config needs from docker, so it can be run against made up images.
+pub trait ValidateRuntime: Sync {
+    /// The `KEY=value` defaults of `image`, `None` if the daemon doesn't have it.
+    fn image_env<'a>(
+        &'a self,
+        image: &'a ImageRef,
+    ) -> BoxFuture<'a, anyhow::Result<Option<Vec<String>>>>;
+    fn pull<'a>(&'a self, image: &'a ImageRef) -> BoxFuture<'a, anyhow::Result<()>>;
+}
+
+/// The env of `image`, pulling it first if the daemon doesn't have it.
+async fn image_env(
+    runtime: &impl ValidateRuntime,
+    image: &ImageRef,
+) -> anyhow::Result<Vec<String>> {
+    if let Some(env) = runtime.image_env(image).await? {
+        return Ok(env);
+    }
+
+    runtime.pull(image).await?;
+    runtime
+        .image_env(image)
+        .await?
+        .ok_or_else(|| anyhow!("{image} was pulled but the daemon doesn't have it"))
+}
+
+/// The images of the daemon.
+pub struct DockerImages<'a> {
+    pub docker: &'a Docker,
+}
+
+impl ValidateRuntime for DockerImages<'_> {
+    fn image_env<'a>(
+        &'a self,
+        image: &'a ImageRef,
+    ) -> BoxFuture<'a, anyhow::Result<Option<Vec<String>>>> {
+        async move {
+            match with_timeout(
+                "inspect image",
+                query_timeout(),
+                self.docker.inspect_image(&image.to_string()),
+            )
+            .await
+            {
+                Ok(inspect) => Ok(Some(
+                    inspect
+                        .config
+                        .and_then(|config| config.env)
+                        .unwrap_or_default(),
+                )),
+                Err(ex) if daemon_status(&ex) == Some(404) => Ok(None),
+                Err(ex) => Err(ex),
+            }
+        }
+        .boxed()
+    }
+
+    fn pull<'a>(&'a self, image: &'a ImageRef) -> BoxFuture<'a, anyhow::Result<()>> {
+        async move {
+            let from_image = image.name();
+            let mut pull = self.docker.create_image(
+                Some(CreateImageOptions {
+                    from_image: from_image.as_str(),
+                    tag: image.reference(),
+                    ..Default::default()
+                }),
+                None,
+                None,
+            );
+            while let Some(result) = pull.next().await {
+                result?;
+            }
+            Ok(())
+        }
+        .boxed()
+    }
+}
+
+pub async fn validate_entry(
+    runtime: &impl ValidateRuntime,
+    database: DatabaseConfig,
+) -> EntryReport {
+    let image = database.image_ref();
+
+    let mut findings = check_volumes(
+        database
+            .volumes
+            .iter()
+            .map(|(name, target)| (name.as_str(), target.as_str())),
+    );
+    findings.extend(check_host_config(&database));
+    findings.extend(check_image_source(&database));
+    findings.extend(check_version_pattern(&database));
+    findings.extend(check_eol_dates(&database));
+
+    if database.dockerfile.is_none() {
+        match image_env(runtime, &image).await {
+            Ok(env) => findings.extend(check_variables(
+                database.variables.values().map(String::as_str),
+                &env,
+            )),
+            Err(ex) => findings.push(Finding::new(
+                Severity::Error,
+                format!("Could not inspect {image}: {ex}"),
+            )),
+        }
+    }
+
+    findings.sort_by_key(|finding| std::cmp::Reverse(finding.severity));
+
+    EntryReport {
+        name: database.name,
+        findings,
+    }
+}
+
+pub async fn validate_config(
+    runtime: &impl ValidateRuntime,
+    databases: Vec<DatabaseConfig>,
+) -> Vec<EntryReport> {
+    stream::iter(databases)
+        .then(|database| validate_entry(runtime, database))
+        .collect()
+        .await
+}
@@ -0,0 +1,122 @@
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+
+/// The placeholder of a version pattern that stands for the major version.
+pub const MAJOR: &str = "{MAJOR}";
+
+/// Reads the major version out of `tag` with `pattern`, such as `{MAJOR}*` for `16.2-alpine`.
+///
+/// `{MAJOR}` matches one or more digits, `*` matches anything, including nothing, and every
+/// other character matches itself. The whole tag has to match, so `latest` has no major version.
+pub fn major_version(pattern: &str, tag: &str) -> Option<u64> {
+    let (prefix, suffix) = pattern.split_once(MAJOR)?;
+    if suffix.contains(MAJOR) {
+        return None;
+    }
+
+    // Every split of the tag whose middle is all digits is a candidate, the first one whose
+    // prefix and suffix match wins
+    let digits = tag
+        .char_indices()
+        .filter(|(_, c)| c.is_ascii_digit())
+        .map(|(index, _)| index);
+    for start in digits {
+        if !matches(prefix, &tag[..start]) {
+            continue;
+        }
+        let rest = &tag[start..];
+        let length = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        for end in (1..=length).rev() {
+            if matches(suffix, &rest[end..]) {
+                return rest[..end].parse().ok();
+            }
+        }
+    }
+
+    None
+}
+
+/// Whether `text` matches `pattern` as a whole, where `*` matches anything.
+fn matches(pattern: &str, text: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == text,
+        Some((literal, rest)) => {
+            let Some(text) = text.strip_prefix(literal) else {
+                return false;
+            };
+            text.char_indices()
+                .map(|(index, _)| index)
+                .chain([text.len()])
+                .any(|index| matches(rest, &text[index..]))
+        }
+    }
+}
+
+/// A change of major version between the tag that wrote a data directory and the tag about to
+/// use it, which most databases can't start on without a dump and restore.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MajorJump {
+    pub from: u64,
+    pub to: u64,
+}
+
+/// The jump from `from` to `to`, `None` when the majors are the same, either tag has none or the
+/// image has no pattern.
+pub fn major_jump(pattern: Option<&str>, from: &str, to: &str) -> Option<MajorJump> {
+    let pattern = pattern?;
+    let from = major_version(pattern, from)?;
+    let to = major_version(pattern, to)?;
+    (from != to).then_some(MajorJump { from, to })
+}
+
+/// How many days before its end of life a major version is warned about.
+pub const EOL_WARNING_DAYS: i64 = 180;
+
+/// Where the major version of a container is in its life, from the end of life dates of its
+/// config entry.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EolStatus {
+    /// Reaches its end of life in this many days, at most [`EOL_WARNING_DAYS`].
+    Soon { date: NaiveDate, days: i64 },
+    /// Reached its end of life on this date.
+    Past(NaiveDate),
+}
+
+impl EolStatus {
+    pub fn is_past(&self) -> bool {
+        matches!(self, EolStatus::Past(_))
+    }
+
+    pub fn describe(&self) -> String {
+        match *self {
+            EolStatus::Soon { days: 1, .. } => "EOL tomorrow".to_string(),
+            EolStatus::Soon { days, .. } if days < 60 => format!("EOL in {days} days"),
+            EolStatus::Soon { days, .. } => format!("EOL in {} months", days / 30),
+            EolStatus::Past(date) => format!(
+                "Past EOL since {}",
+                date.format("%e %b %Y").to_string().trim()
+            ),
+        }
+    }
+}
+
+/// The end of life status of `tag` on `today`, `None` when its major version has no date, the
+/// image has no pattern or the end is more than [`EOL_WARNING_DAYS`] away.
+pub fn eol_status(
+    pattern: Option<&str>,
+    tag: &str,
+    eol_dates: &HashMap<String, NaiveDate>,
+    today: NaiveDate,
+) -> Option<EolStatus> {
+    let major = major_version(pattern?, tag)?;
+    let date = *eol_dates.get(&major.to_string())?;
+    let days = (date - today).num_days();
+    if days <= 0 {
+        Some(EolStatus::Past(date))
+    } else {
+        (days <= EOL_WARNING_DAYS).then_some(EolStatus::Soon { date, days })
+    }
+}
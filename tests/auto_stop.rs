@@ -0,0 +1,110 @@
+//! Checks the deadlines of containers that are stopped after a session timer: when they are
+//! warned about, snoozed and stopped.
+#![allow(dead_code)]
+
+#[path = "../src/app/auto_stop.rs"]
+mod auto_stop;
+
+use std::time::{Duration, Instant};
+
+use auto_stop::{AutoStopAction, AutoStopTimers, StopAfter, SNOOZE, WARNING};
+
+const MINUTE: Duration = Duration::from_secs(60);
+
+#[test]
+fn containers_are_warned_then_stopped() {
+    let start = Instant::now();
+    let mut timers = AutoStopTimers::default();
+    timers.set("orders".into(), 30 * MINUTE, start);
+
+    assert!(timers.tick(start + 10 * MINUTE).is_empty());
+    assert_eq!(
+        timers.remaining("orders", start + 10 * MINUTE),
+        Some(20 * MINUTE)
+    );
+
+    let warned_at = start + 30 * MINUTE - WARNING;
+    assert_eq!(
+        timers.tick(warned_at),
+        vec![("orders".to_string(), AutoStopAction::Warn)]
+    );
+    // The warning is given once
+    assert!(timers.tick(warned_at + MINUTE).is_empty());
+
+    assert_eq!(
+        timers.tick(start + 30 * MINUTE),
+        vec![("orders".to_string(), AutoStopAction::Stop)]
+    );
+    // Stopped containers are forgotten
+    assert!(timers.is_empty());
+    assert!(timers.tick(start + 60 * MINUTE).is_empty());
+    assert_eq!(timers.remaining("orders", start), None);
+}
+
+#[test]
+fn a_late_tick_stops_without_warning_first() {
+    let start = Instant::now();
+    let mut timers = AutoStopTimers::default();
+    timers.set("orders".into(), 30 * MINUTE, start);
+
+    // The laptop slept through the warning
+    assert_eq!(
+        timers.tick(start + 3 * 60 * MINUTE),
+        vec![("orders".to_string(), AutoStopAction::Stop)]
+    );
+}
+
+#[test]
+fn snoozing_pushes_the_deadline_back_and_warns_again() {
+    let start = Instant::now();
+    let mut timers = AutoStopTimers::default();
+    timers.set("orders".into(), 30 * MINUTE, start);
+    let warned_at = start + 26 * MINUTE;
+    assert_eq!(timers.tick(warned_at)[0].1, AutoStopAction::Warn);
+
+    timers.snooze("orders", warned_at);
+    assert_eq!(timers.remaining("orders", warned_at), Some(SNOOZE));
+    assert!(timers.tick(start + 30 * MINUTE).is_empty());
+    assert_eq!(
+        timers.tick(warned_at + SNOOZE - WARNING)[0].1,
+        AutoStopAction::Warn
+    );
+    assert_eq!(timers.tick(warned_at + SNOOZE)[0].1, AutoStopAction::Stop);
+
+    // Snoozing a container without a timer doesn't start one
+    timers.snooze("users", warned_at);
+    assert!(timers.is_empty());
+}
+
+#[test]
+fn cancelled_and_reset_timers() {
+    let start = Instant::now();
+    let mut timers = AutoStopTimers::default();
+    timers.set("orders".into(), 30 * MINUTE, start);
+    timers.set("users".into(), 60 * MINUTE, start);
+
+    // Stopping or starting by hand cancels the timer
+    timers.cancel("orders");
+    assert_eq!(timers.remaining("orders", start), None);
+    let actions = timers.tick(start + 60 * MINUTE);
+    assert_eq!(actions, vec![("users".to_string(), AutoStopAction::Stop)]);
+
+    // Setting it again starts over
+    timers.set("orders".into(), 30 * MINUTE, start);
+    timers.set("orders".into(), 60 * MINUTE, start + 10 * MINUTE);
+    assert_eq!(
+        timers.remaining("orders", start + 10 * MINUTE),
+        Some(60 * MINUTE)
+    );
+}
+
+#[test]
+fn durations_are_offered_in_minutes_and_hours() {
+    assert_eq!(StopAfter::default(), StopAfter(120));
+    assert_eq!(StopAfter(120).duration(), 120 * MINUTE);
+    assert_eq!(
+        StopAfter::OPTIONS.map(|option| option.to_string()),
+        ["30 minutes", "1 hour", "2 hours", "4 hours", "8 hours"]
+    );
+    assert_eq!(StopAfter(90).to_string(), "90 minutes");
+}
@@ -0,0 +1,83 @@
+//! Checks the letter and colour of the avatars drawn for images without a fetched icon.
+#![allow(dead_code)]
+
+#[path = "../src/app/avatar.rs"]
+mod avatar;
+#[path = "../src/image_ref.rs"]
+mod image_ref;
+
+use avatar::{avatar_color, avatar_letter};
+
+#[test]
+fn the_letter_is_the_first_of_the_name() {
+    assert_eq!(avatar_letter("postgres:16"), 'P');
+    assert_eq!(avatar_letter("mysql"), 'M');
+    assert_eq!(avatar_letter("bitnami/redis:7.2"), 'R');
+}
+
+#[test]
+fn registries_and_namespaces_are_skipped() {
+    assert_eq!(avatar_letter("ghcr.io/acme/postgres:16"), 'P');
+    assert_eq!(avatar_letter("localhost:5000/team/mongo:7"), 'M');
+    assert_eq!(avatar_letter("docker.io/library/mariadb:11"), 'M');
+    assert_eq!(
+        avatar_letter(&format!(
+            "clickhouse/clickhouse-server@sha256:{}",
+            "a".repeat(64)
+        )),
+        'C'
+    );
+}
+
+#[test]
+fn names_starting_with_digits_use_their_first_letter() {
+    assert_eq!(avatar_letter("3scale/2fauth"), 'F');
+    assert_eq!(avatar_letter("acme/42"), '4');
+}
+
+#[test]
+fn unicode_names_are_upper_cased() {
+    assert_eq!(avatar_letter("acme/ñandu"), 'Ñ');
+    assert_eq!(avatar_letter("acme/élan:1"), 'É');
+    // Letters without an upper case stay as they are
+    assert_eq!(avatar_letter("acme/データ"), 'デ');
+}
+
+#[test]
+fn names_without_letters_or_digits_get_a_question_mark() {
+    assert_eq!(avatar_letter("acme/__"), '?');
+}
+
+#[test]
+fn every_tag_of_a_repository_shares_its_colour() {
+    let color = avatar_color("postgres");
+
+    assert_eq!(avatar_color("postgres:16"), color);
+    assert_eq!(avatar_color("postgres:16.2-alpine"), color);
+    assert_eq!(avatar_color("docker.io/library/postgres:15"), color);
+    assert_eq!(
+        avatar_color(&format!("postgres@sha256:{}", "a".repeat(64))),
+        color
+    );
+}
+
+#[test]
+fn repositories_get_their_own_colour() {
+    let colors = ["postgres", "mysql", "redis", "mongo", "acme/postgres"]
+        .map(|image| format!("{:?}", avatar_color(image)));
+
+    for (index, color) in colors.iter().enumerate() {
+        assert!(!colors[index + 1..].contains(color), "{color} is repeated");
+    }
+}
+
+#[test]
+fn colours_are_stable_between_releases() {
+    // The FNV-1a hash of library/postgres is 0x9c29_8d92_0f89_0226, a hue of 286 degrees
+    let color = avatar_color("postgres");
+    let expected = [0.582, 0.2025, 0.6975];
+
+    for (channel, expected) in [color.r, color.g, color.b].into_iter().zip(expected) {
+        assert!((channel - expected).abs() < 1e-3, "{color:?}");
+    }
+}
@@ -0,0 +1,152 @@
+//! Checks when scheduled backups are due, which runs count as missed and which old backups are
+//! deleted.
+#![allow(dead_code)]
+
+#[path = "../src/backup.rs"]
+mod backup;
+#[path = "../src/daemon.rs"]
+mod daemon;
+#[path = "../src/docker.rs"]
+mod docker;
+#[path = "../src/image_ref.rs"]
+mod image_ref;
+#[path = "../src/platform.rs"]
+mod platform;
+#[path = "../src/ports.rs"]
+mod ports;
+
+use backup::{file_name, prune, split_missed, BackupOutcome, BackupSchedule, Frequency};
+use chrono::{DateTime, Duration, TimeZone, Utc, Weekday};
+
+fn at(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(y, m, d, h, min, 0).unwrap()
+}
+
+/// Daily at 02:00, last checked at `checked`. 2024-01-01 is a Monday.
+fn daily(checked: DateTime<Utc>) -> BackupSchedule {
+    BackupSchedule::new("/backups".into(), checked.timestamp())
+}
+
+#[test]
+fn daily_runs_are_at_the_hour_of_the_next_day_when_passed() {
+    let schedule = daily(at(2024, 1, 1, 0, 0));
+
+    assert_eq!(
+        schedule.next_run(&at(2024, 1, 1, 1, 59)),
+        Some(at(2024, 1, 1, 2, 0))
+    );
+    assert_eq!(
+        schedule.next_run(&at(2024, 1, 1, 2, 0)),
+        Some(at(2024, 1, 2, 2, 0))
+    );
+}
+
+#[test]
+fn weekly_runs_wait_for_their_weekday() {
+    let schedule = BackupSchedule {
+        frequency: Frequency::Weekly(Weekday::Fri),
+        hour: 23,
+        ..daily(at(2024, 1, 1, 0, 0))
+    };
+
+    assert_eq!(
+        schedule.next_run(&at(2024, 1, 1, 12, 0)),
+        Some(at(2024, 1, 5, 23, 0))
+    );
+    assert_eq!(
+        schedule.next_run(&at(2024, 1, 5, 23, 30)),
+        Some(at(2024, 1, 12, 23, 0))
+    );
+}
+
+#[test]
+fn every_run_since_the_last_check_is_due() {
+    let schedule = daily(at(2024, 1, 1, 3, 0));
+
+    assert!(schedule.due_runs(&at(2024, 1, 2, 1, 0)).is_empty());
+    assert_eq!(
+        schedule.due_runs(&at(2024, 1, 4, 2, 5)),
+        vec![
+            at(2024, 1, 2, 2, 0),
+            at(2024, 1, 3, 2, 0),
+            at(2024, 1, 4, 2, 0)
+        ]
+    );
+}
+
+#[test]
+fn runs_noticed_late_are_missed() {
+    let runs = vec![at(2024, 1, 2, 2, 0), at(2024, 1, 3, 2, 0)];
+
+    let (missed, run_now) = split_missed(runs.clone(), &at(2024, 1, 3, 2, 10));
+    assert_eq!(missed, vec![at(2024, 1, 2, 2, 0)]);
+    assert_eq!(run_now, Some(at(2024, 1, 3, 2, 0)));
+
+    // db-mgr was opened hours after the latest run was due
+    let (missed, run_now) = split_missed(runs.clone(), &at(2024, 1, 3, 9, 0));
+    assert_eq!(missed, runs);
+    assert_eq!(run_now, None);
+
+    assert_eq!(split_missed(vec![], &at(2024, 1, 3, 9, 0)), (vec![], None));
+}
+
+#[test]
+fn the_grace_period_is_fifteen_minutes() {
+    let due = at(2024, 1, 3, 2, 0);
+
+    let (_, run_now) = split_missed(vec![due], &(due + Duration::minutes(15)));
+    assert_eq!(run_now, Some(due));
+    let (missed, run_now) = split_missed(vec![due], &(due + Duration::minutes(16)));
+    assert_eq!((missed, run_now), (vec![due], None));
+}
+
+#[test]
+fn only_the_latest_results_are_kept() {
+    let mut schedule = daily(at(2024, 1, 1, 0, 0));
+    for day in 0..25 {
+        schedule.record(
+            day,
+            BackupOutcome::Skipped("the container was stopped".into()),
+        );
+    }
+
+    assert_eq!(schedule.history.len(), 20);
+    assert_eq!(schedule.history[0].at, 5);
+    assert_eq!(
+        schedule.history[19].outcome.to_string(),
+        "Skipped, the container was stopped"
+    );
+}
+
+#[test]
+fn backups_are_named_so_they_sort_by_time() {
+    assert_eq!(
+        file_name("orders", &at(2024, 1, 3, 2, 0)),
+        "orders-20240103-020000.sql"
+    );
+}
+
+#[test]
+fn backups_beyond_the_kept_ones_are_pruned_oldest_first() {
+    let files = [
+        "orders-20240103-020000.sql",
+        "orders-20240101-020000.sql",
+        "orders-20240102-020000.sql",
+        // Another container whose name starts the same
+        "orders-db-20231201-020000.sql",
+        "orders-notes.sql",
+        "README.md",
+    ]
+    .map(String::from);
+
+    assert_eq!(
+        prune(&files, "orders", 2),
+        vec!["orders-20240101-020000.sql".to_string()]
+    );
+    assert_eq!(prune(&files, "orders", 0).len(), 3);
+    assert!(prune(&files, "orders", 30).is_empty());
+    assert_eq!(
+        prune(&files, "orders-db", 0),
+        vec!["orders-db-20231201-020000.sql".to_string()]
+    );
+}
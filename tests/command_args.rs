@@ -0,0 +1,131 @@
+//! Checks that command arguments are filled in one by one, so values with spaces or quotes reach
+//! docker as a single argument, and that the command an image sets itself isn't shown as given.
+#![allow(dead_code)]
+
+#[path = "../src/backup.rs"]
+mod backup;
+#[path = "../src/daemon.rs"]
+mod daemon;
+#[path = "../src/data.rs"]
+mod data;
+#[path = "../src/definition.rs"]
+mod definition;
+#[path = "../src/diff.rs"]
+mod diff;
+#[path = "../src/docker.rs"]
+mod docker;
+#[path = "../src/idle.rs"]
+mod idle;
+#[path = "../src/image_ref.rs"]
+mod image_ref;
+#[path = "../src/names.rs"]
+mod names;
+#[path = "../src/platform.rs"]
+mod platform;
+#[path = "../src/ports.rs"]
+mod ports;
+#[path = "../src/registry.rs"]
+mod registry;
+#[path = "../src/template.rs"]
+mod template;
+#[path = "../src/tls.rs"]
+mod tls;
+
+use std::collections::HashMap;
+
+use data::ConfigFile;
+use docker::given_command;
+use template::{render, render_args};
+
+fn variables(entries: &[(&str, &str)]) -> HashMap<String, String> {
+    entries
+        .iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
+
+fn args(args: &[&str]) -> Vec<String> {
+    args.iter().map(|arg| arg.to_string()).collect()
+}
+
+#[test]
+fn values_with_spaces_and_quotes_stay_one_argument() {
+    let command = args(&["redis-server", "--requirepass", "{PASSWORD}"]);
+
+    for password in [
+        "correct horse battery",
+        "it's \"quoted\"",
+        "'single' and \"double\"",
+        "  padded  ",
+        "$(rm -rf /); echo",
+    ] {
+        assert_eq!(
+            render_args(&command, &variables(&[("PASSWORD", password)])),
+            args(&["redis-server", "--requirepass", password]),
+        );
+    }
+}
+
+#[test]
+fn arguments_are_never_split_or_joined() {
+    let command = args(&["sh", "-c", "echo {GREETING} > /tmp/out", ""]);
+    let rendered = render_args(&command, &variables(&[("GREETING", "hello world")]));
+
+    assert_eq!(
+        rendered,
+        args(&["sh", "-c", "echo hello world > /tmp/out", ""])
+    );
+}
+
+#[test]
+fn placeholders_without_a_variable_are_left_alone() {
+    let entered = variables(&[("SET", "rs0")]);
+
+    assert_eq!(render("--replSet={SET}", &entered), "--replSet=rs0");
+    assert_eq!(render("{MISSING}", &entered), "{MISSING}");
+    assert_eq!(render("{{SET}} {SET}", &entered), "{SET} rs0");
+    assert_eq!(render("unclosed {SET", &entered), "unclosed {SET");
+    // A value is inserted as it is, its braces aren't rendered again
+    assert_eq!(
+        render("{SET}", &variables(&[("SET", "{OTHER}")])),
+        "{OTHER}"
+    );
+}
+
+#[test]
+fn the_bundled_redis_entry_takes_its_password_as_an_argument() {
+    let config = serde_yaml::from_str::<ConfigFile>(include_str!("../config.yaml"))
+        .expect("The bundled config must parse");
+    let redis = config
+        .databases
+        .iter()
+        .find(|database| database.name == "Redis")
+        .expect("Redis is bundled");
+
+    let rendered = render_args(
+        &redis.command,
+        &variables(&[("REDIS_PASSWORD", "two words")]),
+    );
+    let position = rendered
+        .iter()
+        .position(|arg| arg == "--requirepass")
+        .expect("The password is required");
+    assert_eq!(rendered[position + 1], "two words");
+}
+
+#[test]
+fn the_command_of_the_image_isnt_given() {
+    let image = args(&["redis-server"]);
+
+    assert!(given_command(args(&["redis-server"]), &image).is_empty());
+    assert_eq!(
+        given_command(args(&["redis-server", "--appendonly", "yes"]), &image),
+        args(&["redis-server", "--appendonly", "yes"])
+    );
+    // Images without a command of their own
+    assert_eq!(
+        given_command(args(&["mongod", "--replSet", "rs0"]), &[]),
+        args(&["mongod", "--replSet", "rs0"])
+    );
+    assert!(given_command(vec![], &[]).is_empty());
+}
@@ -0,0 +1,79 @@
+//! Fixtures shared by the tests, included with `mod common;` by tests that also include the
+//! `data` and `docker` modules.
+
+use bollard::service::{ContainerStateStatusEnum, ContainerSummary};
+
+use crate::{
+    data::{ConfigFile, DatabaseConfig},
+    docker::{summary_container, ContainerExit, DbContainer},
+};
+
+/// The entries of the bundled config.yaml, in its order.
+pub fn databases() -> Vec<DatabaseConfig> {
+    serde_yaml::from_str::<ConfigFile>(include_str!("../../config.yaml"))
+        .expect("The bundled config must parse")
+        .databases
+}
+
+/// The bundled entry called `name`.
+pub fn bundled(name: &str) -> DatabaseConfig {
+    databases()
+        .into_iter()
+        .find(|database| database.name == name)
+        .unwrap_or_else(|| panic!("{name} is bundled"))
+}
+
+pub fn postgres() -> DatabaseConfig {
+    bundled("Postgres")
+}
+
+/// Builds a container as a refresh lists it. Until they are overridden, it runs `postgres:16`,
+/// its id is its name followed by `-id` and its state is unknown.
+pub struct ContainerBuilder {
+    summary: ContainerSummary,
+    state: ContainerStateStatusEnum,
+    exit: ContainerExit,
+}
+
+/// A container called `name`, without the leading `/` docker lists names with.
+pub fn container(name: &str) -> ContainerBuilder {
+    ContainerBuilder {
+        summary: ContainerSummary {
+            id: Some(format!("{name}-id")),
+            names: Some(vec![format!("/{name}")]),
+            image: Some("postgres:16".into()),
+            ..Default::default()
+        },
+        state: ContainerStateStatusEnum::EMPTY,
+        exit: ContainerExit::default(),
+    }
+}
+
+impl ContainerBuilder {
+    pub fn id(mut self, id: &str) -> Self {
+        self.summary.id = Some(id.into());
+        self
+    }
+
+    pub fn image(mut self, image: &str) -> Self {
+        self.summary.image = Some(image.into());
+        self
+    }
+
+    pub fn state(mut self, state: ContainerStateStatusEnum) -> Self {
+        self.state = state;
+        self
+    }
+
+    pub fn exit(mut self, exit: ContainerExit) -> Self {
+        self.exit = exit;
+        self
+    }
+
+    pub fn build(self) -> DbContainer {
+        let mut container = summary_container(self.summary).expect("The summary has an id");
+        container.state = self.state;
+        container.exit = self.exit;
+        container
+    }
+}
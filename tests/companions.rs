@@ -0,0 +1,192 @@
+//! Checks the companion admin UIs of a database: how their config is templated from the parent's,
+//! the labels linking them to it and how the sidebar lists them under it.
+#![allow(dead_code)]
+
+#[path = "../src/app/avatar.rs"]
+mod avatar;
+#[path = "../src/backup.rs"]
+mod backup;
+#[path = "../src/app/cantainer_card.rs"]
+mod cantainer_card;
+mod common;
+#[path = "../src/app/container_list.rs"]
+mod container_list;
+#[path = "../src/daemon.rs"]
+mod daemon;
+#[path = "../src/data.rs"]
+mod data;
+#[path = "../src/definition.rs"]
+mod definition;
+#[path = "../src/diff.rs"]
+mod diff;
+#[path = "../src/docker.rs"]
+mod docker;
+#[path = "../src/exits.rs"]
+mod exits;
+#[path = "../src/http.rs"]
+mod http;
+#[path = "../src/idle.rs"]
+mod idle;
+#[path = "../src/image_ref.rs"]
+mod image_ref;
+#[path = "../src/names.rs"]
+mod names;
+#[path = "../src/platform.rs"]
+mod platform;
+#[path = "../src/ports.rs"]
+mod ports;
+#[path = "../src/registry.rs"]
+mod registry;
+#[path = "../src/app/state_badge.rs"]
+mod state_badge;
+#[path = "../src/app/stopping.rs"]
+mod stopping;
+#[path = "../src/template.rs"]
+mod template;
+#[path = "../src/app/thumbnails.rs"]
+mod thumbnails;
+#[path = "../src/tls.rs"]
+mod tls;
+#[path = "../src/versions.rs"]
+mod versions;
+
+use std::collections::HashMap;
+
+use bollard::service::ContainerSummary;
+use common::postgres;
+use container_list::with_companions;
+use data::CompanionConfig;
+use docker::{
+    companion_network, plan_container, summary_container, DbContainer, DbContainerConfig,
+    COMPANION_LABEL, MANAGED_LABELS,
+};
+
+fn companion(name: &str) -> CompanionConfig {
+    postgres()
+        .companions
+        .into_iter()
+        .find(|companion| companion.name == name)
+        .expect("The companion is bundled")
+}
+
+/// The prepared config of a Postgres container named `db-mgr__orders`.
+fn parent() -> DbContainerConfig {
+    let mut parent = postgres().quick_config("db-mgr__orders");
+    parent.variables = HashMap::from([
+        ("POSTGRES_PASSWORD".into(), "hunter2".into()),
+        ("POSTGRES_DB".into(), "orders".into()),
+    ]);
+    parent.project = Some("shop".into());
+    parent
+}
+
+fn container(name: &str, companion_of: Option<&str>) -> DbContainer {
+    let mut container = summary_container(ContainerSummary {
+        id: Some(format!("{name}-id")),
+        names: Some(vec![format!("/{name}")]),
+        image: Some("postgres:16".into()),
+        ..Default::default()
+    })
+    .expect("The summary has an id");
+    container.labels = companion_of
+        .map(|parent| HashMap::from([(COMPANION_LABEL.to_string(), parent.to_string())]))
+        .unwrap_or_default();
+    container
+}
+
+#[test]
+fn the_host_is_the_parents_name() {
+    let config = companion("Adminer").config(&parent());
+
+    assert_eq!(config.name, "db-mgr__orders-adminer");
+    assert_eq!(
+        config
+            .variables
+            .get("ADMINER_DEFAULT_SERVER")
+            .map(String::as_str),
+        Some("db-mgr__orders")
+    );
+    assert_eq!(config.published_ports, vec![8080]);
+}
+
+#[test]
+fn credentials_are_passed_through() {
+    let config = companion("pgAdmin").config(&parent());
+
+    assert_eq!(
+        config
+            .variables
+            .get("PGADMIN_DEFAULT_PASSWORD")
+            .map(String::as_str),
+        Some("hunter2")
+    );
+    assert_eq!(
+        config
+            .variables
+            .get("PGADMIN_DEFAULT_EMAIL")
+            .map(String::as_str),
+        Some("admin@example.com")
+    );
+}
+
+#[test]
+fn companions_follow_their_parent() {
+    let config = companion("Adminer").config(&parent());
+
+    assert_eq!(config.companion_of.as_deref(), Some("db-mgr__orders"));
+    assert_eq!(config.depends_on, vec!["db-mgr__orders".to_string()]);
+    assert_eq!(config.project.as_deref(), Some("shop"));
+    assert!(config.volumes.is_empty());
+    assert_eq!(config.tag, "latest");
+}
+
+#[test]
+fn companions_are_labelled_and_share_a_network_with_their_parent() {
+    let plan = plan_container(&companion("Adminer").config(&parent()), MANAGED_LABELS);
+    let network = companion_network("db-mgr__orders");
+
+    assert!(plan
+        .iter()
+        .any(|op| op.call == format!("GET /networks/{network}")));
+    let create = plan
+        .iter()
+        .find(|op| op.call.starts_with("POST /containers/create"))
+        .and_then(|op| op.body.clone())
+        .expect("The plan creates the container");
+    assert_eq!(create["Labels"][COMPANION_LABEL], "db-mgr__orders");
+    assert_eq!(create["HostConfig"]["NetworkMode"], network.as_str());
+}
+
+#[test]
+fn the_parent_is_read_back_from_the_label() {
+    assert_eq!(
+        container("db-mgr__orders-adminer", Some("db-mgr__orders")).companion_of(),
+        Some("db-mgr__orders")
+    );
+    assert_eq!(container("db-mgr__orders", None).companion_of(), None);
+    assert_eq!(container("db-mgr__orders", Some("")).companion_of(), None);
+}
+
+#[test]
+fn companions_are_listed_under_their_parent() {
+    let containers = vec![
+        container("db-mgr__orders-adminer", Some("db-mgr__orders")),
+        container("db-mgr__cache", None),
+        container("db-mgr__orders", None),
+        container("db-mgr__gone-adminer", Some("db-mgr__gone")),
+    ];
+
+    let names = with_companions(&containers)
+        .into_iter()
+        .map(|container| container.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        vec![
+            "/db-mgr__cache",
+            "/db-mgr__orders",
+            "/db-mgr__orders-adminer",
+            "/db-mgr__gone-adminer",
+        ]
+    );
+}
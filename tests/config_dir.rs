@@ -0,0 +1,144 @@
+//! Checks where db-mgr keeps its files: the precedence of the overrides over the platform
+//! directories, and what happens when the directory can't be written to.
+#![allow(dead_code)]
+
+#[path = "../src/backup.rs"]
+mod backup;
+#[path = "../src/daemon.rs"]
+mod daemon;
+#[path = "../src/data.rs"]
+mod data;
+#[path = "../src/definition.rs"]
+mod definition;
+#[path = "../src/diff.rs"]
+mod diff;
+#[path = "../src/docker.rs"]
+mod docker;
+#[path = "../src/idle.rs"]
+mod idle;
+#[path = "../src/image_ref.rs"]
+mod image_ref;
+#[path = "../src/names.rs"]
+mod names;
+#[path = "../src/platform.rs"]
+mod platform;
+#[path = "../src/ports.rs"]
+mod ports;
+#[path = "../src/registry.rs"]
+mod registry;
+#[path = "../src/template.rs"]
+mod template;
+#[path = "../src/tls.rs"]
+mod tls;
+
+use std::path::PathBuf;
+
+use data::{persistence_problem, read_config_file, Paths};
+
+/// A directory of its own for each test, as they run at once.
+fn scratch(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("db-mgr-paths-{}-{test}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Paths inside a regular file, so no directory can be created for them.
+fn unusable(test: &str) -> Paths {
+    let file = scratch(test).join("not-a-directory");
+    std::fs::write(&file, "").unwrap();
+    let dir = file.join("db-mgr");
+    Paths {
+        config_dir: dir.clone(),
+        data_dir: dir,
+    }
+}
+
+#[test]
+fn the_flag_wins_over_the_environment() {
+    let paths = Paths::resolve(Some("/from/flag".into()), Some("/from/env".into()))
+        .expect("An override always resolves");
+
+    assert_eq!(paths.config_dir, PathBuf::from("/from/flag"));
+    assert_eq!(paths.data_dir, PathBuf::from("/from/flag"));
+    assert_eq!(paths.config_file(), PathBuf::from("/from/flag/config.yaml"));
+}
+
+#[test]
+fn the_environment_is_used_without_the_flag() {
+    let paths =
+        Paths::resolve(None, Some("/from/env".into())).expect("An override always resolves");
+
+    assert_eq!(paths.config_dir, PathBuf::from("/from/env"));
+    assert_eq!(
+        paths.preferences_file(),
+        PathBuf::from("/from/env/prefs.yaml")
+    );
+}
+
+#[test]
+fn empty_overrides_fall_back_to_the_platform_directories() {
+    let platform = Paths::resolve(None, None);
+
+    assert_eq!(Paths::resolve(Some("".into()), None), platform);
+    assert_eq!(Paths::resolve(None, Some("".into())), platform);
+}
+
+#[test]
+fn the_flag_is_read_from_the_arguments() {
+    let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+
+    let separate = Paths::from_env(&args(&["db-mgr", "--config-dir", "/from/flag"]));
+    assert_eq!(separate.unwrap().config_dir, PathBuf::from("/from/flag"));
+
+    let joined = Paths::from_env(&args(&["db-mgr", "--config-dir=/from/flag"]));
+    assert_eq!(joined.unwrap().config_dir, PathBuf::from("/from/flag"));
+}
+
+#[test]
+fn writable_directories_have_no_problem() {
+    let dir = scratch("writable");
+    let paths = Paths {
+        config_dir: dir.join("config"),
+        data_dir: dir.join("data"),
+    };
+
+    assert_eq!(persistence_problem(Some(&paths)), None);
+    assert!(paths.config_dir.is_dir());
+    assert!(paths.data_dir.is_dir());
+}
+
+#[test]
+fn missing_and_unwritable_directories_are_reported() {
+    let missing = persistence_problem(None).expect("Nothing can be saved");
+    assert!(missing.contains("DB_MGR_CONFIG_DIR"), "{missing}");
+    assert!(missing.contains("--config-dir"), "{missing}");
+
+    let unwritable =
+        persistence_problem(Some(&unusable("unwritable"))).expect("Nothing can be saved");
+    assert!(
+        unwritable.starts_with("Settings won't be saved"),
+        "{unwritable}"
+    );
+}
+
+#[test]
+fn the_default_config_is_used_when_it_can_not_be_written() {
+    let (default, problem) = read_config_file(None);
+    assert!(problem.is_none());
+
+    let (config, problem) = read_config_file(Some(&unusable("default-config")));
+    assert!(problem.is_none());
+    assert_eq!(
+        config
+            .databases
+            .iter()
+            .map(|db| &db.name)
+            .collect::<Vec<_>>(),
+        default
+            .databases
+            .iter()
+            .map(|db| &db.name)
+            .collect::<Vec<_>>()
+    );
+    assert!(config.databases.iter().any(|db| db.name == "Postgres"));
+}
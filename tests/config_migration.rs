@@ -0,0 +1,154 @@
+//! Checks the migration of older config files to the current schema, with a before and after
+//! fixture for each step.
+#![allow(dead_code)]
+
+#[path = "../src/backup.rs"]
+mod backup;
+#[path = "../src/daemon.rs"]
+mod daemon;
+#[path = "../src/data.rs"]
+mod data;
+#[path = "../src/definition.rs"]
+mod definition;
+#[path = "../src/diff.rs"]
+mod diff;
+#[path = "../src/docker.rs"]
+mod docker;
+#[path = "../src/idle.rs"]
+mod idle;
+#[path = "../src/image_ref.rs"]
+mod image_ref;
+#[path = "../src/names.rs"]
+mod names;
+#[path = "../src/platform.rs"]
+mod platform;
+#[path = "../src/ports.rs"]
+mod ports;
+#[path = "../src/registry.rs"]
+mod registry;
+#[path = "../src/template.rs"]
+mod template;
+#[path = "../src/tls.rs"]
+mod tls;
+
+use std::path::PathBuf;
+
+use data::{migrate_config, read_config_file, Paths, CONFIG_VERSION};
+use serde_yaml::Value;
+
+fn yaml(source: &str) -> Value {
+    serde_yaml::from_str(source).expect("The fixture must parse")
+}
+
+/// A directory of its own for each test, as they run at once.
+fn scratch(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("db-mgr-migrate-{}-{test}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+const VERSION_0: &str = "
+databases:
+  - name: Postgres
+    image: postgres
+    icon_url: ''
+    tags: ['16']
+    variables:
+      Password: POSTGRES_PASSWORD
+    volumes:
+      pgsql_data: /var/lib/postgresql/data
+low_disk_space_mb: 2048
+";
+
+const VERSION_1: &str = "
+version: 1
+databases:
+  - name: Postgres
+    image: postgres
+    icon_url: ''
+    tags: ['16']
+    variables:
+      Password: POSTGRES_PASSWORD
+    volumes:
+      pgsql_data: /var/lib/postgresql/data
+low_disk_space_mb: 2048
+";
+
+#[test]
+fn unversioned_files_get_version_1() {
+    let migrated = migrate_config(yaml(VERSION_0)).expect("Version 0 can be migrated");
+
+    assert_eq!(migrated, yaml(VERSION_1));
+    // The version goes first so it is the first thing seen in the rewritten file
+    let Value::Mapping(mapping) = &migrated else {
+        panic!("The config is a mapping");
+    };
+    assert_eq!(mapping.keys().next(), Some(&Value::from("version")));
+}
+
+#[test]
+fn current_files_are_left_alone() {
+    assert_eq!(CONFIG_VERSION, 1);
+    assert_eq!(migrate_config(yaml(VERSION_1)).unwrap(), yaml(VERSION_1));
+}
+
+#[test]
+fn unknown_fields_are_kept() {
+    let source = format!("{VERSION_0}future_field:\n  nested: [1, 2]\n");
+    let migrated = migrate_config(yaml(&source)).unwrap();
+
+    assert_eq!(migrated["future_field"], yaml("nested: [1, 2]"));
+    assert_eq!(migrated["databases"][0]["name"], Value::from("Postgres"));
+}
+
+#[test]
+fn newer_files_are_refused() {
+    let ex = migrate_config(yaml("version: 2\ndatabases: []\n")).unwrap_err();
+    assert!(ex.to_string().contains("version 2"), "{ex}");
+    assert!(ex.to_string().contains("update db-mgr"), "{ex}");
+}
+
+#[test]
+fn files_that_are_not_a_mapping_are_refused() {
+    assert!(migrate_config(yaml("- a\n- b\n")).is_err());
+}
+
+#[test]
+fn migrated_files_are_rewritten_with_a_backup() {
+    let dir = scratch("rewrite");
+    let paths = Paths {
+        config_dir: dir.clone(),
+        data_dir: dir.clone(),
+    };
+    std::fs::write(paths.config_file(), VERSION_0).unwrap();
+
+    let (config, problem) = read_config_file(Some(&paths));
+
+    assert_eq!(problem, None);
+    assert_eq!(config.version, CONFIG_VERSION);
+    assert_eq!(
+        std::fs::read_to_string(dir.join("config.yaml.bak")).unwrap(),
+        VERSION_0
+    );
+    let rewritten = std::fs::read_to_string(paths.config_file()).unwrap();
+    assert_eq!(yaml(&rewritten), yaml(VERSION_1));
+}
+
+#[test]
+fn newer_files_fall_back_to_the_default_config_untouched() {
+    let dir = scratch("newer");
+    let paths = Paths {
+        config_dir: dir.clone(),
+        data_dir: dir.clone(),
+    };
+    let newer = "version: 2\ndatabases: []\n";
+    std::fs::write(paths.config_file(), newer).unwrap();
+
+    let (config, problem) = read_config_file(Some(&paths));
+
+    assert!(problem.is_some_and(|problem| problem.contains("version 2")));
+    assert!(config.databases.iter().any(|db| db.name == "Postgres"));
+    assert_eq!(std::fs::read_to_string(paths.config_file()).unwrap(), newer);
+    assert!(!dir.join("config.yaml.bak").exists());
+}
@@ -0,0 +1,332 @@
+//! Checks the config validation against images made up here, so typos in variable names and
+//! volume targets are caught without a docker daemon.
+#![allow(dead_code)]
+
+#[path = "../src/backup.rs"]
+mod backup;
+#[path = "../src/daemon.rs"]
+mod daemon;
+#[path = "../src/data.rs"]
+mod data;
+#[path = "../src/definition.rs"]
+mod definition;
+#[path = "../src/diff.rs"]
+mod diff;
+#[path = "../src/docker.rs"]
+mod docker;
+#[path = "../src/idle.rs"]
+mod idle;
+#[path = "../src/image_ref.rs"]
+mod image_ref;
+#[path = "../src/names.rs"]
+mod names;
+#[path = "../src/platform.rs"]
+mod platform;
+#[path = "../src/ports.rs"]
+mod ports;
+#[path = "../src/registry.rs"]
+mod registry;
+#[path = "../src/template.rs"]
+mod template;
+#[path = "../src/tls.rs"]
+mod tls;
+#[path = "../src/validate.rs"]
+mod validate;
+#[path = "../src/versions.rs"]
+mod versions;
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Mutex,
+};
+
+use data::DatabaseConfig;
+use futures::{executor::block_on, future::BoxFuture, FutureExt};
+use image_ref::ImageRef;
+use validate::{
+    check_variables, check_version_pattern, check_volumes, levenshtein, validate_config, Finding,
+    Severity, ValidateRuntime,
+};
+
+/// A daemon with the images of `env`, and those of `pullable` once they are pulled.
+#[derive(Default)]
+struct Images {
+    env: Mutex<HashMap<String, Vec<String>>>,
+    pullable: HashMap<String, Vec<String>>,
+    unreachable: HashSet<String>,
+    /// What was asked of it, such as `inspect redis:7` and `pull redis:7`.
+    calls: Mutex<Vec<String>>,
+}
+
+impl Images {
+    fn with(self, image: &str, env: &[&str]) -> Self {
+        self.env.lock().unwrap().insert(image.into(), strings(env));
+        self
+    }
+
+    fn calls(&self) -> Vec<String> {
+        self.calls.lock().unwrap().clone()
+    }
+}
+
+impl ValidateRuntime for Images {
+    fn image_env<'a>(
+        &'a self,
+        image: &'a ImageRef,
+    ) -> BoxFuture<'a, anyhow::Result<Option<Vec<String>>>> {
+        let image = image.to_string();
+        self.calls.lock().unwrap().push(format!("inspect {image}"));
+        let result = match self.unreachable.contains(&image) {
+            true => Err(anyhow::anyhow!("connection refused")),
+            false => Ok(self.env.lock().unwrap().get(&image).cloned()),
+        };
+        async move { result }.boxed()
+    }
+
+    fn pull<'a>(&'a self, image: &'a ImageRef) -> BoxFuture<'a, anyhow::Result<()>> {
+        let image = image.to_string();
+        self.calls.lock().unwrap().push(format!("pull {image}"));
+        let result = match self.pullable.get(&image) {
+            Some(env) => {
+                self.env.lock().unwrap().insert(image, env.clone());
+                Ok(())
+            }
+            None => Err(anyhow::anyhow!("manifest for {image} not found")),
+        };
+        async move { result }.boxed()
+    }
+}
+
+fn strings(values: &[&str]) -> Vec<String> {
+    values.iter().map(|value| value.to_string()).collect()
+}
+
+fn entry(yaml: &str) -> DatabaseConfig {
+    serde_yaml::from_str(yaml).expect("The entry must parse")
+}
+
+const POSTGRES_ENV: &[&str] = &[
+    "PATH=/usr/local/bin:/usr/bin",
+    "LANG=en_US.utf8",
+    "PG_MAJOR=16",
+    "PGDATA=/var/lib/postgresql/data",
+];
+
+#[test]
+fn distances_count_single_character_edits() {
+    assert_eq!(levenshtein("PGDATA", "PGDATA"), 0);
+    assert_eq!(levenshtein("PGDAT", "PGDATA"), 1);
+    assert_eq!(levenshtein("POSTGRESS_PASSWORD", "POSTGRES_PASSWORD"), 1);
+    assert_eq!(levenshtein("PG_MAJRO", "PG_MAJOR"), 2);
+    assert_eq!(levenshtein("", "LANG"), 4);
+    assert_eq!(levenshtein("kitten", "sitting"), 3);
+}
+
+#[test]
+fn near_miss_typos_are_warned_about() {
+    let findings = check_variables(["PGDAT", "PG_MAJRO"], &strings(POSTGRES_ENV));
+
+    assert_eq!(
+        findings,
+        vec![
+            Finding {
+                severity: Severity::Warning,
+                message: "PGDAT is not set by the image, did you mean PGDATA?".into(),
+            },
+            Finding {
+                severity: Severity::Warning,
+                message: "PG_MAJRO is not set by the image, did you mean PG_MAJOR?".into(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn undeclared_variables_are_only_noted() {
+    let findings = check_variables(
+        ["POSTGRES_PASSWORD", "PGDATA", "LANG"],
+        &strings(POSTGRES_ENV),
+    );
+    assert_eq!(
+        findings,
+        vec![Finding {
+            severity: Severity::Info,
+            message: "POSTGRES_PASSWORD has no default in the image, check the image documentation"
+                .into(),
+        }]
+    );
+
+    // Entries without a value still count as set, and an image without env has nothing to miss
+    assert!(check_variables(["DEBUG"], &strings(&["DEBUG"])).is_empty());
+    assert_eq!(check_variables(["PGDATA"], &[])[0].severity, Severity::Info);
+}
+
+#[test]
+fn volume_targets_must_be_absolute() {
+    let findings = check_volumes([
+        ("pgsql_data", "/var/lib/postgresql/data"),
+        ("relative", "var/lib/data"),
+        ("windows", "C:\\data"),
+        ("empty", ""),
+    ]);
+
+    assert_eq!(
+        findings
+            .iter()
+            .map(|finding| (finding.severity, finding.message.as_str()))
+            .collect::<Vec<_>>(),
+        vec![
+            (
+                Severity::Error,
+                "Volume relative targets \"var/lib/data\" which is not an absolute path"
+            ),
+            (
+                Severity::Error,
+                "Volume windows targets \"C:\\\\data\" which is not an absolute path"
+            ),
+            (
+                Severity::Error,
+                "Volume empty targets \"\" which is not an absolute path"
+            ),
+        ]
+    );
+}
+
+#[test]
+fn entries_are_checked_against_their_image() {
+    let images = Images::default().with("postgres:16", POSTGRES_ENV);
+    let postgres = entry(
+        "
+name: Postgres
+image: postgres
+icon_url: ''
+tags: ['16']
+variables:
+  Data: PGDAT
+volumes:
+  pgsql_data: var/lib/postgresql/data
+",
+    );
+
+    let reports = block_on(validate_config(&images, vec![postgres]));
+    assert_eq!(reports.len(), 1);
+    assert_eq!(reports[0].name, "Postgres");
+    assert_eq!(reports[0].severity(), Some(Severity::Error));
+    // The worst come first
+    assert_eq!(
+        reports[0]
+            .findings
+            .iter()
+            .map(|finding| finding.severity)
+            .collect::<Vec<_>>(),
+        vec![Severity::Error, Severity::Warning]
+    );
+    assert_eq!(images.calls(), vec!["inspect postgres:16"]);
+}
+
+#[test]
+fn missing_images_are_pulled_once() {
+    let images = Images {
+        pullable: HashMap::from([("redis:7".to_string(), strings(&["REDIS_VERSION=7.2"]))]),
+        ..Default::default()
+    };
+    let redis = entry(
+        "
+name: Redis
+image: redis
+icon_url: ''
+tags: ['7']
+variables:
+  Version: REDIS_VERSION
+volumes:
+  redis_data: /data
+",
+    );
+
+    let reports = block_on(validate_config(&images, vec![redis]));
+    assert!(reports[0].findings.is_empty(), "{:?}", reports[0].findings);
+    assert_eq!(
+        images.calls(),
+        vec!["inspect redis:7", "pull redis:7", "inspect redis:7"]
+    );
+}
+
+#[test]
+fn images_that_cant_be_inspected_are_errors() {
+    let images = Images {
+        unreachable: HashSet::from(["mysql:8".to_string()]),
+        ..Default::default()
+    };
+    let entries = ["mysql", "mongo"].map(|image| {
+        entry(&format!(
+            "
+name: {image}
+image: {image}
+icon_url: ''
+tags: ['8']
+variables: {{}}
+volumes: {{}}
+"
+        ))
+    });
+
+    let reports = block_on(validate_config(&images, entries.to_vec()));
+    assert_eq!(
+        reports[0].findings[0].message,
+        "Could not inspect mysql:8: connection refused"
+    );
+    assert_eq!(
+        reports[1].findings[0].message,
+        "Could not inspect mongo:8: manifest for mongo:8 not found"
+    );
+    assert!(reports
+        .iter()
+        .all(|report| report.severity() == Some(Severity::Error)));
+}
+
+#[test]
+fn built_images_arent_inspected() {
+    let images = Images::default();
+    let built = entry(
+        "
+name: pgvector
+image: postgres
+icon_url: ''
+tags: ['16']
+dockerfile: 'FROM postgres:16'
+variables:
+  Password: POSTGRES_PASSWORD
+volumes: {}
+",
+    );
+
+    let reports = block_on(validate_config(&images, vec![built]));
+    assert_eq!(reports[0].severity(), Some(Severity::Info));
+    assert!(images.calls().is_empty());
+}
+
+#[test]
+fn version_patterns_need_a_single_major() {
+    let postgres = |pattern: &str| {
+        entry(&format!(
+            "
+name: Postgres
+image: postgres
+icon_url: ''
+tags: ['16']
+variables: {{}}
+volumes: {{}}
+{pattern}
+"
+        ))
+    };
+
+    assert!(check_version_pattern(&postgres("version_pattern: '{MAJOR}*'")).is_empty());
+    // Without a pattern the major version is not checked at all
+    assert!(check_version_pattern(&postgres("")).is_empty());
+    for broken in ["'*'", "'{MAJOR}.{MAJOR}'"] {
+        let findings = check_version_pattern(&postgres(&format!("version_pattern: {broken}")));
+        assert_eq!(findings.len(), 1, "{broken}");
+        assert_eq!(findings[0].severity, Severity::Warning);
+    }
+}
@@ -0,0 +1,160 @@
+//! Checks how docker event messages become timeline entries, from captured event JSON, and how
+//! the per container log keeps them.
+#![allow(dead_code)]
+
+#[path = "../src/daemon.rs"]
+mod daemon;
+#[path = "../src/docker.rs"]
+mod docker;
+#[path = "../src/app/events.rs"]
+mod events;
+#[path = "../src/image_ref.rs"]
+mod image_ref;
+#[path = "../src/platform.rs"]
+mod platform;
+#[path = "../src/ports.rs"]
+mod ports;
+
+use bollard::service::EventMessage;
+use docker::{parse_event, ContainerEvent, ContainerEventKind};
+use events::{format_ago, EventLog};
+
+/// A container event as `docker events --format '{{json .}}'` printed it.
+fn container_message(action: &str, attributes: &str) -> EventMessage {
+    serde_json::from_str(&format!(
+        r#"{{
+            "Type": "container",
+            "Action": "{action}",
+            "Actor": {{
+                "ID": "4f3c2a1b9e8d",
+                "Attributes": {{ "image": "postgres:16", "name": "db-mgr__orders"{attributes} }}
+            }},
+            "scope": "local",
+            "time": 1700000000,
+            "timeNano": 1700000000123456789
+        }}"#
+    ))
+    .expect("The captured JSON must parse")
+}
+
+fn kind(action: &str, attributes: &str) -> Option<ContainerEventKind> {
+    parse_event(container_message(action, attributes)).map(|event| event.kind)
+}
+
+fn event(time: i64, kind: ContainerEventKind) -> ContainerEvent {
+    ContainerEvent {
+        container: "4f3c2a1b9e8d".into(),
+        time,
+        kind,
+    }
+}
+
+#[test]
+fn events_carry_the_container_and_time() {
+    assert_eq!(
+        parse_event(container_message("start", "")),
+        Some(event(1700000000, ContainerEventKind::Started))
+    );
+}
+
+#[test]
+fn exits_and_kills_keep_their_details() {
+    assert_eq!(
+        kind("die", r#", "exitCode": "137""#),
+        Some(ContainerEventKind::Died(Some(137)))
+    );
+    assert_eq!(kind("die", ""), Some(ContainerEventKind::Died(None)));
+    assert_eq!(
+        kind("kill", r#", "signal": "SIGKILL""#),
+        Some(ContainerEventKind::Killed(Some("SIGKILL".into())))
+    );
+    assert_eq!(kind("oom", ""), Some(ContainerEventKind::OutOfMemory));
+}
+
+#[test]
+fn health_changes_keep_their_status() {
+    assert_eq!(
+        kind("health_status: unhealthy", ""),
+        Some(ContainerEventKind::Health("unhealthy".into()))
+    );
+}
+
+#[test]
+fn execs_and_other_objects_are_left_out() {
+    assert_eq!(kind("exec_start: psql -U postgres", ""), None);
+    assert_eq!(kind("attach", ""), None);
+
+    let volume: EventMessage = serde_json::from_str(
+        r#"{ "Type": "volume", "Action": "create", "Actor": { "ID": "orders_data" }, "time": 1700000000 }"#,
+    )
+    .unwrap();
+    assert_eq!(parse_event(volume), None);
+}
+
+#[test]
+fn events_are_described_for_the_timeline() {
+    assert_eq!(
+        ContainerEventKind::Died(Some(1)).to_string(),
+        "Exited with code 1"
+    );
+    assert_eq!(ContainerEventKind::Unpaused.to_string(), "Resumed");
+    assert_eq!(
+        ContainerEventKind::Health("healthy".into()).to_string(),
+        "Health check: healthy"
+    );
+}
+
+#[test]
+fn the_log_is_newest_first_without_duplicates() {
+    let mut log = EventLog::default();
+    log.push(event(20, ContainerEventKind::Started));
+    log.push(event(10, ContainerEventKind::Created));
+    // Backfilled events overlap the ones already seen live
+    log.push(event(20, ContainerEventKind::Started));
+    log.push(event(30, ContainerEventKind::Stopped));
+
+    assert_eq!(
+        log.events("4f3c2a1b9e8d"),
+        vec![
+            event(30, ContainerEventKind::Stopped),
+            event(20, ContainerEventKind::Started),
+            event(10, ContainerEventKind::Created),
+        ]
+    );
+    assert_eq!(log.newest_first("4f3c2a1b9e8d").count(), 3);
+    assert!(log.events("other").is_empty());
+}
+
+#[test]
+fn the_log_keeps_the_latest_200_events() {
+    let mut log = EventLog::default();
+    for time in 0..250 {
+        log.push(event(time, ContainerEventKind::Restarted));
+    }
+
+    let events = log.events("4f3c2a1b9e8d");
+    assert_eq!(events.len(), 200);
+    assert_eq!(events.first().map(|event| event.time), Some(249));
+    assert_eq!(events.last().map(|event| event.time), Some(50));
+}
+
+#[test]
+fn backfills_run_once_unless_they_failed() {
+    let mut log = EventLog::default();
+    assert!(log.start_backfill("a"));
+    assert!(!log.start_backfill("a"));
+
+    log.backfill_failed("a");
+    assert!(log.start_backfill("a"));
+}
+
+#[test]
+fn times_are_shown_relative() {
+    assert_eq!(format_ago(-5), "just now");
+    assert_eq!(format_ago(59), "just now");
+    assert_eq!(format_ago(60), "1 min ago");
+    assert_eq!(format_ago(3599), "59 min ago");
+    assert_eq!(format_ago(3600), "1 h ago");
+    assert_eq!(format_ago(86_400), "1 day ago");
+    assert_eq!(format_ago(3 * 86_400 + 5), "3 days ago");
+}
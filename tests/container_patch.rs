@@ -0,0 +1,123 @@
+//! Checks that refreshing a single container patches it into the list without disturbing the
+//! order of the others or what is selected.
+#![allow(dead_code)]
+
+#[path = "../src/backup.rs"]
+mod backup;
+mod common;
+#[path = "../src/daemon.rs"]
+mod daemon;
+#[path = "../src/data.rs"]
+mod data;
+#[path = "../src/definition.rs"]
+mod definition;
+#[path = "../src/diff.rs"]
+mod diff;
+#[path = "../src/docker.rs"]
+mod docker;
+#[path = "../src/idle.rs"]
+mod idle;
+#[path = "../src/image_ref.rs"]
+mod image_ref;
+#[path = "../src/names.rs"]
+mod names;
+#[path = "../src/platform.rs"]
+mod platform;
+#[path = "../src/ports.rs"]
+mod ports;
+#[path = "../src/app/refresh.rs"]
+mod refresh;
+#[path = "../src/registry.rs"]
+mod registry;
+#[path = "../src/template.rs"]
+mod template;
+#[path = "../src/tls.rs"]
+mod tls;
+
+use std::collections::HashSet;
+
+use bollard::service::ContainerStateStatusEnum;
+use common::container;
+use docker::DbContainer;
+use refresh::patch_containers;
+
+use ContainerStateStatusEnum::{CREATED, EXITED, RUNNING};
+
+/// Newest first, as docker lists them.
+fn listed() -> Vec<DbContainer> {
+    vec![
+        container("db-mgr__cache").id("c").state(RUNNING).build(),
+        container("db-mgr__orders").id("b").state(EXITED).build(),
+        container("db-mgr__users").id("a").state(RUNNING).build(),
+    ]
+}
+
+fn ids(containers: &[DbContainer]) -> Vec<&str> {
+    containers
+        .iter()
+        .map(|container| container.id.as_str())
+        .collect()
+}
+
+fn selected(ids: &[&str]) -> HashSet<String> {
+    ids.iter().map(|id| id.to_string()).collect()
+}
+
+#[test]
+fn updates_keep_their_place() {
+    let mut containers = listed();
+    let mut selection = selected(&["b"]);
+
+    let started = container("db-mgr__orders").id("b").state(RUNNING).build();
+    let old = patch_containers(&mut containers, Some(&mut selection), "b", Some(started));
+
+    assert_eq!(ids(&containers), vec!["c", "b", "a"]);
+    assert_eq!(containers[1].state, RUNNING);
+    assert_eq!(ids(&old), vec!["b"]);
+    assert_eq!(old[0].state, EXITED);
+    assert_eq!(selection, selected(&["b"]));
+}
+
+#[test]
+fn new_containers_go_first() {
+    let mut containers = listed();
+    let mut selection = selected(&["a"]);
+
+    let created = container("db-mgr__search").id("d").state(CREATED).build();
+    let old = patch_containers(&mut containers, Some(&mut selection), "d", Some(created));
+
+    assert_eq!(ids(&containers), vec!["d", "c", "b", "a"]);
+    assert!(old.is_empty());
+    assert_eq!(selection, selected(&["a"]));
+}
+
+#[test]
+fn removed_containers_are_dropped_and_deselected() {
+    let mut containers = listed();
+    let mut selection = selected(&["a", "b"]);
+
+    let old = patch_containers(&mut containers, Some(&mut selection), "b", None);
+
+    assert_eq!(ids(&containers), vec!["c", "a"]);
+    assert_eq!(ids(&old), vec!["b"]);
+    assert_eq!(selection, selected(&["a"]));
+
+    // Refreshing a container that is already gone changes nothing
+    let old = patch_containers(&mut containers, None, "b", None);
+    assert!(old.is_empty());
+    assert_eq!(ids(&containers), vec!["c", "a"]);
+}
+
+#[test]
+fn recreated_containers_move_first_and_stay_selected() {
+    let mut containers = listed();
+    let mut selection = selected(&["a", "c"]);
+
+    // users was removed and created again under a new id, so it is now the newest
+    let recreated = container("db-mgr__users").id("e").state(RUNNING).build();
+    let old = patch_containers(&mut containers, Some(&mut selection), "e", Some(recreated));
+
+    assert_eq!(ids(&containers), vec!["e", "c", "b"]);
+    assert_eq!(ids(&old), vec!["a"]);
+    assert_eq!(selection, selected(&["e", "c"]));
+}
@@ -0,0 +1,659 @@
+//! Drives the create form through its `update` like the UI does, from picking an image to the
+//! config the container is created with.
+#![allow(dead_code)]
+
+#[path = "../src/app/add_container.rs"]
+mod add_container;
+#[path = "../src/backup.rs"]
+mod backup;
+#[path = "../src/app/cleanup.rs"]
+mod cleanup;
+mod common;
+#[path = "../src/daemon.rs"]
+mod daemon;
+#[path = "../src/data.rs"]
+mod data;
+#[path = "../src/definition.rs"]
+mod definition;
+#[path = "../src/diff.rs"]
+mod diff;
+#[path = "../src/docker.rs"]
+mod docker;
+#[path = "../src/env_file.rs"]
+mod env_file;
+#[path = "../src/idle.rs"]
+mod idle;
+#[path = "../src/image_ref.rs"]
+mod image_ref;
+#[path = "../src/names.rs"]
+mod names;
+#[path = "../src/app/notes.rs"]
+mod notes;
+#[path = "../src/platform.rs"]
+mod platform;
+#[path = "../src/ports.rs"]
+mod ports;
+#[path = "../src/app/pull_progress.rs"]
+mod pull_progress;
+#[path = "../src/registry.rs"]
+mod registry;
+#[path = "../src/template.rs"]
+mod template;
+#[path = "../src/tls.rs"]
+mod tls;
+#[path = "../src/validate.rs"]
+mod validate;
+#[path = "../src/versions.rs"]
+mod versions;
+
+use std::collections::HashMap;
+
+use add_container::{AddContainer, AddContainerState, ButtonState, Event};
+use common::postgres;
+use docker::{plan_container, DbContainerConfig, LogSettings, EPHEMERAL_LABEL, MANAGED_LABELS};
+use iced::widget::Component;
+use ports::PortAssignment;
+
+#[derive(Debug)]
+enum Message {
+    Add(Box<DbContainerConfig>),
+    Changed(Box<AddContainerState>),
+}
+
+fn form() -> AddContainer<Message> {
+    AddContainer::new(
+        vec![postgres()],
+        HashMap::new(),
+        AddContainerState::default(),
+        ButtonState::Ready,
+        |config| Message::Add(Box::new(config)),
+    )
+    .on_change(|state| Message::Changed(Box::new(state)))
+}
+
+/// Sends `events` to `form` in order, returning the message of the last one.
+fn send(form: &mut AddContainer<Message>, events: Vec<Event>) -> Option<Message> {
+    events
+        .into_iter()
+        .map(|event| form.update(&mut (), event))
+        .last()
+        .flatten()
+}
+
+fn set(key: &str, value: &str) -> Event {
+    Event::EnvVarChanged {
+        key: key.into(),
+        value: value.into(),
+    }
+}
+
+/// Picks Postgres, names the container and sets its password.
+fn filled_in() -> Vec<Event> {
+    vec![
+        Event::SelectContainer(Box::new(postgres())),
+        Event::SelectedTag("16".into()),
+        Event::NameChanged("orders db".into()),
+        set("POSTGRES_PASSWORD", "hunter2"),
+        set("POSTGRES_DB", "orders"),
+    ]
+}
+
+fn added(message: Option<Message>) -> DbContainerConfig {
+    match message {
+        Some(Message::Add(config)) => *config,
+        other => panic!("Expected the container to be added, got {other:?}"),
+    }
+}
+
+#[test]
+fn submitting_shows_the_review_before_adding() {
+    let mut form = form();
+    send(&mut form, filled_in());
+
+    assert!(matches!(
+        send(&mut form, vec![Event::SubmitPressed]),
+        Some(Message::Changed(_))
+    ));
+    let config = added(send(&mut form, vec![Event::ConfirmCreate]));
+
+    assert_eq!(config.name, "db-mgr__orders-db");
+    assert_eq!(config.database, "Postgres");
+    assert_eq!(config.image, "postgres");
+    assert_eq!(config.tag, "16");
+    assert_eq!(config.hostname.as_deref(), Some("orders-db"));
+    assert_eq!(
+        config
+            .variables
+            .get("POSTGRES_PASSWORD")
+            .map(String::as_str),
+        Some("hunter2")
+    );
+    assert_eq!(
+        config.variables.get("POSTGRES_DB").map(String::as_str),
+        Some("orders")
+    );
+    assert_eq!(
+        config.volumes,
+        HashMap::from([(
+            "db-mgr__orders-db__pgsql_data".to_string(),
+            "/var/lib/postgresql/data".to_string()
+        )])
+    );
+    assert!(config.start_after_create);
+    assert!(config.extra_variables.is_empty());
+}
+
+#[test]
+fn the_reviewed_config_is_the_one_added() {
+    let mut form = form();
+    send(&mut form, filled_in());
+
+    let reviewed = match send(&mut form, vec![Event::SubmitPressed]) {
+        Some(Message::Changed(state)) => state
+            .reviewed()
+            .cloned()
+            .expect("Submitting shows the review"),
+        other => panic!("Expected the review to be shown, got {other:?}"),
+    };
+    let added = added(send(&mut form, vec![Event::ConfirmCreate]));
+
+    assert_eq!(added, reviewed);
+    // Derived values are part of what was reviewed, not filled in afterwards
+    assert_eq!(reviewed.name, "db-mgr__orders-db");
+    assert_eq!(reviewed.hostname.as_deref(), Some("orders-db"));
+}
+
+#[test]
+fn going_back_from_the_review_adds_nothing() {
+    let mut form = form();
+    send(&mut form, filled_in());
+    send(&mut form, vec![Event::SubmitPressed, Event::BackToForm]);
+
+    assert!(matches!(
+        send(&mut form, vec![Event::ConfirmCreate]),
+        None | Some(Message::Changed(_))
+    ));
+}
+
+#[test]
+fn skipping_the_review_adds_right_away() {
+    let mut form = form().skip_review(true);
+    send(&mut form, filled_in());
+
+    let config = added(send(&mut form, vec![Event::SubmitPressed]));
+    assert_eq!(config.name, "db-mgr__orders-db");
+}
+
+#[test]
+fn cleared_values_are_left_out_and_extra_ones_recorded() {
+    let mut form = form().skip_review(true);
+    send(&mut form, filled_in());
+    send(
+        &mut form,
+        vec![
+            set("POSTGRES_DB", ""),
+            Event::AddVariable,
+            Event::NewVariableKeyChanged("TZ".into()),
+            Event::NewVariableValueChanged("Pacific/Auckland".into()),
+            Event::ConfirmVariable,
+        ],
+    );
+
+    let config = added(send(&mut form, vec![Event::SubmitPressed]));
+    assert!(!config.variables.contains_key("POSTGRES_DB"));
+    assert_eq!(
+        config.variables.get("TZ").map(String::as_str),
+        Some("Pacific/Auckland")
+    );
+    assert_eq!(config.extra_variables, vec!["TZ".to_string()]);
+}
+
+#[test]
+fn containers_without_volumes_need_the_data_loss_acknowledged() {
+    let mut database = postgres();
+    database.data_loss_warning = true;
+    let mut form = form();
+    send(
+        &mut form,
+        vec![
+            Event::SelectContainer(Box::new(database)),
+            Event::NameChanged("scratch".into()),
+            Event::Persist(false),
+            Event::SubmitPressed,
+        ],
+    );
+
+    assert!(send(&mut form, vec![Event::ConfirmCreate]).is_none());
+
+    send(&mut form, vec![Event::AcceptDataLoss(true)]);
+    let config = added(send(&mut form, vec![Event::ConfirmCreate]));
+    assert!(config.volumes.is_empty());
+}
+
+#[test]
+fn skipping_the_review_still_asks_about_data_loss() {
+    let mut database = postgres();
+    database.data_loss_warning = true;
+    let mut form = form().skip_review(true);
+    send(
+        &mut form,
+        vec![
+            Event::SelectContainer(Box::new(database)),
+            Event::NameChanged("scratch".into()),
+            Event::Persist(false),
+        ],
+    );
+
+    assert!(matches!(
+        send(&mut form, vec![Event::SubmitPressed]),
+        Some(Message::Changed(_))
+    ));
+    assert!(send(&mut form, vec![Event::ConfirmCreate]).is_none());
+    send(&mut form, vec![Event::AcceptDataLoss(true)]);
+    added(send(&mut form, vec![Event::ConfirmCreate]));
+}
+
+#[test]
+fn the_acknowledgement_is_asked_again_after_going_back() {
+    let mut form = form();
+    send(
+        &mut form,
+        vec![
+            Event::SelectContainer(Box::new(postgres())),
+            Event::NameChanged("scratch".into()),
+            Event::Persist(false),
+            Event::SubmitPressed,
+            Event::AcceptDataLoss(true),
+            Event::BackToForm,
+            Event::SubmitPressed,
+        ],
+    );
+
+    assert!(send(&mut form, vec![Event::ConfirmCreate]).is_none());
+}
+
+#[test]
+fn entries_without_the_warning_create_unpersisted_containers_right_away() {
+    let mut database = postgres();
+    database.data_loss_warning = false;
+    let mut form = form().skip_review(true);
+    send(
+        &mut form,
+        vec![
+            Event::SelectContainer(Box::new(database)),
+            Event::NameChanged("scratch".into()),
+            Event::Persist(false),
+        ],
+    );
+
+    let config = added(send(&mut form, vec![Event::SubmitPressed]));
+    assert!(config.volumes.is_empty());
+}
+
+#[test]
+fn persisted_containers_need_no_acknowledgement() {
+    let mut form = form();
+    send(&mut form, filled_in());
+    send(&mut form, vec![Event::SubmitPressed]);
+
+    added(send(&mut form, vec![Event::ConfirmCreate]));
+}
+
+#[test]
+fn only_unpersisted_containers_are_labelled_ephemeral() {
+    let labels = |config: &DbContainerConfig| {
+        plan_container(config, MANAGED_LABELS)
+            .into_iter()
+            .find(|op| op.call.starts_with("POST /containers/create"))
+            .and_then(|op| op.body)
+            .expect("The plan creates the container")["Labels"]
+            .clone()
+    };
+    let mut config = postgres().quick_config("db-mgr__scratch");
+
+    assert!(labels(&config).get(EPHEMERAL_LABEL).is_none());
+
+    config.volumes = HashMap::new();
+    assert_eq!(labels(&config)[EPHEMERAL_LABEL], "true");
+}
+
+#[test]
+fn env_files_fill_in_variables_by_key_and_alias() {
+    let database = postgres();
+    let mut state =
+        AddContainerState::imported(database.quick_config("orders"), database.clone(), vec![]);
+    state.import_env(env_file::parse_env_file(
+        "POSTGRES_DB=orders\nDATABASE_PASSWORD=\"from # env\"\nTZ=Pacific/Auckland\n",
+    ));
+    let mut form = AddContainer::new(
+        vec![database],
+        HashMap::new(),
+        state,
+        ButtonState::Ready,
+        |config| Message::Add(Box::new(config)),
+    )
+    .skip_review(true);
+    send(
+        &mut form,
+        vec![Event::NameChanged("orders".into()), Event::AddUnmatchedEnv],
+    );
+
+    let config = added(send(&mut form, vec![Event::SubmitPressed]));
+    assert_eq!(
+        config.variables.get("POSTGRES_DB").map(String::as_str),
+        Some("orders")
+    );
+    assert_eq!(
+        config
+            .variables
+            .get("POSTGRES_PASSWORD")
+            .map(String::as_str),
+        Some("from # env")
+    );
+    assert!(!config.variables.contains_key("DATABASE_PASSWORD"));
+    assert_eq!(config.extra_variables, vec!["TZ".to_string()]);
+}
+
+/// Adds the extra variable `key` set to `value`, returning the message confirming it.
+fn add_variable(form: &mut AddContainer<Message>, key: &str, value: &str) -> Option<Message> {
+    send(
+        form,
+        vec![
+            Event::AddVariable,
+            Event::NewVariableKeyChanged(key.into()),
+            Event::NewVariableValueChanged(value.into()),
+            Event::ConfirmVariable,
+        ],
+    )
+}
+
+#[test]
+fn extra_variables_can_be_added_edited_and_removed() {
+    let mut form = form().skip_review(true);
+    send(&mut form, filled_in());
+    assert!(add_variable(&mut form, "POSTGRES_INITDB_ARGS", "--data-checksums").is_some());
+    assert!(add_variable(&mut form, "TZ", "UTC").is_some());
+    send(
+        &mut form,
+        vec![
+            set("TZ", "Pacific/Auckland"),
+            set("POSTGRES_INITDB_ARGS", "--data-checksums --locale=C"),
+            Event::RemoveVariable("TZ".into()),
+        ],
+    );
+
+    let config = added(send(&mut form, vec![Event::SubmitPressed]));
+    assert!(!config.variables.contains_key("TZ"));
+    assert_eq!(
+        config
+            .variables
+            .get("POSTGRES_INITDB_ARGS")
+            .map(String::as_str),
+        Some("--data-checksums --locale=C")
+    );
+    assert_eq!(
+        config.extra_variables,
+        vec!["POSTGRES_INITDB_ARGS".to_string()]
+    );
+}
+
+#[test]
+fn duplicate_and_malformed_names_are_rejected() {
+    let mut form = form().skip_review(true);
+    send(&mut form, filled_in());
+    add_variable(&mut form, "TZ", "UTC");
+
+    for key in ["POSTGRES_PASSWORD", "TZ", "1ST", "WITH-DASH", ""] {
+        assert!(
+            add_variable(&mut form, key, "other").is_none(),
+            "{key} was accepted"
+        );
+        send(&mut form, vec![Event::CancelVariable]);
+    }
+
+    let config = added(send(&mut form, vec![Event::SubmitPressed]));
+    assert_eq!(
+        config
+            .variables
+            .get("POSTGRES_PASSWORD")
+            .map(String::as_str),
+        Some("hunter2")
+    );
+    assert_eq!(config.variables.get("TZ").map(String::as_str), Some("UTC"));
+    assert_eq!(config.extra_variables, vec!["TZ".to_string()]);
+}
+
+#[test]
+fn extra_variables_survive_reopening_the_form() {
+    let mut form = form();
+    send(&mut form, filled_in());
+    let state = match add_variable(&mut form, "TZ", "UTC") {
+        Some(Message::Changed(state)) => *state,
+        other => panic!("Expected the form to change, got {other:?}"),
+    };
+
+    let mut form = AddContainer::new(
+        vec![postgres()],
+        HashMap::new(),
+        state,
+        ButtonState::Ready,
+        |config| Message::Add(Box::new(config)),
+    )
+    .skip_review(true);
+    let config = added(send(&mut form, vec![Event::SubmitPressed]));
+    assert_eq!(config.variables.get("TZ").map(String::as_str), Some("UTC"));
+    assert_eq!(config.extra_variables, vec!["TZ".to_string()]);
+}
+
+#[test]
+fn gpus_can_be_given_all_or_picked() {
+    let mut database = postgres();
+    database.gpu_capable = true;
+    let mut form = AddContainer::new(
+        vec![database.clone()],
+        HashMap::new(),
+        AddContainerState::default(),
+        ButtonState::Ready,
+        |config| Message::Add(Box::new(config)),
+    )
+    .gpu_support(Some(true), vec!["GPU-0a1b".into(), "GPU-2c3d".into()])
+    .skip_review(true);
+    send(
+        &mut form,
+        vec![
+            Event::SelectContainer(Box::new(database)),
+            Event::NameChanged("vectors".into()),
+            Event::GpuToggled(true),
+        ],
+    );
+    assert_eq!(
+        added(send(&mut form, vec![Event::SubmitPressed])).gpus,
+        Some(vec![])
+    );
+
+    send(
+        &mut form,
+        vec![
+            Event::GpuDevice("GPU-2c3d".into(), true),
+            Event::GpuDevice("GPU-0a1b".into(), true),
+            Event::GpuDevice("GPU-2c3d".into(), false),
+        ],
+    );
+    assert_eq!(
+        added(send(&mut form, vec![Event::SubmitPressed])).gpus,
+        Some(vec!["GPU-0a1b".to_string()])
+    );
+
+    send(&mut form, vec![Event::GpuToggled(false)]);
+    assert_eq!(
+        added(send(&mut form, vec![Event::SubmitPressed])).gpus,
+        None
+    );
+}
+
+#[test]
+fn the_host_port_is_assigned_unless_one_is_typed() {
+    let mut left_empty = form().skip_review(true);
+    send(&mut left_empty, filled_in());
+    assert_eq!(
+        added(send(&mut left_empty, vec![Event::SubmitPressed])).host_port,
+        Some(PortAssignment::auto(5432))
+    );
+
+    let mut typed = form().skip_review(true);
+    send(&mut typed, filled_in());
+    send(&mut typed, vec![Event::HostPortChanged("54010".into())]);
+    assert_eq!(
+        added(send(&mut typed, vec![Event::SubmitPressed])).host_port,
+        Some(PortAssignment {
+            container: 5432,
+            host: Some(54010),
+        })
+    );
+
+    // Typing auto over a port assigns one again
+    send(&mut typed, vec![Event::HostPortChanged(" auto ".into())]);
+    assert_eq!(
+        added(send(&mut typed, vec![Event::SubmitPressed])).host_port,
+        Some(PortAssignment::auto(5432))
+    );
+}
+
+#[test]
+fn logs_are_rotated_unless_another_driver_is_picked() {
+    let mut form = form().skip_review(true);
+    send(&mut form, filled_in());
+    assert_eq!(
+        added(send(&mut form, vec![Event::SubmitPressed])).log,
+        LogSettings::default()
+    );
+
+    send(
+        &mut form,
+        vec![
+            Event::LogMaxSizeChanged("50m".into()),
+            Event::LogMaxFileChanged("lots".into()),
+        ],
+    );
+    let log = added(send(&mut form, vec![Event::SubmitPressed])).log;
+    assert_eq!(log.option("max-size"), Some("50m"));
+    // Counts that aren't numbers aren't taken
+    assert_eq!(log.option("max-file"), Some("3"));
+
+    send(&mut form, vec![Event::LogDriverSelected("journald")]);
+    let log = added(send(&mut form, vec![Event::SubmitPressed])).log;
+    assert_eq!(log.driver, "journald");
+    assert!(log.options.is_empty());
+
+    send(&mut form, vec![Event::LogDriverSelected("json-file")]);
+    assert_eq!(
+        added(send(&mut form, vec![Event::SubmitPressed])).log,
+        LogSettings::default()
+    );
+}
+
+/// The lines of the variables edited as text after sending `events`.
+fn env_text(form: &mut AddContainer<Message>, events: Vec<Event>) -> Option<Vec<String>> {
+    match send(form, events) {
+        Some(Message::Changed(state)) => state.env_text().map(<[String]>::to_vec),
+        other => panic!("Expected the form to change, got {other:?}"),
+    }
+}
+
+#[test]
+fn variables_are_edited_as_text_declared_ones_first() {
+    let mut form = form().skip_review(true);
+    send(&mut form, filled_in());
+    add_variable(&mut form, "APP_ENV", "dev");
+
+    assert_eq!(
+        env_text(&mut form, vec![Event::ToggleEnvText]),
+        Some(vec![
+            "POSTGRES_DB=orders".to_string(),
+            "POSTGRES_PASSWORD=hunter2".to_string(),
+            "POSTGRES_USERNAME=".to_string(),
+            "APP_ENV=dev".to_string(),
+        ])
+    );
+
+    // Going back without changes leaves the variables as they were
+    assert_eq!(env_text(&mut form, vec![Event::ToggleEnvText]), None);
+    let config = added(send(&mut form, vec![Event::SubmitPressed]));
+    assert_eq!(config.variables["POSTGRES_DB"], "orders");
+    assert_eq!(config.variables["APP_ENV"], "dev");
+    assert_eq!(config.extra_variables, vec!["APP_ENV".to_string()]);
+}
+
+#[test]
+fn variables_typed_as_text_replace_the_fields() {
+    let mut form = form().skip_review(true);
+    send(&mut form, filled_in());
+    send(&mut form, vec![Event::ToggleEnvText]);
+
+    // Pasting several lines over one splits them across lines
+    let lines = env_text(
+        &mut form,
+        vec![
+            Event::EnvLineChanged(2, "POSTGRES_USERNAME=app\r\n# extras\n\nTZ=UTC".into()),
+            Event::EnvLineRemoved(1),
+        ],
+    )
+    .expect("Still editing as text");
+    assert_eq!(
+        lines,
+        vec![
+            "POSTGRES_DB=orders",
+            "POSTGRES_USERNAME=app",
+            "# extras",
+            "",
+            "TZ=UTC"
+        ]
+    );
+
+    send(&mut form, vec![Event::ToggleEnvText]);
+    let config = added(send(&mut form, vec![Event::SubmitPressed]));
+    assert_eq!(
+        config.variables,
+        HashMap::from([
+            ("POSTGRES_DB".to_string(), "orders".to_string()),
+            ("POSTGRES_USERNAME".to_string(), "app".to_string()),
+            ("TZ".to_string(), "UTC".to_string()),
+        ])
+    );
+    assert_eq!(config.extra_variables, vec!["TZ".to_string()]);
+}
+
+#[test]
+fn text_with_errors_isnt_left_or_submitted() {
+    let mut form = form().skip_review(true);
+    send(&mut form, filled_in());
+    send(&mut form, vec![Event::ToggleEnvText]);
+    send(
+        &mut form,
+        vec![
+            Event::EnvLineAdded(2),
+            Event::EnvLineChanged(3, "POSTGRES_DB=again".into()),
+        ],
+    );
+
+    assert!(env_text(&mut form, vec![Event::ToggleEnvText]).is_some());
+    assert!(!matches!(
+        send(&mut form, vec![Event::SubmitPressed]),
+        Some(Message::Add(_))
+    ));
+
+    send(&mut form, vec![Event::EnvLineRemoved(3)]);
+    assert_eq!(env_text(&mut form, vec![Event::ToggleEnvText]), None);
+    assert_eq!(
+        added(send(&mut form, vec![Event::SubmitPressed])).variables["POSTGRES_DB"],
+        "orders"
+    );
+}
+
+#[test]
+fn nothing_is_added_before_an_image_is_picked() {
+    let mut form = form();
+
+    assert!(send(&mut form, vec![Event::SubmitPressed]).is_none());
+    assert!(send(&mut form, vec![Event::ConfirmCreate]).is_none());
+}
@@ -0,0 +1,241 @@
+//! Checks the docker calls creating a container makes, as a dry run records them through the
+//! create runtime, and what is sent to the form while they are made.
+#![allow(dead_code)]
+
+#[path = "../src/backup.rs"]
+mod backup;
+mod common;
+#[path = "../src/daemon.rs"]
+mod daemon;
+#[path = "../src/data.rs"]
+mod data;
+#[path = "../src/definition.rs"]
+mod definition;
+#[path = "../src/diff.rs"]
+mod diff;
+#[path = "../src/docker.rs"]
+mod docker;
+#[path = "../src/idle.rs"]
+mod idle;
+#[path = "../src/image_ref.rs"]
+mod image_ref;
+#[path = "../src/names.rs"]
+mod names;
+#[path = "../src/platform.rs"]
+mod platform;
+#[path = "../src/ports.rs"]
+mod ports;
+#[path = "../src/registry.rs"]
+mod registry;
+#[path = "../src/template.rs"]
+mod template;
+#[path = "../src/tls.rs"]
+mod tls;
+
+use std::collections::HashMap;
+
+use common::postgres;
+use data::is_secret;
+use docker::{
+    companion_network, create_with, plan_container, CreateContainerEvent, DbContainerConfig,
+    Dockerfile, ImageBuild, PlanRecorder, MANAGED_LABELS,
+};
+use futures::executor::block_on;
+
+fn orders() -> DbContainerConfig {
+    let mut config = postgres().quick_config("db-mgr__orders");
+    config.tag = "16".into();
+    config
+}
+
+fn calls(config: &DbContainerConfig) -> Vec<String> {
+    plan_container(config, MANAGED_LABELS)
+        .into_iter()
+        .map(|op| op.call)
+        .collect()
+}
+
+#[test]
+fn pulled_images_are_pulled_before_the_volumes_and_container_are_created() {
+    assert_eq!(
+        calls(&orders()),
+        vec![
+            "GET /containers/db-mgr__orders/json",
+            "GET /containers/json",
+            "POST /images/create?fromImage=postgres&tag=16",
+            "GET /images/postgres:16/json",
+            "GET /volumes/pgsql_data",
+            "POST /volumes/create",
+            "POST /containers/create?name=db-mgr__orders",
+            "POST /containers/db-mgr__orders/start",
+        ]
+    );
+}
+
+#[test]
+fn built_images_are_checked_and_built_instead_of_pulled() {
+    let mut config = orders();
+    config.build = Some(ImageBuild {
+        dockerfile: Dockerfile::Inline("FROM postgres:16\nRUN apt-get update".into()),
+        args: HashMap::new(),
+    });
+
+    let calls = calls(&config);
+
+    assert_eq!(
+        calls[2..4],
+        ["GET /images/postgres:16/json", "POST /build?t=postgres:16"]
+    );
+    assert!(!calls
+        .iter()
+        .any(|call| call.starts_with("POST /images/create")));
+}
+
+#[test]
+fn dockerfiles_that_cant_be_read_end_the_plan() {
+    let mut config = orders();
+    config.build = Some(ImageBuild {
+        dockerfile: Dockerfile::File {
+            path: "/nonexistent/db-mgr/Dockerfile".into(),
+        },
+        args: HashMap::new(),
+    });
+
+    assert_eq!(
+        calls(&config),
+        vec![
+            "GET /containers/db-mgr__orders/json",
+            "GET /containers/json"
+        ]
+    );
+}
+
+#[test]
+fn skipped_checks_and_starts_are_left_out() {
+    let mut config = orders();
+    config.force_shared_volume = true;
+    config.start_after_create = false;
+
+    let calls = calls(&config);
+
+    assert!(!calls.contains(&"GET /containers/json".to_string()));
+    assert_eq!(
+        calls.last().map(String::as_str),
+        Some("POST /containers/create?name=db-mgr__orders")
+    );
+}
+
+#[test]
+fn host_folders_are_bound_without_creating_a_volume() {
+    let mut config = orders();
+    config.volumes = HashMap::from([
+        ("/srv/orders".into(), "/var/lib/postgresql/data".into()),
+        ("orders_wal".into(), "/wal".into()),
+        ("orders_archive".into(), "/archive".into()),
+    ]);
+
+    let volume_calls = calls(&config)
+        .into_iter()
+        .filter(|call| call.contains("/volumes/"))
+        .collect::<Vec<_>>();
+
+    // In name order, so plans of the same config read the same
+    assert_eq!(
+        volume_calls,
+        vec![
+            "GET /volumes/orders_archive",
+            "POST /volumes/create",
+            "GET /volumes/orders_wal",
+            "POST /volumes/create",
+        ]
+    );
+}
+
+#[test]
+fn companions_join_the_network_of_their_parent() {
+    let mut config = orders();
+    config.name = "db-mgr__orders-adminer".into();
+    config.companion_of = Some("db-mgr__orders".into());
+    config.volumes.clear();
+    let network = companion_network("db-mgr__orders");
+
+    let plan = plan_container(&config, MANAGED_LABELS);
+    let calls = plan.iter().map(|op| op.call.as_str()).collect::<Vec<_>>();
+
+    assert_eq!(
+        calls[4..8],
+        [
+            format!("GET /networks/{network}").as_str(),
+            "POST /networks/create",
+            "GET /containers/db-mgr__orders/json",
+            format!("POST /networks/{network}/connect").as_str(),
+        ]
+    );
+    assert_eq!(plan[5].body.as_ref().unwrap()["Name"], network.as_str());
+    assert_eq!(
+        plan[7].body.as_ref().unwrap()["Container"],
+        "db-mgr__orders"
+    );
+}
+
+#[test]
+fn requests_are_recorded_with_their_bodies() {
+    let plan = plan_container(&orders(), MANAGED_LABELS);
+    let body = |call: &str| {
+        plan.iter()
+            .find(|op| op.call.starts_with(call))
+            .and_then(|op| op.body.clone())
+            .unwrap_or_else(|| panic!("{call} has no body"))
+    };
+
+    assert_eq!(body("POST /volumes/create")["Name"], "pgsql_data");
+    assert_eq!(body("POST /containers/create")["Image"], "postgres:16");
+    assert!(plan
+        .iter()
+        .filter(|op| op.call.starts_with("GET "))
+        .all(|op| op.body.is_none()));
+}
+
+#[test]
+fn secrets_in_the_recorded_bodies_can_be_masked() {
+    let mut config = orders();
+    config
+        .variables
+        .insert("POSTGRES_PASSWORD".into(), "hunter2".into());
+
+    let create = plan_container(&config, MANAGED_LABELS)
+        .into_iter()
+        .find(|op| op.call.starts_with("POST /containers/create"))
+        .expect("The plan creates the container")
+        .redacted(|key| is_secret(key, key));
+    let text = create.body_text().expect("The create request has a body");
+
+    assert!(text.contains("POSTGRES_PASSWORD=••••••"));
+    assert!(!text.contains("hunter2"));
+}
+
+#[test]
+fn the_recorder_answers_like_a_daemon_without_the_container() {
+    let recorder = PlanRecorder::default();
+    let mut events = vec![];
+
+    let result = block_on(create_with(
+        &recorder,
+        orders(),
+        MANAGED_LABELS,
+        &mut events,
+    ))
+    .expect("Nothing recorded can fail");
+
+    // The id of a recorded container is its name
+    assert!(matches!(result, CreateContainerEvent::Done(id) if id == "db-mgr__orders"));
+    // Nothing is pulled, so only the steps after it are sent
+    assert!(matches!(
+        events[..],
+        [
+            CreateContainerEvent::Building,
+            CreateContainerEvent::Starting
+        ]
+    ));
+    assert_eq!(recorder.into_plan().len(), 8);
+}
@@ -0,0 +1,172 @@
+//! Checks the record of how containers were created, kept so deleted ones can be recreated.
+#![allow(dead_code)]
+
+#[path = "../src/backup.rs"]
+mod backup;
+mod common;
+#[path = "../src/daemon.rs"]
+mod daemon;
+#[path = "../src/data.rs"]
+mod data;
+#[path = "../src/definition.rs"]
+mod definition;
+#[path = "../src/diff.rs"]
+mod diff;
+#[path = "../src/docker.rs"]
+mod docker;
+#[path = "../src/idle.rs"]
+mod idle;
+#[path = "../src/image_ref.rs"]
+mod image_ref;
+#[path = "../src/names.rs"]
+mod names;
+#[path = "../src/platform.rs"]
+mod platform;
+#[path = "../src/ports.rs"]
+mod ports;
+#[path = "../src/registry.rs"]
+mod registry;
+#[path = "../src/template.rs"]
+mod template;
+#[path = "../src/tls.rs"]
+mod tls;
+
+use std::collections::HashMap;
+
+use common::postgres;
+use data::{Preferences, CREATED_HISTORY};
+use docker::DbContainerConfig;
+
+fn config(name: &str, tag: &str) -> DbContainerConfig {
+    let mut config = postgres().quick_config(&format!("db-mgr__{name}"));
+    config.tag = tag.into();
+    config.variables = HashMap::from([
+        ("POSTGRES_PASSWORD".into(), "hunter2".into()),
+        ("POSTGRES_DB".into(), name.into()),
+    ]);
+    config
+}
+
+fn names(preferences: &Preferences, existing: &[&str]) -> Vec<String> {
+    let existing = existing
+        .iter()
+        .map(|name| name.to_string())
+        .collect::<Vec<_>>();
+    preferences
+        .deleted(&existing)
+        .into_iter()
+        .map(|created| created.name.clone())
+        .collect()
+}
+
+#[test]
+fn containers_are_recorded_without_the_prefix() {
+    let mut preferences = Preferences::default();
+    preferences.record_created(Some(&postgres()), &config("orders", "16"), 1_700_000_000);
+
+    let created = &preferences.created[0];
+    assert_eq!(created.name, "orders");
+    assert_eq!(created.created_at, 1_700_000_000);
+    assert_eq!(created.definition.tag, "16");
+    assert_eq!(created.definition.database.as_deref(), Some("Postgres"));
+}
+
+#[test]
+fn the_newest_container_of_a_name_wins() {
+    let mut preferences = Preferences::default();
+    preferences.record_created(Some(&postgres()), &config("orders", "15"), 1);
+    preferences.record_created(Some(&postgres()), &config("cache", "16"), 2);
+    preferences.record_created(Some(&postgres()), &config("orders", "16"), 3);
+
+    assert_eq!(preferences.created.len(), 2);
+    let orders = preferences
+        .deleted(&[])
+        .into_iter()
+        .find(|created| created.name == "orders");
+    assert_eq!(
+        orders.map(|created| created.definition.tag.as_str()),
+        Some("16")
+    );
+    assert_eq!(names(&preferences, &[]), vec!["orders", "cache"]);
+}
+
+#[test]
+fn only_the_last_definitions_are_kept() {
+    let mut preferences = Preferences::default();
+    for index in 0..CREATED_HISTORY + 5 {
+        preferences.record_created(
+            Some(&postgres()),
+            &config(&format!("db{index}"), "16"),
+            index as i64,
+        );
+    }
+
+    assert_eq!(preferences.created.len(), CREATED_HISTORY);
+    assert_eq!(preferences.created[0].name, "db5");
+    assert_eq!(
+        preferences
+            .created
+            .last()
+            .map(|created| created.name.as_str()),
+        Some(format!("db{}", CREATED_HISTORY + 4).as_str())
+    );
+}
+
+#[test]
+fn only_containers_that_no_longer_exist_are_listed_newest_first() {
+    let mut preferences = Preferences::default();
+    for (index, name) in ["orders", "cache", "billing"].into_iter().enumerate() {
+        preferences.record_created(Some(&postgres()), &config(name, "16"), index as i64);
+    }
+
+    assert_eq!(names(&preferences, &["cache"]), vec!["billing", "orders"]);
+    assert!(names(&preferences, &["orders", "cache", "billing"]).is_empty());
+}
+
+#[test]
+fn secrets_are_only_kept_when_passwords_are_remembered() {
+    let mut preferences = Preferences {
+        remember_passwords: false,
+        ..Preferences::default()
+    };
+    preferences.record_created(Some(&postgres()), &config("orders", "16"), 1);
+    let definition = &preferences.created[0].definition;
+    assert!(!definition.variables.contains_key("POSTGRES_PASSWORD"));
+    assert_eq!(definition.omitted, vec!["POSTGRES_PASSWORD".to_string()]);
+
+    preferences.remember_passwords = true;
+    preferences.record_created(Some(&postgres()), &config("orders", "16"), 2);
+    assert_eq!(
+        preferences.created[0].definition.variables["POSTGRES_PASSWORD"],
+        "hunter2"
+    );
+}
+
+#[test]
+fn recorded_definitions_survive_the_preferences_file() {
+    let mut preferences = Preferences::default();
+    preferences.record_created(Some(&postgres()), &config("orders", "16"), 1);
+
+    let yaml = serde_yaml::to_string(&preferences).expect("The preferences serialize");
+    let read = serde_yaml::from_str::<Preferences>(&yaml).expect("The preferences read back");
+    assert_eq!(read.created, preferences.created);
+}
+
+#[test]
+fn recorded_definitions_recreate_the_same_container() {
+    let mut preferences = Preferences {
+        remember_passwords: true,
+        ..Preferences::default()
+    };
+    let original = config("orders", "16");
+    preferences.record_created(Some(&postgres()), &original, 1);
+
+    let (recreated, database, warnings) = preferences.created[0]
+        .definition
+        .clone()
+        .into_config(&[postgres()]);
+    assert!(warnings.is_empty(), "{warnings:?}");
+    assert_eq!(database.name, "Postgres");
+    assert_eq!(recreated.tag, original.tag);
+    assert_eq!(recreated.variables, original.variables);
+}
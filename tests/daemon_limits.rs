@@ -0,0 +1,165 @@
+//! Checks that daemon calls give up after their timeout and that no more than the configured
+//! number of them run at once, using slow stand-ins for the calls.
+#![allow(dead_code)]
+
+#[path = "../src/daemon.rs"]
+mod daemon;
+
+use std::{
+    future::Future,
+    io,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex, Once,
+    },
+    time::Duration,
+};
+
+use daemon::{configure, with_timeout, DaemonLimits, TimedOut};
+use futures::future::join_all;
+
+const MAX_CONCURRENT: usize = 2;
+
+/// The limits are shared by the whole process, so the tests take turns.
+static SERIAL: Mutex<()> = Mutex::new(());
+
+fn run<T>(test: impl Future<Output = T>) -> T {
+    static CONFIGURE: Once = Once::new();
+    CONFIGURE.call_once(|| {
+        configure(DaemonLimits {
+            max_concurrent: MAX_CONCURRENT,
+            ..DaemonLimits::default()
+        })
+    });
+
+    let _serial = SERIAL
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    tokio::runtime::Runtime::new()
+        .expect("A runtime starts")
+        .block_on(test)
+}
+
+async fn slow(after: Duration) -> Result<&'static str, io::Error> {
+    tokio::time::sleep(after).await;
+    Ok("done")
+}
+
+#[test]
+fn slow_calls_time_out() {
+    let result = run(with_timeout(
+        "listing containers",
+        Some(Duration::from_millis(50)),
+        slow(Duration::from_secs(5)),
+    ));
+
+    let ex = result.expect_err("The call takes longer than its timeout");
+    assert_eq!(
+        ex.downcast_ref::<TimedOut>(),
+        Some(&TimedOut {
+            operation: "listing containers",
+            after: Duration::from_millis(50),
+        })
+    );
+}
+
+#[test]
+fn calls_within_their_timeout_finish() {
+    let result = run(with_timeout(
+        "listing containers",
+        Some(Duration::from_secs(5)),
+        slow(Duration::from_millis(10)),
+    ));
+
+    assert_eq!(result.ok(), Some("done"));
+}
+
+#[test]
+fn calls_without_a_timeout_wait_as_long_as_they_take() {
+    let result = run(with_timeout(
+        "pulling postgres:16",
+        None,
+        slow(Duration::from_millis(200)),
+    ));
+
+    assert_eq!(result.ok(), Some("done"));
+}
+
+#[test]
+fn errors_of_the_call_are_kept() {
+    let result = run(with_timeout(
+        "starting db-mgr__orders",
+        Some(Duration::from_secs(5)),
+        async { Err::<(), _>(io::Error::other("No such container")) },
+    ));
+
+    let ex = result.expect_err("The call failed");
+    assert!(ex.downcast_ref::<TimedOut>().is_none());
+    assert_eq!(ex.to_string(), "No such container");
+}
+
+#[test]
+fn at_most_the_configured_calls_run_at_once() {
+    let in_flight = AtomicUsize::new(0);
+    let most = AtomicUsize::new(0);
+    let call = || async {
+        let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+        most.fetch_max(now, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        in_flight.fetch_sub(1, Ordering::SeqCst);
+        Ok::<_, io::Error>(())
+    };
+
+    let results =
+        run(join_all((0..8).map(|_| {
+            with_timeout("inspecting a container", None, call())
+        })));
+
+    assert!(results.iter().all(Result::is_ok));
+    assert_eq!(most.load(Ordering::SeqCst), MAX_CONCURRENT);
+}
+
+#[test]
+fn waiting_for_a_free_call_counts_towards_the_timeout() {
+    let results = run(async {
+        let busy = (0..MAX_CONCURRENT).map(|_| {
+            with_timeout(
+                "stopping a container",
+                None,
+                slow(Duration::from_millis(300)),
+            )
+        });
+        let waiting = with_timeout(
+            "listing containers",
+            Some(Duration::from_millis(50)),
+            slow(Duration::ZERO),
+        );
+        futures::join!(join_all(busy), waiting)
+    });
+
+    assert!(results.0.iter().all(Result::is_ok));
+    let ex = results.1.expect_err("No call was free before the timeout");
+    assert!(ex.downcast_ref::<TimedOut>().is_some());
+}
+
+#[test]
+fn timed_out_calls_free_their_place() {
+    run(async {
+        let timed_out = (0..MAX_CONCURRENT).map(|_| {
+            with_timeout(
+                "inspecting a container",
+                Some(Duration::from_millis(20)),
+                slow(Duration::from_secs(5)),
+            )
+        });
+        assert!(join_all(timed_out).await.iter().all(Result::is_err));
+
+        let next = with_timeout(
+            "listing containers",
+            Some(Duration::from_millis(500)),
+            slow(Duration::ZERO),
+        )
+        .await;
+        assert_eq!(next.ok(), Some("done"));
+    });
+}
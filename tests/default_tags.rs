@@ -0,0 +1,81 @@
+//! Checks the tag a new container of an entry starts with, and that `latest` is only a fallback.
+#![allow(dead_code)]
+
+#[path = "../src/backup.rs"]
+mod backup;
+#[path = "../src/daemon.rs"]
+mod daemon;
+#[path = "../src/data.rs"]
+mod data;
+#[path = "../src/definition.rs"]
+mod definition;
+#[path = "../src/diff.rs"]
+mod diff;
+#[path = "../src/docker.rs"]
+mod docker;
+#[path = "../src/idle.rs"]
+mod idle;
+#[path = "../src/image_ref.rs"]
+mod image_ref;
+#[path = "../src/names.rs"]
+mod names;
+#[path = "../src/platform.rs"]
+mod platform;
+#[path = "../src/ports.rs"]
+mod ports;
+#[path = "../src/registry.rs"]
+mod registry;
+#[path = "../src/template.rs"]
+mod template;
+#[path = "../src/tls.rs"]
+mod tls;
+
+use data::DatabaseConfig;
+
+fn entry(tags: &str) -> DatabaseConfig {
+    serde_yaml::from_str(&format!(
+        "
+name: Postgres
+image: postgres
+icon_url: ''
+tags: {tags}
+variables: {{}}
+volumes: {{}}
+"
+    ))
+    .expect("The entry must parse")
+}
+
+#[test]
+fn the_first_configured_tag_is_the_default() {
+    let postgres = entry("['16', '15', latest]");
+    assert_eq!(postgres.default_tag(false), "16");
+    assert_eq!(postgres.default_tag(true), "16");
+}
+
+#[test]
+fn latest_is_kept_when_it_is_configured_first() {
+    // Listing it is a deliberate choice, so it isn't dropped even when never defaulting to it
+    let postgres = entry("[latest, '16']");
+    assert_eq!(postgres.default_tag(false), "latest");
+    assert_eq!(postgres.default_tag(true), "latest");
+}
+
+#[test]
+fn entries_without_tags_fall_back_to_latest() {
+    assert_eq!(entry("[]").default_tag(false), "latest");
+}
+
+#[test]
+fn entries_without_tags_start_empty_when_never_defaulting_to_latest() {
+    assert_eq!(entry("[]").default_tag(true), "");
+}
+
+#[test]
+fn quick_configs_use_the_default_tag() {
+    assert_eq!(
+        entry("['16-alpine']").quick_config("orders").tag,
+        "16-alpine"
+    );
+    assert_eq!(entry("[]").quick_config("orders").tag, "latest");
+}
@@ -0,0 +1,169 @@
+//! Checks the container definition files shared between machines: that they read back as they
+//! were written, what is left out of them and how one is matched to the config on import.
+#![allow(dead_code)]
+
+#[path = "../src/backup.rs"]
+mod backup;
+mod common;
+#[path = "../src/daemon.rs"]
+mod daemon;
+#[path = "../src/data.rs"]
+mod data;
+#[path = "../src/definition.rs"]
+mod definition;
+#[path = "../src/diff.rs"]
+mod diff;
+#[path = "../src/docker.rs"]
+mod docker;
+#[path = "../src/idle.rs"]
+mod idle;
+#[path = "../src/image_ref.rs"]
+mod image_ref;
+#[path = "../src/names.rs"]
+mod names;
+#[path = "../src/platform.rs"]
+mod platform;
+#[path = "../src/ports.rs"]
+mod ports;
+#[path = "../src/registry.rs"]
+mod registry;
+#[path = "../src/template.rs"]
+mod template;
+#[path = "../src/tls.rs"]
+mod tls;
+
+use std::collections::HashMap;
+
+use common::{databases, postgres};
+use definition::{ContainerDefinition, DEFINITION_VERSION};
+use docker::DbContainerConfig;
+
+fn config() -> DbContainerConfig {
+    let mut config = postgres().quick_config("orders");
+    config.variables = HashMap::from([
+        ("POSTGRES_USERNAME".into(), "postgres".into()),
+        ("POSTGRES_PASSWORD".into(), "hunter2".into()),
+        ("POSTGRES_DB".into(), "orders".into()),
+    ]);
+    config.tag = "16".into();
+    config.tmpfs = vec!["/tmp/scratch".into()];
+    config.tmpfs_size = Some(64 << 20);
+    config.extra_hosts = vec!["billing:10.0.0.4".into()];
+    config
+}
+
+#[test]
+fn definitions_read_back_as_they_were_written() {
+    let definition = ContainerDefinition::from_config(&config(), Some(&postgres()), true);
+    let yaml = definition.to_yaml().expect("A definition serializes");
+
+    assert_eq!(
+        ContainerDefinition::from_yaml(&yaml).expect("A written definition reads back"),
+        definition
+    );
+}
+
+#[test]
+fn definitions_are_written_with_the_current_version() {
+    let definition = ContainerDefinition::from_config(&config(), Some(&postgres()), true);
+    let yaml = definition.to_yaml().expect("A definition serializes");
+
+    assert_eq!(definition.version, DEFINITION_VERSION);
+    assert!(yaml.contains(&format!("version: {DEFINITION_VERSION}")));
+}
+
+#[test]
+fn definitions_from_a_newer_version_are_refused() {
+    let definition = ContainerDefinition::from_config(&config(), Some(&postgres()), true);
+    let yaml = ContainerDefinition {
+        version: DEFINITION_VERSION + 1,
+        ..definition
+    }
+    .to_yaml()
+    .expect("A definition serializes");
+
+    let error = ContainerDefinition::from_yaml(&yaml).expect_err("Newer versions are refused");
+    assert!(error.to_string().contains("update db-mgr"));
+}
+
+#[test]
+fn other_files_are_not_definitions() {
+    assert!(ContainerDefinition::from_yaml("image: postgres\ntag: '16'\n").is_err());
+    assert!(ContainerDefinition::from_yaml("databases: []\n").is_err());
+}
+
+#[test]
+fn secrets_are_only_kept_when_asked_for() {
+    let without = ContainerDefinition::from_config(&config(), Some(&postgres()), false);
+    assert!(!without.variables.contains_key("POSTGRES_PASSWORD"));
+    assert_eq!(without.omitted, vec!["POSTGRES_PASSWORD".to_string()]);
+    assert_eq!(without.variables["POSTGRES_DB"], "orders");
+
+    let with = ContainerDefinition::from_config(&config(), Some(&postgres()), true);
+    assert_eq!(with.variables["POSTGRES_PASSWORD"], "hunter2");
+    assert!(with.omitted.is_empty());
+}
+
+#[test]
+fn volumes_are_kept_without_the_container_prefix() {
+    let mut config = config();
+    config.volumes = HashMap::from([(
+        "orders__pgsql_data".into(),
+        "/var/lib/postgresql/data".into(),
+    )]);
+    config.read_only_volumes = vec!["orders__pgsql_data".into()];
+
+    let definition = ContainerDefinition::from_config(&config, Some(&postgres()), true);
+    assert_eq!(
+        definition.volumes.get("pgsql_data").map(String::as_str),
+        Some("/var/lib/postgresql/data")
+    );
+    assert_eq!(definition.read_only_volumes, vec!["pgsql_data".to_string()]);
+}
+
+#[test]
+fn imported_definitions_match_their_config_entry() {
+    let definition = ContainerDefinition::from_config(&config(), Some(&postgres()), true);
+    let (imported, database, warnings) = definition.into_config(&databases());
+
+    assert_eq!(database.name, "Postgres");
+    assert!(warnings.is_empty(), "{warnings:?}");
+    assert_eq!(imported.name, "");
+    assert_eq!(imported.database, "Postgres");
+    assert_eq!(imported.tag, "16");
+    assert_eq!(imported.variables, config().variables);
+    assert_eq!(imported.tmpfs, config().tmpfs);
+    assert_eq!(imported.tmpfs_size, config().tmpfs_size);
+    assert_eq!(imported.extra_hosts, config().extra_hosts);
+    assert!(imported.extra_variables.is_empty());
+}
+
+#[test]
+fn omitted_values_and_unknown_tags_are_warned_about() {
+    let mut definition = ContainerDefinition::from_config(&config(), Some(&postgres()), false);
+    definition.tag = "9.6".into();
+    let (_, _, warnings) = definition.into_config(&databases());
+
+    assert_eq!(warnings.len(), 2, "{warnings:?}");
+    assert!(warnings[0].contains("9.6 is not one of the tags"));
+    assert!(warnings[1].contains("Fill in POSTGRES_PASSWORD"));
+}
+
+#[test]
+fn unknown_images_are_imported_as_they_are() {
+    let definition = ContainerDefinition::from_yaml(
+        "version: 1\nimage: example/graphdb\ntag: '2'\nvariables:\n  GRAPH_MODE: single\nomitted: [GRAPH_TOKEN]\n",
+    )
+    .expect("A minimal definition reads");
+    let (imported, database, warnings) = definition.into_config(&databases());
+
+    assert!(warnings[0].contains("example/graphdb is not in config.yaml"));
+    assert_eq!(database.image, "example/graphdb");
+    assert!(database.declares("GRAPH_MODE"));
+    assert!(database.declares("GRAPH_TOKEN"));
+    assert_eq!(imported.tag, "2");
+    assert_eq!(
+        imported.variables.get("GRAPH_MODE").map(String::as_str),
+        Some("single")
+    );
+}
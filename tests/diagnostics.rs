@@ -0,0 +1,126 @@
+//! Checks the diagnostics copied from the about view: that partial engine info still shows up and
+//! that nothing secret from the config makes it in.
+//! environment, without making a request.
+#![allow(dead_code)]
+
+#[path = "../src/app/about.rs"]
+mod about;
+#[path = "../src/backup.rs"]
+mod backup;
+#[path = "../src/app/cleanup.rs"]
+mod cleanup;
+mod common;
+#[path = "../src/daemon.rs"]
+mod daemon;
+#[path = "../src/data.rs"]
+mod data;
+#[path = "../src/definition.rs"]
+mod definition;
+#[path = "../src/diff.rs"]
+mod diff;
+#[path = "../src/docker.rs"]
+mod docker;
+#[path = "../src/idle.rs"]
+mod idle;
+#[path = "../src/image_ref.rs"]
+mod image_ref;
+#[path = "../src/names.rs"]
+mod names;
+#[path = "../src/platform.rs"]
+mod platform;
+#[path = "../src/ports.rs"]
+mod ports;
+#[path = "../src/registry.rs"]
+mod registry;
+#[path = "../src/app/state_badge.rs"]
+mod state_badge;
+#[path = "../src/template.rs"]
+mod template;
+#[path = "../src/tls.rs"]
+mod tls;
+
+use std::collections::HashMap;
+
+use about::diagnostics;
+use common::postgres;
+use data::DatabaseConfig;
+use docker::EngineInfo;
+
+/// The bundled Postgres entry, with secrets in its defaults and connection string.
+fn with_secrets() -> DatabaseConfig {
+    let mut database = postgres();
+    database.tags = vec!["16".into()];
+    database.defaults = HashMap::from([
+        ("POSTGRES_PASSWORD".into(), "hunter2".into()),
+        ("POSTGRES_USER".into(), "admin-secret".into()),
+    ]);
+    database.connection_string =
+        Some("postgres://admin-secret:{POSTGRES_PASSWORD}@{HOST}:{PORT}".into());
+    database
+}
+
+fn engine() -> EngineInfo {
+    EngineInfo {
+        version: Some("24.0.7".into()),
+        api_version: Some("1.43".into()),
+        client_api_version: "1.43".into(),
+        os: Some("Ubuntu 22.04".into()),
+        arch: Some("x86_64".into()),
+        kernel: Some("6.5.0".into()),
+        storage_driver: Some("overlay2".into()),
+        cpus: Some(8),
+        memory: Some(16 << 30),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn secrets_from_the_config_are_left_out() {
+    let out = diagnostics(Some(&engine()), &[with_secrets()], &[]);
+
+    assert!(out.contains("- Postgres (postgres:16)\n"), "{out}");
+    for secret in ["hunter2", "admin-secret", "POSTGRES_PASSWORD", "pgsql_data"] {
+        assert!(!out.contains(secret), "{secret} leaked into\n{out}");
+    }
+}
+
+#[test]
+fn engine_fields_are_listed() {
+    let out = diagnostics(Some(&engine()), &[], &[]);
+
+    assert!(out.contains(&format!(
+        "- db-mgr version: {}\n",
+        env!("CARGO_PKG_VERSION")
+    )));
+    assert!(out.contains("- Engine version: 24.0.7\n"));
+    assert!(out.contains("- Storage driver: overlay2\n"));
+    assert!(out.contains("- Memory: 16.0 GB\n"));
+    assert!(out.contains("- No databases configured\n"));
+}
+
+#[test]
+fn partial_engine_info_is_shown_with_the_errors() {
+    let engine = EngineInfo {
+        version: Some("24.0.7".into()),
+        client_api_version: "1.43".into(),
+        errors: vec!["Could not get info: connection reset".into()],
+        ..Default::default()
+    };
+    let out = diagnostics(Some(&engine), &[], &[]);
+
+    assert!(out.contains("- Engine version: 24.0.7\n"));
+    assert!(out.contains("- Storage driver: unknown\n"));
+    assert!(out.contains("- Error: Could not get info: connection reset\n"));
+}
+
+#[test]
+fn missing_engine_info_is_noted() {
+    let out = diagnostics(
+        None,
+        &[],
+        &[("postgres".into(), "Could not fetch icon".into())],
+    );
+
+    assert!(out.contains("### Docker engine\n\n- Not loaded\n"));
+    assert!(out.contains("### Icons\n\n- postgres: Could not fetch icon\n"));
+}
@@ -0,0 +1,205 @@
+//! Smoke tests against the local docker daemon, run with `cargo test --features docker-tests`.
+//!
+//! Everything created here is labelled with [`TEST_LABELS`] rather than the labels the app uses,
+//! so the tests never see or touch containers managed by a real db-mgr install.
+#![cfg(feature = "docker-tests")]
+#![allow(dead_code)]
+
+#[path = "../src/daemon.rs"]
+mod daemon;
+#[path = "../src/docker.rs"]
+mod docker;
+#[path = "../src/image_ref.rs"]
+mod image_ref;
+#[path = "../src/platform.rs"]
+mod platform;
+#[path = "../src/ports.rs"]
+mod ports;
+
+use std::{
+    collections::HashMap,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bollard::{
+    container::RemoveContainerOptions, service::ContainerStateStatusEnum,
+    volume::RemoveVolumeOptions, Docker,
+};
+use docker::{
+    create_labelled_container, get_containers, get_labelled_containers, start_container,
+    stop_container, update_container_limits, CreateContainerEvent, DbContainer, DbContainerConfig,
+    EnvValue, Labels, LimitChanges, LogSettings, Restart,
+};
+use futures::{channel::mpsc::Receiver, StreamExt};
+use tokio::runtime::Runtime;
+
+const TEST_LABELS: Labels = Labels {
+    container: "test-container",
+    volume: "test-volume",
+};
+
+/// Removes the test container and volume when dropped, even if an assertion failed.
+struct Guard<'a> {
+    runtime: &'a Runtime,
+    docker: &'static Docker,
+    container: String,
+    volume: String,
+}
+
+impl Drop for Guard<'_> {
+    fn drop(&mut self) {
+        self.runtime.block_on(async {
+            if let Err(ex) = self
+                .docker
+                .remove_container(
+                    &self.container,
+                    Some(RemoveContainerOptions {
+                        force: true,
+                        ..Default::default()
+                    }),
+                )
+                .await
+            {
+                eprintln!("Could not remove test container {}: {ex}", self.container);
+            }
+
+            if let Err(ex) = self
+                .docker
+                .remove_volume(&self.volume, Some(RemoveVolumeOptions { force: true }))
+                .await
+            {
+                eprintln!("Could not remove test volume {}: {ex}", self.volume);
+            }
+        });
+    }
+}
+
+fn connect() -> &'static Docker {
+    Box::leak(Box::new(
+        Docker::connect_with_local_defaults().expect("Could not connect to docker"),
+    ))
+}
+
+fn unique_name() -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_nanos();
+    format!("test-{}-{nanos}", std::process::id())
+}
+
+async fn collect_events(rx: Receiver<CreateContainerEvent>) -> Vec<CreateContainerEvent> {
+    rx.collect().await
+}
+
+async fn find(docker: &Docker, name: &str) -> Option<DbContainer> {
+    get_labelled_containers(docker, TEST_LABELS)
+        .await
+        .expect("Could not list containers")
+        .into_iter()
+        .find(|container| container.name.trim_start_matches('/') == name)
+}
+
+#[test]
+fn create_start_and_stop_container() {
+    let runtime = Runtime::new().unwrap();
+    let docker = connect();
+    let name = format!("db-mgr__{}", unique_name());
+    let volume = format!("{name}__data");
+
+    let _guard = Guard {
+        runtime: &runtime,
+        docker,
+        container: name.clone(),
+        volume: volume.clone(),
+    };
+
+    runtime.block_on(async {
+        let config = DbContainerConfig {
+            name: name.clone(),
+            database: "Alpine".into(),
+            variables: HashMap::from([("DB_MGR_TEST".into(), "some value".into())]),
+            image: "alpine".into(),
+            volumes: HashMap::from([(volume.clone(), "/data".into())]),
+            tag: "latest".into(),
+            command: vec!["sleep".into(), "300".into()],
+            reuse_volumes: false,
+            force_shared_volume: false,
+            start_after_create: true,
+            dry_run: false,
+            shm_size_mb: None,
+            cpuset_cpus: None,
+            tmpfs: vec![],
+            tmpfs_size: None,
+            hostname: None,
+            extra_hosts: vec![],
+            user: None,
+            build: None,
+            depends_on: vec![],
+            extra_variables: vec![],
+            read_only_volumes: vec![],
+            volume_options: HashMap::new(),
+            ulimits: vec![],
+            sysctls: HashMap::new(),
+            companion_of: None,
+            published_ports: vec![],
+            host_port: None,
+            gpus: None,
+            log: LogSettings::default(),
+            project: None,
+        };
+
+        let events = collect_events(create_labelled_container(docker, config, TEST_LABELS)).await;
+        match events.last() {
+            Some(CreateContainerEvent::Done(_)) => {}
+            other => panic!("Container was not created: {other:?}"),
+        }
+
+        let container = find(docker, &name).await.expect("Container was not listed");
+        assert_eq!(container.state, ContainerStateStatusEnum::RUNNING);
+        assert_eq!(
+            container
+                .variables
+                .get("DB_MGR_TEST")
+                .and_then(EnvValue::as_text),
+            Some("some value")
+        );
+        assert_eq!(
+            container.volumes.get(&volume).map(String::as_str),
+            Some("/data")
+        );
+        assert_eq!(container.command, vec!["sleep", "300"]);
+
+        assert!(
+            get_containers(docker)
+                .await
+                .unwrap()
+                .iter()
+                .all(|managed| managed.id != container.id),
+            "Test containers must not show up as managed containers"
+        );
+
+        stop_container(container.id.clone(), None, docker)
+            .await
+            .unwrap();
+        let stopped = find(docker, &name).await.unwrap();
+        assert_eq!(stopped.state, ContainerStateStatusEnum::EXITED);
+
+        start_container(container.id.clone(), docker).await.unwrap();
+        let started = find(docker, &name).await.unwrap();
+        assert_eq!(started.state, ContainerStateStatusEnum::RUNNING);
+
+        let changes = LimitChanges {
+            memory: Some(256 * 1024 * 1024),
+            nano_cpus: None,
+            restart: Some(Restart::UnlessStopped),
+        };
+        update_container_limits(container.id.clone(), changes, docker)
+            .await
+            .unwrap();
+        let limited = find(docker, &name).await.unwrap();
+        assert_eq!(limited.state, ContainerStateStatusEnum::RUNNING);
+        assert_eq!(limited.limits.memory, Some(256 * 1024 * 1024));
+        assert_eq!(limited.limits.restart, Restart::UnlessStopped);
+    });
+}
@@ -0,0 +1,115 @@
+//! Checks that a saved create form comes back as it was left, including when its config entry
+//! was removed from config.yaml since.
+#![allow(dead_code)]
+
+#[path = "../src/backup.rs"]
+mod backup;
+mod common;
+#[path = "../src/daemon.rs"]
+mod daemon;
+#[path = "../src/data.rs"]
+mod data;
+#[path = "../src/definition.rs"]
+mod definition;
+#[path = "../src/diff.rs"]
+mod diff;
+#[path = "../src/docker.rs"]
+mod docker;
+#[path = "../src/idle.rs"]
+mod idle;
+#[path = "../src/image_ref.rs"]
+mod image_ref;
+#[path = "../src/names.rs"]
+mod names;
+#[path = "../src/platform.rs"]
+mod platform;
+#[path = "../src/ports.rs"]
+mod ports;
+#[path = "../src/registry.rs"]
+mod registry;
+#[path = "../src/template.rs"]
+mod template;
+#[path = "../src/tls.rs"]
+mod tls;
+
+use common::{databases, postgres};
+use data::Draft;
+use docker::DbContainerConfig;
+
+fn filled_in_form() -> DbContainerConfig {
+    let mut config = postgres().quick_config("myapp-db");
+    config
+        .variables
+        .insert("POSTGRES_PASSWORD".into(), "hunter2".into());
+    config
+        .variables
+        .insert("POSTGRES_DB".into(), "myapp".into());
+    config.variables.insert("PGDATA_EXTRA".into(), "on".into());
+    config.extra_variables.push("PGDATA_EXTRA".into());
+    config
+}
+
+/// The draft as it is read back from the draft file.
+fn saved(draft: &Draft) -> Draft {
+    serde_yaml::from_str(&serde_yaml::to_string(draft).unwrap()).unwrap()
+}
+
+#[test]
+fn draft_restores_the_form() {
+    let form = filled_in_form();
+    let draft = saved(&Draft::new(&form, &postgres(), true, true));
+    assert_eq!(draft.describe(), "Postgres 'myapp-db'");
+
+    let (config, database, warnings) = draft.restore(&databases());
+    assert!(warnings.is_empty(), "{warnings:?}");
+    assert_eq!(database.name, "Postgres");
+    assert_eq!(config.name, "myapp-db");
+    assert_eq!(config.tag, form.tag);
+    assert_eq!(config.variables, form.variables);
+    assert_eq!(config.extra_variables, vec!["PGDATA_EXTRA".to_string()]);
+    assert_eq!(config.volumes, form.volumes);
+}
+
+#[test]
+fn draft_leaves_out_secrets_unless_kept() {
+    let draft = saved(&Draft::new(&filled_in_form(), &postgres(), true, false));
+    assert_eq!(
+        draft.definition.omitted,
+        vec!["POSTGRES_PASSWORD".to_string()]
+    );
+
+    let (config, _, warnings) = draft.restore(&databases());
+    assert!(!config.variables.contains_key("POSTGRES_PASSWORD"));
+    assert!(warnings
+        .iter()
+        .any(|warning| warning.contains("POSTGRES_PASSWORD")));
+}
+
+#[test]
+fn draft_of_a_removed_entry_is_restored_with_a_warning() {
+    let draft = saved(&Draft::new(&filled_in_form(), &postgres(), true, true));
+    let without_postgres = databases()
+        .into_iter()
+        .filter(|database| database.image != "postgres")
+        .collect::<Vec<_>>();
+
+    let (config, database, warnings) = draft.restore(&without_postgres);
+    assert!(warnings
+        .iter()
+        .any(|warning| warning.contains("not in config.yaml")));
+    assert_eq!(database.name, "Postgres");
+    assert_eq!(config.name, "myapp-db");
+    assert_eq!(
+        config.variables.get("POSTGRES_DB").map(String::as_str),
+        Some("myapp")
+    );
+    // Every variable is shown, the made up entry declares them all
+    assert!(database.declares("PGDATA_EXTRA"));
+}
+
+#[test]
+fn draft_without_persistence_has_no_volumes() {
+    let draft = saved(&Draft::new(&filled_in_form(), &postgres(), false, true));
+    let (config, _, _) = draft.restore(&databases());
+    assert!(config.volumes.is_empty());
+}
@@ -0,0 +1,192 @@
+//! Checks the end of life warnings on a fixed day, and the end of life dates of the bundled
+//! databases.
+#![allow(dead_code)]
+
+#[path = "../src/backup.rs"]
+mod backup;
+mod common;
+#[path = "../src/daemon.rs"]
+mod daemon;
+#[path = "../src/data.rs"]
+mod data;
+#[path = "../src/definition.rs"]
+mod definition;
+#[path = "../src/diff.rs"]
+mod diff;
+#[path = "../src/docker.rs"]
+mod docker;
+#[path = "../src/idle.rs"]
+mod idle;
+#[path = "../src/image_ref.rs"]
+mod image_ref;
+#[path = "../src/names.rs"]
+mod names;
+#[path = "../src/platform.rs"]
+mod platform;
+#[path = "../src/ports.rs"]
+mod ports;
+#[path = "../src/registry.rs"]
+mod registry;
+#[path = "../src/template.rs"]
+mod template;
+#[path = "../src/tls.rs"]
+mod tls;
+#[path = "../src/versions.rs"]
+mod versions;
+
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+use common::bundled;
+use versions::{eol_status, EolStatus, EOL_WARNING_DAYS};
+
+fn day(year: i32, month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day).expect("The day must exist")
+}
+
+/// Postgres 16 ends on 9 November 2028.
+fn dates() -> HashMap<String, NaiveDate> {
+    HashMap::from([("16".to_string(), day(2028, 11, 9))])
+}
+
+fn status(tag: &str, today: NaiveDate) -> Option<EolStatus> {
+    eol_status(Some("{MAJOR}*"), tag, &dates(), today)
+}
+
+#[test]
+fn versions_far_from_their_end_are_not_warned_about() {
+    assert_eq!(status("16.2", day(2026, 10, 17)), None);
+    // A day before the warning starts
+    assert_eq!(
+        status(
+            "16.2",
+            day(2028, 11, 9) - chrono::Duration::days(EOL_WARNING_DAYS + 1)
+        ),
+        None
+    );
+}
+
+#[test]
+fn versions_before_their_end_are_warned_about() {
+    let today = day(2028, 11, 9) - chrono::Duration::days(EOL_WARNING_DAYS);
+
+    assert_eq!(
+        status("16-alpine", today),
+        Some(EolStatus::Soon {
+            date: day(2028, 11, 9),
+            days: EOL_WARNING_DAYS,
+        })
+    );
+    assert_eq!(
+        status("16", day(2028, 11, 8)),
+        Some(EolStatus::Soon {
+            date: day(2028, 11, 9),
+            days: 1,
+        })
+    );
+}
+
+#[test]
+fn versions_are_past_their_end_on_the_day() {
+    assert_eq!(
+        status("16", day(2028, 11, 9)),
+        Some(EolStatus::Past(day(2028, 11, 9)))
+    );
+}
+
+#[test]
+fn versions_stay_past_their_end_after_it() {
+    assert_eq!(
+        status("16.4", day(2031, 1, 1)),
+        Some(EolStatus::Past(day(2028, 11, 9)))
+    );
+}
+
+#[test]
+fn missing_data_means_no_warning() {
+    let today = day(2031, 1, 1);
+
+    // No date for the major version
+    assert_eq!(status("17", today), None);
+    // No major version in the tag
+    assert_eq!(status("latest", today), None);
+    // No pattern for the image
+    assert_eq!(eol_status(None, "16", &dates(), today), None);
+    assert_eq!(
+        eol_status(Some("{MAJOR}*"), "16", &HashMap::new(), today),
+        None
+    );
+}
+
+#[test]
+fn the_warning_reads_in_days_months_or_the_date() {
+    let soon = |days| EolStatus::Soon {
+        date: day(2028, 11, 9),
+        days,
+    };
+
+    assert_eq!(soon(1).describe(), "EOL tomorrow");
+    assert_eq!(soon(45).describe(), "EOL in 45 days");
+    assert_eq!(soon(95).describe(), "EOL in 3 months");
+    assert_eq!(
+        EolStatus::Past(day(2028, 11, 9)).describe(),
+        "Past EOL since 9 Nov 2028"
+    );
+}
+
+#[test]
+fn the_bundled_databases_ship_their_dates() {
+    let today = day(2026, 10, 17);
+    let status = |name: &str, tag: &str| {
+        let database = bundled(name);
+        eol_status(
+            database.version_pattern.as_deref(),
+            tag,
+            &database.eol_dates,
+            today,
+        )
+    };
+
+    assert_eq!(
+        status("Postgres", "13"),
+        Some(EolStatus::Past(day(2025, 11, 13)))
+    );
+    assert_eq!(
+        status("MySQL", "8.0.40"),
+        Some(EolStatus::Past(day(2026, 4, 30)))
+    );
+    assert_eq!(status("MySQL", "8.4"), None);
+    assert_eq!(
+        status("MongoDB", "6.0"),
+        Some(EolStatus::Past(day(2025, 7, 31)))
+    );
+    assert_eq!(status("MongoDB", "7"), None);
+}
+
+#[test]
+fn mariadb_dates_are_keyed_by_the_10_x_release() {
+    let mariadb = bundled("MariaDB");
+    let status = |tag: &str, today| {
+        eol_status(
+            mariadb.version_pattern.as_deref(),
+            tag,
+            &mariadb.eol_dates,
+            today,
+        )
+    };
+
+    assert_eq!(
+        status("10.6.20", day(2026, 10, 17)),
+        Some(EolStatus::Past(day(2026, 7, 6)))
+    );
+    // "11" is 10.11
+    assert_eq!(
+        status("10.11", day(2028, 1, 1)),
+        Some(EolStatus::Soon {
+            date: day(2028, 2, 16),
+            days: 46,
+        })
+    );
+    // 11.x releases aren't matched
+    assert_eq!(status("11.4", day(2031, 1, 1)), None);
+}
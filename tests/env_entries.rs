@@ -0,0 +1,74 @@
+//! Checks how the `KEY=value` env entries docker reports are split, and which values are kept
+//! out of the UI.
+#![allow(dead_code)]
+
+#[path = "../src/daemon.rs"]
+mod daemon;
+#[path = "../src/docker.rs"]
+mod docker;
+#[path = "../src/image_ref.rs"]
+mod image_ref;
+#[path = "../src/platform.rs"]
+mod platform;
+#[path = "../src/ports.rs"]
+mod ports;
+
+use docker::{parse_env_entry, split_env_entry, EnvValue, MAX_ENV_VALUE_LEN};
+
+fn text(name: &str, value: &str) -> Option<(String, EnvValue)> {
+    Some((name.into(), EnvValue::Text(value.into())))
+}
+
+#[test]
+fn entries_split_at_the_first_equals_sign() {
+    assert_eq!(
+        split_env_entry("POSTGRES_DB=app"),
+        Some(("POSTGRES_DB", "app"))
+    );
+    assert_eq!(
+        split_env_entry("DATABASE_URL=postgres://u:p@h/db?sslmode=require&x=1"),
+        Some(("DATABASE_URL", "postgres://u:p@h/db?sslmode=require&x=1"))
+    );
+    assert_eq!(split_env_entry("PADDING=="), Some(("PADDING", "=")));
+    assert_eq!(parse_env_entry("TOKEN=YWJj=="), text("TOKEN", "YWJj=="));
+}
+
+#[test]
+fn entries_without_a_value_are_empty() {
+    assert_eq!(split_env_entry("DEBUG="), Some(("DEBUG", "")));
+    assert_eq!(split_env_entry("DEBUG"), Some(("DEBUG", "")));
+    assert_eq!(parse_env_entry("DEBUG"), text("DEBUG", ""));
+    assert_eq!(parse_env_entry("DEBUG="), text("DEBUG", ""));
+}
+
+#[test]
+fn entries_without_a_name_are_skipped() {
+    assert_eq!(split_env_entry("=value"), None);
+    assert_eq!(split_env_entry("="), None);
+    assert_eq!(split_env_entry(""), None);
+    assert_eq!(parse_env_entry("=value"), None);
+}
+
+#[test]
+fn large_and_binary_values_are_kept_out() {
+    let limit = "x".repeat(MAX_ENV_VALUE_LEN);
+    assert_eq!(
+        parse_env_entry(&format!("CERT={limit}")),
+        text("CERT", &limit)
+    );
+    assert_eq!(
+        parse_env_entry(&format!("CERT={limit}y")),
+        Some((
+            "CERT".into(),
+            EnvValue::LargeOrBinary(MAX_ENV_VALUE_LEN + 1)
+        ))
+    );
+
+    assert_eq!(EnvValue::new("a\u{0}b"), EnvValue::LargeOrBinary(3));
+    assert_eq!(EnvValue::new("bad \u{fffd}").as_text(), None);
+    // Whitespace, such as a PEM's newlines, is text
+    assert_eq!(
+        EnvValue::new("line\nline\ttab").as_text(),
+        Some("line\nline\ttab")
+    );
+}
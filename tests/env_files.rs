@@ -0,0 +1,199 @@
+//! Checks how `.env` files are read for the create form, from the line formats they come in, and
+//! how the variables edited as text are checked and written back.
+#![allow(dead_code)]
+
+#[path = "../src/env_file.rs"]
+mod env_file;
+
+use env_file::{format_env_file, parse_env_file, parse_env_lines, LineError};
+
+fn parsed(contents: &str) -> Vec<(String, String)> {
+    parse_env_file(contents)
+}
+
+fn pairs(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+    pairs
+        .iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
+
+#[test]
+fn comments_blank_lines_and_export_prefixes_are_skipped() {
+    let contents = "\
+# Database settings
+export POSTGRES_USER=app
+
+  export   POSTGRES_DB=orders
+    # indented comment
+";
+
+    assert_eq!(
+        parsed(contents),
+        pairs(&[("POSTGRES_USER", "app"), ("POSTGRES_DB", "orders")])
+    );
+}
+
+#[test]
+fn crlf_line_endings_are_read() {
+    assert_eq!(
+        parsed("POSTGRES_USER=app\r\nPOSTGRES_DB=orders\r\n"),
+        pairs(&[("POSTGRES_USER", "app"), ("POSTGRES_DB", "orders")])
+    );
+}
+
+#[test]
+fn quoted_values_keep_hashes_and_spaces() {
+    let contents = r#"PASSWORD="p#ss word" # the admin password
+SINGLE='not # a comment'
+UNQUOTED=p#ss
+COMMENTED=secret # trailing comment
+"#;
+
+    assert_eq!(
+        parsed(contents),
+        pairs(&[
+            ("PASSWORD", "p#ss word"),
+            ("SINGLE", "not # a comment"),
+            ("UNQUOTED", "p#ss"),
+            ("COMMENTED", "secret"),
+        ])
+    );
+}
+
+#[test]
+fn escapes_are_only_read_in_double_quotes() {
+    let contents = r#"DOUBLE="line one\nline \"two\"\t\\"
+SINGLE='line one\n'
+UNKNOWN="C:\data"
+"#;
+
+    assert_eq!(
+        parsed(contents),
+        pairs(&[
+            ("DOUBLE", "line one\nline \"two\"\t\\"),
+            ("SINGLE", r"line one\n"),
+            ("UNKNOWN", r"C:\data"),
+        ])
+    );
+}
+
+#[test]
+fn empty_values_are_kept() {
+    assert_eq!(
+        parsed("EMPTY=\nQUOTED=\"\"\nSPACED=   \n"),
+        pairs(&[("EMPTY", ""), ("QUOTED", ""), ("SPACED", "")])
+    );
+}
+
+#[test]
+fn values_may_contain_equals_signs() {
+    assert_eq!(
+        parsed("DATABASE_URL=postgres://app:pw@db/orders?sslmode=require\n"),
+        pairs(&[(
+            "DATABASE_URL",
+            "postgres://app:pw@db/orders?sslmode=require"
+        )])
+    );
+}
+
+#[test]
+fn unterminated_quotes_take_the_rest_of_the_line() {
+    assert_eq!(
+        parsed("PASSWORD=\"unfinished # still the value\nNEXT=1"),
+        pairs(&[("PASSWORD", "unfinished # still the value"), ("NEXT", "1")])
+    );
+}
+
+#[test]
+fn malformed_lines_are_skipped() {
+    let contents = "\
+just some text
+=no name
+BAD-NAME=1
+GOOD=1
+";
+
+    assert_eq!(parsed(contents), pairs(&[("GOOD", "1")]));
+}
+
+fn error(line: usize, message: &str) -> LineError {
+    LineError {
+        line,
+        message: message.into(),
+    }
+}
+
+#[test]
+fn text_lines_skip_comments_and_blank_lines() {
+    let lines = [
+        "# Settings",
+        "",
+        "POSTGRES_DB=orders",
+        "   ",
+        "export APP_ENV=dev",
+    ];
+
+    assert_eq!(
+        parse_env_lines(lines),
+        Ok(pairs(&[("POSTGRES_DB", "orders"), ("APP_ENV", "dev")]))
+    );
+}
+
+#[test]
+fn every_unusable_line_is_reported_by_its_index() {
+    let lines = [
+        "POSTGRES_DB=orders",
+        "# fine",
+        "not a variable",
+        "=value",
+        "BAD-KEY=1",
+        "POSTGRES_DB=again",
+    ];
+
+    assert_eq!(
+        parse_env_lines(lines),
+        Err(vec![
+            error(2, "Expected KEY=VALUE"),
+            error(3, "The name before = is missing"),
+            error(
+                4,
+                "BAD-KEY may only contain letters, digits, underscores and dots"
+            ),
+            error(5, "POSTGRES_DB is set more than once"),
+        ])
+    );
+}
+
+#[test]
+fn written_variables_are_read_back_unchanged() {
+    let variables = pairs(&[
+        ("PLAIN", "hunter2"),
+        ("EMPTY", ""),
+        ("SPACED", "  padded  "),
+        ("HASH", "pass #1"),
+        ("QUOTES", "\"quoted\" and 'single'"),
+        ("LEADING_QUOTE", "'starts quoted"),
+        ("LINES", "first\nsecond\tindented"),
+        ("BACKSLASH", "C:\\data\\pg"),
+        ("EQUALS", "a=b=c"),
+    ]);
+
+    let written = format_env_file(
+        variables
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str())),
+    );
+
+    assert_eq!(parse_env_file(&written), variables);
+    assert_eq!(parse_env_lines(written.lines()), Ok(variables));
+}
+
+#[test]
+fn plain_values_are_written_unquoted() {
+    assert_eq!(
+        format_env_file([("POSTGRES_DB", "orders"), ("URL", "postgres://a@b/c?x=1")]),
+        "POSTGRES_DB=orders\nURL=postgres://a@b/c?x=1\n"
+    );
+    assert_eq!(format_env_file([("NOTE", "a # b")]), "NOTE=\"a # b\"\n");
+}
@@ -0,0 +1,239 @@
+//! Checks that docker failures are explained, against error messages captured from real daemons.
+#![allow(dead_code)]
+
+#[path = "../src/daemon.rs"]
+mod daemon;
+#[path = "../src/errors.rs"]
+mod errors;
+
+use std::{io::ErrorKind, time::Duration};
+
+use bollard::errors::Error;
+use daemon::TimedOut;
+use errors::{explain, explain_docker_error, explain_message, rejected_sysctl};
+
+fn server(status_code: u16, message: &str) -> Error {
+    Error::DockerResponseServerError {
+        status_code,
+        message: message.into(),
+    }
+}
+
+fn io(kind: ErrorKind, message: &str) -> Error {
+    Error::IOError {
+        err: std::io::Error::new(kind, message),
+    }
+}
+
+/// Answers of the daemon and the title they are explained with.
+const ANSWERS: &[(u16, &str, &str)] = &[
+    (
+        404,
+        "No such container: db-mgr__orders",
+        "The container no longer exists",
+    ),
+    (
+        404,
+        "No such image: postgres:99",
+        "The image could not be found",
+    ),
+    (
+        404,
+        "manifest for postgres:99 not found: manifest unknown: manifest unknown",
+        "The image could not be found",
+    ),
+    (
+        404,
+        "pull access denied for acme/pg, repository does not exist or may require 'docker login': \
+         denied: requested access to the resource is denied",
+        "The registry refused the pull",
+    ),
+    (
+        500,
+        "Head \"https://ghcr.io/v2/acme/pg/manifests/16\": unauthorized",
+        "The registry refused the pull",
+    ),
+    (
+        409,
+        "Conflict. The container name \"/db-mgr__orders\" is already in use by container \
+         \"4f1c\". You have to remove (or rename) that container to be able to reuse that name.",
+        "The name is already in use",
+    ),
+    (
+        500,
+        "driver failed programming external connectivity on endpoint db-mgr__orders (9c2e): Bind \
+         for 0.0.0.0:5432 failed: port is already allocated",
+        "A port is already in use",
+    ),
+    (
+        500,
+        "failed to create endpoint db-mgr__orders on network bridge: listen tcp4 0.0.0.0:5432: \
+         bind: address already in use",
+        "A port is already in use",
+    ),
+];
+
+#[test]
+fn daemon_answers_are_explained() {
+    for (status_code, message, title) in ANSWERS {
+        let explained = explain_docker_error(&server(*status_code, message));
+        assert_eq!(explained.title, *title, "{message}");
+        assert!(explained.suggestion.is_some(), "{message}");
+        assert!(explained.detail.contains(message), "{message}");
+    }
+}
+
+#[test]
+fn status_codes_have_to_match() {
+    // A 500 that only mentions a missing container isn't taken for a 404
+    let explained = explain_docker_error(&server(500, "No such container: db-mgr__orders"));
+    assert_eq!(explained.title, "Docker returned an error");
+    assert_eq!(explained.suggestion, None);
+}
+
+#[test]
+fn connection_failures_are_explained() {
+    let cases = [
+        (
+            io(
+                ErrorKind::NotFound,
+                "No such file or directory (os error 2)",
+            ),
+            "Docker isn't running",
+        ),
+        (
+            io(
+                ErrorKind::ConnectionRefused,
+                "Connection refused (os error 111)",
+            ),
+            "Docker isn't running",
+        ),
+        (
+            io(
+                ErrorKind::PermissionDenied,
+                "Permission denied (os error 13)",
+            ),
+            "No permission to use Docker",
+        ),
+        (Error::RequestTimeoutError, "Docker did not respond in time"),
+    ];
+
+    for (error, title) in cases {
+        assert_eq!(explain_docker_error(&error).title, title, "{error}");
+    }
+}
+
+/// Errors that only survived as text, as background tasks send them.
+const MESSAGES: &[(&str, &str)] = &[
+    (
+        "error trying to connect: No such file or directory (os error 2)",
+        "Docker isn't running",
+    ),
+    (
+        "Cannot connect to the Docker daemon at unix:///var/run/docker.sock. Is the docker daemon \
+         running?",
+        "Docker isn't running",
+    ),
+    (
+        "error trying to connect: Permission denied (os error 13)",
+        "No permission to use Docker",
+    ),
+    (
+        "Got permission denied while trying to connect to the Docker daemon socket at \
+         unix:///var/run/docker.sock",
+        "No permission to use Docker",
+    ),
+    (
+        "error trying to connect: invalid peer certificate: Expired",
+        "A certificate has expired",
+    ),
+    (
+        "error trying to connect: invalid peer certificate: UnknownIssuer",
+        "The certificate isn't signed by the CA",
+    ),
+    (
+        "error trying to connect: received fatal alert: BadCertificate",
+        "The daemon refused the client certificate",
+    ),
+    (
+        "error trying to connect: invalid peer certificate: NotValidForName",
+        "The daemon's certificate is for another host",
+    ),
+    (
+        "Docker did not answer list containers within 10s",
+        "Docker did not respond in time",
+    ),
+];
+
+#[test]
+fn messages_are_explained() {
+    for (message, title) in MESSAGES {
+        let explained = explain_message("Could not list containers", message);
+        assert_eq!(explained.title, *title, "{message}");
+        assert_eq!(
+            explained.detail,
+            format!("Could not list containers: {message}")
+        );
+    }
+
+    let unknown = explain_message("Could not back up orders", "pg_dumpall: exit code 1");
+    assert_eq!(unknown.title, "Could not back up orders");
+    assert_eq!(unknown.body(), "pg_dumpall: exit code 1");
+}
+
+#[test]
+fn helper_errors_keep_what_was_being_done() {
+    let timed_out = anyhow::Error::new(TimedOut {
+        operation: "inspect container",
+        after: Duration::from_secs(10),
+    });
+    let explained = explain("Could not refresh", &timed_out);
+    assert_eq!(explained.title, "Docker did not respond in time");
+    assert_eq!(
+        explained.detail,
+        "Could not refresh: Docker did not answer inspect container within 10s"
+    );
+
+    let known = explain(
+        "Could not start orders",
+        &anyhow::Error::new(server(404, "No such container: orders")),
+    );
+    assert_eq!(known.title, "The container no longer exists");
+    assert!(known.detail.starts_with("Could not start orders: "));
+    assert!(known
+        .body()
+        .ends_with(&format!("Details: {}", known.detail)));
+
+    // Unexplained daemon errors are titled by what was being done
+    let unknown = explain(
+        "Could not start orders",
+        &anyhow::Error::new(server(500, "OCI runtime create failed")),
+    );
+    assert_eq!(unknown.title, "Could not start orders");
+    assert_eq!(unknown.suggestion, None);
+}
+
+#[test]
+fn rejected_sysctls_are_named() {
+    let sysctls = [
+        "net.core.somaxconn".to_string(),
+        "kernel.shmmax".to_string(),
+    ];
+
+    assert_eq!(
+        rejected_sysctl(
+            "OCI runtime create failed: sysctl \"kernel.shmmax\" is not in a separate kernel \
+             namespace",
+            &sysctls
+        ),
+        Some("kernel.shmmax".into())
+    );
+    assert_eq!(
+        rejected_sysctl(
+            "open /proc/sys/net/core/somaxconn: permission denied",
+            &sysctls
+        ),
+        Some("net.core.somaxconn".into())
+    );
+    assert_eq!(rejected_sysctl("port is already allocated", &sysctls), None);
+}
@@ -0,0 +1,214 @@
+//! Checks which containers are marked as killed for running out of memory, as failed or as
+//! restarted by their policy, from their inspect and events.
+#![allow(dead_code)]
+
+#[path = "../src/backup.rs"]
+mod backup;
+mod common;
+#[path = "../src/daemon.rs"]
+mod daemon;
+#[path = "../src/data.rs"]
+mod data;
+#[path = "../src/definition.rs"]
+mod definition;
+#[path = "../src/diff.rs"]
+mod diff;
+#[path = "../src/docker.rs"]
+mod docker;
+#[path = "../src/exits.rs"]
+mod exits;
+#[path = "../src/idle.rs"]
+mod idle;
+#[path = "../src/image_ref.rs"]
+mod image_ref;
+#[path = "../src/names.rs"]
+mod names;
+#[path = "../src/platform.rs"]
+mod platform;
+#[path = "../src/ports.rs"]
+mod ports;
+#[path = "../src/registry.rs"]
+mod registry;
+#[path = "../src/template.rs"]
+mod template;
+#[path = "../src/tls.rs"]
+mod tls;
+
+use bollard::service::ContainerStateStatusEnum;
+use common::{container, ContainerBuilder};
+use docker::{ContainerEvent, ContainerEventKind, ContainerExit};
+use exits::{exit_notes, ExitNotes};
+
+const ID: &str = "0123456789abcdef";
+
+fn mysql() -> ContainerBuilder {
+    container("mysql").id(ID)
+}
+
+/// `kinds` as the events of the container, newest first.
+fn events(kinds: Vec<ContainerEventKind>) -> Vec<ContainerEvent> {
+    let count = kinds.len() as i64;
+    kinds
+        .into_iter()
+        .enumerate()
+        .map(|(i, kind)| ContainerEvent {
+            container: ID.into(),
+            time: 1_700_000_000 + count - i as i64,
+            kind,
+        })
+        .collect()
+}
+
+#[test]
+fn out_of_memory_kills_are_marked() {
+    let killed = mysql()
+        .state(ContainerStateStatusEnum::EXITED)
+        .exit(ContainerExit {
+            oom_killed: true,
+            exit_code: Some(137),
+            restart_count: 0,
+        })
+        .build();
+    let notes = exit_notes(
+        &killed,
+        &events(vec![
+            ContainerEventKind::Died(Some(137)),
+            ContainerEventKind::OutOfMemory,
+            ContainerEventKind::Started,
+        ]),
+    );
+    assert_eq!(
+        notes,
+        ExitNotes {
+            out_of_memory: true,
+            failed: None,
+            policy_restarts: 0,
+        }
+    );
+
+    // The events arrive before the container is inspected again
+    let reported = mysql()
+        .state(ContainerStateStatusEnum::EXITED)
+        .exit(ContainerExit {
+            exit_code: Some(137),
+            ..Default::default()
+        })
+        .build();
+    assert!(
+        exit_notes(
+            &reported,
+            &events(vec![
+                ContainerEventKind::Died(Some(137)),
+                ContainerEventKind::OutOfMemory
+            ])
+        )
+        .out_of_memory
+    );
+}
+
+#[test]
+fn stopped_containers_get_no_notes() {
+    let stopped = mysql()
+        .state(ContainerStateStatusEnum::EXITED)
+        .exit(ContainerExit {
+            exit_code: Some(1),
+            ..Default::default()
+        })
+        .build();
+    let notes = exit_notes(
+        &stopped,
+        &events(vec![
+            ContainerEventKind::Stopped,
+            ContainerEventKind::Died(Some(1)),
+            ContainerEventKind::Killed(Some("15".into())),
+            ContainerEventKind::Started,
+            // An earlier run ran out of memory
+            ContainerEventKind::OutOfMemory,
+        ]),
+    );
+    assert_eq!(notes, ExitNotes::default());
+
+    // Without events, the exit code of the signal docker stops containers with
+    let killed = mysql()
+        .state(ContainerStateStatusEnum::EXITED)
+        .exit(ContainerExit {
+            exit_code: Some(143),
+            ..Default::default()
+        })
+        .build();
+    assert_eq!(exit_notes(&killed, &[]), ExitNotes::default());
+}
+
+#[test]
+fn non_zero_exits_are_failures() {
+    let crashed = mysql()
+        .state(ContainerStateStatusEnum::EXITED)
+        .exit(ContainerExit {
+            exit_code: Some(1),
+            ..Default::default()
+        })
+        .build();
+    assert_eq!(
+        exit_notes(&crashed, &events(vec![ContainerEventKind::Died(Some(1))])).failed,
+        Some(1)
+    );
+
+    // A clean exit or an earlier exit of a running container isn't one
+    let clean = mysql()
+        .state(ContainerStateStatusEnum::EXITED)
+        .exit(ContainerExit {
+            exit_code: Some(0),
+            ..Default::default()
+        })
+        .build();
+    assert_eq!(exit_notes(&clean, &[]), ExitNotes::default());
+    let running = mysql()
+        .state(ContainerStateStatusEnum::RUNNING)
+        .exit(ContainerExit {
+            exit_code: Some(1),
+            ..Default::default()
+        })
+        .build();
+    assert_eq!(exit_notes(&running, &[]), ExitNotes::default());
+}
+
+#[test]
+fn policy_restarts_are_counted() {
+    let restarted = mysql()
+        .state(ContainerStateStatusEnum::RUNNING)
+        .exit(ContainerExit {
+            oom_killed: false,
+            exit_code: Some(1),
+            restart_count: 3,
+        })
+        .build();
+    let notes = exit_notes(
+        &restarted,
+        &events(vec![
+            ContainerEventKind::Started,
+            ContainerEventKind::Died(Some(1)),
+            ContainerEventKind::Started,
+        ]),
+    );
+    assert_eq!(
+        notes,
+        ExitNotes {
+            out_of_memory: false,
+            failed: None,
+            policy_restarts: 3,
+        }
+    );
+
+    // Crash looping after running out of memory
+    let looping = mysql()
+        .state(ContainerStateStatusEnum::RESTARTING)
+        .exit(ContainerExit {
+            oom_killed: true,
+            exit_code: Some(137),
+            restart_count: 5,
+        })
+        .build();
+    let notes = exit_notes(&looping, &[]);
+    assert!(notes.out_of_memory);
+    assert_eq!(notes.policy_restarts, 5);
+}
@@ -0,0 +1,127 @@
+//! Checks what closing the window does with the running containers, which ones are stopped
+//! first and when it closes right away.
+#![allow(dead_code)]
+
+#[path = "../src/daemon.rs"]
+mod daemon;
+#[path = "../src/dependencies.rs"]
+mod dependencies;
+#[path = "../src/docker.rs"]
+mod docker;
+#[path = "../src/app/exit.rs"]
+mod exit;
+#[path = "../src/image_ref.rs"]
+mod image_ref;
+#[path = "../src/platform.rs"]
+mod platform;
+#[path = "../src/ports.rs"]
+mod ports;
+
+use std::{collections::HashSet, time::Duration};
+
+use bollard::service::{ContainerStateStatusEnum, ContainerSummary};
+use docker::{summary_container, DbContainer};
+use exit::{exit_plan, ExitPlan, EXIT_CAP};
+
+fn container(name: &str, running: bool, depends_on: &[&str]) -> DbContainer {
+    let mut container = summary_container(ContainerSummary {
+        id: Some(format!("{name}-id")),
+        names: Some(vec![format!("/{name}")]),
+        image: Some("postgres:16".into()),
+        ..Default::default()
+    })
+    .expect("The summary has an id");
+    container.state = match running {
+        true => ContainerStateStatusEnum::RUNNING,
+        false => ContainerStateStatusEnum::EXITED,
+    };
+    container.depends_on = depends_on.iter().map(|name| name.to_string()).collect();
+    container
+}
+
+fn stop(names: &[&str]) -> ExitPlan {
+    ExitPlan::Stop(names.iter().map(|name| name.to_string()).collect())
+}
+
+fn containers() -> Vec<DbContainer> {
+    vec![
+        container("db-mgr__orders", true, &[]),
+        container("db-mgr__cache", false, &[]),
+        container("db-mgr__api", true, &["db-mgr__orders"]),
+    ]
+}
+
+#[test]
+fn nothing_is_stopped_unless_set_to() {
+    assert_eq!(
+        exit_plan(&containers(), false, &HashSet::new(), false, false),
+        ExitPlan::Close
+    );
+}
+
+#[test]
+fn running_containers_are_stopped_dependents_first() {
+    assert_eq!(
+        exit_plan(&containers(), true, &HashSet::new(), false, false),
+        stop(&["db-mgr__api", "db-mgr__orders"])
+    );
+}
+
+#[test]
+fn kept_containers_are_left_running() {
+    let keep = HashSet::from(["db-mgr__api".to_string()]);
+
+    assert_eq!(
+        exit_plan(&containers(), true, &keep, false, false),
+        stop(&["db-mgr__orders"])
+    );
+}
+
+#[test]
+fn closing_without_running_containers_closes_right_away() {
+    let keep = HashSet::from(["db-mgr__api".to_string(), "db-mgr__orders".to_string()]);
+
+    assert_eq!(
+        exit_plan(&containers(), true, &keep, false, false),
+        ExitPlan::Close
+    );
+    assert_eq!(
+        exit_plan(&[], true, &HashSet::new(), false, false),
+        ExitPlan::Close
+    );
+}
+
+#[test]
+fn read_only_mode_never_stops_containers() {
+    assert_eq!(
+        exit_plan(&containers(), true, &HashSet::new(), true, false),
+        ExitPlan::Close
+    );
+}
+
+#[test]
+fn closing_again_while_stopping_closes_right_away() {
+    assert_eq!(
+        exit_plan(&containers(), true, &HashSet::new(), false, true),
+        ExitPlan::Close
+    );
+}
+
+#[test]
+fn containers_depending_on_each_other_are_still_stopped() {
+    let containers = vec![
+        container("db-mgr__a", true, &["db-mgr__b"]),
+        container("db-mgr__b", true, &["db-mgr__a"]),
+    ];
+
+    assert_eq!(
+        exit_plan(&containers, true, &HashSet::new(), false, false),
+        stop(&["db-mgr__a", "db-mgr__b"])
+    );
+}
+
+#[test]
+fn a_hanging_docker_only_delays_closing_briefly() {
+    assert!(EXIT_CAP > Duration::ZERO);
+    assert!(EXIT_CAP <= Duration::from_secs(60));
+}
@@ -0,0 +1,79 @@
+//! Checks the readable names generated for scratch containers with seeded generators, and the
+//! hostnames derived from container names.
+#![allow(dead_code)]
+
+#[path = "../src/names.rs"]
+mod names;
+
+use names::{default_hostname, generate_name};
+use rand::{rngs::StdRng, SeedableRng};
+
+fn is_adjective_noun(name: &str) -> bool {
+    matches!(
+        name.split('-').collect::<Vec<_>>().as_slice(),
+        [adjective, noun] if !adjective.is_empty()
+            && !noun.is_empty()
+            && name.chars().all(|c| c.is_ascii_lowercase() || c == '-')
+    )
+}
+
+#[test]
+fn the_same_seed_gives_the_same_names() {
+    let names = |seed| {
+        let mut rng = StdRng::seed_from_u64(seed);
+        (0..5)
+            .map(|_| generate_name(&mut rng, &[]))
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(names(7), names(7));
+    assert!(names(7).iter().all(|name| is_adjective_noun(name)));
+}
+
+#[test]
+fn repeated_generation_gives_different_names() {
+    let mut rng = StdRng::seed_from_u64(1);
+    let first = generate_name(&mut rng, &[]);
+
+    assert!((0..20).any(|_| generate_name(&mut rng, &[]) != first));
+}
+
+#[test]
+fn taken_names_are_skipped() {
+    let first = generate_name(&mut StdRng::seed_from_u64(3), &[]);
+
+    let name = generate_name(&mut StdRng::seed_from_u64(3), std::slice::from_ref(&first));
+    assert_ne!(name, first);
+    assert!(is_adjective_noun(&name));
+}
+
+#[test]
+fn names_are_numbered_once_every_attempt_collides() {
+    // Every adjective-noun pair the seeded generator will try
+    let mut rng = StdRng::seed_from_u64(5);
+    let mut taken = (0..1000)
+        .map(|_| generate_name(&mut rng, &[]))
+        .collect::<Vec<_>>();
+    taken.sort();
+    taken.dedup();
+
+    let name = generate_name(&mut StdRng::seed_from_u64(5), &taken);
+    assert!(!taken.contains(&name));
+    let (base, number) = name.rsplit_once('-').expect("The name is numbered");
+    assert!(taken.iter().any(|taken| taken == base));
+    assert_eq!(number, "2");
+
+    taken.push(name.clone());
+    let next = generate_name(&mut StdRng::seed_from_u64(5), &taken);
+    assert_eq!(next, format!("{base}-3"));
+}
+
+#[test]
+fn hostnames_are_lowercase_words_joined_by_dashes() {
+    assert_eq!(default_hostname("orders-db"), "orders-db");
+    assert_eq!(default_hostname("Orders DB"), "orders-db");
+    assert_eq!(default_hostname("my_app.test"), "my-app-test");
+    assert_eq!(default_hostname("--trimmed--"), "trimmed");
+    assert_eq!(default_hostname("___"), "db");
+    assert_eq!(default_hostname(&"a".repeat(100)).len(), 63);
+}
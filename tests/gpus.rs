@@ -0,0 +1,142 @@
+//! Checks how GPUs are given to containers of GPU capable images, read back from inspect and
+//! listed from what the daemon advertises.
+#![allow(dead_code)]
+
+#[path = "../src/backup.rs"]
+mod backup;
+mod common;
+#[path = "../src/daemon.rs"]
+mod daemon;
+#[path = "../src/data.rs"]
+mod data;
+#[path = "../src/definition.rs"]
+mod definition;
+#[path = "../src/diff.rs"]
+mod diff;
+#[path = "../src/docker.rs"]
+mod docker;
+#[path = "../src/idle.rs"]
+mod idle;
+#[path = "../src/image_ref.rs"]
+mod image_ref;
+#[path = "../src/names.rs"]
+mod names;
+#[path = "../src/platform.rs"]
+mod platform;
+#[path = "../src/ports.rs"]
+mod ports;
+#[path = "../src/registry.rs"]
+mod registry;
+#[path = "../src/template.rs"]
+mod template;
+#[path = "../src/tls.rs"]
+mod tls;
+
+use bollard::service::{DeviceRequest, GenericResources};
+use common::postgres;
+use docker::{
+    describe_gpus, parse_gpu_requests, parse_gpus, plan_container, DbContainerConfig,
+    MANAGED_LABELS,
+};
+use serde_json::{json, Value};
+
+fn device_requests(config: &DbContainerConfig) -> Value {
+    plan_container(config, MANAGED_LABELS)
+        .into_iter()
+        .find(|op| op.call.starts_with("POST /containers/create"))
+        .and_then(|op| op.body)
+        .expect("The plan creates the container")["HostConfig"]["DeviceRequests"]
+        .clone()
+}
+
+fn gpu_request(device_ids: Option<Vec<&str>>, count: Option<i64>) -> DeviceRequest {
+    DeviceRequest {
+        driver: Some("nvidia".into()),
+        count,
+        device_ids: device_ids.map(|ids| ids.into_iter().map(String::from).collect()),
+        capabilities: Some(vec![vec!["gpu".into()]]),
+        options: None,
+    }
+}
+
+#[test]
+fn containers_without_gpus_request_none() {
+    let config = postgres().quick_config("orders");
+
+    assert_eq!(device_requests(&config), Value::Null);
+}
+
+#[test]
+fn all_gpus_are_requested_like_gpus_all() {
+    let mut config = postgres().quick_config("orders");
+    config.gpus = Some(vec![]);
+
+    assert_eq!(
+        device_requests(&config),
+        json!([{ "Driver": "nvidia", "Count": -1, "Capabilities": [["gpu"]] }])
+    );
+}
+
+#[test]
+fn picked_gpus_are_requested_by_id() {
+    let mut config = postgres().quick_config("orders");
+    config.gpus = Some(vec!["GPU-0a1b".into(), "GPU-2c3d".into()]);
+
+    assert_eq!(
+        device_requests(&config),
+        json!([{
+            "Driver": "nvidia",
+            "DeviceIDs": ["GPU-0a1b", "GPU-2c3d"],
+            "Capabilities": [["gpu"]]
+        }])
+    );
+}
+
+#[test]
+fn granted_gpus_are_read_back_from_the_device_requests() {
+    assert_eq!(
+        parse_gpu_requests(&[gpu_request(None, Some(-1))]),
+        Some(vec![])
+    );
+    assert_eq!(
+        parse_gpu_requests(&[gpu_request(Some(vec!["GPU-0a1b"]), None)]),
+        Some(vec!["GPU-0a1b".to_string()])
+    );
+    assert_eq!(parse_gpu_requests(&[]), None);
+
+    let other = DeviceRequest {
+        capabilities: Some(vec![vec!["compute".into()]]),
+        ..gpu_request(None, Some(-1))
+    };
+    assert_eq!(parse_gpu_requests(&[other]), None);
+}
+
+#[test]
+fn gpus_are_described_for_the_detail_view() {
+    assert_eq!(describe_gpus(&[]), "all");
+    assert_eq!(
+        describe_gpus(&["GPU-0a1b".into(), "GPU-2c3d".into()]),
+        "GPU-0a1b, GPU-2c3d"
+    );
+}
+
+#[test]
+fn advertised_gpus_are_listed_by_uuid_or_index() {
+    let resources = |json: Value| -> GenericResources {
+        serde_json::from_value(json).expect("The captured JSON must parse")
+    };
+
+    assert_eq!(
+        parse_gpus(&resources(json!({
+            "NamedResourceSpec": { "Kind": "NVIDIA-GPU", "Value": "GPU-0a1b" },
+            "DiscreteResourceSpec": { "Kind": "gpu", "Value": 2 }
+        }))),
+        vec!["GPU-0a1b", "0", "1"]
+    );
+    assert!(parse_gpus(&resources(json!({
+        "NamedResourceSpec": { "Kind": "fpga", "Value": "fpga-0" },
+        "DiscreteResourceSpec": { "Kind": "ssd", "Value": 4 }
+    })))
+    .is_empty());
+    assert!(parse_gpus(&resources(json!({}))).is_empty());
+}
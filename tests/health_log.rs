@@ -0,0 +1,153 @@
+//! Checks how the health check runs docker keeps in an inspected container are read, newest
+//! first, and what containers without a health check show.
+#![allow(dead_code)]
+
+#[path = "../src/daemon.rs"]
+mod daemon;
+#[path = "../src/docker.rs"]
+mod docker;
+#[path = "../src/image_ref.rs"]
+mod image_ref;
+#[path = "../src/platform.rs"]
+mod platform;
+#[path = "../src/ports.rs"]
+mod ports;
+
+use std::time::Duration;
+
+use bollard::service::{ContainerInspectResponse, Health, HealthStatusEnum};
+use chrono::{TimeZone, Utc};
+use docker::{inspected_container, parse_health_log, HealthProbe};
+use serde_json::{json, Value};
+
+/// The `State.Health` of a flapping postgres, as docker reports it with the oldest run first.
+fn flapping() -> Value {
+    json!({
+        "Status": "unhealthy",
+        "FailingStreak": 1,
+        "Log": [
+            {
+                "Start": "2024-03-05T09:12:00.100000000Z",
+                "End": "2024-03-05T09:12:00.150000000Z",
+                "ExitCode": 0,
+                "Output": "/var/run/postgresql:5432 - accepting connections\n"
+            },
+            {
+                "Start": "2024-03-05T09:12:30.100000000Z",
+                "End": "2024-03-05T09:12:33.100000000Z",
+                "ExitCode": 1,
+                "Output": "/var/run/postgresql:5432 - no response\n"
+            },
+            {
+                "Start": "2024-03-05T09:13:00.100000000Z",
+                "End": "2024-03-05T09:13:00.120000000Z",
+                "ExitCode": -1,
+                "Output": "OCI runtime exec failed: exec failed: unable to start container process"
+            }
+        ]
+    })
+}
+
+fn health(value: Value) -> Health {
+    serde_json::from_value(value).expect("The captured JSON must parse")
+}
+
+fn inspect(health: Option<Value>) -> ContainerInspectResponse {
+    let mut state = json!({ "Status": "running", "Running": true });
+    if let Some(health) = health {
+        state["Health"] = health;
+    }
+    serde_json::from_value(json!({
+        "Id": "4f3c2a1b9e8d",
+        "Name": "/db-mgr__orders",
+        "State": state,
+        "Config": { "Image": "postgres:16", "Labels": { "db-mgr-resource": "container" } }
+    }))
+    .expect("The captured JSON must parse")
+}
+
+#[test]
+fn runs_are_listed_newest_first() {
+    let probes = parse_health_log(Some(&health(flapping())));
+
+    let exit_codes = probes
+        .iter()
+        .map(|probe| probe.exit_code)
+        .collect::<Vec<_>>();
+    assert_eq!(exit_codes, vec![Some(-1), Some(1), Some(0)]);
+    assert_eq!(
+        probes[0].start,
+        Some(
+            Utc.with_ymd_and_hms(2024, 3, 5, 9, 13, 0).unwrap()
+                + chrono::Duration::milliseconds(100)
+        )
+    );
+}
+
+#[test]
+fn runs_keep_their_result_duration_and_output() {
+    let probes = parse_health_log(Some(&health(flapping())));
+
+    let failed = &probes[1];
+    assert!(!failed.passed());
+    assert_eq!(failed.duration(), Some(Duration::from_secs(3)));
+    assert_eq!(failed.output, "/var/run/postgresql:5432 - no response");
+
+    let passed = &probes[2];
+    assert!(passed.passed());
+    assert_eq!(passed.duration(), Some(Duration::from_millis(50)));
+}
+
+#[test]
+fn runs_without_times_are_kept_in_order() {
+    let probes = parse_health_log(Some(&health(json!({
+        "Status": "starting",
+        "Log": [
+            { "ExitCode": 0, "Output": "first" },
+            { "ExitCode": 0, "Output": "second", "End": "not a time" }
+        ]
+    }))));
+
+    assert_eq!(
+        probes,
+        vec![
+            HealthProbe {
+                start: None,
+                end: None,
+                exit_code: Some(0),
+                output: "second".into(),
+            },
+            HealthProbe {
+                start: None,
+                end: None,
+                exit_code: Some(0),
+                output: "first".into(),
+            },
+        ]
+    );
+    assert_eq!(probes[0].duration(), None);
+}
+
+#[test]
+fn images_without_a_health_check_have_no_runs() {
+    assert!(parse_health_log(None).is_empty());
+
+    let container = inspected_container(inspect(None)).expect("The inspect has an id");
+    assert!(container.health_log.is_empty());
+    assert_eq!(container.health, None);
+}
+
+#[test]
+fn health_checks_that_havent_run_yet_have_no_runs() {
+    assert!(parse_health_log(Some(&health(json!({ "Status": "starting" })))).is_empty());
+    assert!(parse_health_log(Some(&health(json!({ "Status": "starting", "Log": [] })))).is_empty());
+}
+
+#[test]
+fn inspected_containers_carry_their_runs() {
+    let container = inspected_container(inspect(Some(flapping()))).expect("The inspect has an id");
+
+    assert_eq!(container.health, Some(HealthStatusEnum::UNHEALTHY));
+    assert_eq!(container.health_log.len(), 3);
+    assert_eq!(container.health_log[0].exit_code, Some(-1));
+}
@@ -0,0 +1,104 @@
+//! Checks which fetched and cached icon bytes are drawn, against PNG, JPEG, SVG and HTML
+//! fixtures.
+#![allow(dead_code)]
+
+#[path = "../src/backup.rs"]
+mod backup;
+#[path = "../src/daemon.rs"]
+mod daemon;
+#[path = "../src/data.rs"]
+mod data;
+#[path = "../src/definition.rs"]
+mod definition;
+#[path = "../src/diff.rs"]
+mod diff;
+#[path = "../src/docker.rs"]
+mod docker;
+#[path = "../src/http.rs"]
+mod http;
+#[path = "../src/idle.rs"]
+mod idle;
+#[path = "../src/image_ref.rs"]
+mod image_ref;
+#[path = "../src/names.rs"]
+mod names;
+#[path = "../src/platform.rs"]
+mod platform;
+#[path = "../src/ports.rs"]
+mod ports;
+#[path = "../src/registry.rs"]
+mod registry;
+#[path = "../src/template.rs"]
+mod template;
+#[path = "../src/tls.rs"]
+mod tls;
+
+use std::{fs, io::Cursor};
+
+use http::{validate_cached_icon, validate_icon};
+use image::{ImageOutputFormat, RgbImage};
+
+const PNG: &[u8] = include_bytes!("../default_image.png");
+
+const SVG: &str = r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 24 24"><circle cx="12" cy="12" r="10"/></svg>"#;
+
+const XML_SVG: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!-- Generator: logo tool -->
+<svg xmlns="http://www.w3.org/2000/svg" width="64" height="64"></svg>"#;
+
+const HTML: &str = "\u{feff}
+<!DOCTYPE html>
+<html><head><title>404 Not Found</title></head><body>Not Found</body></html>";
+
+fn jpeg() -> Vec<u8> {
+    let mut bytes = vec![];
+    RgbImage::new(4, 4)
+        .write_to(&mut Cursor::new(&mut bytes), ImageOutputFormat::Jpeg(80))
+        .expect("A JPEG can be encoded");
+    bytes
+}
+
+fn error(bytes: &[u8]) -> String {
+    validate_icon(bytes)
+        .expect_err("The bytes are not an icon")
+        .to_string()
+}
+
+#[test]
+fn raster_images_are_accepted() {
+    validate_icon(PNG).expect("The bundled PNG is an icon");
+    validate_icon(&jpeg()).expect("A JPEG is an icon");
+}
+
+#[test]
+fn svg_logos_are_rejected_with_a_clear_message() {
+    assert!(error(SVG.as_bytes()).contains("is an SVG"));
+    assert!(error(XML_SVG.as_bytes()).contains("is an SVG"));
+}
+
+#[test]
+fn error_pages_are_rejected() {
+    assert!(error(HTML.as_bytes()).contains("web page"));
+    assert!(error(b"<html><body>Service Unavailable</body></html>").contains("web page"));
+}
+
+#[test]
+fn other_bytes_are_rejected() {
+    assert!(validate_icon(b"").is_err());
+    assert!(validate_icon(b"{\"error\": \"rate limited\"}").is_err());
+    assert!(validate_icon(&PNG[..16]).is_err());
+}
+
+#[test]
+fn cached_icons_are_checked_again() {
+    let dir = std::env::temp_dir().join(format!("db-mgr-icon-files-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let (icon, page) = (dir.join("icon"), dir.join("page"));
+    fs::write(&icon, PNG).unwrap();
+    fs::write(&page, HTML).unwrap();
+
+    validate_cached_icon(&icon).expect("The cached PNG is an icon");
+    assert!(validate_cached_icon(&page).is_err());
+    assert!(validate_cached_icon(&dir.join("missing")).is_err());
+    _ = fs::remove_dir_all(&dir);
+}
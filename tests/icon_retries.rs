@@ -0,0 +1,68 @@
+//! Checks how often and after how long failed icon fetches are retried.
+#![allow(dead_code)]
+
+#[path = "../src/backup.rs"]
+mod backup;
+#[path = "../src/daemon.rs"]
+mod daemon;
+#[path = "../src/data.rs"]
+mod data;
+#[path = "../src/definition.rs"]
+mod definition;
+#[path = "../src/diff.rs"]
+mod diff;
+#[path = "../src/docker.rs"]
+mod docker;
+#[path = "../src/http.rs"]
+mod http;
+#[path = "../src/idle.rs"]
+mod idle;
+#[path = "../src/image_ref.rs"]
+mod image_ref;
+#[path = "../src/names.rs"]
+mod names;
+#[path = "../src/platform.rs"]
+mod platform;
+#[path = "../src/ports.rs"]
+mod ports;
+#[path = "../src/registry.rs"]
+mod registry;
+#[path = "../src/template.rs"]
+mod template;
+#[path = "../src/tls.rs"]
+mod tls;
+
+use std::time::Duration;
+
+use http::{retry_delay, ICON_RETRIES, ICON_TIMEOUT};
+
+#[test]
+fn retries_back_off_exponentially_from_a_second() {
+    assert_eq!(retry_delay(1, 3), Some(Duration::from_secs(1)));
+    assert_eq!(retry_delay(2, 3), Some(Duration::from_secs(2)));
+    assert_eq!(retry_delay(3, 3), Some(Duration::from_secs(4)));
+}
+
+#[test]
+fn there_is_no_delay_once_the_retries_are_used_up() {
+    assert_eq!(retry_delay(4, 3), None);
+    assert_eq!(retry_delay(1, 0), None);
+    assert_eq!(retry_delay(u32::MAX, ICON_RETRIES), None);
+}
+
+#[test]
+fn attempts_count_from_one() {
+    // The first fetch isn't a retry, so there is nothing to wait for before it
+    assert_eq!(retry_delay(0, ICON_RETRIES), None);
+}
+
+#[test]
+fn a_failing_icon_gives_up_within_a_bounded_time() {
+    let waited: Duration = (1..)
+        .map_while(|attempt| retry_delay(attempt, ICON_RETRIES))
+        .sum();
+    let requests = ICON_TIMEOUT * (ICON_RETRIES + 1);
+
+    assert_eq!(waited, Duration::from_secs(7));
+    assert!(waited + requests <= Duration::from_secs(20));
+}
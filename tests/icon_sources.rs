@@ -0,0 +1,162 @@
+//! Checks which icons are read from the cache and which are fetched when the icons load, so a
+//! start with every icon cached issues no request.
+#![allow(dead_code)]
+
+#[path = "../src/backup.rs"]
+mod backup;
+#[path = "../src/daemon.rs"]
+mod daemon;
+#[path = "../src/data.rs"]
+mod data;
+#[path = "../src/definition.rs"]
+mod definition;
+#[path = "../src/diff.rs"]
+mod diff;
+#[path = "../src/docker.rs"]
+mod docker;
+#[path = "../src/http.rs"]
+mod http;
+#[path = "../src/idle.rs"]
+mod idle;
+#[path = "../src/image_ref.rs"]
+mod image_ref;
+#[path = "../src/names.rs"]
+mod names;
+#[path = "../src/platform.rs"]
+mod platform;
+#[path = "../src/ports.rs"]
+mod ports;
+#[path = "../src/registry.rs"]
+mod registry;
+#[path = "../src/template.rs"]
+mod template;
+#[path = "../src/app/thumbnails.rs"]
+mod thumbnails;
+#[path = "../src/tls.rs"]
+mod tls;
+
+use std::path::{Path, PathBuf};
+
+use data::DatabaseConfig;
+use http::icon_cache_file;
+use thumbnails::{icon_sources, IconSource};
+
+/// A directory of its own for each test, as they run at once.
+fn scratch(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("db-mgr-icons-{}-{test}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn entry(name: &str, image: &str) -> DatabaseConfig {
+    serde_yaml::from_str(&format!(
+        "
+name: {name}
+image: {image}
+icon_url: https://icons.example/{name}.png
+tags: []
+variables: {{}}
+volumes: {{}}
+"
+    ))
+    .expect("The entry must parse")
+}
+
+fn entries() -> Vec<DatabaseConfig> {
+    vec![entry("postgres", "postgres"), entry("redis", "redis")]
+}
+
+fn cache(dir: &Path, name: &str) -> PathBuf {
+    let file = icon_cache_file(dir, &format!("https://icons.example/{name}.png"));
+    std::fs::write(&file, "cached").unwrap();
+    file
+}
+
+fn fetched(sources: &[(String, IconSource)]) -> Vec<&str> {
+    sources
+        .iter()
+        .filter(|(_, source)| matches!(source, IconSource::Fetch { .. }))
+        .map(|(image, _)| image.as_str())
+        .collect()
+}
+
+#[test]
+fn nothing_is_fetched_when_every_icon_is_cached() {
+    let dir = scratch("all-cached");
+    let postgres = cache(&dir, "postgres");
+    cache(&dir, "redis");
+
+    let sources = icon_sources(&entries(), |_| false, Some(&dir), |_| true);
+
+    assert!(fetched(&sources).is_empty());
+    assert!(sources.contains(&(
+        "docker.io/library/postgres".to_string(),
+        IconSource::Cached(postgres)
+    )));
+}
+
+#[test]
+fn missing_icons_are_fetched_into_the_cache() {
+    let dir = scratch("missing");
+    cache(&dir, "postgres");
+
+    let sources = icon_sources(&entries(), |_| false, Some(&dir), |_| true);
+
+    assert_eq!(fetched(&sources), vec!["docker.io/library/redis"]);
+    assert!(sources.contains(&(
+        "docker.io/library/redis".to_string(),
+        IconSource::Fetch {
+            url: "https://icons.example/redis.png".into(),
+            cache_file: Some(icon_cache_file(&dir, "https://icons.example/redis.png")),
+        }
+    )));
+}
+
+#[test]
+fn invalid_cached_icons_are_fetched_again() {
+    let dir = scratch("invalid");
+    let postgres = cache(&dir, "postgres");
+    cache(&dir, "redis");
+
+    let sources = icon_sources(&entries(), |_| false, Some(&dir), |file| file != postgres);
+
+    assert_eq!(fetched(&sources), vec!["docker.io/library/postgres"]);
+}
+
+#[test]
+fn loaded_icons_are_left_alone() {
+    let sources = icon_sources(
+        &entries(),
+        |image| image == "docker.io/library/postgres",
+        None,
+        |_| panic!("There is no cache to check"),
+    );
+
+    assert_eq!(fetched(&sources), vec!["docker.io/library/redis"]);
+    assert_eq!(sources.len(), 1);
+}
+
+#[test]
+fn icons_are_fetched_without_caching_when_there_is_no_data_directory() {
+    let sources = icon_sources(&entries()[..1], |_| false, None, |_| true);
+
+    assert_eq!(
+        sources,
+        vec![(
+            "docker.io/library/postgres".to_string(),
+            IconSource::Fetch {
+                url: "https://icons.example/postgres.png".into(),
+                cache_file: None,
+            }
+        )]
+    );
+}
+
+#[test]
+fn entries_sharing_a_name_are_loaded_once() {
+    let mut entries = entries();
+    entries.push(entry("postgres", "postgres"));
+
+    assert_eq!(icon_sources(&entries, |_| false, None, |_| true).len(), 2);
+}
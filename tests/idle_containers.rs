@@ -0,0 +1,126 @@
+//! Checks when running containers count as idle, that the user is asked about them at most once a
+//! day, and that the tracked times survive a round trip through the preferences file.
+#![allow(dead_code)]
+
+#[path = "../src/idle.rs"]
+mod idle;
+
+use idle::{format_days, IdleAfter, IdleTracker};
+
+const DAY: u64 = 24 * 60 * 60;
+const START: u64 = 1_700_000_000;
+
+#[test]
+fn running_containers_become_idle_after_the_configured_days() {
+    let mut tracker = IdleTracker::default();
+    tracker.touch("orders", START);
+    let week = IdleAfter(7);
+
+    assert_eq!(
+        tracker.idle_days("orders", true, week, START + 7 * DAY - 1),
+        None
+    );
+    assert_eq!(
+        tracker.idle_days("orders", true, week, START + 7 * DAY),
+        Some(7)
+    );
+    assert_eq!(
+        tracker.idle_days("orders", true, week, START + 15 * DAY + 5),
+        Some(15)
+    );
+}
+
+#[test]
+fn stopped_unknown_and_ignored_containers_are_never_idle() {
+    let mut tracker = IdleTracker::default();
+    tracker.touch("orders", START);
+    let later = START + 30 * DAY;
+
+    assert_eq!(
+        tracker.idle_days("orders", false, IdleAfter(7), later),
+        None
+    );
+    assert_eq!(
+        tracker.idle_days("unknown", true, IdleAfter(7), later),
+        None
+    );
+    assert_eq!(tracker.idle_days("orders", true, IdleAfter(0), later), None);
+
+    tracker.ignore("orders");
+    assert_eq!(tracker.idle_days("orders", true, IdleAfter(7), later), None);
+}
+
+#[test]
+fn using_a_container_restarts_the_clock() {
+    let mut tracker = IdleTracker::default();
+    tracker.touch("orders", START);
+    tracker.touch("orders", START + 6 * DAY);
+
+    assert_eq!(
+        tracker.idle_days("orders", true, IdleAfter(7), START + 10 * DAY),
+        None
+    );
+}
+
+#[test]
+fn prompts_are_throttled_to_once_a_day() {
+    let mut tracker = IdleTracker::default();
+    assert!(tracker.may_prompt("orders", START));
+
+    tracker.prompted("orders", START);
+    assert!(!tracker.may_prompt("orders", START + DAY - 1));
+    assert!(tracker.may_prompt("orders", START + DAY));
+    // Other containers have their own throttle
+    assert!(tracker.may_prompt("billing", START + 1));
+}
+
+#[test]
+fn syncing_starts_new_containers_and_forgets_removed_ones() {
+    let mut tracker = IdleTracker::default();
+    assert!(tracker.sync(["orders", "billing"], START));
+    tracker.prompted("billing", START);
+    tracker.ignore("billing");
+
+    assert!(!tracker.sync(["orders", "billing"], START + DAY));
+    assert_eq!(
+        tracker.idle_days("orders", true, IdleAfter(3), START + 3 * DAY),
+        Some(3)
+    );
+
+    assert!(tracker.sync(["orders"], START + 2 * DAY));
+    // A new container with the old id is tracked from scratch
+    assert!(tracker.sync(["orders", "billing"], START + 2 * DAY));
+    assert!(tracker.may_prompt("billing", START + 2 * DAY));
+    assert_eq!(
+        tracker.idle_days("billing", true, IdleAfter(3), START + 5 * DAY),
+        Some(3)
+    );
+}
+
+#[test]
+fn tracked_times_round_trip_through_yaml() {
+    let mut tracker = IdleTracker::default();
+    tracker.touch("orders", START);
+    tracker.prompted("orders", START + DAY);
+    tracker.ignore("billing");
+
+    let yaml = serde_yaml::to_string(&tracker).unwrap();
+    assert_eq!(serde_yaml::from_str::<IdleTracker>(&yaml).unwrap(), tracker);
+    // Preferences written before idle tracking have none of the fields
+    assert_eq!(
+        serde_yaml::from_str::<IdleTracker>("{}").unwrap(),
+        IdleTracker::default()
+    );
+    assert_eq!(
+        serde_yaml::from_str::<IdleAfter>("14").unwrap(),
+        IdleAfter(14)
+    );
+}
+
+#[test]
+fn idle_thresholds_are_shown_in_days() {
+    assert_eq!(IdleAfter(0).to_string(), "Never");
+    assert_eq!(IdleAfter(1).to_string(), "1 day");
+    assert_eq!(IdleAfter::default().to_string(), "7 days");
+    assert_eq!(format_days(14), "14 days");
+}
@@ -0,0 +1,139 @@
+//! Checks the notes shown next to the tag picker: configs written before they existed still
+//! load, and the notes are split into the lines the create form styles.
+#![allow(dead_code)]
+
+#[path = "../src/backup.rs"]
+mod backup;
+#[path = "../src/daemon.rs"]
+mod daemon;
+#[path = "../src/data.rs"]
+mod data;
+#[path = "../src/definition.rs"]
+mod definition;
+#[path = "../src/diff.rs"]
+mod diff;
+#[path = "../src/docker.rs"]
+mod docker;
+#[path = "../src/idle.rs"]
+mod idle;
+#[path = "../src/image_ref.rs"]
+mod image_ref;
+#[path = "../src/names.rs"]
+mod names;
+#[path = "../src/app/notes.rs"]
+mod notes;
+#[path = "../src/platform.rs"]
+mod platform;
+#[path = "../src/ports.rs"]
+mod ports;
+#[path = "../src/registry.rs"]
+mod registry;
+#[path = "../src/template.rs"]
+mod template;
+#[path = "../src/tls.rs"]
+mod tls;
+
+use data::{ConfigFile, DatabaseConfig};
+use notes::{note_lines, NoteLine};
+
+/// An entry as configs were written before notes existed.
+const WITHOUT_NOTES: &str = "
+name: Redis
+image: redis
+icon_url: https://example.com/redis.png
+tags: [\"7\", 7-alpine]
+variables: {}
+volumes:
+  redis_data: /data
+";
+
+fn entry(yaml: &str) -> DatabaseConfig {
+    serde_yaml::from_str(yaml).expect("The entry must parse")
+}
+
+#[test]
+fn entries_without_notes_still_load() {
+    let redis = entry(WITHOUT_NOTES);
+    assert_eq!(redis.notes, None);
+    assert!(redis.tag_notes.is_empty());
+    assert!(!redis.has_notes());
+    assert!(redis.notes_for("7").is_empty());
+}
+
+#[test]
+fn notes_of_the_image_come_before_the_tag() {
+    let redis = entry(&format!(
+        "{WITHOUT_NOTES}notes: Persists to /data.\ntag_notes:\n  7-alpine: Smaller, uses musl.\n  \"7\": \"  \"\n"
+    ));
+    assert!(redis.has_notes());
+    assert_eq!(
+        redis.notes_for("7-alpine"),
+        vec!["Persists to /data.", "Smaller, uses musl."]
+    );
+    // Blank notes are left out
+    assert_eq!(redis.notes_for("7"), vec!["Persists to /data."]);
+
+    let tag_only = entry(&format!(
+        "{WITHOUT_NOTES}tag_notes:\n  7-alpine: Smaller, uses musl.\n"
+    ));
+    assert!(tag_only.has_notes());
+    assert!(tag_only.notes_for("7").is_empty());
+}
+
+#[test]
+fn the_bundled_tags_have_notes() {
+    let config = serde_yaml::from_str::<ConfigFile>(include_str!("../config.yaml"))
+        .expect("The bundled config must parse");
+    assert_eq!(
+        config
+            .databases
+            .iter()
+            .map(|database| database.name.as_str())
+            .collect::<Vec<_>>(),
+        vec!["Postgres", "Redis", "MySQL", "MariaDB", "MongoDB"]
+    );
+
+    for database in config.databases.iter() {
+        for tag in database.tags.iter() {
+            assert_eq!(database.notes_for(tag).len(), 2, "{} {tag}", database.name);
+        }
+        assert_eq!(database.tags[0], "latest");
+    }
+}
+
+#[test]
+fn markdown_headings_and_lists_are_told_apart() {
+    let lines = note_lines(
+        "
+# Alpine
+Smaller than the
+**Debian** image.
+
+
+- Uses `musl`
+* No apt
+#hashtag
+",
+    );
+    assert_eq!(
+        lines,
+        vec![
+            NoteLine::Heading("Alpine".into()),
+            NoteLine::Text("Smaller than the Debian image.".into()),
+            NoteLine::Gap,
+            NoteLine::Bullet("Uses musl".into()),
+            NoteLine::Bullet("No apt".into()),
+            NoteLine::Text("#hashtag".into()),
+        ]
+    );
+
+    assert_eq!(
+        note_lines("## Tags\n\nPlain"),
+        vec![
+            NoteLine::Heading("Tags".into()),
+            NoteLine::Gap,
+            NoteLine::Text("Plain".into()),
+        ]
+    );
+    assert!(note_lines("\n\n").is_empty());
+}
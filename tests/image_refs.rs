@@ -0,0 +1,159 @@
+//! Checks that image references are split into registry, repository, tag and digest the way
+//! docker reads them.
+#![allow(dead_code)]
+
+#[path = "../src/image_ref.rs"]
+mod image_ref;
+
+use image_ref::{built_image_name, ImageRef};
+
+const DIGEST: &str = "sha256:4c5f8c7a1b2d3e4f5a6b7c8d9e0f1a2b3c4d5e6f7a8b9c0d1e2f3a4b5c6d7e8f";
+
+fn image(
+    registry: Option<&str>,
+    repository: &str,
+    tag: Option<&str>,
+    digest: Option<&str>,
+) -> ImageRef {
+    ImageRef {
+        registry: registry.map(String::from),
+        repository: repository.into(),
+        tag: tag.map(String::from),
+        digest: digest.map(String::from),
+    }
+}
+
+#[test]
+fn official_images_get_the_library_namespace() {
+    assert_eq!(
+        ImageRef::parse("postgres:16"),
+        image(None, "library/postgres", Some("16"), None)
+    );
+    assert_eq!(
+        ImageRef::parse("docker.io/postgres:16"),
+        image(None, "library/postgres", Some("16"), None)
+    );
+    assert_eq!(
+        ImageRef::parse("docker.io/library/postgres:16"),
+        image(None, "library/postgres", Some("16"), None)
+    );
+    // Images of a user or organisation keep their namespace
+    assert_eq!(
+        ImageRef::parse("dpage/pgadmin4:8"),
+        image(None, "dpage/pgadmin4", Some("8"), None)
+    );
+}
+
+#[test]
+fn missing_tags_default_to_latest() {
+    assert_eq!(
+        ImageRef::parse("postgres"),
+        image(None, "library/postgres", Some("latest"), None)
+    );
+    assert_eq!(
+        ImageRef::parse("ghcr.io/org/pg"),
+        image(Some("ghcr.io"), "org/pg", Some("latest"), None)
+    );
+    assert_eq!(ImageRef::parse("redis").reference(), "latest");
+}
+
+#[test]
+fn registries_can_have_a_port() {
+    assert_eq!(
+        ImageRef::parse("ghcr.io:5000/org/pg:16"),
+        image(Some("ghcr.io:5000"), "org/pg", Some("16"), None)
+    );
+    // Without a tag the port isn't mistaken for one
+    assert_eq!(
+        ImageRef::parse("localhost:5000/pg"),
+        image(Some("localhost:5000"), "pg", Some("latest"), None)
+    );
+    assert_eq!(
+        ImageRef::parse("localhost/pg:16"),
+        image(Some("localhost"), "pg", Some("16"), None)
+    );
+    let with_port = ImageRef::parse("registry.example.com:443/team/db/pg:16-alpine");
+    assert_eq!(with_port.name(), "registry.example.com:443/team/db/pg");
+    assert_eq!(with_port.reference(), "16-alpine");
+}
+
+#[test]
+fn digests_pin_the_image() {
+    let pinned = ImageRef::parse(&format!("postgres@{DIGEST}"));
+    assert_eq!(pinned, image(None, "library/postgres", None, Some(DIGEST)));
+    assert_eq!(pinned.reference(), DIGEST);
+    assert_eq!(pinned.to_string(), format!("postgres@{DIGEST}"));
+
+    let registry = ImageRef::parse(&format!("ghcr.io:5000/org/pg@{DIGEST}"));
+    assert_eq!(
+        registry,
+        image(Some("ghcr.io:5000"), "org/pg", None, Some(DIGEST))
+    );
+}
+
+#[test]
+fn tags_and_digests_can_be_given_together() {
+    let both = ImageRef::parse(&format!("postgres:16@{DIGEST}"));
+    assert_eq!(
+        both,
+        image(None, "library/postgres", Some("16"), Some(DIGEST))
+    );
+    // The digest is what gets pulled
+    assert_eq!(both.reference(), DIGEST);
+    assert_eq!(both.to_string(), format!("postgres:16@{DIGEST}"));
+}
+
+#[test]
+fn references_print_as_they_were_written() {
+    for reference in [
+        "postgres:16",
+        "dpage/pgadmin4:latest",
+        "ghcr.io:5000/org/pg:16",
+        "localhost:5000/pg:dev",
+    ] {
+        assert_eq!(ImageRef::parse(reference).to_string(), reference);
+    }
+    assert_eq!(
+        ImageRef::parse("docker.io/postgres").to_string(),
+        "postgres:latest"
+    );
+}
+
+#[test]
+fn tags_of_the_config_can_be_digests() {
+    let postgres = ImageRef::parse("postgres");
+
+    let tagged = postgres.with_reference("16");
+    assert_eq!(
+        (tagged.tag.as_deref(), tagged.digest.as_deref()),
+        (Some("16"), None)
+    );
+
+    for pin in [DIGEST.to_string(), format!("@{DIGEST}")] {
+        let pinned = postgres.with_reference(&pin);
+        assert_eq!(pinned.tag, None);
+        assert_eq!(pinned.digest.as_deref(), Some(DIGEST));
+        assert_eq!(pinned.to_string(), format!("postgres@{DIGEST}"));
+    }
+}
+
+#[test]
+fn keys_ignore_the_tag_and_digest() {
+    let key = ImageRef::parse("postgres:16").key();
+    assert_eq!(key, "docker.io/library/postgres");
+    assert_eq!(ImageRef::parse(&format!("postgres@{DIGEST}")).key(), key);
+    assert_eq!(ImageRef::parse("docker.io/library/postgres").key(), key);
+    assert_eq!(
+        ImageRef::parse("ghcr.io:5000/org/pg:16").key(),
+        "ghcr.io:5000/org/pg"
+    );
+}
+
+#[test]
+fn built_images_are_named_after_their_entry() {
+    assert_eq!(
+        built_image_name("Postgres pgvector"),
+        "db-mgr/postgres-pgvector"
+    );
+    assert_eq!(built_image_name("--"), "db-mgr/custom");
+}
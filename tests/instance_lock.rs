@@ -0,0 +1,157 @@
+//! Checks the lock file that keeps db-mgr to a single running instance, when a lock is stale and
+//! taken over, and that focus requests reach the running instance.
+#![allow(dead_code)]
+
+#[path = "../src/instance.rs"]
+mod instance;
+
+use std::{fs, path::PathBuf, time::Duration};
+
+use futures::StreamExt;
+use instance::{acquire, claim, focus_requests, request_focus, Acquired, Claim, LockOwner};
+
+const OWNER: LockOwner = LockOwner {
+    pid: 4242,
+    port: 50123,
+};
+
+const OTHER: LockOwner = LockOwner {
+    pid: 4343,
+    port: 50124,
+};
+
+fn temp_dir(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("db-mgr-instance-{}-{test}", std::process::id()));
+    _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).expect("The temp dir can be created");
+    dir
+}
+
+fn owner(path: &PathBuf) -> Option<LockOwner> {
+    LockOwner::parse(&fs::read_to_string(path).ok()?)
+}
+
+#[test]
+fn lock_files_are_parsed() {
+    assert_eq!(LockOwner::parse("4242 50123\n"), Some(OWNER));
+    assert_eq!(LockOwner::parse("  4242 50123  "), Some(OWNER));
+    assert_eq!(LockOwner::parse("4242"), None);
+    assert_eq!(LockOwner::parse("4242 port"), None);
+    assert_eq!(LockOwner::parse(""), None);
+}
+
+#[test]
+fn the_first_instance_takes_the_lock() {
+    let dir = temp_dir("first");
+    let path = dir.join("instance.lock");
+
+    let lock = acquire(&path, OWNER, |_| true).expect("The lock can be written");
+    assert!(matches!(lock, Acquired::Lock(_)));
+    assert_eq!(owner(&path), Some(OWNER));
+
+    drop(lock);
+    assert!(!path.exists());
+    _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn a_live_instance_keeps_its_lock() {
+    let dir = temp_dir("live");
+    let path = dir.join("instance.lock");
+    let _lock = acquire(&path, OTHER, |_| true).unwrap();
+
+    match acquire(&path, OWNER, |pid| pid == OTHER.pid).unwrap() {
+        Acquired::Running(running) => assert_eq!(running, OTHER),
+        other => panic!("Expected the running instance, got {other:?}"),
+    }
+    assert_eq!(owner(&path), Some(OTHER));
+    _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn locks_of_dead_processes_are_taken_over() {
+    let dir = temp_dir("dead");
+    let path = dir.join("instance.lock");
+    let stale = acquire(&path, OTHER, |_| true).unwrap();
+
+    let lock = acquire(&path, OWNER, |_| false).unwrap();
+    assert!(matches!(lock, Acquired::Lock(_)));
+    assert_eq!(owner(&path), Some(OWNER));
+
+    // The stale owner going away leaves the lock it lost alone
+    drop(stale);
+    assert_eq!(owner(&path), Some(OWNER));
+    drop(lock);
+    _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn unreadable_locks_and_our_own_pid_are_stale() {
+    let dir = temp_dir("unreadable");
+    let path = dir.join("instance.lock");
+
+    fs::write(&path, "garbage").unwrap();
+    assert!(matches!(
+        acquire(&path, OWNER, |_| true).unwrap(),
+        Acquired::Lock(_)
+    ));
+
+    // A pid reused by this process after a crash is not another instance
+    fs::write(&path, "4242 40000\n").unwrap();
+    assert!(matches!(
+        acquire(&path, OWNER, |_| true).unwrap(),
+        Acquired::Lock(_)
+    ));
+    _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn claiming_in_an_unusable_directory_skips_the_check() {
+    let dir = temp_dir("unusable");
+    let file = dir.join("file");
+    fs::write(&file, "").unwrap();
+
+    assert!(matches!(
+        claim(&file.join("instance.lock")),
+        Claim::Unchecked(_)
+    ));
+    _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn claiming_makes_this_the_single_instance() {
+    let dir = temp_dir("claim");
+    let path = dir.join("nested").join("instance.lock");
+
+    match claim(&path) {
+        Claim::Single(_lock, listener) => assert_eq!(
+            owner(&path).map(|owner| owner.port),
+            Some(listener.local_addr().unwrap().port())
+        ),
+        other => panic!("Expected the lock, got {other:?}"),
+    }
+    assert!(!path.exists());
+    _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn focus_requests_reach_the_running_instance() {
+    let dir = temp_dir("focus");
+    let Claim::Single(_lock, listener) = claim(&dir.join("instance.lock")) else {
+        panic!("Expected the lock");
+    };
+    let port = listener.local_addr().unwrap().port();
+
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    runtime.block_on(async {
+        let requests = focus_requests(&listener);
+        tokio::task::spawn_blocking(move || request_focus(port))
+            .await
+            .unwrap()
+            .expect("The running instance takes the request");
+
+        let received = tokio::time::timeout(Duration::from_secs(5), requests.boxed().next()).await;
+        assert_eq!(received, Ok(Some(())));
+    });
+    _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,157 @@
+//! Checks which containers created with an older label scheme are offered a metadata update, and
+//! that the update recreates them unchanged apart from their labels.
+#![allow(dead_code)]
+
+#[path = "../src/backup.rs"]
+mod backup;
+mod common;
+#[path = "../src/daemon.rs"]
+mod daemon;
+#[path = "../src/data.rs"]
+mod data;
+#[path = "../src/definition.rs"]
+mod definition;
+#[path = "../src/diff.rs"]
+mod diff;
+#[path = "../src/docker.rs"]
+mod docker;
+#[path = "../src/idle.rs"]
+mod idle;
+#[path = "../src/image_ref.rs"]
+mod image_ref;
+#[path = "../src/migrate.rs"]
+mod migrate;
+#[path = "../src/names.rs"]
+mod names;
+#[path = "../src/platform.rs"]
+mod platform;
+#[path = "../src/ports.rs"]
+mod ports;
+#[path = "../src/registry.rs"]
+mod registry;
+#[path = "../src/template.rs"]
+mod template;
+#[path = "../src/tls.rs"]
+mod tls;
+
+use bollard::service::{ContainerStateStatusEnum, ContainerSummary};
+use common::postgres;
+use docker::{
+    summary_container, DbContainer, EnvValue, DATABASE_LABEL, LABEL_SCHEMA_VERSION, SCHEMA_LABEL,
+};
+use migrate::{migration_config, plan_migration, schema_version, MigrationPlan};
+
+fn container(labels: &[(&str, &str)]) -> DbContainer {
+    let mut container = summary_container(ContainerSummary {
+        id: Some("c1".into()),
+        names: Some(vec!["/db-mgr__orders".into()]),
+        image: Some("postgres:16-alpine".into()),
+        labels: Some(
+            labels
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+        ),
+        ..Default::default()
+    })
+    .expect("The summary has an id");
+    container.state = ContainerStateStatusEnum::RUNNING;
+    container.variables = [
+        ("POSTGRES_PASSWORD", EnvValue::new("hunter2")),
+        ("CERT", EnvValue::LargeOrBinary(9000)),
+    ]
+    .into_iter()
+    .map(|(key, value)| (key.to_string(), value))
+    .collect();
+    container.volumes = [(
+        "orders_data".to_string(),
+        "/var/lib/postgresql/data".to_string(),
+    )]
+    .into_iter()
+    .collect();
+    container.command = vec!["postgres".into(), "-c".into(), "fsync=off".into()];
+    container
+}
+
+#[test]
+fn current_containers_need_no_update() {
+    let version = LABEL_SCHEMA_VERSION.to_string();
+    let current = container(&[(SCHEMA_LABEL, &version), (DATABASE_LABEL, "Postgres")]);
+
+    assert_eq!(schema_version(&current), Some(LABEL_SCHEMA_VERSION));
+    assert_eq!(plan_migration(&current, Some(&postgres())), None);
+
+    // Created by a newer version than this one
+    let newer = (LABEL_SCHEMA_VERSION + 1).to_string();
+    assert_eq!(
+        plan_migration(&container(&[(SCHEMA_LABEL, &newer)]), None),
+        None
+    );
+}
+
+#[test]
+fn containers_without_a_schema_record_one() {
+    let legacy = container(&[]);
+    assert_eq!(schema_version(&legacy), None);
+
+    let plan = plan_migration(&legacy, Some(&postgres())).expect("It is legacy");
+    assert_eq!(
+        plan,
+        MigrationPlan {
+            from: None,
+            changes: vec![
+                format!("Record the label schema version v{LABEL_SCHEMA_VERSION}"),
+                "Record that it was created from Postgres".into(),
+                "CERT will not be copied, their values are too large or binary".into(),
+            ],
+        }
+    );
+    assert!(plan
+        .explanation()
+        .starts_with("Created by an older version of db-mgr"));
+}
+
+#[test]
+fn older_and_unreadable_schemas_are_updated() {
+    let older = container(&[(SCHEMA_LABEL, "0"), (DATABASE_LABEL, "Postgres")]);
+    let plan = plan_migration(&older, None).expect("It is older");
+    assert_eq!(plan.from, Some(0));
+    assert_eq!(
+        plan.changes[0],
+        format!("Update the label schema from v0 to v{LABEL_SCHEMA_VERSION}")
+    );
+    // The entry is already recorded
+    assert_eq!(plan.changes.len(), 2);
+    assert!(plan.explanation().starts_with("Created by label schema v0"));
+
+    let garbled = container(&[(SCHEMA_LABEL, "one")]);
+    assert_eq!(schema_version(&garbled), None);
+    assert_eq!(
+        plan_migration(&garbled, None).unwrap().changes[1],
+        "Its config entry can't be found, it will be recorded as unknown"
+    );
+}
+
+#[test]
+fn updates_recreate_the_container_as_it_is() {
+    let legacy = container(&[]);
+    let config = migration_config(&legacy, Some(&postgres()));
+
+    assert_eq!(config.name, "db-mgr__orders");
+    assert_eq!(config.database, "Postgres");
+    assert_eq!(
+        (config.image.as_str(), config.tag.as_str()),
+        ("postgres", "16-alpine")
+    );
+    assert_eq!(config.volumes, legacy.volumes);
+    assert!(config.reuse_volumes);
+    assert!(config.start_after_create);
+    assert_eq!(config.command, legacy.command);
+    assert_eq!(config.variables["POSTGRES_PASSWORD"], "hunter2");
+    assert!(!config.variables.contains_key("CERT"));
+
+    // Without the entry the recorded name is kept
+    let labelled = container(&[(DATABASE_LABEL, "Old name")]);
+    assert_eq!(migration_config(&labelled, None).database, "Old name");
+    assert_eq!(migration_config(&container(&[]), None).database, "");
+}
@@ -0,0 +1,175 @@
+//! Checks which log drivers the logs tab can read, the rotated json-file default of new
+//! containers and how the driver reaches docker and is read back.
+#![allow(dead_code)]
+
+#[path = "../src/backup.rs"]
+mod backup;
+mod common;
+#[path = "../src/daemon.rs"]
+mod daemon;
+#[path = "../src/data.rs"]
+mod data;
+#[path = "../src/definition.rs"]
+mod definition;
+#[path = "../src/diff.rs"]
+mod diff;
+#[path = "../src/docker.rs"]
+mod docker;
+#[path = "../src/idle.rs"]
+mod idle;
+#[path = "../src/image_ref.rs"]
+mod image_ref;
+#[path = "../src/names.rs"]
+mod names;
+#[path = "../src/platform.rs"]
+mod platform;
+#[path = "../src/ports.rs"]
+mod ports;
+#[path = "../src/registry.rs"]
+mod registry;
+#[path = "../src/template.rs"]
+mod template;
+#[path = "../src/tls.rs"]
+mod tls;
+
+use std::collections::HashMap;
+
+use bollard::service::ContainerInspectResponse;
+use common::postgres;
+use docker::{
+    inspected_container, log_support, plan_container, LogSettings, LogSupport, LOG_DRIVERS,
+    MANAGED_LABELS,
+};
+use serde_json::{json, Value};
+
+fn settings(driver: &str, options: &[(&str, &str)]) -> LogSettings {
+    LogSettings {
+        driver: driver.into(),
+        options: options
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect(),
+    }
+}
+
+fn create_body(log: LogSettings) -> Value {
+    let mut config = postgres().quick_config("db-mgr__orders");
+    config.log = log;
+    plan_container(&config, MANAGED_LABELS)
+        .into_iter()
+        .find(|op| op.call.starts_with("POST /containers/create"))
+        .and_then(|op| op.body)
+        .expect("The plan creates the container")
+}
+
+fn inspect(log_config: Option<Value>) -> ContainerInspectResponse {
+    let mut host_config = json!({});
+    if let Some(log_config) = log_config {
+        host_config["LogConfig"] = log_config;
+    }
+    serde_json::from_value(json!({
+        "Id": "4f3c2a1b9e8d",
+        "Name": "/db-mgr__orders",
+        "State": { "Status": "running", "Running": true },
+        "Config": { "Image": "postgres:16", "Labels": { "db-mgr-resource": "container" } },
+        "HostConfig": host_config
+    }))
+    .expect("The captured JSON must parse")
+}
+
+#[test]
+fn drivers_docker_reads_back_have_full_logs() {
+    for driver in ["json-file", "local", "journald"] {
+        assert_eq!(log_support(driver), LogSupport::Full, "{driver}");
+    }
+    // Containers whose driver wasn't reported use the daemon's default
+    assert_eq!(log_support(""), LogSupport::Full);
+}
+
+#[test]
+fn the_none_driver_keeps_nothing() {
+    assert_eq!(log_support("none"), LogSupport::Unavailable);
+}
+
+#[test]
+fn other_drivers_only_have_the_cached_logs() {
+    for driver in ["syslog", "fluentd", "gelf", "awslogs", "splunk"] {
+        assert_eq!(log_support(driver), LogSupport::Cached, "{driver}");
+    }
+}
+
+#[test]
+fn every_offered_driver_is_known() {
+    assert!(LOG_DRIVERS.contains(&"json-file"));
+    assert_eq!(log_support(LOG_DRIVERS[0]), LogSupport::Full);
+}
+
+#[test]
+fn new_containers_rotate_their_logs() {
+    let log = LogSettings::default();
+    assert_eq!(postgres().quick_config("db-mgr__orders").log, log);
+
+    assert_eq!(
+        log,
+        settings("json-file", &[("max-size", "10m"), ("max-file", "3")])
+    );
+    assert!(log.rotates());
+    assert_eq!(log.to_string(), "json-file, max-file 3, max-size 10m");
+}
+
+#[test]
+fn only_json_file_and_local_rotate() {
+    assert!(settings("local", &[]).rotates());
+    assert!(!settings("journald", &[]).rotates());
+    assert!(!settings("none", &[]).rotates());
+    assert_eq!(settings("none", &[]).to_string(), "none");
+}
+
+#[test]
+fn empty_options_are_removed() {
+    let mut log = LogSettings::default();
+    log.set_option("max-size", " 50m ");
+    log.set_option("max-file", "");
+
+    assert_eq!(log.option("max-size"), Some("50m"));
+    assert_eq!(log.option("max-file"), None);
+}
+
+#[test]
+fn the_driver_is_given_to_docker() {
+    assert_eq!(
+        create_body(LogSettings::default())["HostConfig"]["LogConfig"],
+        json!({ "Type": "json-file", "Config": { "max-size": "10m", "max-file": "3" } })
+    );
+    assert_eq!(
+        create_body(settings("none", &[]))["HostConfig"]["LogConfig"],
+        json!({ "Type": "none" })
+    );
+}
+
+#[test]
+fn the_driver_is_read_back_from_the_inspect() {
+    let container = inspected_container(inspect(Some(json!({
+        "Type": "journald",
+        "Config": { "tag": "orders" }
+    }))))
+    .expect("The inspect has an id");
+
+    assert_eq!(
+        container.log,
+        Some(LogSettings {
+            driver: "journald".into(),
+            options: HashMap::from([("tag".to_string(), "orders".to_string())]),
+        })
+    );
+}
+
+#[test]
+fn unreported_drivers_are_left_unknown() {
+    let container = inspected_container(inspect(None)).expect("The inspect has an id");
+    assert_eq!(container.log, None);
+
+    let container =
+        inspected_container(inspect(Some(json!({ "Config": {} })))).expect("The inspect has an id");
+    assert_eq!(container.log, None);
+}
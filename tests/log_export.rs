@@ -0,0 +1,129 @@
+//! Checks how exported logs are laid out, interleaved in one file or split per stream, with
+//! writers in memory.
+#![allow(dead_code)]
+
+#[path = "../src/daemon.rs"]
+mod daemon;
+#[path = "../src/docker.rs"]
+mod docker;
+#[path = "../src/image_ref.rs"]
+mod image_ref;
+#[path = "../src/platform.rs"]
+mod platform;
+#[path = "../src/ports.rs"]
+mod ports;
+
+use std::path::PathBuf;
+
+use bollard::container::LogOutput;
+use docker::{LogFiles, LogRange};
+
+fn stdout(line: &str) -> LogOutput {
+    LogOutput::StdOut {
+        message: line.as_bytes().to_vec().into(),
+    }
+}
+
+fn stderr(line: &str) -> LogOutput {
+    LogOutput::StdErr {
+        message: line.as_bytes().to_vec().into(),
+    }
+}
+
+fn output() -> Vec<LogOutput> {
+    vec![
+        stdout("2024-05-01T10:00:00Z ready to accept connections\n"),
+        stderr("2024-05-01T10:00:01Z FATAL: password authentication failed\n"),
+        stdout(
+            "2024-05-01T10:00:02Z checkpoint starting\n2024-05-01T10:00:03Z checkpoint complete\n",
+        ),
+    ]
+}
+
+/// Writes `output` like an export does, returning the line and byte counts.
+fn write(files: &mut LogFiles<Vec<u8>>, output: Vec<LogOutput>) -> (usize, u64) {
+    output.into_iter().fold((0, 0), |(lines, bytes), output| {
+        let (written_lines, written_bytes) = files.write_output(output).unwrap();
+        (lines + written_lines, bytes + written_bytes)
+    })
+}
+
+#[test]
+fn interleaved_lines_are_prefixed_with_their_stream() {
+    let mut files = LogFiles::Interleaved(vec![]);
+    let (lines, bytes) = write(&mut files, output());
+
+    let LogFiles::Interleaved(file) = files else {
+        panic!("The layout doesn't change");
+    };
+    let file = String::from_utf8(file).unwrap();
+    assert_eq!(
+        file,
+        "stdout | 2024-05-01T10:00:00Z ready to accept connections\n\
+         stderr | 2024-05-01T10:00:01Z FATAL: password authentication failed\n\
+         stdout | 2024-05-01T10:00:02Z checkpoint starting\n2024-05-01T10:00:03Z checkpoint complete\n"
+    );
+    assert_eq!(lines, 4);
+    assert_eq!(bytes, file.len() as u64);
+}
+
+#[test]
+fn split_streams_go_to_their_own_file() {
+    let mut files = LogFiles::Split {
+        stdout: vec![],
+        stderr: vec![],
+    };
+    let (lines, bytes) = write(&mut files, output());
+
+    let LogFiles::Split { stdout, stderr } = files else {
+        panic!("The layout doesn't change");
+    };
+    assert_eq!(
+        String::from_utf8(stderr.clone()).unwrap(),
+        "2024-05-01T10:00:01Z FATAL: password authentication failed\n"
+    );
+    assert!(String::from_utf8(stdout.clone())
+        .unwrap()
+        .starts_with("2024-05-01T10:00:00Z ready"));
+    assert_eq!(lines, 4);
+    assert_eq!(bytes, (stdout.len() + stderr.len()) as u64);
+}
+
+#[test]
+fn output_without_a_newline_counts_as_a_line() {
+    let mut files = LogFiles::Interleaved(vec![]);
+    assert_eq!(write(&mut files, vec![stdout("no newline")]).0, 1);
+}
+
+#[test]
+fn split_exports_name_a_file_per_stream() {
+    assert_eq!(
+        LogFiles::new(PathBuf::from("/tmp/pg.log"), true),
+        LogFiles::Split {
+            stdout: PathBuf::from("/tmp/pg.stdout.log"),
+            stderr: PathBuf::from("/tmp/pg.stderr.log"),
+        }
+    );
+    assert_eq!(
+        LogFiles::new(PathBuf::from("/tmp/pg"), true)
+            .iter()
+            .collect::<Vec<_>>(),
+        vec![
+            &PathBuf::from("/tmp/pg.stdout"),
+            &PathBuf::from("/tmp/pg.stderr")
+        ]
+    );
+    assert_eq!(
+        LogFiles::new(PathBuf::from("/tmp/pg.log"), false),
+        LogFiles::Interleaved(PathBuf::from("/tmp/pg.log"))
+    );
+}
+
+#[test]
+fn ranges_start_back_from_now() {
+    let now = 1_714_557_600;
+    assert_eq!(LogRange::Last15Minutes.since(now), now - 900);
+    assert_eq!(LogRange::LastHour.since(now), now - 3600);
+    assert_eq!(LogRange::LastDay.since(now), now - 86_400);
+    assert_eq!(LogRange::All.since(now), 0);
+}
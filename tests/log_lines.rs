@@ -0,0 +1,170 @@
+//! Checks how followed log lines are split from docker's timestamps, which severity postgres and
+//! mysql lines are read as, and how the kept lines are filtered.
+#![allow(dead_code)]
+
+#[path = "../src/app/logs.rs"]
+mod logs;
+
+use chrono::{TimeZone, Utc};
+use logs::{split_timestamp, LogFilter, LogFollow, LogLine, Severity, MAX_LINES};
+
+const POSTGRES: &str = "\
+2024-03-05T09:12:01.123456789Z 2024-03-05 09:12:01.123 UTC [1] LOG:  starting PostgreSQL 16.2 on x86_64-pc-linux-gnu
+2024-03-05T09:12:01.200000000Z 2024-03-05 09:12:01.200 UTC [1] LOG:  database system is ready to accept connections
+2024-03-05T09:13:44.000000000Z 2024-03-05 09:13:44.000 UTC [57] ERROR:  relation \"orders\" does not exist at character 15
+2024-03-05T09:13:44.000000001Z 2024-03-05 09:13:44.000 UTC [57] STATEMENT:  SELECT * FROM orders;
+2024-03-05T09:14:02.000000000Z 2024-03-05 09:14:02.000 UTC [58] FATAL:  password authentication failed for user \"postgres\"
+2024-03-05T09:15:00.000000000Z 2024-03-05 09:15:00.000 UTC [59] WARNING:  there is no transaction in progress";
+
+const MYSQL: &str = "\
+2024-03-05T09:12:01.000000000Z 2024-03-05T09:12:01.000000Z 0 [System] [MY-010116] [Server] /usr/sbin/mysqld (mysqld 8.3.0) starting as process 1
+2024-03-05T09:12:02.000000000Z 2024-03-05T09:12:02.000000Z 1 [Warning] [MY-010068] [Server] CA certificate ca.pem is self signed.
+2024-03-05T09:12:03.000000000Z 2024-03-05T09:12:03.000000Z 0 [Note] [MY-011323] [Server] X Plugin ready for connections.
+2024-03-05T09:13:00.000000000Z 2024-03-05T09:13:00.000000Z 8 [ERROR] [MY-010584] [Repl] Replica SQL: Error executing row event";
+
+fn lines(logs: &str) -> Vec<LogLine> {
+    logs.lines()
+        .map(|line| LogLine::parse(line, false))
+        .collect()
+}
+
+#[test]
+fn docker_timestamps_are_split_from_the_line() {
+    let (time, text) = split_timestamp("2024-03-05T09:12:01.5Z LOG:  checkpoint starting: time");
+
+    assert_eq!(
+        time,
+        Some(
+            Utc.with_ymd_and_hms(2024, 3, 5, 9, 12, 1).unwrap()
+                + chrono::Duration::milliseconds(500)
+        )
+    );
+    assert_eq!(text, "LOG:  checkpoint starting: time");
+}
+
+#[test]
+fn lines_without_a_timestamp_are_kept_whole() {
+    assert_eq!(
+        split_timestamp("LOG:  checkpoint starting: time"),
+        (None, "LOG:  checkpoint starting: time")
+    );
+    assert_eq!(split_timestamp(""), (None, ""));
+}
+
+#[test]
+fn trailing_line_breaks_are_dropped() {
+    let line = LogLine::parse("2024-03-05T09:12:01Z ready\r\n", true);
+
+    assert_eq!(line.text, "ready");
+    assert!(line.stderr);
+}
+
+#[test]
+fn postgres_levels_are_read() {
+    let severities = lines(POSTGRES)
+        .into_iter()
+        .map(|line| line.severity)
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        severities,
+        vec![
+            None,
+            None,
+            Some(Severity::Error),
+            None,
+            Some(Severity::Fatal),
+            Some(Severity::Warning),
+        ]
+    );
+}
+
+#[test]
+fn mysql_levels_are_read() {
+    let severities = lines(MYSQL)
+        .into_iter()
+        .map(|line| line.severity)
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        severities,
+        vec![None, Some(Severity::Warning), None, Some(Severity::Error)]
+    );
+}
+
+#[test]
+fn only_the_first_level_word_counts() {
+    // The statement logged after the level mentions an error without being one
+    let line = LogLine::parse("LOG:  statement: SELECT 'ERROR' AS panic", false);
+    assert_eq!(line.severity, None);
+}
+
+#[test]
+fn keywords_are_matched_ignoring_case() {
+    let filter = LogFilter {
+        query: "  ORDERS ".into(),
+        severities: vec![],
+    };
+    let matching = lines(POSTGRES)
+        .into_iter()
+        .filter(|line| filter.matches(line))
+        .count();
+
+    assert_eq!(matching, 2);
+}
+
+#[test]
+fn severities_and_keywords_both_have_to_match() {
+    let mut filter = LogFilter::default();
+    filter.toggle(Severity::Error);
+    filter.toggle(Severity::Fatal);
+    let picked = |filter: &LogFilter| {
+        lines(&format!("{POSTGRES}\n{MYSQL}"))
+            .into_iter()
+            .filter(|line| filter.matches(line))
+            .count()
+    };
+
+    assert_eq!(picked(&filter), 3);
+
+    filter.query = "replica".into();
+    assert_eq!(picked(&filter), 1);
+
+    filter.toggle(Severity::Error);
+    assert_eq!(filter.severities, vec![Severity::Fatal]);
+    assert_eq!(picked(&filter), 0);
+}
+
+#[test]
+fn filtering_uses_the_kept_lines() {
+    let mut follow = LogFollow::new("0123".into(), 1, LogFilter::default());
+    for line in lines(POSTGRES) {
+        follow.push(line);
+    }
+    assert_eq!(follow.panel().lines.len(), 6);
+
+    follow.filter.toggle(Severity::Warning);
+    let panel = follow.panel();
+    assert_eq!(panel.lines.len(), 1);
+    assert!(panel.lines[0].text.contains("no transaction in progress"));
+
+    follow.filter = LogFilter::default();
+    assert_eq!(follow.panel().lines.len(), 6);
+}
+
+#[test]
+fn the_oldest_lines_are_dropped_over_the_limit() {
+    let mut follow = LogFollow::new("0123".into(), 1, LogFilter::default());
+    for number in 0..MAX_LINES + 10 {
+        follow.push(LogLine::parse(&format!("line {number}"), false));
+    }
+    follow.filter.query = "line 9".into();
+
+    // The first ten lines were dropped, line 9 with them, while 90-99 and 900-999 are kept
+    let panel = follow.panel();
+    assert_eq!(
+        panel.lines.first().map(|line| line.text.as_str()),
+        Some("line 90")
+    );
+    assert_eq!(panel.older + panel.lines.len(), 110);
+}
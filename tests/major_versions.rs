@@ -0,0 +1,166 @@
+//! Checks how the major version is read out of image tags, when reusing data crosses major
+//! versions and that volumes record the tag they were created for.
+#![allow(dead_code)]
+
+#[path = "../src/backup.rs"]
+mod backup;
+mod common;
+#[path = "../src/daemon.rs"]
+mod daemon;
+#[path = "../src/data.rs"]
+mod data;
+#[path = "../src/definition.rs"]
+mod definition;
+#[path = "../src/diff.rs"]
+mod diff;
+#[path = "../src/docker.rs"]
+mod docker;
+#[path = "../src/idle.rs"]
+mod idle;
+#[path = "../src/image_ref.rs"]
+mod image_ref;
+#[path = "../src/names.rs"]
+mod names;
+#[path = "../src/platform.rs"]
+mod platform;
+#[path = "../src/ports.rs"]
+mod ports;
+#[path = "../src/registry.rs"]
+mod registry;
+#[path = "../src/template.rs"]
+mod template;
+#[path = "../src/tls.rs"]
+mod tls;
+#[path = "../src/versions.rs"]
+mod versions;
+
+use std::collections::HashMap;
+
+use bollard::service::Volume;
+use common::postgres;
+use docker::{managed_volumes, plan_container, MANAGED_LABELS, VOLUME_TAG_LABEL};
+use serde_json::json;
+use versions::{major_jump, major_version, MajorJump};
+
+#[test]
+fn the_major_version_is_read_before_the_rest_of_the_tag() {
+    let pattern = "{MAJOR}*";
+
+    assert_eq!(major_version(pattern, "16"), Some(16));
+    assert_eq!(major_version(pattern, "16.2"), Some(16));
+    assert_eq!(major_version(pattern, "16.2-alpine"), Some(16));
+    assert_eq!(major_version(pattern, "16-bookworm"), Some(16));
+    assert_eq!(major_version(pattern, "16beta1"), Some(16));
+}
+
+#[test]
+fn tags_without_a_version_have_no_major() {
+    for tag in ["latest", "alpine", "", "bookworm"] {
+        assert_eq!(major_version("{MAJOR}*", tag), None, "{tag}");
+    }
+}
+
+#[test]
+fn the_rest_of_the_pattern_has_to_match() {
+    // MariaDB 10.x, where the minor version is what changes the data directory
+    let pattern = "10.{MAJOR}*";
+    assert_eq!(major_version(pattern, "10.11.6"), Some(11));
+    assert_eq!(major_version(pattern, "10.6-jammy"), Some(6));
+    assert_eq!(major_version(pattern, "11.2"), None);
+
+    assert_eq!(major_version("v{MAJOR}", "v7"), Some(7));
+    assert_eq!(major_version("v{MAJOR}", "v7.2"), None);
+    assert_eq!(major_version("{MAJOR}-alpine", "16-alpine"), Some(16));
+    assert_eq!(major_version("{MAJOR}-alpine", "16"), None);
+}
+
+#[test]
+fn patterns_without_exactly_one_major_read_nothing() {
+    assert_eq!(major_version("*", "16.2"), None);
+    assert_eq!(major_version("{MAJOR}.{MAJOR}", "16.2"), None);
+}
+
+#[test]
+fn changing_major_is_a_jump() {
+    assert_eq!(
+        major_jump(Some("{MAJOR}*"), "15.6-alpine", "16.2"),
+        Some(MajorJump { from: 15, to: 16 })
+    );
+    assert_eq!(
+        major_jump(Some("{MAJOR}*"), "16", "15"),
+        Some(MajorJump { from: 16, to: 15 })
+    );
+}
+
+#[test]
+fn minor_updates_are_not_a_jump() {
+    assert_eq!(major_jump(Some("{MAJOR}*"), "16.1", "16.2-alpine"), None);
+    assert_eq!(major_jump(Some("{MAJOR}*"), "16beta1", "16"), None);
+}
+
+#[test]
+fn unknown_versions_and_patterns_skip_the_check() {
+    assert_eq!(major_jump(Some("{MAJOR}*"), "latest", "16"), None);
+    assert_eq!(major_jump(Some("{MAJOR}*"), "15", "latest"), None);
+    assert_eq!(major_jump(None, "15", "16"), None);
+    assert_eq!(major_jump(Some("no placeholder"), "15", "16"), None);
+}
+
+#[test]
+fn the_bundled_postgres_reads_majors() {
+    let pattern = postgres().version_pattern;
+
+    assert_eq!(
+        major_jump(pattern.as_deref(), "15", "16"),
+        Some(MajorJump { from: 15, to: 16 })
+    );
+}
+
+#[test]
+fn volumes_record_the_tag_they_were_created_for() {
+    let mut config = postgres().quick_config("db-mgr__orders");
+    config.tag = "16.2-alpine".into();
+
+    let create = plan_container(&config, MANAGED_LABELS)
+        .into_iter()
+        .find(|op| op.call.starts_with("POST /volumes/create"))
+        .and_then(|op| op.body)
+        .expect("The plan creates the volume");
+
+    assert_eq!(create["Labels"][VOLUME_TAG_LABEL], "16.2-alpine");
+}
+
+#[test]
+fn listed_volumes_carry_their_tag() {
+    let volume = |name: &str, labels: serde_json::Value| -> Volume {
+        serde_json::from_value(json!({
+            "Name": name,
+            "Driver": "local",
+            "Mountpoint": format!("/var/lib/docker/volumes/{name}/_data"),
+            "Labels": labels,
+            "Scope": "local",
+            "Options": {}
+        }))
+        .expect("The captured JSON must parse")
+    };
+    let volumes = managed_volumes(
+        vec![
+            volume(
+                "db-mgr__orders__pgsql_data",
+                json!({ "db-mgr-resource": "volume", VOLUME_TAG_LABEL: "15" }),
+            ),
+            // Created before volumes recorded their tag
+            volume(
+                "db-mgr__users__pgsql_data",
+                json!({ "db-mgr-resource": "volume" }),
+            ),
+        ],
+        &HashMap::new(),
+    );
+
+    let tags = volumes
+        .iter()
+        .map(|volume| volume.tag.as_deref())
+        .collect::<Vec<_>>();
+    assert_eq!(tags, vec![Some("15"), None]);
+}
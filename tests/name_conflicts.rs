@@ -0,0 +1,102 @@
+//! Checks when a container already holding the wanted name may be replaced, from its inspect,
+//! and that the name is checked before anything is pulled.
+#![allow(dead_code)]
+
+#[path = "../src/daemon.rs"]
+mod daemon;
+#[path = "../src/docker.rs"]
+mod docker;
+#[path = "../src/image_ref.rs"]
+mod image_ref;
+#[path = "../src/platform.rs"]
+mod platform;
+#[path = "../src/ports.rs"]
+mod ports;
+
+use bollard::service::ContainerInspectResponse;
+use docker::{can_replace_conflict, Labels, LABEL, MANAGED_LABELS};
+use image_ref::ImageRef;
+use serde_json::json;
+
+/// The inspect of the container named `db-mgr__orders` holding the name.
+fn existing(status: &str, image: &str, label: Option<&str>) -> ContainerInspectResponse {
+    let labels = label
+        .map(|label| json!({ LABEL: label }))
+        .unwrap_or(json!({}));
+    serde_json::from_value(json!({
+        "Id": "4f3c2a1b9e8d",
+        "Name": "/db-mgr__orders",
+        "State": { "Status": status, "Running": status == "running" },
+        "Config": { "Image": image, "Labels": labels }
+    }))
+    .expect("The captured JSON must parse")
+}
+
+fn replaceable(existing: &ContainerInspectResponse, image: &str) -> bool {
+    can_replace_conflict(existing, MANAGED_LABELS, &ImageRef::parse(image))
+}
+
+#[test]
+fn stopped_leftovers_of_the_same_image_can_be_replaced() {
+    for status in ["exited", "created", "dead"] {
+        assert!(
+            replaceable(
+                &existing(status, "postgres:15", Some("container")),
+                "postgres:16"
+            ),
+            "{status} leftovers can be replaced"
+        );
+    }
+}
+
+#[test]
+fn running_containers_are_never_replaced() {
+    for status in ["running", "paused", "restarting", "removing"] {
+        assert!(!replaceable(
+            &existing(status, "postgres:16", Some("container")),
+            "postgres:16"
+        ));
+    }
+}
+
+#[test]
+fn unmanaged_containers_are_never_replaced() {
+    assert!(!replaceable(
+        &existing("exited", "postgres:16", None),
+        "postgres:16"
+    ));
+    // Containers of the test suite or another label scheme aren't this app's
+    assert!(!replaceable(
+        &existing("exited", "postgres:16", Some("test-container")),
+        "postgres:16"
+    ));
+    let test_labels = Labels {
+        container: "test-container",
+        volume: "test-volume",
+    };
+    assert!(can_replace_conflict(
+        &existing("exited", "postgres:16", Some("test-container")),
+        test_labels,
+        &ImageRef::parse("postgres:16")
+    ));
+}
+
+#[test]
+fn containers_of_another_image_are_never_replaced() {
+    assert!(!replaceable(
+        &existing("exited", "mysql:8", Some("container")),
+        "postgres:16"
+    ));
+    assert!(replaceable(
+        &existing("exited", "docker.io/library/postgres:16", Some("container")),
+        "postgres:16"
+    ));
+}
+
+#[test]
+fn inspects_missing_state_or_config_are_not_replaced() {
+    let bare: ContainerInspectResponse =
+        serde_json::from_value(json!({ "Id": "4f3c2a1b9e8d" })).unwrap();
+
+    assert!(!replaceable(&bare, "postgres:16"));
+}
@@ -0,0 +1,166 @@
+//! Checks the network attachments read from captured inspect JSON and the connection strings
+//! other containers on those networks use.
+#![allow(dead_code)]
+
+#[path = "../src/backup.rs"]
+mod backup;
+mod common;
+#[path = "../src/daemon.rs"]
+mod daemon;
+#[path = "../src/data.rs"]
+mod data;
+#[path = "../src/definition.rs"]
+mod definition;
+#[path = "../src/diff.rs"]
+mod diff;
+#[path = "../src/docker.rs"]
+mod docker;
+#[path = "../src/idle.rs"]
+mod idle;
+#[path = "../src/image_ref.rs"]
+mod image_ref;
+#[path = "../src/names.rs"]
+mod names;
+#[path = "../src/platform.rs"]
+mod platform;
+#[path = "../src/ports.rs"]
+mod ports;
+#[path = "../src/registry.rs"]
+mod registry;
+#[path = "../src/template.rs"]
+mod template;
+#[path = "../src/tls.rs"]
+mod tls;
+
+use std::collections::HashMap;
+
+use bollard::service::{ContainerInspectResponse, EndpointSettings};
+use common::postgres;
+use docker::{inspected_container, parse_networks, EnvValue, NetworkAttachment};
+
+const ID: &str = "4f3c2a1b9e8d7c6b5a4f3c2a1b9e8d7c6b5a4f3c2a1b9e8d7c6b5a4f3c2a1b9e";
+
+/// The networks of a container on a user defined network, as `docker inspect` printed them.
+const USER_NETWORK: &str = r#"{
+    "db-mgr": {
+        "IPAMConfig": null,
+        "Links": null,
+        "Aliases": ["db-mgr__orders", "orders", "4f3c2a1b9e8d"],
+        "NetworkID": "a8e2f0b7",
+        "EndpointID": "5d1c",
+        "Gateway": "172.20.0.1",
+        "IPAddress": "172.20.0.3",
+        "IPPrefixLen": 16,
+        "MacAddress": "02:42:ac:14:00:03",
+        "DriverOpts": null
+    }
+}"#;
+
+/// A running container on the default bridge.
+const BRIDGE: &str = r#"{
+    "bridge": {
+        "IPAMConfig": null,
+        "Links": null,
+        "Aliases": null,
+        "NetworkID": "9b1f",
+        "EndpointID": "77aa",
+        "Gateway": "172.17.0.1",
+        "IPAddress": "172.17.0.2",
+        "IPPrefixLen": 16,
+        "MacAddress": "02:42:ac:11:00:02",
+        "DriverOpts": null
+    }
+}"#;
+
+/// A stopped container keeps its networks without an address.
+const STOPPED: &str = r#"{
+    "bridge": {
+        "IPAMConfig": null,
+        "Links": null,
+        "Aliases": null,
+        "NetworkID": "9b1f",
+        "EndpointID": "",
+        "Gateway": "",
+        "IPAddress": "",
+        "IPPrefixLen": 0,
+        "MacAddress": "",
+        "DriverOpts": null
+    }
+}"#;
+
+fn networks(json: &str) -> HashMap<String, EndpointSettings> {
+    serde_json::from_str(json).expect("The captured JSON must parse")
+}
+
+#[test]
+fn aliases_are_read_without_the_short_id() {
+    assert_eq!(
+        parse_networks(ID, &networks(USER_NETWORK)),
+        vec![NetworkAttachment {
+            name: "db-mgr".into(),
+            ip: Some("172.20.0.3".into()),
+            aliases: vec!["db-mgr__orders".into(), "orders".into()],
+        }]
+    );
+}
+
+#[test]
+fn the_default_bridge_still_has_an_ip() {
+    let attachments = parse_networks(ID, &networks(BRIDGE));
+
+    assert_eq!(attachments[0].ip.as_deref(), Some("172.17.0.2"));
+    assert!(attachments[0].aliases.is_empty());
+    assert_eq!(attachments[0].host(), Some("172.17.0.2"));
+}
+
+#[test]
+fn stopped_containers_have_no_ip() {
+    let attachments = parse_networks(ID, &networks(STOPPED));
+
+    assert_eq!(attachments[0].ip, None);
+    assert_eq!(attachments[0].host(), None);
+}
+
+#[test]
+fn networks_are_sorted_by_name() {
+    let mut both = networks(USER_NETWORK);
+    both.extend(networks(BRIDGE));
+
+    let names = parse_networks(ID, &both)
+        .into_iter()
+        .map(|attachment| attachment.name)
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["bridge", "db-mgr"]);
+}
+
+#[test]
+fn inspected_containers_carry_their_networks() {
+    let inspect: ContainerInspectResponse = serde_json::from_str(&format!(
+        r#"{{ "Id": "{ID}", "NetworkSettings": {{ "Networks": {USER_NETWORK} }} }}"#
+    ))
+    .expect("The captured JSON must parse");
+
+    let container = inspected_container(inspect).unwrap();
+    assert_eq!(container.networks.len(), 1);
+    assert_eq!(container.networks[0].host(), Some("db-mgr__orders"));
+}
+
+#[test]
+fn internal_connection_strings_use_the_alias_and_container_port() {
+    let inspect: ContainerInspectResponse = serde_json::from_str(&format!(
+        r#"{{ "Id": "{ID}", "NetworkSettings": {{ "Networks": {USER_NETWORK} }} }}"#
+    ))
+    .unwrap();
+    let mut container = inspected_container(inspect).unwrap();
+    container.variables = HashMap::from([
+        ("POSTGRES_PASSWORD".into(), EnvValue::new("hunter2")),
+        ("POSTGRES_DB".into(), EnvValue::new("orders")),
+    ]);
+    let host = container.networks[0].host().unwrap().to_string();
+
+    let connection = postgres()
+        .internal_connection_string(&container, &host)
+        .expect("Postgres has a connection string");
+    assert!(connection.contains("@db-mgr__orders:5432"), "{connection}");
+    assert!(connection.contains("hunter2"), "{connection}");
+}
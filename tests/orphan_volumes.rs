@@ -0,0 +1,132 @@
+//! Checks which db-mgr volumes are reported as orphaned, from volumes and containers made up as
+//! docker lists them.
+#![allow(dead_code)]
+
+#[path = "../src/daemon.rs"]
+mod daemon;
+#[path = "../src/docker.rs"]
+mod docker;
+#[path = "../src/image_ref.rs"]
+mod image_ref;
+#[path = "../src/platform.rs"]
+mod platform;
+#[path = "../src/ports.rs"]
+mod ports;
+
+use std::collections::HashMap;
+
+use bollard::service::{ContainerSummary, Volume};
+use docker::{
+    find_orphans, managed_volumes, summary_container, DbContainer, DbVolume, LABEL,
+    VOLUME_TAG_LABEL,
+};
+
+fn volume(name: &str, labels: &[(&str, &str)]) -> Volume {
+    Volume {
+        name: name.into(),
+        labels: labels
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect(),
+        ..Default::default()
+    }
+}
+
+fn labelled(name: &str) -> Volume {
+    volume(name, &[(LABEL, "volume"), (VOLUME_TAG_LABEL, "16")])
+}
+
+fn container(name: &str, mounts: &[(&str, &str)]) -> DbContainer {
+    let mut container = summary_container(ContainerSummary {
+        id: Some(format!("{name}-id")),
+        names: Some(vec![format!("/{name}")]),
+        ..Default::default()
+    })
+    .expect("The summary has an id");
+    container.volumes = mounts
+        .iter()
+        .map(|(source, target)| (source.to_string(), target.to_string()))
+        .collect();
+    container
+}
+
+fn names(volumes: &[DbVolume]) -> Vec<&str> {
+    volumes.iter().map(|volume| volume.name.as_str()).collect()
+}
+
+#[test]
+fn only_labelled_volumes_are_managed() {
+    let volumes = managed_volumes(
+        vec![
+            labelled("pgsql_data"),
+            volume("someone_elses", &[]),
+            volume("other_resource", &[(LABEL, "container")]),
+            volume("untagged", &[(LABEL, "volume")]),
+        ],
+        &HashMap::from([
+            ("pgsql_data".to_string(), 2048),
+            ("untagged".to_string(), -1),
+        ]),
+    );
+
+    assert_eq!(
+        volumes,
+        vec![
+            DbVolume {
+                name: "pgsql_data".into(),
+                size: Some(2048),
+                tag: Some("16".into()),
+            },
+            // Docker reports -1 for sizes it didn't measure
+            DbVolume {
+                name: "untagged".into(),
+                size: None,
+                tag: None,
+            },
+        ]
+    );
+}
+
+#[test]
+fn volumes_in_use_arent_orphans() {
+    let volumes = managed_volumes(
+        vec![
+            labelled("orders_data"),
+            labelled("users_data"),
+            labelled("left_behind"),
+        ],
+        &HashMap::new(),
+    );
+    let containers = vec![
+        container("orders", &[("orders_data", "/var/lib/postgresql/data")]),
+        // Stopped containers still hold on to their volumes
+        container(
+            "users",
+            &[("users_data", "/data"), ("/home/me/dump", "/dump")],
+        ),
+    ];
+
+    assert_eq!(
+        names(&find_orphans(&volumes, &containers)),
+        vec!["left_behind"]
+    );
+}
+
+#[test]
+fn every_volume_is_orphaned_without_containers() {
+    let volumes = managed_volumes(vec![labelled("a"), labelled("b")], &HashMap::new());
+
+    assert_eq!(names(&find_orphans(&volumes, &[])), vec!["a", "b"]);
+    assert!(find_orphans(&[], &[container("orders", &[("a", "/data")])]).is_empty());
+}
+
+#[test]
+fn unlabelled_volumes_are_never_orphans() {
+    let volumes = managed_volumes(
+        vec![volume("anonymous", &[]), labelled("pgsql_data")],
+        &HashMap::new(),
+    );
+    let containers = vec![container("orders", &[("pgsql_data", "/data")])];
+
+    assert!(find_orphans(&volumes, &containers).is_empty());
+}
@@ -0,0 +1,155 @@
+//! Checks that containers whose inspect or list data lacks fields are kept with fallbacks and
+//! marked as partial, and that only containers without an id are dropped.
+#![allow(dead_code)]
+
+#[path = "../src/daemon.rs"]
+mod daemon;
+#[path = "../src/docker.rs"]
+mod docker;
+#[path = "../src/image_ref.rs"]
+mod image_ref;
+#[path = "../src/platform.rs"]
+mod platform;
+#[path = "../src/ports.rs"]
+mod ports;
+
+use bollard::service::{
+    ContainerConfig, ContainerInspectResponse, ContainerState, ContainerStateStatusEnum,
+    ContainerSummary, MountPoint, MountPointTypeEnum,
+};
+use docker::{inspected_container, summary_container, EnvValue};
+
+const ID: &str = "0123456789abcdef0123";
+
+/// What docker returns for a running container, with every field the list needs.
+fn complete() -> ContainerInspectResponse {
+    ContainerInspectResponse {
+        id: Some(ID.into()),
+        name: Some("/db-mgr__orders".into()),
+        state: Some(ContainerState {
+            status: Some(ContainerStateStatusEnum::RUNNING),
+            ..Default::default()
+        }),
+        config: Some(ContainerConfig {
+            image: Some("postgres:16".into()),
+            env: Some(vec!["POSTGRES_PASSWORD=hunter2".into()]),
+            ..Default::default()
+        }),
+        mounts: Some(vec![MountPoint {
+            typ: Some(MountPointTypeEnum::VOLUME),
+            name: Some("orders_data".into()),
+            destination: Some("/var/lib/postgresql/data".into()),
+            ..Default::default()
+        }]),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn complete_containers_are_not_partial() {
+    let container = inspected_container(complete()).expect("The container has an id");
+
+    assert!(!container.partial);
+    assert_eq!(container.name, "/db-mgr__orders");
+    assert_eq!(container.image, "postgres:16");
+    assert_eq!(container.state, ContainerStateStatusEnum::RUNNING);
+    assert_eq!(
+        container.variables.get("POSTGRES_PASSWORD"),
+        Some(&EnvValue::new("hunter2"))
+    );
+    assert_eq!(
+        container.volumes.get("orders_data").map(String::as_str),
+        Some("/var/lib/postgresql/data")
+    );
+}
+
+#[test]
+fn containers_without_a_state_are_kept() {
+    let container = inspected_container(ContainerInspectResponse {
+        state: None,
+        ..complete()
+    })
+    .expect("The container has an id");
+
+    assert!(container.partial);
+    assert_eq!(container.state, ContainerStateStatusEnum::EMPTY);
+    assert_eq!(container.image, "postgres:16");
+}
+
+#[test]
+fn containers_without_a_config_are_kept_without_env() {
+    let container = inspected_container(ContainerInspectResponse {
+        config: None,
+        ..complete()
+    })
+    .expect("The container has an id");
+
+    assert!(container.partial);
+    assert_eq!(container.image, "");
+    assert!(container.variables.is_empty());
+    assert!(container.command.is_empty());
+    // The mounts come from the inspect response itself, not the config
+    assert_eq!(container.volumes.len(), 1);
+}
+
+#[test]
+fn containers_without_a_name_are_named_after_their_id() {
+    let container = inspected_container(ContainerInspectResponse {
+        name: None,
+        ..complete()
+    })
+    .expect("The container has an id");
+
+    assert!(container.partial);
+    assert_eq!(container.name, "/0123456789ab");
+}
+
+#[test]
+fn containers_with_only_an_id_are_kept() {
+    let container = inspected_container(ContainerInspectResponse {
+        id: Some(ID.into()),
+        ..Default::default()
+    })
+    .expect("The container has an id");
+
+    assert!(container.partial);
+    assert_eq!(container.id, ID);
+    assert_eq!(container.state, ContainerStateStatusEnum::EMPTY);
+    assert!(container.volumes.is_empty());
+    assert!(container.ports.is_empty());
+}
+
+#[test]
+fn containers_without_an_id_are_skipped() {
+    assert!(inspected_container(ContainerInspectResponse {
+        id: None,
+        ..complete()
+    })
+    .is_none());
+    assert!(summary_container(ContainerSummary::default()).is_none());
+}
+
+#[test]
+fn listed_containers_are_always_partial() {
+    let container = summary_container(ContainerSummary {
+        id: Some(ID.into()),
+        names: Some(vec!["/db-mgr__orders".into()]),
+        image: Some("postgres:16".into()),
+        state: Some("exited".into()),
+        ..Default::default()
+    })
+    .expect("The container has an id");
+
+    assert!(container.partial);
+    assert_eq!(container.name, "/db-mgr__orders");
+    assert_eq!(container.state, ContainerStateStatusEnum::EXITED);
+
+    let unnamed = summary_container(ContainerSummary {
+        id: Some(ID.into()),
+        state: Some("unheard-of".into()),
+        ..Default::default()
+    })
+    .expect("The container has an id");
+    assert_eq!(unnamed.name, "/0123456789ab");
+    assert_eq!(unnamed.state, ContainerStateStatusEnum::EMPTY);
+}
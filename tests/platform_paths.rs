@@ -0,0 +1,75 @@
+//! Checks the bind mount path translation for Windows hosts, which runs on every platform as
+//! the daemon's OS is passed in rather than queried.
+#![allow(dead_code)]
+
+#[path = "../src/platform.rs"]
+mod platform;
+
+use std::path::PathBuf;
+
+use platform::{daemon_bind_source, host_bind_path, is_host_path, DaemonPlatform};
+
+fn daemon(os_type: &str, operating_system: &str) -> DaemonPlatform {
+    DaemonPlatform {
+        os_type: Some(os_type.to_string()),
+        operating_system: Some(operating_system.to_string()),
+    }
+}
+
+#[test]
+fn host_paths_are_told_apart_from_volume_names() {
+    assert!(is_host_path("/srv/postgres"));
+    assert!(is_host_path("C:\\Users\\me\\data"));
+    assert!(is_host_path("d:/data"));
+    assert!(is_host_path("\\\\nas\\backups"));
+    assert!(!is_host_path("db-mgr__pg__data"));
+    assert!(!is_host_path("C:data"));
+}
+
+#[test]
+fn wsl_engines_get_windows_paths_under_mnt() {
+    let wsl = daemon("linux", "Ubuntu 22.04.3 LTS");
+    assert_eq!(
+        daemon_bind_source("C:\\Users\\me\\data", true, &wsl),
+        "/mnt/c/Users/me/data"
+    );
+    assert_eq!(daemon_bind_source("D:/", true, &wsl), "/mnt/d");
+    // Shares can't be reached through /mnt
+    assert_eq!(
+        daemon_bind_source("\\\\nas\\backups", true, &wsl),
+        "\\\\nas\\backups"
+    );
+}
+
+#[test]
+fn docker_desktop_and_other_hosts_keep_paths() {
+    let desktop = daemon("linux", "Docker Desktop");
+    assert_eq!(
+        daemon_bind_source("C:\\Users\\me\\data", true, &desktop),
+        "C:\\Users\\me\\data"
+    );
+    let windows = daemon("windows", "Windows Server 2022");
+    assert_eq!(daemon_bind_source("C:\\data", true, &windows), "C:\\data");
+    let wsl = daemon("linux", "Ubuntu 22.04.3 LTS");
+    assert_eq!(daemon_bind_source("/srv/data", false, &wsl), "/srv/data");
+}
+
+#[test]
+fn reported_sources_map_back_to_windows_folders() {
+    assert_eq!(
+        host_bind_path("/mnt/c/Users/me/data", true),
+        PathBuf::from("C:\\Users\\me\\data")
+    );
+    assert_eq!(
+        host_bind_path("/run/desktop/mnt/host/d/data", true),
+        PathBuf::from("D:\\data")
+    );
+    assert_eq!(
+        host_bind_path("/mnt/wsl/data", true),
+        PathBuf::from("/mnt/wsl/data")
+    );
+    assert_eq!(
+        host_bind_path("/mnt/c/data", false),
+        PathBuf::from("/mnt/c/data")
+    );
+}
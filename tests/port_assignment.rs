@@ -0,0 +1,216 @@
+//! Checks how host ports are picked for the database port of new containers, recorded so
+//! recreating keeps them, and which ports other managed containers already hold.
+#![allow(dead_code)]
+
+#[path = "../src/backup.rs"]
+mod backup;
+mod common;
+#[path = "../src/daemon.rs"]
+mod daemon;
+#[path = "../src/data.rs"]
+mod data;
+#[path = "../src/definition.rs"]
+mod definition;
+#[path = "../src/diff.rs"]
+mod diff;
+#[path = "../src/docker.rs"]
+mod docker;
+#[path = "../src/idle.rs"]
+mod idle;
+#[path = "../src/image_ref.rs"]
+mod image_ref;
+#[path = "../src/names.rs"]
+mod names;
+#[path = "../src/platform.rs"]
+mod platform;
+#[path = "../src/ports.rs"]
+mod ports;
+#[path = "../src/registry.rs"]
+mod registry;
+#[path = "../src/template.rs"]
+mod template;
+#[path = "../src/tls.rs"]
+mod tls;
+
+use std::{collections::HashSet, net::TcpListener};
+
+use bollard::service::{ContainerSummary, Port, PortTypeEnum};
+use common::postgres;
+use docker::{plan_container, summary_container, DbContainer, HOST_PORT_LABEL, MANAGED_LABELS};
+use ports::{is_free, pick_port, taken_ports, PortAssignment, PortRange};
+use serde_json::{json, Value};
+
+fn range(first: u16, last: u16) -> PortRange {
+    PortRange { first, last }
+}
+
+fn container(name: &str, assigned: Option<&str>, bound: Option<u16>) -> DbContainer {
+    let labels = assigned
+        .map(|assigned| [(HOST_PORT_LABEL.to_string(), assigned.to_string())].into())
+        .unwrap_or_default();
+    summary_container(ContainerSummary {
+        id: Some(format!("{name}-id")),
+        names: Some(vec![format!("/db-mgr__{name}")]),
+        image: Some("postgres:16".into()),
+        labels: Some(labels),
+        ports: bound.map(|host| {
+            vec![Port {
+                ip: Some("127.0.0.1".into()),
+                private_port: 5432,
+                public_port: Some(host),
+                typ: Some(PortTypeEnum::TCP),
+            }]
+        }),
+        ..Default::default()
+    })
+    .expect("The summary has an id")
+}
+
+fn create_body(assignment: Option<PortAssignment>) -> Value {
+    let mut config = postgres().quick_config("db-mgr__orders");
+    config.host_port = assignment;
+    plan_container(&config, MANAGED_LABELS)
+        .into_iter()
+        .find(|op| op.call.starts_with("POST /containers/create"))
+        .and_then(|op| op.body)
+        .expect("The plan creates the container")
+}
+
+#[test]
+fn the_first_free_port_of_the_range_is_picked() {
+    assert_eq!(
+        pick_port(range(54000, 54999), &HashSet::new(), |_| true),
+        Some(54000)
+    );
+}
+
+#[test]
+fn ports_of_other_containers_are_skipped() {
+    let taken = HashSet::from([54000, 54001, 54003]);
+    assert_eq!(
+        pick_port(range(54000, 54999), &taken, |_| true),
+        Some(54002)
+    );
+}
+
+#[test]
+fn ports_something_else_listens_on_are_skipped() {
+    let picked = pick_port(range(54000, 54999), &HashSet::from([54000]), |port| {
+        port != 54001
+    });
+    assert_eq!(picked, Some(54002));
+}
+
+#[test]
+fn nothing_is_picked_when_the_range_is_used_up() {
+    let taken = HashSet::from([54000, 54001]);
+    assert_eq!(
+        pick_port(range(54000, 54002), &taken, |port| port != 54002),
+        None
+    );
+    assert_eq!(
+        pick_port(range(54010, 54010), &HashSet::new(), |_| true),
+        Some(54010)
+    );
+}
+
+#[test]
+fn bound_ports_are_not_free() {
+    let listener = TcpListener::bind(("127.0.0.1", 0)).expect("A port can be bound");
+    let port = listener.local_addr().unwrap().port();
+
+    assert!(!is_free(port));
+}
+
+#[test]
+fn assigned_and_bound_ports_are_both_taken() {
+    let containers = vec![
+        // Stopped, so only its label holds the port
+        container("orders", Some("5432:54000"), None),
+        // Docker bound a different port than the one assigned
+        container("users", Some("5432:54001"), Some(54005)),
+        // Created before ports were assigned
+        container("cache", None, Some(54002)),
+        container("scratch", Some("5432:auto"), None),
+    ];
+
+    assert_eq!(
+        taken_ports(&containers),
+        HashSet::from([54000, 54001, 54002, 54005])
+    );
+}
+
+#[test]
+fn assignments_are_written_as_container_and_host_port() {
+    let assigned = PortAssignment {
+        container: 5432,
+        host: Some(54000),
+    };
+    assert_eq!(assigned.to_string(), "5432:54000");
+    assert_eq!("5432:54000".parse(), Ok(assigned));
+    assert_eq!(PortAssignment::auto(5432).to_string(), "5432:auto");
+    assert_eq!("5432:auto".parse(), Ok(PortAssignment::auto(5432)));
+}
+
+#[test]
+fn malformed_assignments_are_rejected() {
+    for value in ["5432", "5432:", ":54000", "5432:lots", "99999:54000", ""] {
+        assert!(
+            value.parse::<PortAssignment>().is_err(),
+            "{value} is rejected"
+        );
+    }
+}
+
+#[test]
+fn the_range_defaults_to_54000_to_54999() {
+    assert_eq!(PortRange::default(), range(54000, 54999));
+    assert_eq!(
+        serde_yaml::from_str::<PortRange>("first: 60000").unwrap(),
+        range(60000, 54999)
+    );
+    assert_eq!(range(54000, 54999).to_string(), "54000-54999");
+}
+
+#[test]
+fn assigned_ports_are_bound_and_recorded() {
+    let body = create_body(Some(PortAssignment {
+        container: 5432,
+        host: Some(54010),
+    }));
+
+    assert_eq!(
+        body["HostConfig"]["PortBindings"],
+        json!({ "5432/tcp": [{ "HostIp": "127.0.0.1", "HostPort": "54010" }] })
+    );
+    assert_eq!(body["Labels"][HOST_PORT_LABEL], "5432:54010");
+}
+
+#[test]
+fn ports_without_a_host_port_are_not_recorded() {
+    let body = create_body(Some(PortAssignment::auto(5432)));
+
+    assert_eq!(
+        body["HostConfig"]["PortBindings"],
+        json!({ "5432/tcp": [{ "HostIp": "127.0.0.1" }] })
+    );
+    assert!(body["Labels"].get(HOST_PORT_LABEL).is_none());
+}
+
+#[test]
+fn the_recorded_port_is_read_back() {
+    let orders = container("orders", Some("5432:54000"), Some(54000));
+
+    assert_eq!(
+        orders.port_assignment(),
+        Some(PortAssignment {
+            container: 5432,
+            host: Some(54000),
+        })
+    );
+    assert_eq!(orders.ports[0].host_port, 54000);
+    assert_eq!(
+        container("cache", None, Some(54002)).port_assignment(),
+        None
+    );
+}
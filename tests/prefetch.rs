@@ -0,0 +1,294 @@
+//! Runs the image prefetch against a scripted docker, checking which images are skipped, the
+//! progress reported while pulling and that a failed image doesn't stop the rest.
+#![allow(dead_code)]
+
+#[path = "../src/daemon.rs"]
+mod daemon;
+#[path = "../src/docker.rs"]
+mod docker;
+#[path = "../src/image_ref.rs"]
+mod image_ref;
+#[path = "../src/platform.rs"]
+mod platform;
+#[path = "../src/ports.rs"]
+mod ports;
+
+use std::{
+    collections::{HashMap, HashSet},
+    io,
+    sync::Mutex,
+};
+
+use anyhow::anyhow;
+use bollard::service::CreateImageInfo;
+use docker::{prefetch, pull_events, PrefetchEvent, PrefetchRuntime, PullEvent};
+use futures::{
+    channel::mpsc::channel,
+    executor::block_on,
+    future::BoxFuture,
+    stream::{self, BoxStream},
+    FutureExt, StreamExt,
+};
+use image_ref::ImageRef;
+use serde_json::json;
+
+type Update = Result<CreateImageInfo, io::Error>;
+
+/// A docker with the images in `present`, pulling the others by playing back their script.
+#[derive(Default)]
+struct Scripted {
+    /// Sizes of the images present locally.
+    present: Mutex<HashMap<String, i64>>,
+    /// The updates of pulling each image, and its size once pulled.
+    pulls: Mutex<HashMap<String, (Vec<Update>, i64)>>,
+    /// Images whose inspect fails.
+    broken: HashSet<String>,
+    /// The images pulled, in order.
+    pulled: Mutex<Vec<String>>,
+}
+
+fn key(image: &str) -> String {
+    ImageRef::parse(image).to_string()
+}
+
+impl Scripted {
+    fn present(self, image: &str, size: i64) -> Self {
+        self.present.lock().unwrap().insert(key(image), size);
+        self
+    }
+
+    fn pulls(self, image: &str, updates: Vec<Update>, size: i64) -> Self {
+        self.pulls
+            .lock()
+            .unwrap()
+            .insert(key(image), (updates, size));
+        self
+    }
+
+    fn broken(mut self, image: &str) -> Self {
+        self.broken.insert(key(image));
+        self
+    }
+}
+
+impl PrefetchRuntime for Scripted {
+    fn local_size<'a>(&'a self, image: &'a ImageRef) -> BoxFuture<'a, anyhow::Result<Option<i64>>> {
+        let image = image.to_string();
+        let result = match self.broken.contains(&image) {
+            true => Err(anyhow!("Docker did not answer inspect image within 10s")),
+            false => Ok(self.present.lock().unwrap().get(&image).copied()),
+        };
+        async move { result }.boxed()
+    }
+
+    fn pull<'a>(&'a self, image: &'a ImageRef) -> BoxStream<'a, anyhow::Result<PullEvent>> {
+        let image = image.to_string();
+        self.pulled.lock().unwrap().push(image.clone());
+        let (updates, size) = self
+            .pulls
+            .lock()
+            .unwrap()
+            .remove(&image)
+            .unwrap_or_else(|| (vec![Err(io::Error::other("manifest unknown"))], 0));
+        // Only a pull that got through all of its updates leaves the image behind
+        if updates.iter().all(Result::is_ok) {
+            self.present.lock().unwrap().insert(image, size);
+        }
+        pull_events(stream::iter(updates)).boxed()
+    }
+}
+
+fn update(value: serde_json::Value) -> Update {
+    Ok(serde_json::from_value(value).expect("The captured JSON must parse"))
+}
+
+/// The updates of pulling a single layer of `size` bytes in two halves.
+fn layer(id: &str, size: i64) -> Vec<Update> {
+    vec![
+        update(json!({ "id": "16", "status": "Pulling from library/postgres" })),
+        update(json!({ "id": id, "status": "Pulling fs layer" })),
+        update(json!({
+            "id": id,
+            "status": "Downloading",
+            "progressDetail": { "current": size / 2, "total": size }
+        })),
+        update(json!({
+            "id": id,
+            "status": "Downloading",
+            "progressDetail": { "current": size, "total": size }
+        })),
+        update(json!({ "id": id, "status": "Download complete", "progressDetail": {} })),
+        update(json!({ "id": id, "status": "Pull complete", "progressDetail": {} })),
+    ]
+}
+
+fn run(runtime: &Scripted, images: &[&str]) -> Vec<PrefetchEvent> {
+    let (mut tx, rx) = channel(100);
+    block_on(prefetch(
+        runtime,
+        images.iter().map(|image| image.to_string()).collect(),
+        &mut tx,
+    ));
+    drop(tx);
+    block_on(rx.collect())
+}
+
+#[test]
+fn present_images_are_skipped() {
+    let runtime = Scripted::default().present("postgres:16", 432_000_000);
+
+    assert_eq!(
+        run(&runtime, &["postgres:16"]),
+        vec![
+            PrefetchEvent::Present("postgres:16".into(), 432_000_000),
+            PrefetchEvent::Done,
+        ]
+    );
+    assert!(runtime.pulled.lock().unwrap().is_empty());
+}
+
+#[test]
+fn pulls_report_their_progress_and_size() {
+    let runtime = Scripted::default().pulls("postgres:16", layer("a1b2", 1000), 432_000_000);
+    let pulling = |event: PullEvent| PrefetchEvent::Pulling("postgres:16".into(), event);
+    let download = |current| PullEvent::Download {
+        layer: "a1b2".into(),
+        current,
+        total: 1000,
+    };
+
+    assert_eq!(
+        run(&runtime, &["postgres:16"]),
+        vec![
+            pulling(download(500)),
+            pulling(download(1000)),
+            // Completed layers are reported without their size
+            pulling(download(1000)),
+            PrefetchEvent::Pulled("postgres:16".into(), Some(432_000_000)),
+            PrefetchEvent::Done,
+        ]
+    );
+}
+
+#[test]
+fn a_failed_pull_doesnt_stop_the_rest() {
+    let runtime = Scripted::default()
+        .pulls(
+            "mysql:8",
+            vec![
+                update(json!({})),
+                Err(io::Error::other(
+                    "toomanyrequests: You have reached your pull rate limit",
+                )),
+                update(json!({ "id": "c3", "status": "Downloading" })),
+            ],
+            0,
+        )
+        .present("postgres:16", 432_000_000);
+
+    assert_eq!(
+        run(&runtime, &["mysql:8", "postgres:16"]),
+        vec![
+            PrefetchEvent::Pulling("mysql:8".into(), PullEvent::Pulling),
+            PrefetchEvent::Failed(
+                "mysql:8".into(),
+                "toomanyrequests: You have reached your pull rate limit".into()
+            ),
+            PrefetchEvent::Present("postgres:16".into(), 432_000_000),
+            PrefetchEvent::Done,
+        ]
+    );
+}
+
+#[test]
+fn images_that_cant_be_inspected_fail_on_their_own() {
+    let runtime =
+        Scripted::default()
+            .broken("postgres:16")
+            .pulls("redis:7", layer("d4", 10), 40_000_000);
+
+    let events = run(&runtime, &["postgres:16", "redis:7"]);
+
+    assert!(matches!(
+        &events[0],
+        PrefetchEvent::Failed(image, message)
+            if image == "postgres:16" && message.contains("inspect image")
+    ));
+    assert_eq!(
+        events[events.len() - 2..],
+        [
+            PrefetchEvent::Pulled("redis:7".into(), Some(40_000_000)),
+            PrefetchEvent::Done,
+        ]
+    );
+    assert_eq!(*runtime.pulled.lock().unwrap(), vec![key("redis:7")]);
+}
+
+#[test]
+fn images_are_pulled_in_order() {
+    let runtime = Scripted::default()
+        .pulls("postgres:15", layer("a", 10), 1)
+        .pulls("postgres:16", layer("b", 10), 2);
+
+    let finished = run(&runtime, &["postgres:16", "postgres:15"])
+        .into_iter()
+        .filter(|event| !matches!(event, PrefetchEvent::Pulling(..)))
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        finished,
+        vec![
+            PrefetchEvent::Pulled("postgres:16".into(), Some(2)),
+            PrefetchEvent::Pulled("postgres:15".into(), Some(1)),
+            PrefetchEvent::Done,
+        ]
+    );
+}
+
+#[test]
+fn nothing_more_is_pulled_once_the_view_is_gone() {
+    let runtime = Scripted::default()
+        .pulls("postgres:16", layer("a", 10), 1)
+        .pulls("redis:7", layer("b", 10), 1);
+    let (mut tx, rx) = channel(0);
+    drop(rx);
+
+    block_on(prefetch(
+        &runtime,
+        vec!["postgres:16".into(), "redis:7".into()],
+        &mut tx,
+    ));
+
+    assert_eq!(*runtime.pulled.lock().unwrap(), vec![key("postgres:16")]);
+}
+
+#[test]
+fn updates_without_a_status_mean_the_pull_started() {
+    let events = block_on(
+        pull_events(stream::iter(vec![
+            update(json!({})),
+            update(json!({ "status": "Digest: sha256:0123" })),
+        ]))
+        .collect::<Vec<_>>(),
+    );
+
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].as_ref().ok(), Some(&PullEvent::Pulling));
+}
+
+#[test]
+fn pull_updates_end_after_the_first_error() {
+    let events = block_on(
+        pull_events(stream::iter(vec![
+            Err(io::Error::other("manifest unknown")),
+            update(json!({})),
+        ]))
+        .collect::<Vec<_>>(),
+    );
+
+    assert_eq!(events.len(), 1);
+    assert_eq!(
+        events[0].as_ref().map_err(ToString::to_string),
+        Err("manifest unknown".to_string())
+    );
+}
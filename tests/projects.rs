@@ -0,0 +1,130 @@
+//! Checks where a container's project comes from, its label or the preferences, and that the
+//! project is written as a label when the container is created.
+#![allow(dead_code)]
+
+#[path = "../src/backup.rs"]
+mod backup;
+#[path = "../src/daemon.rs"]
+mod daemon;
+#[path = "../src/data.rs"]
+mod data;
+#[path = "../src/definition.rs"]
+mod definition;
+#[path = "../src/diff.rs"]
+mod diff;
+#[path = "../src/docker.rs"]
+mod docker;
+#[path = "../src/idle.rs"]
+mod idle;
+#[path = "../src/image_ref.rs"]
+mod image_ref;
+#[path = "../src/names.rs"]
+mod names;
+#[path = "../src/platform.rs"]
+mod platform;
+#[path = "../src/ports.rs"]
+mod ports;
+#[path = "../src/registry.rs"]
+mod registry;
+#[path = "../src/template.rs"]
+mod template;
+#[path = "../src/tls.rs"]
+mod tls;
+
+use std::collections::HashMap;
+
+use bollard::service::ContainerSummary;
+use data::{ConfigFile, Preferences};
+use docker::{
+    plan_container, summary_container, DbContainer, DbContainerConfig, MANAGED_LABELS,
+    PROJECT_LABEL,
+};
+
+fn container(label: Option<&str>) -> DbContainer {
+    let mut container = summary_container(ContainerSummary {
+        id: Some("4f3c2a1b9e8d".into()),
+        names: Some(vec!["/db-mgr__orders".into()]),
+        image: Some("postgres:16".into()),
+        ..Default::default()
+    })
+    .expect("The summary has an id");
+    container.labels = label
+        .map(|project| HashMap::from([(PROJECT_LABEL.to_string(), project.to_string())]))
+        .unwrap_or_default();
+    container
+}
+
+fn preferences(projects: &[(&str, &str)]) -> Preferences {
+    Preferences {
+        projects: projects
+            .iter()
+            .map(|(name, project)| (name.to_string(), project.to_string()))
+            .collect(),
+        ..Preferences::default()
+    }
+}
+
+#[test]
+fn the_label_is_used_without_a_preference() {
+    assert_eq!(
+        preferences(&[]).project(&container(Some("shop"))),
+        Some("shop".to_string())
+    );
+    assert_eq!(preferences(&[]).project(&container(None)), None);
+}
+
+#[test]
+fn empty_labels_are_no_project() {
+    assert_eq!(preferences(&[]).project(&container(Some(""))), None);
+}
+
+#[test]
+fn the_preference_wins_over_the_label() {
+    let preferences = preferences(&[("db-mgr__orders", "billing")]);
+
+    assert_eq!(
+        preferences.project(&container(Some("shop"))),
+        Some("billing".to_string())
+    );
+    assert_eq!(
+        preferences.project(&container(None)),
+        Some("billing".to_string())
+    );
+}
+
+#[test]
+fn an_empty_preference_takes_the_container_out_of_its_project() {
+    assert_eq!(
+        preferences(&[("db-mgr__orders", "")]).project(&container(Some("shop"))),
+        None
+    );
+}
+
+#[test]
+fn preferences_of_other_containers_are_ignored() {
+    assert_eq!(
+        preferences(&[("db-mgr__cache", "billing")]).project(&container(Some("shop"))),
+        Some("shop".to_string())
+    );
+}
+
+#[test]
+fn the_project_is_written_as_a_label() {
+    let mut config = serde_yaml::from_str::<ConfigFile>(include_str!("../config.yaml"))
+        .expect("The bundled config must parse")
+        .databases[0]
+        .quick_config("db-mgr__orders");
+    let labels = |config: &DbContainerConfig| {
+        plan_container(config, MANAGED_LABELS)
+            .into_iter()
+            .find(|op| op.call.starts_with("POST /containers/create"))
+            .and_then(|op| op.body)
+            .expect("The plan creates the container")["Labels"]
+            .clone()
+    };
+
+    assert!(labels(&config).get(PROJECT_LABEL).is_none());
+
+    config.project = Some("shop".into());
+    assert_eq!(labels(&config)[PROJECT_LABEL], "shop");
+}
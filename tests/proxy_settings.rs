@@ -0,0 +1,173 @@
+//! Checks which proxies the shared HTTP client is built with, from the config file and the
+//! environment, without making a request.
+#![allow(dead_code)]
+
+#[path = "../src/backup.rs"]
+mod backup;
+#[path = "../src/daemon.rs"]
+mod daemon;
+#[path = "../src/data.rs"]
+mod data;
+#[path = "../src/definition.rs"]
+mod definition;
+#[path = "../src/diff.rs"]
+mod diff;
+#[path = "../src/docker.rs"]
+mod docker;
+#[path = "../src/http.rs"]
+mod http;
+#[path = "../src/idle.rs"]
+mod idle;
+#[path = "../src/image_ref.rs"]
+mod image_ref;
+#[path = "../src/names.rs"]
+mod names;
+#[path = "../src/platform.rs"]
+mod platform;
+#[path = "../src/ports.rs"]
+mod ports;
+#[path = "../src/registry.rs"]
+mod registry;
+#[path = "../src/template.rs"]
+mod template;
+#[path = "../src/tls.rs"]
+mod tls;
+
+use std::collections::HashMap;
+
+use data::ProxyConfig;
+use http::{build_client, ProxySettings};
+
+/// An environment with only `vars` set.
+fn env(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+    let vars = vars
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect::<HashMap<_, _>>();
+    move |name| vars.get(name).cloned()
+}
+
+#[test]
+fn the_environment_is_used_without_config() {
+    let settings = ProxySettings::resolve(
+        &ProxyConfig::default(),
+        env(&[
+            ("HTTP_PROXY", "http://proxy:3128"),
+            ("HTTPS_PROXY", "http://proxy:3129"),
+            ("NO_PROXY", "localhost,.internal"),
+        ]),
+    );
+
+    assert_eq!(
+        settings,
+        ProxySettings {
+            http: Some("http://proxy:3128".into()),
+            https: Some("http://proxy:3129".into()),
+            no_proxy: Some("localhost,.internal".into()),
+        }
+    );
+}
+
+#[test]
+fn the_config_overrides_the_environment() {
+    let config = ProxyConfig {
+        http: Some("http://configured:8080".into()),
+        no_proxy: Some("registry.local".into()),
+        ..Default::default()
+    };
+    let settings = ProxySettings::resolve(
+        &config,
+        env(&[
+            ("HTTP_PROXY", "http://env:3128"),
+            ("HTTPS_PROXY", "http://env:3129"),
+            ("NO_PROXY", "localhost"),
+        ]),
+    );
+
+    assert_eq!(settings.http.as_deref(), Some("http://configured:8080"));
+    assert_eq!(settings.no_proxy.as_deref(), Some("registry.local"));
+    // What the config leaves out still comes from the environment
+    assert_eq!(settings.https.as_deref(), Some("http://env:3129"));
+}
+
+#[test]
+fn lowercase_and_empty_variables() {
+    let settings = ProxySettings::resolve(
+        &ProxyConfig::default(),
+        env(&[
+            ("http_proxy", "http://lower:3128"),
+            ("HTTPS_PROXY", ""),
+            ("https_proxy", "http://lower:3129"),
+            ("no_proxy", "*"),
+        ]),
+    );
+    assert_eq!(settings.http.as_deref(), Some("http://lower:3128"));
+    // An empty uppercase variable doesn't hide the lowercase one
+    assert_eq!(settings.https.as_deref(), Some("http://lower:3129"));
+    assert_eq!(settings.no_proxy.as_deref(), Some("*"));
+
+    let uppercase_wins = ProxySettings::resolve(
+        &ProxyConfig::default(),
+        env(&[("NO_PROXY", "upper"), ("no_proxy", "lower")]),
+    );
+    assert_eq!(uppercase_wins.no_proxy.as_deref(), Some("upper"));
+
+    assert_eq!(
+        ProxySettings::resolve(&ProxyConfig::default(), env(&[])),
+        ProxySettings::default()
+    );
+}
+
+#[test]
+fn the_client_builds_with_proxies() {
+    let config = ProxyConfig {
+        http: Some("http://proxy:3128".into()),
+        https: Some("http://proxy:3129".into()),
+        no_proxy: Some("localhost,127.0.0.1".into()),
+        extra_ca_cert: None,
+    };
+    assert!(build_client(&config).is_ok());
+}
+
+#[test]
+fn a_missing_ca_file_is_named() {
+    let missing =
+        std::env::temp_dir().join(format!("db-mgr-missing-ca-{}.pem", std::process::id()));
+    let config = ProxyConfig {
+        extra_ca_cert: Some(missing.clone()),
+        ..Default::default()
+    };
+
+    let error = build_client(&config).expect_err("The CA file doesn't exist");
+    assert_eq!(
+        error.to_string(),
+        format!("Could not read CA certificate {}", missing.display())
+    );
+}
+
+#[test]
+fn an_invalid_ca_file_is_named() {
+    let file = std::env::temp_dir().join(format!("db-mgr-invalid-ca-{}.pem", std::process::id()));
+    std::fs::write(&file, "not a certificate").unwrap();
+    let config = ProxyConfig {
+        extra_ca_cert: Some(file.clone()),
+        ..Default::default()
+    };
+
+    let error = build_client(&config).expect_err("The CA file isn't PEM");
+    assert_eq!(
+        error.to_string(),
+        format!("Invalid CA certificate {}", file.display())
+    );
+}
+
+#[test]
+fn an_invalid_proxy_is_named() {
+    let config = ProxyConfig {
+        https: Some("http://[::1".into()),
+        ..Default::default()
+    };
+
+    let error = build_client(&config).expect_err("The proxy isn't a url");
+    assert_eq!(error.to_string(), "Invalid https proxy http://[::1");
+}
@@ -0,0 +1,137 @@
+//! Checks the smoothed transfer rate, the time left and the stalled state shown while an image
+//! is pulled.
+#![allow(dead_code)]
+
+#[path = "../src/app/cleanup.rs"]
+mod cleanup;
+#[path = "../src/daemon.rs"]
+mod daemon;
+#[path = "../src/docker.rs"]
+mod docker;
+#[path = "../src/image_ref.rs"]
+mod image_ref;
+#[path = "../src/platform.rs"]
+mod platform;
+#[path = "../src/ports.rs"]
+mod ports;
+#[path = "../src/app/pull_progress.rs"]
+mod pull_progress;
+
+use std::time::{Duration, Instant};
+
+use pull_progress::{format_eta, format_rate, PullProgress, STALLED_AFTER};
+
+const MB: u64 = 1 << 20;
+const SECOND: Duration = Duration::from_secs(1);
+
+#[test]
+fn layers_add_up() {
+    let start = Instant::now();
+    let mut progress = PullProgress::default();
+    progress.update("a".into(), 10 * MB, 40 * MB, start);
+    progress.update("b".into(), 30 * MB, 60 * MB, start);
+    progress.update("a".into(), 20 * MB, 40 * MB, start);
+
+    assert_eq!(progress.downloaded(), 50 * MB);
+    assert_eq!(progress.total(), 100 * MB);
+    assert_eq!(progress.fraction(), 0.5);
+    assert_eq!(
+        progress.layers(),
+        vec![(&"a".to_string(), 0.5), (&"b".to_string(), 0.5)]
+    );
+}
+
+#[test]
+fn there_is_no_rate_before_a_second_sample() {
+    let start = Instant::now();
+    let mut progress = PullProgress::default();
+    progress.update("a".into(), 0, 100 * MB, start);
+
+    assert_eq!(progress.summary(start), None);
+}
+
+#[test]
+fn a_steady_rate_gives_the_time_left() {
+    let start = Instant::now();
+    let mut progress = PullProgress::default();
+    for second in 0..=10 {
+        progress.update(
+            "a".into(),
+            second * 10 * MB,
+            1300 * MB,
+            start + SECOND * second as u32,
+        );
+    }
+
+    // 1200 MB left at 10 MB/s
+    assert_eq!(
+        progress.summary(start + 10 * SECOND).as_deref(),
+        Some("10.0 MB/s — about 2 min left")
+    );
+}
+
+#[test]
+fn bursts_are_smoothed() {
+    let start = Instant::now();
+    let mut progress = PullProgress::default();
+    progress.update("a".into(), 0, 1000 * MB, start);
+    progress.update("a".into(), 10 * MB, 1000 * MB, start + SECOND);
+    // A second at 100 MB/s after one at 10 MB/s only moves the average part of the way
+    progress.update("a".into(), 110 * MB, 1000 * MB, start + 2 * SECOND);
+
+    let summary = progress.summary(start + 2 * SECOND).unwrap();
+    let rate = summary.split(' ').next().unwrap().parse::<f64>().unwrap();
+    assert!(rate > 10.0 && rate < 100.0, "{summary}");
+    // One second is a fifth of the smoothing time, so about 18% of the way
+    assert!((rate - 26.3).abs() < 0.5, "{summary}");
+}
+
+#[test]
+fn pulls_without_new_bytes_are_stalled() {
+    let start = Instant::now();
+    let mut progress = PullProgress::default();
+    progress.update("a".into(), 0, 100 * MB, start);
+    progress.update("a".into(), 10 * MB, 100 * MB, start + SECOND);
+    let last_bytes = start + SECOND;
+
+    // Updates that bring no new bytes don't count as progress
+    progress.update("a".into(), 10 * MB, 100 * MB, last_bytes + 10 * SECOND);
+    assert!(!progress.is_stalled(last_bytes + STALLED_AFTER - SECOND));
+    assert!(progress.is_stalled(last_bytes + STALLED_AFTER));
+    assert_eq!(
+        progress.summary(last_bytes + STALLED_AFTER).as_deref(),
+        Some("stalled")
+    );
+
+    progress.update("a".into(), 20 * MB, 100 * MB, last_bytes + STALLED_AFTER);
+    assert!(!progress.is_stalled(last_bytes + STALLED_AFTER));
+}
+
+#[test]
+fn finished_pulls_are_not_stalled() {
+    let start = Instant::now();
+    let mut progress = PullProgress::default();
+    progress.update("a".into(), 0, 10 * MB, start);
+    progress.update("a".into(), 10 * MB, 10 * MB, start + SECOND);
+
+    assert!(!progress.is_stalled(start + 10 * STALLED_AFTER));
+}
+
+#[test]
+fn the_time_left_is_rounded_up() {
+    assert_eq!(format_eta(Duration::ZERO), "almost done");
+    assert_eq!(format_eta(Duration::from_millis(900)), "almost done");
+    assert_eq!(format_eta(Duration::from_secs(1)), "about 10 s left");
+    assert_eq!(format_eta(Duration::from_secs(41)), "about 50 s left");
+    assert_eq!(format_eta(Duration::from_secs(59)), "about 60 s left");
+    assert_eq!(format_eta(Duration::from_secs(60)), "about 1 min left");
+    assert_eq!(format_eta(Duration::from_secs(61)), "about 2 min left");
+    assert_eq!(format_eta(Duration::from_secs(3600)), "about 1 h left");
+    assert_eq!(format_eta(Duration::from_secs(3601)), "about 2 h left");
+}
+
+#[test]
+fn rates_are_shown_per_second() {
+    assert_eq!(format_rate(512.0), "512 B/s");
+    assert_eq!(format_rate(42.0 * MB as f64), "42.0 MB/s");
+}
@@ -0,0 +1,155 @@
+//! Checks that volumes can be mounted read-only and created with driver options, and that the
+//! read-only state is read back from inspect.
+#![allow(dead_code)]
+
+#[path = "../src/backup.rs"]
+mod backup;
+mod common;
+#[path = "../src/daemon.rs"]
+mod daemon;
+#[path = "../src/data.rs"]
+mod data;
+#[path = "../src/definition.rs"]
+mod definition;
+#[path = "../src/diff.rs"]
+mod diff;
+#[path = "../src/docker.rs"]
+mod docker;
+#[path = "../src/idle.rs"]
+mod idle;
+#[path = "../src/image_ref.rs"]
+mod image_ref;
+#[path = "../src/names.rs"]
+mod names;
+#[path = "../src/platform.rs"]
+mod platform;
+#[path = "../src/ports.rs"]
+mod ports;
+#[path = "../src/registry.rs"]
+mod registry;
+#[path = "../src/template.rs"]
+mod template;
+#[path = "../src/tls.rs"]
+mod tls;
+
+use std::collections::HashMap;
+
+use bollard::service::ContainerInspectResponse;
+use common::postgres;
+use data::DatabaseConfig;
+use docker::{inspected_container, plan_container, DbContainerConfig, MANAGED_LABELS};
+use serde_json::{json, Value};
+
+/// Postgres with a second volume of seed data, mounted read-only by default.
+fn seeded() -> DatabaseConfig {
+    let mut database = postgres();
+    database
+        .volumes
+        .insert("seed".into(), "/docker-entrypoint-initdb.d".into());
+    database.read_only_volumes = vec!["seed".into()];
+    database
+}
+
+fn request(config: &DbContainerConfig, call: &str) -> Vec<Value> {
+    plan_container(config, MANAGED_LABELS)
+        .into_iter()
+        .filter(|op| op.call.starts_with(call))
+        .filter_map(|op| op.body)
+        .collect()
+}
+
+fn mount<'a>(mounts: &'a Value, target: &str) -> &'a Value {
+    mounts
+        .as_array()
+        .and_then(|mounts| mounts.iter().find(|mount| mount["Target"] == target))
+        .expect("The volume is mounted")
+}
+
+#[test]
+fn entries_pick_the_read_only_volumes() {
+    let config = seeded().quick_config("orders");
+
+    assert_eq!(config.read_only_volumes, vec!["seed".to_string()]);
+}
+
+#[test]
+fn data_volumes_stay_writable() {
+    let config = seeded().quick_config("orders");
+    let create = &request(&config, "POST /containers/create")[0];
+    let mounts = &create["HostConfig"]["Mounts"];
+
+    assert_eq!(
+        mount(mounts, "/docker-entrypoint-initdb.d")["ReadOnly"],
+        json!(true)
+    );
+    assert_eq!(
+        mount(mounts, "/var/lib/postgresql/data")["ReadOnly"],
+        json!(false)
+    );
+}
+
+#[test]
+fn driver_options_are_given_when_the_volume_is_created() {
+    let mut config = seeded().quick_config("orders");
+    config.volume_options = HashMap::from([(
+        "pgsql_data".into(),
+        HashMap::from([
+            ("type".to_string(), "tmpfs".to_string()),
+            ("device".to_string(), "tmpfs".to_string()),
+        ]),
+    )]);
+
+    let volumes = request(&config, "POST /volumes/create");
+    let options = |name: &str| {
+        volumes
+            .iter()
+            .find(|volume| volume["Name"] == name)
+            .map(|volume| volume["DriverOpts"].clone())
+            .expect("The volume is created")
+    };
+    assert_eq!(
+        options("pgsql_data"),
+        json!({ "type": "tmpfs", "device": "tmpfs" })
+    );
+    assert_eq!(options("seed"), json!({}));
+}
+
+#[test]
+fn the_read_only_state_is_read_back_from_inspect() {
+    let inspect: ContainerInspectResponse = serde_json::from_value(json!({
+        "Id": "4f3c2a1b9e8d",
+        "Name": "/db-mgr__orders",
+        "Mounts": [
+            {
+                "Type": "volume",
+                "Name": "db-mgr__orders__pgsql_data",
+                "Destination": "/var/lib/postgresql/data",
+                "RW": true
+            },
+            {
+                "Type": "volume",
+                "Name": "db-mgr__orders__seed",
+                "Destination": "/docker-entrypoint-initdb.d",
+                "RW": false
+            },
+            {
+                "Type": "bind",
+                "Source": "/home/me/init",
+                "Destination": "/init",
+                "RW": false
+            },
+            { "Type": "tmpfs", "Destination": "/tmp/scratch", "RW": false }
+        ]
+    }))
+    .expect("The captured JSON must parse");
+
+    let container = inspected_container(inspect).expect("The container has an id");
+    assert_eq!(
+        container.read_only_volumes,
+        vec![
+            "db-mgr__orders__seed".to_string(),
+            "/home/me/init".to_string()
+        ]
+    );
+    assert_eq!(container.volumes.len(), 3);
+}
@@ -0,0 +1,111 @@
+//! Checks the masking of secret variables in the JSON db-mgr shows and copies, such as an inspect
+//! or the bodies of a dry run.
+#![allow(dead_code)]
+
+#[path = "../src/backup.rs"]
+mod backup;
+#[path = "../src/daemon.rs"]
+mod daemon;
+#[path = "../src/data.rs"]
+mod data;
+#[path = "../src/definition.rs"]
+mod definition;
+#[path = "../src/diff.rs"]
+mod diff;
+#[path = "../src/docker.rs"]
+mod docker;
+#[path = "../src/idle.rs"]
+mod idle;
+#[path = "../src/image_ref.rs"]
+mod image_ref;
+#[path = "../src/names.rs"]
+mod names;
+#[path = "../src/platform.rs"]
+mod platform;
+#[path = "../src/ports.rs"]
+mod ports;
+#[path = "../src/registry.rs"]
+mod registry;
+#[path = "../src/template.rs"]
+mod template;
+#[path = "../src/tls.rs"]
+mod tls;
+
+use data::is_secret;
+use docker::redact_env;
+use serde_json::{json, Value};
+
+fn redacted(mut json: Value) -> Value {
+    redact_env(&mut json, &|key: &str| is_secret(key, key));
+    json
+}
+
+#[test]
+fn secret_looking_keys_are_masked() {
+    let json = redacted(json!({
+        "Env": [
+            "POSTGRES_PASSWORD=hunter2",
+            "GITHUB_TOKEN=ghp_abc",
+            "APP_SECRET=s3cret",
+            "MYSQL_ROOT_PASSWD=root",
+        ]
+    }));
+
+    assert_eq!(
+        json["Env"],
+        json!([
+            "POSTGRES_PASSWORD=••••••",
+            "GITHUB_TOKEN=••••••",
+            "APP_SECRET=••••••",
+            "MYSQL_ROOT_PASSWD=••••••",
+        ])
+    );
+}
+
+#[test]
+fn other_variables_pass_through() {
+    let env = json!([
+        "POSTGRES_DB=orders",
+        "PGDATA=/var/lib/postgresql/data",
+        "EMPTY="
+    ]);
+
+    assert_eq!(redacted(json!({ "Env": env.clone() }))["Env"], env);
+}
+
+#[test]
+fn values_containing_equals_signs_are_masked_whole() {
+    let json = redacted(json!({
+        "Env": [
+            "DATABASE_PASSWORD=a=b==c",
+            "JAVA_OPTS=-Dfoo=bar -Dbaz=qux",
+        ]
+    }));
+
+    assert_eq!(
+        json["Env"],
+        json!(["DATABASE_PASSWORD=••••••", "JAVA_OPTS=-Dfoo=bar -Dbaz=qux"])
+    );
+}
+
+#[test]
+fn entries_without_a_value_are_kept() {
+    // Docker passes `KEY` on its own through from the environment of the daemon
+    let env = json!(["POSTGRES_PASSWORD", 42]);
+
+    assert_eq!(redacted(json!({ "Env": env.clone() }))["Env"], env);
+}
+
+#[test]
+fn env_arrays_are_found_at_any_depth() {
+    let json = redacted(json!([{
+        "Config": { "Env": ["REDIS_PASSWORD=hunter2"] },
+        "Labels": { "Env": "REDIS_PASSWORD=hunter2" },
+        "Args": ["REDIS_PASSWORD=hunter2"],
+    }]));
+
+    assert_eq!(json[0]["Config"]["Env"], json!(["REDIS_PASSWORD=••••••"]));
+    // Only arrays named Env hold variables
+    assert_eq!(json[0]["Labels"]["Env"], "REDIS_PASSWORD=hunter2");
+    assert_eq!(json[0]["Args"], json!(["REDIS_PASSWORD=hunter2"]));
+}
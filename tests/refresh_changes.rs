@@ -0,0 +1,144 @@
+//! Checks how two refreshes of the container list are compared, and which of the changes are
+//! announced.
+#![allow(dead_code)]
+
+#[path = "../src/backup.rs"]
+mod backup;
+#[path = "../src/app/changes.rs"]
+mod changes;
+mod common;
+#[path = "../src/daemon.rs"]
+mod daemon;
+#[path = "../src/data.rs"]
+mod data;
+#[path = "../src/definition.rs"]
+mod definition;
+#[path = "../src/diff.rs"]
+mod diff;
+#[path = "../src/docker.rs"]
+mod docker;
+#[path = "../src/idle.rs"]
+mod idle;
+#[path = "../src/image_ref.rs"]
+mod image_ref;
+#[path = "../src/names.rs"]
+mod names;
+#[path = "../src/platform.rs"]
+mod platform;
+#[path = "../src/ports.rs"]
+mod ports;
+#[path = "../src/registry.rs"]
+mod registry;
+#[path = "../src/template.rs"]
+mod template;
+#[path = "../src/tls.rs"]
+mod tls;
+
+use std::time::{Duration, Instant};
+
+use bollard::service::ContainerStateStatusEnum;
+use changes::{diff_containers, Changeset, ExpectedChanges, StateChange, Toast, TOAST_DURATION};
+use common::container;
+
+use ContainerStateStatusEnum::{EXITED, RUNNING};
+
+#[test]
+fn unchanged_lists_have_an_empty_changeset() {
+    let containers = vec![
+        container("db-mgr__orders").id("1").state(RUNNING).build(),
+        container("db-mgr__cache").id("2").state(EXITED).build(),
+    ];
+
+    assert_eq!(
+        diff_containers(&containers, &containers),
+        Changeset::default()
+    );
+}
+
+#[test]
+fn containers_are_compared_by_id() {
+    let old = vec![
+        container("db-mgr__orders").id("1").state(RUNNING).build(),
+        container("db-mgr__cache").id("2").state(RUNNING).build(),
+        container("db-mgr__billing").id("3").state(RUNNING).build(),
+    ];
+    let new = vec![
+        container("db-mgr__orders").id("1").state(EXITED).build(),
+        // Recreated under the same name, so the old one was removed and a new one added
+        container("db-mgr__cache").id("4").state(RUNNING).build(),
+        container("db-mgr__audit").id("5").state(RUNNING).build(),
+        container("db-mgr__billing").id("3").state(RUNNING).build(),
+    ];
+
+    assert_eq!(
+        diff_containers(&old, &new),
+        Changeset {
+            added: vec!["db-mgr__audit".into(), "db-mgr__cache".into()],
+            removed: vec!["db-mgr__cache".into()],
+            state_changed: vec![StateChange {
+                id: "1".into(),
+                name: "db-mgr__orders".into(),
+                from: RUNNING,
+                to: EXITED,
+            }],
+        }
+    );
+}
+
+#[test]
+fn state_changes_are_sorted_by_name() {
+    let old = vec![
+        container("db-mgr__zeta").id("1").state(RUNNING).build(),
+        container("db-mgr__alpha").id("2").state(RUNNING).build(),
+    ];
+    let new = vec![
+        container("db-mgr__zeta").id("1").state(EXITED).build(),
+        container("db-mgr__alpha").id("2").state(EXITED).build(),
+    ];
+
+    let names = diff_containers(&old, &new)
+        .state_changed
+        .into_iter()
+        .map(|change| change.name)
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["db-mgr__alpha", "db-mgr__zeta"]);
+}
+
+#[test]
+fn only_outside_changes_are_announced() {
+    let changeset = Changeset {
+        added: vec!["db-mgr__audit".into(), "db-mgr__mine".into()],
+        removed: vec!["db-mgr__old".into()],
+        state_changed: vec![],
+    };
+
+    assert_eq!(
+        changeset.announcements(|name| name == "db-mgr__mine"),
+        vec![
+            "db-mgr__audit was created outside the app".to_string(),
+            "db-mgr__old was removed outside the app".to_string(),
+        ]
+    );
+}
+
+#[test]
+fn the_users_own_actions_are_expected_for_a_minute() {
+    let now = Instant::now();
+    let mut expected = ExpectedChanges::default();
+    expected.expect("/db-mgr__orders", now);
+
+    assert!(expected.contains("db-mgr__orders", now + Duration::from_secs(59)));
+    assert!(!expected.contains("db-mgr__orders", now + Duration::from_secs(60)));
+    assert!(!expected.contains("db-mgr__cache", now));
+
+    expected.prune(now + Duration::from_secs(61));
+    assert!(!expected.contains("db-mgr__orders", now));
+}
+
+#[test]
+fn toasts_expire() {
+    let now = Instant::now();
+
+    assert_eq!(Toast::new("gone".into(), now).until, now + TOAST_DURATION);
+    assert_eq!(Toast::with_retry("failed".into(), 7, now).retry, Some(7));
+}
@@ -0,0 +1,80 @@
+//! Checks that overlapping container refreshes are folded into one follow-up and that results
+//! of superseded refreshes are dropped.
+#![allow(dead_code)]
+
+#[path = "../src/daemon.rs"]
+mod daemon;
+#[path = "../src/docker.rs"]
+mod docker;
+#[path = "../src/image_ref.rs"]
+mod image_ref;
+#[path = "../src/platform.rs"]
+mod platform;
+#[path = "../src/ports.rs"]
+mod ports;
+#[path = "../src/app/refresh.rs"]
+mod refresh;
+
+use refresh::RefreshQueue;
+
+#[test]
+fn a_refresh_starts_when_none_is_running() {
+    let mut queue = RefreshQueue::default();
+    assert!(!queue.in_flight());
+
+    assert_eq!(queue.request(), Some(1));
+    assert!(queue.in_flight());
+    assert!(queue.finish(1));
+    assert!(!queue.in_flight());
+
+    // Nothing was asked for while it ran
+    assert_eq!(queue.follow_up(), None);
+    assert_eq!(queue.request(), Some(2));
+}
+
+#[test]
+fn requests_while_running_are_folded_into_one_follow_up() {
+    let mut queue = RefreshQueue::default();
+    assert_eq!(queue.request(), Some(1));
+    assert_eq!(queue.request(), None);
+    assert_eq!(queue.request(), None);
+    assert_eq!(queue.request(), None);
+
+    // The follow-up waits for the running refresh
+    assert_eq!(queue.follow_up(), None);
+    assert!(queue.finish(1));
+    assert_eq!(queue.follow_up(), Some(2));
+    assert!(queue.in_flight());
+    assert!(queue.finish(2));
+
+    // Only one follow-up was owed
+    assert_eq!(queue.follow_up(), None);
+}
+
+#[test]
+fn stale_sequences_are_dropped() {
+    let mut queue = RefreshQueue::default();
+    assert_eq!(queue.request(), Some(1));
+    assert!(queue.finish(1));
+    assert_eq!(queue.request(), Some(2));
+
+    // The result of refresh 1 arriving again, or late, doesn't end refresh 2
+    assert!(!queue.finish(1));
+    assert!(queue.in_flight());
+    assert!(!queue.finish(7));
+    assert!(queue.finish(2));
+    assert!(!queue.in_flight());
+}
+
+#[test]
+fn a_follow_up_requested_during_the_follow_up_runs_after_it() {
+    let mut queue = RefreshQueue::default();
+    queue.request();
+    queue.request();
+    queue.finish(1);
+    assert_eq!(queue.follow_up(), Some(2));
+
+    assert_eq!(queue.request(), None);
+    assert!(queue.finish(2));
+    assert_eq!(queue.follow_up(), Some(3));
+}
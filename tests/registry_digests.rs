@@ -0,0 +1,150 @@
+//! Checks the registry side of the update badge against recorded responses: the digest headers
+//! of manifest requests, the authentication challenges and the `docker login` credentials, and
+//! the day long cache of looked up digests.
+#![allow(dead_code)]
+
+#[path = "../src/image_ref.rs"]
+mod image_ref;
+#[path = "../src/registry.rs"]
+mod registry;
+
+use image_ref::ImageRef;
+use registry::{
+    digest_header, manifest_url, parse_challenge, registry_auth, update_available, DigestCache,
+};
+use reqwest::header::{HeaderMap, HeaderValue};
+
+const DAY: u64 = 24 * 60 * 60;
+const NOW: u64 = 1_700_000_000;
+
+const PG16: &str = "sha256:4b8f6e0cf2ea3c3cfb0b2bd4d87bd2b2a1a2e4a53ca0c1a3f6a8e9b0c1d2e3f4";
+
+/// The headers of `HEAD /v2/library/postgres/manifests/16` on docker hub.
+fn manifest_headers() -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    for (name, value) in [
+        ("content-type", "application/vnd.oci.image.index.v1+json"),
+        ("content-length", "10186"),
+        ("docker-content-digest", PG16),
+        ("docker-distribution-api-version", "registry/2.0"),
+        ("etag", "\"sha256:4b8f6e0c\""),
+        ("ratelimit-limit", "100;w=21600"),
+    ] {
+        headers.insert(name, HeaderValue::from_static(value));
+    }
+    headers
+}
+
+#[test]
+fn the_digest_is_read_from_the_manifest_response() {
+    assert_eq!(digest_header(&manifest_headers()).unwrap(), PG16);
+
+    let mut without = manifest_headers();
+    without.remove("docker-content-digest");
+    assert!(digest_header(&without).is_err());
+}
+
+#[test]
+fn manifests_are_fetched_from_the_image_registry() {
+    assert_eq!(
+        manifest_url(&ImageRef::parse("postgres:16")),
+        "https://registry-1.docker.io/v2/library/postgres/manifests/16"
+    );
+    assert_eq!(
+        manifest_url(&ImageRef::parse("ghcr.io/acme/db:2.1")),
+        "https://ghcr.io/v2/acme/db/manifests/2.1"
+    );
+}
+
+#[test]
+fn docker_hub_challenges_are_parsed() {
+    let challenge = parse_challenge(
+        r#"Bearer realm="https://auth.docker.io/token",service="registry.docker.io",scope="repository:library/postgres:pull""#,
+    )
+    .unwrap();
+
+    assert_eq!(challenge.scheme, "bearer");
+    assert_eq!(challenge.params["realm"], "https://auth.docker.io/token");
+    assert_eq!(challenge.params["service"], "registry.docker.io");
+    assert_eq!(
+        challenge.params["scope"],
+        "repository:library/postgres:pull"
+    );
+}
+
+#[test]
+fn basic_challenges_are_parsed() {
+    let challenge = parse_challenge(r#"Basic realm="Registry Realm""#).unwrap();
+
+    assert_eq!(challenge.scheme, "basic");
+    assert_eq!(challenge.params["realm"], "Registry Realm");
+    assert!(parse_challenge("").is_none());
+}
+
+#[test]
+fn credentials_are_found_by_registry() {
+    let config = r#"{
+        "auths": {
+            "https://index.docker.io/v1/": { "auth": "aHViOnNlY3JldA==" },
+            "ghcr.io": { "auth": "Z2hjcjp0b2tlbg==" },
+            "registry.example.com/": { "auth": "" }
+        },
+        "credsStore": "desktop"
+    }"#;
+
+    assert_eq!(
+        registry_auth(config, None).as_deref(),
+        Some("aHViOnNlY3JldA==")
+    );
+    assert_eq!(
+        registry_auth(config, Some("ghcr.io")).as_deref(),
+        Some("Z2hjcjp0b2tlbg==")
+    );
+    assert_eq!(registry_auth(config, Some("registry.example.com")), None);
+    assert_eq!(registry_auth(config, Some("quay.io")), None);
+    assert_eq!(registry_auth("not json", None), None);
+}
+
+#[test]
+fn only_known_differing_digests_are_updates() {
+    assert!(update_available(Some("sha256:old"), Some(PG16)));
+    assert!(!update_available(Some(PG16), Some(PG16)));
+    assert!(!update_available(None, Some(PG16)));
+    assert!(!update_available(Some(PG16), None));
+}
+
+#[test]
+fn digests_are_cached_for_a_day() {
+    let pg16 = ImageRef::parse("postgres:16");
+    let mut cache = DigestCache::default();
+    assert_eq!(cache.stale([&pg16], NOW), vec![pg16.clone()]);
+
+    cache.insert(&pg16, Some(PG16.into()), NOW);
+    assert_eq!(cache.digest(&pg16), Some(PG16));
+    assert!(cache.stale([&pg16], NOW + DAY - 1).is_empty());
+    assert_eq!(cache.stale([&pg16], NOW + DAY), vec![pg16.clone()]);
+
+    // Failed lookups are cached too, so they aren't retried on every check
+    let pg15 = ImageRef::parse("postgres:15");
+    cache.insert(&pg15, None, NOW);
+    assert_eq!(cache.digest(&pg15), None);
+    assert!(cache.stale([&pg15], NOW + 1).is_empty());
+}
+
+#[test]
+fn lookups_are_limited_and_skip_pinned_images() {
+    let images = (0..15)
+        .map(|tag| ImageRef::parse(&format!("postgres:{tag}")))
+        .chain([ImageRef::parse(&format!("postgres@{PG16}"))])
+        .collect::<Vec<_>>();
+
+    let stale = DigestCache::default().stale(&images, NOW);
+    assert_eq!(stale.len(), 10);
+    assert!(stale.iter().all(|image| image.digest.is_none()));
+    // The same tag is only looked up once
+    let twice = [
+        ImageRef::parse("postgres:16"),
+        ImageRef::parse("postgres:16"),
+    ];
+    assert_eq!(DigestCache::default().stale(&twice, NOW).len(), 1);
+}
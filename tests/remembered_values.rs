@@ -0,0 +1,181 @@
+//! Checks what is remembered of a created container and how it fills in the create form when
+//! the same database is selected again.
+#![allow(dead_code)]
+
+#[path = "../src/backup.rs"]
+mod backup;
+mod common;
+#[path = "../src/daemon.rs"]
+mod daemon;
+#[path = "../src/data.rs"]
+mod data;
+#[path = "../src/definition.rs"]
+mod definition;
+#[path = "../src/diff.rs"]
+mod diff;
+#[path = "../src/docker.rs"]
+mod docker;
+#[path = "../src/idle.rs"]
+mod idle;
+#[path = "../src/image_ref.rs"]
+mod image_ref;
+#[path = "../src/names.rs"]
+mod names;
+#[path = "../src/platform.rs"]
+mod platform;
+#[path = "../src/ports.rs"]
+mod ports;
+#[path = "../src/registry.rs"]
+mod registry;
+#[path = "../src/template.rs"]
+mod template;
+#[path = "../src/tls.rs"]
+mod tls;
+
+use std::collections::HashMap;
+
+use common::postgres;
+use data::{Preferences, RememberedValues};
+use docker::DbContainerConfig;
+
+fn created() -> DbContainerConfig {
+    let mut config = postgres().quick_config("orders");
+    config.tag = "16-alpine".into();
+    config.variables = HashMap::from([
+        ("POSTGRES_USERNAME".into(), "orders".into()),
+        ("POSTGRES_PASSWORD".into(), "hunter2".into()),
+        ("POSTGRES_DB".into(), "orders".into()),
+        ("UNDECLARED".into(), "on".into()),
+    ]);
+    config
+}
+
+fn values(tag: &str, variables: &[(&str, &str)]) -> RememberedValues {
+    RememberedValues {
+        tag: tag.into(),
+        variables: variables
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect(),
+    }
+}
+
+/// The preferences as they are read back from the preferences file.
+fn saved(preferences: &Preferences) -> Preferences {
+    serde_yaml::from_str(&serde_yaml::to_string(preferences).unwrap()).unwrap()
+}
+
+#[test]
+fn declared_values_are_remembered_without_passwords() {
+    let mut preferences = Preferences::default();
+    preferences.remember(&postgres(), &created());
+
+    let preferences = saved(&preferences);
+    assert_eq!(
+        preferences.recall(&postgres()),
+        Some(&values(
+            "16-alpine",
+            &[("POSTGRES_USERNAME", "orders"), ("POSTGRES_DB", "orders")]
+        ))
+    );
+}
+
+#[test]
+fn passwords_are_remembered_when_opted_in() {
+    let mut preferences = Preferences {
+        remember_passwords: true,
+        ..Default::default()
+    };
+    preferences.remember(&postgres(), &created());
+
+    let recalled = preferences.recall(&postgres()).unwrap();
+    assert_eq!(recalled.variables["POSTGRES_PASSWORD"], "hunter2");
+    assert!(!recalled.variables.contains_key("UNDECLARED"));
+}
+
+#[test]
+fn only_the_last_container_is_remembered() {
+    let mut preferences = Preferences::default();
+    preferences.remember(&postgres(), &created());
+    let mut second = created();
+    second.tag = "16".into();
+    second.variables.remove("POSTGRES_DB");
+    preferences.remember(&postgres(), &second);
+
+    assert_eq!(
+        preferences.recall(&postgres()),
+        Some(&values("16", &[("POSTGRES_USERNAME", "orders")]))
+    );
+    assert_eq!(preferences.remembered.len(), 1);
+}
+
+#[test]
+fn nothing_is_remembered_or_recalled_when_turned_off() {
+    let mut preferences = Preferences {
+        remember_values: false,
+        ..Default::default()
+    };
+    preferences.remember(&postgres(), &created());
+    assert!(preferences.remembered.is_empty());
+
+    // Values remembered before it was turned off are kept, but not recalled
+    preferences
+        .remembered
+        .insert("Postgres".into(), values("16", &[]));
+    assert_eq!(preferences.recall(&postgres()), None);
+}
+
+#[test]
+fn the_file_keeps_values_by_database_name() {
+    let yaml = "
+remembered:
+  Postgres:
+    tag: '16'
+    variables:
+      POSTGRES_DB: shop
+";
+    let preferences = serde_yaml::from_str::<Preferences>(yaml).expect("Preferences must parse");
+    assert!(preferences.remember_values);
+    assert!(!preferences.remember_passwords);
+    assert_eq!(
+        preferences.recall(&postgres()),
+        Some(&values("16", &[("POSTGRES_DB", "shop")]))
+    );
+}
+
+#[test]
+fn recalled_values_fill_in_the_form() {
+    let mut config = postgres().quick_config("new");
+    let generated = config.variables["POSTGRES_PASSWORD"].clone();
+
+    let filled = values(
+        "16-alpine",
+        &[("POSTGRES_USERNAME", "orders"), ("POSTGRES_DB", "orders")],
+    )
+    .apply(&postgres(), &mut config);
+
+    assert_eq!(config.tag, "16-alpine");
+    assert_eq!(config.variables["POSTGRES_USERNAME"], "orders");
+    assert_eq!(config.variables["POSTGRES_DB"], "orders");
+    // What wasn't remembered keeps what the form had
+    assert_eq!(config.variables["POSTGRES_PASSWORD"], generated);
+    let mut filled = filled;
+    filled.sort();
+    assert_eq!(filled, vec!["POSTGRES_DB", "POSTGRES_USERNAME"]);
+}
+
+#[test]
+fn values_the_entry_no_longer_has_are_skipped() {
+    let mut config = postgres().quick_config("new");
+    let tag = config.tag.clone();
+
+    let filled = values(
+        "9.6",
+        &[("POSTGRES_USER", "renamed"), ("POSTGRES_DB", "kept")],
+    )
+    .apply(&postgres(), &mut config);
+
+    assert_eq!(config.tag, tag, "A tag the entry dropped isn't recalled");
+    assert!(!config.variables.contains_key("POSTGRES_USER"));
+    assert_eq!(filled, vec!["POSTGRES_DB"]);
+}
@@ -0,0 +1,73 @@
+//! Checks the retries offered by failure toasts: each runs at most once and the ones of toasts
+//! that are gone are dropped.
+#![allow(dead_code)]
+
+#[path = "../src/app/retry.rs"]
+mod retry;
+
+use retry::Retries;
+
+#[test]
+fn registered_retries_get_their_own_ids() {
+    let mut retries = Retries::default();
+
+    let first = retries.register("start a");
+    let second = retries.register("start b");
+
+    assert_ne!(first, second);
+    assert_eq!(retries.take(second), Some("start b"));
+    assert_eq!(retries.take(first), Some("start a"));
+}
+
+#[test]
+fn a_double_clicked_retry_runs_once() {
+    let mut retries = Retries::default();
+    let id = retries.register("start a");
+
+    assert_eq!(retries.take(id), Some("start a"));
+    assert_eq!(retries.take(id), None);
+}
+
+#[test]
+fn unknown_ids_get_nothing() {
+    let mut retries = Retries::<&str>::default();
+
+    assert_eq!(retries.take(1), None);
+}
+
+#[test]
+fn retries_of_forgotten_toasts_are_dropped() {
+    let mut retries = Retries::default();
+    let expired = retries.register("start a");
+    let shown = retries.register("start b");
+
+    retries.retain([shown]);
+
+    assert_eq!(retries.take(expired), None);
+    assert_eq!(retries.take(shown), Some("start b"));
+}
+
+#[test]
+fn retaining_nothing_drops_everything() {
+    let mut retries = Retries::default();
+    let ids = ["start a", "stop b", "remove c"].map(|retry| retries.register(retry));
+
+    retries.retain([]);
+
+    for id in ids {
+        assert_eq!(retries.take(id), None);
+    }
+}
+
+#[test]
+fn ids_are_not_reused_after_a_retry_is_dropped() {
+    let mut retries = Retries::default();
+    let old = retries.register("start a");
+    retries.retain([]);
+
+    let new = retries.register("start b");
+
+    // A click on the old toast can't run the new retry
+    assert_ne!(old, new);
+    assert_eq!(retries.take(old), None);
+}
@@ -0,0 +1,262 @@
+//! Checks the `docker run` commands copied from containers, in particular that every value
+//! reaches docker as the single word it was.
+#![allow(dead_code)]
+
+#[path = "../src/backup.rs"]
+mod backup;
+mod common;
+#[path = "../src/daemon.rs"]
+mod daemon;
+#[path = "../src/data.rs"]
+mod data;
+#[path = "../src/definition.rs"]
+mod definition;
+#[path = "../src/diff.rs"]
+mod diff;
+#[path = "../src/docker.rs"]
+mod docker;
+#[path = "../src/idle.rs"]
+mod idle;
+#[path = "../src/image_ref.rs"]
+mod image_ref;
+#[path = "../src/names.rs"]
+mod names;
+#[path = "../src/platform.rs"]
+mod platform;
+#[path = "../src/ports.rs"]
+mod ports;
+#[path = "../src/registry.rs"]
+mod registry;
+#[path = "../src/run_command.rs"]
+mod run_command;
+#[path = "../src/template.rs"]
+mod template;
+#[path = "../src/tls.rs"]
+mod tls;
+
+use std::collections::HashMap;
+
+use bollard::service::{ContainerStateStatusEnum, ContainerSummary};
+use common::postgres;
+use docker::{
+    summary_container, DbContainer, EnvValue, ImageDefaults, PublishedPort, Restart,
+    EXTRA_VARIABLES_LABEL, HOST_PORT_LABEL,
+};
+use run_command::{docker_run_command, shell_quote, RunCommandOptions};
+
+fn container() -> DbContainer {
+    let mut container = summary_container(ContainerSummary {
+        id: Some("0123456789abcdef".into()),
+        names: Some(vec!["/db-mgr__myapp-db".into()]),
+        image: Some("postgres:16".into()),
+        ..Default::default()
+    })
+    .expect("The summary has an id");
+    container.state = ContainerStateStatusEnum::RUNNING;
+    container.variables = HashMap::from([
+        ("POSTGRES_PASSWORD".into(), EnvValue::new("hunter2")),
+        ("POSTGRES_DB".into(), EnvValue::new("myapp")),
+        ("PATH".into(), EnvValue::new("/usr/bin:/bin")),
+    ]);
+    container.labels = HashMap::new();
+    container
+}
+
+/// The image `container` was created from.
+fn image() -> ImageDefaults {
+    ImageDefaults {
+        env: vec!["PATH=/usr/bin:/bin".into()],
+        labels: HashMap::new(),
+        command: vec!["postgres".into()],
+    }
+}
+
+/// The arguments of a copied command, one per line as it is copied.
+fn lines(command: &str) -> Vec<&str> {
+    command.split(" \\\n  ").collect()
+}
+
+#[test]
+fn plain_values_are_left_unquoted() {
+    assert_eq!(shell_quote("postgres:16"), "postgres:16");
+    assert_eq!(
+        shell_quote("/var/lib/postgresql/data"),
+        "/var/lib/postgresql/data"
+    );
+    assert_eq!(shell_quote("KEY=a,b+c@d%e"), "KEY=a,b+c@d%e");
+}
+
+#[test]
+fn values_with_spaces_and_shell_characters_are_quoted() {
+    assert_eq!(shell_quote("hello world"), "'hello world'");
+    assert_eq!(shell_quote(""), "''");
+    assert_eq!(shell_quote("$HOME"), "'$HOME'");
+    assert_eq!(shell_quote("a;rm -rf /"), "'a;rm -rf /'");
+    assert_eq!(shell_quote("`id`"), "'`id`'");
+    assert_eq!(shell_quote("a\"b"), "'a\"b'");
+    assert_eq!(shell_quote("back\\slash"), "'back\\slash'");
+    assert_eq!(shell_quote("line\nbreak"), "'line\nbreak'");
+    assert_eq!(shell_quote("*"), "'*'");
+    assert_eq!(shell_quote("~"), "'~'");
+}
+
+#[test]
+fn single_quotes_close_and_reopen_the_quotes() {
+    assert_eq!(shell_quote("it's"), r"'it'\''s'");
+    assert_eq!(shell_quote("'"), r"''\'''");
+    assert_eq!(shell_quote("''"), r"''\'''\'''");
+}
+
+#[test]
+fn secrets_become_placeholders_unless_included() {
+    let container = container();
+    let command = docker_run_command(
+        &container,
+        Some(&postgres()),
+        &image(),
+        RunCommandOptions::default(),
+    );
+    let copied = lines(&command);
+    assert!(copied.contains(&"-e \"POSTGRES_PASSWORD=$POSTGRES_PASSWORD\""));
+    assert!(copied.contains(&"-e POSTGRES_DB=myapp"));
+    assert!(!command.contains("hunter2"));
+
+    let command = docker_run_command(
+        &container,
+        Some(&postgres()),
+        &image(),
+        RunCommandOptions {
+            include_secrets: true,
+            ..Default::default()
+        },
+    );
+    assert!(lines(&command).contains(&"-e POSTGRES_PASSWORD=hunter2"));
+}
+
+#[test]
+fn inherited_variables_are_left_out() {
+    let mut container = container();
+    container
+        .variables
+        .insert("SHARED_BUFFERS".into(), EnvValue::new("1GB"));
+    container
+        .labels
+        .insert(EXTRA_VARIABLES_LABEL.into(), "SHARED_BUFFERS".into());
+
+    // With a config entry, only declared and extra variables
+    let command = docker_run_command(
+        &container,
+        Some(&postgres()),
+        &ImageDefaults::default(),
+        RunCommandOptions::default(),
+    );
+    assert!(lines(&command).contains(&"-e SHARED_BUFFERS=1GB"));
+    assert!(!command.contains("PATH="));
+
+    // Without one, the ones the image doesn't set the same
+    let command = docker_run_command(&container, None, &image(), RunCommandOptions::default());
+    assert!(!command.contains("PATH="));
+    assert!(lines(&command).contains(&"-e SHARED_BUFFERS=1GB"));
+}
+
+#[test]
+fn values_are_quoted_as_a_whole() {
+    let mut container = container();
+    container.variables = HashMap::from([
+        ("GREETING".into(), EnvValue::new("it's a \"test\"")),
+        ("EMPTY".into(), EnvValue::new("")),
+    ]);
+    container
+        .volumes
+        .insert("/home/me/My Data".into(), "/data".into());
+    container
+        .labels
+        .insert("note".into(), "dev db; don't delete".into());
+    container.command = vec!["postgres".into(), "-c".into(), "max_connections=200".into()];
+
+    let command = docker_run_command(&container, None, &image(), RunCommandOptions::default());
+    let lines = lines(&command);
+    assert!(lines.contains(&r#"-e 'GREETING=it'\''s a "test"'"#));
+    assert!(lines.contains(&"-e EMPTY="));
+    assert!(lines.contains(&"-v '/home/me/My Data:/data'"));
+    assert!(lines.contains(&r"--label 'note=dev db; don'\''t delete'"));
+    assert_eq!(
+        lines.last(),
+        Some(&"postgres:16 postgres -c max_connections=200")
+    );
+}
+
+#[test]
+fn options_follow_the_container() {
+    let mut container = container();
+    container.volumes = HashMap::from([
+        (
+            "db-mgr__myapp-db__pgsql_data".into(),
+            "/var/lib/postgresql/data".into(),
+        ),
+        ("db-mgr__myapp-db__seed".into(), "/seed".into()),
+    ]);
+    container.read_only_volumes = vec!["db-mgr__myapp-db__seed".into()];
+    container.limits.restart = Restart::UnlessStopped;
+    container.ports = vec![
+        PublishedPort {
+            port: 5432,
+            protocol: "tcp".into(),
+            host_ip: "127.0.0.1".into(),
+            host_port: 15432,
+        },
+        PublishedPort {
+            port: 5433,
+            protocol: "udp".into(),
+            host_ip: "0.0.0.0".into(),
+            host_port: 5433,
+        },
+    ];
+    container.command = vec!["postgres".into()];
+
+    let command = docker_run_command(
+        &container,
+        Some(&postgres()),
+        &image(),
+        RunCommandOptions::default(),
+    );
+    assert_eq!(
+        lines(&command),
+        vec![
+            "docker run -d",
+            "--name myapp-db",
+            "--restart unless-stopped",
+            "-p 127.0.0.1:15432:5432",
+            "-p 5433:5433/udp",
+            "-e POSTGRES_DB=myapp",
+            "-e \"POSTGRES_PASSWORD=$POSTGRES_PASSWORD\"",
+            "-v db-mgr__myapp-db__pgsql_data:/var/lib/postgresql/data",
+            "-v db-mgr__myapp-db__seed:/seed:ro",
+            // The image's own command isn't repeated
+            "postgres:16",
+        ]
+    );
+
+    let command = docker_run_command(
+        &container,
+        Some(&postgres()),
+        &image(),
+        RunCommandOptions {
+            keep_prefix: true,
+            ..Default::default()
+        },
+    );
+    assert!(lines(&command).contains(&"--name db-mgr__myapp-db"));
+}
+
+#[test]
+fn stopped_containers_publish_their_assigned_port() {
+    let mut container = container();
+    container.state = ContainerStateStatusEnum::EXITED;
+    container
+        .labels
+        .insert(HOST_PORT_LABEL.into(), "5432:15432".into());
+
+    let command = docker_run_command(&container, None, &image(), RunCommandOptions::default());
+    assert!(lines(&command).contains(&"-p 127.0.0.1:15432:5432"));
+}
@@ -0,0 +1,151 @@
+//! Checks how a dropped file is matched to the container it runs in, the confirmation asked for
+//! and the summary shown once it ran, none of which needs a window.
+#![allow(dead_code)]
+
+#[path = "../src/backup.rs"]
+mod backup;
+#[path = "../src/daemon.rs"]
+mod daemon;
+#[path = "../src/data.rs"]
+mod data;
+#[path = "../src/definition.rs"]
+mod definition;
+#[path = "../src/diff.rs"]
+mod diff;
+#[path = "../src/docker.rs"]
+mod docker;
+#[path = "../src/idle.rs"]
+mod idle;
+#[path = "../src/image_ref.rs"]
+mod image_ref;
+#[path = "../src/names.rs"]
+mod names;
+#[path = "../src/platform.rs"]
+mod platform;
+#[path = "../src/ports.rs"]
+mod ports;
+#[path = "../src/registry.rs"]
+mod registry;
+#[path = "../src/app/sql_file.rs"]
+mod sql_file;
+#[path = "../src/template.rs"]
+mod template;
+#[path = "../src/tls.rs"]
+mod tls;
+
+use std::path::{Path, PathBuf};
+
+use data::{ConfigFile, DatabaseConfig};
+use sql_file::{confirm_prompt, drop_target, file_name, is_sql_file, DropTarget, SqlRun};
+
+fn bundled(name: &str) -> DatabaseConfig {
+    serde_yaml::from_str::<ConfigFile>(include_str!("../config.yaml"))
+        .expect("The bundled config must parse")
+        .databases
+        .into_iter()
+        .find(|database| database.name == name)
+        .expect("The entry is bundled")
+}
+
+fn ids(ids: &[&str]) -> Vec<String> {
+    ids.iter().map(|id| id.to_string()).collect()
+}
+
+#[test]
+fn only_sql_files_are_run() {
+    assert!(is_sql_file(Path::new("/home/me/schema.sql")));
+    assert!(is_sql_file(Path::new("SEED.SQL")));
+    assert!(!is_sql_file(Path::new("schema.sql.gz")));
+    assert!(!is_sql_file(Path::new("notes.txt")));
+    assert!(!is_sql_file(Path::new("sql")));
+    assert!(!is_sql_file(Path::new("/home/me/.sql")));
+}
+
+#[test]
+fn the_viewed_container_runs_the_file() {
+    assert_eq!(
+        drop_target(Some("b"), &ids(&["a", "b"])),
+        DropTarget::Container("b".into())
+    );
+}
+
+#[test]
+fn the_only_candidate_runs_the_file() {
+    assert_eq!(
+        drop_target(None, &ids(&["a"])),
+        DropTarget::Container("a".into())
+    );
+    // A viewed container that can't run SQL doesn't get in the way
+    assert_eq!(
+        drop_target(Some("stopped"), &ids(&["a"])),
+        DropTarget::Container("a".into())
+    );
+}
+
+#[test]
+fn several_candidates_are_picked_from() {
+    assert_eq!(
+        drop_target(Some("stopped"), &ids(&["a", "b"])),
+        DropTarget::Pick(ids(&["a", "b"]))
+    );
+    assert_eq!(drop_target(Some("a"), &[]), DropTarget::None);
+    assert_eq!(drop_target(None, &[]), DropTarget::None);
+}
+
+#[test]
+fn the_prompt_names_the_file_and_container() {
+    assert_eq!(
+        confirm_prompt(Path::new("/home/me/schema.sql"), "db-mgr__orders"),
+        "Run schema.sql against db-mgr__orders?"
+    );
+    assert_eq!(file_name(Path::new("/")), "/");
+}
+
+#[test]
+fn the_summary_has_the_exit_status_and_last_lines() {
+    let mut run = SqlRun::new(
+        "0123".into(),
+        "db-mgr__orders".into(),
+        PathBuf::from("/home/me/schema.sql"),
+        None,
+        1,
+    );
+    assert_eq!(
+        run.summary(Some(0)),
+        "schema.sql ran against db-mgr__orders"
+    );
+    assert_eq!(
+        run.summary(None),
+        "schema.sql ran against db-mgr__orders, its exit code is unknown"
+    );
+
+    for line in 1..=25 {
+        run.push_output(&format!("line {line}\n"));
+    }
+    let summary = run.summary(Some(3));
+    let mut lines = summary.lines();
+    assert_eq!(
+        lines.next(),
+        Some("schema.sql failed against db-mgr__orders with exit code 3")
+    );
+    assert_eq!(lines.next(), Some(""));
+    let tail = lines.collect::<Vec<_>>();
+    assert_eq!(tail.len(), 20);
+    assert_eq!(tail.first(), Some(&"line 6"));
+    assert_eq!(tail.last(), Some(&"line 25"));
+}
+
+#[test]
+fn progress_is_the_fraction_written() {
+    let mut run = SqlRun::new("0123".into(), "orders".into(), "a.sql".into(), None, 1);
+    assert_eq!(run.fraction(), 0.0);
+
+    run.progress = (25, 100);
+    assert_eq!(run.fraction(), 0.25);
+}
+
+#[test]
+fn bundled_entries_with_a_command_run_sql() {
+    assert!(bundled("Postgres").runs_sql());
+    assert!(!bundled("Redis").runs_sql());
+}
@@ -0,0 +1,352 @@
+//! Checks that containers are started after the containers they depend on are healthy, against
+//! a docker whose health checks are scripted.
+#![allow(dead_code)]
+
+#[path = "../src/daemon.rs"]
+mod daemon;
+#[path = "../src/dependencies.rs"]
+mod dependencies;
+#[path = "../src/docker.rs"]
+mod docker;
+#[path = "../src/image_ref.rs"]
+mod image_ref;
+#[path = "../src/platform.rs"]
+mod platform;
+#[path = "../src/ports.rs"]
+mod ports;
+#[path = "../src/start_order.rs"]
+mod start_order;
+
+use std::{
+    collections::{HashMap, VecDeque},
+    future::Future,
+    pin::Pin,
+    sync::Mutex,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use bollard::service::HealthStatusEnum;
+use dependencies::{start_layers, Graph};
+use futures::{
+    channel::mpsc::channel, executor::block_on, future::BoxFuture, FutureExt, StreamExt,
+};
+use start_order::{start_in_order, Readiness, StartEvent, StartRuntime};
+
+/// A docker whose containers report the scripted readiness one check after the other, the last
+/// one from then on.
+#[derive(Default)]
+struct Scripted {
+    readiness: Mutex<HashMap<String, VecDeque<Readiness>>>,
+    failing: Vec<String>,
+    /// What was asked of it, such as `start pg` and `check pg`.
+    calls: Mutex<Vec<String>>,
+}
+
+impl Scripted {
+    fn script(self, name: &str, script: Vec<Readiness>) -> Self {
+        self.readiness
+            .lock()
+            .unwrap()
+            .insert(name.to_string(), script.into());
+        self
+    }
+
+    fn calls(&self) -> Vec<String> {
+        self.calls.lock().unwrap().clone()
+    }
+
+    fn position(&self, call: &str) -> usize {
+        self.calls()
+            .iter()
+            .position(|made| made == call)
+            .unwrap_or_else(|| panic!("{call} was never made: {:?}", self.calls()))
+    }
+}
+
+impl StartRuntime for Scripted {
+    fn start<'a>(&'a self, name: &'a str) -> BoxFuture<'a, anyhow::Result<()>> {
+        self.calls.lock().unwrap().push(format!("start {name}"));
+        let failing = self.failing.iter().any(|failing| failing == name);
+        async move {
+            match failing {
+                true => Err(anyhow::anyhow!("No such image")),
+                false => Ok(()),
+            }
+        }
+        .boxed()
+    }
+
+    fn readiness<'a>(&'a self, name: &'a str) -> BoxFuture<'a, anyhow::Result<Readiness>> {
+        self.calls.lock().unwrap().push(format!("check {name}"));
+        let mut scripts = self.readiness.lock().unwrap();
+        let script = scripts.entry(name.to_string()).or_default();
+        let readiness = match script.len() {
+            0 => running(None),
+            1 => script[0],
+            _ => script.pop_front().unwrap(),
+        };
+        async move { Ok(readiness) }.boxed()
+    }
+
+    fn sleep(&self, _duration: Duration) -> BoxFuture<'_, ()> {
+        YieldOnce(false).boxed()
+    }
+}
+
+/// Lets the other futures of a test run once, as a real sleep would, without waiting.
+struct YieldOnce(bool);
+
+impl Future for YieldOnce {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            return Poll::Ready(());
+        }
+        self.0 = true;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
+
+fn running(health: Option<HealthStatusEnum>) -> Readiness {
+    Readiness {
+        running: true,
+        health,
+        port_open: None,
+    }
+}
+
+fn graph(edges: &[(&str, &[&str])]) -> Graph {
+    edges
+        .iter()
+        .map(|(name, depends_on)| {
+            (
+                name.to_string(),
+                depends_on.iter().map(|name| name.to_string()).collect(),
+            )
+        })
+        .collect()
+}
+
+fn names(names: &[&str]) -> Vec<String> {
+    names.iter().map(|name| name.to_string()).collect()
+}
+
+/// Runs the ordered start to the end, returning what it reported.
+fn start(
+    runtime: &Scripted,
+    graph: &Graph,
+    started: &[&str],
+    timeout: Duration,
+) -> Vec<StartEvent> {
+    let (mut tx, rx) = channel(100);
+    block_on(start_in_order(
+        runtime,
+        graph,
+        names(started),
+        timeout,
+        &mut tx,
+    ));
+    drop(tx);
+    block_on(rx.collect())
+}
+
+#[test]
+fn layers_follow_the_dependencies() {
+    let graph = graph(&[
+        ("pg", &[]),
+        ("redis", &[]),
+        ("api", &["pg", "redis"]),
+        ("worker", &["api"]),
+        ("adminer", &["pg"]),
+    ]);
+
+    assert_eq!(
+        start_layers(&graph, &names(&["worker", "adminer", "api", "redis", "pg"])),
+        Ok(vec![
+            names(&["pg", "redis"]),
+            names(&["adminer", "api"]),
+            names(&["worker"]),
+        ])
+    );
+    // Dependencies outside of the started containers aren't waited for
+    assert_eq!(
+        start_layers(&graph, &names(&["worker", "api"])),
+        Ok(vec![names(&["api"]), names(&["worker"])])
+    );
+}
+
+#[test]
+fn cycles_and_missing_dependencies_are_reported() {
+    let cyclic = graph(&[("a", &["b"]), ("b", &["a"])]);
+    assert!(start_layers(&cyclic, &names(&["a", "b"]))
+        .unwrap_err()
+        .contains("depend on each other"));
+
+    let missing = graph(&[("api", &["pg"])]);
+    assert_eq!(
+        start_layers(&missing, &names(&["api"])),
+        Err("api depends on pg, which doesn't exist".to_string())
+    );
+}
+
+#[test]
+fn dependents_wait_until_healthy() {
+    let runtime = Scripted::default().script(
+        "pg",
+        vec![
+            running(Some(HealthStatusEnum::STARTING)),
+            running(Some(HealthStatusEnum::STARTING)),
+            running(Some(HealthStatusEnum::HEALTHY)),
+        ],
+    );
+    let graph = graph(&[("pg", &[]), ("api", &["pg"])]);
+
+    let events = start(&runtime, &graph, &["api", "pg"], Duration::from_secs(60));
+    assert_eq!(
+        events,
+        vec![
+            StartEvent::Starting("pg".into()),
+            StartEvent::Waiting("pg".into()),
+            StartEvent::Ready("pg".into(), Duration::from_secs(2)),
+            StartEvent::Starting("api".into()),
+            StartEvent::Started("api".into()),
+            StartEvent::Done,
+        ]
+    );
+    assert_eq!(
+        runtime.calls(),
+        vec!["start pg", "check pg", "check pg", "check pg", "start api"]
+    );
+}
+
+#[test]
+fn images_without_health_checks_wait_for_their_port() {
+    let closed = Readiness {
+        running: true,
+        health: None,
+        port_open: Some(false),
+    };
+    let runtime = Scripted::default().script(
+        "mysql",
+        vec![
+            closed,
+            closed,
+            closed,
+            Readiness {
+                port_open: Some(true),
+                ..closed
+            },
+        ],
+    );
+    let graph = graph(&[("mysql", &[]), ("app", &["mysql"])]);
+
+    let events = start(&runtime, &graph, &["mysql", "app"], Duration::from_secs(60));
+    assert!(events.contains(&StartEvent::Ready("mysql".into(), Duration::from_secs(3))));
+    assert!(runtime.position("start app") > runtime.position("check mysql"));
+}
+
+#[test]
+fn dependents_start_anyway_after_the_timeout() {
+    let runtime = Scripted::default().script("pg", vec![running(Some(HealthStatusEnum::STARTING))]);
+    let graph = graph(&[("pg", &[]), ("api", &["pg"])]);
+
+    let events = start(&runtime, &graph, &["pg", "api"], Duration::from_secs(5));
+    assert!(events.contains(&StartEvent::NotReady(
+        "pg".into(),
+        "It wasn't healthy after 5 seconds".into()
+    )));
+    assert!(events.contains(&StartEvent::Started("api".into())));
+    // Checked right away and after each of the 5 seconds
+    assert_eq!(
+        runtime
+            .calls()
+            .iter()
+            .filter(|call| *call == "check pg")
+            .count(),
+        6
+    );
+}
+
+#[test]
+fn unhealthy_exited_and_failed_containers_are_reported() {
+    let runtime = Scripted {
+        failing: vec!["cache".into()],
+        ..Default::default()
+    }
+    .script("pg", vec![running(Some(HealthStatusEnum::UNHEALTHY))])
+    .script(
+        "mongo",
+        vec![Readiness {
+            running: false,
+            ..Default::default()
+        }],
+    );
+    let graph = graph(&[
+        ("pg", &[]),
+        ("mongo", &[]),
+        ("cache", &[]),
+        ("api", &["pg", "mongo", "cache"]),
+    ]);
+
+    let events = start(
+        &runtime,
+        &graph,
+        &["pg", "mongo", "cache", "api"],
+        Duration::from_secs(60),
+    );
+    assert!(events.contains(&StartEvent::NotReady(
+        "pg".into(),
+        "Its health check failed".into()
+    )));
+    assert!(events.contains(&StartEvent::Failed(
+        "mongo".into(),
+        "It exited while it was waited for".into()
+    )));
+    assert!(events.contains(&StartEvent::Failed("cache".into(), "No such image".into())));
+    assert!(!runtime.calls().contains(&"check cache".to_string()));
+    assert_eq!(events.last(), Some(&StartEvent::Done));
+}
+
+#[test]
+fn cycles_start_everything_at_once() {
+    let runtime = Scripted::default();
+    let graph = graph(&[("a", &["b"]), ("b", &["a"])]);
+
+    let events = start(&runtime, &graph, &["a", "b"], Duration::from_secs(60));
+    assert!(
+        matches!(&events[0], StartEvent::Unordered(warning) if warning.contains("a -> b")
+        || warning.contains("b -> a"))
+    );
+    assert_eq!(runtime.calls(), vec!["start a", "start b"]);
+    assert!(events.contains(&StartEvent::Started("a".into())));
+    assert!(events.contains(&StartEvent::Started("b".into())));
+}
+
+#[test]
+fn dropping_the_receiver_cancels_the_start() {
+    let runtime = Scripted::default().script("pg", vec![running(Some(HealthStatusEnum::STARTING))]);
+    let graph = graph(&[("pg", &[]), ("api", &["pg"])]);
+
+    let (mut tx, mut rx) = channel(100);
+    let engine = start_in_order(
+        &runtime,
+        &graph,
+        names(&["pg", "api"]),
+        Duration::from_secs(3600),
+        &mut tx,
+    );
+    // The UI goes away once pg is being waited for
+    let ui = async move {
+        while let Some(event) = rx.next().await {
+            if event == StartEvent::Waiting("pg".into()) {
+                break;
+            }
+        }
+    };
+    block_on(futures::future::join(engine, ui));
+
+    assert!(!runtime.calls().contains(&"start api".to_string()));
+}
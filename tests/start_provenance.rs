@@ -0,0 +1,156 @@
+//! Checks how the starts db-mgr sends are matched with the starts docker reports, whichever
+//! arrives first, and what is left for starts nobody in db-mgr asked for.
+#![allow(dead_code)]
+
+#[path = "../src/daemon.rs"]
+mod daemon;
+#[path = "../src/docker.rs"]
+mod docker;
+#[path = "../src/app/events.rs"]
+mod events;
+#[path = "../src/image_ref.rs"]
+mod image_ref;
+#[path = "../src/platform.rs"]
+mod platform;
+#[path = "../src/ports.rs"]
+mod ports;
+#[path = "../src/app/provenance.rs"]
+mod provenance;
+
+use provenance::{Provenance, StartProvenance, StartSource, MATCH_WINDOW};
+
+const NOW: i64 = 1_700_000_000;
+
+/// What happened to a container, in the order db-mgr saw it.
+enum Step {
+    Sent(&'static str, i64),
+    Reported(&'static str, i64),
+    Stopped(&'static str),
+}
+
+fn replay(steps: Vec<Step>) -> StartProvenance {
+    let mut provenance = StartProvenance::default();
+    for step in steps {
+        match step {
+            Step::Sent(id, at) => provenance.sent(id.into(), at),
+            Step::Reported(id, at) => provenance.reported(id.into(), at),
+            Step::Stopped(id) => provenance.stopped(id),
+        }
+    }
+    provenance
+}
+
+fn source(provenance: &StartProvenance, id: &str) -> Option<StartSource> {
+    provenance.get(id).map(|started| started.source)
+}
+
+#[test]
+fn starts_reported_after_sending_are_the_apps() {
+    let provenance = replay(vec![Step::Sent("a", NOW), Step::Reported("a", NOW + 2)]);
+
+    assert_eq!(
+        provenance.get("a"),
+        Some(Provenance {
+            source: StartSource::App,
+            at: NOW + 2,
+        })
+    );
+}
+
+#[test]
+fn starts_reported_before_sending_are_the_apps() {
+    // Docker's event can beat the response to the start request
+    let provenance = replay(vec![Step::Reported("a", NOW), Step::Sent("a", NOW + 1)]);
+
+    assert_eq!(source(&provenance, "a"), Some(StartSource::App));
+    // It was started when docker said it was
+    assert_eq!(provenance.get("a").map(|started| started.at), Some(NOW));
+}
+
+#[test]
+fn starts_nobody_sent_are_external() {
+    let provenance = replay(vec![Step::Reported("a", NOW)]);
+
+    assert_eq!(source(&provenance, "a"), Some(StartSource::External));
+}
+
+#[test]
+fn starts_outside_the_window_are_not_matched() {
+    let provenance = replay(vec![
+        Step::Sent("a", NOW),
+        Step::Reported("a", NOW + MATCH_WINDOW + 1),
+        Step::Reported("b", NOW),
+        Step::Sent("b", NOW + MATCH_WINDOW + 1),
+    ]);
+
+    assert_eq!(source(&provenance, "a"), Some(StartSource::External));
+    assert_eq!(source(&provenance, "b"), Some(StartSource::External));
+}
+
+#[test]
+fn the_edge_of_the_window_still_matches() {
+    let provenance = replay(vec![
+        Step::Sent("a", NOW),
+        Step::Reported("a", NOW + MATCH_WINDOW),
+        Step::Reported("b", NOW),
+        Step::Sent("b", NOW + MATCH_WINDOW),
+    ]);
+
+    assert_eq!(source(&provenance, "a"), Some(StartSource::App));
+    assert_eq!(source(&provenance, "b"), Some(StartSource::App));
+}
+
+#[test]
+fn starts_are_matched_by_container() {
+    let provenance = replay(vec![
+        Step::Sent("a", NOW),
+        Step::Reported("b", NOW + 1),
+        Step::Reported("a", NOW + 2),
+    ]);
+
+    assert_eq!(source(&provenance, "a"), Some(StartSource::App));
+    assert_eq!(source(&provenance, "b"), Some(StartSource::External));
+}
+
+#[test]
+fn a_sent_start_explains_one_report() {
+    // Restarted by its restart policy right after db-mgr started it
+    let provenance = replay(vec![
+        Step::Sent("a", NOW),
+        Step::Reported("a", NOW + 1),
+        Step::Stopped("a"),
+        Step::Reported("a", NOW + 5),
+    ]);
+
+    assert_eq!(source(&provenance, "a"), Some(StartSource::External));
+}
+
+#[test]
+fn stopping_forgets_the_start() {
+    let provenance = replay(vec![
+        Step::Sent("a", NOW),
+        Step::Reported("a", NOW + 1),
+        Step::Stopped("a"),
+    ]);
+
+    assert_eq!(provenance.get("a"), None);
+    // Containers running before db-mgr was opened have no entry either
+    assert_eq!(provenance.get("b"), None);
+}
+
+#[test]
+fn the_source_and_time_are_described() {
+    let started = |source| Provenance {
+        source,
+        at: NOW - 4 * 60,
+    };
+
+    assert_eq!(
+        started(StartSource::App).describe(NOW),
+        "Started by db-mgr 4 min ago"
+    );
+    assert_eq!(
+        started(StartSource::External).describe(NOW),
+        "Started outside db-mgr 4 min ago"
+    );
+}
@@ -0,0 +1,106 @@
+//! Checks the label and colour each container state is shown with, and that a failing health
+//! check takes over the badge.
+#![allow(dead_code)]
+
+#[path = "../src/daemon.rs"]
+mod daemon;
+#[path = "../src/docker.rs"]
+mod docker;
+#[path = "../src/image_ref.rs"]
+mod image_ref;
+#[path = "../src/platform.rs"]
+mod platform;
+#[path = "../src/ports.rs"]
+mod ports;
+#[path = "../src/app/state_badge.rs"]
+mod state_badge;
+
+use bollard::service::{ContainerStateStatusEnum, HealthStatusEnum};
+use docker::parse_summary_health;
+use iced_aw::BadgeStyles;
+use itertools::Itertools;
+use state_badge::{state_label, state_look, LEGEND};
+
+use ContainerStateStatusEnum::*;
+
+const STATES: [ContainerStateStatusEnum; 8] = [
+    EMPTY, CREATED, RUNNING, PAUSED, RESTARTING, REMOVING, EXITED, DEAD,
+];
+
+#[test]
+fn every_state_has_its_own_label() {
+    let labels = STATES.map(state_label);
+
+    assert_eq!(
+        labels,
+        [
+            "unknown",
+            "created",
+            "running",
+            "paused",
+            "restarting",
+            "removing",
+            "exited",
+            "dead"
+        ]
+    );
+}
+
+#[test]
+fn states_are_coloured_by_how_they_are_doing() {
+    let style = |state| state_look(state, None).1;
+
+    assert!(matches!(style(RUNNING), BadgeStyles::Success));
+    assert!(matches!(style(PAUSED), BadgeStyles::Warning));
+    assert!(matches!(style(REMOVING), BadgeStyles::Warning));
+    assert!(matches!(style(CREATED), BadgeStyles::Info));
+    assert!(matches!(style(EXITED), BadgeStyles::Secondary));
+    assert!(matches!(style(EMPTY), BadgeStyles::Secondary));
+    assert!(matches!(style(DEAD), BadgeStyles::Danger));
+    assert!(matches!(style(RESTARTING), BadgeStyles::Danger));
+}
+
+#[test]
+fn failing_health_checks_override_the_state() {
+    let (label, style) = state_look(RUNNING, Some(HealthStatusEnum::UNHEALTHY));
+    assert_eq!(label, "unhealthy");
+    assert!(matches!(style, BadgeStyles::Danger));
+
+    for health in [
+        None,
+        Some(HealthStatusEnum::HEALTHY),
+        Some(HealthStatusEnum::STARTING),
+        Some(HealthStatusEnum::NONE),
+    ] {
+        let (label, style) = state_look(RUNNING, health);
+        assert_eq!(label, "running");
+        assert!(matches!(style, BadgeStyles::Success));
+    }
+}
+
+#[test]
+fn health_is_read_from_the_summary_status() {
+    assert_eq!(
+        parse_summary_health("Up 5 minutes (unhealthy)"),
+        Some(HealthStatusEnum::UNHEALTHY)
+    );
+    assert_eq!(
+        parse_summary_health("Up 2 hours (healthy)"),
+        Some(HealthStatusEnum::HEALTHY)
+    );
+    assert_eq!(
+        parse_summary_health("Up 3 seconds (health: starting)"),
+        Some(HealthStatusEnum::STARTING)
+    );
+    assert_eq!(parse_summary_health("Exited (0) 2 days ago"), None);
+}
+
+#[test]
+fn the_legend_explains_each_state_once() {
+    let states = LEGEND.iter().map(|(state, _)| *state).collect::<Vec<_>>();
+
+    assert!(states.iter().map(|state| state_label(*state)).all_unique());
+    assert!(LEGEND.iter().all(|(_, meaning)| !meaning.is_empty()));
+    assert!(states.contains(&RUNNING));
+    assert!(states.contains(&EXITED));
+}
@@ -0,0 +1,139 @@
+//! Checks the JSON document written to the state file and printed by `db-mgr list --json`, whose
+//! shape scripts rely on, and how the file is written.
+#![allow(dead_code)]
+
+#[path = "../src/backup.rs"]
+mod backup;
+mod common;
+#[path = "../src/daemon.rs"]
+mod daemon;
+#[path = "../src/data.rs"]
+mod data;
+#[path = "../src/definition.rs"]
+mod definition;
+#[path = "../src/diff.rs"]
+mod diff;
+#[path = "../src/docker.rs"]
+mod docker;
+#[path = "../src/idle.rs"]
+mod idle;
+#[path = "../src/image_ref.rs"]
+mod image_ref;
+#[path = "../src/names.rs"]
+mod names;
+#[path = "../src/platform.rs"]
+mod platform;
+#[path = "../src/ports.rs"]
+mod ports;
+#[path = "../src/registry.rs"]
+mod registry;
+#[path = "../src/state_file.rs"]
+mod state_file;
+#[path = "../src/template.rs"]
+mod template;
+#[path = "../src/tls.rs"]
+mod tls;
+
+use std::{fs, path::PathBuf};
+
+use bollard::service::ContainerStateStatusEnum;
+use common::container;
+use docker::{DbContainer, PublishedPort};
+use serde_json::json;
+use state_file::{write_state_file, StateDocument, STATE_VERSION};
+
+fn document() -> StateDocument {
+    let mut orders = container("db-mgr__orders")
+        .state(ContainerStateStatusEnum::RUNNING)
+        .build();
+    orders.ports = vec![PublishedPort {
+        port: 5432,
+        protocol: "tcp".into(),
+        host_ip: "127.0.0.1".into(),
+        host_port: 49153,
+    }];
+    let cache = container("db-mgr__cache")
+        .state(ContainerStateStatusEnum::EXITED)
+        .build();
+    let project =
+        |container: &DbContainer| (container.name == "/db-mgr__orders").then(|| "shop".to_string());
+
+    StateDocument::new(&[orders, cache], project, 1_700_000_000)
+}
+
+fn temp_dir(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("db-mgr-state-file-{}-{test}", std::process::id()));
+    _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).expect("The temp dir can be created");
+    dir
+}
+
+#[test]
+fn the_document_keeps_its_shape() {
+    assert_eq!(STATE_VERSION, 1);
+    assert_eq!(
+        serde_json::to_value(document()).expect("The document serializes"),
+        json!({
+            "version": 1,
+            "updated_at": 1_700_000_000,
+            "containers": [
+                {
+                    "name": "db-mgr__cache",
+                    "state": "exited",
+                    "image": "postgres:16",
+                    "ports": [],
+                    "project": null
+                },
+                {
+                    "name": "db-mgr__orders",
+                    "state": "running",
+                    "image": "postgres:16",
+                    "ports": [{
+                        "container_port": 5432,
+                        "protocol": "tcp",
+                        "host_ip": "127.0.0.1",
+                        "host_port": 49153
+                    }],
+                    "project": "shop"
+                }
+            ]
+        })
+    );
+}
+
+#[test]
+fn documents_read_back_as_they_were_written() {
+    let json = serde_json::to_string(&document()).expect("The document serializes");
+
+    assert_eq!(
+        serde_json::from_str::<StateDocument>(&json).expect("The document reads back"),
+        document()
+    );
+}
+
+#[test]
+fn the_file_is_replaced_without_leaving_the_temporary_file() {
+    let dir = temp_dir("replace");
+    let path = dir.join("state.json");
+    fs::write(&path, "stale").unwrap();
+
+    write_state_file(&path, &document()).expect("The state file is written");
+
+    let written = fs::read_to_string(&path).unwrap();
+    assert_eq!(
+        serde_json::from_str::<StateDocument>(&written).unwrap(),
+        document()
+    );
+    assert!(!dir.join("state.json.tmp").exists());
+    _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn unwritable_paths_are_reported() {
+    let dir = temp_dir("unwritable");
+    let file = dir.join("file");
+    fs::write(&file, "").unwrap();
+
+    assert!(write_state_file(&file.join("state.json"), &document()).is_err());
+    _ = fs::remove_dir_all(&dir);
+}
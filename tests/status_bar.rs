@@ -0,0 +1,97 @@
+//! Checks the strings the status bar shows, the shortened docker endpoint and when the
+//! containers were last refreshed.
+#![allow(dead_code)]
+
+#[path = "../src/daemon.rs"]
+mod daemon;
+#[path = "../src/docker.rs"]
+mod docker;
+#[path = "../src/app/events.rs"]
+mod events;
+#[path = "../src/image_ref.rs"]
+mod image_ref;
+#[path = "../src/platform.rs"]
+mod platform;
+#[path = "../src/ports.rs"]
+mod ports;
+#[path = "../src/app/status_bar.rs"]
+mod status_bar;
+#[path = "../src/tls.rs"]
+mod tls;
+
+use status_bar::{docker_endpoint, refreshed_label, short_endpoint};
+use tls::TlsClient;
+
+#[test]
+fn local_endpoints_are_shortened_to_the_socket() {
+    assert_eq!(short_endpoint("unix:///var/run/docker.sock"), "docker.sock");
+    assert_eq!(
+        short_endpoint("unix:///home/me/.colima/default/docker.sock"),
+        "docker.sock"
+    );
+    assert_eq!(
+        short_endpoint("npipe:////./pipe/docker_engine"),
+        "docker_engine"
+    );
+    assert_eq!(
+        short_endpoint(r"npipe://\\.\pipe\docker_engine"),
+        "docker_engine"
+    );
+}
+
+#[test]
+fn remote_endpoints_are_shortened_to_the_host() {
+    assert_eq!(
+        short_endpoint("tcp://docker.example.com:2376"),
+        "docker.example.com:2376"
+    );
+    assert_eq!(
+        short_endpoint("https://docker.example.com:2376/v1.43"),
+        "docker.example.com:2376"
+    );
+    assert_eq!(short_endpoint("ssh://deploy@build-box"), "build-box");
+    assert_eq!(short_endpoint("build-box:2375"), "build-box:2375");
+}
+
+#[test]
+fn tls_endpoints_use_the_certificate_host() {
+    let tls = TlsClient {
+        enabled: true,
+        host: "tcp://docker.example.com:2376".into(),
+        ..TlsClient::default()
+    };
+
+    assert_eq!(
+        docker_endpoint(Some("unix:///var/run/docker.sock"), &tls),
+        "https://docker.example.com:2376"
+    );
+}
+
+#[test]
+fn the_setting_is_used_without_tls() {
+    assert_eq!(
+        docker_endpoint(Some(" tcp://10.0.0.2:2375 "), &TlsClient::default()),
+        "tcp://10.0.0.2:2375"
+    );
+}
+
+#[test]
+fn refresh_times_are_relative() {
+    let now = 1_700_000_000;
+
+    assert_eq!(refreshed_label(None, now), "Not refreshed yet");
+    assert_eq!(refreshed_label(Some(now - 5), now), "Refreshed just now");
+    assert_eq!(refreshed_label(Some(now - 125), now), "Refreshed 2 min ago");
+    assert_eq!(refreshed_label(Some(now - 7200), now), "Refreshed 2 h ago");
+    assert_eq!(
+        refreshed_label(Some(now - 86_400), now),
+        "Refreshed 1 day ago"
+    );
+}
+
+#[test]
+fn refreshes_from_a_clock_ahead_count_as_just_now() {
+    let now = 1_700_000_000;
+
+    assert_eq!(refreshed_label(Some(now + 30), now), "Refreshed just now");
+}
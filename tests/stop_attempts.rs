@@ -0,0 +1,141 @@
+//! Walks a container's stop from `SIGTERM` through the offer to force stop it to `SIGKILL`, and
+//! checks that its exit clears the attempt.
+#![allow(dead_code)]
+
+#[path = "../src/app/stopping.rs"]
+mod stopping;
+
+use std::time::{Duration, Instant};
+
+use stopping::{StopAttempts, StopState, DEFAULT_GRACE};
+
+const GRACE: Duration = Duration::from_secs(30);
+
+fn secs(secs: u64) -> Duration {
+    Duration::from_secs(secs)
+}
+
+#[test]
+fn containers_that_werent_stopped_are_idle() {
+    let stops = StopAttempts::default();
+
+    assert_eq!(stops.state("0123", Instant::now()), StopState::Idle);
+    assert!(!StopState::Idle.is_stopping());
+    assert!(stops.is_empty());
+}
+
+#[test]
+fn stopping_counts_the_seconds_since_sigterm() {
+    let start = Instant::now();
+    let mut stops = StopAttempts::default();
+    stops.begin("0123".into(), GRACE, start);
+
+    assert_eq!(stops.state("0123", start), StopState::Stopping(0));
+    assert_eq!(
+        stops.state("0123", start + secs(25)),
+        StopState::Stopping(25)
+    );
+    assert!(stops.state("0123", start).is_stopping());
+    assert_eq!(stops.state("4567", start), StopState::Idle);
+}
+
+#[test]
+fn force_stopping_is_offered_once_the_grace_period_passed() {
+    let start = Instant::now();
+    let mut stops = StopAttempts::default();
+    stops.begin("0123".into(), GRACE, start);
+
+    assert_eq!(
+        stops.state("0123", start + secs(29)),
+        StopState::Stopping(29)
+    );
+    assert_eq!(
+        stops.state("0123", start + GRACE),
+        StopState::ForceOffered(30)
+    );
+    assert_eq!(
+        stops.state("0123", start + secs(95)),
+        StopState::ForceOffered(95)
+    );
+}
+
+#[test]
+fn entries_without_a_timeout_use_dockers_default() {
+    let start = Instant::now();
+    let mut stops = StopAttempts::default();
+    stops.begin("0123".into(), DEFAULT_GRACE, start);
+
+    assert_eq!(DEFAULT_GRACE, secs(10));
+    assert_eq!(stops.state("0123", start + secs(9)), StopState::Stopping(9));
+    assert_eq!(
+        stops.state("0123", start + secs(10)),
+        StopState::ForceOffered(10)
+    );
+}
+
+#[test]
+fn stopping_again_keeps_the_first_attempt() {
+    let start = Instant::now();
+    let mut stops = StopAttempts::default();
+    stops.begin("0123".into(), GRACE, start);
+    stops.begin("0123".into(), GRACE, start + secs(20));
+
+    assert_eq!(
+        stops.state("0123", start + secs(30)),
+        StopState::ForceOffered(30)
+    );
+}
+
+#[test]
+fn killing_waits_for_the_exit() {
+    let start = Instant::now();
+    let mut stops = StopAttempts::default();
+    stops.begin("0123".into(), GRACE, start);
+    stops.kill("0123", start + secs(5));
+
+    assert_eq!(stops.state("0123", start + secs(5)), StopState::Killing);
+    assert_eq!(stops.state("0123", start + secs(60)), StopState::Killing);
+    assert!(StopState::Killing.is_stopping());
+}
+
+#[test]
+fn containers_can_be_killed_without_being_stopped_first() {
+    let mut stops = StopAttempts::default();
+    stops.kill("0123", Instant::now());
+
+    assert_eq!(stops.state("0123", Instant::now()), StopState::Killing);
+}
+
+#[test]
+fn exiting_clears_the_attempt() {
+    let start = Instant::now();
+    let mut stops = StopAttempts::default();
+    stops.begin("0123".into(), GRACE, start);
+    stops.begin("4567".into(), GRACE, start);
+    stops.kill("4567", start);
+
+    stops.finish("0123");
+    stops.finish("4567");
+
+    assert_eq!(stops.state("0123", start), StopState::Idle);
+    assert_eq!(stops.state("4567", start), StopState::Idle);
+    assert!(stops.is_empty());
+}
+
+#[test]
+fn containers_no_longer_running_are_forgotten() {
+    let start = Instant::now();
+    let mut stops = StopAttempts::default();
+    stops.begin("0123".into(), GRACE, start);
+    stops.begin("4567".into(), GRACE, start);
+    stops.kill("89ab", start);
+
+    stops.retain(["4567"]);
+
+    assert_eq!(stops.state("0123", start), StopState::Idle);
+    assert_eq!(stops.state("4567", start), StopState::Stopping(0));
+    assert_eq!(stops.state("89ab", start), StopState::Idle);
+
+    stops.retain([]);
+    assert!(stops.is_empty());
+}
@@ -0,0 +1,117 @@
+//! Checks the dependency graph used when stopping containers others depend on: who depends on a
+//! container, the order they are stopped in and the cycles that make an order impossible.
+#![allow(dead_code)]
+
+#[path = "../src/daemon.rs"]
+mod daemon;
+#[path = "../src/dependencies.rs"]
+mod dependencies;
+#[path = "../src/docker.rs"]
+mod docker;
+#[path = "../src/image_ref.rs"]
+mod image_ref;
+#[path = "../src/platform.rs"]
+mod platform;
+#[path = "../src/ports.rs"]
+mod ports;
+
+use std::collections::HashMap;
+
+use bollard::service::ContainerSummary;
+use dependencies::{dependents, graph, stop_order, Graph};
+use docker::{parse_depends_on, summary_container, DEPENDS_ON_LABEL};
+
+fn edges(edges: &[(&str, &[&str])]) -> Graph {
+    edges
+        .iter()
+        .map(|(name, depends_on)| {
+            (
+                name.to_string(),
+                depends_on.iter().map(|name| name.to_string()).collect(),
+            )
+        })
+        .collect()
+}
+
+fn names(names: &[&str]) -> Vec<String> {
+    names.iter().map(|name| name.to_string()).collect()
+}
+
+/// pgadmin and the api use postgres, the worker uses the api.
+fn stack() -> Graph {
+    edges(&[
+        ("postgres", &[]),
+        ("pgadmin", &["postgres"]),
+        ("api", &["postgres"]),
+        ("worker", &["api"]),
+        ("redis", &[]),
+    ])
+}
+
+#[test]
+fn the_graph_is_read_from_the_labels() {
+    let container = |name: &str, depends_on: Option<&str>| {
+        summary_container(ContainerSummary {
+            id: Some(format!("{name}-id")),
+            names: Some(vec![format!("/{name}")]),
+            labels: depends_on.map(|depends_on| {
+                HashMap::from([(DEPENDS_ON_LABEL.to_string(), depends_on.to_string())])
+            }),
+            ..Default::default()
+        })
+        .unwrap()
+    };
+
+    let graph = graph(&[
+        container("postgres", None),
+        container("api", Some("postgres, redis,")),
+    ]);
+    assert_eq!(
+        graph,
+        edges(&[("postgres", &[]), ("api", &["postgres", "redis"])])
+    );
+    assert_eq!(parse_depends_on(&HashMap::new()), Vec::<String>::new());
+}
+
+#[test]
+fn dependents_include_indirect_ones() {
+    assert_eq!(
+        dependents(&stack(), "postgres"),
+        names(&["api", "pgadmin", "worker"])
+    );
+    assert_eq!(dependents(&stack(), "api"), names(&["worker"]));
+    assert!(dependents(&stack(), "redis").is_empty());
+    assert!(dependents(&stack(), "unknown").is_empty());
+}
+
+#[test]
+fn dependents_are_stopped_first() {
+    let order = stop_order(&stack(), &names(&["postgres", "api", "worker", "pgadmin"])).unwrap();
+    let position = |name: &str| order.iter().position(|stopped| stopped == name).unwrap();
+
+    assert_eq!(order.len(), 4);
+    assert!(position("worker") < position("api"));
+    assert!(position("api") < position("postgres"));
+    assert!(position("pgadmin") < position("postgres"));
+}
+
+#[test]
+fn containers_outside_the_selection_are_left_out() {
+    assert_eq!(
+        stop_order(&stack(), &names(&["postgres", "worker"])).unwrap(),
+        names(&["postgres", "worker"])
+    );
+}
+
+#[test]
+fn cycles_are_reported() {
+    let cyclic = edges(&[("a", &["b"]), ("b", &["c"]), ("c", &["a"])]);
+
+    let cycle = stop_order(&cyclic, &names(&["a", "b", "c"])).unwrap_err();
+    assert_eq!(cycle.len(), 3);
+    for name in ["a", "b", "c"] {
+        assert!(cycle.contains(&name.to_string()));
+    }
+    // Each container is still only listed once as a dependent
+    assert_eq!(dependents(&cyclic, "a"), names(&["b", "c"]));
+}
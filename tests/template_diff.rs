@@ -0,0 +1,176 @@
+//! Checks the comparison of a container's variables with the defaults of its config entry, and
+//! that secrets stay masked when it is shown.
+#![allow(dead_code)]
+
+#[path = "../src/backup.rs"]
+mod backup;
+mod common;
+#[path = "../src/daemon.rs"]
+mod daemon;
+#[path = "../src/data.rs"]
+mod data;
+#[path = "../src/definition.rs"]
+mod definition;
+#[path = "../src/diff.rs"]
+mod diff;
+#[path = "../src/docker.rs"]
+mod docker;
+#[path = "../src/idle.rs"]
+mod idle;
+#[path = "../src/image_ref.rs"]
+mod image_ref;
+#[path = "../src/names.rs"]
+mod names;
+#[path = "../src/platform.rs"]
+mod platform;
+#[path = "../src/ports.rs"]
+mod ports;
+#[path = "../src/registry.rs"]
+mod registry;
+#[path = "../src/template.rs"]
+mod template;
+#[path = "../src/tls.rs"]
+mod tls;
+
+use std::collections::HashMap;
+
+use bollard::service::ContainerSummary;
+use common::postgres;
+use data::DatabaseConfig;
+use diff::{diff, Change, DiffRow};
+use docker::{summary_container, DbContainer, EnvValue};
+
+fn map(entries: &[(&str, &str)]) -> HashMap<String, String> {
+    entries
+        .iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
+
+fn changes(rows: &[DiffRow]) -> Vec<(&str, Change)> {
+    rows.iter()
+        .map(|row| (row.key.as_str(), row.change))
+        .collect()
+}
+
+#[test]
+fn empty_maps_have_no_rows() {
+    assert!(diff(&HashMap::new(), &HashMap::new()).is_empty());
+
+    let only_template = diff(&HashMap::new(), &map(&[("POSTGRES_DB", "app")]));
+    assert_eq!(
+        changes(&only_template),
+        vec![("POSTGRES_DB", Change::Added)]
+    );
+    let only_container = diff(&map(&[("POSTGRES_DB", "app")]), &HashMap::new());
+    assert_eq!(
+        changes(&only_container),
+        vec![("POSTGRES_DB", Change::Removed)]
+    );
+}
+
+#[test]
+fn identical_maps_are_unchanged() {
+    let variables = map(&[("POSTGRES_DB", "app"), ("POSTGRES_USER", "app")]);
+    let rows = diff(&variables, &variables.clone());
+
+    assert_eq!(
+        changes(&rows),
+        vec![
+            ("POSTGRES_DB", Change::Unchanged),
+            ("POSTGRES_USER", Change::Unchanged)
+        ]
+    );
+}
+
+#[test]
+fn additions_removals_and_changes_are_sorted_by_key() {
+    let rows = diff(
+        &map(&[("PGDATA", "/data"), ("POSTGRES_DB", "old"), ("TZ", "UTC")]),
+        &map(&[("POSTGRES_DB", "new"), ("TZ", "UTC"), ("LANG", "C")]),
+    );
+
+    assert_eq!(
+        changes(&rows),
+        vec![
+            ("LANG", Change::Added),
+            ("PGDATA", Change::Removed),
+            ("POSTGRES_DB", Change::Changed),
+            ("TZ", Change::Unchanged),
+        ]
+    );
+    assert_eq!(
+        rows[2],
+        DiffRow {
+            key: "POSTGRES_DB".into(),
+            container: Some("old".into()),
+            template: Some("new".into()),
+            change: Change::Changed,
+        }
+    );
+    assert_eq!(rows[0].display_values(), ("-".into(), "C".into()));
+    assert_eq!(rows[1].display_values(), ("/data".into(), "-".into()));
+}
+
+#[test]
+fn secrets_are_masked_when_shown() {
+    let rows = diff(
+        &map(&[("POSTGRES_PASSWORD", "hunter2"), ("API_TOKEN", "abc")]),
+        &map(&[("POSTGRES_PASSWORD", "changeme")]),
+    );
+
+    let (container, template) = rows[1].display_values();
+    assert_eq!(rows[1].key, "POSTGRES_PASSWORD");
+    assert_eq!(rows[1].change, Change::Changed);
+    assert!(!container.contains("hunter2") && !template.contains("changeme"));
+    assert_eq!(container, template);
+    // Missing values aren't masked, so additions and removals still show
+    assert_eq!(rows[0].display_values().1, "-");
+    assert_ne!(rows[0].display_values().0, "abc");
+}
+
+/// The bundled Postgres entry, with a template default for the database name.
+fn template() -> DatabaseConfig {
+    let mut database = postgres();
+    database.defaults = HashMap::from([("POSTGRES_DB".into(), "app".into())]);
+    database
+}
+
+fn container(variables: &[(&str, &str)]) -> DbContainer {
+    let mut container = summary_container(ContainerSummary {
+        id: Some("c1".into()),
+        names: Some(vec!["/db-mgr__orders".into()]),
+        image: Some("postgres:16".into()),
+        ..Default::default()
+    })
+    .expect("The summary has an id");
+    container.variables = variables
+        .iter()
+        .map(|(key, value)| (key.to_string(), EnvValue::new(value)))
+        .collect();
+    container
+}
+
+#[test]
+fn containers_are_compared_on_their_declared_variables() {
+    let orders = container(&[
+        ("POSTGRES_PASSWORD", "hunter2"),
+        ("POSTGRES_DB", "orders"),
+        // Set by the image, not declared by the entry
+        ("PATH", "/usr/bin"),
+    ]);
+
+    let rows = template().template_diff(&orders);
+    assert_eq!(
+        changes(&rows),
+        vec![
+            ("POSTGRES_DB", Change::Changed),
+            ("POSTGRES_PASSWORD", Change::Removed),
+        ]
+    );
+
+    let recreated = template().recreate_config(&orders);
+    assert_eq!(recreated.variables["POSTGRES_DB"], "app");
+    assert_eq!(recreated.variables["POSTGRES_PASSWORD"], "hunter2");
+    assert!(!recreated.variables.contains_key("PATH"));
+}
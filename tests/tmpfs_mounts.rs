@@ -0,0 +1,139 @@
+//! Checks the size limits typed for tmpfs data directories and how the mounts reach docker, both
+//! in the create request and in the copied `docker run` command.
+#![allow(dead_code)]
+
+#[path = "../src/backup.rs"]
+mod backup;
+#[path = "../src/app/cleanup.rs"]
+mod cleanup;
+mod common;
+#[path = "../src/daemon.rs"]
+mod daemon;
+#[path = "../src/data.rs"]
+mod data;
+#[path = "../src/definition.rs"]
+mod definition;
+#[path = "../src/diff.rs"]
+mod diff;
+#[path = "../src/docker.rs"]
+mod docker;
+#[path = "../src/idle.rs"]
+mod idle;
+#[path = "../src/image_ref.rs"]
+mod image_ref;
+#[path = "../src/names.rs"]
+mod names;
+#[path = "../src/platform.rs"]
+mod platform;
+#[path = "../src/ports.rs"]
+mod ports;
+#[path = "../src/registry.rs"]
+mod registry;
+#[path = "../src/run_command.rs"]
+mod run_command;
+#[path = "../src/template.rs"]
+mod template;
+#[path = "../src/tls.rs"]
+mod tls;
+
+use std::collections::HashMap;
+
+use bollard::service::ContainerSummary;
+use cleanup::{format_size, parse_size};
+use common::postgres;
+use docker::{plan_container, summary_container, DbContainer, ImageDefaults, MANAGED_LABELS};
+use run_command::{docker_run_command, RunCommandOptions};
+use serde_json::json;
+
+const MIB: u64 = 1 << 20;
+const GIB: u64 = 1 << 30;
+
+#[test]
+fn sizes_with_units_are_powers_of_1024() {
+    assert_eq!(parse_size("512m"), Some(512 * MIB));
+    assert_eq!(parse_size("2g"), Some(2 * GIB));
+    assert_eq!(parse_size("64k"), Some(64 << 10));
+    assert_eq!(parse_size("1t"), Some(1 << 40));
+    assert_eq!(parse_size("2G"), Some(2 * GIB));
+    assert_eq!(parse_size("2gb"), Some(2 * GIB));
+    assert_eq!(parse_size(" 512 m "), Some(512 * MIB));
+}
+
+#[test]
+fn sizes_without_units_are_bytes() {
+    assert_eq!(parse_size("1024"), Some(1024));
+    assert_eq!(parse_size("1024b"), Some(1024));
+    assert_eq!(parse_size("0"), Some(0));
+}
+
+#[test]
+fn malformed_and_overflowing_sizes_are_rejected() {
+    assert_eq!(parse_size(""), None);
+    assert_eq!(parse_size("m"), None);
+    assert_eq!(parse_size("1.5g"), None);
+    assert_eq!(parse_size("-1g"), None);
+    assert_eq!(parse_size("2x"), None);
+    assert_eq!(parse_size("lots"), None);
+    assert_eq!(parse_size("99999999999t"), None);
+}
+
+#[test]
+fn sizes_are_shown_in_the_largest_unit() {
+    assert_eq!(format_size(512), "512 B");
+    assert_eq!(format_size(512 * MIB as i64), "512.0 MB");
+    assert_eq!(format_size(3 * GIB as i64 / 2), "1.5 GB");
+}
+
+#[test]
+fn tmpfs_paths_are_mounted_with_their_size_limit() {
+    let mut config = postgres().quick_config("scratch");
+    config.volumes = HashMap::new();
+    config.tmpfs = vec!["/var/lib/postgresql/data".into()];
+    config.tmpfs_size = parse_size("512m");
+
+    let create = plan_container(&config, MANAGED_LABELS)
+        .into_iter()
+        .find(|op| op.call.starts_with("POST /containers/create"))
+        .expect("The plan creates the container");
+    let body = create.body.expect("Creating a container has a body");
+
+    assert_eq!(
+        body["HostConfig"]["Mounts"],
+        json!([{
+            "Target": "/var/lib/postgresql/data",
+            "Type": "tmpfs",
+            "TmpfsOptions": { "SizeBytes": 512 * MIB },
+        }])
+    );
+}
+
+#[test]
+fn tmpfs_mounts_are_copied_with_their_size_limit() {
+    let mut container = summary_container(ContainerSummary {
+        id: Some("0123456789abcdef".into()),
+        names: Some(vec!["/db-mgr__scratch".into()]),
+        image: Some("postgres:16".into()),
+        ..Default::default()
+    })
+    .expect("The summary has an id");
+    container.labels = HashMap::new();
+    container.tmpfs = vec!["/var/lib/postgresql/data".into()];
+    let image = ImageDefaults {
+        env: vec![],
+        labels: HashMap::new(),
+        command: vec![],
+    };
+    let command = |container: &DbContainer| {
+        docker_run_command(
+            container,
+            Some(&postgres()),
+            &image,
+            RunCommandOptions::default(),
+        )
+    };
+
+    assert!(command(&container).contains("--tmpfs /var/lib/postgresql/data"));
+
+    container.tmpfs_size = Some(512 * MIB);
+    assert!(command(&container).contains("--tmpfs /var/lib/postgresql/data:size=536870912"));
+}
@@ -0,0 +1,126 @@
+//! Checks the tray menu built from the favourite containers, how its items are told apart, and
+//! that builds without tray support start without one.
+#![allow(dead_code)]
+
+#[path = "../src/daemon.rs"]
+mod daemon;
+#[path = "../src/docker.rs"]
+mod docker;
+#[path = "../src/image_ref.rs"]
+mod image_ref;
+#[path = "../src/platform.rs"]
+mod platform;
+#[path = "../src/ports.rs"]
+mod ports;
+#[path = "../src/app/state_badge.rs"]
+mod state_badge;
+#[path = "../src/app/tray.rs"]
+mod tray;
+
+use std::collections::HashSet;
+
+use bollard::service::{ContainerStateStatusEnum, ContainerSummary};
+use docker::{summary_container, DbContainer};
+use tray::{tray_entries, TrayAction, TrayEntry};
+
+fn container(id: &str, name: &str, state: ContainerStateStatusEnum) -> DbContainer {
+    let mut container = summary_container(ContainerSummary {
+        id: Some(id.into()),
+        names: Some(vec![format!("/{name}")]),
+        image: Some("postgres:16".into()),
+        ..Default::default()
+    })
+    .expect("The summary has an id");
+    container.state = state;
+    container
+}
+
+fn favourites(names: &[&str]) -> HashSet<String> {
+    names.iter().map(|name| name.to_string()).collect()
+}
+
+#[test]
+fn menu_ids_are_read_back_as_their_action() {
+    for action in [
+        TrayAction::Start("0123".into()),
+        TrayAction::Stop("4567".into()),
+        TrayAction::ShowWindow,
+        TrayAction::Quit,
+    ] {
+        assert_eq!(TrayAction::parse(&action.menu_id()), Some(action));
+    }
+}
+
+#[test]
+fn disabled_items_are_not_actions() {
+    assert_eq!(TrayAction::parse("entry:db-mgr__orders (paused)"), None);
+    assert_eq!(TrayAction::parse("entry:"), None);
+    assert_eq!(TrayAction::parse("restart"), None);
+}
+
+#[test]
+fn only_favourites_are_listed_by_name() {
+    let containers = vec![
+        container("c", "db-mgr__users", ContainerStateStatusEnum::RUNNING),
+        container("b", "db-mgr__cache", ContainerStateStatusEnum::RUNNING),
+        container("a", "db-mgr__orders", ContainerStateStatusEnum::EXITED),
+    ];
+    let entries = tray_entries(
+        &containers,
+        &favourites(&["db-mgr__users", "db-mgr__orders", "db-mgr__gone"]),
+        false,
+    );
+
+    assert_eq!(
+        entries,
+        vec![
+            TrayEntry {
+                label: "Start db-mgr__orders (exited)".into(),
+                action: Some(TrayAction::Start("a".into())),
+            },
+            TrayEntry {
+                label: "Stop db-mgr__users (running)".into(),
+                action: Some(TrayAction::Stop("c".into())),
+            },
+        ]
+    );
+}
+
+#[test]
+fn containers_changing_state_cant_be_toggled() {
+    let containers = vec![
+        container("a", "db-mgr__orders", ContainerStateStatusEnum::RESTARTING),
+        container("b", "db-mgr__users", ContainerStateStatusEnum::CREATED),
+    ];
+    let entries = tray_entries(
+        &containers,
+        &favourites(&["db-mgr__orders", "db-mgr__users"]),
+        false,
+    );
+
+    assert_eq!(entries[0].action, None);
+    assert!(!entries[0].label.starts_with("Start") && !entries[0].label.starts_with("Stop"));
+    assert_eq!(entries[1].action, Some(TrayAction::Start("b".into())));
+}
+
+#[test]
+fn nothing_can_be_toggled_in_read_only_mode() {
+    let containers = vec![
+        container("a", "db-mgr__orders", ContainerStateStatusEnum::RUNNING),
+        container("b", "db-mgr__users", ContainerStateStatusEnum::EXITED),
+    ];
+    let entries = tray_entries(
+        &containers,
+        &favourites(&["db-mgr__orders", "db-mgr__users"]),
+        true,
+    );
+
+    assert!(entries.iter().all(|entry| entry.action.is_none()));
+    assert_eq!(entries[0].label, "db-mgr__orders (running)");
+}
+
+#[cfg(not(all(feature = "tray", target_os = "linux")))]
+#[test]
+fn builds_without_tray_support_start_without_one() {
+    assert!(tray::Tray::spawn().is_err());
+}
@@ -0,0 +1,450 @@
+//! Characterizes the trickier arms of the update handlers on a whole app that is never connected
+//! to docker: how image downloads move a build along, what a loaded container list resets and
+//! which containers a start in dependency order refreshes.
+#![allow(dead_code)]
+
+#[path = "../src/app/mod.rs"]
+mod app;
+#[path = "../src/backup.rs"]
+mod backup;
+#[path = "../src/cli.rs"]
+mod cli;
+mod common;
+#[path = "../src/daemon.rs"]
+mod daemon;
+#[path = "../src/data.rs"]
+mod data;
+#[path = "../src/definition.rs"]
+mod definition;
+#[path = "../src/dependencies.rs"]
+mod dependencies;
+#[path = "../src/diff.rs"]
+mod diff;
+#[path = "../src/docker.rs"]
+mod docker;
+#[path = "../src/env_file.rs"]
+mod env_file;
+#[path = "../src/errors.rs"]
+mod errors;
+#[path = "../src/exits.rs"]
+mod exits;
+#[path = "../src/http.rs"]
+mod http;
+#[path = "../src/idle.rs"]
+mod idle;
+#[path = "../src/image_ref.rs"]
+mod image_ref;
+#[path = "../src/instance.rs"]
+mod instance;
+#[path = "../src/migrate.rs"]
+mod migrate;
+#[path = "../src/names.rs"]
+mod names;
+#[path = "../src/platform.rs"]
+mod platform;
+#[path = "../src/ports.rs"]
+mod ports;
+#[path = "../src/registry.rs"]
+mod registry;
+#[path = "../src/run_command.rs"]
+mod run_command;
+#[path = "../src/start_order.rs"]
+mod start_order;
+#[path = "../src/state_file.rs"]
+mod state_file;
+#[path = "../src/template.rs"]
+mod template;
+#[path = "../src/tls.rs"]
+mod tls;
+#[path = "../src/validate.rs"]
+mod validate;
+#[path = "../src/versions.rs"]
+mod versions;
+
+use app::{
+    build_card::{BuildJob, BuildPhase},
+    handlers::{ContainerMsg, CreateMsg, UiMsg},
+    start_run::StartRun,
+    view_state::MainViewState,
+    DbMgrApp, Message,
+};
+use bollard::{Docker, API_DEFAULT_VERSION};
+use common::container;
+use data::{ConfigFile, Preferences};
+use docker::DbContainer;
+use futures::FutureExt;
+use iced::{Application, Command};
+use iced_runtime::command::Action;
+use start_order::StartEvent;
+
+/// What a command does, as far as the tests can see without running the app.
+#[derive(Debug, Default)]
+struct Inspected {
+    /// The messages of the futures that finished on their first poll, in order.
+    messages: Vec<Message>,
+    /// The futures still waiting on something, like docker.
+    pending: usize,
+    /// Actions for the window, widgets, fonts or the clipboard.
+    other: usize,
+}
+
+/// Polls every future of `command` once. Nothing the tests build talks to a real docker, so only
+/// the messages the handler sends itself come back.
+fn inspect(command: Command<Message>) -> Inspected {
+    let mut inspected = Inspected::default();
+    for action in command.actions() {
+        match action {
+            Action::Future(future) => match future.now_or_never() {
+                Some(message) => inspected.messages.push(message),
+                None => inspected.pending += 1,
+            },
+            _ => inspected.other += 1,
+        }
+    }
+    inspected
+}
+
+fn app() -> DbMgrApp {
+    app_with(false)
+}
+
+fn app_with(read_only: bool) -> DbMgrApp {
+    // Nothing listens there, and nothing is sent before a future is polled
+    let docker = Docker::connect_with_http("http://127.0.0.1:9", 1, API_DEFAULT_VERSION)
+        .expect("The address must parse");
+    let config = serde_yaml::from_str::<ConfigFile>(include_str!("../config.yaml"))
+        .expect("The bundled config must parse");
+    let (app, _) = DbMgrApp::new((
+        docker,
+        config,
+        Preferences::default(),
+        read_only,
+        None,
+        None,
+    ));
+    app
+}
+
+/// Whether `inspected` only reports that read-only mode refused the message.
+fn refused(inspected: &Inspected) -> bool {
+    matches!(inspected.messages[..], [Message::Ui(UiMsg::Error(_))])
+        && (inspected.pending, inspected.other) == (0, 0)
+}
+
+fn build_job(phase: BuildPhase) -> BuildJob {
+    let config = common::postgres().quick_config("db-mgr__orders");
+    let mut job = BuildJob::new(config, Default::default());
+    job.phase = phase;
+    job
+}
+
+fn download(layer: &str, current: u64, total: u64) -> CreateMsg {
+    CreateMsg::ImageDownload(layer.into(), current, total)
+}
+
+/// Starts refresh number `sequence`, the app counts them from 1, and loads `containers` as its
+/// result.
+fn load(app: &mut DbMgrApp, sequence: u64, containers: Vec<DbContainer>) -> Inspected {
+    // The docker call it starts is never polled
+    let _ = app.handle_container_msg(ContainerMsg::GetContainers);
+    inspect(app.handle_container_msg(ContainerMsg::ContainersLoaded(sequence, containers)))
+}
+
+fn ids(app: &DbMgrApp) -> Vec<&str> {
+    app.containers
+        .iter()
+        .map(|container| container.id.as_str())
+        .collect()
+}
+
+#[test]
+fn downloads_without_a_build_are_dropped() {
+    let mut app = app();
+
+    let inspected = inspect(app.handle_create_msg(download("a", 1, 2)));
+
+    assert!(app.build_job.is_none());
+    assert!(inspected.messages.is_empty());
+    assert_eq!((inspected.pending, inspected.other), (0, 0));
+}
+
+#[test]
+fn the_first_download_starts_pulling() {
+    let mut app = app();
+    app.build_job = Some(build_job(BuildPhase::Creating));
+
+    let inspected = inspect(app.handle_create_msg(download("a", 5, 10)));
+
+    assert!(inspected.messages.is_empty());
+    match app.build_job.map(|job| job.phase) {
+        Some(BuildPhase::Pulling(progress)) => {
+            assert_eq!((progress.downloaded(), progress.total()), (5, 10));
+        }
+        other => panic!("Expected the build to be pulling, got {other:?}"),
+    }
+}
+
+#[test]
+fn later_downloads_add_to_the_pull() {
+    let mut app = app();
+    app.build_job = Some(build_job(BuildPhase::Creating));
+
+    for message in [
+        download("a", 5, 10),
+        download("b", 2, 20),
+        download("a", 10, 10),
+    ] {
+        assert!(inspect(app.handle_create_msg(message)).messages.is_empty());
+    }
+
+    match app.build_job.map(|job| job.phase) {
+        Some(BuildPhase::Pulling(progress)) => {
+            assert_eq!((progress.downloaded(), progress.total()), (12, 30));
+            assert_eq!(progress.layers().len(), 2);
+        }
+        other => panic!("Expected the build to be pulling, got {other:?}"),
+    }
+}
+
+#[test]
+fn downloads_after_another_phase_start_a_new_pull() {
+    let mut app = app();
+    app.build_job = Some(build_job(BuildPhase::BuildingImage(vec![
+        "Step 1/3 : FROM postgres:16".into(),
+    ])));
+
+    let _ = app.handle_create_msg(download("c", 1, 4));
+
+    match app.build_job.map(|job| job.phase) {
+        Some(BuildPhase::Pulling(progress)) => {
+            assert_eq!((progress.downloaded(), progress.total()), (1, 4));
+            assert_eq!(progress.layers().len(), 1);
+        }
+        other => panic!("Expected the build to be pulling, got {other:?}"),
+    }
+}
+
+#[test]
+fn loaded_containers_replace_the_list() {
+    let mut app = app();
+
+    let inspected = load(
+        &mut app,
+        1,
+        vec![
+            container("db-mgr__orders").id("b").build(),
+            container("db-mgr__users").id("a").build(),
+        ],
+    );
+
+    assert_eq!(ids(&app), vec!["b", "a"]);
+    // Loading the volumes and keeping the sidebar scrolled, nothing else without a follow-up
+    assert!(matches!(
+        inspected.messages[..],
+        [Message::Container(ContainerMsg::GetVolumes)]
+    ));
+    assert_eq!((inspected.pending, inspected.other), (0, 1));
+}
+
+#[test]
+fn stale_lists_are_dropped() {
+    let mut app = app();
+    load(
+        &mut app,
+        1,
+        vec![container("db-mgr__users").id("a").build()],
+    );
+
+    // The second refresh is still running when a list from before the first arrives
+    let inspected = load(&mut app, 0, vec![]);
+
+    assert_eq!(ids(&app), vec!["a"]);
+    assert!(inspected.messages.is_empty());
+    assert_eq!((inspected.pending, inspected.other), (0, 0));
+}
+
+#[test]
+fn the_view_of_a_removed_container_is_reset() {
+    let mut app = app();
+    app.main_view = MainViewState::ViewContainer("gone".into());
+
+    load(
+        &mut app,
+        1,
+        vec![container("db-mgr__users").id("a").build()],
+    );
+
+    assert!(matches!(app.main_view, MainViewState::None));
+}
+
+#[test]
+fn other_views_survive_the_load() {
+    let mut app = app();
+    app.main_view = MainViewState::ViewContainer("a".into());
+    load(
+        &mut app,
+        1,
+        vec![container("db-mgr__users").id("a").build()],
+    );
+    assert!(matches!(&app.main_view, MainViewState::ViewContainer(id) if id == "a"));
+
+    app.main_view = MainViewState::Settings;
+    load(&mut app, 2, vec![]);
+    assert!(matches!(app.main_view, MainViewState::Settings));
+}
+
+#[test]
+fn finished_builds_are_cleared_by_the_load() {
+    let mut app = app();
+    app.build_job = Some(build_job(BuildPhase::Created));
+
+    load(&mut app, 1, vec![]);
+
+    assert!(app.build_job.is_none());
+}
+
+#[test]
+fn running_builds_outlive_the_load() {
+    let mut app = app();
+    app.build_job = Some(build_job(BuildPhase::Starting));
+
+    load(&mut app, 1, vec![]);
+
+    assert!(matches!(
+        app.build_job.map(|job| job.phase),
+        Some(BuildPhase::Starting)
+    ));
+}
+
+/// An app listing `a` and `b`, starting both in dependency order.
+fn starting() -> DbMgrApp {
+    let mut app = app();
+    load(
+        &mut app,
+        1,
+        vec![
+            container("db-mgr__orders").id("a").build(),
+            container("db-mgr__orders-adminer").id("b").build(),
+        ],
+    );
+    app.start_run = Some(StartRun::new(
+        0,
+        vec!["db-mgr__orders".into(), "db-mgr__orders-adminer".into()],
+    ));
+    app
+}
+
+/// The ids `inspected` refreshes, `None` if it doesn't only refresh some containers.
+fn refreshed(inspected: &Inspected) -> Option<Vec<String>> {
+    match &inspected.messages[..] {
+        [Message::Container(ContainerMsg::RefreshContainers(ids))] => Some(ids.clone()),
+        _ => None,
+    }
+}
+
+#[test]
+fn started_containers_are_refreshed_on_their_own() {
+    let mut app = starting();
+
+    let started = inspect(app.handle_container_msg(ContainerMsg::StartProgress(
+        StartEvent::Started("db-mgr__orders".into()),
+    )));
+    let waiting = inspect(app.handle_container_msg(ContainerMsg::StartProgress(
+        StartEvent::Waiting("db-mgr__orders-adminer".into()),
+    )));
+
+    assert_eq!(refreshed(&started), Some(vec!["a".to_string()]));
+    assert_eq!(refreshed(&waiting), Some(vec!["b".to_string()]));
+}
+
+#[test]
+fn starts_in_progress_refresh_nothing() {
+    let mut app = starting();
+
+    let inspected = inspect(app.handle_container_msg(ContainerMsg::StartProgress(
+        StartEvent::Starting("db-mgr__orders".into()),
+    )));
+
+    assert!(inspected.messages.is_empty());
+    assert_eq!((inspected.pending, inspected.other), (0, 0));
+}
+
+#[test]
+fn finished_starts_refresh_every_container_of_the_run() {
+    let mut app = starting();
+
+    let inspected =
+        inspect(app.handle_container_msg(ContainerMsg::StartProgress(StartEvent::Done)));
+
+    assert_eq!(
+        refreshed(&inspected),
+        Some(vec!["a".to_string(), "b".to_string()])
+    );
+}
+
+#[test]
+fn cancelled_starts_refresh_the_containers_of_the_run() {
+    let mut app = starting();
+
+    let inspected = inspect(app.handle_container_msg(ContainerMsg::CancelStart));
+
+    assert!(app.start_run.is_none());
+    assert_eq!(
+        refreshed(&inspected),
+        Some(vec!["a".to_string(), "b".to_string()])
+    );
+}
+
+#[test]
+fn read_only_mode_refuses_applying_limits() {
+    let mut app = app_with(true);
+    load(
+        &mut app,
+        1,
+        vec![container("db-mgr__orders").id("a").build()],
+    );
+
+    let inspected = inspect(app.update(Message::Container(ContainerMsg::ApplyLimits("a".into()))));
+
+    // Only the refusal, nothing is sent to docker
+    assert!(refused(&inspected));
+}
+
+#[test]
+fn read_only_mode_refuses_changing_backups() {
+    let mut app = app_with(true);
+
+    for message in [
+        ContainerMsg::SetBackupSchedule("db-mgr__orders".into(), None),
+        ContainerMsg::ChooseBackupDirectory("db-mgr__orders".into()),
+        ContainerMsg::RetryBackup("db-mgr__orders".into()),
+    ] {
+        let name = format!("{message:?}");
+        assert!(
+            refused(&inspect(app.update(Message::Container(message)))),
+            "{name}"
+        );
+    }
+}
+
+#[test]
+fn read_only_mode_refuses_prefetching() {
+    let mut app = app_with(true);
+
+    let inspected = inspect(
+        app.update(Message::Container(ContainerMsg::RunPrefetch(vec![
+            "postgres:16".into(),
+        ]))),
+    );
+
+    assert!(refused(&inspected));
+}
+
+#[test]
+fn read_only_mode_refuses_resuming_a_draft() {
+    let mut app = app_with(true);
+
+    let inspected = inspect(app.update(Message::Create(CreateMsg::ResumeDraft)));
+
+    assert!(refused(&inspected));
+}
@@ -0,0 +1,288 @@
+//! Builds the sidebar cards of many fake containers to catch the view getting slow again, run
+//! with `cargo test --features view-bench`. Add `-- --nocapture` to see what batching thumbnails
+//! saves.
+#![cfg(feature = "view-bench")]
+#![allow(dead_code)]
+
+#[path = "../src/app/avatar.rs"]
+mod avatar;
+#[path = "../src/backup.rs"]
+mod backup;
+#[path = "../src/app/cantainer_card.rs"]
+mod cantainer_card;
+#[path = "../src/app/container_list.rs"]
+mod container_list;
+#[path = "../src/daemon.rs"]
+mod daemon;
+#[path = "../src/data.rs"]
+mod data;
+#[path = "../src/definition.rs"]
+mod definition;
+#[path = "../src/diff.rs"]
+mod diff;
+#[path = "../src/docker.rs"]
+mod docker;
+#[path = "../src/exits.rs"]
+mod exits;
+#[path = "../src/http.rs"]
+mod http;
+#[path = "../src/idle.rs"]
+mod idle;
+#[path = "../src/image_ref.rs"]
+mod image_ref;
+#[path = "../src/names.rs"]
+mod names;
+#[path = "../src/platform.rs"]
+mod platform;
+#[path = "../src/ports.rs"]
+mod ports;
+#[path = "../src/registry.rs"]
+mod registry;
+#[path = "../src/app/state_badge.rs"]
+mod state_badge;
+#[path = "../src/app/stopping.rs"]
+mod stopping;
+#[path = "../src/template.rs"]
+mod template;
+#[path = "../src/app/thumbnails.rs"]
+mod thumbnails;
+#[path = "../src/tls.rs"]
+mod tls;
+#[path = "../src/versions.rs"]
+mod versions;
+
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use bollard::service::ContainerStateStatusEnum;
+use cantainer_card::container_card;
+use container_list::{container_cards, page, CardActions, CardLook, PAGE_SIZE};
+use docker::{ContainerExit, ContainerLimits, DbContainer};
+use iced::widget::{image::Handle, Component};
+use thumbnails::{Thumbnails, FLUSH_INTERVAL};
+
+const CONTAINERS: usize = 500;
+
+/// Generous enough for unoptimized builds on slow CI machines, a regression to rebuilding
+/// everything repeatedly blows well past it.
+const BUDGET: Duration = Duration::from_millis(500);
+
+#[derive(Clone, Debug)]
+enum Message {
+    Id(String),
+    Select(String, bool),
+}
+
+fn fake_containers() -> Vec<DbContainer> {
+    (0..CONTAINERS)
+        .map(|i| DbContainer {
+            id: format!("{i:064x}"),
+            name: format!("/ci-run-{i}"),
+            state: if i % 3 == 0 {
+                ContainerStateStatusEnum::EXITED
+            } else {
+                ContainerStateStatusEnum::RUNNING
+            },
+            variables: HashMap::new(),
+            image: format!("postgres:{}", 12 + i % 5),
+            volumes: HashMap::new(),
+            command: vec![],
+            labels: HashMap::new(),
+            shm_size_mb: None,
+            cpuset_cpus: None,
+            tmpfs: vec![],
+            tmpfs_size: None,
+            hostname: None,
+            extra_hosts: vec![],
+            ulimits: vec![],
+            health: None,
+            health_log: vec![],
+            sysctls: HashMap::new(),
+            gpus: None,
+            log: None,
+            user: None,
+            partial: false,
+            depends_on: vec![],
+            networks: vec![],
+            read_only_volumes: vec![],
+            ports: vec![],
+            limits: ContainerLimits::default(),
+            exit: ContainerExit::default(),
+        })
+        .collect()
+}
+
+fn actions() -> CardActions<Message> {
+    CardActions {
+        on_view: Message::Id,
+        on_select: Message::Select,
+        on_start: Some(Message::Id),
+        on_stop: Some(Message::Id),
+        on_force_stop: Some(Message::Id),
+        on_update: Some(Message::Id),
+        on_back_up: Some(Message::Id),
+    }
+}
+
+#[test]
+fn list_is_paged() {
+    assert_eq!(page(10, PAGE_SIZE), (10, 0));
+    assert_eq!(page(CONTAINERS, PAGE_SIZE), (PAGE_SIZE, PAGE_SIZE));
+    assert_eq!(page(CONTAINERS, CONTAINERS - 10), (CONTAINERS - 10, 10));
+}
+
+#[test]
+fn thumbnails_are_looked_up_once_per_image() {
+    let containers = fake_containers();
+    let lookups = std::cell::Cell::new(0);
+    let cards = container_cards(
+        &containers,
+        usize::MAX,
+        |_| {
+            lookups.set(lookups.get() + 1);
+            Some(Handle::from_pixels(1, 1, vec![0; 4]))
+        },
+        |_| CardLook::default(),
+        actions(),
+    );
+
+    assert_eq!(cards.len(), CONTAINERS);
+    assert_eq!(lookups.get(), 5);
+}
+
+#[test]
+fn building_cards_stays_within_budget() {
+    let containers = fake_containers();
+    let thumbnail = Handle::from_pixels(1, 1, vec![0; 4]);
+
+    let start = Instant::now();
+    let cards = container_cards(
+        &containers,
+        usize::MAX,
+        |_| Some(thumbnail.clone()),
+        |container| CardLook {
+            tile: false,
+            selectable: true,
+            idle_days: container.name.ends_with('7').then_some(3),
+            ..Default::default()
+        },
+        actions(),
+    );
+    let elapsed = start.elapsed();
+
+    assert_eq!(cards.len(), CONTAINERS);
+    assert!(
+        elapsed < BUDGET,
+        "Building {CONTAINERS} cards took {elapsed:?}"
+    );
+}
+
+#[test]
+fn rendering_every_card_stays_within_budget() {
+    let containers = fake_containers();
+    let thumbnail = Handle::from_pixels(1, 1, vec![0; 4]);
+
+    let start = Instant::now();
+    for tile in [false, true] {
+        for container in containers.iter() {
+            let card = container_card::<Message>(container, Some(thumbnail.clone()))
+                .tile(tile)
+                .on_view_click(Message::Id);
+            drop(card.view(&()));
+        }
+    }
+    let elapsed = start.elapsed();
+
+    assert!(
+        elapsed < BUDGET * 2,
+        "Rendering {CONTAINERS} cards in both layouts took {elapsed:?}"
+    );
+}
+
+/// Icons arriving one by one over a slow connection, `ICONS` of them spread over `ARRIVAL`.
+const ICONS: usize = 50;
+const ARRIVAL: Duration = Duration::from_secs(5);
+const ICON_CONTAINERS: usize = 100;
+
+/// Rebuilds the cards of `containers` and returns how long it took, the list is laid out again
+/// every time this happens.
+fn relayout(containers: &[DbContainer], thumbnail: impl Fn(&str) -> Option<Handle>) -> Duration {
+    let start = Instant::now();
+    let cards = container_cards(
+        containers,
+        usize::MAX,
+        thumbnail,
+        |_| CardLook::default(),
+        actions(),
+    );
+    let elapsed = start.elapsed();
+    assert_eq!(cards.len(), containers.len());
+    elapsed
+}
+
+#[test]
+fn thumbnails_arriving_slowly_are_batched() {
+    let containers = fake_containers()
+        .into_iter()
+        .take(ICON_CONTAINERS)
+        .enumerate()
+        .map(|(i, container)| DbContainer {
+            image: format!("image-{}", i % ICONS),
+            ..container
+        })
+        .collect::<Vec<_>>();
+    let icon = |i: usize| format!("icon {i}").into_bytes();
+    let step = ARRIVAL / ICONS as u32;
+
+    // Every icon replacing its handle and relaying the list out as it arrives
+    let mut unbatched = HashMap::new();
+    let mut unbatched_relayouts = 0;
+    let mut unbatched_time = Duration::ZERO;
+    for i in 0..ICONS {
+        unbatched.insert(format!("image-{i}"), Handle::from_memory(icon(i)));
+        unbatched_relayouts += 1;
+        unbatched_time += relayout(&containers, |image| unbatched.get(image).cloned());
+    }
+
+    // The icons queued and flushed on the flush tick
+    let mut batched = Thumbnails::default();
+    let mut batched_relayouts = 0;
+    let mut batched_time = Duration::ZERO;
+    let mut next_flush = FLUSH_INTERVAL;
+    for i in 0..ICONS {
+        let arrived = step * i as u32;
+        while next_flush <= arrived {
+            if batched.flush() > 0 {
+                batched_relayouts += 1;
+                batched_time += relayout(&containers, |image| batched.get(image).cloned());
+            }
+            next_flush += FLUSH_INTERVAL;
+        }
+        batched.queue(format!("image-{i}"), icon(i));
+    }
+    if batched.flush() > 0 {
+        batched_relayouts += 1;
+        batched_time += relayout(&containers, |image| batched.get(image).cloned());
+    }
+    eprintln!(
+        "{ICONS} icons over {ARRIVAL:?}: {unbatched_relayouts} relayouts in {unbatched_time:?} unbatched, {batched_relayouts} in {batched_time:?} batched"
+    );
+
+    let max_flushes = (ARRIVAL.as_millis() / FLUSH_INTERVAL.as_millis()) as usize + 1;
+    assert_eq!(unbatched_relayouts, ICONS);
+    assert!(
+        batched_relayouts <= max_flushes,
+        "{batched_relayouts} relayouts for {ICONS} icons"
+    );
+    for i in 0..ICONS {
+        assert!(batched.get(&format!("image-{i}")).is_some());
+    }
+
+    // Icons fetched again with the same bytes keep their handles
+    for i in 0..ICONS {
+        batched.queue(format!("image-{i}"), icon(i));
+    }
+    assert_eq!(batched.flush(), 0);
+}
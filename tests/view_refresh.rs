@@ -0,0 +1,76 @@
+//! Checks which view stays open when the container list is refreshed: everything but the detail
+//! view of a container that is gone.
+#![allow(dead_code)]
+
+#[path = "../src/daemon.rs"]
+mod daemon;
+#[path = "../src/docker.rs"]
+mod docker;
+#[path = "../src/image_ref.rs"]
+mod image_ref;
+#[path = "../src/platform.rs"]
+mod platform;
+#[path = "../src/ports.rs"]
+mod ports;
+#[path = "../src/app/view_state.rs"]
+mod view_state;
+
+use bollard::service::ContainerSummary;
+use docker::{summary_container, DbContainer};
+use view_state::MainViewState;
+
+fn containers(ids: &[&str]) -> Vec<DbContainer> {
+    ids.iter()
+        .map(|id| {
+            summary_container(ContainerSummary {
+                id: Some(id.to_string()),
+                ..Default::default()
+            })
+            .unwrap()
+        })
+        .collect()
+}
+
+#[test]
+fn the_viewed_container_stays_open_while_it_exists() {
+    let view = MainViewState::ViewContainer("b".into()).after_refresh(&containers(&["a", "b"]));
+
+    assert!(matches!(view, MainViewState::ViewContainer(id) if id == "b"));
+}
+
+#[test]
+fn the_view_of_a_removed_container_is_closed() {
+    let view = MainViewState::ViewContainer("gone".into()).after_refresh(&containers(&["a"]));
+    assert!(matches!(view, MainViewState::None));
+
+    let view = MainViewState::ViewContainer("a".into()).after_refresh(&[]);
+    assert!(matches!(view, MainViewState::None));
+}
+
+#[test]
+fn other_views_are_kept() {
+    let refreshed = containers(&["a"]);
+    let kept = |view: MainViewState| format!("{:?}", view.after_refresh(&refreshed));
+
+    for view in [
+        MainViewState::Welcome,
+        MainViewState::CreateContainer,
+        MainViewState::QuickStart,
+        MainViewState::Credentials,
+        MainViewState::StartFailed,
+        MainViewState::Settings,
+        MainViewState::About,
+        MainViewState::Cleanup,
+        MainViewState::Prefetch,
+        MainViewState::Volumes(true),
+        MainViewState::None,
+    ] {
+        let before = format!("{view:?}");
+        assert_eq!(kept(view), before);
+    }
+    // Even when the list came back empty
+    assert!(matches!(
+        MainViewState::CreateContainer.after_refresh(&[]),
+        MainViewState::CreateContainer
+    ));
+}
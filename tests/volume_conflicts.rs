@@ -0,0 +1,111 @@
+//! Checks that volumes a new container would share with an existing container are found before
+//! it is created.
+#![allow(dead_code)]
+
+#[path = "../src/daemon.rs"]
+mod daemon;
+#[path = "../src/docker.rs"]
+mod docker;
+#[path = "../src/image_ref.rs"]
+mod image_ref;
+#[path = "../src/platform.rs"]
+mod platform;
+#[path = "../src/ports.rs"]
+mod ports;
+
+use std::collections::HashMap;
+
+use bollard::service::ContainerSummary;
+use docker::{shared_volumes, summary_container, DbContainer, VolumeConflict};
+
+fn container(name: &str, mounts: &[(&str, &str)]) -> DbContainer {
+    let mut container = summary_container(ContainerSummary {
+        id: Some(format!("{name}-id")),
+        names: Some(vec![format!("/{name}")]),
+        ..Default::default()
+    })
+    .expect("The summary has an id");
+    container.volumes = mounts
+        .iter()
+        .map(|(source, target)| (source.to_string(), target.to_string()))
+        .collect();
+    container
+}
+
+fn mounts(mounts: &[(&str, &str)]) -> HashMap<String, String> {
+    mounts
+        .iter()
+        .map(|(source, target)| (source.to_string(), target.to_string()))
+        .collect()
+}
+
+fn existing() -> Vec<DbContainer> {
+    vec![
+        container(
+            "db-mgr__orders",
+            &[("orders_data", "/var/lib/postgresql/data")],
+        ),
+        container(
+            "db-mgr__users",
+            &[
+                ("users_data", "/data"),
+                ("/home/me/seed", "/docker-entrypoint-initdb.d"),
+            ],
+        ),
+    ]
+}
+
+#[test]
+fn volumes_of_their_own_dont_conflict() {
+    assert!(shared_volumes(&mounts(&[("billing_data", "/data")]), &existing()).is_empty());
+    assert!(shared_volumes(&HashMap::new(), &existing()).is_empty());
+    assert!(shared_volumes(&mounts(&[("orders_data", "/data")]), &[]).is_empty());
+}
+
+#[test]
+fn a_mounted_volume_conflicts_whatever_its_target() {
+    let conflicts = shared_volumes(&mounts(&[("orders_data", "/somewhere/else")]), &existing());
+
+    assert_eq!(
+        conflicts,
+        vec![VolumeConflict {
+            volume: "orders_data".into(),
+            container: "/db-mgr__orders".into(),
+        }]
+    );
+    assert_eq!(
+        conflicts[0].to_string(),
+        "Volume orders_data is already mounted by db-mgr__orders, sharing a data volume corrupts \
+         most databases"
+    );
+}
+
+#[test]
+fn every_conflict_is_listed_by_volume() {
+    let mut others = existing();
+    others.push(container(
+        "db-mgr__orders-copy",
+        &[("orders_data", "/data")],
+    ));
+
+    let conflicts = shared_volumes(
+        &mounts(&[
+            ("users_data", "/data"),
+            ("orders_data", "/var/lib/postgresql/data"),
+            ("fresh", "/fresh"),
+        ]),
+        &others,
+    );
+
+    assert_eq!(
+        conflicts
+            .iter()
+            .map(|conflict| (conflict.volume.as_str(), conflict.container.as_str()))
+            .collect::<Vec<_>>(),
+        vec![
+            ("orders_data", "/db-mgr__orders"),
+            ("orders_data", "/db-mgr__orders-copy"),
+            ("users_data", "/db-mgr__users"),
+        ]
+    );
+}
@@ -0,0 +1,233 @@
+//! Walks the welcome flow shown on the first start through its steps, and checks what finishing
+//! and skipping it leave behind.
+#![allow(dead_code)]
+
+#[path = "../src/app/avatar.rs"]
+mod avatar;
+#[path = "../src/backup.rs"]
+mod backup;
+mod common;
+#[path = "../src/daemon.rs"]
+mod daemon;
+#[path = "../src/data.rs"]
+mod data;
+#[path = "../src/definition.rs"]
+mod definition;
+#[path = "../src/diff.rs"]
+mod diff;
+#[path = "../src/docker.rs"]
+mod docker;
+#[path = "../src/errors.rs"]
+mod errors;
+#[path = "../src/idle.rs"]
+mod idle;
+#[path = "../src/image_ref.rs"]
+mod image_ref;
+#[path = "../src/names.rs"]
+mod names;
+#[path = "../src/platform.rs"]
+mod platform;
+#[path = "../src/ports.rs"]
+mod ports;
+#[path = "../src/registry.rs"]
+mod registry;
+#[path = "../src/template.rs"]
+mod template;
+#[path = "../src/tls.rs"]
+mod tls;
+#[path = "../src/app/welcome.rs"]
+mod welcome;
+
+use std::{fs, path::PathBuf};
+
+use common::databases;
+use data::{
+    read_preferences, remove_databases, write_preferences, AppTheme, ConfigFile, Paths, Preferences,
+};
+use iced::widget::Component;
+use welcome::{welcome, Event, WelcomeFlow, WelcomeStep};
+
+#[derive(Debug, PartialEq)]
+enum Message {
+    Changed(WelcomeFlow),
+    Finished(WelcomeFlow),
+    Skipped,
+    Retry,
+}
+
+fn names() -> Vec<String> {
+    databases()
+        .into_iter()
+        .map(|database| database.name)
+        .collect()
+}
+
+/// Sends `event` to the flow like the UI does, returning the message and the flow it left.
+fn send(flow: WelcomeFlow, event: Event) -> Option<Message> {
+    let mut welcome = welcome(flow, None)
+        .on_change(Message::Changed)
+        .on_finish(Message::Finished)
+        .on_skip(|| Message::Skipped)
+        .on_retry_click(|| Message::Retry);
+    welcome.update(&mut (), event)
+}
+
+fn changed(flow: WelcomeFlow, event: Event) -> WelcomeFlow {
+    match send(flow, event) {
+        Some(Message::Changed(flow)) => flow,
+        other => panic!("Expected the flow to change, got {other:?}"),
+    }
+}
+
+fn temp_paths(test: &str) -> Paths {
+    let dir: PathBuf =
+        std::env::temp_dir().join(format!("db-mgr-welcome-{}-{test}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    Paths {
+        config_dir: dir.clone(),
+        data_dir: dir,
+    }
+}
+
+#[test]
+fn the_steps_go_in_order() {
+    assert_eq!(WelcomeStep::Docker.previous(), None);
+    assert_eq!(WelcomeStep::Docker.next(), Some(WelcomeStep::Model));
+    assert_eq!(WelcomeStep::Model.next(), Some(WelcomeStep::Theme));
+    assert_eq!(WelcomeStep::Theme.next(), Some(WelcomeStep::Databases));
+    assert_eq!(WelcomeStep::Databases.next(), None);
+    assert_eq!(WelcomeStep::Databases.previous(), Some(WelcomeStep::Theme));
+}
+
+#[test]
+fn the_flow_starts_at_docker_with_every_database_shown() {
+    let flow = WelcomeFlow::new(&databases(), AppTheme::Light);
+
+    assert_eq!(flow.step, WelcomeStep::Docker);
+    assert_eq!(flow.theme, AppTheme::Light);
+    assert_eq!(flow.databases, names());
+    assert!(flow.hidden().is_empty());
+    // Listed with the avatar of their image
+    assert_eq!(flow.images["Postgres"], "postgres");
+    assert_eq!(flow.images.len(), flow.databases.len());
+}
+
+#[test]
+fn back_stays_on_the_first_step() {
+    let mut flow = WelcomeFlow::new(&databases(), AppTheme::Dark);
+    flow.back();
+    assert_eq!(flow.step, WelcomeStep::Docker);
+
+    assert!(flow.advance());
+    flow.back();
+    assert_eq!(flow.step, WelcomeStep::Docker);
+}
+
+#[test]
+fn next_on_the_last_step_finishes_with_the_choices() {
+    let mut flow = WelcomeFlow::new(&databases(), AppTheme::Dark);
+    flow = changed(flow, Event::Next);
+    flow = changed(flow, Event::Next);
+    assert_eq!(flow.step, WelcomeStep::Theme);
+    flow = changed(flow, Event::Theme(AppTheme::Light));
+    flow = changed(flow, Event::Next);
+    assert_eq!(flow.step, WelcomeStep::Databases);
+    flow = changed(flow, Event::ShowDatabase("Postgres".into(), false));
+
+    match send(flow, Event::Next) {
+        Some(Message::Finished(flow)) => {
+            assert_eq!(flow.step, WelcomeStep::Databases);
+            assert_eq!(flow.theme, AppTheme::Light);
+            assert_eq!(flow.hidden(), vec!["Postgres".to_string()]);
+        }
+        other => panic!("Expected the flow to finish, got {other:?}"),
+    }
+}
+
+#[test]
+fn going_back_keeps_the_choices() {
+    let mut flow = WelcomeFlow::new(&databases(), AppTheme::Dark);
+    flow.step = WelcomeStep::Databases;
+    flow = changed(flow, Event::ShowDatabase("Postgres".into(), false));
+    flow = changed(flow, Event::Back);
+
+    assert_eq!(flow.step, WelcomeStep::Theme);
+    assert_eq!(flow.hidden(), vec!["Postgres".to_string()]);
+}
+
+#[test]
+fn databases_are_hidden_in_config_order() {
+    let mut flow = WelcomeFlow::new(&databases(), AppTheme::Dark);
+    for name in names().into_iter().rev() {
+        flow.show_database(name, false);
+    }
+    assert_eq!(flow.hidden(), names());
+
+    flow.show_database("Postgres".into(), true);
+    assert!(!flow.hidden().contains(&"Postgres".to_string()));
+}
+
+#[test]
+fn skipping_leaves_from_any_step_without_changes() {
+    for step in [
+        WelcomeStep::Docker,
+        WelcomeStep::Model,
+        WelcomeStep::Theme,
+        WelcomeStep::Databases,
+    ] {
+        let mut flow = WelcomeFlow::new(&databases(), AppTheme::Dark);
+        flow.step = step;
+        assert_eq!(send(flow, Event::Skip), Some(Message::Skipped));
+    }
+}
+
+#[test]
+fn checking_docker_again_is_left_to_the_app() {
+    let flow = WelcomeFlow::new(&databases(), AppTheme::Dark);
+    assert_eq!(send(flow, Event::RetryDocker), Some(Message::Retry));
+}
+
+#[test]
+fn skipping_saves_the_defaults_so_the_flow_only_shows_once() {
+    let paths = temp_paths("skip");
+    assert!(!paths.preferences_file().exists());
+
+    // Skipping saves the preferences as they were read, which are the defaults on the first start
+    let preferences = read_preferences(Some(&paths));
+    assert_eq!(preferences, Preferences::default());
+    write_preferences(Some(&paths), &preferences);
+
+    assert!(paths.preferences_file().exists());
+    assert_eq!(read_preferences(Some(&paths)), Preferences::default());
+    let _ = fs::remove_dir_all(&paths.config_dir);
+}
+
+#[test]
+fn hidden_databases_are_removed_from_the_config_file() {
+    let paths = temp_paths("remove");
+    fs::create_dir_all(&paths.config_dir).unwrap();
+    fs::write(paths.config_file(), include_str!("../config.yaml")).unwrap();
+
+    remove_databases(&paths, &["Postgres".to_string()]).expect("The config file is rewritten");
+
+    let config =
+        serde_yaml::from_str::<ConfigFile>(&fs::read_to_string(paths.config_file()).unwrap())
+            .expect("The rewritten config parses");
+    let kept = config
+        .databases
+        .into_iter()
+        .map(|database| database.name)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        kept,
+        names()
+            .into_iter()
+            .filter(|name| name != "Postgres")
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(
+        fs::read_to_string(paths.config_dir.join("config.yaml.bak")).unwrap(),
+        include_str!("../config.yaml")
+    );
+    let _ = fs::remove_dir_all(&paths.config_dir);
+}